@@ -0,0 +1,453 @@
+// Types removed - legacy plan_types no longer needed
+
+use crate::types::{AcChangePolicy, Celsius};
+
+/// AC operation modes for API calls
+pub const AC_MODE_OFF: i32 = 0;
+pub const AC_MODE_COOL: i32 = 1;
+pub const AC_MODE_DRY: i32 = 2;
+pub const AC_MODE_FAN: i32 = 3;
+pub const AC_MODE_HEAT: i32 = 4;
+
+/// AC mode constant for a request mode name (Heat, Cool, Dry, Fan)
+/// Returns None for Off and unknown names
+pub fn ac_mode_from_name(name: &str) -> Option<i32> {
+    match name {
+        "Heat" => Some(AC_MODE_HEAT),
+        "Cool" => Some(AC_MODE_COOL),
+        "Dry" => Some(AC_MODE_DRY),
+        "Fan" => Some(AC_MODE_FAN),
+        _ => None,
+    }
+}
+
+/// Request mode name for an AC mode constant, the inverse of `ac_mode_from_name`
+pub fn ac_mode_name(mode: i32) -> Option<&'static str> {
+    match mode {
+        AC_MODE_HEAT => Some("Heat"),
+        AC_MODE_COOL => Some("Cool"),
+        AC_MODE_DRY => Some("Dry"),
+        AC_MODE_FAN => Some("Fan"),
+        _ => None,
+    }
+}
+
+/// Vane position constants for the vertical and horizontal vanes
+/// Fixed positions 1-5 run from top to bottom (vertical) or far left to far right (horizontal)
+pub const VANE_AUTO: i32 = 0;
+pub const VANE_SWING: i32 = 6;
+
+/// Vane position names, indexed by vane position constant
+const VANE_NAMES: [&str; 7] = ["Auto", "Position 1", "Position 2", "Position 3", "Position 4", "Position 5", "Swing"];
+
+/// Names of all vane positions, as offered by the Swing Mode node
+pub fn vane_names() -> Vec<String> {
+    VANE_NAMES.iter().map(|name| name.to_string()).collect()
+}
+
+/// Vane position constant for a vane position name (e.g. "Position 2"), None for unknown names
+pub fn vane_from_name(name: &str) -> Option<i32> {
+    VANE_NAMES.iter().position(|n| *n == name).map(|position| position as i32)
+}
+
+/// Vane position name for a vane position constant, the inverse of `vane_from_name`
+pub fn vane_name(vane: i32) -> Option<&'static str> {
+    usize::try_from(vane).ok().and_then(|index| VANE_NAMES.get(index)).copied()
+}
+
+/// Round a temperature to the nearest multiple of `step` (e.g. 0.5°C for most ACs).
+/// A step of zero or less returns the temperature unchanged.
+pub fn round_temperature(temperature: Celsius, step: f64) -> Celsius {
+    if step <= 0.0 {
+        return temperature;
+    }
+    Celsius((temperature.0 / step).round() * step)
+}
+
+/// Represents the actual state of an AC device
+/// This is what we track to determine if we need to send new commands
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcState {
+    /// Whether the AC is currently on or off
+    pub is_on: bool,
+    /// AC mode: 0 = Off, 1 = Cool, 2 = Dry, 3 = Fan, 4 = Heat
+    pub mode: Option<i32>,
+    /// Fan speed setting (0-5, where 0 is auto)
+    pub fan_speed: Option<i32>,
+    /// Target temperature
+    pub temperature: Option<Celsius>,
+    /// Swing setting (0 = off, 1 = on)
+    pub swing: Option<i32>,
+    /// Vertical vane position (`VANE_*`), None leaves it to the controller
+    pub vertical_vane: Option<i32>,
+    /// Horizontal vane position (`VANE_*`), None leaves it to the controller
+    pub horizontal_vane: Option<i32>,
+    /// Whether powerful mode is active
+    pub powerful_mode: bool,
+}
+
+impl AcState {
+    /// Create a new state representing an off AC
+    /// Sets mode to Some(0) to indicate OFF mode was explicitly set,
+    /// allowing is_defined checks to properly detect that a command was sent
+    pub fn new_off() -> Self {
+        Self {
+            is_on: false,
+            mode: Some(0), // 0 = Off mode (changed from None to track command was sent)
+            fan_speed: None,
+            temperature: None,
+            swing: None,
+            vertical_vane: None,
+            horizontal_vane: None,
+            powerful_mode: false,
+        }
+    }
+
+    /// Create a new state representing an on AC with specific settings
+    pub fn new_on(mode: i32, fan_speed: i32, temperature: Celsius, swing: i32, powerful_mode: bool) -> Self {
+        Self {
+            is_on: true,
+            mode: Some(mode),
+            fan_speed: Some(fan_speed),
+            temperature: Some(temperature),
+            swing: Some(swing),
+            vertical_vane: None,
+            horizontal_vane: None,
+            powerful_mode,
+        }
+    }
+
+    /// Return a copy of this state with the given vane positions
+    pub fn with_vanes(mut self, vertical_vane: Option<i32>, horizontal_vane: Option<i32>) -> Self {
+        self.vertical_vane = vertical_vane;
+        self.horizontal_vane = horizontal_vane;
+        self
+    }
+
+    /// Return a copy of this state with the target temperature rounded to `step`
+    pub fn with_rounded_temperature(mut self, step: f64) -> Self {
+        self.temperature = self.temperature.map(|t| round_temperature(t, step));
+        self
+    }
+
+    /// Check if this state represents a change from another state
+    /// Returns true if the states are different and a command should be sent.
+    /// 
+    /// The device policy decides which differences may trigger a resend.
+    /// By default temperature changes within ±0.5°C are considered equivalent
+    /// to avoid sending redundant commands for minor temperature fluctuations.
+    pub fn requires_change(&self, other: &AcState, policy: &AcChangePolicy) -> bool {
+        // If on/off state differs, it's definitely a change
+        if self.is_on != other.is_on {
+            return true;
+        }
+        
+        // If both are off, no change needed (off state has no other properties that matter)
+        if !self.is_on && !other.is_on {
+            return false;
+        }
+        
+        // Both are on, check other properties
+        if self.mode != other.mode {
+            return true;
+        }
+        
+        if !policy.ignore_fan_speed && self.fan_speed != other.fan_speed {
+            return true;
+        }
+        
+        if !policy.ignore_swing
+            && (self.swing != other.swing
+                || self.vertical_vane != other.vertical_vane
+                || self.horizontal_vane != other.horizontal_vane)
+        {
+            return true;
+        }
+        
+        if !policy.ignore_powerful && self.powerful_mode != other.powerful_mode {
+            return true;
+        }
+        
+        // Fan-only mode doesn't use the target temperature
+        if self.mode == Some(AC_MODE_FAN) {
+            return false;
+        }
+
+        // Check temperature with tolerance
+        match (self.temperature, other.temperature) {
+            (Some(t1), Some(t2)) => {
+                // Temperature change within tolerance is not considered a change
+                (t1.0 - t2.0).abs() > policy.temperature_tolerance
+            }
+            // If one has temperature and other doesn't, it's a change
+            (Some(_), None) | (None, Some(_)) => true,
+            // If neither has temperature (shouldn't happen for on state), no change
+            (None, None) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ac_mode_constants() {
+        // Verify AC modes are defined correctly (API: 1=Cool, 2=Dry, 3=Fan, 4=Heat)
+        assert_eq!(AC_MODE_COOL, 1);
+        assert_eq!(AC_MODE_DRY, 2);
+        assert_eq!(AC_MODE_FAN, 3);
+        assert_eq!(AC_MODE_HEAT, 4);
+    }
+
+    #[test]
+    fn test_ac_mode_names_round_trip() {
+        for name in ["Heat", "Cool", "Dry", "Fan"] {
+            let mode = ac_mode_from_name(name).unwrap();
+            assert_eq!(ac_mode_name(mode), Some(name));
+        }
+        assert_eq!(ac_mode_from_name("Off"), None);
+        assert_eq!(ac_mode_name(AC_MODE_OFF), None);
+    }
+
+    #[test]
+    fn test_fan_mode_ignores_temperature() {
+        let state1 = AcState::new_on(AC_MODE_FAN, 2, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(AC_MODE_FAN, 2, Celsius(26.0), 1, false);
+        let dry = AcState::new_on(AC_MODE_DRY, 2, Celsius(26.0), 1, false);
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()), "Setpoint is irrelevant in fan mode");
+        assert!(state1.requires_change(&dry, &AcChangePolicy::default()), "Fan to dry is a mode change");
+    }
+
+    #[test]
+    fn test_ac_state_equality() {
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state3 = AcState::new_on(4, 0, Celsius(22.0), 0, false);
+
+        assert_eq!(state1, state2);
+        assert_ne!(state1, state3);
+    }
+
+    #[test]
+    fn test_ac_state_off_equality() {
+        let state1 = AcState::new_off();
+        let state2 = AcState::new_off();
+        let state3 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+
+        assert_eq!(state1, state2);
+        assert_ne!(state1, state3);
+    }
+
+    #[test]
+    fn test_requires_change() {
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state3 = AcState::new_on(4, 0, Celsius(22.0), 0, false);
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()));
+        assert!(state1.requires_change(&state3, &AcChangePolicy::default()));
+    }
+
+    #[test]
+    fn test_state_change_detection_temperature() {
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(23.0), 1, false);
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()));
+    }
+
+    #[test]
+    fn test_state_change_detection_mode() {
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(4, 0, Celsius(22.0), 1, false);
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()));
+    }
+
+    #[test]
+    fn test_state_change_detection_powerful() {
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.0), 1, true);
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()));
+    }
+
+    #[test]
+    fn test_state_change_detection_on_to_off() {
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_off();
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()));
+    }
+
+    #[test]
+    fn test_temperature_tolerance_within_threshold() {
+        // Temperature difference of 0.3°C should NOT require a change (within ±0.5°C tolerance)
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.3), 1, false);
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()), "0.3°C difference should not require a change");
+    }
+
+    #[test]
+    fn test_temperature_tolerance_at_threshold() {
+        // Temperature difference of exactly 0.5°C should NOT require a change (at tolerance boundary)
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.5), 1, false);
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()), "0.5°C difference (at tolerance) should not require a change");
+    }
+
+    #[test]
+    fn test_temperature_tolerance_above_threshold() {
+        // Temperature difference of 0.51°C should require a change (above tolerance)
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.51), 1, false);
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()), "0.51°C difference should require a change");
+    }
+
+    #[test]
+    fn test_temperature_tolerance_negative_difference() {
+        // Negative temperature difference should also be handled
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(21.6), 1, false); // 0.4°C lower
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()), "-0.4°C difference should not require a change");
+    }
+
+    #[test]
+    fn test_temperature_tolerance_large_difference() {
+        // Large temperature difference should always require a change
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(25.0), 1, false); // 3°C higher
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()), "3°C difference should require a change");
+    }
+
+    #[test]
+    fn test_same_state_no_change() {
+        // Exactly the same state should not require a change
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()), "Same state should not require a change");
+    }
+
+    #[test]
+    fn test_off_to_off_no_change() {
+        // Both off should not require a change
+        let state1 = AcState::new_off();
+        let state2 = AcState::new_off();
+
+        assert!(!state1.requires_change(&state2, &AcChangePolicy::default()), "Off to off should not require a change");
+    }
+
+    #[test]
+    fn test_off_to_on_requires_change() {
+        // Off to on should require a change
+        let state1 = AcState::new_off();
+        let state2 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()), "Off to on should require a change");
+    }
+
+    #[test]
+    fn test_round_temperature_half_step() {
+        assert_eq!(round_temperature(Celsius(21.999), 0.5), Celsius(22.0));
+        assert_eq!(round_temperature(Celsius(21.74), 0.5), Celsius(21.5));
+        assert_eq!(round_temperature(Celsius(21.76), 0.5), Celsius(22.0));
+        assert_eq!(round_temperature(Celsius(22.0), 0.5), Celsius(22.0));
+    }
+
+    #[test]
+    fn test_round_temperature_disabled() {
+        assert_eq!(round_temperature(Celsius(21.999), 0.0), Celsius(21.999));
+        assert_eq!(round_temperature(Celsius(21.999), -1.0), Celsius(21.999));
+    }
+
+    #[test]
+    fn test_with_rounded_temperature() {
+        let state = AcState::new_on(1, 0, Celsius(21.999), 1, false).with_rounded_temperature(0.5);
+        assert_eq!(state.temperature, Some(Celsius(22.0)));
+        assert_eq!(state, AcState::new_on(1, 0, Celsius(22.0), 1, false));
+
+        // Off state has no temperature and stays untouched
+        let off = AcState::new_off().with_rounded_temperature(0.5);
+        assert_eq!(off, AcState::new_off());
+    }
+
+    #[test]
+    fn test_policy_ignore_swing() {
+        let policy = AcChangePolicy { ignore_swing: true, ..Default::default() };
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 0, false);
+        let state2 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+
+        assert!(state1.requires_change(&state2, &AcChangePolicy::default()), "Default policy should resend on swing change");
+        assert!(!state1.requires_change(&state2, &policy), "Swing change should be ignored");
+    }
+
+    #[test]
+    fn test_vane_names_round_trip() {
+        for name in vane_names() {
+            let vane = vane_from_name(&name).unwrap();
+            assert_eq!(vane_name(vane), Some(name.as_str()));
+        }
+        assert_eq!(vane_from_name("Auto"), Some(VANE_AUTO));
+        assert_eq!(vane_from_name("Swing"), Some(VANE_SWING));
+        assert_eq!(vane_from_name("Position 6"), None);
+        assert_eq!(vane_name(-1), None);
+    }
+
+    #[test]
+    fn test_vane_change_detection() {
+        let state = AcState::new_on(1, 0, Celsius(22.0), 0, false);
+        let aimed = state.clone().with_vanes(Some(2), Some(VANE_SWING));
+
+        assert!(state.requires_change(&aimed, &AcChangePolicy::default()), "Vane changes resend like swing");
+        assert!(!aimed.requires_change(&aimed.clone(), &AcChangePolicy::default()));
+        let policy = AcChangePolicy { ignore_swing: true, ..Default::default() };
+        assert!(!state.requires_change(&aimed, &policy), "Vane changes are ignored with swing");
+    }
+
+    #[test]
+    fn test_policy_ignore_fan_speed_and_powerful() {
+        let policy = AcChangePolicy {
+            ignore_fan_speed: true,
+            ignore_powerful: true,
+            ..Default::default()
+        };
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 3, Celsius(22.0), 1, true);
+
+        assert!(!state1.requires_change(&state2, &policy));
+    }
+
+    #[test]
+    fn test_policy_custom_temperature_tolerance() {
+        let policy = AcChangePolicy { temperature_tolerance: 1.0, ..Default::default() };
+        let state1 = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let within = AcState::new_on(1, 0, Celsius(23.0), 1, false);
+        let above = AcState::new_on(1, 0, Celsius(23.5), 1, false);
+
+        assert!(!state1.requires_change(&within, &policy));
+        assert!(state1.requires_change(&above, &policy));
+    }
+
+    #[test]
+    fn test_policy_never_ignores_mode_or_power() {
+        let policy = AcChangePolicy {
+            temperature_tolerance: 10.0,
+            ignore_fan_speed: true,
+            ignore_swing: true,
+            ignore_powerful: true,
+        };
+        let cool = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let heat = AcState::new_on(4, 0, Celsius(22.0), 1, false);
+
+        assert!(cool.requires_change(&heat, &policy), "Mode change must always resend");
+        assert!(cool.requires_change(&AcState::new_off(), &policy), "Turning off must always resend");
+    }
+}
@@ -0,0 +1,10 @@
+//! Core of Power Control Center: the node system, nodeset execution engine,
+//! AC state planning and shared types.
+//!
+//! This crate has no web server, database or HTTP client dependencies, so external
+//! tools (e.g. a CLI profile tester or an editor preview) can use it directly.
+
+pub mod ac_state;
+pub mod nodes;
+pub mod preview;
+pub mod types;
@@ -0,0 +1,612 @@
+//! Static analysis of nodesets
+//!
+//! `validate_nodeset_for_execution` finds what stops a nodeset from running. The analysis adds
+//! warnings about parts of a nodeset that run but can't matter: flow nodes that can't be reached
+//! from Start, data pins connected to a pin of another type, If nodes with a constant condition so
+//! one branch is never taken, and Sequence outputs after an output that always reaches a terminal
+//! node. Every warning names the nodes involved so the editor can highlight them.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use super::execution::{
+    validate_nodeset_for_execution, DEFAULT_FLOAT_TOLERANCE, NODE_TYPE_DO_NOTHING, NODE_TYPE_EXECUTE_ACTION,
+    NODE_TYPE_LOGIC_AND, NODE_TYPE_LOGIC_EVALUATE_NUMBER, NODE_TYPE_LOGIC_IF, NODE_TYPE_LOGIC_NAND, NODE_TYPE_LOGIC_NOT,
+    NODE_TYPE_LOGIC_OR, NODE_TYPE_LOGIC_SEQUENCE, NODE_TYPE_MATH_CLAMP, NODE_TYPE_MATH_MAX, NODE_TYPE_MATH_MIN, NODE_TYPE_NOTIFY, NODE_TYPE_PRIMITIVE_BOOLEAN,
+    NODE_TYPE_PRIMITIVE_FLOAT, NODE_TYPE_PRIMITIVE_INTEGER, NODE_TYPE_RESET_ACTIVE_COMMAND, NODE_TYPE_START,
+    NODE_TYPE_TEXT_FORMAT_NUMBER, NODE_TYPE_TURN_OFF, NODE_TYPE_VARIABLE,
+};
+use super::node_system::{EnumOption, NodeDefinition, ValueType};
+use super::variables::RESOLVED_VALUE_KEY;
+
+/// Node types whose Any inputs only take numbers
+const NUMERIC_ANY_NODE_TYPES: [&str; 5] = [
+    NODE_TYPE_LOGIC_EVALUATE_NUMBER,
+    NODE_TYPE_MATH_MIN,
+    NODE_TYPE_MATH_MAX,
+    NODE_TYPE_MATH_CLAMP,
+    NODE_TYPE_TEXT_FORMAT_NUMBER,
+];
+
+/// Kind of problem an analysis warning reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum AnalysisCode {
+    /// A flow node that no execution path from Start leads to
+    UnreachableNode,
+    /// A data pin connected to a pin of an incompatible type, see `find_type_mismatches`
+    TypeMismatch,
+    /// An If node whose condition is constant, so one branch is never taken
+    ConstantCondition,
+    /// Sequence outputs that are never tried because an earlier output always reaches a terminal node
+    DeadSequenceOutput,
+}
+
+/// A problem found in a nodeset that doesn't stop it from running
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AnalysisWarning {
+    pub code: AnalysisCode,
+    /// Nodes to highlight, the node the warning is about first
+    pub node_ids: Vec<String>,
+    pub message: String,
+}
+
+/// Result of analyzing a nodeset
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NodesetAnalysis {
+    /// Problems that stop the nodeset from running, see `validate_nodeset_for_execution`
+    pub errors: Vec<String>,
+    pub warnings: Vec<AnalysisWarning>,
+}
+
+/// Connections between pins of incompatible types, by the pin types of the node definitions
+/// A nodeset with these can't be saved while it's active
+pub fn find_type_mismatches(nodes: &[serde_json::Value], edges: &[serde_json::Value]) -> Vec<AnalysisWarning> {
+    Graph::new(nodes, edges).type_mismatches()
+}
+
+/// Analyze a nodeset with its variables resolved
+pub fn analyze_nodeset(nodes: &[serde_json::Value], edges: &[serde_json::Value]) -> NodesetAnalysis {
+    let graph = Graph::new(nodes, edges);
+    let mut warnings = graph.unreachable_nodes();
+    warnings.extend(graph.type_mismatches());
+    warnings.extend(graph.constant_conditions());
+    warnings.extend(graph.dead_sequence_outputs());
+
+    NodesetAnalysis {
+        errors: validate_nodeset_for_execution(nodes, edges),
+        warnings,
+    }
+}
+
+struct GraphNode<'a> {
+    id: &'a str,
+    node_type: &'a str,
+    data: Option<&'a serde_json::Value>,
+}
+
+struct GraphEdge<'a> {
+    source: &'a str,
+    source_handle: &'a str,
+    target: &'a str,
+    target_handle: &'a str,
+}
+
+/// Nodes and edges of a nodeset with the definitions of their node types
+struct Graph<'a> {
+    /// In the order of the nodeset
+    nodes: Vec<GraphNode<'a>>,
+    by_id: HashMap<&'a str, usize>,
+    edges: Vec<GraphEdge<'a>>,
+    definitions: HashMap<String, NodeDefinition>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(nodes: &'a [serde_json::Value], edges: &'a [serde_json::Value]) -> Self {
+        let str_field = |value: &'a serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        let nodes: Vec<GraphNode<'a>> = nodes
+            .iter()
+            .map(|node| GraphNode {
+                id: str_field(node, "id"),
+                node_type: node
+                    .get("data")
+                    .and_then(|d| d.get("definition"))
+                    .and_then(|def| def.get("node_type"))
+                    .and_then(|nt| nt.as_str())
+                    .unwrap_or(""),
+                data: node.get("data"),
+            })
+            .collect();
+        let by_id = nodes.iter().enumerate().map(|(i, node)| (node.id, i)).collect();
+        let edges = edges
+            .iter()
+            .map(|edge| GraphEdge {
+                source: str_field(edge, "source"),
+                source_handle: str_field(edge, "sourceHandle"),
+                target: str_field(edge, "target"),
+                target_handle: str_field(edge, "targetHandle"),
+            })
+            .collect();
+        let definitions = super::get_all_node_definitions()
+            .into_iter()
+            .map(|def| (def.node_type.clone(), def))
+            .collect();
+        Self { nodes, by_id, edges, definitions }
+    }
+
+    fn node(&self, id: &str) -> Option<&GraphNode<'a>> {
+        self.by_id.get(id).map(|&i| &self.nodes[i])
+    }
+
+    fn definition(&self, node_id: &str) -> Option<&NodeDefinition> {
+        self.node(node_id).and_then(|node| self.definitions.get(node.node_type))
+    }
+
+    fn output_type(&self, node_id: &str, handle: &str) -> Option<&ValueType> {
+        if handle.starts_with("then_") {
+            return Some(&ValueType::Execution);
+        }
+        let output = self.definition(node_id)?.outputs.iter().find(|o| o.id == handle)?;
+        Some(&output.value_type)
+    }
+
+    fn input_type(&self, node_id: &str, handle: &str) -> Option<&ValueType> {
+        let inputs = &self.definition(node_id)?.inputs;
+        let input = inputs.iter().find(|i| i.id == handle).or_else(|| {
+            // Pins added with the + button have the type of the first one
+            handle
+                .strip_prefix("input_")
+                .filter(|n| n.parse::<usize>().is_ok())
+                .and_then(|_| inputs.iter().find(|i| i.id == "input_1"))
+        })?;
+        Some(&input.value_type)
+    }
+
+    fn is_execution_edge(&self, edge: &GraphEdge) -> bool {
+        self.output_type(edge.source, edge.source_handle) == Some(&ValueType::Execution)
+    }
+
+    /// Node an execution output leads to, the first edge wins like in the executor
+    fn exec_target(&self, node_id: &str, handle: &str) -> Option<&'a str> {
+        self.edges
+            .iter()
+            .find(|e| e.source == node_id && e.source_handle == handle)
+            .map(|e| e.target)
+    }
+
+    /// Node and output connected to a data input
+    fn input_source(&self, node_id: &str, handle: &str) -> Option<(&'a str, &'a str)> {
+        self.edges
+            .iter()
+            .find(|e| e.target == node_id && e.target_handle == handle)
+            .map(|e| (e.source, e.source_handle))
+    }
+
+    /// Connected then_N outputs of a Sequence node, in the order they are tried
+    fn sequence_outputs(&self, node_id: &str) -> Vec<&'a str> {
+        let mut outputs: Vec<&'a str> = self
+            .edges
+            .iter()
+            .filter(|e| e.source == node_id && e.source_handle.starts_with("then_"))
+            .map(|e| e.source_handle)
+            .collect();
+        outputs.sort_by_key(|handle| handle.strip_prefix("then_").and_then(|n| n.parse::<usize>().ok()).unwrap_or(0));
+        outputs.dedup();
+        outputs
+    }
+
+    /// Flow nodes no execution path from Start leads to
+    fn unreachable_nodes(&self) -> Vec<AnalysisWarning> {
+        let starts: Vec<&str> = self.nodes.iter().filter(|n| n.node_type == NODE_TYPE_START).map(|n| n.id).collect();
+        // Without exactly one Start node everything is unreachable, which validation already reports
+        let [start] = starts[..] else {
+            return Vec::new();
+        };
+
+        let mut reached: HashSet<&str> = HashSet::from([start]);
+        let mut queue = vec![start];
+        while let Some(node_id) = queue.pop() {
+            for edge in self.edges.iter().filter(|e| e.source == node_id && self.is_execution_edge(e)) {
+                if reached.insert(edge.target) {
+                    queue.push(edge.target);
+                }
+            }
+        }
+
+        self.nodes
+            .iter()
+            .filter(|node| !reached.contains(node.id))
+            .filter(|node| {
+                self.definitions
+                    .get(node.node_type)
+                    .is_some_and(|def| def.inputs.iter().any(|i| i.value_type == ValueType::Execution))
+            })
+            .map(|node| AnalysisWarning {
+                code: AnalysisCode::UnreachableNode,
+                node_ids: vec![node.id.to_string()],
+                message: format!("{} node '{}' can't be reached from Start", self.definitions[node.node_type].name, node.id),
+            })
+            .collect()
+    }
+
+    /// Data edges between pins of incompatible types
+    fn type_mismatches(&self) -> Vec<AnalysisWarning> {
+        self.edges
+            .iter()
+            .filter_map(|edge| {
+                // Pins that aren't in the definition, e.g. those of Call Profile nodes, aren't checked
+                let source_type = self.output_type(edge.source, edge.source_handle)?;
+                let target_type = self.input_type(edge.target, edge.target_handle)?;
+                let target_node_type = self.node(edge.target)?.node_type;
+                if types_compatible(source_type, target_type, target_node_type) {
+                    return None;
+                }
+                Some(AnalysisWarning {
+                    code: AnalysisCode::TypeMismatch,
+                    node_ids: vec![edge.target.to_string(), edge.source.to_string()],
+                    message: format!(
+                        "Input '{}' of '{}' ({}) is connected to output '{}' of '{}' ({})",
+                        edge.target_handle,
+                        edge.target,
+                        target_type.kind(),
+                        edge.source_handle,
+                        edge.source,
+                        source_type.kind()
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// If nodes whose condition doesn't depend on the inputs
+    fn constant_conditions(&self) -> Vec<AnalysisWarning> {
+        self.nodes
+            .iter()
+            .filter(|node| node.node_type == NODE_TYPE_LOGIC_IF)
+            .filter_map(|node| {
+                let condition = self.constant_bool(node.id, "condition", &mut HashSet::new())?;
+                let (taken, never) = if condition { ("True", "False") } else { ("False", "True") };
+                Some(AnalysisWarning {
+                    code: AnalysisCode::ConstantCondition,
+                    node_ids: vec![node.id.to_string()],
+                    message: format!(
+                        "The condition of If node '{}' is always {}, its {} branch is never taken",
+                        node.id,
+                        taken.to_lowercase(),
+                        never
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Sequence outputs after an output that always reaches a terminal node
+    fn dead_sequence_outputs(&self) -> Vec<AnalysisWarning> {
+        self.nodes
+            .iter()
+            .filter(|node| node.node_type == NODE_TYPE_LOGIC_SEQUENCE)
+            .filter_map(|node| {
+                let outputs = self.sequence_outputs(node.id);
+                let always = outputs.iter().position(|handle| {
+                    self.exec_target(node.id, handle)
+                        .is_some_and(|target| self.always_terminates(target, &mut HashSet::new()))
+                })?;
+                let dead = &outputs[always + 1..];
+                if dead.is_empty() {
+                    return None;
+                }
+                let mut node_ids = vec![node.id.to_string()];
+                node_ids.extend(dead.iter().filter_map(|handle| self.exec_target(node.id, handle)).map(str::to_string));
+                Some(AnalysisWarning {
+                    code: AnalysisCode::DeadSequenceOutput,
+                    node_ids,
+                    message: format!(
+                        "Output {} of Sequence node '{}' always reaches a terminal node, {} never tried",
+                        outputs[always],
+                        node.id,
+                        match dead {
+                            [output] => format!("output {} is", output),
+                            _ => format!("outputs {} are", dead.join(", ")),
+                        }
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every required data input of a node is connected
+    fn required_inputs_connected(&self, node_id: &str) -> bool {
+        self.definition(node_id).is_some_and(|def| {
+            def.inputs
+                .iter()
+                .filter(|input| input.required && input.value_type != ValueType::Execution)
+                .all(|input| self.input_source(node_id, &input.id).is_some())
+        })
+    }
+
+    /// Whether execution that reaches a node always ends at a terminal node
+    /// Only follows the structure of the flow, data that fails to evaluate can still stop it
+    fn always_terminates(&self, node_id: &'a str, visiting: &mut HashSet<&'a str>) -> bool {
+        let Some(node) = self.node(node_id) else {
+            return false;
+        };
+        // A loop in the flow never terminates
+        if !visiting.insert(node_id) {
+            return false;
+        }
+        let follow = |handle: &str, visiting: &mut HashSet<&'a str>| {
+            self.exec_target(node_id, handle)
+                .is_some_and(|target| self.always_terminates(target, visiting))
+        };
+        let terminates = match node.node_type {
+            NODE_TYPE_EXECUTE_ACTION | NODE_TYPE_DO_NOTHING | NODE_TYPE_TURN_OFF => self.required_inputs_connected(node_id),
+            NODE_TYPE_NOTIFY | NODE_TYPE_RESET_ACTIVE_COMMAND => {
+                self.required_inputs_connected(node_id) && follow("exec_out", visiting)
+            }
+            NODE_TYPE_LOGIC_IF => match self.constant_bool(node_id, "condition", &mut HashSet::new()) {
+                Some(true) => follow("exec_true", visiting),
+                Some(false) => follow("exec_false", visiting),
+                None => {
+                    self.input_source(node_id, "condition").is_some()
+                        && follow("exec_true", visiting)
+                        && follow("exec_false", visiting)
+                }
+            },
+            NODE_TYPE_LOGIC_SEQUENCE => self
+                .sequence_outputs(node_id)
+                .iter()
+                .any(|handle| follow(handle, visiting)),
+            _ => false,
+        };
+        visiting.remove(node_id);
+        terminates
+    }
+
+    /// Value of a boolean input that doesn't depend on the execution inputs, None if it does
+    fn constant_bool(&self, node_id: &str, handle: &str, visiting: &mut HashSet<String>) -> Option<bool> {
+        let (source, _) = self.input_source(node_id, handle)?;
+        let node = self.node(source)?;
+        if !visiting.insert(source.to_string()) {
+            return None;
+        }
+        let inputs = |visiting: &mut HashSet<String>| -> Vec<Option<bool>> {
+            self.edges
+                .iter()
+                .filter(|e| e.target == source && e.target_handle.starts_with("input_"))
+                .map(|e| self.constant_bool(source, e.target_handle, visiting))
+                .collect()
+        };
+        let value = match node.node_type {
+            NODE_TYPE_PRIMITIVE_BOOLEAN => Some(
+                node.data
+                    .and_then(|d| d.get("primitiveValue"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+            ),
+            NODE_TYPE_LOGIC_NOT => self.constant_bool(source, "input", visiting).map(|v| !v),
+            NODE_TYPE_LOGIC_AND | NODE_TYPE_LOGIC_NAND => {
+                let inputs = inputs(visiting);
+                // One constant false input decides it
+                let all = if inputs.contains(&Some(false)) {
+                    Some(false)
+                } else if !inputs.is_empty() && inputs.iter().all(|v| *v == Some(true)) {
+                    Some(true)
+                } else {
+                    None
+                };
+                all.map(|all| if node.node_type == NODE_TYPE_LOGIC_NAND { !all } else { all })
+            }
+            NODE_TYPE_LOGIC_OR => {
+                let inputs = inputs(visiting);
+                if inputs.contains(&Some(true)) {
+                    Some(true)
+                } else if !inputs.is_empty() && inputs.iter().all(|v| *v == Some(false)) {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+            NODE_TYPE_LOGIC_EVALUATE_NUMBER => {
+                let a = self.constant_number(source, "input_a")?;
+                let b = self.constant_number(source, "input_b")?;
+                let operator = node.data.and_then(|d| d.get("operatorValue")).and_then(|v| v.as_str()).unwrap_or(">");
+                let is_equal = (a - b).abs() < DEFAULT_FLOAT_TOLERANCE;
+                Some(match operator {
+                    ">=" => a > b || is_equal,
+                    "==" => is_equal,
+                    "<=" => a < b || is_equal,
+                    "<" => a < b && !is_equal,
+                    _ => a > b && !is_equal,
+                })
+            }
+            _ => None,
+        };
+        visiting.remove(source);
+        value
+    }
+
+    /// Value of a numeric input connected to a number or variable node, None for anything else
+    fn constant_number(&self, node_id: &str, handle: &str) -> Option<f64> {
+        let (source, _) = self.input_source(node_id, handle)?;
+        let node = self.node(source)?;
+        let data = node.data?;
+        match node.node_type {
+            NODE_TYPE_PRIMITIVE_FLOAT | NODE_TYPE_PRIMITIVE_INTEGER => Some(data.get("primitiveValue")?.as_f64().unwrap_or(0.0)),
+            NODE_TYPE_VARIABLE => data.get(RESOLVED_VALUE_KEY)?.as_f64(),
+            _ => None,
+        }
+    }
+}
+
+/// Whether an output of `source` type may feed an input of `target` type, the rules of the editor
+/// An Any output can carry any type, so it is only rejected where no type could fit
+fn types_compatible(source: &ValueType, target: &ValueType, target_node_type: &str) -> bool {
+    match (source, target) {
+        (ValueType::Execution, target) => *target == ValueType::Execution,
+        (_, ValueType::Execution) => false,
+        (ValueType::CauseReason(_), ValueType::CauseReason(_)) => true,
+        (source, ValueType::CauseReason(_)) => *source == ValueType::Any,
+        (source, ValueType::Any) if NUMERIC_ANY_NODE_TYPES.contains(&target_node_type) => {
+            matches!(source, ValueType::Float | ValueType::Integer | ValueType::Any)
+        }
+        (_, ValueType::Any) | (ValueType::Any, _) => true,
+        (ValueType::Object, _) | (_, ValueType::Object) => true,
+        (ValueType::Enum(a), ValueType::Enum(b)) => same_options(a.clone(), b.clone()),
+        (ValueType::EnumWithIds(a), ValueType::EnumWithIds(b)) => {
+            let ids = |options: &[EnumOption]| options.iter().map(|o| o.id.clone()).collect();
+            same_options(ids(a), ids(b))
+        }
+        // Integers are read as floats
+        (ValueType::Integer, ValueType::Float) => true,
+        (source, target) => source.kind() == target.kind(),
+    }
+}
+
+/// Whether two enums have the same options in any order
+/// Enums without options, like the devices that are filled in from the config, aren't checked
+fn same_options(mut a: Vec<String>, mut b: Vec<String>) -> bool {
+    a.sort();
+    b.sort();
+    a.is_empty() || b.is_empty() || a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn node(id: &str, node_type: &str, data: serde_json::Value) -> serde_json::Value {
+        let mut data = data;
+        data["definition"] = json!({"node_type": node_type});
+        json!({"id": id, "type": "custom", "position": {"x": 0, "y": 0}, "data": data})
+    }
+
+    fn edge(source: &str, source_handle: &str, target: &str, target_handle: &str) -> serde_json::Value {
+        json!({
+            "id": format!("e{}-{}-{}", source, target, target_handle),
+            "source": source,
+            "sourceHandle": source_handle,
+            "target": target,
+            "targetHandle": target_handle
+        })
+    }
+
+    fn codes(analysis: &NodesetAnalysis) -> Vec<(AnalysisCode, Vec<String>)> {
+        analysis.warnings.iter().map(|w| (w.code, w.node_ids.clone())).collect()
+    }
+
+    #[test]
+    fn test_clean_nodeset_has_no_warnings() {
+        let nodes = vec![
+            node("start", NODE_TYPE_START, json!({})),
+            node("cause", "cause_reason", json!({"enumValue": "0"})),
+            node("idle", NODE_TYPE_DO_NOTHING, json!({})),
+        ];
+        let edges = vec![
+            edge("start", "exec_out", "idle", "exec_in"),
+            edge("cause", "cause_reason", "idle", "cause_reason"),
+        ];
+        let analysis = analyze_nodeset(&nodes, &edges);
+        assert!(analysis.errors.is_empty(), "{:?}", analysis.errors);
+        assert!(analysis.warnings.is_empty(), "{:?}", analysis.warnings);
+    }
+
+    #[test]
+    fn test_constant_condition_and_unreachable_nodes() {
+        // A variable set to 0 makes "enabled > 0" always false
+        let nodes = vec![
+            node("start", NODE_TYPE_START, json!({})),
+            node("enabled", NODE_TYPE_VARIABLE, json!({"variableName": "enabled", RESOLVED_VALUE_KEY: 0.0})),
+            node("zero", NODE_TYPE_PRIMITIVE_INTEGER, json!({"primitiveValue": 0})),
+            node("is-enabled", NODE_TYPE_LOGIC_EVALUATE_NUMBER, json!({"operatorValue": ">"})),
+            node("if", NODE_TYPE_LOGIC_IF, json!({})),
+            node("cause", "cause_reason", json!({"enumValue": "0"})),
+            node("off", NODE_TYPE_TURN_OFF, json!({})),
+            node("idle", NODE_TYPE_DO_NOTHING, json!({})),
+            node("orphan", NODE_TYPE_DO_NOTHING, json!({})),
+        ];
+        let edges = vec![
+            edge("start", "exec_out", "if", "exec_in"),
+            edge("enabled", "value", "is-enabled", "input_a"),
+            edge("zero", "value", "is-enabled", "input_b"),
+            edge("is-enabled", "result", "if", "condition"),
+            edge("if", "exec_true", "off", "exec_in"),
+            edge("if", "exec_false", "idle", "exec_in"),
+            edge("cause", "cause_reason", "off", "cause_reason"),
+            edge("cause", "cause_reason", "idle", "cause_reason"),
+        ];
+        let analysis = analyze_nodeset(&nodes, &edges);
+        assert_eq!(
+            codes(&analysis),
+            vec![
+                (AnalysisCode::UnreachableNode, vec!["orphan".to_string()]),
+                (AnalysisCode::ConstantCondition, vec!["if".to_string()]),
+            ]
+        );
+        assert_eq!(
+            analysis.warnings[1].message,
+            "The condition of If node 'if' is always false, its True branch is never taken"
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch_and_dead_sequence_outputs() {
+        let nodes = vec![
+            node("start", NODE_TYPE_START, json!({})),
+            node("seq", NODE_TYPE_LOGIC_SEQUENCE, json!({})),
+            node("cause", "cause_reason", json!({"enumValue": "0"})),
+            node("idle", NODE_TYPE_DO_NOTHING, json!({})),
+            node("off", NODE_TYPE_TURN_OFF, json!({})),
+            node("not", NODE_TYPE_LOGIC_NOT, json!({})),
+        ];
+        let edges = vec![
+            edge("start", "exec_out", "seq", "exec_in"),
+            edge("seq", "then_1", "off", "exec_in"),
+            edge("seq", "then_0", "idle", "exec_in"),
+            edge("cause", "cause_reason", "idle", "cause_reason"),
+            edge("cause", "cause_reason", "off", "cause_reason"),
+            // A temperature can't be negated
+            edge("start", "outdoor_temperature", "not", "input"),
+        ];
+        let analysis = analyze_nodeset(&nodes, &edges);
+        assert_eq!(
+            codes(&analysis),
+            vec![
+                (AnalysisCode::TypeMismatch, vec!["not".to_string(), "start".to_string()]),
+                (AnalysisCode::DeadSequenceOutput, vec!["seq".to_string(), "off".to_string()]),
+            ]
+        );
+        assert_eq!(
+            analysis.warnings[1].message,
+            "Output then_0 of Sequence node 'seq' always reaches a terminal node, output then_1 is never tried"
+        );
+    }
+
+    #[test]
+    fn test_integers_feed_floats_and_added_pins_are_typed() {
+        let nodes = vec![
+            node("hours", NODE_TYPE_PRIMITIVE_INTEGER, json!({"primitiveValue": 3})),
+            node("limit", NODE_TYPE_PRIMITIVE_FLOAT, json!({"primitiveValue": 2.5})),
+            node("multiply", "math_multiply", json!({})),
+            node("flag", NODE_TYPE_PRIMITIVE_BOOLEAN, json!({"primitiveValue": true})),
+            node("and", NODE_TYPE_LOGIC_AND, json!({})),
+        ];
+        let edges = vec![
+            edge("hours", "value", "multiply", "input_a"),
+            edge("limit", "value", "multiply", "input_b"),
+            edge("flag", "value", "and", "input_1"),
+            edge("flag", "value", "and", "input_2"),
+            // A pin added with the + button is still a boolean
+            edge("limit", "value", "and", "input_3"),
+        ];
+        let mismatches = find_type_mismatches(&nodes, &edges);
+        assert_eq!(mismatches.len(), 1, "{:?}", mismatches);
+        assert_eq!(mismatches[0].node_ids, vec!["and".to_string(), "limit".to_string()]);
+    }
+}
@@ -0,0 +1,34 @@
+use super::node_system::{Node, NodeDefinition};
+
+/// Comment node - a resizable frame with a note, to label and group parts of a nodeset
+/// Has no pins and is never executed; the executor and validation skip it
+pub struct CommentNode;
+
+impl Node for CommentNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "annotation_comment",
+            "Comment",
+            "A resizable frame with a note. Place it behind related nodes to label and group them. It has no pins and doesn't affect execution.",
+            "Annotations",
+            vec![], // No inputs - never executed
+            vec![], // No outputs - never executed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_node_definition() {
+        let def = CommentNode::definition();
+
+        assert_eq!(def.node_type, "annotation_comment");
+        assert_eq!(def.name, "Comment");
+        assert_eq!(def.category, "Annotations");
+        assert!(def.inputs.is_empty());
+        assert!(def.outputs.is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use super::node_system::{Node, NodeDefinition, NodeInput, NodeOutput, ValueType};
+
+/// Call Profile node - runs another saved profile as a sub-graph
+/// Its pins come from the Profile Input and Profile Output nodes of the selected profile and are
+/// added by the frontend as `in_<name>` and `out_<name>` dynamic pins.
+/// Calls are expanded into the calling nodeset before execution, see `calls`.
+pub struct CallNodesetNode;
+
+impl Node for CallNodesetNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "call_nodeset",
+            "Call Profile",
+            "Runs another saved profile as a reusable sub-graph. Select the profile; its Profile Input nodes become the inputs of this node and its Profile Output nodes its outputs. A profile can't call itself, directly or through other profiles.",
+            "Functions",
+            vec![], // Dynamic - from the Profile Input nodes of the called profile
+            vec![], // Dynamic - from the Profile Output nodes of the called profile
+        )
+    }
+}
+
+/// Profile Input node - a named input of a profile that is called by Call Profile nodes
+/// Outputs whatever the caller connects to the matching pin
+pub struct NodesetInputNode;
+
+impl Node for NodesetInputNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "nodeset_input",
+            "Profile Input",
+            "A named input of this profile when another profile calls it with a Call Profile node. Outputs the value connected to the matching input of the Call Profile node.",
+            "Functions",
+            vec![], // No inputs - provided by the caller
+            vec![
+                NodeOutput::new(
+                    "value",
+                    "Value",
+                    "The value the calling profile connected to this input",
+                    ValueType::Any,
+                ),
+            ],
+        )
+    }
+}
+
+/// Profile Output node - a named output of a profile that is called by Call Profile nodes
+pub struct NodesetOutputNode;
+
+impl Node for NodesetOutputNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "nodeset_output",
+            "Profile Output",
+            "A named output of this profile when another profile calls it with a Call Profile node. The value connected here is output by the matching pin of the Call Profile node.",
+            "Functions",
+            vec![
+                NodeInput::new(
+                    "value",
+                    "Value",
+                    "The value to return to the calling profile",
+                    ValueType::Any,
+                    true,
+                ),
+            ],
+            vec![], // No outputs - returned to the caller
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_node_definitions() {
+        let call = CallNodesetNode::definition();
+        assert_eq!(call.node_type, "call_nodeset");
+        assert_eq!(call.category, "Functions");
+        assert!(call.inputs.is_empty() && call.outputs.is_empty());
+
+        let input = NodesetInputNode::definition();
+        assert_eq!(input.node_type, "nodeset_input");
+        assert!(input.inputs.is_empty());
+        assert_eq!(input.outputs[0].id, "value");
+        assert_eq!(input.outputs[0].value_type, ValueType::Any);
+
+        let output = NodesetOutputNode::definition();
+        assert_eq!(output.node_type, "nodeset_output");
+        assert_eq!(output.inputs[0].id, "value");
+        assert!(output.inputs[0].required);
+        assert!(output.outputs.is_empty());
+    }
+}
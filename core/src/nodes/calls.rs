@@ -0,0 +1,411 @@
+//! Calls between nodesets
+//!
+//! A Call Profile node runs another saved nodeset as a reusable sub-graph. The called nodeset
+//! declares its pins with Profile Input and Profile Output nodes, each named with `pinName`, which
+//! the Call Profile node shows as `in_<name>` and `out_<name>` pins. Before execution every call is
+//! expanded in place: the nodes of the called nodeset are copied in with their ids prefixed by the
+//! id of the call node, and the edges through its Profile Input and Profile Output nodes are
+//! reconnected to the caller. Stateful nodes inside a call (Hysteresis, Cooldown) therefore keep
+//! their state per call node, and the executor never sees a call.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+use super::execution::{NODE_TYPE_CALL_NODESET, NODE_TYPE_NODESET_INPUT, NODE_TYPE_NODESET_OUTPUT};
+
+/// Node data key holding the id of the nodeset a Call Profile node calls
+pub const CALLED_NODESET_KEY: &str = "calledNodesetId";
+/// Node data key holding the pin name of a Profile Input or Profile Output node
+pub const PIN_NAME_KEY: &str = "pinName";
+/// Prefix of the Call Profile input pin for a Profile Input of the called nodeset
+pub const INPUT_PIN_PREFIX: &str = "in_";
+/// Prefix of the Call Profile output pin for a Profile Output of the called nodeset
+pub const OUTPUT_PIN_PREFIX: &str = "out_";
+
+/// Nodes and edges of a nodeset that can be called, with its variables resolved
+#[derive(Debug, Clone, Default)]
+pub struct CallableNodeset {
+    pub nodes: Vec<Value>,
+    pub edges: Vec<Value>,
+}
+
+/// Callable nodesets by id
+pub type CallLibrary = HashMap<i64, CallableNodeset>;
+
+/// Pin names a nodeset offers to Call Profile nodes
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NodesetInterface {
+    /// Names of the Profile Input nodes, sorted
+    pub inputs: Vec<String>,
+    /// Names of the Profile Output nodes, sorted
+    pub outputs: Vec<String>,
+}
+
+fn node_type(node: &Value) -> Option<&str> {
+    node.get("data")?.get("definition")?.get("node_type")?.as_str()
+}
+
+fn node_id(node: &Value) -> &str {
+    node.get("id").and_then(|id| id.as_str()).unwrap_or_default()
+}
+
+fn pin_name(node: &Value) -> &str {
+    node.get("data").and_then(|d| d.get(PIN_NAME_KEY)).and_then(|n| n.as_str()).unwrap_or_default()
+}
+
+fn edge_field<'a>(edge: &'a Value, field: &str) -> &'a str {
+    edge.get(field).and_then(|v| v.as_str()).unwrap_or_default()
+}
+
+/// Whether any node is a Call Profile node
+pub fn has_calls(nodes: &[Value]) -> bool {
+    nodes.iter().any(|node| node_type(node) == Some(NODE_TYPE_CALL_NODESET))
+}
+
+/// Ids of the nodesets the Call Profile nodes call directly
+pub fn called_nodeset_ids(nodes: &[Value]) -> BTreeSet<i64> {
+    nodes
+        .iter()
+        .filter(|node| node_type(node) == Some(NODE_TYPE_CALL_NODESET))
+        .filter_map(|node| node.get("data")?.get(CALLED_NODESET_KEY)?.as_i64())
+        .collect()
+}
+
+/// Pin names of the Profile Input and Profile Output nodes of a nodeset
+pub fn interface(nodes: &[Value]) -> NodesetInterface {
+    let names = |wanted: &str| -> Vec<String> {
+        nodes
+            .iter()
+            .filter(|node| node_type(node) == Some(wanted))
+            .map(|node| pin_name(node).to_string())
+            .filter(|name| !name.is_empty())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    };
+    NodesetInterface {
+        inputs: names(NODE_TYPE_NODESET_INPUT),
+        outputs: names(NODE_TYPE_NODESET_OUTPUT),
+    }
+}
+
+/// Pins of the Call Profile nodes calling `called_id` that are connected but missing from
+/// `interface`, e.g. after a Profile Input or Profile Output node was removed from the called
+/// nodeset. The edges to them would be dropped when the calls are expanded.
+/// Returns the pin handles, sorted
+pub fn missing_pins(nodes: &[Value], edges: &[Value], called_id: i64, interface: &NodesetInterface) -> Vec<String> {
+    let calls: BTreeSet<&str> = nodes
+        .iter()
+        .filter(|node| node_type(node) == Some(NODE_TYPE_CALL_NODESET))
+        .filter(|node| node.get("data").and_then(|d| d.get(CALLED_NODESET_KEY)).and_then(|id| id.as_i64()) == Some(called_id))
+        .map(node_id)
+        .collect();
+
+    let mut missing = BTreeSet::new();
+    for edge in edges {
+        if calls.contains(edge_field(edge, "target")) {
+            let handle = edge_field(edge, "targetHandle");
+            if handle.strip_prefix(INPUT_PIN_PREFIX).is_some_and(|name| !interface.inputs.iter().any(|i| i == name)) {
+                missing.insert(handle.to_string());
+            }
+        }
+        if calls.contains(edge_field(edge, "source")) {
+            let handle = edge_field(edge, "sourceHandle");
+            if handle.strip_prefix(OUTPUT_PIN_PREFIX).is_some_and(|name| !interface.outputs.iter().any(|o| o == name)) {
+                missing.insert(handle.to_string());
+            }
+        }
+    }
+    missing.into_iter().collect()
+}
+
+/// Expand every Call Profile node into the nodes of the nodeset it calls, recursively
+/// `nodeset_id` is the id of the nodeset being expanded, if it is saved, so it can't call itself.
+/// Returns an error if a call selects no nodeset, calls one that doesn't exist or calls form a cycle.
+pub fn expand_calls(
+    nodeset_id: Option<i64>,
+    nodes: &[Value],
+    edges: &[Value],
+    library: &CallLibrary,
+) -> Result<(Vec<Value>, Vec<Value>), String> {
+    let mut stack: Vec<i64> = nodeset_id.into_iter().collect();
+    expand(&mut stack, nodes, edges, library)
+}
+
+fn expand(
+    stack: &mut Vec<i64>,
+    nodes: &[Value],
+    edges: &[Value],
+    library: &CallLibrary,
+) -> Result<(Vec<Value>, Vec<Value>), String> {
+    let mut expanded_nodes = Vec::new();
+    let mut expanded_edges = edges.to_vec();
+
+    for node in nodes {
+        if node_type(node) != Some(NODE_TYPE_CALL_NODESET) {
+            expanded_nodes.push(node.clone());
+            continue;
+        }
+
+        let call_id = node_id(node);
+        let called_id = node
+            .get("data")
+            .and_then(|d| d.get(CALLED_NODESET_KEY))
+            .and_then(|id| id.as_i64())
+            .ok_or_else(|| format!("Call Profile node '{}' has no profile selected", call_id))?;
+        if stack.contains(&called_id) {
+            return Err(format!("Call Profile node '{}' calls profile {} which is already calling it", call_id, called_id));
+        }
+        let called = library
+            .get(&called_id)
+            .ok_or_else(|| format!("Call Profile node '{}' calls profile {} which doesn't exist", call_id, called_id))?;
+
+        stack.push(called_id);
+        let (inner_nodes, inner_edges) = expand(stack, &called.nodes, &called.edges, library)?;
+        stack.pop();
+
+        let prefixed = |id: &str| format!("{}/{}", call_id, id);
+
+        // What the caller connected to each input pin, removing the edges into the call node
+        let mut input_sources: HashMap<String, (String, String)> = HashMap::new();
+        expanded_edges.retain(|edge| {
+            if edge_field(edge, "target") != call_id {
+                return true;
+            }
+            if let Some(name) = edge_field(edge, "targetHandle").strip_prefix(INPUT_PIN_PREFIX) {
+                input_sources.insert(
+                    name.to_string(),
+                    (edge_field(edge, "source").to_string(), edge_field(edge, "sourceHandle").to_string()),
+                );
+            }
+            false
+        });
+
+        let mut input_names: HashMap<&str, &str> = HashMap::new();
+        let mut output_names: HashMap<&str, &str> = HashMap::new();
+        for inner in &inner_nodes {
+            match node_type(inner) {
+                Some(NODE_TYPE_NODESET_INPUT) => {
+                    input_names.insert(node_id(inner), pin_name(inner));
+                }
+                Some(NODE_TYPE_NODESET_OUTPUT) => {
+                    output_names.insert(node_id(inner), pin_name(inner));
+                }
+                _ => {
+                    let mut inner = inner.clone();
+                    inner["id"] = Value::from(prefixed(node_id(&inner)));
+                    expanded_nodes.push(inner);
+                }
+            }
+        }
+
+        // Source of an inner edge in the expanded graph, None if it comes from an unconnected input
+        let source_of = |edge: &Value| -> Option<(String, String)> {
+            let source = edge_field(edge, "source");
+            match input_names.get(source) {
+                Some(name) => input_sources.get(*name).cloned(),
+                None => Some((prefixed(source), edge_field(edge, "sourceHandle").to_string())),
+            }
+        };
+
+        let mut output_sources: HashMap<&str, (String, String)> = HashMap::new();
+        for edge in &inner_edges {
+            let target = edge_field(edge, "target");
+            let Some(source) = source_of(edge) else { continue };
+            if let Some(name) = output_names.get(target) {
+                output_sources.insert(name, source);
+                continue;
+            }
+            let mut edge = edge.clone();
+            edge["id"] = Value::from(prefixed(edge_field(&edge, "id")));
+            edge["source"] = Value::from(source.0);
+            edge["sourceHandle"] = Value::from(source.1);
+            edge["target"] = Value::from(prefixed(target));
+            expanded_edges.push(edge);
+        }
+
+        // Reconnect what the caller took from each output pin; outputs the called nodeset
+        // doesn't provide are dropped and fail as missing inputs if they are used
+        expanded_edges.retain_mut(|edge| {
+            if edge_field(edge, "source") != call_id {
+                return true;
+            }
+            let source = edge_field(edge, "sourceHandle")
+                .strip_prefix(OUTPUT_PIN_PREFIX)
+                .and_then(|name| output_sources.get(name));
+            match source {
+                Some((source, source_handle)) => {
+                    edge["source"] = Value::from(source.clone());
+                    edge["sourceHandle"] = Value::from(source_handle.clone());
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    Ok((expanded_nodes, expanded_edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn node(id: &str, node_type: &str, data: Value) -> Value {
+        let mut data = data;
+        data["definition"] = json!({ "node_type": node_type });
+        json!({ "id": id, "data": data })
+    }
+
+    fn edge(source: &str, source_handle: &str, target: &str, target_handle: &str) -> Value {
+        json!({
+            "id": format!("{}-{}", source, target),
+            "source": source,
+            "sourceHandle": source_handle,
+            "target": target,
+            "targetHandle": target_handle,
+        })
+    }
+
+    fn fields(edge: &Value) -> (&str, &str, &str, &str) {
+        (
+            edge_field(edge, "source"),
+            edge_field(edge, "sourceHandle"),
+            edge_field(edge, "target"),
+            edge_field(edge, "targetHandle"),
+        )
+    }
+
+    /// Nodeset 7: out = min(in_value, in_limit)
+    fn min_library() -> CallLibrary {
+        let nodes = vec![
+            node("input-1", NODE_TYPE_NODESET_INPUT, json!({ "pinName": "value" })),
+            node("input-2", NODE_TYPE_NODESET_INPUT, json!({ "pinName": "limit" })),
+            node("min-1", "math_min", json!({})),
+            node("output-1", NODE_TYPE_NODESET_OUTPUT, json!({ "pinName": "result" })),
+        ];
+        let edges = vec![
+            edge("input-1", "value", "min-1", "input_a"),
+            edge("input-2", "value", "min-1", "input_b"),
+            edge("min-1", "result", "output-1", "value"),
+        ];
+        [(7, CallableNodeset { nodes, edges })].into_iter().collect()
+    }
+
+    fn call_node(id: &str, called: i64) -> Value {
+        node(id, NODE_TYPE_CALL_NODESET, json!({ "calledNodesetId": called }))
+    }
+
+    #[test]
+    fn test_interface() {
+        let library = min_library();
+        assert_eq!(
+            interface(&library[&7].nodes),
+            NodesetInterface {
+                inputs: vec!["limit".to_string(), "value".to_string()],
+                outputs: vec!["result".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_pins() {
+        let nodes = vec![node("float-1", "primitive_float", json!({})), call_node("call-1", 7), call_node("call-2", 8)];
+        let edges = vec![
+            edge("float-1", "value", "call-1", "in_value"),
+            edge("float-1", "value", "call-1", "in_limit"),
+            edge("call-1", "out_result", "float-1", "value"),
+            edge("float-1", "value", "call-2", "in_other"),
+        ];
+        let reduced = NodesetInterface { inputs: vec!["value".to_string()], outputs: vec![] };
+
+        // Only the pins of calls to nodeset 7 are checked
+        assert_eq!(missing_pins(&nodes, &edges, 7, &reduced), ["in_limit", "out_result"]);
+        assert!(missing_pins(&nodes, &edges, 7, &interface(&min_library()[&7].nodes)).is_empty());
+        assert!(missing_pins(&nodes, &edges, 9, &reduced).is_empty());
+    }
+
+    #[test]
+    fn test_expand_reconnects_inputs_and_outputs() {
+        let nodes = vec![
+            node("float-1", "primitive_float", json!({})),
+            node("float-2", "primitive_float", json!({})),
+            call_node("call-1", 7),
+            node("execute-1", "flow_execute_action", json!({})),
+        ];
+        let edges = vec![
+            edge("float-1", "value", "call-1", "in_value"),
+            edge("float-2", "value", "call-1", "in_limit"),
+            edge("call-1", "out_result", "execute-1", "temperature"),
+        ];
+
+        let (nodes, edges) = expand_calls(Some(1), &nodes, &edges, &min_library()).unwrap();
+
+        let ids: Vec<&str> = nodes.iter().map(node_id).collect();
+        assert_eq!(ids, ["float-1", "float-2", "call-1/min-1", "execute-1"]);
+        let mut edges: Vec<_> = edges.iter().map(fields).collect();
+        edges.sort();
+        assert_eq!(edges, [
+            ("call-1/min-1", "result", "execute-1", "temperature"),
+            ("float-1", "value", "call-1/min-1", "input_a"),
+            ("float-2", "value", "call-1/min-1", "input_b"),
+        ]);
+    }
+
+    #[test]
+    fn test_expand_nested_calls_and_unconnected_pins() {
+        let mut library = min_library();
+        // Nodeset 8 passes its input straight through nodeset 7
+        library.insert(8, CallableNodeset {
+            nodes: vec![
+                node("input-1", NODE_TYPE_NODESET_INPUT, json!({ "pinName": "x" })),
+                call_node("inner", 7),
+                node("output-1", NODE_TYPE_NODESET_OUTPUT, json!({ "pinName": "y" })),
+            ],
+            edges: vec![
+                edge("input-1", "value", "inner", "in_value"),
+                edge("inner", "out_result", "output-1", "value"),
+            ],
+        });
+        let nodes = vec![node("float-1", "primitive_float", json!({})), call_node("outer", 8), node("if-1", "logic_if", json!({}))];
+        let edges = vec![
+            edge("float-1", "value", "outer", "in_x"),
+            edge("outer", "out_y", "if-1", "condition"),
+            edge("outer", "out_missing", "if-1", "other"),
+        ];
+
+        let (nodes, edges) = expand_calls(None, &nodes, &edges, &library).unwrap();
+
+        let ids: Vec<&str> = nodes.iter().map(node_id).collect();
+        assert_eq!(ids, ["float-1", "outer/inner/min-1", "if-1"]);
+        let mut edges: Vec<_> = edges.iter().map(fields).collect();
+        edges.sort();
+        // The limit input isn't connected, so min-1 only gets its first input
+        assert_eq!(edges, [
+            ("float-1", "value", "outer/inner/min-1", "input_a"),
+            ("outer/inner/min-1", "result", "if-1", "condition"),
+        ]);
+    }
+
+    #[test]
+    fn test_expand_rejects_cycles_and_missing_nodesets() {
+        let mut library = min_library();
+        library.insert(1, CallableNodeset { nodes: vec![call_node("back", 2)], edges: vec![] });
+        library.insert(2, CallableNodeset { nodes: vec![call_node("forth", 1)], edges: vec![] });
+
+        let error = expand_calls(Some(1), &[call_node("call-1", 1)], &[], &library).unwrap_err();
+        assert!(error.contains("already calling"), "{}", error);
+        let error = expand_calls(None, &[call_node("call-1", 2)], &[], &library).unwrap_err();
+        assert!(error.contains("already calling"), "{}", error);
+        let error = expand_calls(None, &[call_node("call-1", 99)], &[], &library).unwrap_err();
+        assert!(error.contains("doesn't exist"), "{}", error);
+        let error = expand_calls(None, &[node("call-1", NODE_TYPE_CALL_NODESET, json!({}))], &[], &library).unwrap_err();
+        assert!(error.contains("no profile selected"), "{}", error);
+        // The same nodeset may be called twice side by side
+        assert!(expand_calls(None, &[call_node("a", 7), call_node("b", 7)], &[], &library).is_ok());
+    }
+}
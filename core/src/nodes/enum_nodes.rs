@@ -1,4 +1,6 @@
 use super::node_system::{Node, NodeDefinition, NodeOutput, ValueType};
+use crate::ac_state::vane_names;
+use crate::types::Season;
 
 /// Device node - represents an AC device enum selection
 /// This node provides a dropdown/combobox for selecting an AC device
@@ -17,10 +19,8 @@ impl Node for DeviceNode {
                     "device",
                     "Device",
                     "The selected AC device",
-                    ValueType::Enum(vec![
-                        "LivingRoom".to_string(),
-                        "Veranda".to_string(),
-                    ]),
+                    // Populated with the configured devices by the server
+                    ValueType::Enum(vec![]),
                 ),
             ],
         )
@@ -83,7 +83,7 @@ impl Node for CauseReasonNode {
 }
 
 /// RequestMode node - represents a request mode for AC operation
-/// This node provides a dropdown for selecting request mode (Heat, Cool, Off)
+/// This node provides a dropdown for selecting request mode (Heat, Cool, Dry, Fan, Off)
 pub struct RequestModeNode;
 
 impl Node for RequestModeNode {
@@ -102,6 +102,8 @@ impl Node for RequestModeNode {
                     ValueType::Enum(vec![
                         "Heat".to_string(),
                         "Cool".to_string(),
+                        "Dry".to_string(),
+                        "Fan".to_string(),
                         "Off".to_string(),
                     ]),
                 ),
@@ -140,6 +142,131 @@ impl Node for FanSpeedNode {
     }
 }
 
+/// Data Source node - selects the data a Data Age node reports on
+pub struct DataSourceNode;
+
+impl Node for DataSourceNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "data_source",
+            "Data Source",
+            "Select a data source for the Data Age node.",
+            "Enums",
+            vec![], // No inputs - this is a source node with enum selection
+            vec![
+                NodeOutput::new(
+                    "source",
+                    "Source",
+                    "The selected data source",
+                    ValueType::Enum(vec![
+                        "Weather".to_string(),
+                        "Meter".to_string(),
+                        "Sensor".to_string(),
+                    ]),
+                ),
+            ],
+        )
+    }
+}
+
+/// Season node - represents a season selection
+/// Compare with the Start node's Season output to gate heat-only or cool-only logic
+pub struct SeasonNode;
+
+impl Node for SeasonNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "season",
+            "Season",
+            "Select a season to compare with the Start node's Season output.",
+            "Enums",
+            vec![], // No inputs - this is a source node with enum selection
+            vec![
+                NodeOutput::new(
+                    "season",
+                    "Season",
+                    "The selected season",
+                    ValueType::Enum(Season::names()),
+                ),
+            ],
+        )
+    }
+}
+
+/// Swing Mode node - represents a vane position for the vertical or horizontal vane
+/// Connect to the vane inputs of Execute Action
+pub struct SwingModeNode;
+
+impl Node for SwingModeNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "swing_mode",
+            "Swing Mode",
+            "Select a vane position: Auto, Swing, or a fixed Position 1 (top / far left) to 5 (bottom / far right).",
+            "Enums",
+            vec![], // No inputs - this is a source node with enum selection
+            vec![
+                NodeOutput::new(
+                    "swing_mode",
+                    "Swing Mode",
+                    "The selected vane position",
+                    ValueType::Enum(vane_names()),
+                ),
+            ],
+        )
+    }
+}
+
+/// PIR Zone node - represents a zone of PIR sensors
+/// Connect to the zone input of PIR Detection
+pub struct PirZoneNode;
+
+impl Node for PirZoneNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "pir_zone",
+            "PIR Zone",
+            "Select a zone of PIR sensors from the config, to check with PIR Detection.",
+            "Enums",
+            vec![], // No inputs - this is a source node with enum selection
+            vec![
+                NodeOutput::new(
+                    "zone",
+                    "Zone",
+                    "The selected zone",
+                    // Populated with the configured zones by the server
+                    ValueType::Enum(vec![]),
+                ),
+            ],
+        )
+    }
+}
+
+/// PIR Sensor node - represents a single named PIR sensor
+/// Connect to the sensor input of PIR Detection
+pub struct PirSensorNode;
+
+impl Node for PirSensorNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "pir_sensor",
+            "PIR Sensor",
+            "Select a PIR sensor from the config, to check with PIR Detection.",
+            "Enums",
+            vec![], // No inputs - this is a source node with enum selection
+            vec![
+                NodeOutput::new(
+                    "sensor",
+                    "Sensor",
+                    "The selected sensor",
+                    // Populated with the configured sensors by the server
+                    ValueType::Enum(vec![]),
+                ),
+            ],
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,12 +281,9 @@ mod tests {
         assert_eq!(def.inputs.len(), 0); // Source node has no inputs
         assert_eq!(def.outputs.len(), 1); // One output: device
         
-        // Verify output is an enum with device values
+        // Verify output is an enum, its device values are filled in by the server
         match &def.outputs[0].value_type {
-            ValueType::Enum(values) => {
-                assert!(values.contains(&"LivingRoom".to_string()));
-                assert!(values.contains(&"Veranda".to_string()));
-            }
+            ValueType::Enum(values) => assert!(values.is_empty()),
             _ => panic!("Expected Enum type for device output"),
         }
     }
@@ -237,9 +361,11 @@ mod tests {
         // Verify output is an enum with request mode values
         match &def.outputs[0].value_type {
             ValueType::Enum(values) => {
-                assert_eq!(values.len(), 3);
+                assert_eq!(values.len(), 5);
                 assert!(values.contains(&"Heat".to_string()));
                 assert!(values.contains(&"Cool".to_string()));
+                assert!(values.contains(&"Dry".to_string()));
+                assert!(values.contains(&"Fan".to_string()));
                 assert!(values.contains(&"Off".to_string()));
             }
             _ => panic!("Expected Enum type for request_mode output"),
@@ -269,4 +395,60 @@ mod tests {
             _ => panic!("Expected Enum type for fan_speed output"),
         }
     }
+
+    #[test]
+    fn test_data_source_node_definition() {
+        let def = DataSourceNode::definition();
+
+        assert_eq!(def.node_type, "data_source");
+        assert_eq!(def.category, "Enums");
+        assert_eq!(def.inputs.len(), 0);
+        assert_eq!(
+            def.outputs[0].value_type,
+            ValueType::Enum(vec!["Weather".to_string(), "Meter".to_string(), "Sensor".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_season_node_definition() {
+        let def = SeasonNode::definition();
+
+        assert_eq!(def.node_type, "season");
+        assert_eq!(def.category, "Enums");
+        assert_eq!(def.inputs.len(), 0);
+        assert_eq!(
+            def.outputs[0].value_type,
+            ValueType::Enum(vec!["Heating".to_string(), "Cooling".to_string(), "Neutral".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_swing_mode_node_definition() {
+        let def = SwingModeNode::definition();
+
+        assert_eq!(def.node_type, "swing_mode");
+        assert_eq!(def.category, "Enums");
+        assert_eq!(def.inputs.len(), 0);
+        match &def.outputs[0].value_type {
+            ValueType::Enum(values) => {
+                assert_eq!(values.len(), 7);
+                assert_eq!(values.first().map(String::as_str), Some("Auto"));
+                assert!(values.contains(&"Position 3".to_string()));
+                assert!(values.contains(&"Swing".to_string()));
+            }
+            _ => panic!("Expected Enum type for swing_mode output"),
+        }
+    }
+
+    #[test]
+    fn test_pir_zone_and_sensor_node_definitions() {
+        for (def, output_id) in [(PirZoneNode::definition(), "zone"), (PirSensorNode::definition(), "sensor")] {
+            assert_eq!(def.category, "Enums");
+            assert_eq!(def.inputs.len(), 0);
+            assert_eq!(def.outputs.len(), 1);
+            assert_eq!(def.outputs[0].id, output_id);
+            // Values are filled in by the server
+            assert_eq!(def.outputs[0].value_type, ValueType::Enum(vec![]));
+        }
+    }
 }
@@ -0,0 +1,5329 @@
+//! Nodeset Execution Engine
+//! 
+//! This module provides the runtime execution of node graphs.
+//! It takes a nodeset configuration (nodes and edges) and input values,
+//! then evaluates the graph to produce an output result.
+//!
+//! Execution Flow:
+//! The execution follows "Execution" type connections from the Start node.
+//! When an If node is reached, it evaluates its condition and follows either
+//! the True or False execution path. When a Sequence node is reached, it tries
+//! each output in order until one path leads to a terminal node (Execute Action
+//! or Do Nothing). Data connections (non-Execution types) are evaluated lazily
+//! when needed by nodes along the execution path.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::text_nodes::MAX_FORMAT_DECIMALS;
+use super::variables;
+// Import AC mode constants from ac_executor
+use crate::ac_state::ac_mode_name;
+use crate::types::{Celsius, Minutes, Season, Watts};
+
+/// Node type identifiers
+pub const NODE_TYPE_START: &str = "flow_start";
+pub const NODE_TYPE_EXECUTE_ACTION: &str = "flow_execute_action";
+pub const NODE_TYPE_DO_NOTHING: &str = "flow_do_nothing";
+pub const NODE_TYPE_TURN_OFF: &str = "flow_turn_off";
+pub const NODE_TYPE_ACTIVE_COMMAND: &str = "flow_active_command";
+pub const NODE_TYPE_RESET_ACTIVE_COMMAND: &str = "flow_reset_active_command";
+pub const NODE_TYPE_NOTIFY: &str = "flow_notify";
+pub const NODE_TYPE_LOGIC_AND: &str = "logic_and";
+pub const NODE_TYPE_LOGIC_OR: &str = "logic_or";
+pub const NODE_TYPE_LOGIC_NAND: &str = "logic_nand";
+pub const NODE_TYPE_LOGIC_IF: &str = "logic_if";
+pub const NODE_TYPE_LOGIC_NOT: &str = "logic_not";
+pub const NODE_TYPE_LOGIC_EQUALS: &str = "logic_equals";
+pub const NODE_TYPE_LOGIC_EVALUATE_NUMBER: &str = "logic_evaluate_number";
+pub const NODE_TYPE_LOGIC_BRANCH: &str = "logic_branch";
+pub const NODE_TYPE_LOGIC_SEQUENCE: &str = "logic_sequence";
+pub const NODE_TYPE_LOGIC_HYSTERESIS: &str = "logic_hysteresis";
+pub const NODE_TYPE_LOGIC_COOLDOWN: &str = "logic_cooldown";
+pub const NODE_TYPE_MATH_ADD: &str = "math_add";
+pub const NODE_TYPE_MATH_SUBTRACT: &str = "math_subtract";
+pub const NODE_TYPE_MATH_MULTIPLY: &str = "math_multiply";
+pub const NODE_TYPE_MATH_DIVIDE: &str = "math_divide";
+pub const NODE_TYPE_MATH_MIN: &str = "math_min";
+pub const NODE_TYPE_MATH_MAX: &str = "math_max";
+pub const NODE_TYPE_MATH_CLAMP: &str = "math_clamp";
+pub const NODE_TYPE_MATH_AVERAGE: &str = "math_average";
+pub const NODE_TYPE_TEXT_CONCAT: &str = "text_concat";
+pub const NODE_TYPE_TEXT_FORMAT_NUMBER: &str = "text_format_number";
+pub const NODE_TYPE_ANNOTATION_COMMENT: &str = "annotation_comment";
+pub const NODE_TYPE_CALL_NODESET: &str = "call_nodeset";
+pub const NODE_TYPE_NODESET_INPUT: &str = "nodeset_input";
+pub const NODE_TYPE_NODESET_OUTPUT: &str = "nodeset_output";
+pub const NODE_TYPE_PRIMITIVE_FLOAT: &str = "primitive_float";
+pub const NODE_TYPE_PRIMITIVE_INTEGER: &str = "primitive_integer";
+pub const NODE_TYPE_PRIMITIVE_BOOLEAN: &str = "primitive_boolean";
+pub const NODE_TYPE_PRIMITIVE_STRING: &str = "primitive_string";
+pub const NODE_TYPE_VARIABLE: &str = "primitive_variable";
+pub const NODE_TYPE_DEVICE: &str = "device";
+pub const NODE_TYPE_INTENSITY: &str = "intensity";
+pub const NODE_TYPE_CAUSE_REASON: &str = "cause_reason";
+pub const NODE_TYPE_REQUEST_MODE: &str = "request_mode";
+pub const NODE_TYPE_FAN_SPEED: &str = "fan_speed";
+pub const NODE_TYPE_PIR_DETECTION: &str = "pir_detection";
+pub const NODE_TYPE_OCCUPANCY: &str = "occupancy";
+pub const NODE_TYPE_CONTACT_SENSOR: &str = "contact_sensor";
+pub const NODE_TYPE_HUMIDITY: &str = "humidity";
+pub const NODE_TYPE_TIME_OF_DAY: &str = "time_of_day";
+pub const NODE_TYPE_DAY_OF_WEEK: &str = "day_of_week";
+pub const NODE_TYPE_ENERGY_PRICE: &str = "energy_price";
+pub const NODE_TYPE_BATTERY: &str = "battery";
+pub const NODE_TYPE_POWER_BUDGET: &str = "power_budget";
+pub const NODE_TYPE_DATA_SOURCE: &str = "data_source";
+pub const NODE_TYPE_DATA_AGE: &str = "data_age";
+pub const NODE_TYPE_WEATHER_FORECAST: &str = "weather_forecast";
+pub const NODE_TYPE_SOLAR_FORECAST: &str = "solar_forecast";
+pub const NODE_TYPE_SEASON: &str = "season";
+pub const NODE_TYPE_SWING_MODE: &str = "swing_mode";
+pub const NODE_TYPE_PIR_ZONE: &str = "pir_zone";
+pub const NODE_TYPE_PIR_SENSOR: &str = "pir_sensor";
+
+/// Annotations longer than this are cut off, they are stored with every execution and command
+pub const MAX_ANNOTATION_CHARS: usize = 200;
+
+/// Sentinel value indicating no PIR detection has ever occurred
+pub const PIR_NEVER_DETECTED: i64 = -1;
+
+/// Sentinel value for humidity outputs when no humidity reading is available
+pub const HUMIDITY_UNAVAILABLE: f64 = -1.0;
+
+/// Sentinel value for cheap_hour_rank when no price is known for the current hour
+pub const PRICE_RANK_UNAVAILABLE: i64 = -1;
+
+/// Sentinel value for the Battery node's soc_percent when the battery status is unavailable
+pub const BATTERY_SOC_UNAVAILABLE: f64 = -1.0;
+
+/// Sentinel value for the Data Age node's age_minutes when the data is unavailable
+pub const DATA_AGE_UNAVAILABLE: i64 = -1;
+
+/// Night starts at this local hour (inclusive) for the Time of Day node's is_night output
+pub const NIGHT_START_HOUR: i64 = 22;
+
+/// Night ends at this local hour (exclusive) for the Time of Day node's is_night output
+pub const NIGHT_END_HOUR: i64 = 7;
+
+/// Sentinel value for dr_minutes_until_event when no demand-response event is scheduled
+pub const NO_DR_EVENT_SCHEDULED: i64 = -1;
+
+/// Default temperature value for Turn Off node (used when the AC is turned off)
+pub const TURN_OFF_DEFAULT_TEMPERATURE: f64 = 21.0;
+
+/// Cooldown used by Notify nodes that don't set `notifyCooldownMinutes`
+pub const DEFAULT_NOTIFY_COOLDOWN_MINUTES: i64 = 60;
+
+/// Default tolerance for floating-point comparisons (suitable for temperature values in AC control)
+/// Can be overridden per executor with `NodesetExecutor::with_float_tolerance`
+pub const DEFAULT_FLOAT_TOLERANCE: f64 = 0.0001;
+
+/// A runtime value that can flow through nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RuntimeValue {
+    Float(f64),
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+    /// Represents an Active Command object (the last command sent to a device)
+    ActiveCommand(ActiveCommandData),
+}
+
+/// Data for the Active Command - represents the last command sent to a device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ActiveCommandData {
+    /// Whether an active command exists (a command was previously sent)
+    pub is_defined: bool,
+    /// Whether the AC is currently on (based on last command)
+    pub is_on: bool,
+    /// Target temperature in Celsius from the last command
+    pub temperature: f64,
+    /// AC mode: 0 = Off, 1 = Cool, 2 = Dry, 3 = Fan, 4 = Heat
+    pub mode: i32,
+    /// Fan speed setting (0-5, where 0 is auto)
+    pub fan_speed: i32,
+    /// Swing setting (0 = off, 1 = on)
+    pub swing: i32,
+    /// Whether powerful/turbo mode was enabled
+    pub is_powerful: bool,
+    /// Temperature the controller aims for: the profile's temperature plus the setpoint offset,
+    /// before `temperature` was rounded to the step the AC supports
+    #[serde(default)]
+    pub target_temperature: f64,
+}
+
+impl Default for ActiveCommandData {
+    fn default() -> Self {
+        Self {
+            is_defined: false,
+            is_on: false,
+            temperature: 0.0,
+            mode: 0,
+            fan_speed: 0,
+            swing: 0,
+            is_powerful: false,
+            target_temperature: 0.0,
+        }
+    }
+}
+
+/// Demand-response state provided by the utility aggregator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DemandResponseData {
+    /// Whether a demand-response event is currently in progress
+    pub event_active: bool,
+    /// Curtailment level of the current or upcoming event (0 if none)
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub level: i64,
+    /// Minutes until the next event starts (0 while active, -1 if none scheduled)
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub minutes_until_event: i64,
+}
+
+impl Default for DemandResponseData {
+    fn default() -> Self {
+        Self {
+            event_active: false,
+            level: 0,
+            minutes_until_event: NO_DR_EVENT_SCHEDULED,
+        }
+    }
+}
+
+/// Share of the total AC power budget, estimated from the tracked state of every device
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PowerBudgetData {
+    /// Whether a power budget is configured
+    pub is_enabled: bool,
+    /// Most power all devices may draw together, in watts (0 if disabled)
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub budget_watt: i64,
+    /// Estimated draw of the other devices, in watts
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub other_devices_watt: i64,
+    /// Estimated draw of the evaluated device while running, in watts
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub device_watt: i64,
+    /// Estimated draw of the evaluated device in powerful mode, in watts
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub device_powerful_watt: i64,
+}
+
+impl PowerBudgetData {
+    /// Watts left for the evaluated device (0 if disabled, negative if already exceeded)
+    pub fn remaining_watt(&self) -> i64 {
+        if self.is_enabled { self.budget_watt - self.other_devices_watt } else { 0 }
+    }
+
+    /// Whether the evaluated device may draw `watt` without exceeding the budget
+    pub fn fits(&self, watt: i64) -> bool {
+        !self.is_enabled || watt <= self.remaining_watt()
+    }
+}
+
+/// Local wall-clock time of an evaluation
+/// Plain numbers so the simulator can evaluate a nodeset as if it were another time of day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LocalTimeData {
+    /// Hour of the day (0-23)
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub hour: i64,
+    /// Minute of the hour (0-59)
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub minute: i64,
+    /// Day of the week, 1 = Monday through 7 = Sunday
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub weekday: i64,
+}
+
+impl Default for LocalTimeData {
+    fn default() -> Self {
+        Self {
+            hour: 12,
+            minute: 0,
+            weekday: 1,
+        }
+    }
+}
+
+impl LocalTimeData {
+    /// Minutes since local midnight
+    pub fn minutes_since_midnight(&self) -> i64 {
+        self.hour * 60 + self.minute
+    }
+
+    /// Saturday or Sunday
+    pub fn is_weekend(&self) -> bool {
+        self.weekday >= 6
+    }
+
+    /// Between NIGHT_START_HOUR and NIGHT_END_HOUR
+    pub fn is_night(&self) -> bool {
+        self.hour >= NIGHT_START_HOUR || self.hour < NIGHT_END_HOUR
+    }
+}
+
+/// Day-ahead electricity prices for dynamic tariffs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EnergyPriceData {
+    /// Wholesale price of the current hour in EUR/kWh, None if unavailable
+    pub current_price_eur_kwh: Option<f64>,
+    /// Upcoming hours with a known price as offsets from the current hour (0 = current hour, up to 23),
+    /// sorted from cheapest to most expensive
+    #[cfg_attr(feature = "ts", ts(type = "Array<number>"))]
+    pub cheapest_hours_next_24h: Vec<i64>,
+}
+
+impl EnergyPriceData {
+    /// 1-based rank of the current hour among the upcoming hours, None if its price is unknown
+    pub fn current_hour_rank(&self) -> Option<i64> {
+        self.cheapest_hours_next_24h
+            .iter()
+            .position(|offset| *offset == 0)
+            .map(|index| index as i64 + 1)
+    }
+}
+
+/// Hourly weather forecast for the hours after the current one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WeatherForecastData {
+    /// Forecast outdoor temperature in Celsius of each hour after the current one (index 0 = next hour),
+    /// up to 48 hours, empty if unavailable
+    pub hourly_temperatures: Vec<f64>,
+    /// Forecast solar radiation in W/m² of the same hours, empty if unavailable
+    pub hourly_solar_radiation: Vec<f64>,
+}
+
+impl WeatherForecastData {
+    /// Lowest forecast temperature of the next `hours` hours, None if unavailable
+    pub fn min_next(&self, hours: usize) -> Option<f64> {
+        self.hourly_temperatures.iter().take(hours).copied().reduce(f64::min)
+    }
+
+    /// Highest forecast temperature of the next `hours` hours, None if unavailable
+    pub fn max_next(&self, hours: usize) -> Option<f64> {
+        self.hourly_temperatures.iter().take(hours).copied().reduce(f64::max)
+    }
+
+    /// Forecast temperature `hours` hours from now, None if the forecast doesn't cover that hour
+    pub fn temperature_in(&self, hours: i64) -> Option<f64> {
+        Self::hour(&self.hourly_temperatures, hours)
+    }
+
+    /// Forecast solar radiation `hours` hours from now, None if the forecast doesn't cover that hour
+    pub fn solar_radiation_in(&self, hours: i64) -> Option<f64> {
+        Self::hour(&self.hourly_solar_radiation, hours)
+    }
+
+    fn hour(values: &[f64], hours: i64) -> Option<f64> {
+        let index = usize::try_from(hours).ok()?.checked_sub(1)?;
+        values.get(index).copied()
+    }
+}
+
+/// Age of the data behind the inputs, None if the data is unavailable
+/// Cached and stale fallback data can be older than the evaluation, see the Data Age node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DataAges {
+    /// Minutes since the outdoor weather was fetched
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub weather: Option<Minutes>,
+    /// Minutes since the smart meter was read
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub meter: Option<Minutes>,
+    /// Minutes since the device sensor was read
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub sensor: Option<Minutes>,
+}
+
+impl DataAges {
+    /// All data was just read, e.g. for inputs entered in the simulator
+    pub fn fresh() -> Self {
+        Self {
+            weather: Some(Minutes(0)),
+            meter: Some(Minutes(0)),
+            sensor: Some(Minutes(0)),
+        }
+    }
+
+    /// Age of a data source by its Data Source node value ("Weather", "Meter" or "Sensor")
+    pub fn get(&self, source: &str) -> Option<Option<Minutes>> {
+        match source {
+            "Weather" => Some(self.weather),
+            "Meter" => Some(self.meter),
+            "Sensor" => Some(self.sensor),
+            _ => None,
+        }
+    }
+}
+
+impl RuntimeValue {
+    /// Get the type name for error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RuntimeValue::Float(_) => "Float",
+            RuntimeValue::Integer(_) => "Integer",
+            RuntimeValue::Boolean(_) => "Boolean",
+            RuntimeValue::String(_) => "String",
+            RuntimeValue::ActiveCommand(_) => "ActiveCommand",
+        }
+    }
+
+    /// Try to convert to f64 (for numeric comparisons)
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            RuntimeValue::Float(v) => Some(*v),
+            RuntimeValue::Integer(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    /// Try to convert to bool
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            RuntimeValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Try to convert to string
+    pub fn as_string(&self) -> String {
+        match self {
+            RuntimeValue::Float(v) => v.to_string(),
+            RuntimeValue::Integer(v) => v.to_string(),
+            RuntimeValue::Boolean(v) => v.to_string(),
+            RuntimeValue::String(v) => v.clone(),
+            RuntimeValue::ActiveCommand(_) => "ActiveCommand".to_string(),
+        }
+    }
+
+    /// Try to get as ActiveCommandData
+    pub fn as_active_command(&self) -> Option<&ActiveCommandData> {
+        match self {
+            RuntimeValue::ActiveCommand(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Convert to JSON, e.g. for an execution trace
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            RuntimeValue::Float(v) => serde_json::Value::from(*v),
+            RuntimeValue::Integer(v) => serde_json::Value::from(*v),
+            RuntimeValue::Boolean(v) => serde_json::Value::from(*v),
+            RuntimeValue::String(v) => serde_json::Value::from(v.clone()),
+            RuntimeValue::ActiveCommand(data) => serde_json::to_value(data).unwrap_or_default(),
+        }
+    }
+}
+
+/// Input values provided to the Start node from the simulation context
+/// Deserializable so the editor preview can supply inputs as JSON (missing fields use defaults),
+/// serializable so the controller can store a snapshot of the inputs of every cycle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionInputs {
+    pub device: String,
+    pub device_sensor_temperature: Celsius,
+    pub is_auto_mode: bool,
+    pub last_change_minutes: Minutes,
+    pub outdoor_temperature: Celsius,
+    pub is_user_home: bool,
+    /// Number of people home according to presence detection, at least 1 while is_user_home
+    pub people_home_count: i64,
+    pub net_power_watt: Watts,
+    pub raw_solar_watt: Watts,
+    /// Average outdoor temperature for the next 24 hours
+    pub avg_next_24h_outdoor_temp: Celsius,
+    /// Lowest outdoor temperature forecast for the next 12 hours
+    pub forecast_min_next_12h_temp: Celsius,
+    /// Hourly forecast for the next 48 hours, see the Weather Forecast node
+    pub weather_forecast: WeatherForecastData,
+    /// Expected PV production (Wh) of the current and next two hours, None if no panels are configured or the forecast can't be read
+    pub expected_solar_next_3h_wh: Option<f64>,
+    /// Heating or cooling season, from the rolling average outdoor temperature
+    pub season: Season,
+    /// PIR detection state by device: (is_recently_triggered, minutes_ago)
+    pub pir_state: HashMap<String, (bool, Minutes)>,
+    /// PIR detection state by named sensor, like `pir_state`
+    pub pir_sensors: HashMap<String, (bool, Minutes)>,
+    /// PIR detection state by zone, from its most recently triggered sensor
+    pub pir_zones: HashMap<String, (bool, Minutes)>,
+    /// Occupancy probability (0-1) of each device's room, missing rooms are unoccupied
+    pub occupancy: HashMap<String, f64>,
+    /// Whether a window or door contact sensor in the evaluated device's room reports open
+    pub is_window_open: bool,
+    /// Minutes the longest open window or door of each device's room has been open, rooms with everything closed are missing
+    pub open_windows: HashMap<String, Minutes>,
+    /// Active command data (last command sent to the device)
+    pub active_command: ActiveCommandData,
+    /// Whether the grid appears to be down
+    pub possible_grid_outage: bool,
+    /// Utility demand-response event state
+    pub demand_response: DemandResponseData,
+    /// Indoor relative humidity (%) reported by the device, None if the controller has no humidity sensor
+    pub device_humidity: Option<f64>,
+    /// Outdoor relative humidity (%) from the weather API, None if unavailable
+    pub outdoor_humidity: Option<f64>,
+    /// Local time of the evaluation
+    pub local_time: LocalTimeData,
+    /// Day-ahead electricity prices, empty if no bidding zone is configured
+    pub energy_price: EnergyPriceData,
+    /// Home battery state of charge (%), None if no battery is configured or it can't be read
+    pub battery_soc_percent: Option<f64>,
+    /// Home battery power, positive while discharging and negative while charging, None if unavailable
+    pub battery_power_watt: Option<Watts>,
+    /// Share of the total AC power budget available to the device
+    pub power_budget: PowerBudgetData,
+    /// Age of the weather, meter and sensor data, unavailable unless provided
+    pub data_ages: DataAges,
+    /// Latched state of each Hysteresis node after the previous execution, by node id
+    pub hysteresis_state: HashMap<String, bool>,
+    /// Unix time each Cooldown node last let execution through, by node id
+    pub cooldown_state: HashMap<String, i64>,
+}
+
+/// Result of executing a nodeset
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExecutionResult {
+    /// Whether the execution reached a terminal node
+    pub completed: bool,
+    /// The type of terminal node reached
+    pub terminal_type: Option<String>,
+    /// If Execute Action, the action parameters
+    pub action: Option<ActionResult>,
+    /// If Do Nothing, the do_nothing parameters (for debugging/simulation)
+    pub do_nothing: Option<DoNothingResult>,
+    /// Any error that occurred during execution
+    pub error: Option<String>,
+    /// Validation warnings (e.g., disconnected nodes)
+    pub warnings: Vec<String>,
+    /// Whether the active command should be reset to undefined state
+    pub reset_active_command: bool,
+    /// Notifications queued by Notify nodes on the path to the terminal node
+    pub notifications: Vec<NotificationResult>,
+}
+
+/// Action parameters when Execute Action node is reached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ActionResult {
+    pub device: String,
+    pub temperature: f64,
+    pub mode: String,
+    pub fan_speed: String,
+    pub is_powerful: bool,
+    pub enable_swing: bool,
+    pub cause_reason: String,
+    /// Vane position names from the optional vane inputs, e.g. "Position 2"
+    #[serde(default)]
+    pub vertical_vane: Option<String>,
+    #[serde(default)]
+    pub horizontal_vane: Option<String>,
+    /// Text from the optional annotation input, e.g. "preheat: solar=3200W"
+    #[serde(default)]
+    pub annotation: Option<String>,
+}
+
+/// Notification queued by a Notify node, with placeholders already filled in
+/// Sending and cooldown tracking are up to the caller
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NotificationResult {
+    /// ID of the Notify node, used to track its cooldown
+    pub node_id: String,
+    pub channel: String,
+    pub title: String,
+    pub message: String,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub cooldown_minutes: i64,
+}
+
+/// One step of a traced execution, serialized with its kind in `type`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceStep {
+    /// Execution flow reached a node
+    Visit { node_id: String },
+    /// The value of a node output, the first time it was read
+    Value { node_id: String, output_id: String, value: serde_json::Value },
+    /// An If or Sequence node followed one of its execution outputs
+    Branch { node_id: String, output_id: String },
+    /// A Sequence output didn't reach a terminal node, so the Sequence tries its next output
+    Backtrack { node_id: String, output_id: String, error: String },
+}
+
+/// Do Nothing parameters when Do Nothing node is reached (for debugging/simulation)
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DoNothingResult {
+    pub device: String,
+    pub cause_reason: String,
+}
+
+/// Errors that can occur during nodeset execution
+#[derive(Debug, Clone)]
+pub enum ExecutionError {
+    /// No Start node found
+    MissingStartNode,
+    /// Multiple Start nodes found
+    MultipleStartNodes,
+    /// No terminal node found
+    MissingTerminalNode,
+    /// Node not found
+    NodeNotFound(String),
+    /// Required input not connected
+    MissingInput { node_id: String, input_id: String },
+    /// Type mismatch
+    TypeMismatch { expected: String, got: String },
+    /// Invalid node configuration
+    InvalidNode { node_id: String, reason: String },
+    /// Cycle detected
+    CycleDetected,
+    /// Generic error
+    Other(String),
+}
+
+impl std::fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionError::MissingStartNode => write!(f, "No Start node found in the nodeset"),
+            ExecutionError::MultipleStartNodes => write!(f, "Multiple Start nodes found (expected exactly one)"),
+            ExecutionError::MissingTerminalNode => write!(f, "No path to a terminal node (Execute Action or Do Nothing)"),
+            ExecutionError::NodeNotFound(id) => write!(f, "Node not found: {}", id),
+            ExecutionError::MissingInput { node_id, input_id } => {
+                write!(f, "Required input '{}' on node '{}' is not connected", input_id, node_id)
+            }
+            ExecutionError::TypeMismatch { expected, got } => {
+                write!(f, "Type mismatch: expected {}, got {}", expected, got)
+            }
+            ExecutionError::InvalidNode { node_id, reason } => {
+                write!(f, "Invalid node '{}': {}", node_id, reason)
+            }
+            ExecutionError::CycleDetected => write!(f, "Cycle detected in node connections"),
+            ExecutionError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Runtime representation of a node for execution
+#[derive(Debug, Clone)]
+struct RuntimeNode {
+    id: String,
+    node_type: String,
+    data: serde_json::Value,
+}
+
+/// Runtime representation of an edge for execution
+#[derive(Debug, Clone)]
+struct RuntimeEdge {
+    source: String,
+    source_handle: String,
+    target: String,
+    target_handle: String,
+}
+
+/// The execution engine that evaluates nodesets
+pub struct NodesetExecutor {
+    nodes: HashMap<String, RuntimeNode>,
+    edges: Vec<RuntimeEdge>,
+    /// Cache of computed output values: (node_id, output_id) -> value
+    output_cache: HashMap<(String, String), RuntimeValue>,
+    /// Track nodes being evaluated to detect cycles
+    evaluating: std::collections::HashSet<String>,
+    /// Inputs from the simulation context
+    inputs: ExecutionInputs,
+    /// Flag to track if reset_active_command was triggered during execution
+    reset_active_command_triggered: bool,
+    /// Notifications queued by Notify nodes on the current execution path
+    notifications: Vec<NotificationResult>,
+    /// Tolerance used for float equality comparisons
+    float_tolerance: f64,
+    /// Latched state of each Hysteresis node, updated when one is evaluated
+    hysteresis_state: HashMap<String, bool>,
+    /// Unix time each Cooldown node last let execution through, updated when one passes
+    cooldown_state: HashMap<String, i64>,
+    /// Unix time of the execution, used by Cooldown nodes
+    now: i64,
+    /// Steps of the execution, only recorded when enabled with `with_trace`
+    trace: Option<Vec<TraceStep>>,
+    /// Outputs whose value is already in the trace
+    traced_outputs: std::collections::HashSet<(String, String)>,
+}
+
+impl NodesetExecutor {
+    /// Create a new executor from a nodeset configuration
+    pub fn new(
+        nodes: &[serde_json::Value],
+        edges: &[serde_json::Value],
+        inputs: ExecutionInputs,
+    ) -> Result<Self, ExecutionError> {
+        let mut node_map = HashMap::new();
+        
+        for node_json in nodes {
+            let id = node_json.get("id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| ExecutionError::Other("Node missing id".to_string()))?
+                .to_string();
+            
+            let node_type = node_json
+                .get("data")
+                .and_then(|d| d.get("definition"))
+                .and_then(|def| def.get("node_type"))
+                .and_then(|nt| nt.as_str())
+                .ok_or_else(|| ExecutionError::InvalidNode { 
+                    node_id: id.clone(), 
+                    reason: "Missing node_type in definition".to_string() 
+                })?
+                .to_string();
+            
+            // Comment nodes only organize the editor, they take no part in execution
+            if node_type == NODE_TYPE_ANNOTATION_COMMENT {
+                continue;
+            }
+            
+            node_map.insert(id.clone(), RuntimeNode {
+                id,
+                node_type,
+                data: node_json.clone(),
+            });
+        }
+        
+        let mut edge_list = Vec::new();
+        for edge_json in edges {
+            let source = edge_json.get("source")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let source_handle = edge_json.get("sourceHandle")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let target = edge_json.get("target")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let target_handle = edge_json.get("targetHandle")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            
+            if !source.is_empty() && !target.is_empty() {
+                edge_list.push(RuntimeEdge {
+                    source,
+                    source_handle,
+                    target,
+                    target_handle,
+                });
+            }
+        }
+        
+        // Only keep the state of Hysteresis nodes that still exist, so removed nodes are forgotten
+        let hysteresis_state = inputs.hysteresis_state.iter()
+            .filter(|(node_id, _)| node_map.get(*node_id).is_some_and(|n| n.node_type == NODE_TYPE_LOGIC_HYSTERESIS))
+            .map(|(node_id, is_on)| (node_id.clone(), *is_on))
+            .collect();
+        let cooldown_state = inputs.cooldown_state.iter()
+            .filter(|(node_id, _)| node_map.get(*node_id).is_some_and(|n| n.node_type == NODE_TYPE_LOGIC_COOLDOWN))
+            .map(|(node_id, passed_at)| (node_id.clone(), *passed_at))
+            .collect();
+        
+        Ok(Self {
+            nodes: node_map,
+            edges: edge_list,
+            output_cache: HashMap::new(),
+            evaluating: std::collections::HashSet::new(),
+            inputs,
+            reset_active_command_triggered: false,
+            notifications: Vec::new(),
+            float_tolerance: DEFAULT_FLOAT_TOLERANCE,
+            hysteresis_state,
+            cooldown_state,
+            now: 0,
+            trace: None,
+            traced_outputs: std::collections::HashSet::new(),
+        })
+    }
+
+    /// Override the tolerance used for float equality (Equals and Evaluate Number nodes)
+    pub fn with_float_tolerance(mut self, tolerance: f64) -> Self {
+        self.float_tolerance = tolerance;
+        self
+    }
+    
+    /// Latched state of every Hysteresis node after execution, by node id
+    /// Pass it back as `ExecutionInputs::hysteresis_state` on the next execution
+    pub fn hysteresis_state(&self) -> &HashMap<String, bool> {
+        &self.hysteresis_state
+    }
+    
+    /// Set the Unix time of the execution, which Cooldown nodes measure their cooldown against
+    pub fn with_now(mut self, now: i64) -> Self {
+        self.now = now;
+        self
+    }
+    
+    /// Record every node visited, output value read and branch taken, see `trace`
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+    
+    /// Steps of the last execution in the order they happened, empty unless enabled with `with_trace`
+    pub fn trace(&self) -> &[TraceStep] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+    
+    /// Add a step to the trace if tracing is enabled
+    fn record(&mut self, step: impl FnOnce() -> TraceStep) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(step());
+        }
+    }
+    
+    /// Add the value of an output to the trace the first time it is read
+    fn record_value(&mut self, node_id: &str, output_id: &str, value: &RuntimeValue) {
+        if self.trace.is_some() && self.traced_outputs.insert((node_id.to_string(), output_id.to_string())) {
+            self.record(|| TraceStep::Value {
+                node_id: node_id.to_string(),
+                output_id: output_id.to_string(),
+                value: value.to_json(),
+            });
+        }
+    }
+    
+    /// Unix time every Cooldown node last let execution through, by node id
+    /// Pass it back as `ExecutionInputs::cooldown_state` on the next execution
+    pub fn cooldown_state(&self) -> &HashMap<String, i64> {
+        &self.cooldown_state
+    }
+    
+    /// Execute the nodeset and return the result
+    /// 
+    /// The execution follows the execution flow pins from Start node:
+    /// 1. Start node's exec_out connects to an If, Sequence, or terminal node
+    /// 2. If nodes evaluate their condition and follow either True or False exec path
+    /// 3. Sequence nodes try each output in order until a terminal is reached
+    /// 4. When Execute Action or Do Nothing is reached, execution completes
+    pub fn execute(&mut self) -> ExecutionResult {
+        // Find the Start node
+        let start_nodes: Vec<_> = self.nodes.values()
+            .filter(|n| n.node_type == NODE_TYPE_START)
+            .collect();
+        
+        if start_nodes.is_empty() {
+            return ExecutionResult {
+                completed: false,
+                terminal_type: None,
+                action: None,
+                do_nothing: None,
+                error: Some(ExecutionError::MissingStartNode.to_string()),
+                warnings: vec![],
+                reset_active_command: false,
+                notifications: vec![],
+            };
+        }
+        
+        if start_nodes.len() > 1 {
+            return ExecutionResult {
+                completed: false,
+                terminal_type: None,
+                action: None,
+                do_nothing: None,
+                error: Some(ExecutionError::MultipleStartNodes.to_string()),
+                warnings: vec![],
+                reset_active_command: false,
+                notifications: vec![],
+            };
+        }
+        
+        let start_node_id = start_nodes[0].id.clone();
+        
+        // Find all terminal nodes - collect IDs and types to avoid borrow issues
+        let terminal_nodes: Vec<(String, String)> = self.nodes.values()
+            .filter(|n| n.node_type == NODE_TYPE_EXECUTE_ACTION || n.node_type == NODE_TYPE_DO_NOTHING || n.node_type == NODE_TYPE_TURN_OFF)
+            .map(|n| (n.id.clone(), n.node_type.clone()))
+            .collect();
+        
+        if terminal_nodes.is_empty() {
+            return ExecutionResult {
+                completed: false,
+                terminal_type: None,
+                action: None,
+                do_nothing: None,
+                error: Some(ExecutionError::MissingTerminalNode.to_string()),
+                warnings: vec![],
+                reset_active_command: false,
+                notifications: vec![],
+            };
+        }
+        
+        // Populate the Start node outputs first
+        if let Err(e) = self.populate_start_node_outputs(&start_node_id) {
+            return ExecutionResult {
+                completed: false,
+                terminal_type: None,
+                action: None,
+                do_nothing: None,
+                error: Some(e.to_string()),
+                warnings: vec![],
+                reset_active_command: false,
+                notifications: vec![],
+            };
+        }
+        
+        // Follow execution flow from Start node's exec_out pin
+        self.record(|| TraceStep::Visit { node_id: start_node_id.clone() });
+        match self.follow_execution_flow(&start_node_id, "exec_out") {
+            Ok(mut result) => {
+                // Propagate the reset_active_command flag and notifications from the executor
+                result.reset_active_command = self.reset_active_command_triggered;
+                result.notifications = std::mem::take(&mut self.notifications);
+                result
+            }
+            Err(e) => ExecutionResult {
+                completed: false,
+                terminal_type: None,
+                action: None,
+                do_nothing: None,
+                error: Some(e.to_string()),
+                warnings: vec![],
+                reset_active_command: self.reset_active_command_triggered,
+                notifications: vec![],
+            },
+        }
+    }
+    
+    /// Follow the execution flow from a node's execution output pin
+    /// Returns the result when a terminal node is reached
+    fn follow_execution_flow(&mut self, source_node_id: &str, exec_output_id: &str) -> Result<ExecutionResult, ExecutionError> {
+        // Find the edge connected to this execution output
+        let edge = self.edges.iter()
+            .find(|e| e.source == source_node_id && e.source_handle == exec_output_id)
+            .cloned();
+        
+        match edge {
+            Some(e) => {
+                // Get the target node
+                let target_node = self.nodes.get(&e.target)
+                    .ok_or_else(|| ExecutionError::NodeNotFound(e.target.clone()))?
+                    .clone();
+                self.record(|| TraceStep::Visit { node_id: target_node.id.clone() });
+                
+                // Execute based on target node type
+                match target_node.node_type.as_str() {
+                    NODE_TYPE_EXECUTE_ACTION => {
+                        // Terminal node - evaluate and return action
+                        let action = self.evaluate_execute_action_node(&target_node.id)?;
+                        Ok(ExecutionResult {
+                            completed: true,
+                            terminal_type: Some("Execute Action".to_string()),
+                            action: Some(action),
+                            do_nothing: None,
+                            error: None,
+                            warnings: vec![],
+                            reset_active_command: false,
+                            notifications: vec![],
+                        })
+                    }
+                    NODE_TYPE_DO_NOTHING => {
+                        // Terminal node - evaluate and return do nothing
+                        let do_nothing = self.evaluate_do_nothing_node(&target_node.id)?;
+                        Ok(ExecutionResult {
+                            completed: true,
+                            terminal_type: Some("Do Nothing".to_string()),
+                            action: None,
+                            do_nothing: Some(do_nothing),
+                            error: None,
+                            warnings: vec![],
+                            reset_active_command: false,
+                            notifications: vec![],
+                        })
+                    }
+                    NODE_TYPE_TURN_OFF => {
+                        // Terminal node - alias for Execute Action with fixed "turn off" parameters
+                        let action = self.evaluate_turn_off_node(&target_node.id)?;
+                        Ok(ExecutionResult {
+                            completed: true,
+                            terminal_type: Some("Execute Action".to_string()),
+                            action: Some(action),
+                            do_nothing: None,
+                            error: None,
+                            warnings: vec![],
+                            reset_active_command: false,
+                            notifications: vec![],
+                        })
+                    }
+                    NODE_TYPE_RESET_ACTIVE_COMMAND => {
+                        // Pass-through node - set reset flag and continue to next node
+                        self.reset_active_command_triggered = true;
+                        // Continue execution from this node's exec_out
+                        self.follow_execution_flow(&target_node.id, "exec_out")
+                    }
+                    NODE_TYPE_NOTIFY => {
+                        // Pass-through node - queue the notification and continue to next node
+                        let notification = self.evaluate_notify_node(&target_node)?;
+                        self.notifications.push(notification);
+                        self.follow_execution_flow(&target_node.id, "exec_out")
+                    }
+                    NODE_TYPE_LOGIC_IF => {
+                        // If node - evaluate condition and follow appropriate path
+                        self.execute_if_node(&target_node.id)
+                    }
+                    NODE_TYPE_LOGIC_SEQUENCE => {
+                        // Sequence node - try each output in order
+                        self.execute_sequence_node(&target_node.id)
+                    }
+                    _ => {
+                        Err(ExecutionError::InvalidNode {
+                            node_id: target_node.id.clone(),
+                            reason: format!("Node type '{}' cannot receive execution flow", target_node.node_type),
+                        })
+                    }
+                }
+            }
+            None => {
+                // No execution connection - flow ends without reaching terminal
+                Err(ExecutionError::Other(format!(
+                    "Execution flow from '{}' output '{}' is not connected",
+                    source_node_id, exec_output_id
+                )))
+            }
+        }
+    }
+    
+    /// Execute an If node - evaluate condition and follow appropriate execution path
+    fn execute_if_node(&mut self, node_id: &str) -> Result<ExecutionResult, ExecutionError> {
+        // Evaluate the condition input
+        let condition = self.get_input_value(node_id, "condition")?;
+        let is_true = match condition {
+            RuntimeValue::Boolean(v) => v,
+            _ => return Err(ExecutionError::TypeMismatch {
+                expected: "Boolean".to_string(),
+                got: condition.type_name().to_string(),
+            }),
+        };
+        
+        // Follow the appropriate execution output
+        let output_id = if is_true { "exec_true" } else { "exec_false" };
+        self.record(|| TraceStep::Branch { node_id: node_id.to_string(), output_id: output_id.to_string() });
+        self.follow_execution_flow(node_id, output_id)
+    }
+    
+    /// Sort "then_N" output IDs by their numeric suffix
+    fn sort_then_outputs(outputs: &mut Vec<String>) {
+        outputs.sort_by(|a, b| {
+            let a_num: usize = a.strip_prefix("then_").and_then(|s| s.parse().ok()).unwrap_or(0);
+            let b_num: usize = b.strip_prefix("then_").and_then(|s| s.parse().ok()).unwrap_or(0);
+            a_num.cmp(&b_num)
+        });
+    }
+    
+    /// Get dynamic outputs from a node's data, if any
+    fn get_dynamic_outputs_from_node(node: &RuntimeNode) -> Vec<String> {
+        let mut outputs = Vec::new();
+        if let Some(dynamic_outputs) = node.data.get("data")
+            .and_then(|d| d.get("dynamicOutputs"))
+            .and_then(|o| o.as_array()) 
+        {
+            for output in dynamic_outputs {
+                if let Some(id) = output.get("id").and_then(|i| i.as_str()) {
+                    if id.starts_with("then_") {
+                        outputs.push(id.to_string());
+                    }
+                }
+            }
+        }
+        outputs
+    }
+    
+    /// Execute a Sequence node - try each output in order until one reaches a terminal
+    /// 
+    /// The sequence evaluates each "then_N" output in order (then_0, then_1, etc.).
+    /// Execution stops when any path successfully reaches a terminal node (Execute Action or Do Nothing).
+    /// If a path is not connected or leads to an error, it continues to the next output.
+    fn execute_sequence_node(&mut self, node_id: &str) -> Result<ExecutionResult, ExecutionError> {
+        // Get the node to check for dynamic outputs
+        let node = self.nodes.get(node_id)
+            .ok_or_else(|| ExecutionError::NodeNotFound(node_id.to_string()))?
+            .clone();
+        
+        // Collect all "then_N" outputs from the node's edges
+        let mut then_outputs: Vec<String> = self.edges.iter()
+            .filter(|e| e.source == node_id && e.source_handle.starts_with("then_"))
+            .map(|e| e.source_handle.clone())
+            .collect();
+        
+        // Add any dynamic outputs defined in the node data
+        for output_id in Self::get_dynamic_outputs_from_node(&node) {
+            if !then_outputs.contains(&output_id) {
+                then_outputs.push(output_id);
+            }
+        }
+        
+        // Sort by the numeric suffix to ensure correct order
+        Self::sort_then_outputs(&mut then_outputs);
+        
+        // If no then outputs are found, that's an error
+        if then_outputs.is_empty() {
+            return Err(ExecutionError::Other(format!(
+                "Sequence node '{}' has no connected outputs",
+                node_id
+            )));
+        }
+        
+        // Try each output in order, collecting errors for debugging
+        let mut last_error: Option<ExecutionError> = None;
+        for output_id in &then_outputs {
+            let queued_notifications = self.notifications.len();
+            self.record(|| TraceStep::Branch { node_id: node_id.to_string(), output_id: output_id.clone() });
+            match self.follow_execution_flow(node_id, output_id) {
+                Ok(result) => {
+                    // Path reached a terminal - return the result
+                    return Ok(result);
+                }
+                Err(e) => {
+                    // This path didn't work, record the error and try the next one
+                    // Notifications queued on the abandoned path are not sent
+                    self.notifications.truncate(queued_notifications);
+                    self.record(|| TraceStep::Backtrack {
+                        node_id: node_id.to_string(),
+                        output_id: output_id.clone(),
+                        error: e.to_string(),
+                    });
+                    last_error = Some(e);
+                    continue;
+                }
+            }
+        }
+        
+        // No path reached a terminal - include the last error for debugging
+        let error_info = last_error
+            .map(|e| format!(": last error was '{}'", e))
+            .unwrap_or_default();
+        Err(ExecutionError::Other(format!(
+            "Sequence node '{}': no path reached a terminal node{}",
+            node_id, error_info
+        )))
+    }
+    
+    /// Populate the Start node's output values from the execution inputs
+    fn populate_start_node_outputs(&mut self, start_node_id: &str) -> Result<(), ExecutionError> {
+        self.output_cache.insert(
+            (start_node_id.to_string(), "device".to_string()),
+            RuntimeValue::String(self.inputs.device.clone()),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "device_sensor_temperature".to_string()),
+            RuntimeValue::Float(self.inputs.device_sensor_temperature.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "is_auto_mode".to_string()),
+            RuntimeValue::Boolean(self.inputs.is_auto_mode),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "last_change_minutes".to_string()),
+            RuntimeValue::Integer(self.inputs.last_change_minutes.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "outdoor_temperature".to_string()),
+            RuntimeValue::Float(self.inputs.outdoor_temperature.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "is_user_home".to_string()),
+            RuntimeValue::Boolean(self.inputs.is_user_home),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "people_home_count".to_string()),
+            RuntimeValue::Integer(self.inputs.people_home_count),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "occupancy_probability".to_string()),
+            RuntimeValue::Float(self.occupancy_probability(&self.inputs.device)),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "net_power_watt".to_string()),
+            RuntimeValue::Integer(self.inputs.net_power_watt.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "raw_solar_watt".to_string()),
+            RuntimeValue::Integer(self.inputs.raw_solar_watt.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "avg_next_24h_outdoor_temp".to_string()),
+            RuntimeValue::Float(self.inputs.avg_next_24h_outdoor_temp.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "forecast_min_next_12h_temp".to_string()),
+            RuntimeValue::Float(self.inputs.forecast_min_next_12h_temp.0),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "season".to_string()),
+            RuntimeValue::String(self.inputs.season.as_str().to_string()),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "active_command".to_string()),
+            RuntimeValue::ActiveCommand(self.inputs.active_command.clone()),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "possible_grid_outage".to_string()),
+            RuntimeValue::Boolean(self.inputs.possible_grid_outage),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "dr_event_active".to_string()),
+            RuntimeValue::Boolean(self.inputs.demand_response.event_active),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "dr_level".to_string()),
+            RuntimeValue::Integer(self.inputs.demand_response.level),
+        );
+        self.output_cache.insert(
+            (start_node_id.to_string(), "dr_minutes_until_event".to_string()),
+            RuntimeValue::Integer(self.inputs.demand_response.minutes_until_event),
+        );
+        
+        Ok(())
+    }
+    
+    /// Evaluate the Execute Action node and return the action parameters
+    /// Device is inferred from the execution context (Start node)
+    fn evaluate_execute_action_node(&mut self, node_id: &str) -> Result<ActionResult, ExecutionError> {
+        // Device is inferred from execution context, not from node input
+        let device = self.inputs.device.clone();
+        let temperature = self.get_input_value(node_id, "temperature")?
+            .as_f64()
+            .ok_or_else(|| ExecutionError::TypeMismatch {
+                expected: "Float".to_string(),
+                got: "non-numeric".to_string(),
+            })?;
+        let mode = self.get_input_value(node_id, "mode")?
+            .as_string();
+        let fan_speed = self.get_input_value(node_id, "fan_speed")?
+            .as_string();
+        let is_powerful = self.get_input_value(node_id, "is_powerful")?
+            .as_bool()
+            .ok_or_else(|| ExecutionError::TypeMismatch {
+                expected: "Boolean".to_string(),
+                got: "non-boolean".to_string(),
+            })?;
+        let enable_swing = self.get_input_value(node_id, "enable_swing")?
+            .as_bool()
+            .ok_or_else(|| ExecutionError::TypeMismatch {
+                expected: "Boolean".to_string(),
+                got: "non-boolean".to_string(),
+            })?;
+        let cause_reason = self.get_input_value(node_id, "cause_reason")?
+            .as_string();
+        let vertical_vane = self.evaluate_optional_string(node_id, "vertical_vane")?;
+        let horizontal_vane = self.evaluate_optional_string(node_id, "horizontal_vane")?;
+        let annotation = self.evaluate_annotation(node_id)?;
+        
+        Ok(ActionResult {
+            device,
+            temperature,
+            mode,
+            fan_speed,
+            is_powerful,
+            enable_swing,
+            cause_reason,
+            vertical_vane,
+            horizontal_vane,
+            annotation,
+        })
+    }
+    
+    /// Evaluate the Do Nothing node and return the device and cause_reason for debugging
+    /// Device is inferred from the execution context (Start node)
+    /// Execution flow is handled by the flow engine, so we just need to get cause_reason
+    fn evaluate_do_nothing_node(&mut self, node_id: &str) -> Result<DoNothingResult, ExecutionError> {
+        // Device is inferred from execution context, not from node input
+        let device = self.inputs.device.clone();
+        let cause_reason = self.get_input_value(node_id, "cause_reason")?
+            .as_string();
+        
+        Ok(DoNothingResult {
+            device,
+            cause_reason,
+        })
+    }
+    
+    /// Evaluate a Notify node and fill in the placeholders of its title and message
+    fn evaluate_notify_node(&mut self, node: &RuntimeNode) -> Result<NotificationResult, ExecutionError> {
+        let data = node.data.get("data");
+        let text_field = |key: &str| {
+            data.and_then(|d| d.get(key))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        
+        let channel = text_field("notifyChannel");
+        if channel.trim().is_empty() {
+            return Err(ExecutionError::InvalidNode {
+                node_id: node.id.clone(),
+                reason: "Notify node has no channel".to_string(),
+            });
+        }
+        let cooldown_minutes = data
+            .and_then(|d| d.get("notifyCooldownMinutes"))
+            .and_then(|v| v.as_i64())
+            .unwrap_or(DEFAULT_NOTIFY_COOLDOWN_MINUTES)
+            .max(0);
+        
+        // The value input is optional
+        let has_value_input = self.edges.iter().any(|e| e.target == node.id && e.target_handle == "value");
+        let value = if has_value_input {
+            self.get_input_value(&node.id, "value")?.as_string()
+        } else {
+            String::new()
+        };
+        
+        Ok(NotificationResult {
+            node_id: node.id.clone(),
+            channel,
+            title: self.render_notify_template(&text_field("notifyTitle"), &value),
+            message: self.render_notify_template(&text_field("notifyMessage"), &value),
+            cooldown_minutes,
+        })
+    }
+    
+    /// Replace the Notify node placeholders with values from the execution inputs
+    fn render_notify_template(&self, template: &str, value: &str) -> String {
+        template
+            .replace("{device}", &self.inputs.device)
+            .replace("{indoor_temp}", &format!("{:.1}", self.inputs.device_sensor_temperature.0))
+            .replace("{outdoor_temp}", &format!("{:.1}", self.inputs.outdoor_temperature.0))
+            .replace("{solar_watt}", &self.inputs.raw_solar_watt.0.to_string())
+            .replace("{net_power_watt}", &self.inputs.net_power_watt.0.to_string())
+            .replace("{value}", value)
+    }
+    
+    /// Evaluate the Turn Off node and return action parameters
+    /// This is an alias for Execute Action with fixed "turn off" parameters:
+    /// - Temperature: 21
+    /// - Mode: Off
+    /// - Fan Speed: Auto
+    /// - Is Powerful: false
+    /// - Enable Swing: false
+    /// Device is inferred from the execution context (Start node)
+    fn evaluate_turn_off_node(&mut self, node_id: &str) -> Result<ActionResult, ExecutionError> {
+        // Device is inferred from execution context, not from node input
+        let device = self.inputs.device.clone();
+        let cause_reason = self.get_input_value(node_id, "cause_reason")?
+            .as_string();
+        let annotation = self.evaluate_annotation(node_id)?;
+        
+        Ok(ActionResult {
+            device,
+            temperature: TURN_OFF_DEFAULT_TEMPERATURE,
+            mode: "Off".to_string(),
+            fan_speed: "Auto".to_string(),
+            is_powerful: false,
+            enable_swing: false,
+            cause_reason,
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation,
+        })
+    }
+    
+    /// Get an optional string input, None if it isn't connected
+    fn evaluate_optional_string(&mut self, node_id: &str, input_id: &str) -> Result<Option<String>, ExecutionError> {
+        if !self.edges.iter().any(|e| e.target == node_id && e.target_handle == input_id) {
+            return Ok(None);
+        }
+        Ok(Some(self.get_input_value(node_id, input_id)?.as_string()))
+    }
+    
+    /// Get the optional annotation input of Execute Action or Turn Off
+    /// Returns None if it isn't connected or blank, longer text is cut off at MAX_ANNOTATION_CHARS
+    fn evaluate_annotation(&mut self, node_id: &str) -> Result<Option<String>, ExecutionError> {
+        let Some(annotation) = self.evaluate_optional_string(node_id, "annotation")? else {
+            return Ok(None);
+        };
+        let annotation = annotation.trim();
+        if annotation.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(annotation.chars().take(MAX_ANNOTATION_CHARS).collect()))
+    }
+    
+    /// Get the value for a node's input by finding the connected edge and evaluating the source
+    fn get_input_value(&mut self, node_id: &str, input_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Find the edge that connects to this input
+        let edge = self.edges.iter()
+            .find(|e| e.target == node_id && e.target_handle == input_id)
+            .cloned();
+        
+        match edge {
+            Some(e) => {
+                // Evaluate the source node's output
+                self.evaluate_output(&e.source, &e.source_handle)
+            }
+            None => {
+                Err(ExecutionError::MissingInput {
+                    node_id: node_id.to_string(),
+                    input_id: input_id.to_string(),
+                })
+            }
+        }
+    }
+    
+    /// Evaluate a specific output of a node
+    fn evaluate_output(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Check cache first
+        let cache_key = (node_id.to_string(), output_id.to_string());
+        if let Some(value) = self.output_cache.get(&cache_key).cloned() {
+            self.record_value(node_id, output_id, &value);
+            return Ok(value);
+        }
+        
+        // Check for cycles
+        if self.evaluating.contains(node_id) {
+            return Err(ExecutionError::CycleDetected);
+        }
+        self.evaluating.insert(node_id.to_string());
+        
+        let node = self.nodes.get(node_id)
+            .ok_or_else(|| ExecutionError::NodeNotFound(node_id.to_string()))?
+            .clone();
+        
+        let result = self.evaluate_node_output(&node, output_id);
+        
+        self.evaluating.remove(node_id);
+        
+        // Cache the result if successful
+        if let Ok(ref value) = result {
+            self.output_cache.insert(cache_key, value.clone());
+            self.record_value(node_id, output_id, value);
+        }
+        
+        result
+    }
+    
+    /// Evaluate a node and return the requested output value
+    fn evaluate_node_output(&mut self, node: &RuntimeNode, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        match node.node_type.as_str() {
+            // Source nodes that provide values directly
+            NODE_TYPE_START => {
+                // Start node outputs should already be in cache
+                self.output_cache
+                    .get(&(node.id.clone(), output_id.to_string()))
+                    .cloned()
+                    .ok_or_else(|| ExecutionError::InvalidNode {
+                        node_id: node.id.clone(),
+                        reason: format!("Start node output '{}' not found", output_id),
+                    })
+            }
+            
+            // Primitive nodes
+            NODE_TYPE_PRIMITIVE_FLOAT => {
+                let value = node.data
+                    .get("data")
+                    .and_then(|d| d.get("primitiveValue"))
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(0.0);
+                Ok(RuntimeValue::Float(value))
+            }
+            
+            NODE_TYPE_PRIMITIVE_INTEGER => {
+                let value = node.data
+                    .get("data")
+                    .and_then(|d| d.get("primitiveValue"))
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0);
+                Ok(RuntimeValue::Integer(value))
+            }
+            
+            NODE_TYPE_PRIMITIVE_BOOLEAN => {
+                let value = node.data
+                    .get("data")
+                    .and_then(|d| d.get("primitiveValue"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                Ok(RuntimeValue::Boolean(value))
+            }
+            
+            NODE_TYPE_PRIMITIVE_STRING => {
+                let value = node.data
+                    .get("data")
+                    .and_then(|d| d.get("primitiveValue"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                Ok(RuntimeValue::String(value.to_string()))
+            }
+            
+            // Variable nodes carry the value resolved when the nodeset was loaded
+            NODE_TYPE_VARIABLE => {
+                let data = node.data.get("data");
+                if let Some(value) = data.and_then(|d| d.get(variables::RESOLVED_VALUE_KEY)).and_then(|v| v.as_f64()) {
+                    return Ok(RuntimeValue::Float(value));
+                }
+                let name = data
+                    .and_then(|d| d.get(variables::VARIABLE_NAME_KEY))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                Err(ExecutionError::InvalidNode {
+                    node_id: node.id.clone(),
+                    reason: format!("Variable '{}' is not defined in this profile", name),
+                })
+            }
+            
+            // Enum nodes
+            NODE_TYPE_DEVICE | NODE_TYPE_INTENSITY | NODE_TYPE_CAUSE_REASON | NODE_TYPE_REQUEST_MODE | NODE_TYPE_FAN_SPEED
+            | NODE_TYPE_DATA_SOURCE | NODE_TYPE_SEASON | NODE_TYPE_SWING_MODE | NODE_TYPE_PIR_ZONE | NODE_TYPE_PIR_SENSOR => {
+                let value = node.data
+                    .get("data")
+                    .and_then(|d| d.get("enumValue"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                Ok(RuntimeValue::String(value))
+            }
+            
+            // Logic nodes
+            NODE_TYPE_LOGIC_AND => {
+                self.evaluate_logic_and(&node.id)
+            }
+            
+            NODE_TYPE_LOGIC_OR => {
+                self.evaluate_logic_or(&node.id)
+            }
+            
+            NODE_TYPE_LOGIC_NAND => {
+                let and_result = self.evaluate_logic_and(&node.id)?;
+                match and_result {
+                    RuntimeValue::Boolean(v) => Ok(RuntimeValue::Boolean(!v)),
+                    _ => Err(ExecutionError::TypeMismatch {
+                        expected: "Boolean".to_string(),
+                        got: and_result.type_name().to_string(),
+                    }),
+                }
+            }
+            
+            NODE_TYPE_LOGIC_NOT => {
+                let input = self.get_input_value(&node.id, "input")?;
+                match input {
+                    RuntimeValue::Boolean(v) => Ok(RuntimeValue::Boolean(!v)),
+                    _ => Err(ExecutionError::TypeMismatch {
+                        expected: "Boolean".to_string(),
+                        got: input.type_name().to_string(),
+                    }),
+                }
+            }
+            
+            // If node now only has execution outputs, not data outputs
+            // It's handled by follow_execution_flow instead
+            NODE_TYPE_LOGIC_IF => {
+                Err(ExecutionError::InvalidNode {
+                    node_id: node.id.clone(),
+                    reason: "If node only has execution outputs, not data outputs".to_string(),
+                })
+            }
+            
+            // Sequence node only has execution outputs
+            NODE_TYPE_LOGIC_SEQUENCE => {
+                Err(ExecutionError::InvalidNode {
+                    node_id: node.id.clone(),
+                    reason: "Sequence node only has execution outputs, not data outputs".to_string(),
+                })
+            }
+            
+            NODE_TYPE_LOGIC_EQUALS => {
+                let a = self.get_input_value(&node.id, "input_a")?;
+                let b = self.get_input_value(&node.id, "input_b")?;
+                
+                // Compare values - both must be same type and equal
+                let tolerance = self.float_tolerance;
+                let is_equal = match (&a, &b) {
+                    (RuntimeValue::Float(av), RuntimeValue::Float(bv)) => (av - bv).abs() < tolerance,
+                    (RuntimeValue::Integer(av), RuntimeValue::Integer(bv)) => av == bv,
+                    (RuntimeValue::Boolean(av), RuntimeValue::Boolean(bv)) => av == bv,
+                    (RuntimeValue::String(av), RuntimeValue::String(bv)) => av == bv,
+                    // Allow comparing Integer and Float
+                    (RuntimeValue::Float(av), RuntimeValue::Integer(bv)) => (av - (*bv as f64)).abs() < tolerance,
+                    (RuntimeValue::Integer(av), RuntimeValue::Float(bv)) => ((*av as f64) - bv).abs() < tolerance,
+                    _ => false,
+                };
+                
+                Ok(RuntimeValue::Boolean(is_equal))
+            }
+            
+            NODE_TYPE_LOGIC_EVALUATE_NUMBER => {
+                let a = self.get_input_value(&node.id, "input_a")?;
+                let b = self.get_input_value(&node.id, "input_b")?;
+                
+                let a_num = a.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: a.type_name().to_string(),
+                })?;
+                let b_num = b.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: b.type_name().to_string(),
+                })?;
+                
+                // Get the operator from node data
+                let operator = node.data
+                    .get("data")
+                    .and_then(|d| d.get("operatorValue"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(">");
+                
+                // Values within the tolerance are treated as equal by every operator
+                let is_equal = (a_num - b_num).abs() < self.float_tolerance;
+                let result = match operator {
+                    ">" => a_num > b_num && !is_equal,
+                    ">=" => a_num > b_num || is_equal,
+                    "==" => is_equal,
+                    "<=" => a_num < b_num || is_equal,
+                    "<" => a_num < b_num && !is_equal,
+                    _ => a_num > b_num && !is_equal, // Default to >
+                };
+                
+                Ok(RuntimeValue::Boolean(result))
+            }
+            
+            NODE_TYPE_LOGIC_BRANCH => {
+                let condition = self.get_input_value(&node.id, "condition")?;
+                let is_true = match condition {
+                    RuntimeValue::Boolean(v) => v,
+                    _ => return Err(ExecutionError::TypeMismatch {
+                        expected: "Boolean".to_string(),
+                        got: condition.type_name().to_string(),
+                    }),
+                };
+                
+                // Return the appropriate value based on condition
+                if is_true {
+                    self.get_input_value(&node.id, "true_value")
+                } else {
+                    self.get_input_value(&node.id, "false_value")
+                }
+            }
+            
+            NODE_TYPE_LOGIC_HYSTERESIS => {
+                self.evaluate_hysteresis(&node.id)
+            }
+            
+            NODE_TYPE_LOGIC_COOLDOWN => {
+                self.evaluate_cooldown(&node.id)
+            }
+            
+            NODE_TYPE_PIR_DETECTION => {
+                self.evaluate_pir_detection(&node.id, output_id)
+            }
+            
+            NODE_TYPE_OCCUPANCY => {
+                self.evaluate_occupancy(&node.id, output_id)
+            }
+            
+            NODE_TYPE_CONTACT_SENSOR => {
+                self.evaluate_contact_sensor(&node.id, output_id)
+            }
+            
+            NODE_TYPE_HUMIDITY => {
+                self.evaluate_humidity(&node.id, output_id)
+            }
+            
+            NODE_TYPE_TIME_OF_DAY => {
+                self.evaluate_time_of_day(&node.id, output_id)
+            }
+            
+            NODE_TYPE_DAY_OF_WEEK => {
+                self.evaluate_day_of_week(&node.id, output_id)
+            }
+            
+            NODE_TYPE_ENERGY_PRICE => {
+                self.evaluate_energy_price(&node.id, output_id)
+            }
+            
+            NODE_TYPE_BATTERY => {
+                self.evaluate_battery(&node.id, output_id)
+            }
+            
+            NODE_TYPE_POWER_BUDGET => {
+                self.evaluate_power_budget(&node.id, output_id)
+            }
+            
+            NODE_TYPE_DATA_AGE => {
+                self.evaluate_data_age(&node.id, output_id)
+            }
+            
+            NODE_TYPE_WEATHER_FORECAST => {
+                self.evaluate_weather_forecast(&node.id, output_id)
+            }
+            
+            NODE_TYPE_SOLAR_FORECAST => {
+                self.evaluate_solar_forecast(&node.id, output_id)
+            }
+            
+            NODE_TYPE_ACTIVE_COMMAND => {
+                self.evaluate_active_command(&node.id, output_id)
+            }
+            
+            NODE_TYPE_MATH_ADD => {
+                self.evaluate_math_add(&node.id)
+            }
+            
+            NODE_TYPE_MATH_SUBTRACT => {
+                self.evaluate_math_subtract(&node.id)
+            }
+            
+            NODE_TYPE_MATH_MULTIPLY => {
+                self.evaluate_math_multiply(&node.id)
+            }
+            
+            NODE_TYPE_MATH_DIVIDE => {
+                self.evaluate_math_divide(&node.id)
+            }
+            
+            NODE_TYPE_MATH_MIN => {
+                self.evaluate_math_min_max(&node.id, false)
+            }
+            
+            NODE_TYPE_MATH_MAX => {
+                self.evaluate_math_min_max(&node.id, true)
+            }
+            
+            NODE_TYPE_MATH_CLAMP => {
+                self.evaluate_math_clamp(&node.id)
+            }
+            
+            NODE_TYPE_MATH_AVERAGE => {
+                self.evaluate_math_average(&node.id)
+            }
+            
+            NODE_TYPE_TEXT_CONCAT => {
+                self.evaluate_text_concat(&node.id)
+            }
+            
+            NODE_TYPE_TEXT_FORMAT_NUMBER => {
+                self.evaluate_text_format_number(&node.id)
+            }
+            
+            // Calls are expanded before execution, so these are only reached in a profile that
+            // is run on its own or with calls that weren't expanded
+            NODE_TYPE_CALL_NODESET | NODE_TYPE_NODESET_INPUT => Err(ExecutionError::InvalidNode {
+                node_id: node.id.clone(),
+                reason: "Profile calls are only available in saved profiles run by the controller or simulator".to_string(),
+            }),
+            
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node.id.clone(),
+                reason: format!("Unknown node type: {}", node.node_type),
+            }),
+        }
+    }
+    
+    /// Evaluate AND node with dynamic inputs
+    fn evaluate_logic_and(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Get all inputs connected to this node
+        let connected_edges: Vec<_> = self.edges.iter()
+            .filter(|e| e.target == node_id)
+            .cloned()
+            .collect();
+        
+        if connected_edges.is_empty() {
+            return Err(ExecutionError::MissingInput {
+                node_id: node_id.to_string(),
+                input_id: "input_1".to_string(),
+            });
+        }
+        
+        // All inputs must be true
+        for edge in connected_edges {
+            let value = self.evaluate_output(&edge.source, &edge.source_handle)?;
+            match value {
+                RuntimeValue::Boolean(v) => {
+                    if !v {
+                        return Ok(RuntimeValue::Boolean(false));
+                    }
+                }
+                _ => return Err(ExecutionError::TypeMismatch {
+                    expected: "Boolean".to_string(),
+                    got: value.type_name().to_string(),
+                }),
+            }
+        }
+        
+        Ok(RuntimeValue::Boolean(true))
+    }
+    
+    /// Evaluate OR node with dynamic inputs
+    fn evaluate_logic_or(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Get all inputs connected to this node
+        let connected_edges: Vec<_> = self.edges.iter()
+            .filter(|e| e.target == node_id)
+            .cloned()
+            .collect();
+        
+        if connected_edges.is_empty() {
+            return Err(ExecutionError::MissingInput {
+                node_id: node_id.to_string(),
+                input_id: "input_1".to_string(),
+            });
+        }
+        
+        // Any input being true returns true
+        for edge in connected_edges {
+            let value = self.evaluate_output(&edge.source, &edge.source_handle)?;
+            match value {
+                RuntimeValue::Boolean(v) => {
+                    if v {
+                        return Ok(RuntimeValue::Boolean(true));
+                    }
+                }
+                _ => return Err(ExecutionError::TypeMismatch {
+                    expected: "Boolean".to_string(),
+                    got: value.type_name().to_string(),
+                }),
+            }
+        }
+        
+        Ok(RuntimeValue::Boolean(false))
+    }
+    
+    /// Evaluate Hysteresis node
+    /// Latches on at the on threshold and off at the off threshold, keeping the previous state in between.
+    /// An on threshold below the off threshold turns on when the value falls (heating), otherwise when it rises (cooling).
+    fn evaluate_hysteresis(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let mut numeric_input = |input_id: &str| {
+            let value = self.get_input_value(node_id, input_id)?;
+            value.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                expected: "Numeric".to_string(),
+                got: value.type_name().to_string(),
+            })
+        };
+        let value = numeric_input("value")?;
+        let on_threshold = numeric_input("on_threshold")?;
+        let off_threshold = numeric_input("off_threshold")?;
+        
+        let was_on = self.hysteresis_state.get(node_id).copied().unwrap_or(false);
+        let (reached_on, reached_off) = if on_threshold < off_threshold {
+            (value <= on_threshold, value >= off_threshold)
+        } else {
+            (value >= on_threshold, value <= off_threshold)
+        };
+        let is_on = if reached_on {
+            true
+        } else if reached_off {
+            false
+        } else {
+            was_on
+        };
+        
+        self.hysteresis_state.insert(node_id.to_string(), is_on);
+        Ok(RuntimeValue::Boolean(is_on))
+    }
+    
+    /// Evaluate Cooldown node
+    /// Passes a true trigger only once the cooldown has elapsed since the node last passed, and remembers when it did.
+    fn evaluate_cooldown(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let trigger = self.get_input_value(node_id, "trigger")?;
+        let trigger = trigger.as_bool().ok_or_else(|| ExecutionError::TypeMismatch {
+            expected: "Boolean".to_string(),
+            got: trigger.type_name().to_string(),
+        })?;
+        let cooldown_minutes = match self.get_input_value(node_id, "cooldown_minutes")? {
+            RuntimeValue::Integer(v) => v,
+            other => return Err(ExecutionError::TypeMismatch {
+                expected: "Integer".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        };
+        if cooldown_minutes < 0 {
+            return Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Cooldown Minutes can't be negative, got {}", cooldown_minutes),
+            });
+        }
+        
+        // Saturating, so a huge cooldown never lets the trigger through again instead of wrapping
+        let cooled_down = self.cooldown_state
+            .get(node_id)
+            .is_none_or(|passed_at| self.now.saturating_sub(*passed_at) >= cooldown_minutes.saturating_mul(60));
+        let pass = trigger && cooled_down;
+        if pass {
+            self.cooldown_state.insert(node_id.to_string(), self.now);
+        }
+        Ok(RuntimeValue::Boolean(pass))
+    }
+    
+    /// Evaluate PIR Detection node
+    /// A connected zone takes precedence over a connected sensor, which takes precedence over the device
+    fn evaluate_pir_detection(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Find the state of the zone, sensor or device
+        // PIR_NEVER_DETECTED (-1) indicates no detection has ever occurred
+        let state = if let Some(zone) = self.evaluate_optional_string(node_id, "zone")? {
+            self.inputs.pir_zones.get(&zone)
+        } else if let Some(sensor) = self.evaluate_optional_string(node_id, "sensor")? {
+            self.inputs.pir_sensors.get(&sensor)
+        } else {
+            let device = self.get_input_value(node_id, "device")?.as_string();
+            self.inputs.pir_state.get(&device)
+        };
+        let (is_triggered, Minutes(minutes_ago)) = state.copied().unwrap_or((false, Minutes(PIR_NEVER_DETECTED)));
+        let timeout_minutes = self.get_input_value(node_id, "timeout_minutes")?;
+        let timeout = match timeout_minutes {
+            RuntimeValue::Integer(v) => v,
+            _ => return Err(ExecutionError::TypeMismatch {
+                expected: "Integer".to_string(),
+                got: timeout_minutes.type_name().to_string(),
+            }),
+        };
+        
+        match output_id {
+            "is_recently_triggered" => {
+                let is_recent = is_triggered && minutes_ago >= 0 && minutes_ago < timeout;
+                Ok(RuntimeValue::Boolean(is_recent))
+            }
+            "last_detection_minutes_ago" => {
+                Ok(RuntimeValue::Integer(minutes_ago))
+            }
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Occupancy probability of a device's room, 0 if the model has nothing on it
+    fn occupancy_probability(&self, device: &str) -> f64 {
+        self.inputs.occupancy.get(device).copied().unwrap_or(0.0)
+    }
+    
+    /// Evaluate Occupancy node
+    /// Checks the evaluated device unless a device is connected
+    fn evaluate_occupancy(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let device = match self.evaluate_optional_string(node_id, "device")? {
+            Some(device) => device,
+            None => self.inputs.device.clone(),
+        };
+        let probability = self.occupancy_probability(&device);
+        
+        match output_id {
+            "probability" => Ok(RuntimeValue::Float(probability)),
+            "is_occupied" => {
+                let threshold = self.get_input_value(node_id, "threshold")?
+                    .as_f64()
+                    .ok_or_else(|| ExecutionError::TypeMismatch {
+                        expected: "Float".to_string(),
+                        got: "non-numeric".to_string(),
+                    })?;
+                Ok(RuntimeValue::Boolean(probability >= threshold))
+            }
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Contact Sensor node
+    /// Checks the evaluated device unless a device is connected
+    fn evaluate_contact_sensor(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let device = match self.evaluate_optional_string(node_id, "device")? {
+            Some(device) => device,
+            None => self.inputs.device.clone(),
+        };
+        let open_minutes = self.inputs.open_windows.get(&device).copied();
+        let is_open = if device == self.inputs.device {
+            self.inputs.is_window_open
+        } else {
+            open_minutes.is_some()
+        };
+        
+        match output_id {
+            "is_open" => Ok(RuntimeValue::Boolean(is_open)),
+            "open_minutes" => {
+                let Minutes(minutes) = open_minutes.filter(|_| is_open).unwrap_or(Minutes(0));
+                Ok(RuntimeValue::Integer(minutes))
+            }
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Humidity node
+    /// Unavailable readings output HUMIDITY_UNAVAILABLE (-1) with the matching availability flag false
+    fn evaluate_humidity(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        match output_id {
+            "device_humidity" => Ok(RuntimeValue::Float(
+                self.inputs.device_humidity.unwrap_or(HUMIDITY_UNAVAILABLE),
+            )),
+            "is_device_humidity_available" => Ok(RuntimeValue::Boolean(self.inputs.device_humidity.is_some())),
+            "outdoor_humidity" => Ok(RuntimeValue::Float(
+                self.inputs.outdoor_humidity.unwrap_or(HUMIDITY_UNAVAILABLE),
+            )),
+            "is_outdoor_humidity_available" => Ok(RuntimeValue::Boolean(self.inputs.outdoor_humidity.is_some())),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Time of Day node
+    fn evaluate_time_of_day(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let time = &self.inputs.local_time;
+        match output_id {
+            "hour" => Ok(RuntimeValue::Integer(time.hour)),
+            "minute" => Ok(RuntimeValue::Integer(time.minute)),
+            "minutes_since_midnight" => Ok(RuntimeValue::Integer(time.minutes_since_midnight())),
+            "is_night" => Ok(RuntimeValue::Boolean(time.is_night())),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Day of Week node
+    fn evaluate_day_of_week(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let time = &self.inputs.local_time;
+        match output_id {
+            "weekday" => Ok(RuntimeValue::Integer(time.weekday)),
+            "is_weekend" => Ok(RuntimeValue::Boolean(time.is_weekend())),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Energy Price node
+    /// The current hour is cheap if it is among the `cheap_hours` cheapest of the next 24 hours
+    fn evaluate_energy_price(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let prices = &self.inputs.energy_price;
+        match output_id {
+            "current_price" => Ok(RuntimeValue::Float(prices.current_price_eur_kwh.unwrap_or(0.0))),
+            "is_price_available" => Ok(RuntimeValue::Boolean(prices.current_price_eur_kwh.is_some())),
+            "cheap_hour_rank" => Ok(RuntimeValue::Integer(
+                prices.current_hour_rank().unwrap_or(PRICE_RANK_UNAVAILABLE),
+            )),
+            "is_cheap_hour" => {
+                let rank = prices.current_hour_rank();
+                let cheap_hours = self.get_input_value(node_id, "cheap_hours")?;
+                let cheap_hours = match cheap_hours {
+                    RuntimeValue::Integer(v) => v,
+                    _ => return Err(ExecutionError::TypeMismatch {
+                        expected: "Integer".to_string(),
+                        got: cheap_hours.type_name().to_string(),
+                    }),
+                };
+                Ok(RuntimeValue::Boolean(rank.is_some_and(|rank| rank <= cheap_hours)))
+            }
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Battery node
+    /// An unavailable battery outputs BATTERY_SOC_UNAVAILABLE (-1), 0 W and is never discharging
+    fn evaluate_battery(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let power = self.inputs.battery_power_watt.map_or(0, |Watts(w)| w);
+        match output_id {
+            "soc_percent" => Ok(RuntimeValue::Float(
+                self.inputs.battery_soc_percent.unwrap_or(BATTERY_SOC_UNAVAILABLE),
+            )),
+            "power_watt" => Ok(RuntimeValue::Integer(power)),
+            "is_discharging" => Ok(RuntimeValue::Boolean(power > 0)),
+            "is_battery_available" => Ok(RuntimeValue::Boolean(
+                self.inputs.battery_soc_percent.is_some() && self.inputs.battery_power_watt.is_some(),
+            )),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Power Budget node
+    /// Without a configured budget the device can always turn on and use powerful mode
+    fn evaluate_power_budget(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let budget = &self.inputs.power_budget;
+        match output_id {
+            "is_budget_enabled" => Ok(RuntimeValue::Boolean(budget.is_enabled)),
+            "budget_watt" => Ok(RuntimeValue::Integer(budget.budget_watt)),
+            "other_devices_watt" => Ok(RuntimeValue::Integer(budget.other_devices_watt)),
+            "remaining_watt" => Ok(RuntimeValue::Integer(budget.remaining_watt())),
+            "can_turn_on" => Ok(RuntimeValue::Boolean(budget.fits(budget.device_watt))),
+            "can_use_powerful" => Ok(RuntimeValue::Boolean(budget.fits(budget.device_powerful_watt))),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Weather Forecast node
+    /// Temperatures the forecast doesn't cover fall back to the current outdoor temperature,
+    /// solar radiation to 0
+    fn evaluate_weather_forecast(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let current = self.inputs.outdoor_temperature.0;
+        match output_id {
+            "min_next_12h" => Ok(RuntimeValue::Float(self.inputs.weather_forecast.min_next(12).unwrap_or(current))),
+            "max_next_12h" => Ok(RuntimeValue::Float(self.inputs.weather_forecast.max_next(12).unwrap_or(current))),
+            "is_forecast_available" => Ok(RuntimeValue::Boolean(
+                !self.inputs.weather_forecast.hourly_temperatures.is_empty(),
+            )),
+            "temp_in_n_hours" | "solar_in_n_hours" => {
+                let hours = self.get_input_value(node_id, "hours")?;
+                let hours = match hours {
+                    RuntimeValue::Integer(v) => v,
+                    _ => return Err(ExecutionError::TypeMismatch {
+                        expected: "Integer".to_string(),
+                        got: hours.type_name().to_string(),
+                    }),
+                };
+                let forecast = &self.inputs.weather_forecast;
+                if output_id == "temp_in_n_hours" {
+                    Ok(RuntimeValue::Float(forecast.temperature_in(hours).unwrap_or(current)))
+                } else {
+                    Ok(RuntimeValue::Float(forecast.solar_radiation_in(hours).unwrap_or(0.0)))
+                }
+            }
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Solar Forecast node
+    /// Without a forecast the expected production is 0 and never ramping up
+    fn evaluate_solar_forecast(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let expected_wh = self.inputs.expected_solar_next_3h_wh.unwrap_or(0.0);
+        match output_id {
+            "expected_next_3h_wh" => Ok(RuntimeValue::Float(expected_wh)),
+            // Average power of the next 3 hours compared to what the panels produce right now
+            "is_ramping_up" => Ok(RuntimeValue::Boolean(
+                self.inputs.expected_solar_next_3h_wh.is_some() && expected_wh / 3.0 > self.inputs.raw_solar_watt.0 as f64,
+            )),
+            "is_forecast_available" => Ok(RuntimeValue::Boolean(self.inputs.expected_solar_next_3h_wh.is_some())),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Data Age node
+    /// Unavailable data outputs DATA_AGE_UNAVAILABLE (-1) and is never fresh
+    fn evaluate_data_age(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let source = self.get_input_value(node_id, "source")?.as_string();
+        let age = self.inputs.data_ages.get(&source).ok_or_else(|| ExecutionError::InvalidNode {
+            node_id: node_id.to_string(),
+            reason: format!("Unknown data source: {}", source),
+        })?;
+
+        match output_id {
+            "age_minutes" => Ok(RuntimeValue::Integer(age.map_or(DATA_AGE_UNAVAILABLE, |Minutes(m)| m))),
+            "is_fresh" => {
+                let max_age = self.get_input_value(node_id, "max_age_minutes")?;
+                let max_age = match max_age {
+                    RuntimeValue::Integer(v) => v,
+                    _ => return Err(ExecutionError::TypeMismatch {
+                        expected: "Integer".to_string(),
+                        got: max_age.type_name().to_string(),
+                    }),
+                };
+                Ok(RuntimeValue::Boolean(age.is_some_and(|Minutes(m)| m <= max_age)))
+            }
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Active Command node
+    /// Extracts properties from the active command input
+    fn evaluate_active_command(&mut self, node_id: &str, output_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Get the active_command input
+        let active_command_input = self.get_input_value(node_id, "active_command")?;
+        let active_command = match active_command_input {
+            RuntimeValue::ActiveCommand(data) => data,
+            _ => return Err(ExecutionError::TypeMismatch {
+                expected: "ActiveCommand".to_string(),
+                got: active_command_input.type_name().to_string(),
+            }),
+        };
+        
+        match output_id {
+            "is_defined" => Ok(RuntimeValue::Boolean(active_command.is_defined)),
+            "is_on" => Ok(RuntimeValue::Boolean(active_command.is_on)),
+            "temperature" => Ok(RuntimeValue::Float(active_command.temperature)),
+            "target_temperature" => Ok(RuntimeValue::Float(active_command.target_temperature)),
+            "mode" => {
+                // Convert mode integer to string
+                let mode_str = if !active_command.is_on {
+                    "Off"
+                } else {
+                    ac_mode_name(active_command.mode).unwrap_or_else(|| {
+                        log::warn!("Unknown AC mode value {} in active command, defaulting to 'Off'", active_command.mode);
+                        "Off"
+                    })
+                };
+                Ok(RuntimeValue::String(mode_str.to_string()))
+            }
+            "fan_speed" => Ok(RuntimeValue::Integer(active_command.fan_speed as i64)),
+            "swing" => Ok(RuntimeValue::Integer(active_command.swing as i64)),
+            "is_powerful" => Ok(RuntimeValue::Boolean(active_command.is_powerful)),
+            _ => Err(ExecutionError::InvalidNode {
+                node_id: node_id.to_string(),
+                reason: format!("Unknown output: {}", output_id),
+            }),
+        }
+    }
+    
+    /// Evaluate Add node - adds two numeric values
+    /// If both inputs are integers, returns an integer. Otherwise returns a float.
+    fn evaluate_math_add(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let a = self.get_input_value(node_id, "input_a")?;
+        let b = self.get_input_value(node_id, "input_b")?;
+        
+        match (&a, &b) {
+            (RuntimeValue::Integer(av), RuntimeValue::Integer(bv)) => {
+                Ok(RuntimeValue::Integer(av + bv))
+            }
+            _ => {
+                let a_num = a.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: a.type_name().to_string(),
+                })?;
+                let b_num = b.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: b.type_name().to_string(),
+                })?;
+                Ok(RuntimeValue::Float(a_num + b_num))
+            }
+        }
+    }
+    
+    /// Evaluate Subtract node - subtracts second value from first
+    /// If both inputs are integers, returns an integer. Otherwise returns a float.
+    fn evaluate_math_subtract(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let a = self.get_input_value(node_id, "input_a")?;
+        let b = self.get_input_value(node_id, "input_b")?;
+        
+        match (&a, &b) {
+            (RuntimeValue::Integer(av), RuntimeValue::Integer(bv)) => {
+                Ok(RuntimeValue::Integer(av - bv))
+            }
+            _ => {
+                let a_num = a.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: a.type_name().to_string(),
+                })?;
+                let b_num = b.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: b.type_name().to_string(),
+                })?;
+                Ok(RuntimeValue::Float(a_num - b_num))
+            }
+        }
+    }
+    
+    /// Evaluate Multiply node - multiplies two float values
+    fn evaluate_math_multiply(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let a = self.get_input_value(node_id, "input_a")?;
+        let b = self.get_input_value(node_id, "input_b")?;
+        
+        let a_num = a.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+            expected: "Float".to_string(),
+            got: a.type_name().to_string(),
+        })?;
+        let b_num = b.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+            expected: "Float".to_string(),
+            got: b.type_name().to_string(),
+        })?;
+        
+        Ok(RuntimeValue::Float(a_num * b_num))
+    }
+    
+    /// Evaluate Divide node - divides first value by second
+    /// Returns 0.0 if dividing by zero to avoid panics.
+    fn evaluate_math_divide(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let a = self.get_input_value(node_id, "input_a")?;
+        let b = self.get_input_value(node_id, "input_b")?;
+        
+        let a_num = a.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+            expected: "Float".to_string(),
+            got: a.type_name().to_string(),
+        })?;
+        let b_num = b.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+            expected: "Float".to_string(),
+            got: b.type_name().to_string(),
+        })?;
+        
+        // Handle division by zero by returning 0.0
+        if b_num.abs() < DEFAULT_FLOAT_TOLERANCE {
+            Ok(RuntimeValue::Float(0.0))
+        } else {
+            Ok(RuntimeValue::Float(a_num / b_num))
+        }
+    }
+    
+    /// Evaluate Min or Max node - outputs the smaller or larger of two numeric values
+    /// If both inputs are integers, returns an integer. Otherwise returns a float.
+    fn evaluate_math_min_max(&mut self, node_id: &str, is_max: bool) -> Result<RuntimeValue, ExecutionError> {
+        let a = self.get_input_value(node_id, "input_a")?;
+        let b = self.get_input_value(node_id, "input_b")?;
+        
+        match (&a, &b) {
+            (RuntimeValue::Integer(av), RuntimeValue::Integer(bv)) => {
+                Ok(RuntimeValue::Integer(if is_max { *av.max(bv) } else { *av.min(bv) }))
+            }
+            _ => {
+                let a_num = a.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: a.type_name().to_string(),
+                })?;
+                let b_num = b.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: b.type_name().to_string(),
+                })?;
+                Ok(RuntimeValue::Float(if is_max { a_num.max(b_num) } else { a_num.min(b_num) }))
+            }
+        }
+    }
+    
+    /// Evaluate Clamp node - limits a value to the range from min to max
+    /// If all inputs are integers, returns an integer. Otherwise returns a float.
+    /// Max wins when min is above max.
+    fn evaluate_math_clamp(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let value = self.get_input_value(node_id, "value")?;
+        let min = self.get_input_value(node_id, "min")?;
+        let max = self.get_input_value(node_id, "max")?;
+        
+        match (&value, &min, &max) {
+            (RuntimeValue::Integer(v), RuntimeValue::Integer(lo), RuntimeValue::Integer(hi)) => {
+                Ok(RuntimeValue::Integer(*v.max(lo).min(hi)))
+            }
+            _ => {
+                let numeric = |v: &RuntimeValue| v.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                    expected: "Numeric".to_string(),
+                    got: v.type_name().to_string(),
+                });
+                Ok(RuntimeValue::Float(numeric(&value)?.max(numeric(&min)?).min(numeric(&max)?)))
+            }
+        }
+    }
+    
+    /// Evaluate Average node with dynamic inputs
+    fn evaluate_math_average(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        // Get all inputs connected to this node
+        let connected_edges: Vec<_> = self.edges.iter()
+            .filter(|e| e.target == node_id)
+            .cloned()
+            .collect();
+        
+        if connected_edges.is_empty() {
+            return Err(ExecutionError::MissingInput {
+                node_id: node_id.to_string(),
+                input_id: "input_1".to_string(),
+            });
+        }
+        
+        let mut sum = 0.0;
+        for edge in &connected_edges {
+            let value = self.evaluate_output(&edge.source, &edge.source_handle)?;
+            sum += value.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+                expected: "Float".to_string(),
+                got: value.type_name().to_string(),
+            })?;
+        }
+        
+        Ok(RuntimeValue::Float(sum / connected_edges.len() as f64))
+    }
+    
+    /// Evaluate Concat node with dynamic inputs, joined in the order of the input pins
+    fn evaluate_text_concat(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let mut connected_edges: Vec<_> = self.edges.iter()
+            .filter(|e| e.target == node_id)
+            .cloned()
+            .collect();
+        
+        if connected_edges.is_empty() {
+            return Err(ExecutionError::MissingInput {
+                node_id: node_id.to_string(),
+                input_id: "input_1".to_string(),
+            });
+        }
+        
+        // Pins are input_1, input_2, ..., sort by number so input_10 follows input_9
+        connected_edges.sort_by_key(|e| {
+            e.target_handle.strip_prefix("input_").and_then(|n| n.parse::<usize>().ok()).unwrap_or(usize::MAX)
+        });
+        
+        let mut text = String::new();
+        for edge in &connected_edges {
+            text.push_str(&self.evaluate_output(&edge.source, &edge.source_handle)?.as_string());
+        }
+        Ok(RuntimeValue::String(text))
+    }
+    
+    /// Evaluate Format Number node - the value rounded to 0 to MAX_FORMAT_DECIMALS decimals
+    fn evaluate_text_format_number(&mut self, node_id: &str) -> Result<RuntimeValue, ExecutionError> {
+        let value = self.get_input_value(node_id, "value")?;
+        let decimals = self.get_input_value(node_id, "decimals")?;
+        
+        let number = value.as_f64().ok_or_else(|| ExecutionError::TypeMismatch {
+            expected: "Numeric".to_string(),
+            got: value.type_name().to_string(),
+        })?;
+        let decimals = match decimals {
+            RuntimeValue::Integer(d) => d.clamp(0, MAX_FORMAT_DECIMALS) as usize,
+            other => return Err(ExecutionError::TypeMismatch {
+                expected: "Integer".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        };
+        Ok(RuntimeValue::String(format!("{:.*}", decimals, number)))
+    }
+}
+
+/// Validate a nodeset configuration and return any errors
+pub fn validate_nodeset_for_execution(
+    nodes: &[serde_json::Value],
+    edges: &[serde_json::Value],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    
+    // Check for Start node
+    let start_nodes: Vec<_> = nodes.iter()
+        .filter(|n| {
+            n.get("data")
+                .and_then(|d| d.get("definition"))
+                .and_then(|def| def.get("node_type"))
+                .and_then(|nt| nt.as_str())
+                == Some(NODE_TYPE_START)
+        })
+        .collect();
+    
+    if start_nodes.is_empty() {
+        errors.push("Missing Start node".to_string());
+    } else if start_nodes.len() > 1 {
+        errors.push(format!("Multiple Start nodes found (expected 1, found {})", start_nodes.len()));
+    }
+    
+    // Check for terminal nodes
+    let terminal_nodes: Vec<_> = nodes.iter()
+        .filter(|n| {
+            let node_type = n.get("data")
+                .and_then(|d| d.get("definition"))
+                .and_then(|def| def.get("node_type"))
+                .and_then(|nt| nt.as_str());
+            matches!(node_type, Some(NODE_TYPE_EXECUTE_ACTION) | Some(NODE_TYPE_DO_NOTHING) | Some(NODE_TYPE_TURN_OFF))
+        })
+        .collect();
+    
+    if terminal_nodes.is_empty() {
+        errors.push("Missing terminal node (Execute Action, Do Nothing, or Turn Off)".to_string());
+    }
+    
+    // Build a map of node IDs
+    let node_ids: std::collections::HashSet<_> = nodes.iter()
+        .filter_map(|n| n.get("id").and_then(|id| id.as_str()))
+        .collect();
+    
+    // Check that all edges reference valid nodes
+    for (i, edge) in edges.iter().enumerate() {
+        let source = edge.get("source").and_then(|v| v.as_str()).unwrap_or("");
+        let target = edge.get("target").and_then(|v| v.as_str()).unwrap_or("");
+        
+        if !source.is_empty() && !node_ids.contains(source) {
+            errors.push(format!("Edge {} references non-existent source node: {}", i, source));
+        }
+        if !target.is_empty() && !node_ids.contains(target) {
+            errors.push(format!("Edge {} references non-existent target node: {}", i, target));
+        }
+    }
+    
+    // Check that if Active Command node exists, its is_defined output must be connected
+    let active_command_nodes: Vec<_> = nodes.iter()
+        .filter(|n| {
+            n.get("data")
+                .and_then(|d| d.get("definition"))
+                .and_then(|def| def.get("node_type"))
+                .and_then(|nt| nt.as_str())
+                == Some(NODE_TYPE_ACTIVE_COMMAND)
+        })
+        .collect();
+    
+    for active_command_node in active_command_nodes {
+        let node_id = active_command_node.get("id").and_then(|id| id.as_str()).unwrap_or("");
+        
+        // Check if is_defined output is connected
+        let is_defined_connected = edges.iter().any(|edge| {
+            let source = edge.get("source").and_then(|v| v.as_str()).unwrap_or("");
+            let source_handle = edge.get("sourceHandle").and_then(|v| v.as_str()).unwrap_or("");
+            source == node_id && source_handle == "is_defined"
+        });
+        
+        if !is_defined_connected {
+            errors.push("Active Command requires Is Defined pin to be handled".to_string());
+        }
+    }
+    
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn create_start_node() -> serde_json::Value {
+        json!({
+            "id": "start-1",
+            "type": "custom",
+            "position": { "x": 0, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_start",
+                    "name": "Start",
+                    "description": "Entry point",
+                    "category": "System",
+                    "inputs": [],
+                    "outputs": [
+                        { "id": "exec_out", "label": "▶" },
+                        { "id": "device", "label": "Device" },
+                        { "id": "device_sensor_temperature", "label": "Device Sensor Temperature" }
+                    ]
+                }
+            }
+        })
+    }
+
+    fn create_execute_action_node() -> serde_json::Value {
+        json!({
+            "id": "execute-1",
+            "type": "custom",
+            "position": { "x": 400, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_execute_action",
+                    "name": "Execute Action",
+                    "description": "Executes AC command",
+                    "category": "System",
+                    "inputs": [
+                        { "id": "exec_in", "label": "▶" },
+                        { "id": "temperature", "label": "Temperature" },
+                        { "id": "mode", "label": "Mode" },
+                        { "id": "fan_speed", "label": "Fan Speed" },
+                        { "id": "is_powerful", "label": "Is Powerful" },
+                        { "id": "enable_swing", "label": "Enable Swing" },
+                        { "id": "cause_reason", "label": "Cause Reason" }
+                    ],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    fn create_do_nothing_node() -> serde_json::Value {
+        json!({
+            "id": "do-nothing-1",
+            "type": "custom",
+            "position": { "x": 400, "y": 100 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_do_nothing",
+                    "name": "Do Nothing",
+                    "description": "Does nothing",
+                    "category": "System",
+                    "inputs": [
+                        { "id": "exec_in", "label": "▶" },
+                        { "id": "cause_reason", "label": "Cause Reason" }
+                    ],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    fn create_if_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "logic_if",
+                    "name": "If",
+                    "description": "Routes execution",
+                    "category": "Logic",
+                    "inputs": [
+                        { "id": "exec_in", "label": "▶" },
+                        { "id": "condition", "label": "Condition" }
+                    ],
+                    "outputs": [
+                        { "id": "exec_true", "label": "True ▶" },
+                        { "id": "exec_false", "label": "False ▶" }
+                    ]
+                }
+            }
+        })
+    }
+
+    fn create_float_node(id: &str, value: f64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 0 },
+            "data": {
+                "primitiveValue": value,
+                "definition": {
+                    "node_type": "primitive_float",
+                    "name": "Float",
+                    "description": "Float value",
+                    "category": "Primitives",
+                    "inputs": [],
+                    "outputs": [{ "id": "value", "label": "Value" }]
+                }
+            }
+        })
+    }
+
+    fn create_boolean_node(id: &str, value: bool) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 100 },
+            "data": {
+                "primitiveValue": value,
+                "definition": {
+                    "node_type": "primitive_boolean",
+                    "name": "Boolean",
+                    "description": "Boolean value",
+                    "category": "Primitives",
+                    "inputs": [],
+                    "outputs": [{ "id": "value", "label": "Value" }]
+                }
+            }
+        })
+    }
+
+    fn create_enum_node(id: &str, node_type: &str, value: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 200 },
+            "data": {
+                "enumValue": value,
+                "definition": {
+                    "node_type": node_type,
+                    "name": "Enum",
+                    "description": "Enum value",
+                    "category": "Enums",
+                    "inputs": [],
+                    "outputs": [{ "id": "value", "label": "Value" }]
+                }
+            }
+        })
+    }
+
+    fn create_edge(source: &str, source_handle: &str, target: &str, target_handle: &str) -> serde_json::Value {
+        json!({
+            "id": format!("e{}-{}", source, target),
+            "source": source,
+            "sourceHandle": source_handle,
+            "target": target,
+            "targetHandle": target_handle
+        })
+    }
+
+    fn create_do_nothing_node_with_id(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 500, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_do_nothing",
+                    "name": "Do Nothing",
+                    "category": "System",
+                    "inputs": [
+                        { "id": "exec_in", "label": "▶" },
+                        { "id": "cause_reason", "label": "Cause Reason" }
+                    ],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_simple_execution() {
+        // Create a simple nodeset: Start -> Execute Action
+        // With execution flow and data connections
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 22.0),
+            create_boolean_node("bool-1", false),
+            create_boolean_node("bool-2", true), // enable_swing
+            create_enum_node("mode-1", "request_mode", "Heat"),
+            create_enum_node("fan-speed-1", "fan_speed", "Auto"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_execute_action_node(),
+        ];
+        
+        let edges = vec![
+            // Execution flow: Start -> Execute Action
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            // Data connections
+            create_edge("float-1", "value", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("bool-1", "value", "execute-1", "is_powerful"),
+            create_edge("bool-2", "value", "execute-1", "enable_swing"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            device_sensor_temperature: Celsius(20.0),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Execute Action".to_string()));
+        assert!(result.action.is_some());
+        
+        let action = result.action.unwrap();
+        assert_eq!(action.device, "LivingRoom");
+        assert!((action.temperature - 22.0).abs() < f64::EPSILON);
+        assert_eq!(action.mode, "Heat");
+        assert_eq!(action.fan_speed, "Auto");
+        assert!(!action.is_powerful);
+        assert!(action.enable_swing);
+    }
+
+    #[test]
+    fn test_missing_start_node() {
+        let nodes = vec![
+            create_execute_action_node(),
+        ];
+        let edges = vec![];
+        
+        let inputs = ExecutionInputs::default();
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(!result.completed);
+        assert!(result.error.is_some());
+        assert!(result.error.unwrap().contains("Start node"));
+    }
+
+    #[test]
+    fn test_missing_terminal_node() {
+        let nodes = vec![
+            create_start_node(),
+        ];
+        let edges = vec![];
+        
+        let inputs = ExecutionInputs::default();
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(!result.completed);
+        assert!(result.error.is_some());
+        assert!(result.error.unwrap().contains("terminal node"));
+    }
+
+    #[test]
+    fn test_missing_execution_flow() {
+        // Execute Action has data connections but no execution flow
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 22.0),
+            create_boolean_node("bool-1", false),
+            create_enum_node("mode-1", "request_mode", "Heat"),
+            create_enum_node("fan-speed-1", "fan_speed", "Auto"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_execute_action_node(),
+        ];
+        let edges = vec![
+            // Data connections but no execution flow
+            create_edge("float-1", "value", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("bool-1", "value", "execute-1", "is_powerful"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs::default();
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(!result.completed);
+        assert!(result.error.is_some());
+        assert!(result.error.unwrap().contains("not connected"));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        // Test with no nodes
+        let errors = validate_nodeset_for_execution(&[], &[]);
+        assert!(!errors.is_empty());
+        assert!(errors.iter().any(|e| e.contains("Start")));
+        assert!(errors.iter().any(|e| e.contains("terminal")));
+    }
+
+    #[test]
+    fn test_comment_nodes_are_skipped() {
+        let nodes = vec![
+            create_start_node(),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            json!({
+                "id": "comment-1",
+                "type": "custom",
+                "position": { "x": -50, "y": -50 },
+                "width": 600,
+                "height": 300,
+                "data": {
+                    "comment": "Night setback",
+                    "definition": {
+                        "node_type": "annotation_comment",
+                        "name": "Comment",
+                        "category": "Annotations",
+                        "inputs": [],
+                        "outputs": []
+                    }
+                }
+            }),
+        ];
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        assert!(validate_nodeset_for_execution(&nodes, &edges).is_empty());
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        let result = executor.execute();
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert!(matches!(
+            executor.evaluate_output("comment-1", "value"),
+            Err(ExecutionError::NodeNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_and_node_evaluation() {
+        let nodes = vec![
+            create_start_node(),
+            create_boolean_node("bool-1", true),
+            create_boolean_node("bool-2", true),
+            json!({
+                "id": "and-1",
+                "type": "custom",
+                "position": { "x": 300, "y": 0 },
+                "data": {
+                    "definition": {
+                        "node_type": "logic_and",
+                        "name": "AND",
+                        "category": "Logic"
+                    }
+                }
+            }),
+            // If node to route execution based on AND result
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            // Data flow: bool-1 AND bool-2 -> if condition
+            create_edge("bool-1", "value", "and-1", "input_1"),
+            create_edge("bool-2", "value", "and-1", "input_2"),
+            create_edge("and-1", "result", "if-1", "condition"),
+            // Execution flow: Start -> If -> Do Nothing (true path)
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            // Data flow for Do Nothing
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        // Verify do_nothing result has the expected values
+        assert!(result.do_nothing.is_some());
+        let do_nothing = result.do_nothing.unwrap();
+        assert_eq!(do_nothing.device, "LivingRoom");
+        assert_eq!(do_nothing.cause_reason, "1");
+    }
+
+    #[test]
+    fn test_branch_node_true_path() {
+        let nodes = vec![
+            create_start_node(),
+            create_boolean_node("condition", true),
+            create_float_node("true-val", 25.0),
+            create_float_node("false-val", 15.0),
+            json!({
+                "id": "branch-1",
+                "type": "custom",
+                "position": { "x": 300, "y": 0 },
+                "data": {
+                    "definition": {
+                        "node_type": "logic_branch",
+                        "name": "Branch",
+                        "category": "Logic"
+                    }
+                }
+            }),
+            create_enum_node("mode-1", "request_mode", "Heat"),
+            create_enum_node("fan-speed-1", "fan_speed", "Medium"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_boolean_node("powerful", false),
+            create_boolean_node("swing", true),
+            create_execute_action_node(),
+        ];
+        
+        // Execution flow + data connections
+        let edges = vec![
+            // Execution flow: Start -> Execute Action
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            // Data flow
+            create_edge("condition", "value", "branch-1", "condition"),
+            create_edge("true-val", "value", "branch-1", "true_value"),
+            create_edge("false-val", "value", "branch-1", "false_value"),
+            create_edge("branch-1", "result", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("powerful", "value", "execute-1", "is_powerful"),
+            create_edge("swing", "value", "execute-1", "enable_swing"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        let action = result.action.unwrap();
+        // Should use true path value (25.0) since condition is true
+        assert!((action.temperature - 25.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_branch_node_false_path() {
+        let nodes = vec![
+            create_start_node(),
+            create_boolean_node("condition", false), // Condition is false
+            create_float_node("true-val", 25.0),
+            create_float_node("false-val", 15.0),
+            json!({
+                "id": "branch-1",
+                "type": "custom",
+                "position": { "x": 300, "y": 0 },
+                "data": {
+                    "definition": {
+                        "node_type": "logic_branch",
+                        "name": "Branch",
+                        "category": "Logic"
+                    }
+                }
+            }),
+            create_enum_node("mode-1", "request_mode", "Cool"),
+            create_enum_node("fan-speed-1", "fan_speed", "High"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_boolean_node("powerful", false),
+            create_boolean_node("swing", false),
+            create_execute_action_node(),
+        ];
+        
+        // Execution flow + data connections
+        let edges = vec![
+            // Execution flow: Start -> Execute Action
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            // Data flow
+            create_edge("condition", "value", "branch-1", "condition"),
+            create_edge("true-val", "value", "branch-1", "true_value"),
+            create_edge("false-val", "value", "branch-1", "false_value"),
+            create_edge("branch-1", "result", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("powerful", "value", "execute-1", "is_powerful"),
+            create_edge("swing", "value", "execute-1", "enable_swing"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "Veranda".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        let action = result.action.unwrap();
+        // Should use false path value (15.0) since condition is false
+        assert!((action.temperature - 15.0).abs() < f64::EPSILON);
+    }
+
+    fn create_active_command_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 300, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_active_command",
+                    "name": "Active Command",
+                    "description": "Gets active command properties",
+                    "category": "System",
+                    "inputs": [
+                        { "id": "active_command", "label": "Active Command" }
+                    ],
+                    "outputs": [
+                        { "id": "is_defined", "label": "Is Defined" },
+                        { "id": "is_on", "label": "Is On" },
+                        { "id": "temperature", "label": "Temperature" },
+                        { "id": "mode", "label": "Mode" },
+                        { "id": "fan_speed", "label": "Fan Speed" },
+                        { "id": "swing", "label": "Swing" },
+                        { "id": "is_powerful", "label": "Is Powerful" }
+                    ]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_active_command_validation_missing_is_defined() {
+        // Active Command node without is_defined connected should fail validation
+        let nodes = vec![
+            create_start_node(),
+            create_active_command_node("active-cmd-1"),
+            create_execute_action_node(),
+        ];
+        
+        // Only connect active_command input, not the is_defined output
+        let edges = vec![
+            create_edge("start-1", "active_command", "active-cmd-1", "active_command"),
+            create_edge("active-cmd-1", "temperature", "execute-1", "temperature"),
+        ];
+        
+        let errors = validate_nodeset_for_execution(&nodes, &edges);
+        
+        assert!(errors.iter().any(|e| e.contains("Active Command requires Is Defined pin to be handled")));
+    }
+
+    #[test]
+    fn test_active_command_validation_with_is_defined() {
+        // Active Command node with is_defined connected should not produce this error
+        // We use an If node to route execution based on is_defined
+        let nodes = vec![
+            create_start_node(),
+            create_active_command_node("active-cmd-1"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        // Connect is_defined to If node condition
+        let edges = vec![
+            create_edge("start-1", "active_command", "active-cmd-1", "active_command"),
+            create_edge("active-cmd-1", "is_defined", "if-1", "condition"), // is_defined is connected (handled)
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let errors = validate_nodeset_for_execution(&nodes, &edges);
+        
+        // Should not contain the Active Command validation error
+        assert!(!errors.iter().any(|e| e.contains("Active Command requires Is Defined pin to be handled")));
+    }
+
+    #[test]
+    fn test_active_command_evaluation_defined() {
+        // Test evaluation of Active Command node when command is defined
+        // We use If node to route execution based on is_defined
+        let nodes = vec![
+            create_start_node(),
+            create_active_command_node("active-cmd-1"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        // Execution flow with If node routing based on is_defined
+        let edges = vec![
+            create_edge("start-1", "active_command", "active-cmd-1", "active_command"),
+            create_edge("active-cmd-1", "is_defined", "if-1", "condition"),
+            // Execution flow: Start -> If -> Do Nothing (true path = is_defined)
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            active_command: ActiveCommandData {
+                is_defined: true,
+                is_on: true,
+                temperature: 22.5,
+                mode: 1, // Heat
+                fan_speed: 2,
+                swing: 1,
+                is_powerful: false,
+                target_temperature: 22.3,
+            },
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+
+        // The target and the rounded command temperature are separate outputs
+        let mut temperature = |output_id| executor.evaluate_active_command("active-cmd-1", output_id).unwrap().as_f64();
+        assert_eq!(temperature("temperature"), Some(22.5));
+        assert_eq!(temperature("target_temperature"), Some(22.3));
+    }
+
+    #[test]
+    fn test_active_command_evaluation_not_defined() {
+        // Test evaluation of Active Command node when command is not defined
+        // When is_defined is false, the If node should take the false path
+        let nodes = vec![
+            create_start_node(),
+            create_active_command_node("active-cmd-1"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        // Execution flow with If node routing based on is_defined
+        let edges = vec![
+            create_edge("start-1", "active_command", "active-cmd-1", "active_command"),
+            create_edge("active-cmd-1", "is_defined", "if-1", "condition"),
+            // Execution flow: Start -> If -> Do Nothing (false path = !is_defined)
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_false", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        // Default ActiveCommandData has is_defined = false
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        // Do Nothing node should be reached via false path since is_defined = false
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+    }
+
+    fn create_reset_active_command_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_reset_active_command",
+                    "name": "Reset Active Command",
+                    "description": "Resets the active command to undefined state",
+                    "category": "System",
+                    "inputs": [
+                        { "id": "exec_in", "label": "▶" }
+                    ],
+                    "outputs": [
+                        { "id": "exec_out", "label": "▶" }
+                    ]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_reset_active_command_node_execution() {
+        // Test that Reset Active Command node passes execution through and sets the flag
+        // Flow: Start -> Reset Active Command -> Do Nothing
+        let nodes = vec![
+            create_start_node(),
+            create_reset_active_command_node("reset-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            // Execution flow: Start -> Reset Active Command -> Do Nothing
+            create_edge("start-1", "exec_out", "reset-1", "exec_in"),
+            create_edge("reset-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            active_command: ActiveCommandData {
+                is_defined: true,
+                is_on: true,
+                temperature: 22.5,
+                mode: 1,
+                fan_speed: 2,
+                swing: 1,
+                is_powerful: false,
+                target_temperature: 22.3,
+            },
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        // The reset_active_command flag should be set
+        assert!(result.reset_active_command, "Reset Active Command flag should be set");
+    }
+
+    #[test]
+    fn test_reset_active_command_flag_not_set_without_node() {
+        // Test that when Reset Active Command node is not used, the flag is false
+        // Flow: Start -> Do Nothing (no reset node)
+        let nodes = vec![
+            create_start_node(),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            // Execution flow: Start -> Do Nothing (no reset node in between)
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        // The reset_active_command flag should NOT be set
+        assert!(!result.reset_active_command, "Reset Active Command flag should NOT be set when node is not used");
+    }
+
+    #[test]
+    fn test_reset_active_command_node_exec_out_not_connected() {
+        // Test that when Reset Active Command node's exec_out is not connected, we get an error
+        // Flow: Start -> Reset Active Command (exec_out not connected)
+        let nodes = vec![
+            create_start_node(),
+            create_reset_active_command_node("reset-1"),
+            create_do_nothing_node_with_id("do-nothing-1"), // Present but not connected
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            // Execution flow: Start -> Reset Active Command (but exec_out not connected)
+            create_edge("start-1", "exec_out", "reset-1", "exec_in"),
+            // Missing: create_edge("reset-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        // Execution should fail because exec_out is not connected
+        assert!(!result.completed);
+        assert!(result.error.is_some());
+        let error_msg = result.error.unwrap();
+        assert!(error_msg.contains("not connected"), "Error should indicate exec_out is not connected, got: {}", error_msg);
+        // The reset flag should still be propagated even in error case since the node was executed
+        assert!(result.reset_active_command, "Reset Active Command flag should be set even when exec_out is not connected");
+    }
+
+    fn create_turn_off_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 400, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "flow_turn_off",
+                    "name": "Turn Off",
+                    "description": "Turns off the AC",
+                    "category": "System",
+                    "inputs": [
+                        { "id": "exec_in", "label": "▶" },
+                        { "id": "cause_reason", "label": "Cause Reason" }
+                    ],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_turn_off_node_execution() {
+        // Test Turn Off node executes with fixed parameters
+        // Flow: Start -> Turn Off
+        let nodes = vec![
+            create_start_node(),
+            create_turn_off_node("turn-off-1"),
+            create_enum_node("cause-1", "cause_reason", "TooHot"),
+        ];
+        
+        let edges = vec![
+            // Execution flow: Start -> Turn Off
+            create_edge("start-1", "exec_out", "turn-off-1", "exec_in"),
+            // Data connection for cause reason
+            create_edge("cause-1", "value", "turn-off-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            device_sensor_temperature: Celsius(28.0),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        // Turn Off should result in an Execute Action with specific terminal type
+        assert_eq!(result.terminal_type, Some("Execute Action".to_string()));
+        assert!(result.action.is_some());
+        assert!(result.do_nothing.is_none());
+        
+        let action = result.action.unwrap();
+        assert_eq!(action.device, "LivingRoom");
+        // Verify the fixed "turn off" parameters
+        assert!((action.temperature - TURN_OFF_DEFAULT_TEMPERATURE).abs() < f64::EPSILON, "Temperature should be TURN_OFF_DEFAULT_TEMPERATURE");
+        assert_eq!(action.mode, "Off", "Mode should be Off");
+        assert_eq!(action.fan_speed, "Auto", "Fan Speed should be Auto");
+        assert!(!action.is_powerful, "Is Powerful should be false");
+        assert_eq!(action.cause_reason, "TooHot");
+    }
+
+    #[test]
+    fn test_turn_off_node_with_if_node() {
+        // Test Turn Off node works correctly when routed through If node
+        // Flow: Start -> If (condition=true) -> Turn Off
+        let nodes = vec![
+            create_start_node(),
+            create_boolean_node("condition", true),
+            create_if_node("if-1"),
+            create_turn_off_node("turn-off-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_enum_node("cause-2", "cause_reason", "2"),
+        ];
+        
+        let edges = vec![
+            // Data: condition -> If
+            create_edge("condition", "value", "if-1", "condition"),
+            // Execution flow: Start -> If
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            // If true -> Turn Off
+            create_edge("if-1", "exec_true", "turn-off-1", "exec_in"),
+            // If false -> Do Nothing (not taken)
+            create_edge("if-1", "exec_false", "do-nothing-1", "exec_in"),
+            // Data connections for cause reasons
+            create_edge("cause-1", "value", "turn-off-1", "cause_reason"),
+            create_edge("cause-2", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "Veranda".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Execute Action".to_string()));
+        assert!(result.action.is_some());
+        
+        let action = result.action.unwrap();
+        assert_eq!(action.device, "Veranda");
+        assert!((action.temperature - TURN_OFF_DEFAULT_TEMPERATURE).abs() < f64::EPSILON);
+        assert_eq!(action.mode, "Off");
+        assert_eq!(action.fan_speed, "Auto");
+        assert!(!action.is_powerful);
+    }
+
+    #[test]
+    fn test_turn_off_node_missing_cause_reason() {
+        // Test that Turn Off node fails when cause_reason is not connected
+        let nodes = vec![
+            create_start_node(),
+            create_turn_off_node("turn-off-1"),
+            // Note: no cause reason node
+        ];
+        
+        let edges = vec![
+            // Execution flow: Start -> Turn Off
+            create_edge("start-1", "exec_out", "turn-off-1", "exec_in"),
+            // Missing: cause_reason connection
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        // Should fail because cause_reason is required
+        assert!(!result.completed);
+        assert!(result.error.is_some());
+        let error_msg = result.error.unwrap();
+        assert!(error_msg.contains("cause_reason"), "Error should mention missing cause_reason input, got: {}", error_msg);
+    }
+
+    fn create_math_node(id: &str, node_type: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": node_type,
+                    "name": node_type,
+                    "category": "Logic"
+                }
+            }
+        })
+    }
+
+    fn create_integer_node(id: &str, value: i64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 0 },
+            "data": {
+                "primitiveValue": value,
+                "definition": {
+                    "node_type": "primitive_integer",
+                    "name": "Integer",
+                    "description": "Integer value",
+                    "category": "Primitives",
+                    "inputs": [],
+                    "outputs": [{ "id": "value", "label": "Value" }]
+                }
+            }
+        })
+    }
+
+    // =========================================================================
+    // Math Node Tests
+    // =========================================================================
+
+    #[test]
+    fn test_add_node_with_integers() {
+        // Test: 5 + 3 = 8 (Integer + Integer = Integer)
+        let nodes = vec![
+            create_start_node(),
+            create_integer_node("int-1", 5),
+            create_integer_node("int-2", 3),
+            create_math_node("add-1", "math_add"),
+            create_float_node("base-temp", 20.0),
+            create_enum_node("mode-1", "request_mode", "Heat"),
+            create_enum_node("fan-speed-1", "fan_speed", "Auto"),
+            create_boolean_node("powerful-1", false),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_execute_action_node(),
+        ];
+        
+        let edges = vec![
+            // Execution flow
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            // Add node inputs
+            create_edge("int-1", "value", "add-1", "input_a"),
+            create_edge("int-2", "value", "add-1", "input_b"),
+            // Use base-temp for temperature (can't use add result directly since it's Integer)
+            create_edge("base-temp", "value", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("powerful-1", "value", "execute-1", "is_powerful"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        // Test the Add node output directly
+        let add_result = executor.evaluate_output("add-1", "result").unwrap();
+        assert_eq!(add_result, RuntimeValue::Integer(8), "5 + 3 should equal 8");
+    }
+
+    #[test]
+    fn test_add_node_with_floats() {
+        // Test: 2.5 + 3.5 = 6.0 (Float + Float = Float)
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 2.5),
+            create_float_node("float-2", 3.5),
+            create_math_node("add-1", "math_add"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "add-1", "input_a"),
+            create_edge("float-2", "value", "add-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let add_result = executor.evaluate_output("add-1", "result").unwrap();
+        match add_result {
+            RuntimeValue::Float(v) => assert!((v - 6.0).abs() < f64::EPSILON, "2.5 + 3.5 should equal 6.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_variable_node_uses_resolved_value() {
+        let variable = |id: &str, name: &str| json!({
+            "id": id,
+            "type": "custom",
+            "data": {
+                "variableName": name,
+                "definition": { "node_type": NODE_TYPE_VARIABLE, "inputs": [], "outputs": [{ "id": "value" }] }
+            }
+        });
+        let mut nodes = vec![
+            create_start_node(),
+            variable("var-1", "comfort_min"),
+            variable("var-2", "undefined_name"),
+            create_float_node("float-1", 0.5),
+            create_math_node("add-1", "math_add"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("var-1", "value", "add-1", "input_a"),
+            create_edge("float-1", "value", "add-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        let variables = variables::Variables::from([("comfort_min".to_string(), 20.0)]);
+        variables::resolve_variables(&mut nodes, &variables);
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        
+        assert_eq!(executor.evaluate_output("add-1", "result").unwrap(), RuntimeValue::Float(20.5));
+        let error = executor.evaluate_output("var-2", "value").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid node 'var-2': Variable 'undefined_name' is not defined in this profile");
+    }
+
+    #[test]
+    fn test_add_node_with_mixed_types() {
+        // Test: 5 (int) + 2.5 (float) = 7.5 (Float)
+        let nodes = vec![
+            create_start_node(),
+            create_integer_node("int-1", 5),
+            create_float_node("float-1", 2.5),
+            create_math_node("add-1", "math_add"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("int-1", "value", "add-1", "input_a"),
+            create_edge("float-1", "value", "add-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let add_result = executor.evaluate_output("add-1", "result").unwrap();
+        match add_result {
+            RuntimeValue::Float(v) => assert!((v - 7.5).abs() < f64::EPSILON, "5 + 2.5 should equal 7.5"),
+            _ => panic!("Expected Float result for mixed type addition"),
+        }
+    }
+
+    #[test]
+    fn test_subtract_node_with_integers() {
+        // Test: 10 - 3 = 7 (Integer - Integer = Integer)
+        let nodes = vec![
+            create_start_node(),
+            create_integer_node("int-1", 10),
+            create_integer_node("int-2", 3),
+            create_math_node("sub-1", "math_subtract"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("int-1", "value", "sub-1", "input_a"),
+            create_edge("int-2", "value", "sub-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let sub_result = executor.evaluate_output("sub-1", "result").unwrap();
+        assert_eq!(sub_result, RuntimeValue::Integer(7), "10 - 3 should equal 7");
+    }
+
+    #[test]
+    fn test_subtract_node_with_floats() {
+        // Test: 10.5 - 3.5 = 7.0
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 10.5),
+            create_float_node("float-2", 3.5),
+            create_math_node("sub-1", "math_subtract"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "sub-1", "input_a"),
+            create_edge("float-2", "value", "sub-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let sub_result = executor.evaluate_output("sub-1", "result").unwrap();
+        match sub_result {
+            RuntimeValue::Float(v) => assert!((v - 7.0).abs() < f64::EPSILON, "10.5 - 3.5 should equal 7.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_subtract_node_negative_result() {
+        // Test: 3 - 10 = -7 (Integer)
+        let nodes = vec![
+            create_start_node(),
+            create_integer_node("int-1", 3),
+            create_integer_node("int-2", 10),
+            create_math_node("sub-1", "math_subtract"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("int-1", "value", "sub-1", "input_a"),
+            create_edge("int-2", "value", "sub-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let sub_result = executor.evaluate_output("sub-1", "result").unwrap();
+        assert_eq!(sub_result, RuntimeValue::Integer(-7), "3 - 10 should equal -7");
+    }
+
+    #[test]
+    fn test_multiply_node() {
+        // Test: 4.0 * 2.5 = 10.0
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 4.0),
+            create_float_node("float-2", 2.5),
+            create_math_node("mul-1", "math_multiply"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "mul-1", "input_a"),
+            create_edge("float-2", "value", "mul-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let mul_result = executor.evaluate_output("mul-1", "result").unwrap();
+        match mul_result {
+            RuntimeValue::Float(v) => assert!((v - 10.0).abs() < f64::EPSILON, "4.0 * 2.5 should equal 10.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_multiply_node_with_zero() {
+        // Test: 5.0 * 0.0 = 0.0
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 5.0),
+            create_float_node("float-2", 0.0),
+            create_math_node("mul-1", "math_multiply"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "mul-1", "input_a"),
+            create_edge("float-2", "value", "mul-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let mul_result = executor.evaluate_output("mul-1", "result").unwrap();
+        match mul_result {
+            RuntimeValue::Float(v) => assert!(v.abs() < f64::EPSILON, "5.0 * 0.0 should equal 0.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_divide_node() {
+        // Test: 10.0 / 2.0 = 5.0
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 10.0),
+            create_float_node("float-2", 2.0),
+            create_math_node("div-1", "math_divide"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "div-1", "input_a"),
+            create_edge("float-2", "value", "div-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let div_result = executor.evaluate_output("div-1", "result").unwrap();
+        match div_result {
+            RuntimeValue::Float(v) => assert!((v - 5.0).abs() < f64::EPSILON, "10.0 / 2.0 should equal 5.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_divide_node_by_zero() {
+        // Test: 10.0 / 0.0 = 0.0 (handled by returning 0 instead of panic)
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 10.0),
+            create_float_node("float-2", 0.0),
+            create_math_node("div-1", "math_divide"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "div-1", "input_a"),
+            create_edge("float-2", "value", "div-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let div_result = executor.evaluate_output("div-1", "result").unwrap();
+        match div_result {
+            RuntimeValue::Float(v) => assert!(v.abs() < f64::EPSILON, "Division by zero should return 0.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_divide_node_fractional_result() {
+        // Test: 7.0 / 2.0 = 3.5
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 7.0),
+            create_float_node("float-2", 2.0),
+            create_math_node("div-1", "math_divide"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("float-1", "value", "div-1", "input_a"),
+            create_edge("float-2", "value", "div-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let div_result = executor.evaluate_output("div-1", "result").unwrap();
+        match div_result {
+            RuntimeValue::Float(v) => assert!((v - 3.5).abs() < f64::EPSILON, "7.0 / 2.0 should equal 3.5"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_chained_math_operations() {
+        // Test: (5 + 3) * 2.0 = 16.0 (chain add and multiply)
+        // However, since multiply only accepts Float, we need to use the result from add node
+        // Add two floats first, then multiply
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-1", 5.0),
+            create_float_node("float-2", 3.0),
+            create_math_node("add-1", "math_add"),
+            create_float_node("float-3", 2.0),
+            create_math_node("mul-1", "math_multiply"),
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            // Add 5 + 3
+            create_edge("float-1", "value", "add-1", "input_a"),
+            create_edge("float-2", "value", "add-1", "input_b"),
+            // Multiply result * 2
+            create_edge("add-1", "result", "mul-1", "input_a"),
+            create_edge("float-3", "value", "mul-1", "input_b"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        
+        let mul_result = executor.evaluate_output("mul-1", "result").unwrap();
+        match mul_result {
+            RuntimeValue::Float(v) => assert!((v - 16.0).abs() < f64::EPSILON, "(5 + 3) * 2 should equal 16.0"),
+            _ => panic!("Expected Float result"),
+        }
+    }
+
+    #[test]
+    fn test_min_max_nodes() {
+        let nodes = vec![
+            create_integer_node("int-1", 5),
+            create_integer_node("int-2", 3),
+            create_float_node("float-1", 2.5),
+            create_math_node("min-1", "math_min"),
+            create_math_node("max-1", "math_max"),
+            create_math_node("min-2", "math_min"),
+        ];
+        let edges = vec![
+            create_edge("int-1", "value", "min-1", "input_a"),
+            create_edge("int-2", "value", "min-1", "input_b"),
+            create_edge("int-1", "value", "max-1", "input_a"),
+            create_edge("int-2", "value", "max-1", "input_b"),
+            create_edge("int-1", "value", "min-2", "input_a"),
+            create_edge("float-1", "value", "min-2", "input_b"),
+        ];
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("min-1", "result").unwrap(), RuntimeValue::Integer(3));
+        assert_eq!(executor.evaluate_output("max-1", "result").unwrap(), RuntimeValue::Integer(5));
+        // Integer and Float gives a Float
+        assert_eq!(executor.evaluate_output("min-2", "result").unwrap(), RuntimeValue::Float(2.5));
+    }
+
+    #[test]
+    fn test_clamp_node_limits_setpoint() {
+        // clamp(outdoor + 2, 19, 23)
+        let cases = [(10.0, 19.0), (19.5, 21.5), (25.0, 23.0)];
+        for (outdoor, expected) in cases {
+            let nodes = vec![
+                create_float_node("outdoor", outdoor),
+                create_float_node("two", 2.0),
+                create_float_node("min", 19.0),
+                create_float_node("max", 23.0),
+                create_math_node("add-1", "math_add"),
+                create_math_node("clamp-1", "math_clamp"),
+            ];
+            let edges = vec![
+                create_edge("outdoor", "value", "add-1", "input_a"),
+                create_edge("two", "value", "add-1", "input_b"),
+                create_edge("add-1", "result", "clamp-1", "value"),
+                create_edge("min", "value", "clamp-1", "min"),
+                create_edge("max", "value", "clamp-1", "max"),
+            ];
+            let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+            assert_eq!(
+                executor.evaluate_output("clamp-1", "result").unwrap(),
+                RuntimeValue::Float(expected),
+                "outdoor {}°C",
+                outdoor
+            );
+        }
+        
+        // Integers stay integers, and Max wins when the bounds cross
+        let nodes = vec![
+            create_integer_node("value", 10),
+            create_integer_node("min", 20),
+            create_integer_node("max", 15),
+            create_math_node("clamp-1", "math_clamp"),
+        ];
+        let edges = vec![
+            create_edge("value", "value", "clamp-1", "value"),
+            create_edge("min", "value", "clamp-1", "min"),
+            create_edge("max", "value", "clamp-1", "max"),
+        ];
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("clamp-1", "result").unwrap(), RuntimeValue::Integer(15));
+    }
+
+    #[test]
+    fn test_average_node_with_dynamic_inputs() {
+        let nodes = vec![
+            create_float_node("float-1", 20.0),
+            create_float_node("float-2", 21.0),
+            create_float_node("float-3", 25.0),
+            create_math_node("avg-1", "math_average"),
+        ];
+        let edges = vec![
+            create_edge("float-1", "value", "avg-1", "input_1"),
+            create_edge("float-2", "value", "avg-1", "input_2"),
+            create_edge("float-3", "value", "avg-1", "input_3"),
+        ];
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("avg-1", "result").unwrap(), RuntimeValue::Float(22.0));
+        
+        let nodes = vec![create_math_node("avg-1", "math_average")];
+        let mut executor = NodesetExecutor::new(&nodes, &[], ExecutionInputs::default()).unwrap();
+        assert!(matches!(
+            executor.evaluate_output("avg-1", "result"),
+            Err(ExecutionError::MissingInput { .. })
+        ));
+    }
+
+    fn create_string_node(id: &str, value: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "primitive_string",
+                    "name": "String",
+                    "category": "Primitives"
+                },
+                "primitiveValue": value
+            }
+        })
+    }
+
+    #[test]
+    fn test_format_number_node() {
+        for (value, decimals, expected) in [
+            (create_float_node("value-1", 21.456), 1, "21.5"),
+            (create_integer_node("value-1", 3200), 0, "3200"),
+            (create_integer_node("value-1", 3200), 2, "3200.00"),
+            (create_float_node("value-1", 0.5), 99, "0.500000"),
+            (create_float_node("value-1", 2.5), -1, "2"),
+        ] {
+            let nodes = vec![
+                value,
+                create_integer_node("decimals-1", decimals),
+                create_math_node("format-1", "text_format_number"),
+            ];
+            let edges = vec![
+                create_edge("value-1", "value", "format-1", "value"),
+                create_edge("decimals-1", "value", "format-1", "decimals"),
+            ];
+            let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+            assert_eq!(executor.evaluate_output("format-1", "text").unwrap(), RuntimeValue::String(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_annotation_flows_into_execute_action() {
+        // "preheat: solar=" + format(3200.4, 0) + "W", with more than 9 Concat pins kept in order
+        let mut nodes = vec![
+            create_start_node(),
+            create_string_node("prefix", "preheat: solar="),
+            create_float_node("solar", 3200.4),
+            create_integer_node("decimals", 0),
+            create_math_node("format-1", "text_format_number"),
+            create_string_node("unit", "W"),
+            create_math_node("concat-1", "text_concat"),
+            create_float_node("temp", 22.0),
+            create_enum_node("mode-1", "request_mode", "Heat"),
+            create_enum_node("fan-speed-1", "fan_speed", "Auto"),
+            create_boolean_node("powerful-1", false),
+            create_boolean_node("swing-1", false),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_execute_action_node(),
+        ];
+        let mut edges = vec![
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            create_edge("solar", "value", "format-1", "value"),
+            create_edge("decimals", "value", "format-1", "decimals"),
+            create_edge("unit", "value", "concat-1", "input_10"),
+            create_edge("format-1", "text", "concat-1", "input_2"),
+            create_edge("prefix", "value", "concat-1", "input_1"),
+            create_edge("concat-1", "text", "execute-1", "annotation"),
+            create_edge("temp", "value", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("powerful-1", "value", "execute-1", "is_powerful"),
+            create_edge("swing-1", "value", "execute-1", "enable_swing"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let result = NodesetExecutor::new(&nodes, &edges, inputs.clone()).unwrap().execute();
+        assert!(result.completed, "{:?}", result.error);
+        let action = result.action.unwrap();
+        assert_eq!(action.annotation.as_deref(), Some("preheat: solar=3200W"));
+        assert_eq!(action.cause_reason, "1");
+        
+        // Without the annotation input connected there is no annotation
+        edges.retain(|e| e["targetHandle"] != "annotation");
+        let result = NodesetExecutor::new(&nodes, &edges, inputs.clone()).unwrap().execute();
+        assert_eq!(result.action.unwrap().annotation, None);
+        
+        // Long annotations are cut off
+        nodes[1] = create_string_node("prefix", &"x".repeat(500));
+        edges.push(create_edge("concat-1", "text", "execute-1", "annotation"));
+        let result = NodesetExecutor::new(&nodes, &edges, inputs).unwrap().execute();
+        assert_eq!(result.action.unwrap().annotation.unwrap().chars().count(), MAX_ANNOTATION_CHARS);
+    }
+
+    #[test]
+    fn test_vanes_flow_into_execute_action() {
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("temp", 24.0),
+            create_enum_node("mode-1", "request_mode", "Cool"),
+            create_enum_node("fan-speed-1", "fan_speed", "Auto"),
+            create_boolean_node("powerful-1", false),
+            create_boolean_node("swing-1", false),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_enum_node("vane-1", "swing_mode", "Position 1"),
+            create_execute_action_node(),
+        ];
+        let mut edges = vec![
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            create_edge("temp", "value", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("powerful-1", "value", "execute-1", "is_powerful"),
+            create_edge("swing-1", "value", "execute-1", "enable_swing"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+            create_edge("vane-1", "value", "execute-1", "vertical_vane"),
+        ];
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+
+        let result = NodesetExecutor::new(&nodes, &edges, inputs.clone()).unwrap().execute();
+        assert!(result.completed, "{:?}", result.error);
+        let action = result.action.unwrap();
+        assert_eq!(action.vertical_vane.as_deref(), Some("Position 1"));
+        assert_eq!(action.horizontal_vane, None, "Unconnected vanes are left to the controller");
+
+        edges.retain(|e| e["targetHandle"] != "vertical_vane");
+        let result = NodesetExecutor::new(&nodes, &edges, inputs).unwrap().execute();
+        assert_eq!(result.action.unwrap().vertical_vane, None);
+    }
+
+    #[test]
+    fn test_math_with_execute_action() {
+        // Test using math result as temperature for Execute Action
+        // Add 20.0 + 2.0 = 22.0 for temperature
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("base-temp", 20.0),
+            create_float_node("offset", 2.0),
+            create_math_node("add-1", "math_add"),
+            create_enum_node("mode-1", "request_mode", "Heat"),
+            create_enum_node("fan-speed-1", "fan_speed", "Auto"),
+            create_boolean_node("powerful-1", false),
+            create_boolean_node("swing-1", true),
+            create_enum_node("cause-1", "cause_reason", "1"),
+            create_execute_action_node(),
+        ];
+        
+        let edges = vec![
+            // Execution flow
+            create_edge("start-1", "exec_out", "execute-1", "exec_in"),
+            // Add for temperature
+            create_edge("base-temp", "value", "add-1", "input_a"),
+            create_edge("offset", "value", "add-1", "input_b"),
+            // Execute Action inputs
+            create_edge("add-1", "result", "execute-1", "temperature"),
+            create_edge("mode-1", "value", "execute-1", "mode"),
+            create_edge("fan-speed-1", "value", "execute-1", "fan_speed"),
+            create_edge("powerful-1", "value", "execute-1", "is_powerful"),
+            create_edge("swing-1", "value", "execute-1", "enable_swing"),
+            create_edge("cause-1", "value", "execute-1", "cause_reason"),
+        ];
+        
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Execute Action".to_string()));
+        assert!(result.action.is_some());
+        
+        let action = result.action.unwrap();
+        assert!((action.temperature - 22.0).abs() < f64::EPSILON, "Temperature should be 20 + 2 = 22");
+    }
+
+    // =========================================================================
+    // Float Tolerance Tests
+    // =========================================================================
+
+    fn create_evaluate_number_node(id: &str, operator: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 200, "y": 0 },
+            "data": {
+                "operatorValue": operator,
+                "definition": {
+                    "node_type": "logic_evaluate_number",
+                    "name": "Evaluate Number",
+                    "category": "Logic"
+                }
+            }
+        })
+    }
+
+    fn create_comparison_nodeset(node: serde_json::Value, a: f64, b: f64) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+        let node_id = node["id"].as_str().unwrap().to_string();
+        let nodes = vec![
+            create_start_node(),
+            create_float_node("float-a", a),
+            create_float_node("float-b", b),
+            node,
+            create_do_nothing_node(),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        let edges = vec![
+            create_edge("start-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+            create_edge("float-a", "value", &node_id, "input_a"),
+            create_edge("float-b", "value", &node_id, "input_b"),
+        ];
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_equals_node_uses_default_tolerance() {
+        let (nodes, edges) = create_comparison_nodeset(create_math_node("eq-1", "logic_equals"), 22.0, 22.00001);
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("eq-1", "result").unwrap(), RuntimeValue::Boolean(true));
+
+        let (nodes, edges) = create_comparison_nodeset(create_math_node("eq-1", "logic_equals"), 22.0, 21.999);
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("eq-1", "result").unwrap(), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_equals_node_with_custom_tolerance() {
+        let (nodes, edges) = create_comparison_nodeset(create_math_node("eq-1", "logic_equals"), 22.0, 21.999);
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default())
+            .unwrap()
+            .with_float_tolerance(0.01);
+        assert_eq!(executor.evaluate_output("eq-1", "result").unwrap(), RuntimeValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_evaluate_number_operators_respect_tolerance() {
+        // 21.999 vs 22.0 with a 0.01 tolerance is considered equal by every operator
+        let cases = [
+            (">", false),
+            (">=", true),
+            ("==", true),
+            ("<=", true),
+            ("<", false),
+        ];
+
+        for (operator, expected) in cases {
+            let (nodes, edges) = create_comparison_nodeset(create_evaluate_number_node("eval-1", operator), 21.999, 22.0);
+            let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default())
+                .unwrap()
+                .with_float_tolerance(0.01);
+            assert_eq!(
+                executor.evaluate_output("eval-1", "result").unwrap(),
+                RuntimeValue::Boolean(expected),
+                "21.999 {} 22.0 should be {} with tolerance 0.01",
+                operator,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_number_operators_outside_tolerance() {
+        let cases = [
+            (">", false),
+            (">=", false),
+            ("==", false),
+            ("<=", true),
+            ("<", true),
+        ];
+
+        for (operator, expected) in cases {
+            let (nodes, edges) = create_comparison_nodeset(create_evaluate_number_node("eval-1", operator), 21.5, 22.0);
+            let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+            assert_eq!(
+                executor.evaluate_output("eval-1", "result").unwrap(),
+                RuntimeValue::Boolean(expected),
+                "21.5 {} 22.0 should be {}",
+                operator,
+                expected
+            );
+        }
+    }
+
+    fn create_hysteresis_nodeset(value: f64, on_threshold: f64, off_threshold: f64) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+        let nodes = vec![
+            json!({
+                "id": "hysteresis-1",
+                "type": "custom",
+                "position": { "x": 200, "y": 0 },
+                "data": {
+                    "definition": {
+                        "node_type": "logic_hysteresis",
+                        "name": "Hysteresis",
+                        "description": "Latches between thresholds",
+                        "category": "Logic",
+                        "inputs": [
+                            { "id": "value", "label": "Value" },
+                            { "id": "on_threshold", "label": "On Threshold" },
+                            { "id": "off_threshold", "label": "Off Threshold" }
+                        ],
+                        "outputs": [{ "id": "is_on", "label": "Is On" }]
+                    }
+                }
+            }),
+            create_float_node("value-1", value),
+            create_float_node("on-1", on_threshold),
+            create_float_node("off-1", off_threshold),
+        ];
+        let edges = vec![
+            create_edge("value-1", "value", "hysteresis-1", "value"),
+            create_edge("on-1", "value", "hysteresis-1", "on_threshold"),
+            create_edge("off-1", "value", "hysteresis-1", "off_threshold"),
+        ];
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_hysteresis_latches_between_executions() {
+        // Heating: on at 19, off at 21, the state carries over between executions
+        let mut state = HashMap::new();
+        let steps = [(20.0, false), (19.0, true), (20.5, true), (21.0, false), (19.5, false)];
+        for (temperature, expected) in steps {
+            let (nodes, edges) = create_hysteresis_nodeset(temperature, 19.0, 21.0);
+            let inputs = ExecutionInputs {
+                hysteresis_state: state,
+                ..Default::default()
+            };
+            let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+            assert_eq!(
+                executor.evaluate_output("hysteresis-1", "is_on").unwrap(),
+                RuntimeValue::Boolean(expected),
+                "heating at {}°C",
+                temperature
+            );
+            state = executor.hysteresis_state().clone();
+        }
+
+        // Cooling: on at 26, off at 24
+        let (nodes, edges) = create_hysteresis_nodeset(25.0, 26.0, 24.0);
+        let inputs = ExecutionInputs {
+            hysteresis_state: [("hysteresis-1".to_string(), true)].into_iter().collect(),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.evaluate_output("hysteresis-1", "is_on").unwrap(), RuntimeValue::Boolean(true));
+
+        let (nodes, edges) = create_hysteresis_nodeset(26.5, 26.0, 24.0);
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("hysteresis-1", "is_on").unwrap(), RuntimeValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_hysteresis_state_of_removed_nodes_is_dropped() {
+        let (nodes, edges) = create_hysteresis_nodeset(20.0, 19.0, 21.0);
+        let inputs = ExecutionInputs {
+            hysteresis_state: [("hysteresis-1".to_string(), true), ("deleted-node".to_string(), true)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.hysteresis_state().len(), 1);
+        assert_eq!(executor.hysteresis_state().get("hysteresis-1"), Some(&true));
+    }
+
+    fn create_cooldown_nodeset(trigger: bool, cooldown_minutes: i64) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+        let nodes = vec![
+            json!({
+                "id": "cooldown-1",
+                "type": "custom",
+                "position": { "x": 200, "y": 0 },
+                "data": {
+                    "definition": {
+                        "node_type": "logic_cooldown",
+                        "name": "Cooldown",
+                        "description": "Lets a trigger through once per cooldown",
+                        "category": "Logic",
+                        "inputs": [
+                            { "id": "trigger", "label": "Trigger" },
+                            { "id": "cooldown_minutes", "label": "Cooldown Minutes" }
+                        ],
+                        "outputs": [{ "id": "pass", "label": "Pass" }]
+                    }
+                }
+            }),
+            create_boolean_node("trigger-1", trigger),
+            create_integer_node("minutes-1", cooldown_minutes),
+        ];
+        let edges = vec![
+            create_edge("trigger-1", "value", "cooldown-1", "trigger"),
+            create_edge("minutes-1", "value", "cooldown-1", "cooldown_minutes"),
+        ];
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_cooldown_passes_once_per_cooldown() {
+        // 30 minute cooldown, evaluated every 10 minutes
+        let mut state = HashMap::new();
+        let steps = [
+            (0, true, true),
+            (600, true, false),
+            (1200, false, false),
+            (1800, true, true),
+            (2400, false, false),
+            (3000, true, false),
+            (3600, true, true),
+        ];
+        for (now, trigger, expected) in steps {
+            let (nodes, edges) = create_cooldown_nodeset(trigger, 30);
+            let inputs = ExecutionInputs {
+                cooldown_state: state,
+                ..Default::default()
+            };
+            let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap().with_now(now);
+            assert_eq!(
+                executor.evaluate_output("cooldown-1", "pass").unwrap(),
+                RuntimeValue::Boolean(expected),
+                "trigger {} at {}s",
+                trigger,
+                now
+            );
+            state = executor.cooldown_state().clone();
+        }
+        assert_eq!(state.get("cooldown-1"), Some(&3600));
+    }
+
+    #[test]
+    fn test_cooldown_with_out_of_range_minutes() {
+        let state: HashMap<String, i64> = [("cooldown-1".to_string(), 0)].into_iter().collect();
+
+        let (nodes, edges) = create_cooldown_nodeset(true, i64::MAX);
+        let inputs = ExecutionInputs { cooldown_state: state.clone(), ..Default::default() };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap().with_now(600);
+        assert_eq!(executor.evaluate_output("cooldown-1", "pass").unwrap(), RuntimeValue::Boolean(false));
+
+        let (nodes, edges) = create_cooldown_nodeset(true, -1);
+        let inputs = ExecutionInputs { cooldown_state: state, ..Default::default() };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap().with_now(600);
+        assert!(matches!(
+            executor.evaluate_output("cooldown-1", "pass"),
+            Err(ExecutionError::InvalidNode { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cooldown_state_of_removed_nodes_is_dropped() {
+        let (nodes, edges) = create_cooldown_nodeset(true, 30);
+        let inputs = ExecutionInputs {
+            cooldown_state: [("cooldown-1".to_string(), 100), ("hysteresis-1".to_string(), 100)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.cooldown_state().len(), 1);
+        assert_eq!(executor.cooldown_state().get("cooldown-1"), Some(&100));
+    }
+
+    #[test]
+    fn test_demand_response_start_outputs() {
+        // Route execution through an If node based on dr_event_active
+        let nodes = vec![
+            create_start_node(),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("start-1", "dr_event_active", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            demand_response: DemandResponseData {
+                event_active: true,
+                level: 2,
+                minutes_until_event: 0,
+            },
+            ..Default::default()
+        };
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+
+        assert!(result.completed);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(executor.evaluate_output("start-1", "dr_level").unwrap(), RuntimeValue::Integer(2));
+        assert_eq!(executor.evaluate_output("start-1", "dr_minutes_until_event").unwrap(), RuntimeValue::Integer(0));
+    }
+
+    #[test]
+    fn test_demand_response_defaults_to_no_event() {
+        let data = DemandResponseData::default();
+        assert!(!data.event_active);
+        assert_eq!(data.level, 0);
+        assert_eq!(data.minutes_until_event, NO_DR_EVENT_SCHEDULED);
+    }
+
+    fn create_pir_detection_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 300 },
+            "data": {
+                "definition": {
+                    "node_type": "pir_detection",
+                    "name": "PIR Detection",
+                    "description": "PIR Detection",
+                    "category": "Sensors",
+                    "inputs": [],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_pir_detection_of_zone_sensor_or_device() {
+        let nodes = vec![
+            create_start_node(),
+            create_pir_detection_node("pir-1"),
+            create_integer_node("timeout-1", 10),
+            create_enum_node("device-1", "device", "LivingRoom"),
+            create_enum_node("zone-1", "pir_zone", "Downstairs"),
+            create_enum_node("sensor-1", "pir_sensor", "hall"),
+        ];
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            pir_state: [("LivingRoom".to_string(), (false, Minutes(PIR_NEVER_DETECTED)))].into_iter().collect(),
+            pir_sensors: [("hall".to_string(), (true, Minutes(12)))].into_iter().collect(),
+            pir_zones: [("Downstairs".to_string(), (true, Minutes(4)))].into_iter().collect(),
+            ..Default::default()
+        };
+        let pir = |extra_edges: &[(&str, &str)]| {
+            let mut edges = vec![
+                create_edge("timeout-1", "value", "pir-1", "timeout_minutes"),
+                create_edge("device-1", "value", "pir-1", "device"),
+            ];
+            edges.extend(extra_edges.iter().map(|(source, target)| create_edge(source, "value", "pir-1", target)));
+            let mut executor = NodesetExecutor::new(&nodes, &edges, inputs.clone()).unwrap();
+            (
+                executor.evaluate_output("pir-1", "is_recently_triggered").unwrap(),
+                executor.evaluate_output("pir-1", "last_detection_minutes_ago").unwrap(),
+            )
+        };
+
+        assert_eq!(pir(&[]), (RuntimeValue::Boolean(false), RuntimeValue::Integer(PIR_NEVER_DETECTED)));
+        // Detected 12 minutes ago, outside the 10 minute timeout
+        assert_eq!(pir(&[("sensor-1", "sensor")]), (RuntimeValue::Boolean(false), RuntimeValue::Integer(12)));
+        // The zone takes precedence over the sensor
+        assert_eq!(
+            pir(&[("sensor-1", "sensor"), ("zone-1", "zone")]),
+            (RuntimeValue::Boolean(true), RuntimeValue::Integer(4))
+        );
+    }
+
+    #[test]
+    fn test_occupancy_of_evaluated_or_connected_device() {
+        let nodes = vec![
+            create_start_node(),
+            json!({
+                "id": "occupancy-1",
+                "type": "custom",
+                "position": { "x": 100, "y": 300 },
+                "data": {
+                    "definition": {
+                        "node_type": "occupancy",
+                        "name": "Occupancy",
+                        "description": "Occupancy",
+                        "category": "Sensors",
+                        "inputs": [],
+                        "outputs": []
+                    }
+                }
+            }),
+            create_float_node("threshold-1", 0.5),
+            create_enum_node("device-1", "device", "Veranda"),
+        ];
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            occupancy: [("LivingRoom".to_string(), 0.7), ("Veranda".to_string(), 0.2)].into_iter().collect(),
+            ..Default::default()
+        };
+        let mut edges = vec![create_edge("threshold-1", "value", "occupancy-1", "threshold")];
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs.clone()).unwrap();
+        assert_eq!(executor.evaluate_output("occupancy-1", "probability").unwrap(), RuntimeValue::Float(0.7));
+        assert_eq!(executor.evaluate_output("occupancy-1", "is_occupied").unwrap(), RuntimeValue::Boolean(true));
+
+        edges.push(create_edge("device-1", "value", "occupancy-1", "device"));
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.evaluate_output("occupancy-1", "probability").unwrap(), RuntimeValue::Float(0.2));
+        assert_eq!(executor.evaluate_output("occupancy-1", "is_occupied").unwrap(), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_contact_sensor_of_evaluated_or_connected_device() {
+        let nodes = vec![
+            create_start_node(),
+            json!({
+                "id": "contact-1",
+                "type": "custom",
+                "position": { "x": 100, "y": 300 },
+                "data": {
+                    "definition": {
+                        "node_type": "contact_sensor",
+                        "name": "Contact Sensor",
+                        "description": "Contact Sensor",
+                        "category": "Sensors",
+                        "inputs": [],
+                        "outputs": []
+                    }
+                }
+            }),
+            create_enum_node("device-1", "device", "LivingRoom"),
+        ];
+        let inputs = ExecutionInputs {
+            device: "Veranda".to_string(),
+            is_window_open: true,
+            open_windows: [("Veranda".to_string(), Minutes(7))].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let mut executor = NodesetExecutor::new(&nodes, &[], inputs.clone()).unwrap();
+        assert_eq!(executor.evaluate_output("contact-1", "is_open").unwrap(), RuntimeValue::Boolean(true));
+        assert_eq!(executor.evaluate_output("contact-1", "open_minutes").unwrap(), RuntimeValue::Integer(7));
+
+        let edges = vec![create_edge("device-1", "value", "contact-1", "device")];
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.evaluate_output("contact-1", "is_open").unwrap(), RuntimeValue::Boolean(false));
+        assert_eq!(executor.evaluate_output("contact-1", "open_minutes").unwrap(), RuntimeValue::Integer(0));
+    }
+
+    fn create_humidity_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 300 },
+            "data": {
+                "definition": {
+                    "node_type": "humidity",
+                    "name": "Humidity",
+                    "description": "Humidity",
+                    "category": "Sensors",
+                    "inputs": [],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_humidity_node_outputs() {
+        // Dehumidify-style check: act only while indoor humidity is above 60%
+        let nodes = vec![
+            create_start_node(),
+            create_humidity_node("humidity-1"),
+            create_float_node("threshold-1", 60.0),
+            create_evaluate_number_node("compare-1", ">"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("humidity-1", "device_humidity", "compare-1", "input_a"),
+            create_edge("threshold-1", "value", "compare-1", "input_b"),
+            create_edge("compare-1", "result", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            device_humidity: Some(72.0),
+            ..Default::default()
+        };
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+
+        assert!(result.completed, "error: {:?}", result.error);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(
+            executor.evaluate_output("humidity-1", "is_device_humidity_available").unwrap(),
+            RuntimeValue::Boolean(true)
+        );
+        assert_eq!(
+            executor.evaluate_output("humidity-1", "outdoor_humidity").unwrap(),
+            RuntimeValue::Float(HUMIDITY_UNAVAILABLE)
+        );
+        assert_eq!(
+            executor.evaluate_output("humidity-1", "is_outdoor_humidity_available").unwrap(),
+            RuntimeValue::Boolean(false)
+        );
+    }
+
+    fn create_battery_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 300 },
+            "data": {
+                "definition": {
+                    "node_type": "battery",
+                    "name": "Battery",
+                    "description": "Battery",
+                    "category": "Sensors",
+                    "inputs": [],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_battery_node_outputs() {
+        // Don't heat from the battery: do nothing while it is discharging
+        let nodes = vec![
+            create_start_node(),
+            create_battery_node("battery-1"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("battery-1", "is_discharging", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            battery_soc_percent: Some(64.5),
+            battery_power_watt: Some(Watts(850)),
+            ..Default::default()
+        };
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+
+        assert!(result.completed, "error: {:?}", result.error);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(
+            executor.evaluate_output("battery-1", "soc_percent").unwrap(),
+            RuntimeValue::Float(64.5)
+        );
+
+        // No battery configured
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(
+            executor.evaluate_output("battery-1", "soc_percent").unwrap(),
+            RuntimeValue::Float(BATTERY_SOC_UNAVAILABLE)
+        );
+        assert_eq!(
+            executor.evaluate_output("battery-1", "is_discharging").unwrap(),
+            RuntimeValue::Boolean(false)
+        );
+        assert_eq!(
+            executor.evaluate_output("battery-1", "is_battery_available").unwrap(),
+            RuntimeValue::Boolean(false)
+        );
+    }
+
+    fn create_power_budget_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 300 },
+            "data": {
+                "definition": {
+                    "node_type": "power_budget",
+                    "name": "Power Budget",
+                    "description": "Power Budget",
+                    "category": "Sensors",
+                    "inputs": [],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_power_budget_node_outputs() {
+        // Do nothing when the other devices leave no room to turn on
+        let nodes = vec![
+            create_start_node(),
+            create_power_budget_node("budget-1"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("budget-1", "can_turn_on", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_false", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            power_budget: PowerBudgetData {
+                is_enabled: true,
+                budget_watt: 2500,
+                other_devices_watt: 1600,
+                device_watt: 1000,
+                device_powerful_watt: 1600,
+            },
+            ..Default::default()
+        };
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+
+        assert!(result.completed, "error: {:?}", result.error);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(
+            executor.evaluate_output("budget-1", "remaining_watt").unwrap(),
+            RuntimeValue::Integer(900)
+        );
+        assert_eq!(
+            executor.evaluate_output("budget-1", "can_use_powerful").unwrap(),
+            RuntimeValue::Boolean(false)
+        );
+
+        // No budget configured
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(
+            executor.evaluate_output("budget-1", "is_budget_enabled").unwrap(),
+            RuntimeValue::Boolean(false)
+        );
+        assert_eq!(
+            executor.evaluate_output("budget-1", "can_use_powerful").unwrap(),
+            RuntimeValue::Boolean(true)
+        );
+    }
+
+    fn create_notify_node(id: &str, channel: &str, message: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 0 },
+            "data": {
+                "notifyChannel": channel,
+                "notifyTitle": "{device}",
+                "notifyMessage": message,
+                "notifyCooldownMinutes": 30,
+                "definition": {
+                    "node_type": "flow_notify",
+                    "name": "Notify",
+                    "category": "System"
+                }
+            }
+        })
+    }
+
+    fn create_sequence_node(id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 0 },
+            "data": {
+                "definition": {
+                    "node_type": "logic_sequence",
+                    "name": "Sequence",
+                    "category": "Logic"
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_notify_node_passes_through_and_renders_message() {
+        let nodes = vec![
+            create_start_node(),
+            create_notify_node("notify-1", "phone", "Solar collapsed to {value} W, indoor {indoor_temp}°C"),
+            create_float_node("solar-1", 120.0),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("start-1", "exec_out", "notify-1", "exec_in"),
+            create_edge("solar-1", "value", "notify-1", "value"),
+            create_edge("notify-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "Veranda".to_string(),
+            device_sensor_temperature: Celsius(19.34),
+            ..Default::default()
+        };
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+
+        assert!(result.completed, "error: {:?}", result.error);
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(result.notifications.len(), 1);
+        let notification = &result.notifications[0];
+        assert_eq!(notification.node_id, "notify-1");
+        assert_eq!(notification.channel, "phone");
+        assert_eq!(notification.title, "Veranda");
+        assert_eq!(notification.message, "Solar collapsed to 120 W, indoor 19.3°C");
+        assert_eq!(notification.cooldown_minutes, 30);
+    }
+
+    #[test]
+    fn test_notify_node_on_abandoned_sequence_path_is_dropped() {
+        // then_0 notifies but never reaches a terminal, then_1 does
+        let nodes = vec![
+            create_start_node(),
+            create_sequence_node("seq-1"),
+            create_notify_node("notify-1", "phone", "unreachable"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("start-1", "exec_out", "seq-1", "exec_in"),
+            create_edge("seq-1", "then_0", "notify-1", "exec_in"),
+            create_edge("seq-1", "then_1", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        let result = executor.execute();
+
+        assert!(result.completed, "error: {:?}", result.error);
+        assert!(result.notifications.is_empty());
+    }
+
+    #[test]
+    fn test_trace_records_path_values_and_branches() {
+        // then_0 leads to the unconnected False branch of an If, then_1 to Do Nothing
+        let nodes = vec![
+            create_start_node(),
+            create_sequence_node("seq-1"),
+            create_if_node("if-1"),
+            create_boolean_node("bool-1", false),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("start-1", "exec_out", "seq-1", "exec_in"),
+            create_edge("seq-1", "then_0", "if-1", "exec_in"),
+            create_edge("bool-1", "value", "if-1", "condition"),
+            create_edge("seq-1", "then_1", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        assert!(executor.execute().completed);
+        assert!(executor.trace().is_empty(), "only traced when enabled");
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap().with_trace();
+        let result = executor.execute();
+        assert!(result.completed, "error: {:?}", result.error);
+
+        let visit = |node_id: &str| TraceStep::Visit { node_id: node_id.to_string() };
+        let branch = |node_id: &str, output_id: &str| TraceStep::Branch {
+            node_id: node_id.to_string(),
+            output_id: output_id.to_string(),
+        };
+        let value = |node_id: &str, value: serde_json::Value| TraceStep::Value {
+            node_id: node_id.to_string(),
+            output_id: "value".to_string(),
+            value,
+        };
+        assert_eq!(
+            executor.trace(),
+            [
+                visit("start-1"),
+                visit("seq-1"),
+                branch("seq-1", "then_0"),
+                visit("if-1"),
+                value("bool-1", json!(false)),
+                branch("if-1", "exec_false"),
+                TraceStep::Backtrack {
+                    node_id: "seq-1".to_string(),
+                    output_id: "then_0".to_string(),
+                    error: "Execution flow from 'if-1' output 'exec_false' is not connected".to_string(),
+                },
+                branch("seq-1", "then_1"),
+                visit("do-nothing-1"),
+                value("cause-1", json!("1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_notify_node_requires_channel() {
+        let nodes = vec![
+            create_start_node(),
+            create_notify_node("notify-1", " ", "message"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+
+        let edges = vec![
+            create_edge("start-1", "exec_out", "notify-1", "exec_in"),
+            create_edge("notify-1", "exec_out", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let mut executor = NodesetExecutor::new(&nodes, &edges, ExecutionInputs::default()).unwrap();
+        let result = executor.execute();
+
+        assert!(!result.completed);
+        assert!(result.error.unwrap().contains("no channel"));
+    }
+
+    fn create_sensor_node(id: &str, node_type: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "type": "custom",
+            "position": { "x": 100, "y": 300 },
+            "data": {
+                "definition": {
+                    "node_type": node_type,
+                    "name": node_type,
+                    "description": node_type,
+                    "category": "Sensors",
+                    "inputs": [],
+                    "outputs": []
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_time_nodes_follow_local_time_input() {
+        // Do nothing at night, otherwise fall through without reaching a terminal node
+        let nodes = vec![
+            create_start_node(),
+            create_sensor_node("time-1", "time_of_day"),
+            create_sensor_node("day-1", "day_of_week"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        let edges = vec![
+            create_edge("time-1", "is_night", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let night = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            local_time: LocalTimeData { hour: 23, minute: 15, weekday: 6 },
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, night).unwrap();
+        let result = executor.execute();
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(executor.evaluate_output("time-1", "hour").unwrap(), RuntimeValue::Integer(23));
+        assert_eq!(
+            executor.evaluate_output("time-1", "minutes_since_midnight").unwrap(),
+            RuntimeValue::Integer(23 * 60 + 15)
+        );
+        assert_eq!(executor.evaluate_output("day-1", "weekday").unwrap(), RuntimeValue::Integer(6));
+        assert_eq!(executor.evaluate_output("day-1", "is_weekend").unwrap(), RuntimeValue::Boolean(true));
+
+        let day = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            local_time: LocalTimeData { hour: 7, minute: 0, weekday: 5 },
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, day).unwrap();
+        executor.execute();
+        assert_eq!(executor.evaluate_output("time-1", "is_night").unwrap(), RuntimeValue::Boolean(false));
+        assert_eq!(executor.evaluate_output("day-1", "is_weekend").unwrap(), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_energy_price_node_cheap_hours() {
+        let nodes = vec![
+            create_start_node(),
+            create_sensor_node("price-1", "energy_price"),
+            create_integer_node("hours-1", 3),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        let edges = vec![
+            create_edge("hours-1", "value", "price-1", "cheap_hours"),
+            create_edge("price-1", "is_cheap_hour", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        // Current hour is the third cheapest
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            energy_price: EnergyPriceData {
+                current_price_eur_kwh: Some(-0.01),
+                cheapest_hours_next_24h: vec![4, 5, 0, 1, 2, 3],
+            },
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(executor.evaluate_output("price-1", "cheap_hour_rank").unwrap(), RuntimeValue::Integer(3));
+        assert_eq!(executor.evaluate_output("price-1", "current_price").unwrap(), RuntimeValue::Float(-0.01));
+
+        // Without prices the current hour is never cheap
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        executor.execute();
+        assert_eq!(executor.evaluate_output("price-1", "is_cheap_hour").unwrap(), RuntimeValue::Boolean(false));
+        assert_eq!(
+            executor.evaluate_output("price-1", "cheap_hour_rank").unwrap(),
+            RuntimeValue::Integer(PRICE_RANK_UNAVAILABLE)
+        );
+        assert_eq!(executor.evaluate_output("price-1", "is_price_available").unwrap(), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_weather_forecast_node() {
+        let nodes = vec![
+            create_start_node(),
+            create_sensor_node("forecast-1", "weather_forecast"),
+            create_integer_node("hours-1", 3),
+        ];
+        let edges = vec![create_edge("hours-1", "value", "forecast-1", "hours")];
+
+        let inputs = ExecutionInputs {
+            outdoor_temperature: Celsius(4.0),
+            weather_forecast: WeatherForecastData {
+                hourly_temperatures: vec![3.0, 1.0, -2.0, -6.0, -4.0, 0.0, 2.0, 5.0, 6.0, 7.0, 6.5, 5.0, 12.0],
+                hourly_solar_radiation: vec![0.0, 0.0, 150.0],
+            },
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.evaluate_output("forecast-1", "min_next_12h").unwrap(), RuntimeValue::Float(-6.0));
+        assert_eq!(executor.evaluate_output("forecast-1", "max_next_12h").unwrap(), RuntimeValue::Float(7.0), "13th hour is excluded");
+        assert_eq!(executor.evaluate_output("forecast-1", "temp_in_n_hours").unwrap(), RuntimeValue::Float(-2.0));
+        assert_eq!(executor.evaluate_output("forecast-1", "solar_in_n_hours").unwrap(), RuntimeValue::Float(150.0));
+        assert_eq!(executor.evaluate_output("forecast-1", "is_forecast_available").unwrap(), RuntimeValue::Boolean(true));
+
+        // Without a forecast the current temperature is used
+        let inputs = ExecutionInputs {
+            outdoor_temperature: Celsius(4.0),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        assert_eq!(executor.evaluate_output("forecast-1", "min_next_12h").unwrap(), RuntimeValue::Float(4.0));
+        assert_eq!(executor.evaluate_output("forecast-1", "temp_in_n_hours").unwrap(), RuntimeValue::Float(4.0));
+        assert_eq!(executor.evaluate_output("forecast-1", "solar_in_n_hours").unwrap(), RuntimeValue::Float(0.0));
+        assert_eq!(executor.evaluate_output("forecast-1", "is_forecast_available").unwrap(), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_solar_forecast_node() {
+        let nodes = vec![create_start_node(), create_sensor_node("solar-1", "solar_forecast")];
+
+        let inputs = ExecutionInputs {
+            raw_solar_watt: Watts(400),
+            expected_solar_next_3h_wh: Some(4500.0),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &[], inputs).unwrap();
+        assert_eq!(executor.evaluate_output("solar-1", "expected_next_3h_wh").unwrap(), RuntimeValue::Float(4500.0));
+        assert_eq!(executor.evaluate_output("solar-1", "is_ramping_up").unwrap(), RuntimeValue::Boolean(true), "1500 W on average");
+        assert_eq!(executor.evaluate_output("solar-1", "is_forecast_available").unwrap(), RuntimeValue::Boolean(true));
+
+        // Production already peaked
+        let inputs = ExecutionInputs {
+            raw_solar_watt: Watts(2000),
+            expected_solar_next_3h_wh: Some(4500.0),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &[], inputs).unwrap();
+        assert_eq!(executor.evaluate_output("solar-1", "is_ramping_up").unwrap(), RuntimeValue::Boolean(false));
+
+        // No panels configured
+        let mut executor = NodesetExecutor::new(&nodes, &[], ExecutionInputs::default()).unwrap();
+        assert_eq!(executor.evaluate_output("solar-1", "expected_next_3h_wh").unwrap(), RuntimeValue::Float(0.0));
+        assert_eq!(executor.evaluate_output("solar-1", "is_ramping_up").unwrap(), RuntimeValue::Boolean(false));
+        assert_eq!(executor.evaluate_output("solar-1", "is_forecast_available").unwrap(), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_data_age_node() {
+        let nodes = vec![
+            create_start_node(),
+            create_sensor_node("age-1", "data_age"),
+            create_enum_node("source-1", "data_source", "Weather"),
+            create_integer_node("max-1", 30),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        let edges = vec![
+            create_edge("source-1", "value", "age-1", "source"),
+            create_edge("max-1", "value", "age-1", "max_age_minutes"),
+            create_edge("age-1", "is_fresh", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            data_ages: DataAges {
+                weather: Some(Minutes(20)),
+                ..DataAges::fresh()
+            },
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+        assert_eq!(executor.evaluate_output("age-1", "age_minutes").unwrap(), RuntimeValue::Integer(20));
+
+        // Data older than the threshold is not fresh
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            data_ages: DataAges {
+                weather: Some(Minutes(45)),
+                ..DataAges::fresh()
+            },
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        executor.execute();
+        assert_eq!(executor.evaluate_output("age-1", "is_fresh").unwrap(), RuntimeValue::Boolean(false));
+
+        // Unavailable data is never fresh
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        executor.execute();
+        assert_eq!(executor.evaluate_output("age-1", "is_fresh").unwrap(), RuntimeValue::Boolean(false));
+        assert_eq!(
+            executor.evaluate_output("age-1", "age_minutes").unwrap(),
+            RuntimeValue::Integer(DATA_AGE_UNAVAILABLE)
+        );
+    }
+
+    #[test]
+    fn test_season_gates_logic() {
+        let nodes = vec![
+            create_start_node(),
+            create_math_node("eq-1", "logic_equals"),
+            create_enum_node("season-1", "season", "Heating"),
+            create_if_node("if-1"),
+            create_do_nothing_node_with_id("do-nothing-1"),
+            create_enum_node("cause-1", "cause_reason", "1"),
+        ];
+        let edges = vec![
+            create_edge("start-1", "season", "eq-1", "input_a"),
+            create_edge("season-1", "value", "eq-1", "input_b"),
+            create_edge("eq-1", "result", "if-1", "condition"),
+            create_edge("start-1", "exec_out", "if-1", "exec_in"),
+            create_edge("if-1", "exec_true", "do-nothing-1", "exec_in"),
+            create_edge("cause-1", "value", "do-nothing-1", "cause_reason"),
+        ];
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            season: Season::Heating,
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        let result = executor.execute();
+        assert_eq!(result.terminal_type, Some("Do Nothing".to_string()));
+
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            season: Season::Cooling,
+            ..Default::default()
+        };
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs).unwrap();
+        executor.execute();
+        assert_eq!(executor.evaluate_output("eq-1", "result").unwrap(), RuntimeValue::Boolean(false));
+    }
+}
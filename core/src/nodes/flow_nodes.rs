@@ -1,4 +1,6 @@
 use super::node_system::{Node, NodeDefinition, NodeInput, NodeOutput, ValueType};
+use crate::ac_state::vane_names;
+use crate::types::Season;
 
 /// Maximum value for evaluate_every_minutes (24 hours * 60 minutes = 1440)
 pub const MAX_EVALUATE_EVERY_MINUTES: i32 = 1440;
@@ -43,10 +45,8 @@ impl Node for StartNode {
                     "device",
                     "Device",
                     "The AC device being evaluated",
-                    ValueType::Enum(vec![
-                        "LivingRoom".to_string(),
-                        "Veranda".to_string(),
-                    ]),
+                    // Populated with the configured devices by the server
+                    ValueType::Enum(vec![]),
                 ),
                 NodeOutput::new(
                     "device_sensor_temperature",
@@ -75,9 +75,21 @@ impl Node for StartNode {
                 NodeOutput::new(
                     "is_user_home",
                     "Is User Home",
-                    "True if the user is home and awake based on schedule settings",
+                    "True if the user is home and awake based on schedule settings, or if anybody is home when presence detection is configured",
                     ValueType::Boolean,
                 ),
+                NodeOutput::new(
+                    "people_home_count",
+                    "People Home Count",
+                    "Number of people home according to presence detection (1 while the user is home if presence detection isn't configured)",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "occupancy_probability",
+                    "Occupancy Probability",
+                    "Probability (0-1) that the device's room is occupied, inferred from PIR and door sensor events with decay. Steadier than the raw PIR state, see the Occupancy node.",
+                    ValueType::Float,
+                ),
                 NodeOutput::new(
                     "net_power_watt",
                     "Net Power Watt",
@@ -96,19 +108,55 @@ impl Node for StartNode {
                     "Average outdoor temperature in Celsius forecasted for the next 24 hours. This is the absolute average temperature, not a trend or offset.",
                     ValueType::Float,
                 ),
+                NodeOutput::new(
+                    "forecast_min_next_12h_temp",
+                    "Forecast Min Next 12h Temp",
+                    "Lowest outdoor temperature in Celsius forecasted for the next 12 hours. Useful for pre-warming before a cold snap.",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "season",
+                    "Season",
+                    "Heating, Cooling or Neutral, classified from the average outdoor temperature of the last days. Compare with a Season node to gate heat-only or cool-only logic.",
+                    ValueType::Enum(Season::names()),
+                ),
                 NodeOutput::new(
                     "active_command",
                     "Active Command",
                     "The active command struct containing the last command sent to the device",
                     ValueType::Object,
                 ),
+                NodeOutput::new(
+                    "possible_grid_outage",
+                    "Possible Grid Outage",
+                    "True if the grid appears to be down (UPS on battery, or meter data lost while solar production is zero)",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "dr_event_active",
+                    "DR Event Active",
+                    "True while a utility demand-response event is in progress",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "dr_level",
+                    "DR Level",
+                    "Curtailment level of the current or upcoming demand-response event (0 if none)",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "dr_minutes_until_event",
+                    "DR Minutes Until Event",
+                    "Minutes until the next demand-response event starts (0 while active, -1 if none scheduled). Useful for pre-cooling.",
+                    ValueType::Integer,
+                ),
             ],
         )
     }
 }
 
 /// Execute Action Node - End point that executes the command and stores to database
-/// Takes raw AC control values: temperature, mode (Heat/Cool/Off), fan_speed, and isPowerful
+/// Takes raw AC control values: temperature, mode (Heat/Cool/Dry/Fan/Off), fan_speed, and isPowerful
 /// This node represents the final action in the evaluation flow
 /// NOTE: The device is inferred from the evaluation context (Start node) at runtime.
 /// NOTE: The cause_reason input accepts a CauseReason type (connect from a Cause Reason node).
@@ -142,10 +190,12 @@ impl Node for ExecuteActionNode {
                 NodeInput::new(
                     "mode",
                     "Mode",
-                    "AC operating mode: Heat, Cool, or Off",
+                    "AC operating mode: Heat, Cool, Dry, Fan, or Off",
                     ValueType::Enum(vec![
                         "Heat".to_string(),
                         "Cool".to_string(),
+                        "Dry".to_string(),
+                        "Fan".to_string(),
                         "Off".to_string(),
                     ]),
                     true,
@@ -184,6 +234,28 @@ impl Node for ExecuteActionNode {
                     ValueType::CauseReason(vec![]),
                     true,
                 ),
+                NodeInput::new(
+                    "vertical_vane",
+                    "Vertical Vane",
+                    "Optional vertical vane position, connect from a Swing Mode node. Ignored by devices without supports_vertical_vane.",
+                    ValueType::Enum(vane_names()),
+                    false,
+                ),
+                NodeInput::new(
+                    "horizontal_vane",
+                    "Horizontal Vane",
+                    "Optional horizontal vane position, connect from a Swing Mode node. Ignored by devices without supports_horizontal_vane.",
+                    ValueType::Enum(vane_names()),
+                    false,
+                ),
+
+                NodeInput::new(
+                    "annotation",
+                    "Annotation",
+                    "Optional text recorded with the action, e.g. \"preheat: solar=3200W\". Shown in the execution history and the dashboard.",
+                    ValueType::String,
+                    false,
+                ),
             ],
             vec![], // No outputs - this is a terminal node
         )
@@ -234,10 +306,12 @@ impl Node for ActiveCommandNode {
                 NodeOutput::new(
                     "mode",
                     "Mode",
-                    "AC operating mode from the last command: Heat, Cool, or Off",
+                    "AC operating mode from the last command: Heat, Cool, Dry, Fan, or Off",
                     ValueType::Enum(vec![
                         "Heat".to_string(),
                         "Cool".to_string(),
+                        "Dry".to_string(),
+                        "Fan".to_string(),
                         "Off".to_string(),
                     ]),
                 ),
@@ -259,6 +333,12 @@ impl Node for ActiveCommandNode {
                     "True if powerful/turbo mode was enabled in the last command",
                     ValueType::Boolean,
                 ),
+                NodeOutput::new(
+                    "target_temperature",
+                    "Target Temperature",
+                    "Temperature in Celsius the controller aims for (the profile's temperature plus the setpoint offset). The command's Temperature is this rounded to the step the AC supports",
+                    ValueType::Float,
+                ),
             ],
         )
     }
@@ -336,6 +416,14 @@ impl Node for TurnOffNode {
                     ValueType::CauseReason(vec![]),
                     true,
                 ),
+
+                NodeInput::new(
+                    "annotation",
+                    "Annotation",
+                    "Optional text recorded with the action. Shown in the execution history and the dashboard.",
+                    ValueType::String,
+                    false,
+                ),
             ],
             vec![], // No outputs - this is a terminal node
         )
@@ -377,6 +465,46 @@ impl Node for ResetActiveCommandNode {
     }
 }
 
+/// Notify node - queues a notification and continues execution
+/// Channel, title, message template and cooldown are edited on the node and stored in its data
+/// (`notifyChannel`, `notifyTitle`, `notifyMessage`, `notifyCooldownMinutes`)
+pub struct NotifyNode;
+
+impl Node for NotifyNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "flow_notify",
+            "Notify",
+            "Sends a notification to the configured channel and continues to the next connected node. The title and message may contain placeholders: {device}, {indoor_temp}, {outdoor_temp}, {solar_watt}, {net_power_watt} and {value}. The notification is not sent again for this device until the cooldown has passed.",
+            "System",
+            vec![
+                NodeInput::new(
+                    "exec_in",
+                    "▶",
+                    "Execution flow input - triggers the notification",
+                    ValueType::Execution,
+                    true,
+                ),
+                NodeInput::new(
+                    "value",
+                    "Value",
+                    "Optional value available as {value} in the title and message",
+                    ValueType::Any,
+                    false,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "exec_out",
+                    "▶",
+                    "Execution flow output - continues to the next node",
+                    ValueType::Execution,
+                ),
+            ],
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -389,7 +517,7 @@ mod tests {
         assert_eq!(def.name, "Start");
         assert_eq!(def.category, "System");
         assert_eq!(def.inputs.len(), 1); // evaluate_every_minutes input
-        assert_eq!(def.outputs.len(), 11); // exec_out, device, device_sensor_temperature, is_auto_mode, last_change_minutes, outdoor_temperature, is_user_home, net_power_watt, raw_solar_watt, avg_next_24h_outdoor_temp, active_command
+        assert_eq!(def.outputs.len(), 19); // exec_out, device, device_sensor_temperature, is_auto_mode, last_change_minutes, outdoor_temperature, is_user_home, people_home_count, occupancy_probability, net_power_watt, raw_solar_watt, avg_next_24h_outdoor_temp, forecast_min_next_12h_temp, season, active_command, possible_grid_outage, dr_event_active, dr_level, dr_minutes_until_event
         
         // Verify evaluate_every_minutes input
         let eval_input = def.inputs.iter().find(|i| i.id == "evaluate_every_minutes").unwrap();
@@ -401,13 +529,10 @@ mod tests {
         let exec_output = def.outputs.iter().find(|o| o.id == "exec_out").unwrap();
         assert_eq!(exec_output.value_type, ValueType::Execution);
         
-        // Verify device output is an enum with device values
+        // Verify device output is an enum, its device values are filled in by the server
         let device_output = def.outputs.iter().find(|o| o.id == "device").unwrap();
         match &device_output.value_type {
-            ValueType::Enum(values) => {
-                assert!(values.contains(&"LivingRoom".to_string()));
-                assert!(values.contains(&"Veranda".to_string()));
-            }
+            ValueType::Enum(values) => assert!(values.is_empty()),
             _ => panic!("Expected Enum type for device output"),
         }
         
@@ -432,6 +557,10 @@ mod tests {
         let user_home_output = def.outputs.iter().find(|o| o.id == "is_user_home").unwrap();
         assert_eq!(user_home_output.value_type, ValueType::Boolean);
         
+        // Verify occupancy_probability output is a float
+        let occupancy_output = def.outputs.iter().find(|o| o.id == "occupancy_probability").unwrap();
+        assert_eq!(occupancy_output.value_type, ValueType::Float);
+        
         // Verify net_power_watt output is an integer
         let net_power_output = def.outputs.iter().find(|o| o.id == "net_power_watt").unwrap();
         assert_eq!(net_power_output.value_type, ValueType::Integer);
@@ -443,6 +572,22 @@ mod tests {
         // Verify avg_next_24h_outdoor_temp output is a float
         let avg_temp_output = def.outputs.iter().find(|o| o.id == "avg_next_24h_outdoor_temp").unwrap();
         assert_eq!(avg_temp_output.value_type, ValueType::Float);
+        
+        // Verify forecast_min_next_12h_temp output is a float
+        let min_temp_output = def.outputs.iter().find(|o| o.id == "forecast_min_next_12h_temp").unwrap();
+        assert_eq!(min_temp_output.value_type, ValueType::Float);
+        
+        // Verify possible_grid_outage output is a boolean
+        let outage_output = def.outputs.iter().find(|o| o.id == "possible_grid_outage").unwrap();
+        assert_eq!(outage_output.value_type, ValueType::Boolean);
+        
+        // Verify demand-response outputs
+        let dr_active_output = def.outputs.iter().find(|o| o.id == "dr_event_active").unwrap();
+        assert_eq!(dr_active_output.value_type, ValueType::Boolean);
+        let dr_level_output = def.outputs.iter().find(|o| o.id == "dr_level").unwrap();
+        assert_eq!(dr_level_output.value_type, ValueType::Integer);
+        let dr_minutes_output = def.outputs.iter().find(|o| o.id == "dr_minutes_until_event").unwrap();
+        assert_eq!(dr_minutes_output.value_type, ValueType::Integer);
     }
     
     #[test]
@@ -458,7 +603,7 @@ mod tests {
         assert_eq!(def.node_type, "flow_execute_action");
         assert_eq!(def.name, "Execute Action");
         assert_eq!(def.category, "System");
-        assert_eq!(def.inputs.len(), 7); // exec_in, temperature, mode, fan_speed, is_powerful, enable_swing, cause_reason (device is inferred from context)
+        assert_eq!(def.inputs.len(), 10); // exec_in, temperature, mode, fan_speed, is_powerful, enable_swing, cause_reason, vertical_vane, horizontal_vane, annotation (device is inferred from context)
         assert_eq!(def.outputs.len(), 0); // Terminal node has no outputs
         
         // Verify exec_in input (execution flow)
@@ -471,13 +616,15 @@ mod tests {
         assert_eq!(temp_input.value_type, ValueType::Float);
         assert!(temp_input.required);
         
-        // Verify mode input (Heat/Cool/Off)
+        // Verify mode input (Heat/Cool/Dry/Fan/Off)
         let mode_input = def.inputs.iter().find(|i| i.id == "mode").unwrap();
         match &mode_input.value_type {
             ValueType::Enum(values) => {
-                assert_eq!(values.len(), 3);
+                assert_eq!(values.len(), 5);
                 assert!(values.contains(&"Heat".to_string()));
                 assert!(values.contains(&"Cool".to_string()));
+                assert!(values.contains(&"Dry".to_string()));
+                assert!(values.contains(&"Fan".to_string()));
                 assert!(values.contains(&"Off".to_string()));
             }
             _ => panic!("Expected Enum type for mode input"),
@@ -519,6 +666,18 @@ mod tests {
         }
         assert!(cause_input.required);
         
+        // Verify the optional annotation input
+        let annotation_input = def.inputs.iter().find(|i| i.id == "annotation").unwrap();
+        assert_eq!(annotation_input.value_type, ValueType::String);
+        assert!(!annotation_input.required);
+        
+        // Verify the optional vane inputs take Swing Mode positions
+        for id in ["vertical_vane", "horizontal_vane"] {
+            let vane_input = def.inputs.iter().find(|i| i.id == id).unwrap();
+            assert_eq!(vane_input.value_type, ValueType::Enum(vane_names()));
+            assert!(!vane_input.required);
+        }
+        
         // Verify no device input (device is inferred from context)
         assert!(def.inputs.iter().find(|i| i.id == "device").is_none());
     }
@@ -559,7 +718,7 @@ mod tests {
         assert_eq!(def.node_type, "flow_turn_off");
         assert_eq!(def.name, "Turn Off");
         assert_eq!(def.category, "System");
-        assert_eq!(def.inputs.len(), 2); // exec_in and cause_reason inputs
+        assert_eq!(def.inputs.len(), 3); // exec_in, cause_reason and annotation inputs
         assert_eq!(def.outputs.len(), 0); // Terminal node has no outputs
         
         // Verify exec_in input (execution flow)
@@ -590,6 +749,7 @@ mod tests {
             TurnOffNode::definition(),
             ActiveCommandNode::definition(),
             ResetActiveCommandNode::definition(),
+            NotifyNode::definition(),
         ];
         
         for def in definitions {
@@ -630,7 +790,7 @@ mod tests {
         assert_eq!(def.name, "Active Command");
         assert_eq!(def.category, "System");
         assert_eq!(def.inputs.len(), 1); // active_command input
-        assert_eq!(def.outputs.len(), 7); // is_defined, is_on, temperature, mode, fan_speed, swing, is_powerful
+        assert_eq!(def.outputs.len(), 8); // is_defined, is_on, temperature, mode, fan_speed, swing, is_powerful, target_temperature
         
         // Verify input
         let input = &def.inputs[0];
@@ -654,9 +814,11 @@ mod tests {
         let mode_output = def.outputs.iter().find(|o| o.id == "mode").unwrap();
         match &mode_output.value_type {
             ValueType::Enum(values) => {
-                assert_eq!(values.len(), 3);
+                assert_eq!(values.len(), 5);
                 assert!(values.contains(&"Heat".to_string()));
                 assert!(values.contains(&"Cool".to_string()));
+                assert!(values.contains(&"Dry".to_string()));
+                assert!(values.contains(&"Fan".to_string()));
                 assert!(values.contains(&"Off".to_string()));
             }
             _ => panic!("Expected Enum type for mode output"),
@@ -673,6 +835,10 @@ mod tests {
         // Verify is_powerful output
         let is_powerful_output = def.outputs.iter().find(|o| o.id == "is_powerful").unwrap();
         assert_eq!(is_powerful_output.value_type, ValueType::Boolean);
+
+        // Verify target_temperature output
+        let target_output = def.outputs.iter().find(|o| o.id == "target_temperature").unwrap();
+        assert_eq!(target_output.value_type, ValueType::Float);
     }
 
     #[test]
@@ -706,6 +872,27 @@ mod tests {
         assert_eq!(exec_output.value_type, ValueType::Execution);
     }
 
+    #[test]
+    fn test_notify_node_definition() {
+        let def = NotifyNode::definition();
+        
+        assert_eq!(def.node_type, "flow_notify");
+        assert_eq!(def.category, "System");
+        assert_eq!(def.inputs.len(), 2); // exec_in and optional value
+        assert_eq!(def.outputs.len(), 1); // exec_out
+        
+        let exec_input = def.inputs.iter().find(|i| i.id == "exec_in").unwrap();
+        assert_eq!(exec_input.value_type, ValueType::Execution);
+        assert!(exec_input.required);
+        
+        let value_input = def.inputs.iter().find(|i| i.id == "value").unwrap();
+        assert_eq!(value_input.value_type, ValueType::Any);
+        assert!(!value_input.required);
+        
+        let exec_output = def.outputs.iter().find(|o| o.id == "exec_out").unwrap();
+        assert_eq!(exec_output.value_type, ValueType::Execution);
+    }
+
     #[test]
     fn test_reset_active_command_node_serializable() {
         let def = ResetActiveCommandNode::definition();
@@ -8,14 +8,17 @@ mod integration_tests {
     fn test_get_all_node_definitions() {
         let definitions = nodes::get_all_node_definitions();
         
-        // Verify we have 28 node definitions:
-        // System: 6 (flow_start, flow_execute_action, flow_do_nothing, flow_turn_off, flow_active_command, flow_reset_active_command)
-        // Sensors: 1 (pir_detection)
-        // Logic: 9 (and, or, nand, if, not, equals, evaluate_number, branch, sequence)
-        // Math: 4 (add, subtract, multiply, divide)
-        // Primitives: 3 (float, integer, boolean)
-        // Enums: 5 (device, intensity, cause_reason, request_mode, fan_speed)
-        assert_eq!(definitions.len(), 28);
+        // Verify we have 59 node definitions:
+        // System: 7 (flow_start, flow_execute_action, flow_do_nothing, flow_turn_off, flow_active_command, flow_reset_active_command, flow_notify)
+        // Sensors: 12 (pir_detection, occupancy, contact_sensor, humidity, time_of_day, day_of_week, energy_price, battery, power_budget, data_age, weather_forecast, solar_forecast)
+        // Logic: 11 (and, or, nand, if, not, equals, evaluate_number, branch, sequence, hysteresis, cooldown)
+        // Math: 8 (add, subtract, multiply, divide, min, max, clamp, average)
+        // Text: 2 (concat, format_number)
+        // Primitives: 5 (float, integer, boolean, string, variable)
+        // Enums: 10 (device, intensity, cause_reason, request_mode, fan_speed, data_source, season, swing_mode, pir_zone, pir_sensor)
+        // Functions: 3 (call_nodeset, nodeset_input, nodeset_output)
+        // Annotations: 1 (comment)
+        assert_eq!(definitions.len(), 59);
         
         // Verify system node types
         let node_types: Vec<&str> = definitions.iter().map(|d| d.node_type.as_str()).collect();
@@ -25,9 +28,19 @@ mod integration_tests {
         assert!(node_types.contains(&"flow_turn_off"));
         assert!(node_types.contains(&"flow_active_command"));
         assert!(node_types.contains(&"flow_reset_active_command"));
+        assert!(node_types.contains(&"flow_notify"));
         
         // Verify sensor node types
         assert!(node_types.contains(&"pir_detection"));
+        assert!(node_types.contains(&"humidity"));
+        assert!(node_types.contains(&"time_of_day"));
+        assert!(node_types.contains(&"day_of_week"));
+        assert!(node_types.contains(&"energy_price"));
+        assert!(node_types.contains(&"battery"));
+        assert!(node_types.contains(&"power_budget"));
+        assert!(node_types.contains(&"data_age"));
+        assert!(node_types.contains(&"weather_forecast"));
+        assert!(node_types.contains(&"solar_forecast"));
         
         // Verify logic node types
         assert!(node_types.contains(&"logic_and"));
@@ -39,17 +52,24 @@ mod integration_tests {
         assert!(node_types.contains(&"logic_evaluate_number"));
         assert!(node_types.contains(&"logic_branch"));
         assert!(node_types.contains(&"logic_sequence"));
+        assert!(node_types.contains(&"logic_hysteresis"));
+        assert!(node_types.contains(&"logic_cooldown"));
         
         // Verify math node types
         assert!(node_types.contains(&"math_add"));
         assert!(node_types.contains(&"math_subtract"));
         assert!(node_types.contains(&"math_multiply"));
         assert!(node_types.contains(&"math_divide"));
+        assert!(node_types.contains(&"math_min"));
+        assert!(node_types.contains(&"math_max"));
+        assert!(node_types.contains(&"math_clamp"));
+        assert!(node_types.contains(&"math_average"));
         
         // Verify primitive node types
         assert!(node_types.contains(&"primitive_float"));
         assert!(node_types.contains(&"primitive_integer"));
         assert!(node_types.contains(&"primitive_boolean"));
+        assert!(node_types.contains(&"primitive_variable"));
         
         // Verify enum node types
         assert!(node_types.contains(&"device"));
@@ -81,24 +101,33 @@ mod integration_tests {
         // Verify categories are assigned appropriately
         for def in &definitions {
             match def.node_type.as_str() {
-                "flow_start" | "flow_execute_action" | "flow_do_nothing" | "flow_turn_off" | "flow_active_command" | "flow_reset_active_command" => {
+                "flow_start" | "flow_execute_action" | "flow_do_nothing" | "flow_turn_off" | "flow_active_command" | "flow_reset_active_command" | "flow_notify" => {
                     assert_eq!(def.category, "System", "System nodes should be in 'System' category");
                 }
-                "pir_detection" => {
+                "pir_detection" | "occupancy" | "contact_sensor" | "humidity" | "time_of_day" | "day_of_week" | "energy_price" | "battery" | "power_budget" | "data_age" | "weather_forecast" | "solar_forecast" => {
                     assert_eq!(def.category, "Sensors", "Sensor nodes should be in 'Sensors' category");
                 }
-                "logic_and" | "logic_or" | "logic_nand" | "logic_if" | "logic_not" | "logic_equals" | "logic_evaluate_number" | "logic_branch" | "logic_sequence" => {
+                "logic_and" | "logic_or" | "logic_nand" | "logic_if" | "logic_not" | "logic_equals" | "logic_evaluate_number" | "logic_branch" | "logic_sequence" | "logic_hysteresis" | "logic_cooldown" => {
                     assert_eq!(def.category, "Logic", "Logic nodes should be in 'Logic' category");
                 }
-                "math_add" | "math_subtract" | "math_multiply" | "math_divide" => {
+                "math_add" | "math_subtract" | "math_multiply" | "math_divide" | "math_min" | "math_max" | "math_clamp" | "math_average" => {
                     assert_eq!(def.category, "Logic", "Math nodes should be in 'Logic' category");
                 }
-                "primitive_float" | "primitive_integer" | "primitive_boolean" => {
+                "text_concat" | "text_format_number" => {
+                    assert_eq!(def.category, "Logic", "Text nodes should be in 'Logic' category");
+                }
+                "primitive_float" | "primitive_integer" | "primitive_boolean" | "primitive_string" | "primitive_variable" => {
                     assert_eq!(def.category, "Primitives", "Primitive nodes should be in 'Primitives' category");
                 }
-                "device" | "intensity" | "cause_reason" | "request_mode" | "fan_speed" => {
+                "device" | "intensity" | "cause_reason" | "request_mode" | "fan_speed" | "data_source" | "season" | "swing_mode" | "pir_zone" | "pir_sensor" => {
                     assert_eq!(def.category, "Enums", "Enum nodes should be in 'Enums' category");
                 }
+                "call_nodeset" | "nodeset_input" | "nodeset_output" => {
+                    assert_eq!(def.category, "Functions", "Profile call nodes should be in 'Functions' category");
+                }
+                "annotation_comment" => {
+                    assert_eq!(def.category, "Annotations", "Comment nodes should be in 'Annotations' category");
+                }
                 _ => panic!("Unexpected node type: {}", def.node_type),
             }
         }
@@ -168,7 +197,7 @@ mod integration_tests {
         let definitions = nodes::get_all_node_definitions();
         
         // Primitive nodes should have no inputs (they are source nodes)
-        for node_type in &["primitive_float", "primitive_integer", "primitive_boolean"] {
+        for node_type in &["primitive_float", "primitive_integer", "primitive_boolean", "primitive_variable"] {
             let node = definitions.iter().find(|d| d.node_type == *node_type).unwrap();
             assert_eq!(node.inputs.len(), 0, "{} should have no inputs", node_type);
             assert_eq!(node.outputs.len(), 1, "{} should have 1 output", node_type);
@@ -194,10 +223,7 @@ mod integration_tests {
         assert_eq!(device_node.inputs.len(), 0, "Device node should have no inputs");
         assert_eq!(device_node.outputs.len(), 1, "Device node should have 1 output");
         match &device_node.outputs[0].value_type {
-            nodes::ValueType::Enum(values) => {
-                assert!(values.contains(&"LivingRoom".to_string()));
-                assert!(values.contains(&"Veranda".to_string()));
-            }
+            nodes::ValueType::Enum(values) => assert!(values.is_empty(), "Devices are filled in by the server"),
             _ => panic!("Expected Enum type for device output"),
         }
         
@@ -219,7 +245,7 @@ mod integration_tests {
         let definitions = nodes::get_all_node_definitions();
         let pir_node = definitions.iter().find(|d| d.node_type == "pir_detection").unwrap();
         
-        assert_eq!(pir_node.inputs.len(), 2, "PIR node should have 2 inputs");
+        assert_eq!(pir_node.inputs.len(), 4, "PIR node should have 4 inputs");
         assert_eq!(pir_node.outputs.len(), 2, "PIR node should have 2 outputs");
         
         // Verify inputs
@@ -246,7 +272,7 @@ mod integration_tests {
         let start_node = definitions.iter().find(|d| d.node_type == "flow_start").unwrap();
         
         assert_eq!(start_node.inputs.len(), 1, "Start node should have 1 input (evaluate_every_minutes)");
-        assert_eq!(start_node.outputs.len(), 11, "Start node should have 11 outputs (including exec_out)");
+        assert_eq!(start_node.outputs.len(), 19, "Start node should have 19 outputs (including exec_out)");
         assert_eq!(start_node.category, "System");
         
         // Verify evaluate_every_minutes input
@@ -261,10 +287,7 @@ mod integration_tests {
         // Verify device output
         let device_output = start_node.outputs.iter().find(|o| o.id == "device").unwrap();
         match &device_output.value_type {
-            nodes::ValueType::Enum(values) => {
-                assert!(values.contains(&"LivingRoom".to_string()));
-                assert!(values.contains(&"Veranda".to_string()));
-            }
+            nodes::ValueType::Enum(values) => assert!(values.is_empty(), "Devices are filled in by the server"),
             _ => panic!("Expected Enum type for device output"),
         }
         
@@ -296,9 +319,25 @@ mod integration_tests {
         let avg_temp_output = start_node.outputs.iter().find(|o| o.id == "avg_next_24h_outdoor_temp").unwrap();
         assert_eq!(avg_temp_output.value_type, nodes::ValueType::Float);
         
+        // Verify forecast_min_next_12h_temp output
+        let min_temp_output = start_node.outputs.iter().find(|o| o.id == "forecast_min_next_12h_temp").unwrap();
+        assert_eq!(min_temp_output.value_type, nodes::ValueType::Float);
+        
+        // Verify occupancy_probability output
+        let occupancy_output = start_node.outputs.iter().find(|o| o.id == "occupancy_probability").unwrap();
+        assert_eq!(occupancy_output.value_type, nodes::ValueType::Float);
+        
         // Verify active_command output
         let active_command_output = start_node.outputs.iter().find(|o| o.id == "active_command").unwrap();
         assert_eq!(active_command_output.value_type, nodes::ValueType::Object);
+        
+        // Verify possible_grid_outage output
+        let outage_output = start_node.outputs.iter().find(|o| o.id == "possible_grid_outage").unwrap();
+        assert_eq!(outage_output.value_type, nodes::ValueType::Boolean);
+        
+        // Verify dr_event_active output
+        let dr_output = start_node.outputs.iter().find(|o| o.id == "dr_event_active").unwrap();
+        assert_eq!(dr_output.value_type, nodes::ValueType::Boolean);
     }
     
     #[test]
@@ -306,8 +345,8 @@ mod integration_tests {
         let definitions = nodes::get_all_node_definitions();
         let execute_node = definitions.iter().find(|d| d.node_type == "flow_execute_action").unwrap();
         
-        // 7 inputs: exec_in + temperature, mode, fan_speed, is_powerful, enable_swing, cause_reason
-        assert_eq!(execute_node.inputs.len(), 7, "Execute Action node should have 7 inputs (exec_in + 6 data inputs)");
+        // 10 inputs: exec_in + temperature, mode, fan_speed, is_powerful, enable_swing, cause_reason, vertical_vane, horizontal_vane, annotation
+        assert_eq!(execute_node.inputs.len(), 10, "Execute Action node should have 10 inputs (exec_in + 9 data inputs)");
         assert_eq!(execute_node.outputs.len(), 0, "Execute Action node should have no outputs (terminal)");
         assert_eq!(execute_node.category, "System");
         
@@ -316,7 +355,7 @@ mod integration_tests {
         assert_eq!(exec_input.value_type, nodes::ValueType::Execution);
         assert!(exec_input.required);
         
-        // Verify all inputs exist and are required
+        // Verify all inputs exist and all but the vanes and annotation are required
         let input_ids: Vec<&str> = execute_node.inputs.iter().map(|i| i.id.as_str()).collect();
         assert!(input_ids.contains(&"exec_in"));
         assert!(input_ids.contains(&"temperature"));
@@ -325,12 +364,16 @@ mod integration_tests {
         assert!(input_ids.contains(&"is_powerful"));
         assert!(input_ids.contains(&"enable_swing"));
         assert!(input_ids.contains(&"cause_reason"));
+        assert!(input_ids.contains(&"vertical_vane"));
+        assert!(input_ids.contains(&"horizontal_vane"));
+        assert!(input_ids.contains(&"annotation"));
         
         // Verify no device input (device is inferred from context)
         assert!(!input_ids.contains(&"device"), "Execute Action should not have device input (inferred from context)");
         
+        let optional_inputs = ["vertical_vane", "horizontal_vane", "annotation"];
         for input in &execute_node.inputs {
-            assert!(input.required, "All Execute Action inputs should be required");
+            assert_eq!(input.required, !optional_inputs.contains(&input.id.as_str()), "All Execute Action inputs but the vanes and annotation should be required");
         }
     }
     
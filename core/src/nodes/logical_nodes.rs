@@ -380,6 +380,96 @@ impl Node for SequenceNode {
     }
 }
 
+/// Hysteresis node - a boolean that latches between executions
+/// Turns on when the value reaches the on threshold and off when it reaches the off threshold.
+/// While the value is between the thresholds, the state of the previous execution is kept,
+/// so the output doesn't flip back and forth around a single threshold.
+/// The state is kept per device, and starts off.
+pub struct HysteresisNode;
+
+impl Node for HysteresisNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "logic_hysteresis",
+            "Hysteresis",
+            "Outputs a boolean that turns on when the value reaches the On threshold and off when it reaches the Off threshold, keeping its previous state in between. With the On threshold below the Off threshold (heating: on at 19, off at 21) it turns on when the value falls; otherwise (cooling: on at 26, off at 24) when it rises. The state is remembered between evaluations per device and starts off.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "value",
+                    "Value",
+                    "Value to compare, e.g. the room temperature",
+                    ValueType::Float,
+                    true,
+                ),
+                NodeInput::new(
+                    "on_threshold",
+                    "On Threshold",
+                    "The output turns on when the value reaches this threshold",
+                    ValueType::Float,
+                    true,
+                ),
+                NodeInput::new(
+                    "off_threshold",
+                    "Off Threshold",
+                    "The output turns off when the value reaches this threshold",
+                    ValueType::Float,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "is_on",
+                    "Is On",
+                    "Latched state: true after reaching the On threshold until the Off threshold is reached",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Cooldown node - lets a true trigger through at most once per cooldown
+/// Outputs true when the trigger is true and at least the cooldown has passed since the node last
+/// output true, e.g. so Powerful mode isn't toggled every cycle.
+/// The time it last passed is kept per device; a node that never passed lets the first trigger through.
+pub struct CooldownNode;
+
+impl Node for CooldownNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "logic_cooldown",
+            "Cooldown",
+            "Outputs true when the trigger is true and at least Cooldown Minutes have passed since this node last output true, otherwise false. Use it to keep a change from being repeated every evaluation. The time it last passed is remembered between evaluations per device.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "trigger",
+                    "Trigger",
+                    "Condition to let through once the cooldown has passed",
+                    ValueType::Boolean,
+                    true,
+                ),
+                NodeInput::new(
+                    "cooldown_minutes",
+                    "Cooldown Minutes",
+                    "Minutes that must pass after the node output true before it can output true again",
+                    ValueType::Integer,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "pass",
+                    "Pass",
+                    "True if the trigger is true and the cooldown has passed",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -599,6 +689,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_hysteresis_node_definition() {
+        let def = HysteresisNode::definition();
+
+        assert_eq!(def.node_type, "logic_hysteresis");
+        assert_eq!(def.category, "Logic");
+        let input_ids: Vec<_> = def.inputs.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(input_ids, ["value", "on_threshold", "off_threshold"]);
+        assert!(def.inputs.iter().all(|i| i.value_type == ValueType::Float && i.required));
+        assert_eq!(def.outputs.len(), 1);
+        assert_eq!(def.outputs[0].id, "is_on");
+        assert_eq!(def.outputs[0].value_type, ValueType::Boolean);
+    }
+
+    #[test]
+    fn test_cooldown_node_definition() {
+        let def = CooldownNode::definition();
+
+        assert_eq!(def.node_type, "logic_cooldown");
+        assert_eq!(def.category, "Logic");
+        let inputs: Vec<_> = def.inputs.iter().map(|i| (i.id.as_str(), &i.value_type)).collect();
+        assert_eq!(inputs, [("trigger", &ValueType::Boolean), ("cooldown_minutes", &ValueType::Integer)]);
+        assert!(def.inputs.iter().all(|i| i.required));
+        assert_eq!(def.outputs.len(), 1);
+        assert_eq!(def.outputs[0].id, "pass");
+        assert_eq!(def.outputs[0].value_type, ValueType::Boolean);
+    }
+
     #[test]
     fn test_logical_nodes_serializable() {
         let definitions = vec![
@@ -611,6 +729,7 @@ mod tests {
             EvaluateNumberNode::definition(),
             BranchNode::definition(),
             SequenceNode::definition(),
+            HysteresisNode::definition(),
         ];
         
         for def in definitions {
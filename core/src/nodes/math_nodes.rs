@@ -171,6 +171,175 @@ impl Node for DivideNode {
     }
 }
 
+/// Min node - outputs the smaller of two numeric values
+/// 
+/// Uses the same type constraint behavior as the Add node: the output is an Integer if both
+/// inputs are Integers, otherwise a Float.
+pub struct MinNode;
+
+impl Node for MinNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "math_min",
+            "Min",
+            "Outputs the smaller of two numeric values. When one input is connected, the other input's type constraint matches that input's type (Float or Integer). Resets to Float/Integer constraint when all pins are disconnected.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "input_a",
+                    "A",
+                    "First numeric value (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+                NodeInput::new(
+                    "input_b",
+                    "B",
+                    "Second numeric value (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "result",
+                    "Result",
+                    "The smaller of the two input values",
+                    ValueType::Any, // Output type matches input types
+                ),
+            ],
+        )
+    }
+}
+
+/// Max node - outputs the larger of two numeric values
+/// 
+/// Uses the same type constraint behavior as the Add node: the output is an Integer if both
+/// inputs are Integers, otherwise a Float.
+pub struct MaxNode;
+
+impl Node for MaxNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "math_max",
+            "Max",
+            "Outputs the larger of two numeric values. When one input is connected, the other input's type constraint matches that input's type (Float or Integer). Resets to Float/Integer constraint when all pins are disconnected.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "input_a",
+                    "A",
+                    "First numeric value (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+                NodeInput::new(
+                    "input_b",
+                    "B",
+                    "Second numeric value (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "result",
+                    "Result",
+                    "The larger of the two input values",
+                    ValueType::Any, // Output type matches input types
+                ),
+            ],
+        )
+    }
+}
+
+/// Clamp node - limits a numeric value to a range
+/// 
+/// Uses the same type constraint behavior as the Add node: the output is an Integer if all
+/// inputs are Integers, otherwise a Float. If Min is above Max, Max wins.
+pub struct ClampNode;
+
+impl Node for ClampNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "math_clamp",
+            "Clamp",
+            "Limits a value to the range from Min to Max, e.g. a setpoint to 19-23. If Min is above Max, Max wins. When one input is connected, the other inputs' type constraint matches that input's type (Float or Integer). Resets to Float/Integer constraint when all pins are disconnected.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "value",
+                    "Value",
+                    "Value to limit (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+                NodeInput::new(
+                    "min",
+                    "Min",
+                    "Lowest value to output (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+                NodeInput::new(
+                    "max",
+                    "Max",
+                    "Highest value to output (accepts Float or Integer)",
+                    ValueType::Any, // Uses Any for flexible type matching, but frontend constrains to Float/Integer
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "result",
+                    "Result",
+                    "The value limited to the range",
+                    ValueType::Any, // Output type matches input types
+                ),
+            ],
+        )
+    }
+}
+
+/// Average node - averages float values
+/// Has dynamic number of float input pins (minimum 2), like the AND node
+pub struct AverageNode;
+
+impl Node for AverageNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "math_average",
+            "Average",
+            "Outputs the average of all inputs. Add or remove input pins with + and - buttons.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "input_1",
+                    "Input 1",
+                    "First float value",
+                    ValueType::Float,
+                    true,
+                ),
+                NodeInput::new(
+                    "input_2",
+                    "Input 2",
+                    "Second float value",
+                    ValueType::Float,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "result",
+                    "Result",
+                    "The average of the input values",
+                    ValueType::Float,
+                ),
+            ],
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +440,38 @@ mod tests {
         assert_eq!(def.outputs[0].value_type, ValueType::Float);
     }
 
+    #[test]
+    fn test_min_max_clamp_node_definitions() {
+        for (def, node_type, input_ids) in [
+            (MinNode::definition(), "math_min", vec!["input_a", "input_b"]),
+            (MaxNode::definition(), "math_max", vec!["input_a", "input_b"]),
+            (ClampNode::definition(), "math_clamp", vec!["value", "min", "max"]),
+        ] {
+            assert_eq!(def.node_type, node_type);
+            assert_eq!(def.category, "Logic");
+            assert_eq!(def.inputs.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), input_ids);
+            
+            // Verify inputs and output are Any type for flexible matching between Float/Integer
+            assert!(def.inputs.iter().all(|i| i.value_type == ValueType::Any && i.required));
+            assert_eq!(def.outputs.len(), 1);
+            assert_eq!(def.outputs[0].id, "result");
+            assert_eq!(def.outputs[0].value_type, ValueType::Any);
+        }
+    }
+
+    #[test]
+    fn test_average_node_definition() {
+        let def = AverageNode::definition();
+        
+        assert_eq!(def.node_type, "math_average");
+        assert_eq!(def.name, "Average");
+        assert_eq!(def.category, "Logic");
+        assert_eq!(def.inputs.len(), 2); // Minimum 2 inputs
+        assert!(def.inputs.iter().all(|i| i.value_type == ValueType::Float && i.required));
+        assert_eq!(def.outputs.len(), 1);
+        assert_eq!(def.outputs[0].value_type, ValueType::Float);
+    }
+
     #[test]
     fn test_math_nodes_serializable() {
         let definitions = vec![
@@ -278,6 +479,10 @@ mod tests {
             SubtractNode::definition(),
             MultiplyNode::definition(),
             DivideNode::definition(),
+            MinNode::definition(),
+            MaxNode::definition(),
+            ClampNode::definition(),
+            AverageNode::definition(),
         ];
         
         for def in definitions {
@@ -0,0 +1,104 @@
+mod node_system;
+mod logical_nodes;
+mod primitive_nodes;
+mod enum_nodes;
+mod sensor_nodes;
+mod math_nodes;
+mod text_nodes;
+mod annotation_nodes;
+mod call_nodes;
+pub mod flow_nodes;
+pub mod execution;
+pub mod schema;
+pub mod variables;
+pub mod calls;
+pub mod analysis;
+#[cfg(test)]
+mod integration_test;
+
+pub use node_system::{Node, NodeDefinition, ValueType, EnumOption};
+pub use logical_nodes::{AndNode, OrNode, NandNode, IfNode, NotNode, EqualsNode, EvaluateNumberNode, BranchNode, SequenceNode, HysteresisNode, CooldownNode};
+pub use primitive_nodes::{FloatNode, IntegerNode, BooleanNode, StringNode, VariableNode};
+pub use enum_nodes::{DeviceNode, IntensityNode, CauseReasonNode, RequestModeNode, FanSpeedNode, DataSourceNode, SeasonNode, SwingModeNode, PirZoneNode, PirSensorNode};
+pub use sensor_nodes::{PirDetectionNode, OccupancyNode, ContactSensorNode, HumidityNode, TimeOfDayNode, DayOfWeekNode, EnergyPriceNode, BatteryNode, PowerBudgetNode, DataAgeNode, WeatherForecastNode, SolarForecastNode};
+pub use flow_nodes::{StartNode, ExecuteActionNode, DoNothingNode, TurnOffNode, ActiveCommandNode, ResetActiveCommandNode, NotifyNode};
+pub use execution::{NodesetExecutor, ExecutionInputs, ExecutionResult, ActionResult, ActiveCommandData, DemandResponseData, EnergyPriceData, PowerBudgetData, WeatherForecastData, DataAges, LocalTimeData, NotificationResult, TraceStep, validate_nodeset_for_execution};
+pub use annotation_nodes::CommentNode;
+pub use call_nodes::{CallNodesetNode, NodesetInputNode, NodesetOutputNode};
+pub use math_nodes::{AddNode, SubtractNode, MultiplyNode, DivideNode, MinNode, MaxNode, ClampNode, AverageNode};
+pub use text_nodes::{ConcatNode, FormatNumberNode};
+pub use analysis::{analyze_nodeset, find_type_mismatches, NodesetAnalysis, AnalysisWarning, AnalysisCode};
+
+/// Get all available node definitions for the frontend
+pub fn get_all_node_definitions() -> Vec<NodeDefinition> {
+    vec![
+        // System/Flow nodes
+        StartNode::definition(),
+        ExecuteActionNode::definition(),
+        DoNothingNode::definition(),
+        TurnOffNode::definition(),
+        ActiveCommandNode::definition(),
+        ResetActiveCommandNode::definition(),
+        NotifyNode::definition(),
+        // Sensor nodes
+        PirDetectionNode::definition(),
+        OccupancyNode::definition(),
+        ContactSensorNode::definition(),
+        HumidityNode::definition(),
+        TimeOfDayNode::definition(),
+        DayOfWeekNode::definition(),
+        EnergyPriceNode::definition(),
+        BatteryNode::definition(),
+        PowerBudgetNode::definition(),
+        DataAgeNode::definition(),
+        WeatherForecastNode::definition(),
+        SolarForecastNode::definition(),
+        // Logic nodes
+        AndNode::definition(),
+        OrNode::definition(),
+        NandNode::definition(),
+        IfNode::definition(),
+        NotNode::definition(),
+        EqualsNode::definition(),
+        EvaluateNumberNode::definition(),
+        BranchNode::definition(),
+        SequenceNode::definition(),
+        HysteresisNode::definition(),
+        CooldownNode::definition(),
+        // Math nodes
+        AddNode::definition(),
+        SubtractNode::definition(),
+        MultiplyNode::definition(),
+        DivideNode::definition(),
+        MinNode::definition(),
+        MaxNode::definition(),
+        ClampNode::definition(),
+        AverageNode::definition(),
+        // Text nodes
+        ConcatNode::definition(),
+        FormatNumberNode::definition(),
+        // Primitive nodes
+        FloatNode::definition(),
+        IntegerNode::definition(),
+        BooleanNode::definition(),
+        StringNode::definition(),
+        VariableNode::definition(),
+        // Enum nodes
+        DeviceNode::definition(),
+        IntensityNode::definition(),
+        CauseReasonNode::definition(),
+        RequestModeNode::definition(),
+        FanSpeedNode::definition(),
+        DataSourceNode::definition(),
+        SeasonNode::definition(),
+        SwingModeNode::definition(),
+        PirZoneNode::definition(),
+        PirSensorNode::definition(),
+        // Function nodes
+        CallNodesetNode::definition(),
+        NodesetInputNode::definition(),
+        NodesetOutputNode::definition(),
+        // Annotation nodes
+        CommentNode::definition(),
+    ]
+}
@@ -2,6 +2,7 @@ use serde::{Serialize, Deserialize};
 
 /// Represents a key-value pair for enums that need ID-based tracking
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct EnumOption {
     pub id: String,
     pub label: String,
@@ -9,6 +10,7 @@ pub struct EnumOption {
 
 /// Represents a type of value that can flow through nodes
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(tag = "type", content = "value")]
 pub enum ValueType {
     /// Floating point number (temperature, etc.)
@@ -53,10 +55,27 @@ impl ValueType {
             ValueType::CauseReason(_) => "#F06292", // Pink for cause reason (distinct from other enums)
         }
     }
+
+    /// Name of the type without its options, e.g. "Enum"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ValueType::Float => "Float",
+            ValueType::Integer => "Integer",
+            ValueType::Boolean => "Boolean",
+            ValueType::String => "String",
+            ValueType::Enum(_) => "Enum",
+            ValueType::EnumWithIds(_) => "EnumWithIds",
+            ValueType::Object => "Object",
+            ValueType::Any => "Any",
+            ValueType::Execution => "Execution",
+            ValueType::CauseReason(_) => "CauseReason",
+        }
+    }
 }
 
 /// Represents an input port on a node
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct NodeInput {
     /// Unique identifier for this input
     pub id: String,
@@ -88,6 +107,7 @@ impl NodeInput {
 
 /// Represents an output port on a node
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct NodeOutput {
     /// Unique identifier for this output
     pub id: String,
@@ -116,6 +136,7 @@ impl NodeOutput {
 
 /// Defines the structure and capabilities of a node type
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct NodeDefinition {
     /// Unique type identifier for this node
     pub node_type: String,
@@ -155,6 +176,23 @@ impl NodeDefinition {
         }
     }
 
+    /// Version of the node's ports, embedded in exported nodesets
+    /// A short hash of the port ids and types, so it changes whenever a port is added, removed or
+    /// retyped. Enum options (devices, cause reasons) are left out, so it is the same on every install.
+    pub fn port_version(&self) -> String {
+        let inputs = self.inputs.iter().map(|i| format!("in:{}:{}", i.id, i.value_type.kind()));
+        let outputs = self.outputs.iter().map(|o| format!("out:{}:{}", o.id, o.value_type.kind()));
+        let signature = inputs.chain(outputs).collect::<Vec<_>>().join(";");
+
+        // FNV-1a, unlike std's DefaultHasher it is stable across Rust releases
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in signature.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{:016x}", hash)
+    }
+
     /// Get the color for a category
     fn get_category_color(category: &str) -> &'static str {
         match category {
@@ -164,6 +202,8 @@ impl NodeDefinition {
             "Primitives" => "#FF9800",     // Orange for primitive nodes
             "Sensors" => "#00BCD4",        // Cyan for sensor nodes
             "Enums" => "#E91E63",          // Pink for enum nodes
+            "Functions" => "#795548",      // Brown for profile call nodes
+            "Annotations" => "#607D8B",    // Blue gray for comment frames
             _ => "#757575",                // Gray for others
         }
     }
@@ -240,4 +280,22 @@ mod tests {
         
         assert_eq!(vt, deserialized);
     }
+
+    #[test]
+    fn test_port_version() {
+        let definition = |value_type: ValueType, extra_output: bool| {
+            let mut outputs = vec![NodeOutput::new("device", "Device", "Selected device", value_type)];
+            if extra_output {
+                outputs.push(NodeOutput::new("count", "Count", "A count", ValueType::Integer));
+            }
+            NodeDefinition::new("test_node", "Test Node", "A test node", "Testing", vec![], outputs)
+        };
+        let version = definition(ValueType::Enum(vec!["LivingRoom".to_string()]), false).port_version();
+
+        assert_eq!(version.len(), 16);
+        // Enum options differ between installs and don't change the version
+        assert_eq!(definition(ValueType::Enum(vec!["Veranda".to_string()]), false).port_version(), version);
+        assert_ne!(definition(ValueType::String, false).port_version(), version);
+        assert_ne!(definition(ValueType::Enum(vec![]), true).port_version(), version);
+    }
 }
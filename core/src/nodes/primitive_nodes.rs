@@ -72,6 +72,54 @@ impl Node for BooleanNode {
     }
 }
 
+/// String primitive node - provides a user-editable text value
+/// Has a textbox for user input, e.g. a prefix for an annotation built with Concat
+pub struct StringNode;
+
+impl Node for StringNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "primitive_string",
+            "String",
+            "A text input. Enter the text in the textbox.",
+            "Primitives",
+            vec![], // No inputs - this is a source node
+            vec![
+                NodeOutput::new(
+                    "value",
+                    "Value",
+                    "The user-specified text",
+                    ValueType::String,
+                ),
+            ],
+        )
+    }
+}
+
+/// Variable node - reads a named constant defined in the nodeset's variables
+/// The variable name is entered in a textbox, its value is resolved when the nodeset is loaded
+pub struct VariableNode;
+
+impl Node for VariableNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "primitive_variable",
+            "Variable",
+            "A named constant from this profile's variables. Change the value in the variables panel to update every node that uses it.",
+            "Primitives",
+            vec![], // No inputs - this is a source node
+            vec![
+                NodeOutput::new(
+                    "value",
+                    "Value",
+                    "The current value of the variable",
+                    ValueType::Float,
+                ),
+            ],
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,12 +169,44 @@ mod tests {
         assert_eq!(def.outputs[0].value_type, ValueType::Boolean);
     }
 
+    #[test]
+    fn test_string_node_definition() {
+        let def = StringNode::definition();
+        
+        assert_eq!(def.node_type, "primitive_string");
+        assert_eq!(def.name, "String");
+        assert_eq!(def.category, "Primitives");
+        assert_eq!(def.inputs.len(), 0); // Source node has no inputs
+        assert_eq!(def.outputs.len(), 1);
+        
+        // Verify output type
+        assert_eq!(def.outputs[0].id, "value");
+        assert_eq!(def.outputs[0].value_type, ValueType::String);
+    }
+
+    #[test]
+    fn test_variable_node_definition() {
+        let def = VariableNode::definition();
+        
+        assert_eq!(def.node_type, "primitive_variable");
+        assert_eq!(def.name, "Variable");
+        assert_eq!(def.category, "Primitives");
+        assert_eq!(def.inputs.len(), 0); // Source node has no inputs
+        assert_eq!(def.outputs.len(), 1);
+        
+        // Verify output type
+        assert_eq!(def.outputs[0].id, "value");
+        assert_eq!(def.outputs[0].value_type, ValueType::Float);
+    }
+
     #[test]
     fn test_primitive_nodes_serializable() {
         let definitions = vec![
             FloatNode::definition(),
             IntegerNode::definition(),
             BooleanNode::definition(),
+            StringNode::definition(),
+            VariableNode::definition(),
         ];
         
         for def in definitions {
@@ -144,6 +224,7 @@ mod tests {
             FloatNode::definition(),
             IntegerNode::definition(),
             BooleanNode::definition(),
+            StringNode::definition(),
         ];
         
         for def in definitions {
@@ -0,0 +1,142 @@
+//! Nodeset schema versioning
+//!
+//! Every stored nodeset carries a `schema_version`. When the node system changes shape,
+//! bump `CURRENT_SCHEMA_VERSION` and append a migration step to `MIGRATIONS` that upgrades
+//! the previous version. Stored nodesets are upgraded on startup and whenever they are loaded.
+//! Nodesets saved by a newer version are refused instead of failing inside the executor.
+
+use serde_json::{Map, Value};
+
+/// Schema version written to newly saved nodesets
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// A single migration step, upgrading a nodeset document by one version
+type Migration = fn(&mut Map<String, Value>);
+
+/// MIGRATIONS[i] upgrades a nodeset from version i to i + 1
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// The nodeset was saved by a newer version of the application
+    NewerVersion { found: u32, supported: u32 },
+    /// The document is not a valid nodeset
+    Invalid(String),
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::NewerVersion { found, supported } => write!(
+                f,
+                "Nodeset uses schema version {} but this version only supports up to {}. Please update Power Control Center.",
+                found, supported
+            ),
+            SchemaError::Invalid(msg) => write!(f, "Invalid nodeset: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// v0 (unversioned) nodesets may be missing the nodes or edges arrays
+fn migrate_v0_to_v1(doc: &mut Map<String, Value>) {
+    doc.entry("nodes").or_insert_with(|| Value::Array(vec![]));
+    doc.entry("edges").or_insert_with(|| Value::Array(vec![]));
+}
+
+/// Get the schema version of a nodeset document (0 if unversioned)
+fn get_schema_version(doc: &Map<String, Value>) -> Result<u32, SchemaError> {
+    match doc.get("schema_version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| SchemaError::Invalid(format!("schema_version must be a non-negative integer, got {}", v))),
+    }
+}
+
+/// Upgrade a nodeset document to the current schema version in place
+/// Returns true if the document was changed
+pub fn migrate_nodeset(doc: &mut Value) -> Result<bool, SchemaError> {
+    let doc = doc
+        .as_object_mut()
+        .ok_or_else(|| SchemaError::Invalid("expected a JSON object".to_string()))?;
+
+    let version = get_schema_version(doc)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(SchemaError::NewerVersion {
+            found: version,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+    if version == CURRENT_SCHEMA_VERSION {
+        return Ok(false);
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(doc);
+    }
+    doc.insert("schema_version".to_string(), Value::from(CURRENT_SCHEMA_VERSION));
+    Ok(true)
+}
+
+/// Parse stored nodeset JSON and upgrade it to the current schema version
+pub fn parse_nodeset_json(json: &str) -> Result<Value, SchemaError> {
+    let mut doc: Value = serde_json::from_str(json).map_err(|e| SchemaError::Invalid(e.to_string()))?;
+    migrate_nodeset(&mut doc)?;
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        assert_eq!(MIGRATIONS.len(), CURRENT_SCHEMA_VERSION as usize);
+    }
+
+    #[test]
+    fn test_unversioned_nodeset_is_upgraded() {
+        let mut doc = json!({"nodes": [{"id": "start-1"}]});
+
+        assert!(migrate_nodeset(&mut doc).unwrap());
+        assert_eq!(doc["schema_version"], json!(CURRENT_SCHEMA_VERSION));
+        assert_eq!(doc["nodes"].as_array().unwrap().len(), 1);
+        assert_eq!(doc["edges"], json!([]));
+    }
+
+    #[test]
+    fn test_current_nodeset_is_unchanged() {
+        let mut doc = json!({"schema_version": CURRENT_SCHEMA_VERSION, "nodes": [], "edges": []});
+        let original = doc.clone();
+
+        assert!(!migrate_nodeset(&mut doc).unwrap());
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn test_newer_nodeset_is_refused() {
+        let json = format!(r#"{{"schema_version": {}, "nodes": [], "edges": []}}"#, CURRENT_SCHEMA_VERSION + 1);
+
+        match parse_nodeset_json(&json) {
+            Err(SchemaError::NewerVersion { found, supported }) => {
+                assert_eq!(found, CURRENT_SCHEMA_VERSION + 1);
+                assert_eq!(supported, CURRENT_SCHEMA_VERSION);
+            }
+            other => panic!("Expected NewerVersion error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_documents_are_rejected() {
+        assert!(matches!(parse_nodeset_json("not json"), Err(SchemaError::Invalid(_))));
+        assert!(matches!(parse_nodeset_json("[]"), Err(SchemaError::Invalid(_))));
+        assert!(matches!(
+            parse_nodeset_json(r#"{"schema_version": "one"}"#),
+            Err(SchemaError::Invalid(_))
+        ));
+    }
+}
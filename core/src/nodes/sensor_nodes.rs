@@ -0,0 +1,742 @@
+use super::node_system::{Node, NodeDefinition, NodeInput, NodeOutput, ValueType};
+
+/// PIR Detection node - checks PIR sensor detection status
+/// Checks a zone, a single sensor or a device, in that order of precedence, and outputs the
+/// detection status and time since the last detection. A zone counts the most recent of its sensors
+pub struct PirDetectionNode;
+
+impl Node for PirDetectionNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "pir_detection",
+            "PIR Detection",
+            "Checks PIR (motion sensor) detection status for a device, a single named sensor or a zone of sensors. Connect a PIR Zone or PIR Sensor node to check those instead of the device; a zone is triggered when any of its sensors is. Outputs whether motion was recently detected and how many minutes ago the last detection occurred.",
+            "Sensors",
+            vec![
+                NodeInput::new(
+                    "timeout_minutes",
+                    "Timeout Minutes",
+                    "Number of minutes to consider a detection as 'recent'",
+                    ValueType::Integer,
+                    true,
+                ),
+                NodeInput::new(
+                    "device",
+                    "Device",
+                    "The device to check PIR detection for, when no zone or sensor is connected",
+                    // Populated with the configured devices by the server
+                    ValueType::Enum(vec![]),
+                    false,
+                ),
+                NodeInput::new(
+                    "zone",
+                    "Zone",
+                    "Optional zone to check instead, triggered when any of its sensors is",
+                    // Populated with the configured zones by the server
+                    ValueType::Enum(vec![]),
+                    false,
+                ),
+                NodeInput::new(
+                    "sensor",
+                    "Sensor",
+                    "Optional sensor to check instead of the device",
+                    // Populated with the configured sensors by the server
+                    ValueType::Enum(vec![]),
+                    false,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "is_recently_triggered",
+                    "Is Recently Triggered",
+                    "True if PIR was triggered within the timeout period",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "last_detection_minutes_ago",
+                    "Last Detection Minutes Ago",
+                    "Number of minutes since the last PIR detection (or -1 if never detected)",
+                    ValueType::Integer,
+                ),
+            ],
+        )
+    }
+}
+
+/// Occupancy node - probability that a room is occupied, from the occupancy model
+/// Smooths PIR and door sensor events with exponential decay and a time-of-day prior, so it doesn't
+/// flap like the raw PIR state. Checks the evaluated device's room unless a device is connected
+pub struct OccupancyNode;
+
+impl Node for OccupancyNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "occupancy",
+            "Occupancy",
+            "Probability (0-1) that a device's room is occupied, inferred from its PIR detections and door sensor events with exponential decay and the time-of-day prior. Unlike PIR Detection it fades out gradually instead of flipping after a timeout. Checks the evaluated device unless a device is connected.",
+            "Sensors",
+            vec![
+                NodeInput::new(
+                    "threshold",
+                    "Threshold",
+                    "Probability from which the room counts as occupied, e.g. 0.5",
+                    ValueType::Float,
+                    true,
+                ),
+                NodeInput::new(
+                    "device",
+                    "Device",
+                    "Optional device whose room to check instead of the evaluated device",
+                    // Populated with the configured devices by the server
+                    ValueType::Enum(vec![]),
+                    false,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "probability",
+                    "Probability",
+                    "Occupancy probability of the room, from 0 to 1",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "is_occupied",
+                    "Is Occupied",
+                    "True if the probability is at least the threshold",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Contact Sensor node - whether a window or door of a room is open
+/// Reads the contact sensors configured for the room of the evaluated device, or of a connected device
+pub struct ContactSensorNode;
+
+impl Node for ContactSensorNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "contact_sensor",
+            "Contact Sensor",
+            "Whether a window or door contact sensor in a device's room reports open, and for how long, e.g. to stop heating while the veranda door is left open. Checks the evaluated device unless a device is connected.",
+            "Sensors",
+            vec![
+                NodeInput::new(
+                    "device",
+                    "Device",
+                    "Optional device whose room to check instead of the evaluated device",
+                    // Populated with the configured devices by the server
+                    ValueType::Enum(vec![]),
+                    false,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "is_open",
+                    "Is Open",
+                    "True if any window or door in the room is open",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "open_minutes",
+                    "Open Minutes",
+                    "Minutes the longest open window or door has been open, 0 when all are closed",
+                    ValueType::Integer,
+                ),
+            ],
+        )
+    }
+}
+
+/// Humidity node - exposes indoor and outdoor relative humidity
+/// Readings are -1 when unavailable, use the availability outputs to guard dehumidify logic
+pub struct HumidityNode;
+
+impl Node for HumidityNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "humidity",
+            "Humidity",
+            "Provides the relative humidity (%) measured by the device and the current outdoor relative humidity. Readings are -1 when unavailable (e.g. the controller has no humidity sensor).",
+            "Sensors",
+            vec![],
+            vec![
+                NodeOutput::new(
+                    "device_humidity",
+                    "Device Humidity",
+                    "Indoor relative humidity (%) reported by the device, or -1 if unavailable",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "is_device_humidity_available",
+                    "Device Humidity Available",
+                    "True if the device reported a humidity reading",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "outdoor_humidity",
+                    "Outdoor Humidity",
+                    "Current outdoor relative humidity (%) from the weather API, or -1 if unavailable",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "is_outdoor_humidity_available",
+                    "Outdoor Humidity Available",
+                    "True if the outdoor humidity is known",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Time of Day node - exposes the local time of the evaluation
+/// is_night covers 22:00 through 06:59
+pub struct TimeOfDayNode;
+
+impl Node for TimeOfDayNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "time_of_day",
+            "Time of Day",
+            "Provides the current local time. Use it to behave differently at night, e.g. a lower target temperature from 22:00. The simulator can evaluate the profile at another time.",
+            "Sensors",
+            vec![],
+            vec![
+                NodeOutput::new(
+                    "hour",
+                    "Hour",
+                    "Current local hour (0-23)",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "minute",
+                    "Minute",
+                    "Current minute of the hour (0-59)",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "minutes_since_midnight",
+                    "Minutes Since Midnight",
+                    "Minutes since local midnight (0-1439), convenient for comparing against times like 15:30 (930)",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "is_night",
+                    "Is Night",
+                    "True from 22:00 until 07:00",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Day of Week node - exposes the local weekday of the evaluation
+pub struct DayOfWeekNode;
+
+impl Node for DayOfWeekNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "day_of_week",
+            "Day of Week",
+            "Provides the current local day of the week.",
+            "Sensors",
+            vec![],
+            vec![
+                NodeOutput::new(
+                    "weekday",
+                    "Weekday",
+                    "Day of the week, 1 = Monday through 7 = Sunday",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "is_weekend",
+                    "Is Weekend",
+                    "True on Saturday and Sunday",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Energy Price node - exposes day-ahead electricity prices for dynamic tariffs
+/// Lets profiles pre-heat or pre-cool while power is cheap
+pub struct EnergyPriceNode;
+
+impl Node for EnergyPriceNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "energy_price",
+            "Energy Price",
+            "Provides the day-ahead electricity price of the current hour and how it ranks against the next 24 hours. Use Is Cheap Hour to pre-heat or pre-cool while power is cheap. Requires energy_price_bidding_zone in the config.",
+            "Sensors",
+            vec![
+                NodeInput::new(
+                    "cheap_hours",
+                    "Cheap Hours",
+                    "Number of cheapest hours of the next 24 hours that count as cheap",
+                    ValueType::Integer,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "current_price",
+                    "Current Price",
+                    "Wholesale price of the current hour in EUR/kWh (can be negative), or 0 if unavailable",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "is_price_available",
+                    "Price Available",
+                    "True if the price of the current hour is known",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "is_cheap_hour",
+                    "Is Cheap Hour",
+                    "True if the current hour is among the cheapest hours of the next 24 hours",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "cheap_hour_rank",
+                    "Cheap Hour Rank",
+                    "Rank of the current hour in the next 24 hours, 1 being the cheapest, or -1 if unavailable",
+                    ValueType::Integer,
+                ),
+            ],
+        )
+    }
+}
+
+/// Battery node - exposes the home battery's state of charge and power
+/// Lets profiles avoid heating or cooling from the battery, e.g. at night
+pub struct BatteryNode;
+
+impl Node for BatteryNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "battery",
+            "Battery",
+            "Provides the state of charge and charge/discharge power of the home battery. Use Is Discharging to avoid running the AC from the battery, e.g. at night. Requires battery_api_endpoint in the config.",
+            "Sensors",
+            vec![],
+            vec![
+                NodeOutput::new(
+                    "soc_percent",
+                    "State of Charge",
+                    "State of charge of the battery (%), or -1 if unavailable",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "power_watt",
+                    "Power (W)",
+                    "Battery power in watts, positive while discharging and negative while charging, or 0 if unavailable",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "is_discharging",
+                    "Is Discharging",
+                    "True while the battery supplies power to the home",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "is_battery_available",
+                    "Battery Available",
+                    "True if the battery status is known",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Power Budget node - exposes how much of the total AC power budget other devices use
+/// Lets profiles stay out of powerful mode while another room is already drawing most of the budget
+pub struct PowerBudgetNode;
+
+impl Node for PowerBudgetNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "power_budget",
+            "Power Budget",
+            "Provides how much of the configured power budget the other AC devices are estimated to draw and whether this device still fits. Commands that would exceed the budget are limited anyway; use this node to decide what to do instead. Requires power_budget_watt in the config.",
+            "Sensors",
+            vec![],
+            vec![
+                NodeOutput::new(
+                    "is_budget_enabled",
+                    "Budget Enabled",
+                    "True if a power budget is configured",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "budget_watt",
+                    "Budget (W)",
+                    "Most power all AC devices may draw together, or 0 if no budget is configured",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "other_devices_watt",
+                    "Other Devices (W)",
+                    "Estimated power drawn by the other AC devices",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "remaining_watt",
+                    "Remaining (W)",
+                    "Power left for this device, negative if the budget is already exceeded, or 0 if no budget is configured",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "can_turn_on",
+                    "Can Turn On",
+                    "True if this device can run without exceeding the budget",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "can_use_powerful",
+                    "Can Use Powerful",
+                    "True if this device can run in powerful mode without exceeding the budget",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Data Age node - reports how old the weather, meter or sensor data is
+/// Cached data and stale fallbacks after failed requests can be older than the evaluation,
+/// so profiles can refuse to act on data older than a threshold
+pub struct DataAgeNode;
+
+impl Node for DataAgeNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "data_age",
+            "Data Age",
+            "Outputs how many minutes ago the selected data was read. Use Is Fresh to skip actions when the weather, meter or sensor data is older than Max Age Minutes or unavailable.",
+            "Sensors",
+            vec![
+                NodeInput::new(
+                    "source",
+                    "Source",
+                    "The data to check, connect a Data Source node",
+                    ValueType::Enum(vec![
+                        "Weather".to_string(),
+                        "Meter".to_string(),
+                        "Sensor".to_string(),
+                    ]),
+                    true,
+                ),
+                NodeInput::new(
+                    "max_age_minutes",
+                    "Max Age Minutes",
+                    "Data older than this many minutes is not fresh",
+                    ValueType::Integer,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "age_minutes",
+                    "Age Minutes",
+                    "Minutes since the data was read, or -1 if unavailable",
+                    ValueType::Integer,
+                ),
+                NodeOutput::new(
+                    "is_fresh",
+                    "Is Fresh",
+                    "True if the data is available and at most Max Age Minutes old",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Weather Forecast node - exposes the hourly outdoor temperature forecast
+/// Lets profiles pre-heat or pre-cool ahead of a change in the weather
+pub struct WeatherForecastNode;
+
+impl Node for WeatherForecastNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "weather_forecast",
+            "Weather Forecast",
+            "Provides the hourly outdoor forecast for the next 48 hours. Use Min Next 12h or Temp In N Hours to pre-heat before a cold front arrives. Without a forecast, temperatures fall back to the current outdoor temperature.",
+            "Sensors",
+            vec![
+                NodeInput::new(
+                    "hours",
+                    "Hours",
+                    "Hours from now for Temp In N Hours and Solar In N Hours (1-48)",
+                    ValueType::Integer,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "min_next_12h",
+                    "Min Next 12h",
+                    "Lowest forecast outdoor temperature (°C) of the next 12 hours",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "max_next_12h",
+                    "Max Next 12h",
+                    "Highest forecast outdoor temperature (°C) of the next 12 hours",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "temp_in_n_hours",
+                    "Temp In N Hours",
+                    "Forecast outdoor temperature (°C) the given number of hours from now",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "solar_in_n_hours",
+                    "Solar In N Hours",
+                    "Forecast solar radiation (W/m²) the given number of hours from now, or 0 if unavailable",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "is_forecast_available",
+                    "Forecast Available",
+                    "True if the hourly forecast is known",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+/// Solar Forecast node - exposes the expected PV production of the coming hours
+/// Lets profiles defer heavy heating until the panels actually produce
+pub struct SolarForecastNode;
+
+impl Node for SolarForecastNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "solar_forecast",
+            "Solar Forecast",
+            "Provides the PV production expected for the current and next two hours, predicted from the radiation forecast on the panels. Use Is Ramping Up to wait with powerful heating until production increases. Requires solar_panels in the config.",
+            "Sensors",
+            vec![],
+            vec![
+                NodeOutput::new(
+                    "expected_next_3h_wh",
+                    "Expected Next 3h (Wh)",
+                    "Expected production (Wh) of the current and next two hours, or 0 if unavailable",
+                    ValueType::Float,
+                ),
+                NodeOutput::new(
+                    "is_ramping_up",
+                    "Is Ramping Up",
+                    "True if the expected average production of the next 3 hours exceeds the current production",
+                    ValueType::Boolean,
+                ),
+                NodeOutput::new(
+                    "is_forecast_available",
+                    "Forecast Available",
+                    "True if the solar forecast is known",
+                    ValueType::Boolean,
+                ),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pir_detection_node_definition() {
+        let def = PirDetectionNode::definition();
+        
+        assert_eq!(def.node_type, "pir_detection");
+        assert_eq!(def.name, "PIR Detection");
+        assert_eq!(def.category, "Sensors");
+        assert_eq!(def.inputs.len(), 4); // timeout_minutes, device, zone and sensor
+        assert_eq!(def.outputs.len(), 2); // is_recently_triggered and last_detection_minutes_ago
+        
+        // Verify inputs
+        let input_ids: Vec<&str> = def.inputs.iter().map(|i| i.id.as_str()).collect();
+        assert!(input_ids.contains(&"timeout_minutes"));
+        assert!(input_ids.contains(&"device"));
+        assert!(input_ids.contains(&"zone"));
+        assert!(input_ids.contains(&"sensor"));
+        
+        // Verify outputs
+        let output_ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert!(output_ids.contains(&"is_recently_triggered"));
+        assert!(output_ids.contains(&"last_detection_minutes_ago"));
+        
+        // Verify input types
+        let timeout_input = def.inputs.iter().find(|i| i.id == "timeout_minutes").unwrap();
+        assert_eq!(timeout_input.value_type, ValueType::Integer);
+        assert!(timeout_input.required);
+        
+        let device_input = def.inputs.iter().find(|i| i.id == "device").unwrap();
+        match &device_input.value_type {
+            ValueType::Enum(values) => assert!(values.is_empty()),
+            _ => panic!("Expected Enum type for device input"),
+        }
+        // Not required when a zone or sensor is checked instead
+        assert!(!device_input.required);
+        assert!(def.inputs.iter().filter(|i| i.id == "zone" || i.id == "sensor").all(|i| !i.required));
+        
+        // Verify output types
+        let triggered_output = def.outputs.iter().find(|o| o.id == "is_recently_triggered").unwrap();
+        assert_eq!(triggered_output.value_type, ValueType::Boolean);
+        
+        let minutes_output = def.outputs.iter().find(|o| o.id == "last_detection_minutes_ago").unwrap();
+        assert_eq!(minutes_output.value_type, ValueType::Integer);
+    }
+
+    #[test]
+    fn test_pir_detection_node_serializable() {
+        let def = PirDetectionNode::definition();
+        let json = serde_json::to_string(&def).unwrap();
+        let deserialized: NodeDefinition = serde_json::from_str(&json).unwrap();
+        
+        assert_eq!(def.node_type, deserialized.node_type);
+        assert_eq!(def.inputs.len(), deserialized.inputs.len());
+        assert_eq!(def.outputs.len(), deserialized.outputs.len());
+    }
+
+    #[test]
+    fn test_occupancy_node_definition() {
+        let def = OccupancyNode::definition();
+
+        assert_eq!(def.node_type, "occupancy");
+        assert_eq!(def.category, "Sensors");
+        let threshold_input = def.inputs.iter().find(|i| i.id == "threshold").unwrap();
+        assert_eq!(threshold_input.value_type, ValueType::Float);
+        assert!(threshold_input.required);
+        let device_input = def.inputs.iter().find(|i| i.id == "device").unwrap();
+        assert!(!device_input.required, "defaults to the evaluated device");
+
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["probability", "is_occupied"]);
+    }
+
+    #[test]
+    fn test_contact_sensor_node_definition() {
+        let def = ContactSensorNode::definition();
+
+        assert_eq!(def.node_type, "contact_sensor");
+        assert_eq!(def.category, "Sensors");
+        assert_eq!(def.inputs.len(), 1);
+        assert!(!def.inputs[0].required, "defaults to the evaluated device");
+
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["is_open", "open_minutes"]);
+    }
+
+    #[test]
+    fn test_humidity_node_definition() {
+        let def = HumidityNode::definition();
+
+        assert_eq!(def.node_type, "humidity");
+        assert_eq!(def.category, "Sensors");
+        assert!(def.inputs.is_empty());
+        assert_eq!(def.outputs.len(), 4);
+
+        let device_output = def.outputs.iter().find(|o| o.id == "device_humidity").unwrap();
+        assert_eq!(device_output.value_type, ValueType::Float);
+        let available_output = def.outputs.iter().find(|o| o.id == "is_device_humidity_available").unwrap();
+        assert_eq!(available_output.value_type, ValueType::Boolean);
+        let outdoor_output = def.outputs.iter().find(|o| o.id == "outdoor_humidity").unwrap();
+        assert_eq!(outdoor_output.value_type, ValueType::Float);
+    }
+
+    #[test]
+    fn test_time_nodes_definitions() {
+        let def = TimeOfDayNode::definition();
+        assert_eq!(def.node_type, "time_of_day");
+        assert_eq!(def.category, "Sensors");
+        assert!(def.inputs.is_empty());
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["hour", "minute", "minutes_since_midnight", "is_night"]);
+
+        let def = DayOfWeekNode::definition();
+        assert_eq!(def.node_type, "day_of_week");
+        let is_weekend = def.outputs.iter().find(|o| o.id == "is_weekend").unwrap();
+        assert_eq!(is_weekend.value_type, ValueType::Boolean);
+    }
+
+    #[test]
+    fn test_energy_price_node_definition() {
+        let def = EnergyPriceNode::definition();
+
+        assert_eq!(def.node_type, "energy_price");
+        assert_eq!(def.category, "Sensors");
+        assert_eq!(def.inputs.len(), 1);
+        assert_eq!(def.inputs[0].value_type, ValueType::Integer);
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["current_price", "is_price_available", "is_cheap_hour", "cheap_hour_rank"]);
+    }
+
+    #[test]
+    fn test_battery_node_definition() {
+        let def = BatteryNode::definition();
+
+        assert_eq!(def.node_type, "battery");
+        assert_eq!(def.category, "Sensors");
+        assert!(def.inputs.is_empty());
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["soc_percent", "power_watt", "is_discharging", "is_battery_available"]);
+    }
+
+    #[test]
+    fn test_power_budget_node_definition() {
+        let def = PowerBudgetNode::definition();
+
+        assert_eq!(def.node_type, "power_budget");
+        assert_eq!(def.category, "Sensors");
+        assert!(def.inputs.is_empty());
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["is_budget_enabled", "budget_watt", "other_devices_watt", "remaining_watt", "can_turn_on", "can_use_powerful"]
+        );
+    }
+
+    #[test]
+    fn test_data_age_node_definition() {
+        let def = DataAgeNode::definition();
+
+        assert_eq!(def.node_type, "data_age");
+        assert_eq!(def.category, "Sensors");
+        let inputs: Vec<&str> = def.inputs.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(inputs, vec!["source", "max_age_minutes"]);
+        let outputs: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(outputs, vec!["age_minutes", "is_fresh"]);
+    }
+
+    #[test]
+    fn test_weather_forecast_node_definition() {
+        let def = WeatherForecastNode::definition();
+        assert_eq!(def.node_type, "weather_forecast");
+        assert_eq!(def.category, "Sensors");
+        let hours = def.inputs.iter().find(|i| i.id == "hours").unwrap();
+        assert_eq!(hours.value_type, ValueType::Integer);
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["min_next_12h", "max_next_12h", "temp_in_n_hours", "solar_in_n_hours", "is_forecast_available"]);
+    }
+
+    #[test]
+    fn test_solar_forecast_node_definition() {
+        let def = SolarForecastNode::definition();
+        assert_eq!(def.node_type, "solar_forecast");
+        assert_eq!(def.category, "Sensors");
+        assert!(def.inputs.is_empty());
+        let ids: Vec<&str> = def.outputs.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, vec!["expected_next_3h_wh", "is_ramping_up", "is_forecast_available"]);
+    }
+}
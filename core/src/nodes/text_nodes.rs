@@ -0,0 +1,107 @@
+use super::node_system::{Node, NodeDefinition, NodeInput, NodeOutput, ValueType};
+
+/// Maximum number of decimals the Format Number node rounds to
+pub const MAX_FORMAT_DECIMALS: i64 = 6;
+
+/// Concat node - joins text values
+/// Has dynamic number of string input pins (minimum 2), like the AND node
+pub struct ConcatNode;
+
+impl Node for ConcatNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "text_concat",
+            "Concat",
+            "Joins the text of all inputs in order, e.g. into an annotation for Execute Action. Add or remove input pins with + and - buttons.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "input_1",
+                    "Input 1",
+                    "First text",
+                    ValueType::String,
+                    true,
+                ),
+                NodeInput::new(
+                    "input_2",
+                    "Input 2",
+                    "Second text",
+                    ValueType::String,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "text",
+                    "Text",
+                    "The inputs joined together",
+                    ValueType::String,
+                ),
+            ],
+        )
+    }
+}
+
+/// Format Number node - converts a number to text with a fixed number of decimals
+/// The value input accepts Float or Integer (constrained by the frontend)
+pub struct FormatNumberNode;
+
+impl Node for FormatNumberNode {
+    fn definition() -> NodeDefinition {
+        NodeDefinition::new(
+            "text_format_number",
+            "Format Number",
+            "Converts a number to text rounded to the given number of decimals (0 to 6), e.g. 3200 or 21.5.",
+            "Logic",
+            vec![
+                NodeInput::new(
+                    "value",
+                    "Value",
+                    "The number to format (Float or Integer)",
+                    ValueType::Any,
+                    true,
+                ),
+                NodeInput::new(
+                    "decimals",
+                    "Decimals",
+                    "Number of decimals, values outside 0 to 6 are clamped",
+                    ValueType::Integer,
+                    true,
+                ),
+            ],
+            vec![
+                NodeOutput::new(
+                    "text",
+                    "Text",
+                    "The formatted number",
+                    ValueType::String,
+                ),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_node_definitions() {
+        let def = ConcatNode::definition();
+        assert_eq!(def.node_type, "text_concat");
+        assert_eq!(def.category, "Logic");
+        assert_eq!(def.inputs.len(), 2); // Minimum 2 inputs
+        assert!(def.inputs.iter().all(|i| i.value_type == ValueType::String && i.required));
+        assert_eq!(def.outputs[0].value_type, ValueType::String);
+
+        let def = FormatNumberNode::definition();
+        assert_eq!(def.node_type, "text_format_number");
+        assert_eq!(def.category, "Logic");
+        let input_ids: Vec<&str> = def.inputs.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(input_ids, vec!["value", "decimals"]);
+        assert_eq!(def.inputs[0].value_type, ValueType::Any);
+        assert_eq!(def.inputs[1].value_type, ValueType::Integer);
+        assert_eq!(def.outputs.len(), 1);
+        assert_eq!(def.outputs[0].value_type, ValueType::String);
+    }
+}
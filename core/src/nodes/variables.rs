@@ -0,0 +1,154 @@
+//! Nodeset variables
+//!
+//! Named constants stored alongside a nodeset's nodes and edges, e.g. `"variables": {"comfort_min": 20.0}`.
+//! Variable nodes reference one by name. Values are resolved into the nodes when the nodeset is
+//! loaded, so a setpoint used in several places can be changed without rewiring the graph.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::execution::NODE_TYPE_VARIABLE;
+
+/// Variable values by name
+pub type Variables = BTreeMap<String, f64>;
+
+/// Node data key holding the variable name a Variable node references
+pub const VARIABLE_NAME_KEY: &str = "variableName";
+/// Node data key the resolved value is written to when the nodeset is loaded
+pub const RESOLVED_VALUE_KEY: &str = "resolvedValue";
+
+/// Longest allowed variable name
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Check that a variable name is an identifier: a letter or underscore followed by
+/// letters, digits or underscores
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid_start = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    if !valid_start || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!(
+            "Variable name '{}' must start with a letter or underscore and contain only letters, digits and underscores",
+            name
+        ));
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(format!("Variable name '{}' is longer than {} characters", name, MAX_NAME_LENGTH));
+    }
+    Ok(())
+}
+
+/// Check every variable name and value
+/// Returns a list of errors (empty if valid)
+pub fn validate_variables(variables: &Variables) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (name, value) in variables {
+        if let Err(e) = validate_name(name) {
+            errors.push(e);
+        }
+        if !value.is_finite() {
+            errors.push(format!("Variable '{}' must be a finite number", name));
+        }
+    }
+    errors
+}
+
+/// Get the variable name a node references, None if it isn't a Variable node
+fn referenced_name(node: &Value) -> Option<&str> {
+    let data = node.get("data")?;
+    let node_type = data.get("definition").and_then(|def| def.get("node_type")).and_then(|nt| nt.as_str());
+    if node_type != Some(NODE_TYPE_VARIABLE) {
+        return None;
+    }
+    Some(data.get(VARIABLE_NAME_KEY).and_then(|name| name.as_str()).unwrap_or_default())
+}
+
+/// Write the value of each referenced variable into its Variable node
+/// Nodes referencing an undefined variable are left without a value and fail when evaluated
+pub fn resolve_variables(nodes: &mut [Value], variables: &Variables) {
+    for node in nodes {
+        let Some(value) = referenced_name(node).map(|name| variables.get(name).copied()) else {
+            continue;
+        };
+        let Some(data) = node.get_mut("data").and_then(|d| d.as_object_mut()) else {
+            continue;
+        };
+        match value {
+            Some(value) => data.insert(RESOLVED_VALUE_KEY.to_string(), Value::from(value)),
+            None => data.remove(RESOLVED_VALUE_KEY),
+        };
+    }
+}
+
+/// Read the variables of a nodeset document, ignoring entries that aren't numbers
+pub fn variables_from_document(doc: &Value) -> Variables {
+    doc.get("variables")
+        .and_then(|v| v.as_object())
+        .map(|vars| {
+            vars.iter()
+                .filter_map(|(name, value)| value.as_f64().map(|value| (name.clone(), value)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolve the variables of a nodeset document into its nodes
+pub fn resolve_document(doc: &mut Value) {
+    let variables = variables_from_document(doc);
+    if let Some(nodes) = doc.get_mut("nodes").and_then(|n| n.as_array_mut()) {
+        resolve_variables(nodes, &variables);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn variable_node(id: &str, name: &str) -> Value {
+        json!({
+            "id": id,
+            "data": {
+                "definition": {"node_type": NODE_TYPE_VARIABLE},
+                "variableName": name
+            }
+        })
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("comfort_min").is_ok());
+        assert!(validate_name("_night2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("2fast").is_err());
+        assert!(validate_name("comfort min").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_variables_rejects_non_finite_values() {
+        let variables = Variables::from([("comfort_min".to_string(), 20.0), ("broken".to_string(), f64::NAN)]);
+        let errors = validate_variables(&variables);
+        assert_eq!(errors, vec!["Variable 'broken' must be a finite number"]);
+    }
+
+    #[test]
+    fn test_resolve_document() {
+        let mut doc = json!({
+            "variables": {"comfort_min": 20.5},
+            "nodes": [
+                variable_node("var-1", "comfort_min"),
+                variable_node("var-2", "missing"),
+                {"id": "float-1", "data": {"definition": {"node_type": "primitive_float"}}}
+            ],
+            "edges": []
+        });
+        doc["nodes"][1]["data"][RESOLVED_VALUE_KEY] = json!(1.0);
+
+        resolve_document(&mut doc);
+
+        assert_eq!(doc["nodes"][0]["data"][RESOLVED_VALUE_KEY], json!(20.5));
+        // A value left over from a removed variable doesn't survive
+        assert!(doc["nodes"][1]["data"].get(RESOLVED_VALUE_KEY).is_none());
+        assert!(doc["nodes"][2]["data"].get(RESOLVED_VALUE_KEY).is_none());
+    }
+}
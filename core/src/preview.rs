@@ -0,0 +1,90 @@
+//! Nodeset evaluation from JSON, for in-browser preview in the web editor
+//!
+//! With the `wasm` feature, `evaluate_nodeset` is exported to JavaScript. It runs the same
+//! schema migration and executor as the server, so previews match server-side results.
+
+use crate::nodes::{schema, variables, ExecutionInputs, ExecutionResult, NodesetExecutor};
+
+/// Evaluate a nodeset document against the given inputs
+///
+/// `nodeset_json` is a stored nodeset (`{"nodes": [...], "edges": [...], "variables": {...}}`) and `inputs_json`
+/// is a (partial) `ExecutionInputs` object. Missing inputs use their defaults.
+pub fn evaluate_nodeset_json(
+    nodeset_json: &str,
+    inputs_json: &str,
+    float_tolerance: f64,
+) -> Result<ExecutionResult, String> {
+    let mut nodeset = schema::parse_nodeset_json(nodeset_json).map_err(|e| e.to_string())?;
+    variables::resolve_document(&mut nodeset);
+    let inputs: ExecutionInputs =
+        serde_json::from_str(inputs_json).map_err(|e| format!("Invalid inputs: {}", e))?;
+
+    let empty = Vec::new();
+    let nodes = nodeset.get("nodes").and_then(|n| n.as_array()).unwrap_or(&empty);
+    let edges = nodeset.get("edges").and_then(|e| e.as_array()).unwrap_or(&empty);
+
+    let mut executor = NodesetExecutor::new(nodes, edges, inputs)
+        .map_err(|e| e.to_string())?
+        .with_float_tolerance(float_tolerance);
+    Ok(executor.execute())
+}
+
+/// JavaScript entry point. Returns `{"result": ExecutionResult}` or `{"error": "..."}` as JSON.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen(js_name = evaluateNodeset)]
+pub fn evaluate_nodeset(nodeset_json: &str, inputs_json: &str, float_tolerance: f64) -> String {
+    let response = match evaluate_nodeset_json(nodeset_json, inputs_json, float_tolerance) {
+        Ok(result) => serde_json::json!({ "result": result }),
+        Err(e) => serde_json::json!({ "error": e }),
+    };
+    response.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::execution::DEFAULT_FLOAT_TOLERANCE;
+
+    #[test]
+    fn test_invalid_nodeset_is_reported() {
+        let result = evaluate_nodeset_json("not json", "{}", DEFAULT_FLOAT_TOLERANCE);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_reported() {
+        let inputs = r#"{"device_sensor_temperature": "warm"}"#;
+        let result = evaluate_nodeset_json(r#"{"nodes": [], "edges": []}"#, inputs, DEFAULT_FLOAT_TOLERANCE);
+        assert!(result.unwrap_err().starts_with("Invalid inputs"));
+    }
+
+    #[test]
+    fn test_evaluate_default_nodeset() {
+        let nodeset = include_str!("../../defaults/default_nodeset.json");
+        let inputs = r#"{"device": "LivingRoom", "device_sensor_temperature": 22.0, "is_auto_mode": true}"#;
+
+        let result = evaluate_nodeset_json(nodeset, inputs, DEFAULT_FLOAT_TOLERANCE).unwrap();
+        assert!(result.completed, "Default nodeset should complete: {:?}", result.error);
+    }
+
+    #[test]
+    fn test_default_nodeset_follows_season() {
+        let nodeset = include_str!("../../defaults/default_nodeset.json");
+        let mode = |season: &str, temperature: f64| {
+            let inputs = format!(
+                r#"{{"device": "LivingRoom", "device_sensor_temperature": {}, "is_auto_mode": true, "is_user_home": true,
+                    "last_change_minutes": 60, "outdoor_temperature": 10.0, "season": "{}",
+                    "active_command": {{"is_defined": true, "is_on": true, "temperature": 21.0, "mode": 1, "fan_speed": 0, "swing": 0, "is_powerful": false}}}}"#,
+                temperature, season
+            );
+            let result = evaluate_nodeset_json(nodeset, &inputs, DEFAULT_FLOAT_TOLERANCE).unwrap();
+            result.action.map(|a| a.mode)
+        };
+
+        assert_eq!(mode("Heating", 15.0).as_deref(), Some("Heat"));
+        assert_eq!(mode("Cooling", 30.0).as_deref(), Some("Cool"));
+        // Heat-only and cool-only logic is gated by the season
+        assert_eq!(mode("Heating", 30.0).as_deref(), Some("Off"));
+        assert_eq!(mode("Cooling", 15.0).as_deref(), Some("Off"));
+    }
+}
@@ -0,0 +1,39 @@
+use serde::Deserialize;
+
+/// Per-device sensitivity for deciding whether a new command must be sent.
+/// Every IR command makes the unit beep, so minor differences can be ignored.
+/// On/off and mode changes always trigger a resend.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AcChangePolicy {
+    /// Temperature differences up to this many °C are not considered a change
+    #[serde(default = "default_change_temperature_tolerance")]
+    pub temperature_tolerance: f64,
+    /// Ignore fan speed differences
+    #[serde(default)]
+    pub ignore_fan_speed: bool,
+    /// Ignore swing and vane position differences
+    #[serde(default)]
+    pub ignore_swing: bool,
+    /// Ignore powerful mode differences
+    #[serde(default)]
+    pub ignore_powerful: bool,
+}
+
+/// Default temperature tolerance in Celsius for state change detection.
+/// If the temperature difference is within this tolerance, we skip sending a new command.
+pub const DEFAULT_TEMPERATURE_TOLERANCE: f64 = 0.5;
+
+fn default_change_temperature_tolerance() -> f64 {
+    DEFAULT_TEMPERATURE_TOLERANCE
+}
+
+impl Default for AcChangePolicy {
+    fn default() -> Self {
+        Self {
+            temperature_tolerance: default_change_temperature_tolerance(),
+            ignore_fan_speed: false,
+            ignore_swing: false,
+            ignore_powerful: false,
+        }
+    }
+}
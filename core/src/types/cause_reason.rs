@@ -3,6 +3,8 @@ use serde::{Serialize, Deserialize};
 /// Enum representing the reason/cause for an AC action or decision
 /// Each variant has a unique ID for database storage
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[repr(i32)]
 pub enum CauseReason {
     /// Default/undefined reason
@@ -29,6 +31,18 @@ pub enum CauseReason {
     /// When a device switches from manual control to automatic mode, we immediately send
     /// the appropriate command to establish the desired state
     ManualToAutoTransition = 7,
+    /// AC is OFF to shed load during a grid outage while running on backup power
+    GridOutage = 12,
+    /// The last command was re-sent by hand to verify the IR bridge
+    ManualReplay = 13,
+    /// A state was forced through the API, the nodeset is skipped until the override expires
+    ManualOverride = 14,
+    /// The action was limited to stay within the total AC power budget
+    PowerBudget = 15,
+    /// The heat pump runs briefly ahead of a forecast cold snap
+    ColdSnapPreheat = 16,
+    /// The AC was put in its shutdown state when the service stopped
+    Shutdown = 17,
 }
 
 impl CauseReason {
@@ -48,6 +62,12 @@ impl CauseReason {
             CauseReason::MajorTemperatureChangePending => "Major Temperature Change Pending",
             CauseReason::ExcessiveSolarPower => "Excessive Solar Power",
             CauseReason::ManualToAutoTransition => "Manual to Auto Transition",
+            CauseReason::GridOutage => "Grid Outage",
+            CauseReason::ManualReplay => "Manual Replay",
+            CauseReason::ManualOverride => "Manual Override",
+            CauseReason::PowerBudget => "Power Budget",
+            CauseReason::ColdSnapPreheat => "Cold Snap Preheat",
+            CauseReason::Shutdown => "Shutdown",
         }
     }
 
@@ -62,6 +82,12 @@ impl CauseReason {
             CauseReason::MajorTemperatureChangePending => "Operating at high intensity due to a significant temperature change forecast. The system is taking preemptive action to prepare for upcoming weather changes.",
             CauseReason::ExcessiveSolarPower => "Operating at high intensity (Powerful mode) to utilize excess solar power production. This aggressive climate control has minimal environmental and cost impact when solar production is high.",
             CauseReason::ManualToAutoTransition => "The AC device was switched from manual control to automatic mode. The system is sending the appropriate command to immediately establish the desired climate control state.",
+            CauseReason::GridOutage => "AC is OFF because a grid outage was detected and the system is running on backup power. Load is shed to preserve the UPS battery.",
+            CauseReason::ManualReplay => "The last command sent to the device was replayed by hand, e.g. to verify the IR bridge after moving it.",
+            CauseReason::ManualOverride => "The AC state was forced by hand for a limited time. The nodeset takes control again once the override expires.",
+            CauseReason::PowerBudget => "AC is OFF or out of Powerful mode because running as requested would exceed the total power budget shared by all AC devices.",
+            CauseReason::ColdSnapPreheat => "Heating briefly because extreme cold is forecast within 12 hours. The home is pre-warmed while the outdoor temperature still allows the heat pump to run efficiently.",
+            CauseReason::Shutdown => "Power Control Center was stopping, e.g. for a restart or update, and put the AC in the configured shutdown state so it isn't left running unattended.",
         }
     }
 
@@ -76,6 +102,12 @@ impl CauseReason {
             5 => CauseReason::MajorTemperatureChangePending,
             6 => CauseReason::ExcessiveSolarPower,
             7 => CauseReason::ManualToAutoTransition,
+            12 => CauseReason::GridOutage,
+            13 => CauseReason::ManualReplay,
+            14 => CauseReason::ManualOverride,
+            15 => CauseReason::PowerBudget,
+            16 => CauseReason::ColdSnapPreheat,
+            17 => CauseReason::Shutdown,
             _ => CauseReason::Undefined, // Default to Undefined for unknown IDs
         }
     }
@@ -95,6 +127,12 @@ mod tests {
         assert_eq!(CauseReason::MajorTemperatureChangePending.id(), 5);
         assert_eq!(CauseReason::ExcessiveSolarPower.id(), 6);
         assert_eq!(CauseReason::ManualToAutoTransition.id(), 7);
+        assert_eq!(CauseReason::GridOutage.id(), 12);
+        assert_eq!(CauseReason::ManualReplay.id(), 13);
+        assert_eq!(CauseReason::ManualOverride.id(), 14);
+        assert_eq!(CauseReason::PowerBudget.id(), 15);
+        assert_eq!(CauseReason::ColdSnapPreheat.id(), 16);
+        assert_eq!(CauseReason::Shutdown.id(), 17);
     }
 
     #[test]
@@ -128,6 +166,12 @@ mod tests {
         assert_eq!(CauseReason::from_id(5), CauseReason::MajorTemperatureChangePending);
         assert_eq!(CauseReason::from_id(6), CauseReason::ExcessiveSolarPower);
         assert_eq!(CauseReason::from_id(7), CauseReason::ManualToAutoTransition);
+        assert_eq!(CauseReason::from_id(12), CauseReason::GridOutage);
+        assert_eq!(CauseReason::from_id(13), CauseReason::ManualReplay);
+        assert_eq!(CauseReason::from_id(14), CauseReason::ManualOverride);
+        assert_eq!(CauseReason::from_id(15), CauseReason::PowerBudget);
+        assert_eq!(CauseReason::from_id(16), CauseReason::ColdSnapPreheat);
+        assert_eq!(CauseReason::from_id(17), CauseReason::Shutdown);
         assert_eq!(CauseReason::from_id(999), CauseReason::Undefined); // Unknown defaults to Undefined
     }
 
@@ -142,6 +186,12 @@ mod tests {
             CauseReason::MajorTemperatureChangePending,
             CauseReason::ExcessiveSolarPower,
             CauseReason::ManualToAutoTransition,
+            CauseReason::GridOutage,
+            CauseReason::ManualReplay,
+            CauseReason::ManualOverride,
+            CauseReason::PowerBudget,
+            CauseReason::ColdSnapPreheat,
+            CauseReason::Shutdown,
         ];
         for cause in causes {
             let id = cause.id();
@@ -0,0 +1,14 @@
+pub use webserver_types::*;
+pub mod webserver_types;
+
+pub use cause_reason::*;
+pub mod cause_reason;
+
+pub use ac_change_policy::*;
+pub mod ac_change_policy;
+
+pub use units::*;
+pub mod units;
+
+pub use season::*;
+pub mod season;
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use super::Celsius;
+
+/// Heating or cooling season, classified from the rolling average outdoor temperature
+/// Lets a single profile gate heat-only and cool-only logic on the Start node's Season output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Season {
+    /// Outdoors is cold enough that rooms need heating
+    Heating,
+    /// Outdoors is warm enough that rooms need cooling
+    Cooling,
+    /// Spring and fall weather between the two thresholds
+    #[default]
+    Neutral,
+}
+
+impl Season {
+    /// All seasons, in the order shown in Season enum pickers
+    pub const ALL: [Season; 3] = [Season::Heating, Season::Cooling, Season::Neutral];
+
+    /// Name of the season as used in node enum values
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Season::Heating => "Heating",
+            Season::Cooling => "Cooling",
+            Season::Neutral => "Neutral",
+        }
+    }
+
+    /// Names of all seasons, for enum ports
+    pub fn names() -> Vec<String> {
+        Self::ALL.iter().map(|s| s.as_str().to_string()).collect()
+    }
+
+    /// Classify a rolling average outdoor temperature
+    /// Below `heating_below` is Heating, above `cooling_above` is Cooling, anything between is Neutral
+    pub fn classify(average_outdoor: Celsius, heating_below: Celsius, cooling_above: Celsius) -> Season {
+        if average_outdoor < heating_below {
+            Season::Heating
+        } else if average_outdoor > cooling_above {
+            Season::Cooling
+        } else {
+            Season::Neutral
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_season() {
+        let classify = |avg| Season::classify(Celsius(avg), Celsius(17.0), Celsius(22.0));
+
+        assert_eq!(classify(5.0), Season::Heating);
+        assert_eq!(classify(16.9), Season::Heating);
+        assert_eq!(classify(17.0), Season::Neutral);
+        assert_eq!(classify(22.0), Season::Neutral);
+        assert_eq!(classify(22.1), Season::Cooling);
+    }
+
+    #[test]
+    fn test_season_names() {
+        assert_eq!(Season::names(), vec!["Heating", "Cooling", "Neutral"]);
+        assert_eq!(serde_json::to_string(&Season::Cooling).unwrap(), r#""Cooling""#);
+        assert_eq!(Season::default(), Season::Neutral);
+    }
+}
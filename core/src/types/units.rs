@@ -0,0 +1,64 @@
+//! Unit newtypes for values that flow between sensors, nodesets and AC state
+//!
+//! Temperatures, power and durations are all plain numbers on the wire, which makes it easy to
+//! pass a watts value where a temperature is expected. Wrapping them in distinct types turns such
+//! mix-ups into compile errors. Serde is transparent, so stored JSON and API payloads are unchanged.
+//! Unwrap with `.0` at the edges: RuntimeValue outputs, HTTP requests and database rows.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Temperature in degrees Celsius
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(transparent)]
+pub struct Celsius(pub f64);
+
+/// Power in watts (net power is positive while importing from the grid)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(transparent)]
+pub struct Watts(pub i64);
+
+/// Duration in whole minutes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(transparent)]
+pub struct Minutes(pub i64);
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}°C", self.0)
+    }
+}
+
+impl fmt::Display for Watts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} W", self.0)
+    }
+}
+
+impl fmt::Display for Minutes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} min", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units_serialize_as_plain_numbers() {
+        assert_eq!(serde_json::to_string(&Celsius(21.5)).unwrap(), "21.5");
+        assert_eq!(serde_json::to_string(&Watts(-800)).unwrap(), "-800");
+        assert_eq!(serde_json::from_str::<Minutes>("15").unwrap(), Minutes(15));
+    }
+
+    #[test]
+    fn test_units_display() {
+        assert_eq!(Celsius(21.5).to_string(), "21.5°C");
+        assert_eq!(Watts(1200).to_string(), "1200 W");
+        assert_eq!(Minutes(5).to_string(), "5 min");
+    }
+}
@@ -4,10 +4,9 @@ pub type ApiError = ApiResponse<()>;
 
 /// used by webserver::json_response()
 #[derive(Serialize, Deserialize)]
-pub struct ApiResponse<T>
-where
-    T: Serialize,
-{
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
@@ -0,0 +1,232 @@
+// Typed API client
+// Response types in ./types are generated from the Rust types, see "API Client" in the README.
+// Regenerate them after changing an API type instead of editing them by hand.
+
+/**
+ * @template T
+ * @typedef {import('./types/ApiResponse').ApiResponse<T>} ApiResponse
+ */
+
+/**
+ * Send a request and parse the standard API response envelope
+ * @template T
+ * @param {string} path
+ * @param {RequestInit} [init]
+ * @returns {Promise<ApiResponse<T>>}
+ */
+export async function apiRequest(path, init) {
+  const response = await fetch(path, init);
+  return response.json();
+}
+
+/**
+ * POST a JSON body
+ * @template T
+ * @param {string} path
+ * @param {unknown} body
+ * @returns {Promise<ApiResponse<T>>}
+ */
+export function apiPost(path, body) {
+  return apiRequest(path, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+}
+
+/** @returns {Promise<ApiResponse<import('./types/DashboardStatus').DashboardStatus>>} */
+export function getDashboardStatus() {
+  return apiRequest('/api/dashboard/status');
+}
+
+/**
+ * @param {string} device
+ * @param {number} limit
+ * @returns {Promise<ApiResponse<import('./types/CycleEntry').CycleEntry[]>>}
+ */
+export function getCycles(device, limit = 20) {
+  return apiRequest(`/api/dashboard/cycles?device=${encodeURIComponent(device)}&limit=${limit}`);
+}
+
+/**
+ * @param {string | undefined} device - All devices if undefined
+ * @param {number} limit
+ * @returns {Promise<ApiResponse<import('./types/ExecutionEntry').ExecutionEntry[]>>}
+ */
+export function getExecutions(device, limit = 50) {
+  const deviceParam = device ? `device=${encodeURIComponent(device)}&` : '';
+  return apiRequest(`/api/nodes/executions?${deviceParam}limit=${limit}`);
+}
+
+/** @returns {Promise<ApiResponse<import('./types/LiveInputs').LiveInputs>>} */
+export function getSimulatorLiveInputs() {
+  return apiRequest('/api/simulator/live-inputs');
+}
+
+/**
+ * @param {import('./types/SimulatorInputs').SimulatorInputs} inputs
+ * @returns {Promise<ApiResponse<import('./types/SimulatorResult').SimulatorResult>>}
+ */
+export function evaluateSimulator(inputs) {
+  return apiPost('/api/simulator/evaluate', inputs);
+}
+
+/** @returns {Promise<ApiResponse<import('./types/DemandResponseData').DemandResponseData>>} */
+export function getDemandResponseStatus() {
+  return apiRequest('/api/demand-response/status');
+}
+
+/** @returns {Promise<ApiResponse<import('./types/PirDeviceState').PirDeviceState[]>>} */
+export function getPirState() {
+  return apiRequest('/api/pir/state');
+}
+
+/**
+ * @param {string} device
+ * @returns {Promise<ApiResponse<string>>}
+ */
+export function clearPirDetection(device) {
+  return apiRequest(`/api/pir/${encodeURIComponent(device)}`, { method: 'DELETE' });
+}
+
+/** @returns {Promise<ApiResponse<import('./types/DeviceInfo').DeviceInfo[]>>} */
+export function getDevices() {
+  return apiRequest('/api/devices');
+}
+
+/**
+ * @param {string} device
+ * @param {string} displayName
+ * @returns {Promise<ApiResponse<import('./types/DeviceInfo').DeviceInfo>>}
+ */
+export function setDeviceDisplayName(device, displayName) {
+  return apiRequest(`/api/devices/${encodeURIComponent(device)}/display-name`, {
+    method: 'PUT',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ display_name: displayName }),
+  });
+}
+
+/**
+ * @param {string} device
+ * @returns {Promise<ApiResponse<string>>}
+ */
+export function removeDeviceDisplayName(device) {
+  return apiRequest(`/api/devices/${encodeURIComponent(device)}/display-name`, { method: 'DELETE' });
+}
+
+/**
+ * @param {string} level - Least severe level to include, e.g. 'warn'
+ * @param {string} adminKey - The configured `admin_api_key`
+ * @returns {Promise<ApiResponse<import('./types/LogEntry').LogEntry[]>>}
+ */
+export function getLogs(level, adminKey) {
+  return apiRequest(`/api/logs?level=${encodeURIComponent(level)}`, {
+    headers: { Authorization: `Bearer ${adminKey}` },
+  });
+}
+
+// Delay before reconnecting a dropped log stream
+const LOG_STREAM_RETRY_MS = 5000;
+
+/**
+ * Tail the log records on /api/logs/stream, reconnecting when the stream drops.
+ * Read with fetch rather than EventSource, which can't send the admin key.
+ * @param {string} level - Least severe level to include, e.g. 'warn'
+ * @param {string} adminKey - The configured `admin_api_key`
+ * @param {(entry: import('./types/LogEntry').LogEntry) => void} onEntry
+ * @returns {() => void} Closes the stream
+ */
+export function subscribeLogs(level, adminKey, onEntry) {
+  const controller = new AbortController();
+
+  async function connect() {
+    try {
+      const response = await fetch(`/api/logs/stream?level=${encodeURIComponent(level)}`, {
+        headers: { Authorization: `Bearer ${adminKey}` },
+        signal: controller.signal,
+      });
+      if (!response.ok) {
+        // A wrong key won't get better by retrying
+        console.error('Log stream refused:', response.status);
+        return;
+      }
+      const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+      let buffered = '';
+      for (;;) {
+        const { value, done } = await reader.read();
+        if (done) break;
+        buffered += value;
+        const events = buffered.split('\n\n');
+        buffered = events.pop();
+        for (const event of events) {
+          const data = event
+            .split('\n')
+            .filter((line) => line.startsWith('data:'))
+            .map((line) => line.slice(5).trimStart())
+            .join('\n');
+          if (!data) continue;
+          try {
+            onEntry(JSON.parse(data));
+          } catch (e) {
+            console.error('Invalid log record:', e);
+          }
+        }
+      }
+    } catch (e) {
+      if (controller.signal.aborted) return;
+      console.error('Log stream dropped:', e);
+    }
+    if (!controller.signal.aborted) {
+      setTimeout(connect, LOG_STREAM_RETRY_MS);
+    }
+  }
+
+  connect();
+  return () => controller.abort();
+}
+
+/**
+ * Subscribe to the live event stream on /api/ws, reconnecting with backoff when it drops
+ * @param {(event: import('./types/LiveEvent').LiveEvent) => void} onEvent
+ * @param {(connected: boolean) => void} [onConnectionChange]
+ * @returns {() => void} Closes the stream
+ */
+export function subscribeLiveEvents(onEvent, onConnectionChange = () => {}) {
+  const protocol = location.protocol === 'https:' ? 'wss:' : 'ws:';
+  /** @type {WebSocket | null} */
+  let socket = null;
+  /** @type {ReturnType<typeof setTimeout> | null} */
+  let reconnectTimer = null;
+  let retryDelayMs = 1000;
+  let closed = false;
+
+  function connect() {
+    socket = new WebSocket(`${protocol}//${location.host}/api/ws`);
+    socket.onopen = () => {
+      retryDelayMs = 1000;
+      onConnectionChange(true);
+    };
+    socket.onmessage = (message) => {
+      try {
+        onEvent(JSON.parse(message.data));
+      } catch (e) {
+        console.error('Invalid live event:', e);
+      }
+    };
+    socket.onclose = () => {
+      onConnectionChange(false);
+      if (!closed) {
+        reconnectTimer = setTimeout(connect, retryDelayMs);
+        retryDelayMs = Math.min(retryDelayMs * 2, 30000);
+      }
+    };
+  }
+
+  connect();
+  return () => {
+    closed = true;
+    if (reconnectTimer) clearTimeout(reconnectTimer);
+    socket?.close();
+  };
+}
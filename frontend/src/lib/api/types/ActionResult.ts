@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Action parameters when Execute Action node is reached
+ */
+export type ActionResult = { device: string, temperature: number, mode: string, fan_speed: string, is_powerful: boolean, enable_swing: boolean, cause_reason: string, 
+/**
+ * Vane position names from the optional vane inputs, e.g. "Position 2"
+ */
+vertical_vane: string | null, horizontal_vane: string | null, 
+/**
+ * Text from the optional annotation input, e.g. "preheat: solar=3200W"
+ */
+annotation: string | null, };
@@ -0,0 +1,39 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Data for the Active Command - represents the last command sent to a device
+ */
+export type ActiveCommandData = { 
+/**
+ * Whether an active command exists (a command was previously sent)
+ */
+is_defined: boolean, 
+/**
+ * Whether the AC is currently on (based on last command)
+ */
+is_on: boolean, 
+/**
+ * Target temperature in Celsius from the last command
+ */
+temperature: number, 
+/**
+ * AC mode: 0 = Off, 1 = Cool, 2 = Dry, 3 = Fan, 4 = Heat
+ */
+mode: number, 
+/**
+ * Fan speed setting (0-5, where 0 is auto)
+ */
+fan_speed: number, 
+/**
+ * Swing setting (0 = off, 1 = on)
+ */
+swing: number, 
+/**
+ * Whether powerful/turbo mode was enabled
+ */
+is_powerful: boolean, 
+/**
+ * Temperature the controller aims for: the profile's temperature plus the setpoint offset,
+ * before `temperature` was rounded to the step the AC supports
+ */
+target_temperature: number, };
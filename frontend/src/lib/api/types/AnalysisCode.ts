@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of problem an analysis warning reports
+ */
+export type AnalysisCode = "unreachable_node" | "type_mismatch" | "constant_condition" | "dead_sequence_output";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnalysisCode } from "./AnalysisCode";
+
+/**
+ * A problem found in a nodeset that doesn't stop it from running
+ */
+export type AnalysisWarning = { code: AnalysisCode, 
+/**
+ * Nodes to highlight, the node the warning is about first
+ */
+node_ids: Array<string>, message: string, };
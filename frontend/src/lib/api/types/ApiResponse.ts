@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * used by webserver::json_response()
+ */
+export type ApiResponse<T> = { success: boolean, data: T | null, error: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Enum representing the reason/cause for an AC action or decision
+ * Each variant has a unique ID for database storage
+ */
+export type CauseReason = "Undefined" | "IceException" | "PirDetection" | "NobodyHome" | "MildTemperature" | "MajorTemperatureChangePending" | "ExcessiveSolarPower" | "ManualToAutoTransition" | "GridOutage" | "ManualReplay" | "ManualOverride" | "PowerBudget" | "ColdSnapPreheat" | "Shutdown";
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * State of a window or door contact sensor
+ */
+export type ContactSensorStatus = { name: string, 
+/**
+ * Device whose room the window or door is in
+ */
+device: string, 
+/**
+ * Whether the sensor last reported open, false if it hasn't reported since startup
+ */
+is_open: boolean, 
+/**
+ * Unix timestamp (seconds) the sensor last opened or closed, None if it hasn't reported since startup
+ */
+changed_at: number | null, };
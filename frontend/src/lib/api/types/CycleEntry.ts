@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputChange } from "./InputChange";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * A control cycle with the inputs that changed since the previous cycle of the device
+ */
+export type CycleEntry = { evaluated_at: number, decision: string, inputs: { [key in string]?: JsonValue }, 
+/**
+ * Inputs that changed since the previous cycle, None if there is no earlier cycle
+ */
+changed: Array<InputChange> | null, };
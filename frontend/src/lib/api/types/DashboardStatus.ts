@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeviceStatus } from "./DeviceStatus";
+
+export type DashboardStatus = { devices: Array<DeviceStatus>, outdoor_temp: number | null, outdoor_temp_trend: number | null, solar_production_watts: number | null, current_consumption_watt: number | null, current_production_watt: number | null, net_power_w: number | null, pir_timeout_minutes: number, user_is_home: boolean, user_home_override_until: number | null, possible_grid_outage: boolean, 
+/**
+ * Whether the UPS reports running on battery (None if no recent UPS signal)
+ */
+ups_on_battery: boolean | null, 
+/**
+ * Whether the controller is in deep idle with lengthened polling intervals
+ */
+deep_idle: boolean, 
+/**
+ * Whether nodesets are evaluated without sending AC commands
+ */
+shadow_mode: boolean, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Age of the data behind the inputs, None if the data is unavailable
+ * Cached and stale fallback data can be older than the evaluation, see the Data Age node
+ */
+export type DataAges = { 
+/**
+ * Minutes since the outdoor weather was fetched
+ */
+weather: number | null, 
+/**
+ * Minutes since the smart meter was read
+ */
+meter: number | null, 
+/**
+ * Minutes since the device sensor was read
+ */
+sensor: number | null, };
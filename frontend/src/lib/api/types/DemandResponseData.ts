@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Demand-response state provided by the utility aggregator
+ */
+export type DemandResponseData = { 
+/**
+ * Whether a demand-response event is currently in progress
+ */
+event_active: boolean, 
+/**
+ * Curtailment level of the current or upcoming event (0 if none)
+ */
+level: number, 
+/**
+ * Minutes until the next event starts (0 while active, -1 if none scheduled)
+ */
+minutes_until_event: number, };
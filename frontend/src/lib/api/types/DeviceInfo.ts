@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Identifier and display name of a device
+ */
+export type DeviceInfo = { 
+/**
+ * Identifier used in config, nodesets and history
+ */
+device: string, 
+/**
+ * Name shown to the user, the identifier if no display name is set
+ */
+display_name: string, 
+/**
+ * Base URL of the device's IR controller
+ */
+endpoint: string, };
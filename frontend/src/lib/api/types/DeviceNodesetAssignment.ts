@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Nodeset a device runs
+ */
+export type DeviceNodesetAssignment = { device: string, 
+/**
+ * Name shown to the user, `device` if no display name is set
+ */
+display_name: string, 
+/**
+ * Nodeset assigned to the device, None if it runs the active nodeset
+ */
+nodeset_id: number | null, 
+/**
+ * Nodeset the device actually runs
+ */
+effective_nodeset_id: number, 
+/**
+ * Evaluation interval of the device, None if it follows the Start node of the active nodeset
+ */
+evaluate_every_minutes: number | null, };
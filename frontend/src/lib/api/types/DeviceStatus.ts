@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DeviceStatus = { name: string, 
+/**
+ * Name shown to the user, `name` if no display name is set
+ */
+display_name: string, is_on: boolean, mode: string | null, 
+/**
+ * Temperature sent to the AC, rounded to the step it supports
+ */
+temperature_setpoint: number | null, 
+/**
+ * Temperature the controller aims for, may differ from `temperature_setpoint` by the rounding
+ */
+target_temperature: number | null, indoor_temperature: number | null, 
+/**
+ * Median-filtered indoor temperature used by the profile at the last evaluation
+ */
+filtered_indoor_temperature: number | null, fan_speed: number | null, swing: number | null, powerful_mode: boolean, is_automatic_mode: boolean, last_pir_detection: number | null, 
+/**
+ * Whether the latest indoor temperature changed implausibly fast and is being ignored
+ */
+sensor_anomaly: boolean, 
+/**
+ * How the AC disagreed with its last command after it was read back, empty if it was applied
+ */
+command_not_applied: Array<string>, };
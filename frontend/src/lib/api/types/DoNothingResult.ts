@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Do Nothing parameters when Do Nothing node is reached (for debugging/simulation)
+ */
+export type DoNothingResult = { device: string, cause_reason: string, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Day-ahead electricity prices for dynamic tariffs
+ */
+export type EnergyPriceData = { 
+/**
+ * Wholesale price of the current hour in EUR/kWh, None if unavailable
+ */
+current_price_eur_kwh: number | null, 
+/**
+ * Upcoming hours with a known price as offsets from the current hour (0 = current hour, up to 23),
+ * sorted from cheapest to most expensive
+ */
+cheapest_hours_next_24h: Array<number>, };
@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionResult } from "./ActionResult";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Outcome of one nodeset execution
+ */
+export type ExecutionEntry = { device: string, executed_at: number, nodeset_id: number, 
+/**
+ * Revision of the nodeset that ran, None for executions recorded before revisions existed
+ */
+nodeset_revision: number | null, 
+/**
+ * Terminal node reached, None if the execution didn't reach one
+ */
+terminal_type: string | null, 
+/**
+ * Action parameters when an Execute Action node was reached
+ */
+action: ActionResult | null, 
+/**
+ * Cause reason ID of the action or Do Nothing node
+ */
+cause_reason: string | null, error: string | null, inputs: { [key in string]?: JsonValue }, 
+/**
+ * Warnings of the execution, e.g. disconnected nodes or an unknown cause reason
+ */
+warnings: Array<string>, 
+/**
+ * Executed in shadow mode, the action was not sent to the AC
+ */
+is_shadow: boolean, 
+/**
+ * How the state the AC reported after the command differed from it, empty if the command was
+ * applied and None if it wasn't read back
+ */
+command_discrepancies: Array<string> | null, };
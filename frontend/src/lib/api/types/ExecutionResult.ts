@@ -0,0 +1,41 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionResult } from "./ActionResult";
+import type { DoNothingResult } from "./DoNothingResult";
+import type { NotificationResult } from "./NotificationResult";
+
+/**
+ * Result of executing a nodeset
+ */
+export type ExecutionResult = { 
+/**
+ * Whether the execution reached a terminal node
+ */
+completed: boolean, 
+/**
+ * The type of terminal node reached
+ */
+terminal_type: string | null, 
+/**
+ * If Execute Action, the action parameters
+ */
+action: ActionResult | null, 
+/**
+ * If Do Nothing, the do_nothing parameters (for debugging/simulation)
+ */
+do_nothing: DoNothingResult | null, 
+/**
+ * Any error that occurred during execution
+ */
+error: string | null, 
+/**
+ * Validation warnings (e.g., disconnected nodes)
+ */
+warnings: Array<string>, 
+/**
+ * Whether the active command should be reset to undefined state
+ */
+reset_active_command: boolean, 
+/**
+ * Notifications queued by Notify nodes on the path to the terminal node
+ */
+notifications: Array<NotificationResult>, };
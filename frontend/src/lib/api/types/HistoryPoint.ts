@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type HistoryPoint = { 
+/**
+ * Unix timestamp of the sample, or of the start of the averaged period
+ */
+timestamp: number, value: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * An input whose value differs from the previous cycle
+ */
+export type InputChange = { key: string, 
+/**
+ * Value in the previous cycle, None if the input didn't exist yet
+ */
+previous: JsonValue | null, 
+/**
+ * Value in this cycle, None if the input no longer exists
+ */
+current: JsonValue | null, };
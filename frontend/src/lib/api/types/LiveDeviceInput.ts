@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Live inputs for a specific device
+ */
+export type LiveDeviceInput = { name: string, temperature: number | null, 
+/**
+ * Relative humidity in %, None if the controller has no humidity sensor
+ */
+humidity: number | null, is_auto_mode: boolean, pir_recently_triggered: boolean, pir_minutes_ago: number | null, 
+/**
+ * Occupancy probability (0-1) of the device's room
+ */
+occupancy_probability: number, 
+/**
+ * Whether a window or door in the device's room is open
+ */
+is_window_open: boolean, last_change_minutes: number | null, };
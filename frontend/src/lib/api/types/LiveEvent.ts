@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An event pushed to dashboard clients, serialized with its kind in `type`
+ */
+export type LiveEvent = { "type": "execution", device: string, timestamp: number, 
+/**
+ * Short description of the decision, e.g. "Execute Action: Heat 21°C"
+ */
+decision: string, error: string | null, 
+/**
+ * Evaluated in shadow mode, no command was sent
+ */
+is_shadow: boolean, } | { "type": "ac_state", device: string, timestamp: number, is_on: boolean, mode: string | null, temperature_setpoint: number | null, fan_speed: number | null, swing: number | null, powerful_mode: boolean, } | { "type": "pir_detection", device: string, timestamp: number, } | { "type": "meter_reading", timestamp: number, consumption_watt: number, production_watt: number, 
+/**
+ * Positive while importing from the grid
+ */
+net_power_watt: number, } | { "type": "state_mismatch", device: string, timestamp: number, mismatches: Array<string>, } | { "type": "command_not_applied", device: string, timestamp: number, discrepancies: Array<string>, } | { "type": "schedule_transition", timestamp: number, schedule_id: number, schedule_name: string, 
+/**
+ * Device whose assignment was switched, None for the active nodeset
+ */
+device: string | null, nodeset_id: number, 
+/**
+ * Why the nodeset could not be switched
+ */
+error: string | null, };
@@ -0,0 +1,91 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EnergyPriceData } from "./EnergyPriceData";
+import type { LiveDeviceInput } from "./LiveDeviceInput";
+import type { LocalTimeData } from "./LocalTimeData";
+import type { Season } from "./Season";
+import type { WeatherForecastData } from "./WeatherForecastData";
+
+/**
+ * Live inputs from the current environment
+ */
+export type LiveInputs = { 
+/**
+ * All configured devices
+ */
+devices: Array<LiveDeviceInput>, 
+/**
+ * Current solar production in watts (raw solar)
+ */
+solar_production: number | null, 
+/**
+ * Current outdoor temperature
+ */
+outdoor_temp: number | null, 
+/**
+ * Average outdoor temperature for the next 24 hours
+ */
+avg_next_24h_outdoor_temp: number | null, 
+/**
+ * Lowest outdoor temperature for the next 12 hours
+ */
+forecast_min_next_12h_temp: number | null, 
+/**
+ * Hourly forecast for the next 48 hours, empty if unavailable
+ */
+weather_forecast: WeatherForecastData, 
+/**
+ * Expected PV production of the next 3 hours in Wh, None if no panels are configured
+ */
+expected_solar_next_3h_wh: number | null, 
+/**
+ * Current heating or cooling season
+ */
+season: Season, 
+/**
+ * Whether user is home
+ */
+user_is_home: boolean, 
+/**
+ * Number of people home according to presence detection
+ */
+people_home_count: number, 
+/**
+ * Current net power in watts (positive = consuming, negative = exporting)
+ */
+net_power_watt: number | null, 
+/**
+ * Whether the grid appears to be down
+ */
+possible_grid_outage: boolean, 
+/**
+ * Whether a demand-response event is in progress
+ */
+dr_event_active: boolean, 
+/**
+ * Curtailment level of the current or upcoming demand-response event
+ */
+dr_level: number, 
+/**
+ * Minutes until the next demand-response event (-1 if none scheduled)
+ */
+dr_minutes_until_event: number, 
+/**
+ * Current outdoor relative humidity in %
+ */
+outdoor_humidity: number | null, 
+/**
+ * Current local time
+ */
+local_time: LocalTimeData, 
+/**
+ * Current day-ahead energy prices
+ */
+energy_price: EnergyPriceData, 
+/**
+ * Current home battery state of charge in %
+ */
+battery_soc_percent: number | null, 
+/**
+ * Current home battery power in watts (positive = discharging, negative = charging)
+ */
+battery_power_watt: number | null, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Local wall-clock time of an evaluation
+ * Plain numbers so the simulator can evaluate a nodeset as if it were another time of day
+ */
+export type LocalTimeData = { 
+/**
+ * Hour of the day (0-23)
+ */
+hour: number, 
+/**
+ * Minute of the hour (0-59)
+ */
+minute: number, 
+/**
+ * Day of the week, 1 = Monday through 7 = Sunday
+ */
+weekday: number, };
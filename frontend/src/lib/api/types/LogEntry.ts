@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogLevel } from "./LogLevel";
+
+/**
+ * A log record as returned by `GET /api/logs`
+ */
+export type LogEntry = { 
+/**
+ * Increases by one per record since startup, used as the SSE event ID
+ */
+id: number, 
+/**
+ * Unix timestamp in milliseconds
+ */
+timestamp_ms: number, level: LogLevel, 
+/**
+ * Module path the record was logged from, e.g. "power_control_center::ac_controller::ac_executor"
+ */
+module: string, message: string, };
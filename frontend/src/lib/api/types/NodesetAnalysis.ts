@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnalysisWarning } from "./AnalysisWarning";
+
+/**
+ * Result of analyzing a nodeset
+ */
+export type NodesetAnalysis = { 
+/**
+ * Problems that stop the nodeset from running, see `validate_nodeset_for_execution`
+ */
+errors: Array<string>, warnings: Array<AnalysisWarning>, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Pin names a nodeset offers to Call Profile nodes
+ */
+export type NodesetInterface = { 
+/**
+ * Names of the Profile Input nodes, sorted
+ */
+inputs: Array<string>, 
+/**
+ * Names of the Profile Output nodes, sorted
+ */
+outputs: Array<string>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Notification queued by a Notify node, with placeholders already filled in
+ * Sending and cooldown tracking are up to the caller
+ */
+export type NotificationResult = { 
+/**
+ * ID of the Notify node, used to track its cooldown
+ */
+node_id: string, channel: string, title: string, message: string, cooldown_minutes: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * PIR detection state of a device
+ */
+export type PirDeviceState = { device: string, 
+/**
+ * Unix timestamp (seconds) of the last detection, None if there was none since startup
+ */
+last_detection: number | null, 
+/**
+ * Whether the detection is recent enough to keep the AC off
+ */
+is_suppressed: boolean, 
+/**
+ * Unix timestamp (seconds) at which the suppression ends, None if not suppressed
+ */
+suppressed_until: number | null, };
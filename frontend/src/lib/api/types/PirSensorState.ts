@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * PIR detection state of a named sensor
+ */
+export type PirSensorState = { sensor_id: string, 
+/**
+ * Zone from `pir_sensors`, None for sensors without one (e.g. registered on their first detection)
+ */
+zone: string | null, 
+/**
+ * Device whose AC a detection turns off
+ */
+device: string | null, 
+/**
+ * Unix timestamp (seconds) of the last detection, None if there was none since startup
+ */
+last_detection: number | null, 
+/**
+ * Whether the last detection is within `pir_timeout_minutes`
+ */
+is_recently_triggered: boolean, };
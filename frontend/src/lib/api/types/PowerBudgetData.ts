@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Share of the total AC power budget, estimated from the tracked state of every device
+ */
+export type PowerBudgetData = { 
+/**
+ * Whether a power budget is configured
+ */
+is_enabled: boolean, 
+/**
+ * Most power all devices may draw together, in watts (0 if disabled)
+ */
+budget_watt: number, 
+/**
+ * Estimated draw of the other devices, in watts
+ */
+other_devices_watt: number, 
+/**
+ * Estimated draw of the evaluated device while running, in watts
+ */
+device_watt: number, 
+/**
+ * Estimated draw of the evaluated device in powerful mode, in watts
+ */
+device_powerful_watt: number, };
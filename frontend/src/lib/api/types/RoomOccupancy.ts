@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Occupancy of a device's room
+ */
+export type RoomOccupancy = { device: string, 
+/**
+ * Probability (0-1) that the room is occupied, the evidence combined with the prior
+ */
+probability: number, 
+/**
+ * Decayed evidence (0-1) of the PIR and door sensor events
+ */
+evidence: number, 
+/**
+ * Prior (0-1) for the current local hour
+ */
+prior: number, };
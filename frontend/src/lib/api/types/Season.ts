@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Heating or cooling season, classified from the rolling average outdoor temperature
+ * Lets a single profile gate heat-only and cool-only logic on the Start node's Season output
+ */
+export type Season = "Heating" | "Cooling" | "Neutral";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HistoryPoint } from "./HistoryPoint";
+
+/**
+ * Recorded values of a metric, see `sensor_history`
+ */
+export type SensorHistory = { metric: string, device: string | null, 
+/**
+ * raw (every control cycle), 5m or 1h averages, chosen from the range
+ */
+resolution: string, points: Array<HistoryPoint>, };
@@ -0,0 +1,38 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The AC state that would be set
+ */
+export type SimulatorAcState = { 
+/**
+ * Whether the AC would be on
+ */
+is_on: boolean, 
+/**
+ * AC mode description (Heat/Cool/Dry/Fan)
+ */
+mode: string | null, 
+/**
+ * Fan speed (0 = auto, 1-5 = manual)
+ */
+fan_speed: number | null, 
+/**
+ * Target temperature in Celsius
+ */
+temperature: number | null, 
+/**
+ * Swing setting (0 = off, 1 = on)
+ */
+swing: number | null, 
+/**
+ * Vertical vane position name, None if not set or the device doesn't support it
+ */
+vertical_vane: string | null, 
+/**
+ * Horizontal vane position name, None if not set or the device doesn't support it
+ */
+horizontal_vane: string | null, 
+/**
+ * Whether powerful mode would be active
+ */
+powerful_mode: boolean, };
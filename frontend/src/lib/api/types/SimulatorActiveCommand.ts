@@ -0,0 +1,38 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Active command data from the simulator input
+ */
+export type SimulatorActiveCommand = { 
+/**
+ * Whether an active command exists
+ */
+is_defined: boolean, 
+/**
+ * Whether the AC is currently on
+ */
+is_on: boolean, 
+/**
+ * Target temperature in Celsius
+ */
+temperature: number, 
+/**
+ * AC mode: 0 = Off, 1 = Cool, 2 = Dry, 3 = Fan, 4 = Heat
+ */
+mode: number, 
+/**
+ * Fan speed setting (0-5, where 0 is auto)
+ */
+fan_speed: number, 
+/**
+ * Swing setting (0 = off, 1 = on)
+ */
+swing: number, 
+/**
+ * Whether powerful mode was enabled
+ */
+is_powerful: boolean, 
+/**
+ * Unrounded temperature the controller aims for, defaults to `temperature`
+ */
+target_temperature: number | null, };
@@ -0,0 +1,155 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DataAges } from "./DataAges";
+import type { EnergyPriceData } from "./EnergyPriceData";
+import type { LocalTimeData } from "./LocalTimeData";
+import type { Season } from "./Season";
+import type { SimulatorActiveCommand } from "./SimulatorActiveCommand";
+import type { WeatherForecastData } from "./WeatherForecastData";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Input parameters for the simulator
+ */
+export type SimulatorInputs = { 
+/**
+ * Device name (e.g., "LivingRoom", "Veranda")
+ */
+device: string, 
+/**
+ * Current indoor temperature
+ */
+temperature: number, 
+/**
+ * Whether the device is in auto mode
+ */
+is_auto_mode: boolean, 
+/**
+ * Solar production in watts (optional, fetched if not provided)
+ */
+solar_production: number | null, 
+/**
+ * Outdoor temperature (optional, fetched if not provided)
+ */
+outdoor_temp: number | null, 
+/**
+ * Average outdoor temperature in next 24 hours (optional, fetched if not provided)
+ */
+avg_next_24h_outdoor_temp: number | null, 
+/**
+ * Lowest outdoor temperature in next 12 hours (optional, fetched if not provided)
+ */
+forecast_min_next_12h_temp: number | null, 
+/**
+ * Hourly forecast for the next 48 hours (optional, fetched if not provided)
+ */
+weather_forecast: WeatherForecastData | null, 
+/**
+ * Expected PV production of the next 3 hours in Wh (optional, fetched if not provided)
+ */
+expected_solar_next_3h_wh: number | null, 
+/**
+ * Heating or cooling season (optional, classified from the weather history if not provided)
+ */
+season: Season | null, 
+/**
+ * Whether user is home (optional, calculated if not provided)
+ */
+user_is_home: boolean | null, 
+/**
+ * Number of people home (optional, follows user_is_home if that is provided, otherwise presence detection)
+ */
+people_home_count: number | null, 
+/**
+ * PIR detection status for this device (optional, defaults to false)
+ */
+pir_detected: boolean | null, 
+/**
+ * PIR detection minutes ago (optional, used if pir_detected is true)
+ */
+pir_minutes_ago: number | null, 
+/**
+ * Occupancy probability (0-1) of the device's room (optional, read from the occupancy model if not provided)
+ */
+occupancy_probability: number | null, 
+/**
+ * Whether a window or door in the device's room is open (optional, read from the contact sensors if not provided)
+ */
+is_window_open: boolean | null, 
+/**
+ * Minutes since last AC command (optional, defaults to 60)
+ */
+last_change_minutes: number | null, 
+/**
+ * Net power in watts (optional, positive = consuming, negative = exporting)
+ */
+net_power_watt: number | null, 
+/**
+ * Whether the grid appears to be down (optional, detected if not provided)
+ */
+possible_grid_outage: boolean | null, 
+/**
+ * Whether a demand-response event is in progress (optional, uses live state if not provided)
+ */
+dr_event_active: boolean | null, 
+/**
+ * Demand-response curtailment level (optional, uses live state if not provided)
+ */
+dr_level: number | null, 
+/**
+ * Minutes until the next demand-response event, -1 if none (optional, uses live state if not provided)
+ */
+dr_minutes_until_event: number | null, 
+/**
+ * Indoor relative humidity in % (optional, unavailable if not provided)
+ */
+device_humidity: number | null, 
+/**
+ * Outdoor relative humidity in % (optional, fetched if not provided)
+ */
+outdoor_humidity: number | null, 
+/**
+ * Local time to evaluate at (optional, current local time if not provided)
+ */
+local_time: LocalTimeData | null, 
+/**
+ * Day-ahead energy prices (optional, fetched if not provided)
+ */
+energy_price: EnergyPriceData | null, 
+/**
+ * Home battery state of charge in % (optional, fetched if not provided)
+ */
+battery_soc_percent: number | null, 
+/**
+ * Home battery power in watts, positive = discharging (optional, fetched if not provided)
+ */
+battery_power_watt: number | null, 
+/**
+ * Minutes since each data source was read (optional, all fresh if not provided)
+ */
+data_ages: DataAges | null, 
+/**
+ * Nodeset ID to evaluate (optional, uses active nodeset if not provided)
+ * Use -1 for new unsaved nodesets
+ */
+nodeset_id: number | null, 
+/**
+ * Nodes configuration for unsaved/new nodesets (when nodeset_id is -1)
+ */
+nodes: Array<JsonValue> | null, 
+/**
+ * Edges configuration for unsaved/new nodesets (when nodeset_id is -1)
+ */
+edges: Array<JsonValue> | null, 
+/**
+ * Variables for unsaved/new nodesets (when nodeset_id is -1)
+ */
+variables: { [key in string]?: number } | null, 
+/**
+ * Active command data for simulator testing (optional)
+ * When provided, overrides the state manager's tracked state
+ */
+active_command: SimulatorActiveCommand | null, 
+/**
+ * Return the execution trace (optional, defaults to false)
+ */
+trace: boolean | null, };
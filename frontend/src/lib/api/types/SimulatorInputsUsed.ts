@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DataAges } from "./DataAges";
+import type { EnergyPriceData } from "./EnergyPriceData";
+import type { LocalTimeData } from "./LocalTimeData";
+import type { Season } from "./Season";
+import type { WeatherForecastData } from "./WeatherForecastData";
+
+/**
+ * Input values used for the simulation (including fetched defaults)
+ */
+export type SimulatorInputsUsed = { device: string, temperature: number, is_auto_mode: boolean, solar_production: number, outdoor_temp: number, 
+/**
+ * Average outdoor temperature for the next 24 hours
+ */
+avg_next_24h_outdoor_temp: number, 
+/**
+ * Lowest outdoor temperature for the next 12 hours
+ */
+forecast_min_next_12h_temp: number, weather_forecast: WeatherForecastData, expected_solar_next_3h_wh: number | null, season: Season, user_is_home: boolean, people_home_count: number, pir_detected: boolean, occupancy_probability: number, is_window_open: boolean, last_change_minutes: number, net_power_watt: number, possible_grid_outage: boolean, dr_event_active: boolean, dr_level: number, dr_minutes_until_event: number, device_humidity: number | null, outdoor_humidity: number | null, local_time: LocalTimeData, energy_price: EnergyPriceData, battery_soc_percent: number | null, battery_power_watt: number | null, data_ages: DataAges, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The plan result from simulation
+ */
+export type SimulatorPlanResult = { 
+/**
+ * The request mode (Colder, Warmer, Off, NoChange)
+ */
+mode: string, 
+/**
+ * The intensity (Low, Medium, High)
+ */
+intensity: string, 
+/**
+ * The cause reason label
+ */
+cause_label: string, 
+/**
+ * The cause reason description
+ */
+cause_description: string, 
+/**
+ * Annotation of the action, if the profile set one
+ */
+annotation: string | null, };
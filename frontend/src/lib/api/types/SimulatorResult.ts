@@ -0,0 +1,44 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationResult } from "./NotificationResult";
+import type { SimulatorAcState } from "./SimulatorAcState";
+import type { SimulatorInputsUsed } from "./SimulatorInputsUsed";
+import type { SimulatorPlanResult } from "./SimulatorPlanResult";
+import type { TraceStep } from "./TraceStep";
+
+/**
+ * Result of simulating a workflow
+ */
+export type SimulatorResult = { 
+/**
+ * Whether the simulation was successful
+ */
+success: boolean, 
+/**
+ * The plan result (mode, intensity, cause)
+ */
+plan: SimulatorPlanResult | null, 
+/**
+ * The AC state that would be set
+ */
+ac_state: SimulatorAcState | null, 
+/**
+ * Error message if simulation failed
+ */
+error: string | null, 
+/**
+ * Input values used for the simulation (including fetched defaults)
+ */
+inputs_used: SimulatorInputsUsed, 
+/**
+ * The evaluate_every_minutes value from the Start node (no effect in simulator, just reported)
+ * This value controls how often the AC state is reevaluated in the actual controller
+ */
+evaluate_every_minutes: number | null, 
+/**
+ * Notifications the Notify nodes on the evaluated path would send (cooldowns are not applied)
+ */
+notifications: Array<NotificationResult>, 
+/**
+ * Every node visited, output value read and branch taken in order, empty unless `trace` was requested
+ */
+trace: Array<TraceStep>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * One step of a traced execution, serialized with its kind in `type`
+ */
+export type TraceStep = { "type": "visit", node_id: string, } | { "type": "value", node_id: string, output_id: string, value: JsonValue, } | { "type": "branch", node_id: string, output_id: string, } | { "type": "backtrack", node_id: string, output_id: string, error: string, };
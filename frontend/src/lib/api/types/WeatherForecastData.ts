@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Hourly weather forecast for the hours after the current one
+ */
+export type WeatherForecastData = { 
+/**
+ * Forecast outdoor temperature in Celsius of each hour after the current one (index 0 = next hour),
+ * up to 48 hours, empty if unavailable
+ */
+hourly_temperatures: Array<number>, 
+/**
+ * Forecast solar radiation in W/m² of the same hours, empty if unavailable
+ */
+hourly_solar_radiation: Array<number>, };
@@ -145,9 +145,13 @@ Description=Power Control Center Service
 After=network.target
 
 [Service]
-Type=simple
+Type=notify
+# Restarted when a control cycle hangs, see "Watchdog" in the README
+WatchdogSec=120
 User=power_control
 Group=power_control
+# Install a binary staged by POST /api/admin/self-update ("+" runs this step as root)
+ExecStartPre=+/bin/sh -c 'if [ -f /var/lib/power_control_center/staged/power_control_center ]; then install -m 755 /var/lib/power_control_center/staged/power_control_center /usr/local/bin/power_control_center && rm -f /var/lib/power_control_center/staged/power_control_center; fi'
 ExecStart=/usr/local/bin/power_control_center
 Restart=always
 RestartSec=5
@@ -0,0 +1,3 @@
+-- Add a persistent, random installation ID used to anonymously identify this installation in telemetry
+-- Generated once; never derived from hardware or user data
+INSERT OR IGNORE INTO settings (setting_key, setting_value) VALUES ('installation_id', lower(hex(randomblob(16))));
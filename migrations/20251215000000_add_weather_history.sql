@@ -0,0 +1,7 @@
+-- Hourly outdoor temperature history, used as outdoor context for reports over past weeks
+-- Recorded live once an hour; gaps (e.g. while the controller was down) are backfilled from the weather archive
+CREATE TABLE weather_history (
+    hour_timestamp INTEGER PRIMARY KEY, -- Unix timestamp of the start of the hour (UTC)
+    outdoor_temperature FLOAT NOT NULL,
+    source TEXT NOT NULL -- live, archive
+);
@@ -0,0 +1,4 @@
+-- Add command_latency_ms column to ac_actions table
+-- Stores the round-trip time (in milliseconds) of the command to the AC endpoint
+
+ALTER TABLE ac_actions ADD COLUMN command_latency_ms INTEGER;
@@ -0,0 +1,13 @@
+-- Nodesets queued to become the active nodeset at a future time (e.g. "vacation profile Friday 18:00")
+-- When revert_after_minutes is set, activating inserts a follow-up row that restores the previously active nodeset
+CREATE TABLE scheduled_activations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    nodeset_id INTEGER NOT NULL,
+    activate_at INTEGER NOT NULL, -- Unix timestamp
+    revert_after_minutes INTEGER NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, done, failed, cancelled
+    error TEXT NULL, -- Reason the activation failed
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_scheduled_activations_pending ON scheduled_activations(status, activate_at);
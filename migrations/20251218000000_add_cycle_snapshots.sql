@@ -0,0 +1,11 @@
+-- Execution inputs and decision of every control cycle per device, to see what changed between cycles
+-- Kept for a limited time; older snapshots are deleted when new ones are recorded
+CREATE TABLE cycle_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_identifier TEXT NOT NULL,
+    evaluated_at INTEGER NOT NULL, -- Unix timestamp
+    inputs TEXT NOT NULL, -- JSON object of flattened input values, e.g. {"active_command.is_on": true}
+    decision TEXT NOT NULL -- e.g. "Execute Action: Heat 22°C", "Do Nothing", "Error: ..."
+);
+
+CREATE INDEX idx_cycle_snapshots_device ON cycle_snapshots(device_identifier, evaluated_at);
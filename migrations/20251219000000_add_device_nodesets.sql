@@ -0,0 +1,7 @@
+-- Nodeset assigned to a single device, overriding the globally active nodeset for that device
+-- Devices without a row run the active nodeset (settings.active_nodeset)
+CREATE TABLE device_nodesets (
+    device_identifier TEXT PRIMARY KEY,
+    nodeset_id INTEGER NOT NULL,
+    assigned_at INTEGER NOT NULL -- Unix timestamp
+);
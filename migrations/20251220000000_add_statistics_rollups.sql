@@ -0,0 +1,40 @@
+-- Long-term statistics rolled up from cycle_snapshots (kept for a week) and ac_actions
+-- Hourly rows are computed once an hour is complete; daily rows (UTC days) are summed from the hourly rows
+CREATE TABLE device_stats_hourly (
+    period_start INTEGER NOT NULL, -- Unix timestamp of the start of the hour (UTC)
+    device_identifier TEXT NOT NULL,
+    samples INTEGER NOT NULL, -- Number of control cycles
+    avg_indoor_temperature FLOAT NULL,
+    avg_outdoor_temperature FLOAT NULL,
+    runtime_minutes FLOAT NOT NULL, -- Minutes the AC was on
+    actions INTEGER NOT NULL, -- Commands sent to the AC
+    PRIMARY KEY (period_start, device_identifier)
+);
+
+CREATE TABLE device_stats_daily (
+    period_start INTEGER NOT NULL, -- Unix timestamp of the start of the day (UTC)
+    device_identifier TEXT NOT NULL,
+    samples INTEGER NOT NULL,
+    avg_indoor_temperature FLOAT NULL,
+    avg_outdoor_temperature FLOAT NULL,
+    runtime_minutes FLOAT NOT NULL,
+    actions INTEGER NOT NULL,
+    PRIMARY KEY (period_start, device_identifier)
+);
+
+-- Household energy, measured by the meter during control cycles of any device
+CREATE TABLE energy_hourly (
+    period_start INTEGER PRIMARY KEY, -- Unix timestamp of the start of the hour (UTC)
+    covered_minutes FLOAT NOT NULL, -- Minutes of the hour covered by meter readings
+    grid_import_kwh FLOAT NOT NULL,
+    grid_export_kwh FLOAT NOT NULL,
+    solar_kwh FLOAT NOT NULL
+);
+
+CREATE TABLE energy_daily (
+    period_start INTEGER PRIMARY KEY, -- Unix timestamp of the start of the day (UTC)
+    covered_minutes FLOAT NOT NULL,
+    grid_import_kwh FLOAT NOT NULL,
+    grid_export_kwh FLOAT NOT NULL,
+    solar_kwh FLOAT NOT NULL
+);
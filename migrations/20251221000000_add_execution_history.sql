@@ -0,0 +1,16 @@
+-- Result of every nodeset execution per device, to find out afterwards why the AC did what it did
+-- Kept longer than cycle_snapshots; older entries are deleted when new ones are recorded
+CREATE TABLE execution_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device_identifier TEXT NOT NULL,
+    executed_at INTEGER NOT NULL, -- Unix timestamp
+    nodeset_id INTEGER NOT NULL, -- Nodeset the device ran, not a foreign key so history survives deleting it
+    terminal_type TEXT, -- e.g. "Execute Action", "Do Nothing", NULL if no terminal node was reached
+    action TEXT, -- JSON of the action parameters when an Execute Action node was reached
+    cause_reason TEXT, -- Cause reason ID of the action or Do Nothing node
+    error TEXT,
+    inputs TEXT NOT NULL -- JSON object of flattened input values, same format as cycle_snapshots
+);
+
+CREATE INDEX idx_execution_history_device ON execution_history(device_identifier, executed_at);
+CREATE INDEX idx_execution_history_executed_at ON execution_history(executed_at);
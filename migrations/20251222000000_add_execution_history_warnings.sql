@@ -0,0 +1,2 @@
+-- Warnings of the execution, e.g. disconnected nodes or an unknown cause reason in strict mode
+ALTER TABLE execution_history ADD COLUMN warnings TEXT NOT NULL DEFAULT '[]'; -- JSON array of strings
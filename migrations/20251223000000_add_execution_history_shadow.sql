@@ -0,0 +1,2 @@
+-- Executions in shadow mode are evaluated and recorded, but their action is never sent to the AC
+ALTER TABLE execution_history ADD COLUMN is_shadow BOOLEAN NOT NULL DEFAULT 0;
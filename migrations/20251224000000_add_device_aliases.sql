@@ -0,0 +1,6 @@
+-- Display names of devices, shown in the dashboard and APIs
+-- Config, endpoints and nodesets keep using the device identifier, so renaming a device doesn't break them
+CREATE TABLE device_aliases (
+    device_identifier TEXT PRIMARY KEY,
+    display_name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
@@ -0,0 +1,8 @@
+-- Latched state of Hysteresis nodes, kept between executions of a nodeset for each device
+CREATE TABLE hysteresis_state (
+    nodeset_id INTEGER NOT NULL,
+    device_identifier TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_on INTEGER NOT NULL,
+    PRIMARY KEY (nodeset_id, device_identifier, node_id)
+);
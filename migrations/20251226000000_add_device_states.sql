@@ -0,0 +1,11 @@
+-- Last state tracked for each AC device, restored on startup so a restart doesn't force re-sync commands
+CREATE TABLE device_states (
+    device_identifier TEXT PRIMARY KEY NOT NULL,
+    is_on INTEGER NOT NULL,
+    mode INTEGER,
+    fan_speed INTEGER,
+    temperature REAL,
+    swing INTEGER,
+    powerful_mode INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,20 @@
+-- Saved versions of every nodeset, the newest one matches the current nodeset
+-- Only the last `nodeset_revisions_kept` revisions of each nodeset are kept
+CREATE TABLE nodeset_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    nodeset_id INTEGER NOT NULL,
+    revision INTEGER NOT NULL, -- Counts up per nodeset, starting at 1
+    node_json TEXT NOT NULL,
+    created_at INTEGER NOT NULL, -- Unix timestamp
+    UNIQUE (nodeset_id, revision)
+);
+
+-- Current revision of each nodeset
+ALTER TABLE nodesets ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+
+-- Revision of the nodeset the device ran, NULL for executions recorded before revisions existed
+ALTER TABLE execution_history ADD COLUMN nodeset_revision INTEGER;
+
+-- Existing nodesets start at revision 1, the default nodeset can't be edited and has no revisions
+INSERT INTO nodeset_revisions (nodeset_id, revision, node_json, created_at)
+SELECT id, 1, node_json, CAST(strftime('%s', 'now') AS INTEGER) FROM nodesets WHERE id != 0;
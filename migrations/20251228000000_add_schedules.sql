@@ -0,0 +1,17 @@
+-- Recurring rules that switch a nodeset in during a weekly time window (e.g. "Workday profile mon-fri 07:00-17:00")
+-- device_identifier NULL switches the active nodeset, otherwise the nodeset assigned to that device
+-- When several rules of the same target are in effect, the highest priority wins
+CREATE TABLE schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    nodeset_id INTEGER NOT NULL,
+    device_identifier TEXT NULL,
+    days TEXT NOT NULL, -- Cron-like day field in local time, e.g. "mon-fri", "sat,sun" or "*"
+    start_time TEXT NOT NULL, -- Local "HH:MM"
+    end_time TEXT NOT NULL, -- Local "HH:MM", before start_time for windows past midnight, equal for the whole day
+    priority INTEGER NOT NULL DEFAULT 0,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_schedules_nodeset ON schedules(nodeset_id);
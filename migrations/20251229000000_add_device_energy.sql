@@ -0,0 +1,12 @@
+-- Estimated AC energy per hour, rolled up with the statistics from cycle_snapshots and ac_actions
+-- The cause is the cause of the last command sent to the device before the minutes were counted
+CREATE TABLE device_energy (
+    period_start INTEGER NOT NULL, -- Unix timestamp of the start of the hour (UTC)
+    device_identifier TEXT NOT NULL,
+    cause_id INTEGER NOT NULL,
+    mode TEXT NOT NULL, -- Heat, Cool, Dry, Fan or Unknown
+    is_powerful BOOLEAN NOT NULL,
+    runtime_minutes FLOAT NOT NULL,
+    estimated_kwh FLOAT NOT NULL,
+    PRIMARY KEY (period_start, device_identifier, cause_id, mode, is_powerful)
+);
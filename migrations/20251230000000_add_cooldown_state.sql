@@ -0,0 +1,8 @@
+-- Time each Cooldown node last let execution through, kept between executions of a nodeset for each device
+CREATE TABLE cooldown_state (
+    nodeset_id INTEGER NOT NULL,
+    device_identifier TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    passed_at INTEGER NOT NULL,
+    PRIMARY KEY (nodeset_id, device_identifier, node_id)
+);
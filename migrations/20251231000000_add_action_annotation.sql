@@ -0,0 +1,4 @@
+-- Add annotation column to ac_actions table
+-- Stores the optional text a profile attached to the Execute Action or Turn Off node that sent the command
+
+ALTER TABLE ac_actions ADD COLUMN annotation TEXT;
@@ -0,0 +1,7 @@
+-- Add vane position columns to ac_actions and device_states
+-- NULL when no position was requested, so the controller keeps its own default
+
+ALTER TABLE ac_actions ADD COLUMN vertical_vane INTEGER;
+ALTER TABLE ac_actions ADD COLUMN horizontal_vane INTEGER;
+ALTER TABLE device_states ADD COLUMN vertical_vane INTEGER;
+ALTER TABLE device_states ADD COLUMN horizontal_vane INTEGER;
@@ -0,0 +1,3 @@
+-- Result of reading back the AC state after an execution's command
+-- NULL when it wasn't verified, a JSON array of discrepancies otherwise (empty when the command was applied)
+ALTER TABLE execution_history ADD COLUMN command_discrepancies TEXT;
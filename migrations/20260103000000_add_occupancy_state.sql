@@ -0,0 +1,6 @@
+-- Occupancy evidence of each room at its last PIR or door sensor event, so a restart continues the decay
+CREATE TABLE occupancy_state (
+    device_identifier TEXT PRIMARY KEY NOT NULL,
+    evidence REAL NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Sensor history for charts: indoor temperature per device, outdoor temperature, solar production and net power
+-- Raw samples are recorded every control cycle and downsampled into 5 minute and hourly averages
+CREATE TABLE telemetry (
+    metric TEXT NOT NULL, -- indoor_temperature, outdoor_temperature, solar_watt, net_power_watt
+    device_identifier TEXT NOT NULL, -- device of indoor_temperature, empty for household metrics
+    resolution TEXT NOT NULL, -- raw, 5m, 1h
+    timestamp INTEGER NOT NULL, -- Unix timestamp of the sample, or of the start of the period
+    value FLOAT NOT NULL, -- average over the period
+    samples INTEGER NOT NULL, -- number of raw samples averaged
+    PRIMARY KEY (metric, device_identifier, resolution, timestamp)
+);
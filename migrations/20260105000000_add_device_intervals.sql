@@ -0,0 +1,7 @@
+-- Evaluation interval of a single device, overriding the Start node of the active nodeset for that device
+-- Devices without a row are evaluated on the interval of the active nodeset
+CREATE TABLE device_intervals (
+    device_identifier TEXT PRIMARY KEY NOT NULL,
+    evaluate_every_minutes INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL -- Unix timestamp
+);
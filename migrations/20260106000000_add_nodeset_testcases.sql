@@ -0,0 +1,12 @@
+-- Saved test cases of a nodeset, run through POST /api/nodes/nodesets/:id/run-tests
+CREATE TABLE nodeset_testcases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    nodeset_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    inputs TEXT NOT NULL, -- JSON of the execution inputs, missing fields use their defaults
+    expected_terminal TEXT NOT NULL, -- "Execute Action" or "Do Nothing"
+    expected_action TEXT, -- JSON of the action fields to compare when an action is expected, NULL to only check the terminal
+    created_at INTEGER NOT NULL -- Unix timestamp
+);
+
+CREATE INDEX idx_nodeset_testcases_nodeset ON nodeset_testcases(nodeset_id);
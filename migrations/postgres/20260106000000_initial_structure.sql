@@ -0,0 +1,250 @@
+-- Schema of PostgreSQL databases, the same as the SQLite migrations in ../ up to this version
+-- Later schema changes are added here and to ../ with the same version
+-- Column types match the Rust types they are read into: INTEGER for i32, BIGINT for i64,
+-- REAL for f32, DOUBLE PRECISION for f64 and BOOLEAN for bool
+
+CREATE TABLE ac_actions (
+    id SERIAL PRIMARY KEY,
+    action_timestamp INTEGER NOT NULL,
+    device_identifier TEXT NOT NULL,
+    action_type TEXT NOT NULL, -- on, off, toggle-powerful
+    mode INTEGER,
+    fan_speed INTEGER,
+    request_temperature REAL,
+    swing INTEGER,
+    measured_temperature REAL, -- Temperature measured by the device at the time of request
+    measured_net_power_watt INTEGER, -- Measured power usage (negative for production) at the time of request
+    is_human_home BOOLEAN,
+    cause_id INTEGER NOT NULL DEFAULT 0,
+    measured_solar_production_watt INTEGER,
+    command_latency_ms INTEGER, -- Round trip of the command to the AC endpoint
+    annotation TEXT, -- Text the profile attached to the node that sent the command
+    vertical_vane INTEGER,
+    horizontal_vane INTEGER
+);
+
+CREATE TABLE settings (
+    setting_key TEXT PRIMARY KEY,
+    setting_value TEXT NOT NULL
+);
+
+INSERT INTO settings (setting_key, setting_value) VALUES
+    ('active_nodeset', '0'),
+    ('user_is_home_override', '0'),
+    ('installation_id', md5(random()::text || clock_timestamp()::text));
+
+CREATE TABLE nodesets (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    node_json TEXT NOT NULL,
+    revision BIGINT NOT NULL DEFAULT 1
+);
+
+-- The default nodeset, which can't be edited and has no revisions
+INSERT INTO nodesets (id, name, node_json) VALUES (0, 'Default', '{"nodes": [], "edges": []}');
+
+-- System cause reasons (IDs 0-99) are inserted by db::defaults, user-created ones start at ID 100
+CREATE TABLE cause_reasons (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    description TEXT NOT NULL,
+    is_hidden BOOLEAN NOT NULL DEFAULT FALSE,
+    is_editable BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE TABLE weather_history (
+    hour_timestamp BIGINT PRIMARY KEY, -- Unix timestamp of the start of the hour (UTC)
+    outdoor_temperature DOUBLE PRECISION NOT NULL,
+    source TEXT NOT NULL -- live, archive
+);
+
+CREATE TABLE scheduled_activations (
+    id BIGSERIAL PRIMARY KEY,
+    nodeset_id BIGINT NOT NULL,
+    activate_at BIGINT NOT NULL, -- Unix timestamp
+    revert_after_minutes BIGINT NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, done, failed, cancelled
+    error TEXT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX idx_scheduled_activations_pending ON scheduled_activations(status, activate_at);
+
+CREATE TABLE cycle_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    device_identifier TEXT NOT NULL,
+    evaluated_at BIGINT NOT NULL, -- Unix timestamp
+    inputs TEXT NOT NULL, -- JSON object of flattened input values
+    decision TEXT NOT NULL
+);
+
+CREATE INDEX idx_cycle_snapshots_device ON cycle_snapshots(device_identifier, evaluated_at);
+
+CREATE TABLE device_nodesets (
+    device_identifier TEXT PRIMARY KEY,
+    nodeset_id BIGINT NOT NULL,
+    assigned_at BIGINT NOT NULL -- Unix timestamp
+);
+
+CREATE TABLE device_stats_hourly (
+    period_start BIGINT NOT NULL, -- Unix timestamp of the start of the hour (UTC)
+    device_identifier TEXT NOT NULL,
+    samples BIGINT NOT NULL,
+    avg_indoor_temperature DOUBLE PRECISION NULL,
+    avg_outdoor_temperature DOUBLE PRECISION NULL,
+    runtime_minutes DOUBLE PRECISION NOT NULL,
+    actions BIGINT NOT NULL,
+    PRIMARY KEY (period_start, device_identifier)
+);
+
+CREATE TABLE device_stats_daily (
+    period_start BIGINT NOT NULL, -- Unix timestamp of the start of the day (UTC)
+    device_identifier TEXT NOT NULL,
+    samples BIGINT NOT NULL,
+    avg_indoor_temperature DOUBLE PRECISION NULL,
+    avg_outdoor_temperature DOUBLE PRECISION NULL,
+    runtime_minutes DOUBLE PRECISION NOT NULL,
+    actions BIGINT NOT NULL,
+    PRIMARY KEY (period_start, device_identifier)
+);
+
+CREATE TABLE energy_hourly (
+    period_start BIGINT PRIMARY KEY, -- Unix timestamp of the start of the hour (UTC)
+    covered_minutes DOUBLE PRECISION NOT NULL,
+    grid_import_kwh DOUBLE PRECISION NOT NULL,
+    grid_export_kwh DOUBLE PRECISION NOT NULL,
+    solar_kwh DOUBLE PRECISION NOT NULL
+);
+
+CREATE TABLE energy_daily (
+    period_start BIGINT PRIMARY KEY, -- Unix timestamp of the start of the day (UTC)
+    covered_minutes DOUBLE PRECISION NOT NULL,
+    grid_import_kwh DOUBLE PRECISION NOT NULL,
+    grid_export_kwh DOUBLE PRECISION NOT NULL,
+    solar_kwh DOUBLE PRECISION NOT NULL
+);
+
+CREATE TABLE execution_history (
+    id BIGSERIAL PRIMARY KEY,
+    device_identifier TEXT NOT NULL,
+    executed_at BIGINT NOT NULL, -- Unix timestamp
+    nodeset_id BIGINT NOT NULL, -- Not a foreign key so history survives deleting the nodeset
+    terminal_type TEXT,
+    action TEXT, -- JSON of the action parameters
+    cause_reason TEXT,
+    error TEXT,
+    inputs TEXT NOT NULL, -- JSON object of flattened input values
+    warnings TEXT NOT NULL DEFAULT '[]', -- JSON array of strings
+    is_shadow BOOLEAN NOT NULL DEFAULT FALSE,
+    nodeset_revision BIGINT,
+    command_discrepancies TEXT -- JSON array, NULL when the command wasn't read back
+);
+
+CREATE INDEX idx_execution_history_device ON execution_history(device_identifier, executed_at);
+CREATE INDEX idx_execution_history_executed_at ON execution_history(executed_at);
+
+CREATE TABLE device_aliases (
+    device_identifier TEXT PRIMARY KEY,
+    display_name TEXT NOT NULL
+);
+
+-- Display names are unique regardless of case, like COLLATE NOCASE in SQLite
+CREATE UNIQUE INDEX idx_device_aliases_display_name ON device_aliases(lower(display_name));
+
+CREATE TABLE hysteresis_state (
+    nodeset_id BIGINT NOT NULL,
+    device_identifier TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    is_on BOOLEAN NOT NULL,
+    PRIMARY KEY (nodeset_id, device_identifier, node_id)
+);
+
+CREATE TABLE device_states (
+    device_identifier TEXT PRIMARY KEY,
+    is_on BOOLEAN NOT NULL,
+    mode INTEGER,
+    fan_speed INTEGER,
+    temperature DOUBLE PRECISION,
+    swing INTEGER,
+    powerful_mode BOOLEAN NOT NULL,
+    updated_at BIGINT NOT NULL,
+    vertical_vane INTEGER,
+    horizontal_vane INTEGER
+);
+
+CREATE TABLE nodeset_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    nodeset_id BIGINT NOT NULL,
+    revision BIGINT NOT NULL, -- Counts up per nodeset, starting at 1
+    node_json TEXT NOT NULL,
+    created_at BIGINT NOT NULL, -- Unix timestamp
+    UNIQUE (nodeset_id, revision)
+);
+
+CREATE TABLE schedules (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    nodeset_id BIGINT NOT NULL,
+    device_identifier TEXT NULL,
+    days TEXT NOT NULL, -- Cron-like day field in local time, e.g. "mon-fri"
+    start_time TEXT NOT NULL, -- Local "HH:MM"
+    end_time TEXT NOT NULL, -- Local "HH:MM"
+    priority BIGINT NOT NULL DEFAULT 0,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX idx_schedules_nodeset ON schedules(nodeset_id);
+
+CREATE TABLE device_energy (
+    period_start BIGINT NOT NULL, -- Unix timestamp of the start of the hour (UTC)
+    device_identifier TEXT NOT NULL,
+    cause_id BIGINT NOT NULL,
+    mode TEXT NOT NULL, -- Heat, Cool, Dry, Fan or Unknown
+    is_powerful BOOLEAN NOT NULL,
+    runtime_minutes DOUBLE PRECISION NOT NULL,
+    estimated_kwh DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (period_start, device_identifier, cause_id, mode, is_powerful)
+);
+
+CREATE TABLE cooldown_state (
+    nodeset_id BIGINT NOT NULL,
+    device_identifier TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    passed_at BIGINT NOT NULL,
+    PRIMARY KEY (nodeset_id, device_identifier, node_id)
+);
+
+CREATE TABLE occupancy_state (
+    device_identifier TEXT PRIMARY KEY,
+    evidence DOUBLE PRECISION NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+CREATE TABLE telemetry (
+    metric TEXT NOT NULL, -- indoor_temperature, outdoor_temperature, solar_watt, net_power_watt
+    device_identifier TEXT NOT NULL, -- Device of indoor_temperature, empty for household metrics
+    resolution TEXT NOT NULL, -- raw, 5m, 1h
+    timestamp BIGINT NOT NULL, -- Unix timestamp of the sample, or of the start of the period
+    value DOUBLE PRECISION NOT NULL, -- Average over the period
+    samples BIGINT NOT NULL, -- Number of raw samples averaged
+    PRIMARY KEY (metric, device_identifier, resolution, timestamp)
+);
+
+CREATE TABLE device_intervals (
+    device_identifier TEXT PRIMARY KEY,
+    evaluate_every_minutes INTEGER NOT NULL,
+    updated_at BIGINT NOT NULL -- Unix timestamp
+);
+
+CREATE TABLE nodeset_testcases (
+    id BIGSERIAL PRIMARY KEY,
+    nodeset_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    inputs TEXT NOT NULL, -- JSON of the execution inputs
+    expected_terminal TEXT NOT NULL, -- "Execute Action" or "Do Nothing"
+    expected_action TEXT, -- JSON of the action fields to compare, NULL to only check the terminal
+    created_at BIGINT NOT NULL -- Unix timestamp
+);
+
+CREATE INDEX idx_nodeset_testcases_nodeset ON nodeset_testcases(nodeset_id);
@@ -1,22 +1,37 @@
-mod types;
 
-pub use types::{AcState, AC_MODE_OFF, AC_MODE_COOL, AC_MODE_HEAT};
+pub use power_control_center_core::ac_state::{
+    ac_mode_from_name, ac_mode_name, AcState, AC_MODE_OFF, AC_MODE_COOL, AC_MODE_DRY, AC_MODE_FAN, AC_MODE_HEAT,
+};
 
-use super::devices::AcDevices;
+use super::devices::AcDevice;
 use crate::device_requests;
+use crate::db::{self, DbPool};
+use crate::live_events::{self, LiveEvent};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
+use tokio::sync::{mpsc, oneshot};
 
 /// Global state manager for all AC devices
 /// Tracks the last known state of each AC to avoid unnecessary API calls
-static AC_STATE_MANAGER: std::sync::OnceLock<AcStateManager> = std::sync::OnceLock::new();
+static AC_STATE_MANAGER: std::sync::OnceLock<Arc<AcStateManager>> = std::sync::OnceLock::new();
+
+/// A change to write to the device_states table
+enum StateUpdate {
+    Save(String, AcState, i64),
+    Delete(String),
+    /// Answered once every earlier change is written
+    Flush(oneshot::Sender<()>),
+}
 
 /// Manages state for all AC devices
+#[derive(Default)]
 pub struct AcStateManager {
     states: Arc<RwLock<HashMap<String, AcState>>>,
     /// Tracks whether each device has had its first command sent after startup
     /// This ensures we always send commands on first execution regardless of state
     initialized_devices: Arc<RwLock<HashMap<String, bool>>>,
+    /// Writes state changes to the database once persistence is enabled
+    persistence: OnceLock<mpsc::UnboundedSender<StateUpdate>>,
 }
 
 impl AcStateManager {
@@ -24,6 +39,67 @@ impl AcStateManager {
         Self {
             states: Arc::new(RwLock::new(HashMap::new())),
             initialized_devices: Arc::new(RwLock::new(HashMap::new())),
+            persistence: OnceLock::new(),
+        }
+    }
+
+    /// Restore the states stored before the last restart
+    /// Restored devices count as initialized, so they aren't re-synced. States older than
+    /// `max_age_minutes` are considered undefined and left out, as the device may have been changed since.
+    pub async fn load(&self, pool: &DbPool, max_age_minutes: u32) -> Result<(), sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        for (device_name, state, updated_at) in db::device_states::get_all(pool).await? {
+            if now - updated_at > i64::from(max_age_minutes) * 60 {
+                log::info!("Stored state of device '{}' is stale, it will be re-synced", device_name);
+                continue;
+            }
+            log::info!("Restored state of device '{}': {:?}", device_name, state);
+            self.states.write().unwrap().insert(device_name.clone(), state);
+            self.mark_device_initialized(&device_name);
+        }
+        Ok(())
+    }
+
+    /// Write every state change to the database from now on
+    pub fn persist_to(&self, pool: DbPool) {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        if self.persistence.set(sender).is_err() {
+            return;
+        }
+        tokio::spawn(async move {
+            while let Some(update) = receiver.recv().await {
+                let result = match update {
+                    StateUpdate::Save(device_name, state, updated_at) => {
+                        db::device_states::upsert(&pool, &device_name, &state, updated_at).await
+                    }
+                    StateUpdate::Delete(device_name) => db::device_states::delete(&pool, &device_name).await,
+                    StateUpdate::Flush(done) => {
+                        let _ = done.send(());
+                        continue;
+                    }
+                };
+                if let Err(e) = result {
+                    log::warn!("Failed to store AC device state: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Wait until every queued state change is written, e.g. before shutting down
+    pub async fn flush(&self) {
+        let Some(sender) = self.persistence.get() else {
+            return;
+        };
+        let (done, written) = oneshot::channel();
+        if sender.send(StateUpdate::Flush(done)).is_ok() {
+            let _ = written.await;
+        }
+    }
+
+    /// Queue a state change for the database, if persistence is enabled
+    fn persist(&self, update: StateUpdate) {
+        if let Some(sender) = self.persistence.get() {
+            let _ = sender.send(update);
         }
     }
 
@@ -39,7 +115,8 @@ impl AcStateManager {
     /// Update the state for a device
     pub fn set_state(&self, device_name: &str, state: AcState) {
         let mut states = self.states.write().unwrap();
-        states.insert(device_name.to_string(), state);
+        states.insert(device_name.to_string(), state.clone());
+        self.persist(StateUpdate::Save(device_name.to_string(), state, chrono::Utc::now().timestamp()));
     }
 
     /// Check if a device has been initialized (had its first command sent)
@@ -60,6 +137,14 @@ impl AcStateManager {
         initialized.remove(device_name);
     }
 
+    /// Reset a device to untracked "off" so the next control cycle forces a sync
+    pub fn reset_device(&self, device_name: &str) {
+        self.states.write().unwrap().insert(device_name.to_string(), AcState::new_off());
+        self.clear_device_initialization(device_name);
+        self.persist(StateUpdate::Delete(device_name.to_string()));
+        log::info!("Reset state for device '{}'", device_name);
+    }
+
     /// Clear all initialization flags
     fn clear_all_initialization(&self) {
         let mut initialized = self.initialized_devices.write().unwrap();
@@ -68,13 +153,13 @@ impl AcStateManager {
 }
 
 /// Get the global state manager instance
-pub fn get_state_manager() -> &'static AcStateManager {
-    AC_STATE_MANAGER.get_or_init(AcStateManager::new)
+pub fn get_state_manager() -> &'static Arc<AcStateManager> {
+    AC_STATE_MANAGER.get_or_init(|| Arc::new(AcStateManager::new()))
 }
 
 /// Check if a device is currently off according to tracked state
 /// Returns true if the device is off or not yet tracked (defaults to off)
-pub fn is_device_off(device: &AcDevices) -> bool {
+pub fn is_device_off(device: &AcDevice) -> bool {
     let device_name = device.as_str();
     let state_manager = get_state_manager();
     let current_state = state_manager.get_state(device_name);
@@ -82,12 +167,8 @@ pub fn is_device_off(device: &AcDevices) -> bool {
 }
 
 /// Reset the state for a specific device (useful for testing or manual override)
-pub fn reset_device_state(device: &AcDevices) {
-    let device_name = device.as_str();
-    let state_manager = get_state_manager();
-    state_manager.set_state(device_name, AcState::new_off());
-    state_manager.clear_device_initialization(device_name);
-    log::info!("Reset state for device '{}'", device_name);
+pub fn reset_device_state(device: &AcDevice) {
+    get_state_manager().reset_device(device.as_str());
 }
 
 /// Reset all device states (useful for testing or system restart)
@@ -114,7 +195,7 @@ pub fn reset_all_states() {
 /// * `Ok(false)` if the device is already off (no command needed)
 /// * `Err` if the API call failed
 pub async fn turn_off_device(
-    device: &AcDevices,
+    device: &AcDevice,
     cause: crate::types::CauseReason,
 ) -> Result<bool, Box<dyn std::error::Error>> {
     let device_name = device.as_str();
@@ -129,10 +210,14 @@ pub async fn turn_off_device(
     
     // Turn off the device
     log::info!("Turning off AC '{}' due to {:?}", device_name, cause);
-    device_requests::ac::turn_off_ac(device_name, cause.id()).await?;
+    device_requests::ac::turn_off_ac(device_name, cause.id(), None).await?;
     
     // Update the tracked state
-    state_manager.set_state(device_name, AcState::new_off());
+    let state = AcState::new_off();
+    live_events::get_live_events().publish(LiveEvent::ac_state(device_name, &state));
+    state_manager.set_state(device_name, state);
+    super::target_temperature::get_target_temperatures().clear(device_name);
+    super::min_off_time::get_min_off_time_state().record_turn_off(device_name);
     
     Ok(true)
 }
@@ -140,6 +225,7 @@ pub async fn turn_off_device(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{AcChangePolicy, Celsius};
 
     #[test]
     fn test_state_manager_get_default() {
@@ -151,7 +237,7 @@ mod tests {
     #[test]
     fn test_state_manager_set_and_get() {
         let manager = AcStateManager::new();
-        let test_state = AcState::new_on(4, 0, 22.0, 1, false);
+        let test_state = AcState::new_on(4, 0, Celsius(22.0), 1, false);
 
         manager.set_state("TestDevice", test_state.clone());
         let retrieved_state = manager.get_state("TestDevice");
@@ -162,8 +248,8 @@ mod tests {
     #[test]
     fn test_state_manager_multiple_devices() {
         let manager = AcStateManager::new();
-        let state1 = AcState::new_on(4, 0, 22.0, 1, false);
-        let state2 = AcState::new_on(1, 0, 24.0, 0, false);
+        let state1 = AcState::new_on(4, 0, Celsius(22.0), 1, false);
+        let state2 = AcState::new_on(1, 0, Celsius(24.0), 0, false);
 
         manager.set_state("Device1", state1.clone());
         manager.set_state("Device2", state2.clone());
@@ -174,7 +260,7 @@ mod tests {
 
     #[test]
     fn test_reset_all_states() {
-        let state1 = AcState::new_on(4, 0, 22.0, 1, false);
+        let state1 = AcState::new_on(4, 0, Celsius(22.0), 1, false);
         
         // Set state through global manager
         let manager = get_state_manager();
@@ -196,22 +282,51 @@ mod tests {
     fn test_state_change_detection_comprehensive() {
         // Test various state change scenarios
         let off_state = AcState::new_off();
-        let cool_low = AcState::new_on(1, 0, 26.0, 1, false);
-        let cool_high = AcState::new_on(1, 1, 20.0, 1, true);
-        let heat_med = AcState::new_on(4, 0, 22.0, 0, false);
+        let cool_low = AcState::new_on(1, 0, Celsius(26.0), 1, false);
+        let cool_high = AcState::new_on(1, 1, Celsius(20.0), 1, true);
+        let heat_med = AcState::new_on(4, 0, Celsius(22.0), 0, false);
 
         // Off to Cool should require change
-        assert!(off_state.requires_change(&cool_low));
+        assert!(off_state.requires_change(&cool_low, &AcChangePolicy::default()));
 
         // Cool low to Cool high should require change
-        assert!(cool_low.requires_change(&cool_high));
+        assert!(cool_low.requires_change(&cool_high, &AcChangePolicy::default()));
 
         // Cool to Heat should require change
-        assert!(cool_low.requires_change(&heat_med));
+        assert!(cool_low.requires_change(&heat_med, &AcChangePolicy::default()));
 
         // Same state should not require change
-        let cool_low_copy = AcState::new_on(1, 0, 26.0, 1, false);
-        assert!(!cool_low.requires_change(&cool_low_copy));
+        let cool_low_copy = AcState::new_on(1, 0, Celsius(26.0), 1, false);
+        assert!(!cool_low.requires_change(&cool_low_copy, &AcChangePolicy::default()));
+    }
+
+    #[tokio::test]
+    async fn test_load_and_persist_states() {
+        let pool = crate::db::connect_in_memory().await;
+        let heating = AcState::new_on(4, 0, Celsius(22.0), 1, false);
+        let now = chrono::Utc::now().timestamp();
+        db::device_states::upsert(&pool, "LivingRoom", &heating, now - 10 * 60).await.unwrap();
+        db::device_states::upsert(&pool, "Veranda", &heating, now - 90 * 60).await.unwrap();
+
+        let manager = AcStateManager::new();
+        manager.load(&pool, 60).await.unwrap();
+        assert_eq!(manager.get_state("LivingRoom"), heating);
+        assert!(manager.is_device_initialized("LivingRoom"), "restored devices aren't re-synced");
+        assert!(!manager.is_device_initialized("Veranda"), "stale states are undefined");
+
+        // Changes are written to the database in the background
+        manager.persist_to(pool.clone());
+        manager.set_state("Veranda", AcState::new_off());
+        manager.reset_device("LivingRoom");
+        manager.flush().await;
+        let stored = db::device_states::get_all(&pool).await.unwrap();
+        assert_eq!(stored.len(), 1, "the reset device is forgotten");
+        assert_eq!(stored[0].1, AcState::new_off());
+    }
+
+    fn living_room() -> AcDevice {
+        let registry = crate::ac_controller::DeviceRegistry::from_config(&crate::types::Config::default());
+        registry.get("LivingRoom").unwrap().clone()
     }
 
     #[test]
@@ -220,22 +335,22 @@ mod tests {
         reset_all_states();
         
         // Device not yet tracked should default to off
-        assert!(is_device_off(&AcDevices::LivingRoom));
+        assert!(is_device_off(&living_room()));
         
         // Set device to on
         let manager = get_state_manager();
-        let on_state = AcState::new_on(1, 0, 22.0, 1, false);
+        let on_state = AcState::new_on(1, 0, Celsius(22.0), 1, false);
         manager.set_state("LivingRoom", on_state);
         
         // Should now be on (not off)
-        assert!(!is_device_off(&AcDevices::LivingRoom));
+        assert!(!is_device_off(&living_room()));
         
         // Set device to off
         let off_state = AcState::new_off();
         manager.set_state("LivingRoom", off_state);
         
         // Should now be off
-        assert!(is_device_off(&AcDevices::LivingRoom));
+        assert!(is_device_off(&living_room()));
     }
 
     #[test]
@@ -261,14 +376,14 @@ mod tests {
         let manager = get_state_manager();
         
         // Set state and mark as initialized
-        manager.set_state("LivingRoom", AcState::new_on(4, 0, 22.0, 1, false));
+        manager.set_state("LivingRoom", AcState::new_on(4, 0, Celsius(22.0), 1, false));
         manager.mark_device_initialized("LivingRoom");
         
         // Verify it's initialized
         assert!(manager.is_device_initialized("LivingRoom"));
         
         // Reset device state
-        reset_device_state(&AcDevices::LivingRoom);
+        reset_device_state(&living_room());
         
         // Device should no longer be initialized
         assert!(!manager.is_device_initialized("LivingRoom"));
@@ -283,9 +398,9 @@ mod tests {
         let manager = get_state_manager();
         
         // Set multiple devices
-        manager.set_state("Device1", AcState::new_on(4, 0, 22.0, 1, false));
+        manager.set_state("Device1", AcState::new_on(4, 0, Celsius(22.0), 1, false));
         manager.mark_device_initialized("Device1");
-        manager.set_state("Device2", AcState::new_on(1, 0, 24.0, 0, false));
+        manager.set_state("Device2", AcState::new_on(1, 0, Celsius(24.0), 0, false));
         manager.mark_device_initialized("Device2");
         
         // Verify they're initialized
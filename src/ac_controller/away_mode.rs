@@ -0,0 +1,91 @@
+//! Away mode
+//!
+//! While away mode is on, the user is considered away regardless of the user home override,
+//! presence detection or the time schedule, so nodesets see `is_user_home` false and nobody home.
+//! It is meant to be flipped by a phone automation when leaving and returning home, through the
+//! signed `POST /api/hooks/away` webhook. The setting is stored in the database and survives restarts.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crate::{
+    ac_controller::{devices::AcDevice, node_executor},
+    db::{self, DbPool},
+};
+
+/// Settings key of the stored away mode flag
+const AWAY_MODE_KEY: &str = "away_mode";
+
+/// Global away mode state
+static AWAY_MODE: OnceLock<Arc<AwayMode>> = OnceLock::new();
+
+/// Whether the user is away from home
+#[derive(Default)]
+pub struct AwayMode {
+    enabled: AtomicBool,
+}
+
+impl AwayMode {
+    /// Check if away mode is on
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Load the stored setting, away mode is off if it was never set
+    pub async fn load(&self, pool: &DbPool) -> Result<(), sqlx::Error> {
+        let enabled = db::settings::get(pool, AWAY_MODE_KEY).await?.as_deref() == Some("true");
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if enabled {
+            log::info!("Away mode is on, the user is considered away");
+        }
+        Ok(())
+    }
+
+    /// Turn away mode on or off and store the setting
+    /// Returns true if the mode changed
+    pub async fn set_enabled(&self, pool: &DbPool, enabled: bool) -> Result<bool, sqlx::Error> {
+        db::settings::set(pool, AWAY_MODE_KEY, if enabled { "true" } else { "false" }).await?;
+        let changed = self.enabled.swap(enabled, Ordering::SeqCst) != enabled;
+        if changed {
+            log::info!("Away mode turned {}", if enabled { "on" } else { "off" });
+        }
+        Ok(changed)
+    }
+}
+
+/// Get the global away mode instance
+pub fn get_away_mode() -> &'static Arc<AwayMode> {
+    AWAY_MODE.get_or_init(Arc::default)
+}
+
+/// Evaluate the devices right away, so a changed away mode doesn't wait for the next cycle
+pub fn apply_now(devices: Vec<AcDevice>) {
+    tokio::spawn(async move {
+        for device in devices {
+            if let node_executor::NodeExecutionResult::Error(e) = node_executor::execute_nodeset_for_device(&device).await {
+                log::error!("Failed to execute nodeset for {} after away mode change: {}", device.as_str(), e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_away_mode_survives_reload() {
+        let pool = db::connect_in_memory().await;
+        let away_mode = AwayMode::default();
+        away_mode.load(&pool).await.unwrap();
+        assert!(!away_mode.is_enabled());
+
+        assert!(away_mode.set_enabled(&pool, true).await.unwrap());
+        assert!(!away_mode.set_enabled(&pool, true).await.unwrap(), "already on");
+        assert!(away_mode.is_enabled());
+
+        let reloaded = AwayMode::default();
+        reloaded.load(&pool).await.unwrap();
+        assert!(reloaded.is_enabled());
+    }
+}
@@ -0,0 +1,343 @@
+//! Read-back of the AC state after a command
+//!
+//! A command the controller accepted doesn't always change the unit, e.g. when its IR signal didn't
+//! reach it. For devices with `supports_state_readback`, the state the unit reports is read back
+//! `command_verification.delay_seconds` after every command and compared with what was sent. On a
+//! mismatch the command is resent up to `max_retries` times. If the unit still disagrees, the command
+//! is reported as not applied: a `CommandNotApplied` live event is published, the device is flagged
+//! on the dashboard and the discrepancies are recorded on the execution that sent the command.
+//! A newer command to the device supersedes a pending verification, whichever path sent it (see
+//! `device_requests::ac`). Nothing is resent while a manual override is active, shadow mode is on
+//! or the unit is in manual mode, and resends are held to the same power budget, minimum on/off
+//! times and do-not-disturb windows as the command itself.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::{
+    ac_controller::{
+        ac_executor::{ac_mode_name, AcState},
+        node_executor,
+    },
+    config, db,
+    device_requests::{self, ac::ReportedState},
+    live_events::{self, LiveEvent},
+    types::{AcCommand, Celsius, CommandVerificationConfig},
+};
+
+/// Temperatures closer than this are considered equal
+const TEMPERATURE_TOLERANCE: f64 = 0.05;
+
+/// Global command verifier
+static COMMAND_VERIFIER: OnceLock<CommandVerifier> = OnceLock::new();
+
+/// Describe how the state a unit reports differs from the state sent to it
+/// Settings the unit doesn't report are not compared. Returns an empty list when they agree
+pub fn find_discrepancies(sent: &AcState, reported: &ReportedState) -> Vec<String> {
+    if sent.is_on != reported.is_on {
+        return vec![format!(
+            "the AC was turned {} but reports being {}",
+            on_off(sent.is_on),
+            on_off(reported.is_on)
+        )];
+    }
+    if !sent.is_on {
+        return Vec::new();
+    }
+
+    let mut discrepancies = Vec::new();
+    if let (Some(sent_mode), Some(reported_mode)) = (sent.mode, reported.mode)
+        && sent_mode != reported_mode
+    {
+        discrepancies.push(format!(
+            "mode {} was sent but the AC reports {}",
+            mode_label(sent_mode),
+            mode_label(reported_mode)
+        ));
+    }
+    if let (Some(sent_temperature), Some(reported_temperature)) = (sent.temperature, reported.temperature)
+        && (sent_temperature.0 - reported_temperature).abs() > TEMPERATURE_TOLERANCE
+    {
+        discrepancies.push(format!(
+            "{:.1}°C was sent but the AC reports {:.1}°C",
+            sent_temperature.0, reported_temperature
+        ));
+    }
+    if let (Some(sent_fan_speed), Some(reported_fan_speed)) = (sent.fan_speed, reported.fan_speed)
+        && sent_fan_speed != reported_fan_speed
+    {
+        discrepancies.push(format!(
+            "fan speed {} was sent but the AC reports {}",
+            sent_fan_speed, reported_fan_speed
+        ));
+    }
+    discrepancies
+}
+
+fn on_off(is_on: bool) -> &'static str {
+    if is_on { "on" } else { "off" }
+}
+
+/// The state a unit reports, with the settings it doesn't report taken from the state sent to it
+fn reported_as_state(sent: &AcState, reported: &ReportedState) -> AcState {
+    if !reported.is_on {
+        return AcState::new_off();
+    }
+    let sent_temperature = sent.temperature.map(|t| t.0);
+    AcState {
+        is_on: true,
+        mode: reported.mode.or(sent.mode),
+        temperature: reported.temperature.or(sent_temperature).map(Celsius),
+        fan_speed: reported.fan_speed.or(sent.fan_speed),
+        ..sent.clone()
+    }
+}
+
+fn mode_label(mode: i32) -> String {
+    ac_mode_name(mode).map_or_else(|| mode.to_string(), String::from)
+}
+
+/// Verification of the last command of a device
+#[derive(Debug, Default)]
+struct DeviceVerification {
+    /// Incremented for every command, so a superseded verification can tell
+    generation: u64,
+    /// Discrepancies of the last verified command, empty if it was applied
+    not_applied: Vec<String>,
+}
+
+/// Tracks the verification of the last command per device
+pub struct CommandVerifier {
+    devices: RwLock<HashMap<String, DeviceVerification>>,
+}
+
+impl CommandVerifier {
+    pub(super) fn new() -> Self {
+        Self {
+            devices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// A command was sent to a device, superseding the verification of its previous one
+    /// Returns the generation the verification of this command finishes with
+    pub fn start(&self, device: &str) -> u64 {
+        let mut devices = self.devices.write().unwrap();
+        let verification = devices.entry(device.to_string()).or_default();
+        verification.generation += 1;
+        verification.not_applied.clear();
+        verification.generation
+    }
+
+    /// Whether no newer command was sent to the device since `generation` started
+    pub fn is_current(&self, device: &str, generation: u64) -> bool {
+        self.devices
+            .read()
+            .unwrap()
+            .get(device)
+            .is_some_and(|verification| verification.generation == generation)
+    }
+
+    /// Record the discrepancies a verification ended with
+    /// Returns false if a newer command superseded it, in which case nothing is recorded
+    pub fn finish(&self, device: &str, generation: u64, discrepancies: Vec<String>) -> bool {
+        let mut devices = self.devices.write().unwrap();
+        match devices.get_mut(device) {
+            Some(verification) if verification.generation == generation => {
+                verification.not_applied = discrepancies;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Discrepancies of the device's last command, empty if it was applied or not verified (yet)
+    pub fn get_not_applied(&self, device: &str) -> Vec<String> {
+        self.devices
+            .read()
+            .unwrap()
+            .get(device)
+            .map(|verification| verification.not_applied.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Get the global command verifier
+pub fn get_command_verifier() -> &'static CommandVerifier {
+    COMMAND_VERIFIER.get_or_init(CommandVerifier::new)
+}
+
+/// A command waiting to be verified
+struct PendingCommand {
+    device: String,
+    state: AcState,
+    cause_id: i32,
+    annotation: Option<String>,
+    generation: u64,
+    /// Unix timestamp the command was sent at
+    sent_at: i64,
+}
+
+/// Verify a command that was just sent to a device, in the background
+/// Does nothing for devices that don't support reading back their state
+pub fn schedule(device_name: &str, sent: &AcState, cause_id: i32, annotation: Option<&str>) {
+    let cfg = config::get_config();
+    if !cfg.supports_state_readback(device_name) {
+        return;
+    }
+
+    let command = PendingCommand {
+        device: device_name.to_string(),
+        state: sent.clone(),
+        cause_id,
+        annotation: annotation.map(String::from),
+        generation: get_command_verifier().start(device_name),
+        sent_at: chrono::Utc::now().timestamp(),
+    };
+    let settings = cfg.command_verification.clone();
+    let cycle_timeout_seconds = cfg.device_cycle_timeout_seconds as i64;
+    tokio::spawn(async move {
+        verify(command, settings, cycle_timeout_seconds).await;
+    });
+}
+
+/// Read back the unit's state, resending the command while it disagrees
+async fn verify(command: PendingCommand, settings: CommandVerificationConfig, cycle_timeout_seconds: i64) {
+    let device = command.device.as_str();
+    let verifier = get_command_verifier();
+
+    let mut discrepancies = Vec::new();
+    let mut reported_state = command.state.clone();
+    for attempt in 0..=settings.max_retries {
+        if attempt > 0 {
+            // A newer command may have been sent while the state was read back
+            if !verifier.is_current(device, command.generation) {
+                log::debug!("Verification of the command to {} superseded by a newer command", device);
+                return;
+            }
+            if let Some(reason) = node_executor::resend_blocks(device, &reported_state, &command.state) {
+                log::info!("Not resending the command to {}, the device {}", device, reason);
+                break;
+            }
+            log::warn!(
+                "Command to {} was not applied ({}), resending (retry {}/{})",
+                device,
+                discrepancies.join("; "),
+                attempt,
+                settings.max_retries
+            );
+            if let Err(e) = resend(&command).await {
+                log::warn!("Failed to resend the command to {}: {}", device, e);
+                return;
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(settings.delay_seconds)).await;
+        if !verifier.is_current(device, command.generation) {
+            log::debug!("Verification of the command to {} superseded by a newer command", device);
+            return;
+        }
+        let reported = match device_requests::ac::get_reported_state(device).await {
+            Ok(reported) => reported,
+            Err(e) => {
+                log::warn!("Failed to read back the state of {}: {}", device, e);
+                return;
+            }
+        };
+        discrepancies = find_discrepancies(&command.state, &reported);
+        reported_state = reported_as_state(&command.state, &reported);
+        if discrepancies.is_empty() {
+            break;
+        }
+    }
+
+    if !verifier.finish(device, command.generation, discrepancies.clone()) {
+        return;
+    }
+    if discrepancies.is_empty() {
+        log::debug!("Command to {} was applied", device);
+    } else {
+        log::warn!("CommandNotApplied on {}: {}", device, discrepancies.join("; "));
+        live_events::get_live_events().publish(LiveEvent::command_not_applied(device, discrepancies.clone()));
+    }
+
+    let pool = db::get_pool().await;
+    let since = command.sent_at - cycle_timeout_seconds;
+    if let Err(e) =
+        db::execution_history::set_command_discrepancies(pool, device, since, command.sent_at, &discrepancies).await
+    {
+        log::warn!("Failed to record the verification of the command to {}: {}", device, e);
+    }
+}
+
+/// Send a command again, powerful mode is left as it is
+async fn resend(command: &PendingCommand) -> Result<bool, Box<dyn std::error::Error>> {
+    let ac_command = AcCommand::from_state(&command.state)?;
+    let annotation = command.annotation.as_deref();
+    Ok(device_requests::ac::resend_command(&command.device, &ac_command, command.cause_id, annotation).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ac_controller::ac_executor::{AC_MODE_COOL, AC_MODE_HEAT};
+
+    fn reported(is_on: bool, mode: Option<i32>, temperature: Option<f64>) -> ReportedState {
+        ReportedState {
+            is_on,
+            mode,
+            temperature,
+            fan_speed: None,
+        }
+    }
+
+    #[test]
+    fn test_find_discrepancies() {
+        let heating = AcState::new_on(AC_MODE_HEAT, 0, Celsius(21.5), 0, false);
+        assert!(find_discrepancies(&heating, &reported(true, Some(AC_MODE_HEAT), Some(21.5))).is_empty());
+        assert!(find_discrepancies(&heating, &reported(true, None, None)).is_empty(), "unreported settings");
+        assert!(find_discrepancies(&AcState::new_off(), &reported(false, Some(AC_MODE_COOL), None)).is_empty());
+
+        assert_eq!(
+            find_discrepancies(&heating, &reported(false, None, None)),
+            vec!["the AC was turned on but reports being off"]
+        );
+        assert_eq!(
+            find_discrepancies(&heating, &reported(true, Some(AC_MODE_COOL), Some(24.0))),
+            vec![
+                "mode Heat was sent but the AC reports Cool",
+                "21.5°C was sent but the AC reports 24.0°C",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reported_state_keeps_unreported_settings() {
+        let heating = AcState::new_on(AC_MODE_HEAT, 2, Celsius(21.5), 1, false);
+        assert_eq!(reported_as_state(&heating, &reported(false, Some(AC_MODE_HEAT), None)), AcState::new_off());
+        assert_eq!(
+            reported_as_state(&heating, &reported(true, Some(AC_MODE_COOL), None)),
+            AcState::new_on(AC_MODE_COOL, 2, Celsius(21.5), 1, false)
+        );
+    }
+
+    #[test]
+    fn test_newer_command_supersedes_verification() {
+        let verifier = CommandVerifier::new();
+        let not_applied = vec!["the AC was turned on but reports being off".to_string()];
+
+        let first = verifier.start("Veranda");
+        let second = verifier.start("Veranda");
+        assert!(!verifier.is_current("Veranda", first));
+        assert!(!verifier.finish("Veranda", first, not_applied.clone()));
+        assert!(verifier.get_not_applied("Veranda").is_empty());
+
+        assert!(verifier.finish("Veranda", second, not_applied.clone()));
+        assert_eq!(verifier.get_not_applied("Veranda"), not_applied);
+
+        // The next command clears the flag until it is verified itself
+        verifier.start("Veranda");
+        assert!(verifier.get_not_applied("Veranda").is_empty());
+        assert!(verifier.get_not_applied("LivingRoom").is_empty());
+    }
+}
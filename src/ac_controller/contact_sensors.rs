@@ -0,0 +1,111 @@
+//! Window and door contact sensors
+//!
+//! Contact sensors report whether a window or door is open through
+//! `POST /api/sensors/contact/{name}`. Each configured sensor (`contact_sensors`) belongs to the
+//! room of a device, and nodesets get `is_window_open` when any sensor in the room reports open,
+//! so a profile can stop heating while the veranda door is left open. A sensor that opens or
+//! closes also counts as a door event for the occupancy model. The state is kept in memory, a
+//! sensor reads as closed until it reports after a restart.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::types::ContactSensorConfig;
+
+/// Global contact sensor state
+static CONTACT_SENSOR_STATE: OnceLock<Arc<ContactSensorState>> = OnceLock::new();
+
+/// Last reported state of a contact sensor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactReading {
+    pub is_open: bool,
+    /// When the sensor last changed between open and closed
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Thread-safe open/closed state of every contact sensor
+#[derive(Default)]
+pub struct ContactSensorState {
+    sensors: RwLock<HashMap<String, ContactReading>>,
+}
+
+impl ContactSensorState {
+    /// Record the state a sensor reports
+    /// Returns true if it changed, a repeated report keeps the time it changed at
+    pub fn report(&self, sensor: &str, is_open: bool, now: DateTime<Utc>) -> bool {
+        let mut sensors = self.sensors.write().unwrap();
+        if sensors.get(sensor).is_some_and(|reading| reading.is_open == is_open) {
+            return false;
+        }
+        sensors.insert(sensor.to_string(), ContactReading { is_open, changed_at: now });
+        true
+    }
+
+    /// Last reported state of a sensor, None if it hasn't reported yet
+    pub fn get(&self, sensor: &str) -> Option<ContactReading> {
+        self.sensors.read().unwrap().get(sensor).copied()
+    }
+
+    /// When the longest open sensor of each device's room opened, rooms with everything closed are missing
+    pub fn open_since_by_device(&self, sensors: &HashMap<String, ContactSensorConfig>) -> HashMap<String, DateTime<Utc>> {
+        let readings = self.sensors.read().unwrap();
+        let mut open_since: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for (name, sensor) in sensors {
+            let Some(reading) = readings.get(name).filter(|reading| reading.is_open) else {
+                continue;
+            };
+            open_since
+                .entry(sensor.device.clone())
+                .and_modify(|since| *since = (*since).min(reading.changed_at))
+                .or_insert(reading.changed_at);
+        }
+        open_since
+    }
+}
+
+/// Get the global contact sensor state
+pub fn get_contact_sensor_state() -> &'static Arc<ContactSensorState> {
+    CONTACT_SENSOR_STATE.get_or_init(Arc::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn sensors() -> HashMap<String, ContactSensorConfig> {
+        [("veranda_door", "Veranda"), ("veranda_window", "Veranda"), ("living_window", "LivingRoom")]
+            .into_iter()
+            .map(|(name, device)| (name.to_string(), ContactSensorConfig { device: device.to_string() }))
+            .collect()
+    }
+
+    #[test]
+    fn test_repeated_report_keeps_change_time() {
+        let state = ContactSensorState::default();
+        let start = Utc::now();
+
+        assert!(state.report("veranda_door", true, start));
+        assert!(!state.report("veranda_door", true, start + Duration::minutes(5)));
+        assert_eq!(state.get("veranda_door").unwrap().changed_at, start);
+
+        assert!(state.report("veranda_door", false, start + Duration::minutes(10)));
+        assert!(!state.get("veranda_door").unwrap().is_open);
+        assert!(state.get("living_window").is_none());
+    }
+
+    #[test]
+    fn test_open_since_by_device() {
+        let state = ContactSensorState::default();
+        let start = Utc::now();
+        state.report("veranda_window", true, start + Duration::minutes(3));
+        state.report("veranda_door", true, start);
+        state.report("living_window", false, start);
+        // Unconfigured sensors have no room
+        state.report("attic_hatch", true, start);
+
+        let open_since = state.open_since_by_device(&sensors());
+        assert_eq!(open_since, HashMap::from([("Veranda".to_string(), start)]));
+    }
+}
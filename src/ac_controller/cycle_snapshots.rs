@@ -0,0 +1,265 @@
+//! Per-cycle snapshots of execution inputs and decisions
+//!
+//! Every nodeset evaluation stores its inputs, flattened to dotted keys such as
+//! `active_command.temperature`, together with a short description of the decision. Comparing a
+//! snapshot with the previous one of the same device shows which inputs changed, which answers
+//! "what changed that made it turn on" without reading through the logs.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    db::{self, DbPool},
+    nodes::{ExecutionInputs, ExecutionResult},
+};
+
+/// Snapshots older than this are deleted when new ones are recorded
+const SNAPSHOT_RETENTION_DAYS: i64 = 7;
+
+/// Flattened input values by dotted key
+pub type InputSnapshot = BTreeMap<String, serde_json::Value>;
+
+/// An input whose value differs from the previous cycle
+#[derive(Debug, Clone, Serialize, PartialEq, utoipa::ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct InputChange {
+    pub key: String,
+    /// Value in the previous cycle, None if the input didn't exist yet
+    pub previous: Option<serde_json::Value>,
+    /// Value in this cycle, None if the input no longer exists
+    pub current: Option<serde_json::Value>,
+}
+
+/// Flatten the execution inputs into a snapshot
+pub fn snapshot_inputs(inputs: &ExecutionInputs) -> InputSnapshot {
+    let mut snapshot = InputSnapshot::new();
+    match serde_json::to_value(inputs) {
+        Ok(value) => flatten_into(&mut snapshot, String::new(), value),
+        Err(e) => log::warn!("Failed to serialize execution inputs for {}: {}", inputs.device, e),
+    }
+    snapshot
+}
+
+fn flatten_into(snapshot: &mut InputSnapshot, prefix: String, value: serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                flatten_into(snapshot, key, value);
+            }
+        }
+        value => {
+            snapshot.insert(prefix, value);
+        }
+    }
+}
+
+/// Rebuild the execution inputs from a snapshot, the reverse of `snapshot_inputs`
+/// Inputs missing from the snapshot, e.g. ones added since it was recorded, get their defaults
+pub fn inputs_from_snapshot(snapshot: &InputSnapshot) -> Result<ExecutionInputs, serde_json::Error> {
+    let mut root = serde_json::Map::new();
+    for (key, value) in snapshot {
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let Some(last) = parts.pop() else { continue };
+        let mut object = &mut root;
+        for part in parts {
+            let entry = object.entry(part.to_string()).or_insert(serde_json::Value::Null);
+            if !entry.is_object() {
+                *entry = serde_json::Value::Object(serde_json::Map::new());
+            }
+            object = entry.as_object_mut().expect("entry was just made an object");
+        }
+        object.insert(last.to_string(), value.clone());
+    }
+    serde_json::from_value(serde_json::Value::Object(root))
+}
+
+/// Short description of the decision of an evaluation
+pub fn describe_decision(result: &ExecutionResult) -> String {
+    if let Some(error) = &result.error {
+        return format!("Error: {}", error);
+    }
+    if let Some(action) = &result.action {
+        if action.mode == "Off" {
+            return "Execute Action: Off".to_string();
+        }
+        return format!("Execute Action: {} {}°C", action.mode, action.temperature);
+    }
+    result
+        .terminal_type
+        .clone()
+        .unwrap_or_else(|| "No terminal node reached".to_string())
+}
+
+/// Inputs that differ between two snapshots, sorted by key
+pub fn diff_inputs(previous: &InputSnapshot, current: &InputSnapshot) -> Vec<InputChange> {
+    let mut keys: Vec<&String> = previous.keys().chain(current.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter(|key| previous.get(*key) != current.get(*key))
+        .map(|key| InputChange {
+            key: key.clone(),
+            previous: previous.get(key).cloned(),
+            current: current.get(key).cloned(),
+        })
+        .collect()
+}
+
+/// Store the snapshot of a cycle and delete the device's expired snapshots
+/// Failures are logged only, a missing snapshot must not stop the control cycle
+pub async fn record_cycle(
+    pool: &DbPool,
+    device_name: &str,
+    snapshot: &InputSnapshot,
+    result: &ExecutionResult,
+    now: i64,
+) {
+    let inputs = match serde_json::to_string(snapshot) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            log::warn!("Failed to serialize cycle snapshot for {}: {}", device_name, e);
+            return;
+        }
+    };
+
+    if let Err(e) = db::cycle_snapshots::insert(pool, device_name, now, &inputs, &describe_decision(result)).await {
+        log::warn!("Failed to record cycle snapshot for {}: {}", device_name, e);
+        return;
+    }
+
+    let cutoff = now - SNAPSHOT_RETENTION_DAYS * 24 * 60 * 60;
+    if let Err(e) = db::cycle_snapshots::delete_older_than(pool, device_name, cutoff).await {
+        log::warn!("Failed to delete expired cycle snapshots for {}: {}", device_name, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::{ActionResult, ActiveCommandData};
+    use crate::types::{Celsius, Minutes};
+
+    fn result_with_action(mode: &str) -> ExecutionResult {
+        ExecutionResult {
+            completed: true,
+            terminal_type: Some("Execute Action".to_string()),
+            action: Some(ActionResult {
+                device: "Veranda".to_string(),
+                temperature: 22.0,
+                mode: mode.to_string(),
+                fan_speed: "Auto".to_string(),
+                is_powerful: false,
+                enable_swing: false,
+                cause_reason: "0".to_string(),
+                vertical_vane: None,
+                horizontal_vane: None,
+                annotation: None,
+            }),
+            do_nothing: None,
+            error: None,
+            warnings: vec![],
+            reset_active_command: false,
+            notifications: vec![],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_flattens_nested_inputs() {
+        let inputs = ExecutionInputs {
+            device: "Veranda".to_string(),
+            device_sensor_temperature: Celsius(19.5),
+            active_command: ActiveCommandData {
+                is_defined: true,
+                is_on: true,
+                ..Default::default()
+            },
+            pir_state: [("Veranda".to_string(), (true, Minutes(3)))].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let snapshot = snapshot_inputs(&inputs);
+        assert_eq!(snapshot["device_sensor_temperature"], serde_json::json!(19.5));
+        assert_eq!(snapshot["active_command.is_on"], serde_json::json!(true));
+        assert_eq!(snapshot["pir_state.Veranda"], serde_json::json!([true, 3]));
+        assert_eq!(snapshot["device_humidity"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_inputs_from_snapshot_round_trip() {
+        let inputs = ExecutionInputs {
+            device: "Veranda".to_string(),
+            outdoor_temperature: Celsius(-3.5),
+            active_command: ActiveCommandData {
+                is_defined: true,
+                mode: 4,
+                ..Default::default()
+            },
+            pir_state: [("Veranda".to_string(), (true, Minutes(3)))].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let restored = inputs_from_snapshot(&snapshot_inputs(&inputs)).unwrap();
+        assert_eq!(snapshot_inputs(&restored), snapshot_inputs(&inputs));
+        assert_eq!(restored.pir_state["Veranda"], (true, Minutes(3)));
+
+        let partial: InputSnapshot = [("device".to_string(), serde_json::json!("LivingRoom"))].into_iter().collect();
+        assert_eq!(inputs_from_snapshot(&partial).unwrap().device, "LivingRoom", "missing inputs use defaults");
+    }
+
+    #[test]
+    fn test_diff_inputs() {
+        let previous: InputSnapshot = [
+            ("outdoor_temperature".to_string(), serde_json::json!(12.0)),
+            ("is_user_home".to_string(), serde_json::json!(false)),
+            ("pir_state.Veranda".to_string(), serde_json::json!([false, 30])),
+        ]
+        .into_iter()
+        .collect();
+        let current: InputSnapshot = [
+            ("outdoor_temperature".to_string(), serde_json::json!(12.0)),
+            ("is_user_home".to_string(), serde_json::json!(true)),
+            ("net_power_watt".to_string(), serde_json::json!(-800)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(diff_inputs(&previous, &current), vec![
+            InputChange {
+                key: "is_user_home".to_string(),
+                previous: Some(serde_json::json!(false)),
+                current: Some(serde_json::json!(true)),
+            },
+            InputChange {
+                key: "net_power_watt".to_string(),
+                previous: None,
+                current: Some(serde_json::json!(-800)),
+            },
+            InputChange {
+                key: "pir_state.Veranda".to_string(),
+                previous: Some(serde_json::json!([false, 30])),
+                current: None,
+            },
+        ]);
+        assert!(diff_inputs(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_describe_decision() {
+        assert_eq!(describe_decision(&result_with_action("Heat")), "Execute Action: Heat 22°C");
+        assert_eq!(describe_decision(&result_with_action("Off")), "Execute Action: Off");
+
+        let mut result = result_with_action("Heat");
+        result.error = Some("Nodeset has no Start node".to_string());
+        assert_eq!(describe_decision(&result), "Error: Nodeset has no Start node");
+
+        let result = ExecutionResult {
+            terminal_type: Some("Do Nothing".to_string()),
+            action: None,
+            ..result_with_action("Heat")
+        };
+        assert_eq!(describe_decision(&result), "Do Nothing");
+    }
+}
@@ -0,0 +1,107 @@
+//! Control cycle timing
+//!
+//! Records how long the last control cycle and each device in it took, reported on `/api/status/details`,
+//! so a slow or unreachable controller shows up without digging through the logs.
+
+use serde::Serialize;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+/// Devices taking longer than this in a cycle are logged as slow
+const SLOW_DEVICE_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Global timing of the last cycle
+static LAST_CYCLE: OnceLock<RwLock<Option<CycleTiming>>> = OnceLock::new();
+
+/// How long a device took in a cycle
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DeviceTiming {
+    pub device: String,
+    pub duration_ms: u64,
+    /// Evaluation was abandoned after `device_cycle_timeout_seconds`
+    pub timed_out: bool,
+}
+
+impl DeviceTiming {
+    pub fn new(device: &str, duration: Duration, timed_out: bool) -> Self {
+        Self {
+            device: device.to_string(),
+            duration_ms: duration.as_millis() as u64,
+            timed_out,
+        }
+    }
+
+    fn is_slow(&self) -> bool {
+        self.timed_out || self.duration_ms > SLOW_DEVICE_THRESHOLD.as_millis() as u64
+    }
+}
+
+/// How long a control cycle took
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CycleTiming {
+    /// Unix timestamp the cycle finished at
+    pub finished_at: i64,
+    pub duration_ms: u64,
+    /// Devices were evaluated one at a time, because they claim the power budget in turn
+    pub sequential: bool,
+    pub devices: Vec<DeviceTiming>,
+}
+
+impl CycleTiming {
+    pub fn new(duration: Duration, sequential: bool, devices: Vec<DeviceTiming>) -> Self {
+        Self {
+            finished_at: chrono::Utc::now().timestamp(),
+            duration_ms: duration.as_millis() as u64,
+            sequential,
+            devices,
+        }
+    }
+
+    /// Devices that timed out or took longer than `SLOW_DEVICE_THRESHOLD`
+    pub fn slow_devices(&self) -> Vec<&DeviceTiming> {
+        self.devices.iter().filter(|timing| timing.is_slow()).collect()
+    }
+}
+
+fn last_cycle() -> &'static RwLock<Option<CycleTiming>> {
+    LAST_CYCLE.get_or_init(|| RwLock::new(None))
+}
+
+/// Log a finished cycle and keep it as the last one
+pub fn record(cycle: CycleTiming) {
+    for timing in cycle.slow_devices() {
+        if timing.timed_out {
+            log::warn!("Device {} timed out after {} ms", timing.device, timing.duration_ms);
+        } else {
+            log::warn!("Device {} was slow, it took {} ms", timing.device, timing.duration_ms);
+        }
+    }
+    log::info!("AC control cycle completed in {} ms", cycle.duration_ms);
+    *last_cycle().write().unwrap() = Some(cycle);
+}
+
+/// Timing of the last finished cycle, None before the first one finished
+pub fn get_last_cycle() -> Option<CycleTiming> {
+    last_cycle().read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_devices() {
+        let cycle = CycleTiming::new(
+            Duration::from_secs(31),
+            false,
+            vec![
+                DeviceTiming::new("LivingRoom", Duration::from_millis(800), false),
+                DeviceTiming::new("Veranda", Duration::from_secs(30), true),
+                DeviceTiming::new("Bedroom", Duration::from_secs(12), false),
+            ],
+        );
+        let slow: Vec<&str> = cycle.slow_devices().iter().map(|timing| timing.device.as_str()).collect();
+        assert_eq!(slow, vec!["Veranda", "Bedroom"]);
+        assert_eq!(cycle.duration_ms, 31000);
+    }
+}
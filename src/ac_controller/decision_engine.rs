@@ -0,0 +1,116 @@
+//! Decision engines
+//!
+//! A decision engine turns the inputs gathered for a device into a decision: an action, Do Nothing
+//! or an execution error. Everything around the decision (manual mode, overrides, load shedding,
+//! execution history and sending commands) is handled by the node executor, so another engine
+//! (e.g. ML-based or an external service) only implements `DecisionEngine` and is added to
+//! `DecisionEngineKind`. Devices select their engine with `decision_engine` in the config.
+
+use crate::{
+    config, db,
+    nodes::{ExecutionInputs, ExecutionResult, NodesetExecutor},
+    types::DecisionEngineKind,
+};
+
+use super::{
+    cycle_snapshots::{self, InputSnapshot},
+    node_executor::{self, CycleNodesets},
+    AcDevice,
+};
+
+/// What an engine decided for a device
+pub struct Decision {
+    pub result: ExecutionResult,
+    /// Nodeset the decision was made with, recorded in the execution history
+    pub nodeset_id: i64,
+    /// Revision of that nodeset
+    pub nodeset_revision: i64,
+    /// Inputs the decision was made from, including state the engine added
+    pub snapshot: InputSnapshot,
+}
+
+/// Decides what a device should do from its execution inputs
+pub trait DecisionEngine {
+    /// Make a decision for the device
+    /// Returns an error message if no decision could be made
+    async fn decide(&self, device: &AcDevice, inputs: ExecutionInputs) -> Result<Decision, String>;
+}
+
+/// Runs the device's nodeset: its assigned nodeset, or the active nodeset
+pub struct NodesetEngine<'a> {
+    /// Nodesets snapshotted for the current cycle, loaded from the database if None
+    pub nodesets: Option<&'a CycleNodesets>,
+}
+
+impl DecisionEngine for NodesetEngine<'_> {
+    async fn decide(&self, device: &AcDevice, mut inputs: ExecutionInputs) -> Result<Decision, String> {
+        let device_name = device.as_str();
+
+        // Load the nodeset assigned to the device, or the active nodeset
+        let pool = db::get_pool().await;
+        let loaded = match self.nodesets {
+            Some(nodesets) => nodesets.get(device_name),
+            None => node_executor::load_nodeset_for_device(device_name).await,
+        };
+        let (nodeset_id, nodeset_revision, nodes, edges) = loaded.map_err(|e| {
+            log::error!("Failed to load nodeset for {}: {}", device_name, e);
+            format!("Failed to load nodeset: {}", e)
+        })?;
+
+        // Hysteresis nodes continue from the state they latched in the previous execution
+        inputs.hysteresis_state = db::hysteresis_state::get(pool, nodeset_id, device_name).await.unwrap_or_else(|e| {
+            log::warn!("Failed to load hysteresis state for {}: {}", device_name, e);
+            Default::default()
+        });
+        // Cooldown nodes continue from when they last passed
+        inputs.cooldown_state = db::cooldown_state::get(pool, nodeset_id, device_name).await.unwrap_or_else(|e| {
+            log::warn!("Failed to load cooldown state for {}: {}", device_name, e);
+            Default::default()
+        });
+
+        // Validate the nodeset
+        let validation_errors = crate::nodes::validate_nodeset_for_execution(&nodes, &edges);
+        if !validation_errors.is_empty() {
+            log::error!("Nodeset validation failed: {}", validation_errors.join("; "));
+            return Err(format!("Nodeset validation failed: {}", validation_errors.join("; ")));
+        }
+
+        // Create and execute the nodeset
+        let snapshot = cycle_snapshots::snapshot_inputs(&inputs);
+        let mut executor = NodesetExecutor::new(&nodes, &edges, inputs)
+            .map_err(|e| {
+                log::error!("Failed to create executor for {}: {}", device_name, e);
+                format!("Failed to create executor: {}", e)
+            })?
+            .with_float_tolerance(config::get_config().float_tolerance)
+            .with_now(chrono::Utc::now().timestamp());
+
+        let result = executor.execute();
+        if let Err(e) = db::hysteresis_state::replace(pool, nodeset_id, device_name, executor.hysteresis_state()).await {
+            log::warn!("Failed to save hysteresis state for {}: {}", device_name, e);
+        }
+        if let Err(e) = db::cooldown_state::replace(pool, nodeset_id, device_name, executor.cooldown_state()).await {
+            log::warn!("Failed to save cooldown state for {}: {}", device_name, e);
+        }
+
+        Ok(Decision {
+            result,
+            nodeset_id,
+            nodeset_revision,
+            snapshot,
+        })
+    }
+}
+
+/// Make a decision for a device with the engine it is configured to use
+/// `nodesets` are the nodesets snapshotted for the current cycle, if any
+pub async fn decide(
+    kind: DecisionEngineKind,
+    device: &AcDevice,
+    inputs: ExecutionInputs,
+    nodesets: Option<&CycleNodesets>,
+) -> Result<Decision, String> {
+    match kind {
+        DecisionEngineKind::Nodeset => NodesetEngine { nodesets }.decide(device, inputs).await,
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::nodes::DemandResponseData;
+
+/// Global demand-response state manager
+static DEMAND_RESPONSE_STATE: OnceLock<Arc<DemandResponseState>> = OnceLock::new();
+
+/// A utility demand-response event announced by an external aggregator
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrEvent {
+    /// Curtailment level requested by the utility (higher means more load reduction)
+    pub level: i64,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Thread-safe demand-response state
+pub struct DemandResponseState {
+    /// The current or upcoming event, if any
+    event: RwLock<Option<DrEvent>>,
+}
+
+impl DemandResponseState {
+    fn new() -> Self {
+        Self {
+            event: RwLock::new(None),
+        }
+    }
+
+    /// Set the current or upcoming event, replacing any previous one
+    pub fn set_event(&self, event: DrEvent) {
+        log::info!(
+            "Demand-response event set: level={}, {} - {}",
+            event.level, event.starts_at, event.ends_at
+        );
+        let mut current = self.event.write().unwrap();
+        *current = Some(event);
+    }
+
+    /// Cancel the current or upcoming event
+    pub fn clear_event(&self) {
+        let mut current = self.event.write().unwrap();
+        if current.take().is_some() {
+            log::info!("Demand-response event cleared");
+        }
+    }
+
+    /// Get the event if it has not ended yet
+    pub fn get_event(&self) -> Option<DrEvent> {
+        let current = self.event.read().unwrap();
+        current.clone().filter(|e| e.ends_at > Utc::now())
+    }
+
+    /// Get the demand-response data for node execution
+    pub fn get_data(&self) -> DemandResponseData {
+        get_data_at(self.get_event().as_ref(), Utc::now())
+    }
+}
+
+/// Get the global demand-response state instance
+pub fn get_demand_response_state() -> &'static Arc<DemandResponseState> {
+    DEMAND_RESPONSE_STATE.get_or_init(|| Arc::new(DemandResponseState::new()))
+}
+
+/// Compute the demand-response data for an event at the given time
+fn get_data_at(event: Option<&DrEvent>, now: DateTime<Utc>) -> DemandResponseData {
+    match event {
+        Some(event) if now >= event.ends_at => DemandResponseData::default(),
+        Some(event) if now >= event.starts_at => DemandResponseData {
+            event_active: true,
+            level: event.level,
+            minutes_until_event: 0,
+        },
+        Some(event) => {
+            // Round up so an event starting in 30 seconds doesn't report 0 minutes
+            let seconds = event.starts_at.signed_duration_since(now).num_seconds();
+            DemandResponseData {
+                event_active: false,
+                level: event.level,
+                minutes_until_event: (seconds + 59) / 60,
+            }
+        }
+        None => DemandResponseData::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn event(now: DateTime<Utc>, start_offset_min: i64, duration_min: i64) -> DrEvent {
+        DrEvent {
+            level: 2,
+            starts_at: now + Duration::minutes(start_offset_min),
+            ends_at: now + Duration::minutes(start_offset_min + duration_min),
+        }
+    }
+
+    #[test]
+    fn test_no_event() {
+        let data = get_data_at(None, Utc::now());
+        assert_eq!(data, DemandResponseData::default());
+    }
+
+    #[test]
+    fn test_upcoming_event() {
+        let now = Utc::now();
+        let data = get_data_at(Some(&event(now, 45, 60)), now);
+
+        assert!(!data.event_active);
+        assert_eq!(data.level, 2);
+        assert_eq!(data.minutes_until_event, 45);
+    }
+
+    #[test]
+    fn test_upcoming_event_rounds_up() {
+        let now = Utc::now();
+        let mut e = event(now, 0, 60);
+        e.starts_at = now + Duration::seconds(30);
+
+        assert_eq!(get_data_at(Some(&e), now).minutes_until_event, 1);
+    }
+
+    #[test]
+    fn test_active_event() {
+        let now = Utc::now();
+        let data = get_data_at(Some(&event(now, -10, 60)), now);
+
+        assert!(data.event_active);
+        assert_eq!(data.level, 2);
+        assert_eq!(data.minutes_until_event, 0);
+    }
+
+    #[test]
+    fn test_ended_event() {
+        let now = Utc::now();
+        let data = get_data_at(Some(&event(now, -120, 60)), now);
+        assert_eq!(data, DemandResponseData::default());
+    }
+
+    #[test]
+    fn test_set_and_clear_event() {
+        let state = DemandResponseState::new();
+        let now = Utc::now();
+
+        state.set_event(event(now, -5, 30));
+        assert!(state.get_event().is_some());
+        assert!(state.get_data().event_active);
+
+        state.clear_event();
+        assert!(state.get_event().is_none());
+        assert!(!state.get_data().event_active);
+    }
+
+    #[test]
+    fn test_ended_event_is_not_returned() {
+        let state = DemandResponseState::new();
+        state.set_event(event(Utc::now(), -120, 60));
+        assert!(state.get_event().is_none());
+    }
+}
@@ -0,0 +1,124 @@
+//! Per-device evaluation timers
+//!
+//! Each device is evaluated on its own interval: the one set for it through
+//! `PUT /api/nodes/nodesets/assignments/:device/interval`, otherwise `evaluate_every_minutes` of the
+//! active nodeset's Start node. The AC controller sleeps until the next device is due and evaluates
+//! all devices that are due then in one cycle, so the power budget still sees them together.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::AcDevice;
+use crate::db;
+
+/// Evaluation interval of every device
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationIntervals {
+    /// Interval of the active nodeset, for devices without their own
+    pub default_minutes: i32,
+    /// Intervals set per device
+    pub devices: HashMap<String, i32>,
+}
+
+impl EvaluationIntervals {
+    /// Load the active nodeset's interval and the device intervals
+    pub async fn load() -> Self {
+        let default_minutes = db::nodesets::get_evaluate_every_minutes().await;
+        let pool = db::get_pool().await;
+        let devices = match db::device_nodesets::get_intervals(pool).await {
+            Ok(intervals) => intervals.into_iter().collect(),
+            Err(e) => {
+                log::warn!("Failed to load the device evaluation intervals: {}. Using the active profile's.", e);
+                HashMap::new()
+            }
+        };
+        Self { default_minutes, devices }
+    }
+
+    /// Minutes between evaluations of a device
+    pub fn minutes(&self, device: &str) -> i32 {
+        self.devices.get(device).copied().unwrap_or(self.default_minutes)
+    }
+
+    fn duration(&self, device: &str) -> Duration {
+        Duration::from_secs(self.minutes(device).max(1) as u64 * 60)
+    }
+}
+
+/// When each device is evaluated next
+#[derive(Debug, Default)]
+pub struct DeviceTimers {
+    next_due: HashMap<String, Instant>,
+}
+
+impl DeviceTimers {
+    /// Devices that are due at `now`, devices without a timer yet are due right away
+    pub fn due(&self, devices: &[AcDevice], now: Instant) -> Vec<AcDevice> {
+        devices
+            .iter()
+            .filter(|device| self.next_due.get(device.as_str()).is_none_or(|due| *due <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Schedule the next evaluation of devices that were evaluated at `now`
+    pub fn reschedule(&mut self, devices: &[AcDevice], intervals: &EvaluationIntervals, now: Instant) {
+        for device in devices {
+            self.next_due
+                .insert(device.as_str().to_string(), now + intervals.duration(device.as_str()));
+        }
+    }
+
+    /// Time until the next device is due, zero if one is due already
+    pub fn until_next(&self, devices: &[AcDevice], intervals: &EvaluationIntervals, now: Instant) -> Duration {
+        devices
+            .iter()
+            .map(|device| match self.next_due.get(device.as_str()) {
+                Some(due) => due.saturating_duration_since(now),
+                None => Duration::ZERO,
+            })
+            .min()
+            .unwrap_or_else(|| intervals.duration(""))
+    }
+
+    /// Make every device due right away, e.g. after leaving deep idle
+    pub fn reset(&mut self) {
+        self.next_due.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ac_controller::DeviceRegistry, types::Config};
+
+    #[test]
+    fn test_devices_run_on_their_own_interval() {
+        let registry = DeviceRegistry::from_config(&Config::default());
+        let devices = registry.all();
+        let intervals = EvaluationIntervals {
+            default_minutes: 3,
+            devices: HashMap::from([("Veranda".to_string(), 15)]),
+        };
+        let mut timers = DeviceTimers::default();
+        let start = Instant::now();
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+
+        // Everything is due on startup
+        assert_eq!(timers.due(devices, start).len(), 2);
+        timers.reschedule(devices, &intervals, start);
+        assert_eq!(timers.until_next(devices, &intervals, start), minutes(3));
+
+        // Only the living room is due after 3 minutes
+        let due = timers.due(devices, start + minutes(3));
+        assert_eq!(due.iter().map(AcDevice::as_str).collect::<Vec<_>>(), vec!["LivingRoom"]);
+        timers.reschedule(&due, &intervals, start + minutes(3));
+        assert_eq!(timers.until_next(devices, &intervals, start + minutes(3)), minutes(3));
+
+        let due = timers.due(devices, start + minutes(15));
+        assert_eq!(due.len(), 2, "the veranda after 15 minutes and the living room, which is overdue");
+
+        timers.reset();
+        assert_eq!(timers.until_next(devices, &intervals, start), Duration::ZERO);
+    }
+}
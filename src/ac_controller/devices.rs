@@ -1,35 +1,90 @@
-/// AcDevices must be defined in config
-#[derive(Debug)]
-pub enum AcDevices {
-    LivingRoom,
-    Veranda,
+//! Registry of the AC devices in `ac_controller_endpoints`
+//!
+//! Devices are not hardcoded: every endpoint in the config is a device, identified by its key.
+//! The registry is built from the current config when needed, so a device added to the config
+//! is controlled from the next cycle on after a config reload, without recompiling or restarting.
+
+use crate::{config, types::Config};
+
+/// An AC device configured in `ac_controller_endpoints`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AcDevice {
+    name: String,
+    endpoint: String,
 }
 
-impl AcDevices {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            AcDevices::LivingRoom => "LivingRoom",
-            AcDevices::Veranda => "Veranda",
-        }
+impl AcDevice {
+    /// Identifier of the device, its key in `ac_controller_endpoints`
+    pub fn as_str(&self) -> &str {
+        &self.name
     }
 
-    /// Convert a device name string to AcDevices enum
-    /// Returns None if the device name is not recognized
-    pub fn from_str(device: &str) -> Option<Self> {
-        match device {
-            "LivingRoom" => Some(AcDevices::LivingRoom),
-            "Veranda" => Some(AcDevices::Veranda),
-            _ => None,
+    /// Base URL of the device's IR controller
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+/// The configured AC devices, sorted by name
+/// Also lists the configured PIR zones and sensors, which profiles select like devices
+#[derive(Debug, Clone, Default)]
+pub struct DeviceRegistry {
+    devices: Vec<AcDevice>,
+    pir_zones: Vec<String>,
+    pir_sensors: Vec<String>,
+}
+
+impl DeviceRegistry {
+    /// Build the registry from the endpoints in a config
+    pub fn from_config(config: &Config) -> Self {
+        let mut devices: Vec<AcDevice> = config
+            .ac_controller_endpoints
+            .iter()
+            .map(|(name, props)| AcDevice {
+                name: name.clone(),
+                endpoint: props.endpoint.clone(),
+            })
+            .collect();
+        devices.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut pir_sensors: Vec<String> = config.pir_sensors.keys().cloned().collect();
+        pir_sensors.sort();
+        Self {
+            devices,
+            pir_zones: config.pir_zones().into_keys().collect(),
+            pir_sensors,
         }
     }
 
-    /// Get all AC devices for iteration
-    pub fn all() -> Vec<Self> {
-        vec![
-            AcDevices::LivingRoom,
-            AcDevices::Veranda,
-        ]
+    /// All devices, for iteration
+    pub fn all(&self) -> &[AcDevice] {
+        &self.devices
+    }
+
+    /// Look up a device by name
+    /// Returns None if no device with this name is configured (case sensitive)
+    pub fn get(&self, device: &str) -> Option<&AcDevice> {
+        self.devices.iter().find(|d| d.name == device)
+    }
+
+    /// Names of all devices
+    pub fn names(&self) -> Vec<String> {
+        self.devices.iter().map(|d| d.name.clone()).collect()
     }
+
+    /// Names of the configured PIR zones, sorted
+    pub fn pir_zone_names(&self) -> &[String] {
+        &self.pir_zones
+    }
+
+    /// IDs of the configured PIR sensors, sorted
+    pub fn pir_sensor_names(&self) -> &[String] {
+        &self.pir_sensors
+    }
+}
+
+/// Registry of the devices in the current global config
+pub fn get_device_registry() -> DeviceRegistry {
+    DeviceRegistry::from_config(&config::get_config())
 }
 
 #[cfg(test)]
@@ -37,32 +92,27 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_ac_devices_as_str() {
-        assert_eq!(AcDevices::LivingRoom.as_str(), "LivingRoom");
-        assert_eq!(AcDevices::Veranda.as_str(), "Veranda");
-    }
+    fn test_registry_from_config() {
+        let registry = DeviceRegistry::from_config(&Config::default());
+        assert_eq!(registry.names(), vec!["LivingRoom", "Veranda"]);
 
-    #[test]
-    fn test_ac_devices_from_str() {
-        // Test valid device names
-        assert!(matches!(AcDevices::from_str("LivingRoom"), Some(AcDevices::LivingRoom)));
-        assert!(matches!(AcDevices::from_str("Veranda"), Some(AcDevices::Veranda)));
-        
-        // Test invalid device names
-        assert!(AcDevices::from_str("Unknown").is_none());
-        assert!(AcDevices::from_str("").is_none());
-        assert!(AcDevices::from_str("livingroom").is_none()); // Case sensitive
+        let veranda = registry.get("Veranda").unwrap();
+        assert_eq!(veranda.as_str(), "Veranda");
+        assert!(!veranda.endpoint().is_empty());
+
+        assert!(registry.get("Unknown").is_none());
+        assert!(registry.get("").is_none());
+        assert!(registry.get("livingroom").is_none()); // Case sensitive
     }
 
     #[test]
-    fn test_ac_devices_round_trip() {
-        // Test that as_str and from_str are consistent
-        let devices = vec![AcDevices::LivingRoom, AcDevices::Veranda];
-        for device in devices {
-            let device_str = device.as_str();
-            let parsed = AcDevices::from_str(device_str);
-            assert!(parsed.is_some());
-            assert_eq!(parsed.unwrap().as_str(), device_str);
-        }
+    fn test_registry_includes_added_devices() {
+        let mut config = Config::default();
+        let props = config.ac_controller_endpoints["Veranda"].clone();
+        config.ac_controller_endpoints.insert("Bedroom".to_string(), props);
+
+        let registry = DeviceRegistry::from_config(&config);
+        assert_eq!(registry.names(), vec!["Bedroom", "LivingRoom", "Veranda"]);
+        assert_eq!(registry.get("Bedroom").unwrap().as_str(), "Bedroom");
     }
 }
@@ -0,0 +1,150 @@
+//! Do-not-disturb windows
+//!
+//! Every IR command makes the AC unit beep. During a configured DND window,
+//! commands that only tweak the setpoint are deferred. Since the nodeset is
+//! reevaluated periodically, a deferred command is sent on the first evaluation
+//! after the window ends. Turning on/off and mode changes are always sent.
+
+use chrono::{Local, NaiveTime};
+
+use crate::{config, types::DndWindow};
+
+use super::ac_executor::AcState;
+
+/// Parse a "HH:MM" time string
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Check if a time falls within a window. Windows may wrap past midnight (e.g. 22:00-07:00).
+/// The start is inclusive and the end is exclusive.
+fn is_time_in_window(window: &DndWindow, now: NaiveTime) -> bool {
+    let (start, end) = match (parse_time(&window.start), parse_time(&window.end)) {
+        (Some(start), Some(end)) => (start, end),
+        _ => {
+            log::warn!(
+                "Ignoring DND window with invalid time format '{}'-'{}' (expected HH:MM)",
+                window.start, window.end
+            );
+            return false;
+        }
+    };
+
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Get the DND window that is active at the given time, if any
+pub fn get_active_window(windows: &[DndWindow], now: NaiveTime) -> Option<&DndWindow> {
+    windows.iter().find(|w| is_time_in_window(w, now))
+}
+
+/// Check if a state transition is a pure setpoint tweak within the allowed change.
+/// Anything else (on/off, mode, fan, swing or powerful changes) is considered essential.
+pub fn is_non_essential_change(current: &AcState, desired: &AcState, max_setpoint_change: f64) -> bool {
+    if !current.is_on || !desired.is_on {
+        return false;
+    }
+
+    if current.mode != desired.mode
+        || current.fan_speed != desired.fan_speed
+        || current.swing != desired.swing
+        || current.powerful_mode != desired.powerful_mode
+    {
+        return false;
+    }
+
+    match (current.temperature, desired.temperature) {
+        (Some(t1), Some(t2)) => (t1.0 - t2.0).abs() <= max_setpoint_change,
+        _ => false,
+    }
+}
+
+/// Check if the command for a device should be deferred because of an active DND window
+pub fn should_defer_command(device_name: &str, current: &AcState, desired: &AcState) -> bool {
+    let config = config::get_config();
+    let windows = config.get_dnd_windows(device_name);
+    let now = Local::now().time();
+
+    match get_active_window(windows, now) {
+        Some(window) => is_non_essential_change(current, desired, window.max_setpoint_change),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Celsius;
+
+    fn window(start: &str, end: &str) -> DndWindow {
+        DndWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+            max_setpoint_change: 1.0,
+        }
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_window_same_day() {
+        let windows = vec![window("13:00", "15:00")];
+
+        assert!(get_active_window(&windows, time(13, 0)).is_some());
+        assert!(get_active_window(&windows, time(14, 30)).is_some());
+        assert!(get_active_window(&windows, time(15, 0)).is_none());
+        assert!(get_active_window(&windows, time(12, 59)).is_none());
+    }
+
+    #[test]
+    fn test_window_wraps_past_midnight() {
+        let windows = vec![window("22:00", "07:00")];
+
+        assert!(get_active_window(&windows, time(23, 0)).is_some());
+        assert!(get_active_window(&windows, time(0, 0)).is_some());
+        assert!(get_active_window(&windows, time(6, 59)).is_some());
+        assert!(get_active_window(&windows, time(7, 0)).is_none());
+        assert!(get_active_window(&windows, time(12, 0)).is_none());
+    }
+
+    #[test]
+    fn test_invalid_window_is_ignored() {
+        let windows = vec![window("late", "07:00")];
+        assert!(get_active_window(&windows, time(23, 0)).is_none());
+    }
+
+    #[test]
+    fn test_setpoint_tweak_is_non_essential() {
+        let current = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let desired = AcState::new_on(1, 0, Celsius(23.0), 1, false);
+
+        assert!(is_non_essential_change(&current, &desired, 1.0));
+        assert!(!is_non_essential_change(&current, &desired, 0.5), "Change larger than allowed is essential");
+    }
+
+    #[test]
+    fn test_power_and_mode_changes_are_essential() {
+        let cool = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+        let heat = AcState::new_on(4, 0, Celsius(22.0), 1, false);
+        let off = AcState::new_off();
+
+        assert!(!is_non_essential_change(&cool, &heat, 1.0));
+        assert!(!is_non_essential_change(&cool, &off, 1.0));
+        assert!(!is_non_essential_change(&off, &cool, 1.0));
+    }
+
+    #[test]
+    fn test_other_setting_changes_are_essential() {
+        let current = AcState::new_on(1, 0, Celsius(22.0), 1, false);
+
+        assert!(!is_non_essential_change(&current, &AcState::new_on(1, 2, Celsius(22.0), 1, false), 1.0));
+        assert!(!is_non_essential_change(&current, &AcState::new_on(1, 0, Celsius(22.0), 0, false), 1.0));
+        assert!(!is_non_essential_change(&current, &AcState::new_on(1, 0, Celsius(22.0), 1, true), 1.0));
+    }
+}
@@ -0,0 +1,171 @@
+//! Persistent log of nodeset execution results
+//!
+//! Cycle snapshots answer "what changed between cycles" for the last week. The execution history
+//! keeps the structured outcome of every execution for longer: which nodeset ran, the terminal node
+//! it reached, the action parameters and cause reason, the inputs it saw and any warnings. That answers "why did
+//! the AC turn on at 3am" without digging through the logs.
+
+use super::cycle_snapshots::InputSnapshot;
+use crate::{db::{self, DbPool}, nodes::ExecutionResult, types::db_types};
+
+/// Build the record of an execution
+fn to_record(
+    device_name: &str,
+    (nodeset_id, nodeset_revision): (i64, i64),
+    snapshot: &InputSnapshot,
+    result: &ExecutionResult,
+    now: i64,
+    is_shadow: bool,
+) -> Result<db_types::ExecutionRecord, serde_json::Error> {
+    let action = result.action.as_ref().map(serde_json::to_string).transpose()?;
+    let cause_reason = match (&result.action, &result.do_nothing) {
+        (Some(action), _) => Some(action.cause_reason.clone()),
+        (None, Some(do_nothing)) => Some(do_nothing.cause_reason.clone()),
+        (None, None) => None,
+    };
+
+    Ok(db_types::ExecutionRecord {
+        id: 0,
+        device_identifier: device_name.to_string(),
+        executed_at: now,
+        nodeset_id,
+        terminal_type: result.terminal_type.clone(),
+        action,
+        cause_reason,
+        error: result.error.clone(),
+        inputs: serde_json::to_string(snapshot)?,
+        warnings: serde_json::to_string(&result.warnings)?,
+        is_shadow,
+        nodeset_revision: Some(nodeset_revision),
+        command_discrepancies: None,
+    })
+}
+
+/// Store an execution, expired ones are deleted by the daily maintenance
+/// `nodeset` is the ID and revision of the nodeset that ran
+/// `is_shadow` marks executions in shadow mode, whose action was never sent to the AC
+/// Failures are logged only, a missing record must not stop the control cycle
+pub async fn record_execution(
+    pool: &DbPool,
+    device_name: &str,
+    nodeset: (i64, i64),
+    snapshot: &InputSnapshot,
+    result: &ExecutionResult,
+    now: i64,
+    is_shadow: bool,
+) {
+    let record = match to_record(device_name, nodeset, snapshot, result, now, is_shadow) {
+        Ok(record) => record,
+        Err(e) => {
+            log::warn!("Failed to serialize execution result for {}: {}", device_name, e);
+            return;
+        }
+    };
+
+    if let Err(e) = db::execution_history::insert(pool, &record).await {
+        log::warn!("Failed to record execution for {}: {}", device_name, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::{ActionResult, execution::DoNothingResult};
+
+    fn result() -> ExecutionResult {
+        ExecutionResult {
+            completed: true,
+            terminal_type: None,
+            action: None,
+            do_nothing: None,
+            error: None,
+            warnings: vec![],
+            reset_active_command: false,
+            notifications: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_executions() {
+        let state = crate::app_state::AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+        let now = 1_764_547_200;
+        let snapshot: InputSnapshot = [("is_user_home".to_string(), serde_json::json!(true))].into_iter().collect();
+
+        let action = ExecutionResult {
+            terminal_type: Some("Execute Action".to_string()),
+            action: Some(ActionResult {
+                device: "Veranda".to_string(),
+                temperature: 22.0,
+                mode: "Heat".to_string(),
+                fan_speed: "Auto".to_string(),
+                is_powerful: false,
+                enable_swing: true,
+                cause_reason: "3".to_string(),
+                vertical_vane: None,
+                horizontal_vane: None,
+                annotation: Some("preheat: solar=3200W".to_string()),
+            }),
+            ..result()
+        };
+        let do_nothing = ExecutionResult {
+            terminal_type: Some("Do Nothing".to_string()),
+            warnings: vec!["Cause reason '0' does not exist".to_string()],
+            do_nothing: Some(DoNothingResult {
+                device: "LivingRoom".to_string(),
+                cause_reason: "0".to_string(),
+            }),
+            ..result()
+        };
+
+        record_execution(pool, "LivingRoom", (0, 1), &snapshot, &do_nothing, now - 60, false).await;
+        record_execution(pool, "Veranda", (2, 5), &snapshot, &action, now, true).await;
+
+        let all = db::execution_history::get_recent(pool, None, 10).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].device_identifier, "Veranda");
+        assert_eq!(all[0].nodeset_id, 2);
+        assert_eq!(all[0].nodeset_revision, Some(5));
+        assert_eq!(all[0].cause_reason.as_deref(), Some("3"));
+        let stored_action: serde_json::Value = serde_json::from_str(all[0].action.as_deref().unwrap()).unwrap();
+        assert_eq!(stored_action["mode"], "Heat");
+        assert_eq!(stored_action["annotation"], "preheat: solar=3200W");
+        assert_eq!(all[1].terminal_type.as_deref(), Some("Do Nothing"));
+        assert_eq!(all[1].cause_reason.as_deref(), Some("0"));
+        assert_eq!(all[1].action, None);
+        assert_eq!(all[1].warnings, r#"["Cause reason '0' does not exist"]"#);
+        assert_eq!(all[0].warnings, "[]");
+        assert!(all[0].is_shadow && !all[1].is_shadow);
+
+        let living_room = db::execution_history::get_recent(pool, Some("LivingRoom"), 10).await.unwrap();
+        assert_eq!(living_room.len(), 1);
+        assert_eq!(living_room[0].inputs, r#"{"is_user_home":true}"#);
+    }
+
+    #[tokio::test]
+    async fn test_command_discrepancies_recorded_on_sending_execution() {
+        let state = crate::app_state::AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+        let now = 1_764_547_200;
+        let snapshot = InputSnapshot::default();
+        let action = ExecutionResult {
+            terminal_type: Some("Execute Action".to_string()),
+            ..result()
+        };
+
+        record_execution(pool, "Veranda", (2, 1), &snapshot, &action, now - 600, false).await;
+        record_execution(pool, "Veranda", (2, 1), &snapshot, &action, now - 5, false).await;
+        record_execution(pool, "Veranda", (2, 1), &snapshot, &action, now - 2, true).await;
+
+        let discrepancies = vec!["the AC was turned on but reports being off".to_string()];
+        let found = db::execution_history::set_command_discrepancies(pool, "Veranda", now - 90, now, &discrepancies).await;
+        assert!(found.unwrap());
+        // Commands without an execution in the cycle, e.g. a preheat, are not recorded
+        let found = db::execution_history::set_command_discrepancies(pool, "LivingRoom", now - 90, now, &[]).await;
+        assert!(!found.unwrap());
+
+        let executions = db::execution_history::get_recent(pool, Some("Veranda"), 10).await.unwrap();
+        let recorded: Vec<Option<&str>> = executions.iter().map(|e| e.command_discrepancies.as_deref()).collect();
+        assert_eq!(recorded, vec![None, Some(r#"["the AC was turned on but reports being off"]"#), None]);
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// UPS signals older than this are considered stale and ignored
+pub const UPS_SIGNAL_TIMEOUT_MINUTES: i64 = 10;
+
+/// Global grid state manager
+static GRID_STATE: OnceLock<Arc<GridState>> = OnceLock::new();
+
+/// Thread-safe grid/UPS state
+pub struct GridState {
+    /// Last UPS signal: (is_on_battery, received_at)
+    ups_signal: RwLock<Option<(bool, DateTime<Utc>)>>,
+}
+
+impl GridState {
+    fn new() -> Self {
+        Self {
+            ups_signal: RwLock::new(None),
+        }
+    }
+
+    /// Record a UPS status signal
+    pub fn record_ups_signal(&self, on_battery: bool) {
+        let mut signal = self.ups_signal.write().unwrap();
+        *signal = Some((on_battery, Utc::now()));
+        log::info!("UPS signal recorded: on_battery={}", on_battery);
+    }
+
+    /// Get whether the UPS reports running on battery
+    /// Returns None if no UPS signal was received or the last one is stale
+    pub fn get_ups_on_battery(&self) -> Option<bool> {
+        let signal = self.ups_signal.read().unwrap();
+        let (on_battery, received_at) = (*signal)?;
+        let minutes_ago = Utc::now().signed_duration_since(received_at).num_minutes();
+        if minutes_ago < UPS_SIGNAL_TIMEOUT_MINUTES {
+            Some(on_battery)
+        } else {
+            None
+        }
+    }
+
+    /// Check if the system is known to be running on backup power
+    pub fn is_on_backup_power(&self) -> bool {
+        self.get_ups_on_battery() == Some(true)
+    }
+}
+
+/// Get the global grid state instance
+pub fn get_grid_state() -> &'static Arc<GridState> {
+    GRID_STATE.get_or_init(|| Arc::new(GridState::new()))
+}
+
+/// Estimate whether the grid is down
+///
+/// A fresh UPS signal is trusted when available. Without one, losing meter data
+/// while solar production is zero is treated as a possible outage, since the
+/// meter and inverter both go dark when the grid drops.
+pub fn detect_possible_grid_outage(meter_available: bool, raw_solar_watt: i64, ups_on_battery: Option<bool>) -> bool {
+    match ups_on_battery {
+        Some(on_battery) => on_battery,
+        None => !meter_available && raw_solar_watt <= 0,
+    }
+}
+
+/// Check for a possible grid outage using the cached meter and solar data
+pub async fn check_possible_grid_outage() -> bool {
+    let meter_available = crate::device_requests::meter::get_latest_reading_cached().await.is_ok();
+    let raw_solar_watt = match crate::device_requests::meter::get_solar_production_cached().await {
+        Ok(production) => production.current_production.max(0) as i64,
+        Err(_) => 0,
+    };
+    detect_possible_grid_outage(meter_available, raw_solar_watt, get_grid_state().get_ups_on_battery())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_ups_signal_initially() {
+        let state = GridState::new();
+        assert_eq!(state.get_ups_on_battery(), None);
+        assert!(!state.is_on_backup_power());
+    }
+
+    #[test]
+    fn test_record_ups_signal() {
+        let state = GridState::new();
+
+        state.record_ups_signal(true);
+        assert_eq!(state.get_ups_on_battery(), Some(true));
+        assert!(state.is_on_backup_power());
+
+        state.record_ups_signal(false);
+        assert_eq!(state.get_ups_on_battery(), Some(false));
+        assert!(!state.is_on_backup_power());
+    }
+
+    #[test]
+    fn test_stale_ups_signal_is_ignored() {
+        let state = GridState::new();
+        {
+            let mut signal = state.ups_signal.write().unwrap();
+            *signal = Some((true, Utc::now() - chrono::Duration::minutes(UPS_SIGNAL_TIMEOUT_MINUTES + 1)));
+        }
+        assert_eq!(state.get_ups_on_battery(), None);
+        assert!(!state.is_on_backup_power());
+    }
+
+    #[test]
+    fn test_detect_outage_without_ups() {
+        // Meter down and no solar: possible outage
+        assert!(detect_possible_grid_outage(false, 0, None));
+        // Meter down but solar producing: just a meter problem
+        assert!(!detect_possible_grid_outage(false, 500, None));
+        // Meter available: grid is fine
+        assert!(!detect_possible_grid_outage(true, 0, None));
+    }
+
+    #[test]
+    fn test_detect_outage_ups_takes_precedence() {
+        assert!(detect_possible_grid_outage(true, 1000, Some(true)));
+        assert!(!detect_possible_grid_outage(false, 0, Some(false)));
+    }
+}
@@ -0,0 +1,152 @@
+//! Deep idle mode
+//!
+//! When every device is off, the user is away and no demand-response event is
+//! scheduled, there is little to react to. In deep idle, the control loop and the
+//! manual mode monitor sleep for a multiple of their normal interval, which also
+//! reduces meter and weather requests and database writes. Any trigger (PIR,
+//! webhook or mutating API call) wakes the loops immediately.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::config;
+
+use super::{ac_executor, demand_response, devices, time_helpers};
+
+/// Global idle state manager
+static IDLE_STATE: OnceLock<IdleState> = OnceLock::new();
+
+/// Tracks whether the controller is in deep idle and wakes sleeping loops
+pub struct IdleState {
+    deep_idle: AtomicBool,
+    wake_notify: Notify,
+}
+
+impl IdleState {
+    fn new() -> Self {
+        Self {
+            deep_idle: AtomicBool::new(false),
+            wake_notify: Notify::new(),
+        }
+    }
+
+    /// Check if the controller is currently in deep idle
+    pub fn is_deep_idle(&self) -> bool {
+        self.deep_idle.load(Ordering::SeqCst)
+    }
+
+    /// Enter deep idle mode
+    pub fn enter(&self) {
+        if !self.deep_idle.swap(true, Ordering::SeqCst) {
+            log::info!("Entering deep idle mode (all devices off, user away)");
+        }
+    }
+
+    /// Leave deep idle mode and wake any sleeping loops
+    pub fn wake(&self, reason: &str) {
+        if self.deep_idle.swap(false, Ordering::SeqCst) {
+            log::info!("Leaving deep idle mode: {}", reason);
+            self.wake_notify.notify_waiters();
+        }
+    }
+
+    /// Sleep for the normal interval, or a multiple of it while in deep idle.
+    /// Returns early if woken from deep idle.
+    pub async fn sleep(&self, normal: Duration) {
+        self.sleep_with_multiplier(normal, config::get_config().deep_idle_interval_multiplier)
+            .await;
+    }
+
+    async fn sleep_with_multiplier(&self, normal: Duration, multiplier: u32) {
+        // Register for wake-ups before checking the flag so a concurrent wake isn't missed
+        let notified = self.wake_notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        if !self.is_deep_idle() {
+            tokio::time::sleep(normal).await;
+            return;
+        }
+
+        let multiplier = multiplier.max(1);
+        tokio::select! {
+            _ = tokio::time::sleep(normal * multiplier) => {}
+            _ = notified => {}
+        }
+    }
+}
+
+/// Get the global idle state instance
+pub fn get_idle_state() -> &'static IdleState {
+    IDLE_STATE.get_or_init(IdleState::new)
+}
+
+/// Decide whether the controller can enter deep idle
+pub fn should_enter_deep_idle(all_devices_off: bool, user_is_home: bool, dr_event_scheduled: bool) -> bool {
+    all_devices_off && !user_is_home && !dr_event_scheduled
+}
+
+/// Re-evaluate deep idle after a control cycle
+pub async fn update_after_cycle() {
+    if !config::get_config().deep_idle_enabled {
+        return;
+    }
+
+    let all_devices_off = devices::get_device_registry().all().iter().all(ac_executor::is_device_off);
+    let user_is_home = time_helpers::is_user_home_and_awake_async().await;
+    let dr_event_scheduled = demand_response::get_demand_response_state().get_event().is_some();
+
+    let state = get_idle_state();
+    if should_enter_deep_idle(all_devices_off, user_is_home, dr_event_scheduled) {
+        state.enter();
+    } else {
+        state.wake("conditions changed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_enter_deep_idle() {
+        assert!(should_enter_deep_idle(true, false, false));
+        assert!(!should_enter_deep_idle(false, false, false), "A device is on");
+        assert!(!should_enter_deep_idle(true, true, false), "User is home");
+        assert!(!should_enter_deep_idle(true, false, true), "DR event scheduled");
+    }
+
+    #[test]
+    fn test_enter_and_wake() {
+        let state = IdleState::new();
+        assert!(!state.is_deep_idle());
+
+        state.enter();
+        assert!(state.is_deep_idle());
+
+        state.wake("test");
+        assert!(!state.is_deep_idle());
+    }
+
+    #[tokio::test]
+    async fn test_wake_interrupts_sleep() {
+        let state = std::sync::Arc::new(IdleState::new());
+        state.enter();
+
+        let sleeper = state.clone();
+        let handle = tokio::spawn(async move {
+            sleeper.sleep_with_multiplier(Duration::from_secs(3600), 10).await;
+        });
+
+        // Give the sleeper a moment to start waiting
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        state.wake("test");
+
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("Sleep should be interrupted by wake")
+            .unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 /// Tracks whether each device is in manual or automatic mode
+#[derive(Default)]
 pub struct ManualModeMonitor {
     /// Maps device name to is_automatic_mode flag
     /// true = Auto mode, false = Manual mode
@@ -51,11 +52,11 @@ impl ManualModeMonitor {
 }
 
 /// Global instance of the manual mode monitor
-static MANUAL_MODE_MONITOR: std::sync::OnceLock<ManualModeMonitor> = std::sync::OnceLock::new();
+static MANUAL_MODE_MONITOR: std::sync::OnceLock<Arc<ManualModeMonitor>> = std::sync::OnceLock::new();
 
 /// Get the global manual mode monitor instance
-pub fn get_manual_mode_monitor() -> &'static ManualModeMonitor {
-    MANUAL_MODE_MONITOR.get_or_init(ManualModeMonitor::new)
+pub fn get_manual_mode_monitor() -> &'static Arc<ManualModeMonitor> {
+    MANUAL_MODE_MONITOR.get_or_init(|| Arc::new(ManualModeMonitor::new()))
 }
 
 #[cfg(test)]
@@ -0,0 +1,206 @@
+//! Manual overrides set through the API
+//!
+//! The manual mode monitor only notices changes made at the physical unit. An override forces a
+//! state through `POST /api/ac/:device/override` (or the MQTT bridge) for a number of minutes,
+//! during which the node executor skips the device. Once it expires the nodeset is evaluated again right away.
+//! Overrides are kept in memory and do not survive restarts.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::{ac_executor::AcState, devices, node_executor};
+use crate::{
+    app_state::AppState,
+    device_requests,
+    live_events::{self, LiveEvent},
+    types::{AcCommandRequest, CauseReason},
+};
+
+/// Longest manual override (1 day)
+pub const MAX_OVERRIDE_MINUTES: u32 = 24 * 60;
+
+/// Global manual override state
+static MANUAL_OVERRIDES: OnceLock<Arc<ManualOverrides>> = OnceLock::new();
+
+/// A state forced on a device until a point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManualOverride {
+    pub state: AcState,
+    pub until: DateTime<Utc>,
+}
+
+/// Thread-safe manual overrides per device
+#[derive(Default)]
+pub struct ManualOverrides {
+    overrides: RwLock<HashMap<String, ManualOverride>>,
+}
+
+impl ManualOverrides {
+    /// Force a state on a device until `until`, replacing any earlier override
+    pub fn set(&self, device: &str, state: AcState, until: DateTime<Utc>) {
+        let mut overrides = self.overrides.write().unwrap();
+        overrides.insert(device.to_string(), ManualOverride { state, until });
+        log::info!("Manual override set for device '{}' until {}", device, until);
+    }
+
+    /// The active override of a device, expired overrides are removed
+    pub fn get_active(&self, device: &str) -> Option<ManualOverride> {
+        let mut overrides = self.overrides.write().unwrap();
+        match overrides.get(device) {
+            Some(o) if Utc::now() < o.until => Some(o.clone()),
+            Some(_) => {
+                overrides.remove(device);
+                log::info!("Manual override of device '{}' expired, returning control to the nodeset", device);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Remove the override of a device, returns false if there was no active override
+    pub fn clear(&self, device: &str) -> bool {
+        let mut overrides = self.overrides.write().unwrap();
+        let cleared = overrides.remove(device).is_some_and(|o| Utc::now() < o.until);
+        if cleared {
+            log::info!("Manual override of device '{}' cleared", device);
+        }
+        cleared
+    }
+
+    /// Remove the override of a device if it is still the one ending at `until`
+    /// Returns false if it was cleared or replaced in the meantime
+    fn expire(&self, device: &str, until: DateTime<Utc>) -> bool {
+        let mut overrides = self.overrides.write().unwrap();
+        if overrides.get(device).is_some_and(|o| o.until == until) {
+            overrides.remove(device);
+            log::info!("Manual override of device '{}' expired, returning control to the nodeset", device);
+            return true;
+        }
+        false
+    }
+}
+
+/// Get the global manual overrides
+pub fn get_manual_overrides() -> &'static Arc<ManualOverrides> {
+    MANUAL_OVERRIDES.get_or_init(|| Arc::new(ManualOverrides::default()))
+}
+
+/// Why an override could not be started or cleared
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverrideError {
+    UnknownDevice,
+    /// The request is invalid, the message explains why
+    Invalid(String),
+    /// The command could not be sent to the device
+    SendFailed(String),
+    /// There is no active override to clear
+    NoOverride,
+}
+
+/// Send a command to a device and skip its nodeset for `minutes`
+/// Returns the time at which the nodeset takes control again
+pub async fn start(
+    state: &AppState,
+    device: &str,
+    request: &AcCommandRequest,
+    minutes: u32,
+) -> Result<DateTime<Utc>, OverrideError> {
+    if state.devices().get(device).is_none() {
+        return Err(OverrideError::UnknownDevice);
+    }
+    if minutes == 0 || minutes > MAX_OVERRIDE_MINUTES {
+        return Err(OverrideError::Invalid(format!(
+            "Minutes must be between 1 and {} (1 day)",
+            MAX_OVERRIDE_MINUTES
+        )));
+    }
+    let command = request.to_command().map_err(OverrideError::Invalid)?;
+    let requested_vanes = (request.vertical_vane.as_deref(), request.horizontal_vane.as_deref());
+    let (vertical_vane, horizontal_vane) = state.config().supported_vanes(device, requested_vanes.0, requested_vanes.1);
+    if (requested_vanes.0.is_some() && vertical_vane.is_none()) || (requested_vanes.1.is_some() && horizontal_vane.is_none()) {
+        return Err(OverrideError::Invalid(format!(
+            "Device '{}' doesn't support the requested vane positions",
+            device
+        )));
+    }
+
+    if let Err(e) = device_requests::ac::send_command(device, &command, CauseReason::ManualOverride.id(), None).await {
+        log::error!("Failed to send the manual override to {}: {}", device, e);
+        return Err(OverrideError::SendFailed(e.to_string()));
+    }
+
+    let desired_state = command.to_state();
+    let until = Utc::now() + chrono::Duration::minutes(i64::from(minutes));
+    live_events::get_live_events().publish(LiveEvent::ac_state(device, &desired_state));
+    state.ac_states.set_state(device, desired_state.clone());
+    state.manual_overrides.set(device, desired_state, until);
+    return_control_at_expiry(state.manual_overrides.clone(), device.to_string(), until);
+    Ok(until)
+}
+
+/// End the override of a device early and return control to the nodeset right away
+pub fn stop(state: &AppState, device: &str) -> Result<(), OverrideError> {
+    let Some(ac_device) = state.devices().get(device).cloned() else {
+        return Err(OverrideError::UnknownDevice);
+    };
+    if !state.manual_overrides.clear(device) {
+        return Err(OverrideError::NoOverride);
+    }
+    return_control_now(ac_device);
+    Ok(())
+}
+
+/// Evaluate the nodeset of a device as soon as its override ends, instead of waiting for the next cycle
+pub fn return_control_at_expiry(overrides: Arc<ManualOverrides>, device_name: String, until: DateTime<Utc>) {
+    tokio::spawn(async move {
+        let wait = (until - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        if !overrides.expire(&device_name, until) {
+            return;
+        }
+        let Some(device) = devices::get_device_registry().get(&device_name).cloned() else {
+            return;
+        };
+        if let node_executor::NodeExecutionResult::Error(e) = node_executor::execute_nodeset_for_device(&device).await {
+            log::error!("Failed to execute nodeset for {} after its manual override: {}", device_name, e);
+        }
+    });
+}
+
+/// Return control to the nodeset right away after an override was cleared
+pub fn return_control_now(device: devices::AcDevice) {
+    tokio::spawn(async move {
+        if let node_executor::NodeExecutionResult::Error(e) = node_executor::execute_nodeset_for_device(&device).await {
+            log::error!("Failed to execute nodeset for {} after clearing its manual override: {}", device.as_str(), e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Celsius;
+
+    #[test]
+    fn test_override_expiry_and_clear() {
+        let overrides = ManualOverrides::default();
+        let state = AcState::new_on(4, 0, Celsius(21.0), 1, false);
+        assert!(overrides.get_active("LivingRoom").is_none());
+
+        let until = Utc::now() + chrono::Duration::minutes(30);
+        overrides.set("LivingRoom", state.clone(), until);
+        assert_eq!(overrides.get_active("LivingRoom").map(|o| o.state), Some(state.clone()));
+
+        // A replaced override is not expired by the timer of the earlier one
+        let later = until + chrono::Duration::minutes(30);
+        overrides.set("LivingRoom", state.clone(), later);
+        assert!(!overrides.expire("LivingRoom", until));
+        assert!(overrides.expire("LivingRoom", later));
+        assert!(!overrides.clear("LivingRoom"));
+
+        overrides.set("LivingRoom", state, Utc::now() - chrono::Duration::minutes(1));
+        assert!(overrides.get_active("LivingRoom").is_none(), "expired overrides are ignored");
+        assert!(!overrides.clear("LivingRoom"));
+    }
+}
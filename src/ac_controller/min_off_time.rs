@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Global minimum off-time state manager
+static MIN_OFF_TIME_STATE: OnceLock<Arc<MinOffTimeState>> = OnceLock::new();
+
+/// Thread-safe minimum off-time tracking state
+/// Keeps the compressor from being turned back on right after it was turned off (short cycling).
+/// The minimum itself comes from the config (`min_off_time_minutes`, overridable per device).
+#[derive(Default)]
+pub struct MinOffTimeState {
+    /// Maps device name to the time it was last turned off
+    last_turn_off: RwLock<HashMap<String, DateTime<Utc>>>,
+}
+
+impl MinOffTimeState {
+    fn new() -> Self {
+        Self {
+            last_turn_off: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record when a device was turned off
+    pub fn record_turn_off(&self, device: &str) {
+        let mut map = self.last_turn_off.write().unwrap();
+        map.insert(device.to_string(), Utc::now());
+        log::debug!("Recorded turn-off time for device: {}", device);
+    }
+
+    /// Clear the turn-off time for a device, lifting its lockout immediately
+    /// Returns false if no turn-off was recorded
+    pub fn clear_turn_off_time(&self, device: &str) -> bool {
+        let mut map = self.last_turn_off.write().unwrap();
+        let cleared = map.remove(device).is_some();
+        log::debug!("Cleared turn-off time for device: {}", device);
+        cleared
+    }
+
+    /// Check if a device has been off for at least `minimum_minutes`
+    /// Returns true if the device can be turned on (either no turn-off recorded or minimum time has passed)
+    pub fn can_turn_on(&self, device: &str, minimum_minutes: u32) -> bool {
+        if !self.is_locked_out(device, minimum_minutes) {
+            return true;
+        }
+        log::info!(
+            "Device {} has been off for only {} minutes (< {} minimum), cannot turn on yet",
+            device,
+            self.get_minutes_off(device).unwrap_or_default(),
+            minimum_minutes
+        );
+        false
+    }
+
+    /// Check if a device was turned off less than `minimum_minutes` ago, without logging
+    pub fn is_locked_out(&self, device: &str, minimum_minutes: u32) -> bool {
+        self.get_minutes_off(device)
+            .is_some_and(|minutes_off| minutes_off < i64::from(minimum_minutes))
+    }
+
+    /// Get the last turn-off time for a device
+    pub fn get_last_turn_off(&self, device: &str) -> Option<DateTime<Utc>> {
+        let map = self.last_turn_off.read().unwrap();
+        map.get(device).copied()
+    }
+
+    /// Get how many minutes the device has been off
+    pub fn get_minutes_off(&self, device: &str) -> Option<i64> {
+        self.get_last_turn_off(device)
+            .map(|turn_off_time| Utc::now().signed_duration_since(turn_off_time).num_minutes())
+    }
+}
+
+/// Get the global minimum off-time state instance
+pub fn get_min_off_time_state() -> &'static Arc<MinOffTimeState> {
+    MIN_OFF_TIME_STATE.get_or_init(|| Arc::new(MinOffTimeState::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_can_turn_on() {
+        let state = MinOffTimeState::new();
+
+        // Initially no turn-off recorded, can turn on
+        assert!(state.can_turn_on("TestDevice", 5));
+
+        state.record_turn_off("TestDevice");
+
+        // Locked out right after turning off, unless no minimum is configured
+        assert!(!state.can_turn_on("TestDevice", 5));
+        assert!(state.is_locked_out("TestDevice", 5));
+        assert!(state.can_turn_on("TestDevice", 0));
+        assert!(!state.is_locked_out("TestDevice", 0));
+        assert_eq!(state.get_minutes_off("TestDevice"), Some(0));
+
+        // Other devices are unaffected
+        assert!(state.can_turn_on("OtherDevice", 5));
+    }
+
+    #[test]
+    fn test_clear_turn_off_time_allows_turn_on() {
+        let state = MinOffTimeState::new();
+
+        state.record_turn_off("TestDevice");
+        assert!(!state.can_turn_on("TestDevice", 5));
+
+        assert!(state.clear_turn_off_time("TestDevice"));
+        assert!(state.can_turn_on("TestDevice", 5));
+        assert!(!state.clear_turn_off_time("TestDevice"));
+    }
+}
@@ -9,6 +9,7 @@ const MINIMUM_ON_TIME_MINUTES: i64 = 30;
 static MIN_ON_TIME_STATE: OnceLock<Arc<MinOnTimeState>> = OnceLock::new();
 
 /// Thread-safe minimum on-time tracking state
+#[derive(Default)]
 pub struct MinOnTimeState {
     /// Maps device name to the time it was last turned on
     last_turn_on: RwLock<HashMap<String, DateTime<Utc>>>,
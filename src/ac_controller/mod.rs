@@ -1,22 +1,50 @@
 pub mod devices;
+pub mod demand_response;
 pub mod pir_state;
+pub mod power_budget;
 pub mod ac_executor;
-mod manual_mode_monitor;
+pub mod away_mode;
+pub mod command_verification;
+pub mod contact_sensors;
+pub mod cycle_snapshots;
+pub mod cycle_timing;
+pub mod decision_engine;
+pub mod device_timers;
+mod dnd;
+pub mod execution_history;
+pub mod grid_state;
+pub mod idle_state;
+pub mod manual_mode_monitor;
+pub mod manual_override;
+pub mod min_off_time;
 pub mod min_on_time;
 pub mod node_executor;
+pub mod notifications;
+pub mod occupancy;
+pub mod preheat;
+pub mod season;
+pub mod sensor_anomaly;
+pub mod sensor_filter;
+pub mod setpoint_offset;
+pub mod shadow_mode;
+pub mod state_reconciliation;
+pub mod target_temperature;
 pub mod time_helpers;
+pub mod watchdog;
 
 // Re-export types needed by other modules
-pub use devices::AcDevices;
+pub use devices::{AcDevice, DeviceRegistry};
 
-use std::time::Duration;
+use futures_util::future::join_all;
+use std::time::{Duration, Instant};
 use tokio;
 
 /// Manual mode polling interval in seconds (10 seconds)
 const MANUAL_MODE_POLL_INTERVAL_SECS: u64 = 10;
 
 /// Start the AC controller loop
-/// Runs immediately on startup, then repeats at the interval specified in the active profile
+/// Every device runs on its own timer: evaluated immediately on startup, then at its own interval
+/// or the one of the active profile (see `device_timers`). Devices due at the same time share a cycle
 /// Also spawns a separate task to monitor devices in manual mode
 /// Also spawns a background task to process the logging queue
 pub async fn start_ac_controller() {
@@ -36,32 +64,58 @@ pub async fn start_ac_controller() {
         logging_queue_processing_loop().await;
     });
     
-    // Get the initial interval from the active profile
-    let mut current_interval_minutes = crate::db::nodesets::get_evaluate_every_minutes().await;
+    // Get the initial intervals from the active profile and the devices
+    let mut intervals = device_timers::EvaluationIntervals::load().await;
     log::info!(
         "AC controller using evaluate_every_minutes={} from active profile",
-        current_interval_minutes
+        intervals.default_minutes
     );
+    for (device, minutes) in &intervals.devices {
+        log::info!("{} is evaluated every {} minutes", device, minutes);
+    }
     
-    // Main control loop with dynamic interval
+    // Main control loop, waking whenever a device is due
+    let mut timers = device_timers::DeviceTimers::default();
     loop {
-        // Execute AC control for all devices
-        execute_ac_control_cycle().await;
-        
-        // Check if the interval has changed in the active profile
-        let new_interval_minutes = crate::db::nodesets::get_evaluate_every_minutes().await;
-        if new_interval_minutes != current_interval_minutes {
-            log::info!(
-                "Evaluation interval changed from {} to {} minutes",
-                current_interval_minutes,
-                new_interval_minutes
-            );
-            current_interval_minutes = new_interval_minutes;
+        let registry = devices::get_device_registry();
+        let due = timers.due(registry.all(), Instant::now());
+        if !due.is_empty() {
+            // Execute AC control for the devices that are due
+            watchdog::cycle_started();
+            execute_ac_control_cycle(&due).await;
+            
+            // Check if the intervals have changed, they apply from the next evaluation of each device
+            let new_intervals = device_timers::EvaluationIntervals::load().await;
+            if new_intervals.default_minutes != intervals.default_minutes {
+                log::info!(
+                    "Evaluation interval changed from {} to {} minutes",
+                    intervals.default_minutes,
+                    new_intervals.default_minutes
+                );
+            }
+            intervals = new_intervals;
+            timers.reschedule(&due, &intervals, Instant::now());
+            
+            // Enter or leave deep idle based on the state after this cycle
+            idle_state::update_after_cycle().await;
+            watchdog::cycle_completed();
         }
         
-        // Wait before next cycle using the current interval
-        let interval_secs = (current_interval_minutes as u64) * 60;
-        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        // Wait until the next device is due (longer while in deep idle)
+        let wait = timers.until_next(registry.all(), &intervals, Instant::now());
+        let idle = idle_state::get_idle_state();
+        let was_deep_idle = idle.is_deep_idle();
+        tokio::select! {
+            _ = idle.sleep(wait) => {}
+            _ = crate::shutdown::requested() => {
+                log::info!("AC controller stopped");
+                return;
+            }
+        }
+        if was_deep_idle && !idle.is_deep_idle() {
+            // Woken from deep idle, evaluate every device right away
+            timers.reset();
+        }
     }
 }
 
@@ -72,7 +126,7 @@ async fn collect_initial_device_states() {
     
     let monitor = manual_mode_monitor::get_manual_mode_monitor();
     
-    for device in AcDevices::all() {
+    for device in devices::get_device_registry().all() {
         let device_name = device.as_str();
         
         // Fetch sensor data to get both temperature and Auto/Manual mode
@@ -100,33 +154,72 @@ async fn collect_initial_device_states() {
     log::info!("Initial device state collection complete");
 }
 
-/// Execute one cycle of AC control for all devices using node-based execution
-async fn execute_ac_control_cycle() {
-    log::info!("Starting AC control cycle (node-based)");
+/// Execute one cycle of AC control for the due devices using node-based execution
+async fn execute_ac_control_cycle(due: &[AcDevice]) {
+    log::info!("Starting AC control cycle (node-based) for {} device(s)", due.len());
+    let started = Instant::now();
     
-    // Process each device
-    for device in AcDevices::all() {
-        let device_name = device.as_str();
-        log::debug!("Processing device: {}", device_name);
-        
-        // Execute the active nodeset for this device
-        match node_executor::execute_nodeset_for_device(&device).await {
-            node_executor::NodeExecutionResult::CommandExecuted => {
-                log::info!("AC command executed for {}", device_name);
-            }
-            node_executor::NodeExecutionResult::NoAction => {
-                log::debug!("No action needed for {} (state unchanged or Do Nothing)", device_name);
-            }
-            node_executor::NodeExecutionResult::ManualMode => {
-                log::debug!("Device {} is in manual mode, skipped", device_name);
-            }
-            node_executor::NodeExecutionResult::Error(e) => {
-                log::error!("Failed to execute nodeset for {}: {}", device_name, e);
-            }
+    // Snapshot the nodesets once, changes made during the cycle apply from the next cycle
+    let nodesets = node_executor::CycleNodesets::load(due).await;
+    
+    let config = crate::config::get_config();
+    let timeout = Duration::from_secs(config.device_cycle_timeout_seconds);
+    let devices = power_budget::by_priority(&config, due);
+    
+    // With a power budget, devices are processed one at a time, highest priority first, so it claims the budget first.
+    // Otherwise they don't affect each other and run concurrently, so a slow controller doesn't delay the others.
+    let sequential = config.power_budget_watt > 0;
+    let timings = if sequential {
+        let mut timings = Vec::with_capacity(devices.len());
+        for device in devices {
+            timings.push(execute_device_in_cycle(device, &nodesets, timeout).await);
+        }
+        timings
+    } else {
+        join_all(devices.into_iter().map(|device| execute_device_in_cycle(device, &nodesets, timeout))).await
+    };
+    
+    nodesets.log_deferred_reloads(due).await;
+    cycle_timing::record(cycle_timing::CycleTiming::new(started.elapsed(), sequential, timings));
+    crate::sensor_history::record_cycle();
+}
+
+/// Execute the nodeset of one device in a control cycle, giving up after `timeout`
+async fn execute_device_in_cycle(
+    device: &AcDevice,
+    nodesets: &node_executor::CycleNodesets,
+    timeout: Duration,
+) -> cycle_timing::DeviceTiming {
+    let device_name = device.as_str();
+    log::debug!("Processing device: {}", device_name);
+    let started = Instant::now();
+    
+    // Execute the active nodeset for this device
+    let execution = node_executor::execute_nodeset_for_device_in_cycle(device, nodesets);
+    let (result, timed_out) = match tokio::time::timeout(timeout, execution).await {
+        Ok(result) => (result, false),
+        Err(_) => {
+            let message = format!("no result within {}s", timeout.as_secs());
+            (node_executor::NodeExecutionResult::Error(message), true)
+        }
+    };
+    crate::telemetry::record_cycle(matches!(result, node_executor::NodeExecutionResult::Error(_)));
+    match result {
+        node_executor::NodeExecutionResult::CommandExecuted => {
+            log::info!("AC command executed for {}", device_name);
+        }
+        node_executor::NodeExecutionResult::NoAction => {
+            log::debug!("No action needed for {} (state unchanged or Do Nothing)", device_name);
+        }
+        node_executor::NodeExecutionResult::ManualMode => {
+            log::debug!("Device {} is in manual mode, skipped", device_name);
+        }
+        node_executor::NodeExecutionResult::Error(e) => {
+            log::error!("Failed to execute nodeset for {}: {}", device_name, e);
         }
     }
     
-    log::info!("AC control cycle completed");
+    cycle_timing::DeviceTiming::new(device_name, started.elapsed(), timed_out)
 }
 
 /// Monitor devices in manual mode and detect transitions to auto mode
@@ -146,7 +239,7 @@ async fn manual_mode_monitoring_loop() {
         let monitor = manual_mode_monitor::get_manual_mode_monitor();
         
         // Check each device
-        for device in AcDevices::all() {
+        for device in devices::get_device_registry().all() {
             let device_name = device.as_str();
             
             // Fetch current sensor data to check automatic mode status
@@ -156,6 +249,7 @@ async fn manual_mode_monitoring_loop() {
                     let transitioned_to_auto = monitor.update_mode(device_name, sensor_data.is_automatic_mode);
                     
                     if transitioned_to_auto {
+                        idle_state::get_idle_state().wake("manual to auto transition");
                         log::info!(
                             "Device '{}' transitioned from Manual to Auto mode - resetting state and triggering immediate nodeset execution",
                             device_name
@@ -163,10 +257,10 @@ async fn manual_mode_monitoring_loop() {
                         
                         // Reset the device state to force re-sync with physical device
                         // This ensures we don't rely on potentially stale state from before the manual intervention
-                        ac_executor::reset_device_state(&device);
+                        ac_executor::reset_device_state(device);
                         
                         // Execute nodeset with forced execution to bypass state comparison
-                        match node_executor::execute_nodeset_for_device_forced(&device).await {
+                        match node_executor::execute_nodeset_for_device_forced(device).await {
                             node_executor::NodeExecutionResult::CommandExecuted => {
                                 log::info!("Manual→Auto transition command sent for {}", device_name);
                             }
@@ -193,8 +287,8 @@ async fn manual_mode_monitoring_loop() {
             }
         }
         
-        // Wait before next check
-        tokio::time::sleep(Duration::from_secs(MANUAL_MODE_POLL_INTERVAL_SECS)).await;
+        // Wait before next check (longer while in deep idle)
+        idle_state::get_idle_state().sleep(Duration::from_secs(MANUAL_MODE_POLL_INTERVAL_SECS)).await;
     }
 }
 
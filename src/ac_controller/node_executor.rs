@@ -4,21 +4,23 @@
 //! and the actual AC control commands. It replaces the old plan_types-based
 //! execution with the more flexible node-based system.
 
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
 use crate::{
     ac_controller::{
-        ac_executor::{get_state_manager, AC_MODE_COOL, AC_MODE_HEAT},
-        manual_mode_monitor, time_helpers, pir_state, AcDevices,
+        ac_executor::{ac_mode_from_name, get_state_manager, AC_MODE_HEAT},
+        command_verification, contact_sensors, cycle_snapshots, decision_engine::{self, Decision}, execution_history, manual_mode_monitor, manual_override, notifications, occupancy, power_budget, preheat, time_helpers, pir_state, grid_state, demand_response, season, sensor_anomaly, sensor_filter, setpoint_offset, shadow_mode, target_temperature, AcDevice,
     },
     config,
     db,
     device_requests,
+    live_events::{self, LiveEvent},
     nodes::{
-        ActiveCommandData, ActionResult, ExecutionInputs, ExecutionResult, NodesetExecutor,
-        execution::PIR_NEVER_DETECTED,
+        ActiveCommandData, ActionResult, DataAges, ExecutionInputs, ExecutionResult,
+        execution::PIR_NEVER_DETECTED, schema, variables,
     },
-    types::CauseReason,
+    types::{fan_speed_from_name, AcCommand, CauseReason, Celsius, Config, Minutes, Watts},
 };
 
 use super::ac_executor::AcState;
@@ -33,20 +35,30 @@ pub enum NodeExecutionResult {
     CommandExecuted,
     /// No action was taken (Do Nothing node reached or no change needed)
     NoAction,
-    /// Device is in manual mode, skipping execution
+    /// Device is in manual mode or manually overridden, skipping execution
     ManualMode,
     /// Nodeset validation or execution failed
     Error(String),
 }
 
-/// Execute the active nodeset for a specific device
+/// Execute the nodeset for a specific device (its assigned nodeset, or the active nodeset)
 /// This function:
 /// 1. Checks if device is in automatic mode (skips if in manual mode)
 /// 2. Gathers all necessary input data for the execution context
-/// 3. Loads and executes the active nodeset
+/// 3. Loads and executes the device's nodeset
 /// 4. Converts the execution result to actual AC commands
 /// 5. Handles state management and logging
-pub async fn execute_nodeset_for_device(device: &AcDevices) -> NodeExecutionResult {
+pub async fn execute_nodeset_for_device(device: &AcDevice) -> NodeExecutionResult {
+    execute_nodeset(device, None).await
+}
+
+/// Execute the nodeset for a device as part of a control cycle
+/// Same as `execute_nodeset_for_device`, but runs the nodeset snapshotted at the start of the cycle
+pub async fn execute_nodeset_for_device_in_cycle(device: &AcDevice, nodesets: &CycleNodesets) -> NodeExecutionResult {
+    execute_nodeset(device, Some(nodesets)).await
+}
+
+async fn execute_nodeset(device: &AcDevice, nodesets: Option<&CycleNodesets>) -> NodeExecutionResult {
     let device_name = device.as_str();
     log::debug!("Executing nodeset for device: {}", device_name);
 
@@ -81,10 +93,37 @@ pub async fn execute_nodeset_for_device(device: &AcDevices) -> NodeExecutionResu
         return NodeExecutionResult::ManualMode;
     }
 
+    if let Some(manual_override) = manual_override::get_manual_overrides().get_active(device_name) {
+        log::info!(
+            "Device '{}' has a manual override until {}, skipping automatic command execution",
+            device_name,
+            manual_override.until
+        );
+        return NodeExecutionResult::ManualMode;
+    }
+
+    // Shed load instead of evaluating the nodeset while running on backup power
+    // (a fresh UPS on-battery signal implies a grid outage)
+    if config::get_config().shed_load_on_grid_outage && grid_state::get_grid_state().is_on_backup_power() {
+        if shadow_mode::get_shadow_mode().is_enabled() {
+            log::info!("Shadow mode: would shed load for device '{}' during the grid outage", device_name);
+            return NodeExecutionResult::NoAction;
+        }
+        return shed_load_for_grid_outage(device).await;
+    }
+
+    // Briefly heat ahead of a forecast cold snap, the nodeset is skipped while preheating
+    if let Some(result) = run_cold_snap_preheat(device).await {
+        return result;
+    }
+
     // Execute nodeset core logic
-    let result = execute_nodeset_core(device).await;
+    let result = execute_nodeset_core(device, nodesets).await;
     
     match result {
+        Ok(execution_result) if shadow_mode::get_shadow_mode().is_enabled() => {
+            log_shadow_result(device_name, &execution_result)
+        }
         Ok(execution_result) => {
             // Convert execution result to AC commands
             execute_result_to_commands(device, execution_result).await
@@ -93,19 +132,169 @@ pub async fn execute_nodeset_for_device(device: &AcDevices) -> NodeExecutionResu
     }
 }
 
+/// Log what the result would have done instead of sending commands, for shadow mode
+/// The result is already recorded in the execution history
+fn log_shadow_result(device_name: &str, result: &ExecutionResult) -> NodeExecutionResult {
+    if let Some(error) = &result.error {
+        log::error!("Nodeset execution error for {}: {}", device_name, error);
+        return NodeExecutionResult::Error(error.clone());
+    }
+    log::info!(
+        "Shadow mode: not sending commands for device '{}', the nodeset decided: {}",
+        device_name,
+        cycle_snapshots::describe_decision(result)
+    );
+    NodeExecutionResult::NoAction
+}
+
+/// Turn off a device because of a grid outage while running on backup power
+async fn shed_load_for_grid_outage(device: &AcDevice) -> NodeExecutionResult {
+    let device_name = device.as_str();
+    log::warn!(
+        "Grid outage detected while on backup power, shedding load for device '{}'",
+        device_name
+    );
+
+    match super::ac_executor::turn_off_device(device, CauseReason::GridOutage).await {
+        Ok(true) => NodeExecutionResult::CommandExecuted,
+        Ok(false) => NodeExecutionResult::NoAction,
+        Err(e) => NodeExecutionResult::Error(format!("Failed to shed load: {}", e)),
+    }
+}
+
+/// Start or continue a cold snap preheat, see `preheat`
+/// Returns None if the device isn't preheating, so the nodeset is evaluated as usual
+async fn run_cold_snap_preheat(device: &AcDevice) -> Option<NodeExecutionResult> {
+    let device_name = device.as_str();
+    let config = config::get_config();
+    if !config.cold_snap_preheat.enabled {
+        return None;
+    }
+
+    let preheat_state = preheat::get_preheat_state();
+    let now = chrono::Utc::now();
+    if let Some(until) = preheat_state.active_until(device_name, now) {
+        log::info!(
+            "Device '{}' is preheating for a cold snap until {}, skipping automatic command execution",
+            device_name,
+            until
+        );
+        return Some(NodeExecutionResult::NoAction);
+    }
+
+    // Without weather data there is nothing to act on
+    let weather_coordinates = config.get_weather_coordinates(device_name);
+    let global_coordinates = (config.latitude, config.longitude);
+    let outdoor_temperature = device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_current_outdoor_temp_cached,
+    )
+    .await
+    .ok()?;
+    let forecast_min_next_12h_temp = device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_min_next_12h_outdoor_temp_cached,
+    )
+    .await
+    .ok()?;
+
+    let local_hour = time_helpers::local_time_now().hour as u32;
+    if !preheat_state.should_start(
+        &config.cold_snap_preheat,
+        device_name,
+        outdoor_temperature,
+        forecast_min_next_12h_temp,
+        local_hour,
+        now,
+    ) {
+        return None;
+    }
+
+    if shadow_mode::get_shadow_mode().is_enabled() {
+        log::info!(
+            "Shadow mode: would preheat device '{}' for a cold snap ({:.1}°C forecast)",
+            device_name,
+            forecast_min_next_12h_temp
+        );
+        return None;
+    }
+
+    let command = AcCommand::On {
+        mode: AC_MODE_HEAT,
+        fan_speed: 0,
+        temperature: Celsius(config.cold_snap_preheat.temperature),
+        swing: 0,
+        vertical_vane: None,
+        horizontal_vane: None,
+    };
+    let desired_state = command.to_state();
+    let current_state = get_state_manager().get_state(device_name);
+
+    // Leave the device to its nodeset if preheating would break the power budget or short cycle it
+    if limit_to_power_budget(device_name, &desired_state).is_some() {
+        return None;
+    }
+    if !current_state.is_on
+        && !super::min_off_time::get_min_off_time_state()
+            .can_turn_on(device_name, config.get_min_off_time_minutes(device_name))
+    {
+        return None;
+    }
+
+    log::info!(
+        "Preheating device '{}' for a cold snap: {:.1}°C forecast within 12 hours, {:.1}°C outside now",
+        device_name,
+        forecast_min_next_12h_temp,
+        outdoor_temperature
+    );
+    let cause_id = CauseReason::ColdSnapPreheat.id();
+    if let Err(e) = send_ac_command(device_name, &current_state, &desired_state, cause_id, None, false).await {
+        log::error!("Failed to start the cold snap preheat for {}: {}", device_name, e);
+        return Some(NodeExecutionResult::Error(format!("Failed to start preheat: {}", e)));
+    }
+
+    update_state_manager(device_name, &desired_state);
+    if !current_state.is_on {
+        super::min_on_time::get_min_on_time_state().record_turn_on(device_name);
+    }
+    target_temperature::get_target_temperatures().update(device_name, &desired_state);
+    preheat_state.start(device_name, now, &config.cold_snap_preheat);
+    Some(NodeExecutionResult::CommandExecuted)
+}
+
 /// Gather all inputs needed for nodeset execution
-async fn gather_execution_inputs(device: &AcDevices) -> Result<ExecutionInputs, String> {
+async fn gather_execution_inputs(device: &AcDevice) -> Result<ExecutionInputs, String> {
     let device_name = device.as_str();
     let config = config::get_config();
 
-    // Get device sensor temperature
-    let device_sensor_temperature = match device_requests::ac::get_sensors_cached(device_name).await {
-        Ok(sensor_data) => sensor_data.temperature,
+    // Get device sensor temperature and humidity (if the controller has a humidity sensor)
+    let (device_sensor_temperature, device_humidity) = match device_requests::ac::get_sensors_cached(device_name).await {
+        Ok(sensor_data) => (sensor_data.temperature, sensor_data.humidity),
         Err(e) => {
             return Err(format!("Failed to get sensor data: {}", e));
         }
     };
 
+    // Median of the last readings, so single-sample spikes don't reach the profile
+    let device_sensor_temperature = sensor_filter::get_sensor_filter_state().filter_reading(
+        device_name,
+        device_sensor_temperature,
+        config.sensor_median_window,
+    );
+
+    // Implausibly fast changes are treated as stale: keep using the last plausible reading
+    let device_sensor_temperature = if config.sensor_max_rate_c_per_minute > 0.0 {
+        sensor_anomaly::get_sensor_anomaly_state().filter_reading(
+            device_name,
+            device_sensor_temperature,
+            config.sensor_max_rate_c_per_minute,
+        )
+    } else {
+        device_sensor_temperature
+    };
+
     // Get auto mode status (already checked above, but we need it for inputs)
     let is_auto_mode = manual_mode_monitor::get_manual_mode_monitor()
         .get_mode(device_name)
@@ -124,43 +313,81 @@ async fn gather_execution_inputs(device: &AcDevices) -> Result<ExecutionInputs,
         }
     };
 
+    // Get outdoor weather at the device's coordinates, failing over to the global location
+    let weather_coordinates = config.get_weather_coordinates(device_name);
+    let global_coordinates = (config.latitude, config.longitude);
+
     // Get outdoor temperature
-    let outdoor_temperature = match device_requests::weather::get_current_outdoor_temp_cached(
-        config.latitude,
-        config.longitude,
+    let (outdoor_temperature, outdoor_temperature_available) = match device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_current_outdoor_temp_cached,
     )
     .await
     {
-        Ok(temp) => temp,
+        Ok(temp) => (temp, true),
         Err(e) => {
             log::warn!("Failed to get outdoor temperature: {}. Using default.", e);
-            DEFAULT_OUTDOOR_TEMPERATURE
+            (DEFAULT_OUTDOOR_TEMPERATURE, false)
+        }
+    };
+
+    // Get outdoor humidity (optional, profiles check availability)
+    let outdoor_humidity = match device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_current_outdoor_humidity_cached,
+    )
+    .await
+    {
+        Ok(humidity) => Some(humidity),
+        Err(e) => {
+            log::warn!("Failed to get outdoor humidity: {}", e);
+            None
         }
     };
 
+    // Get day-ahead energy prices (empty unless a bidding zone is configured)
+    let energy_price = device_requests::energy_price::get_energy_price_data(
+        &config.energy_price_bidding_zone,
+        chrono::Utc::now().timestamp(),
+    )
+    .await;
+
+    // Get the home battery status (None unless a battery endpoint is configured)
+    let battery = device_requests::battery::get_battery_data(&config.battery_api_endpoint).await;
+
     // Get is_user_home
     let is_user_home = time_helpers::is_user_home_and_awake_async().await;
 
     // Get net power and raw solar
-    let (net_power_watt, raw_solar_watt) = match device_requests::meter::get_latest_reading_cached().await {
+    let (net_power_watt, raw_solar_watt, meter_available) = match device_requests::meter::get_latest_reading_cached().await {
         Ok(reading) => {
             let net = ((reading.current_consumption_kw - reading.current_production_kw) * 1000.0) as i64;
             let solar = match device_requests::meter::get_solar_production_cached().await {
                 Ok(production) => production.current_production.max(0) as i64,
                 Err(_) => (reading.current_production_kw * 1000.0).max(0.0) as i64,
             };
-            (net, solar)
+            (net, solar, true)
         }
         Err(e) => {
             log::warn!("Failed to get meter reading: {}. Using defaults.", e);
-            (0, 0)
+            (0, 0, false)
         }
     };
 
+    // Detect a possible grid outage from meter availability, solar and UPS signal
+    let possible_grid_outage = grid_state::detect_possible_grid_outage(
+        meter_available,
+        raw_solar_watt,
+        grid_state::get_grid_state().get_ups_on_battery(),
+    );
+
     // Get avg_next_24h_outdoor_temp
-    let avg_next_24h_outdoor_temp = match device_requests::weather::get_avg_next_24h_outdoor_temp_cached(
-        config.latitude,
-        config.longitude,
+    let avg_next_24h_outdoor_temp = match device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_avg_next_24h_outdoor_temp_cached,
     )
     .await
     {
@@ -171,19 +398,110 @@ async fn gather_execution_inputs(device: &AcDevices) -> Result<ExecutionInputs,
         }
     };
 
-    // Get PIR state
+    // Get the lowest forecast temperature for the next 12 hours
+    let forecast_min_next_12h_temp = match device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_min_next_12h_outdoor_temp_cached,
+    )
+    .await
+    {
+        Ok(min) => min,
+        Err(e) => {
+            log::warn!("Failed to get 12h minimum outdoor temperature: {}. Using current.", e);
+            outdoor_temperature
+        }
+    };
+
+    // Get the hourly forecast for the Weather Forecast node (empty if unavailable)
+    let weather_forecast = match device_requests::weather::with_failover(
+        weather_coordinates,
+        global_coordinates,
+        device_requests::weather::get_hourly_forecast_cached,
+    )
+    .await
+    {
+        Ok(forecast) => forecast,
+        Err(e) => {
+            log::warn!("Failed to get the hourly weather forecast: {}", e);
+            Default::default()
+        }
+    };
+
+    // Get the expected PV production of the next 3 hours (None unless solar panels are configured)
+    let expected_solar_next_3h_wh = device_requests::solar_forecast::get_expected_solar_next_3h_wh(&config).await;
+
+    // Classify the season from the rolling outdoor average
+    let season = season::current_season(&config, avg_next_24h_outdoor_temp).await;
+
+    // Age of the data read above, so profiles can refuse to act on stale data
+    // Weather falls back to stale cache, so its age can exceed the cache TTL
+    let weather_age = if outdoor_temperature_available {
+        // Without data for the device's coordinates the temperature came from the global location
+        match device_requests::weather::get_current_outdoor_temp_age(weather_coordinates.0, weather_coordinates.1).await {
+            Some(age) => Some(age),
+            None => device_requests::weather::get_current_outdoor_temp_age(global_coordinates.0, global_coordinates.1).await,
+        }
+    } else {
+        None
+    };
+    let meter_age = if meter_available {
+        device_requests::meter::get_latest_reading_age().await
+    } else {
+        None
+    };
+    let data_ages = DataAges {
+        weather: weather_age.map(to_minutes),
+        meter: meter_age.map(to_minutes),
+        sensor: device_requests::ac::get_sensors_age(device_name).await.map(to_minutes),
+    };
+
+    // Get PIR state of the device, the named sensors and their zones
     let pir = pir_state::get_pir_state();
+    let now = chrono::Utc::now();
     let mut pir_state_map = HashMap::new();
-    
-    if let Some(last_detection) = pir.get_last_detection(device_name) {
-        let now = chrono::Utc::now();
-        let minutes_ago = now.signed_duration_since(last_detection).num_minutes();
-        let is_triggered = pir.has_recent_detection(device_name, config.pir_timeout_minutes);
-        pir_state_map.insert(device_name.to_string(), (is_triggered, minutes_ago));
-    } else {
-        // No detection ever - use sentinel value indicating never detected
-        pir_state_map.insert(device_name.to_string(), (false, PIR_NEVER_DETECTED));
-    }
+    pir_state_map.insert(
+        device_name.to_string(),
+        pir_detection_state(pir.get_last_detection(device_name), now, config.pir_timeout_minutes),
+    );
+    let pir_sensors = config
+        .pir_sensors
+        .keys()
+        .cloned()
+        .chain(pir.get_detected_sensors())
+        .map(|sensor_id| {
+            let state = pir_detection_state(pir.get_sensor_last_detection(&sensor_id), now, config.pir_timeout_minutes);
+            (sensor_id, state)
+        })
+        .collect();
+    let pir_zones = config
+        .pir_zones()
+        .into_iter()
+        .map(|(zone, sensor_ids)| {
+            let state = pir_detection_state(pir.get_zone_last_detection(&sensor_ids), now, config.pir_timeout_minutes);
+            (zone, state)
+        })
+        .collect();
+
+    // Occupancy of every device's room, the Occupancy node can check other rooms
+    let local_time = time_helpers::local_time_now();
+    let occupancy_model = occupancy::get_occupancy_model();
+    let occupancy = config
+        .ac_controller_endpoints
+        .keys()
+        .map(|device| {
+            let probability = occupancy_model.probability(device, &config.occupancy, local_time.hour as u32, now);
+            (device.clone(), probability)
+        })
+        .collect();
+
+    // Open windows and doors of every device's room, the Contact Sensor node can check other rooms
+    let open_windows: HashMap<String, Minutes> = contact_sensors::get_contact_sensor_state()
+        .open_since_by_device(&config.contact_sensors)
+        .into_iter()
+        .map(|(device, open_since)| (device, Minutes(now.signed_duration_since(open_since).num_minutes().max(0))))
+        .collect();
+    let is_window_open = open_windows.contains_key(device_name);
 
     // Get active command from state manager
     let state_manager = get_state_manager();
@@ -195,7 +513,11 @@ async fn gather_execution_inputs(device: &AcDevices) -> Result<ExecutionInputs,
     let active_command = ActiveCommandData {
         is_defined,
         is_on: ac_state.is_on,
-        temperature: ac_state.temperature.unwrap_or(0.0),
+        temperature: ac_state.temperature.map_or(0.0, |t| t.0),
+        target_temperature: target_temperature::get_target_temperatures()
+            .get(device_name)
+            .or(ac_state.temperature)
+            .map_or(0.0, |t| t.0),
         mode: ac_state.mode.unwrap_or(0),
         fan_speed: ac_state.fan_speed.unwrap_or(0),
         swing: ac_state.swing.unwrap_or(0),
@@ -204,39 +526,129 @@ async fn gather_execution_inputs(device: &AcDevices) -> Result<ExecutionInputs,
 
     Ok(ExecutionInputs {
         device: device_name.to_string(),
-        device_sensor_temperature,
+        device_sensor_temperature: Celsius(device_sensor_temperature),
         is_auto_mode,
-        last_change_minutes,
-        outdoor_temperature,
+        last_change_minutes: Minutes(last_change_minutes),
+        outdoor_temperature: Celsius(outdoor_temperature),
         is_user_home,
-        net_power_watt,
-        raw_solar_watt,
-        avg_next_24h_outdoor_temp,
+        people_home_count: time_helpers::people_home_count(is_user_home),
+        net_power_watt: Watts(net_power_watt),
+        raw_solar_watt: Watts(raw_solar_watt),
+        avg_next_24h_outdoor_temp: Celsius(avg_next_24h_outdoor_temp),
+        forecast_min_next_12h_temp: Celsius(forecast_min_next_12h_temp),
+        weather_forecast,
+        expected_solar_next_3h_wh,
+        season,
         pir_state: pir_state_map,
+        pir_sensors,
+        pir_zones,
+        occupancy,
+        is_window_open,
+        open_windows,
         active_command,
+        possible_grid_outage,
+        demand_response: demand_response::get_demand_response_state().get_data(),
+        device_humidity,
+        outdoor_humidity,
+        local_time,
+        energy_price,
+        battery_soc_percent: battery.as_ref().map(|b| b.soc_percent),
+        battery_power_watt: battery.as_ref().map(|b| Watts(b.power_watt.round() as i64)),
+        power_budget: power_budget::get_data(&config, get_state_manager(), device_name),
+        data_ages,
+        // Depend on the nodeset, loaded with it
+        hysteresis_state: HashMap::new(),
+        cooldown_state: HashMap::new(),
     })
 }
 
-/// Load the active nodeset from the database
-async fn load_active_nodeset() -> Result<(Vec<serde_json::Value>, Vec<serde_json::Value>), String> {
-    let pool = db::get_pool().await;
+/// Whole minutes of a data age
+fn to_minutes(age: std::time::Duration) -> Minutes {
+    Minutes((age.as_secs() / 60) as i64)
+}
 
-    // Get the active nodeset id
-    let active_id = match get_active_nodeset_id(pool).await {
-        Ok(id) => id,
-        Err(e) => return Err(format!("Failed to get active nodeset id: {}", e)),
-    };
+/// A nodeset ID and revision with its nodes and edges
+type LoadedNodeset = (i64, i64, Vec<serde_json::Value>, Vec<serde_json::Value>);
+
+/// Nodesets of all devices, snapshotted at the start of a control cycle
+/// Every device in a cycle runs the graph that was current when the cycle started, so saving or
+/// activating a nodeset mid-cycle can't give devices later in the cycle a different graph
+pub struct CycleNodesets {
+    nodesets: HashMap<String, Result<LoadedNodeset, String>>,
+}
+
+impl CycleNodesets {
+    /// Load the nodeset of every device
+    pub async fn load(devices: &[AcDevice]) -> Self {
+        let mut nodesets = HashMap::new();
+        for device in devices {
+            nodesets.insert(device.as_str().to_string(), load_nodeset_for_device(device.as_str()).await);
+        }
+        Self { nodesets }
+    }
+
+    /// The nodeset a device runs this cycle
+    pub(super) fn get(&self, device_name: &str) -> Result<LoadedNodeset, String> {
+        self.nodesets
+            .get(device_name)
+            .cloned()
+            .unwrap_or_else(|| Err(format!("No nodeset snapshot for device '{}'", device_name)))
+    }
+
+    /// Names of the devices whose nodeset ID and revision differ in `revisions`, sorted
+    /// A device whose nodeset failed to load counts as changed once its revision can be read
+    fn changed_devices(&self, revisions: &HashMap<String, (i64, i64)>) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .nodesets
+            .iter()
+            .filter(|(device_name, nodeset)| {
+                revisions.get(*device_name).is_some_and(|current| match nodeset {
+                    Ok((id, revision, _, _)) => (*id, *revision) != *current,
+                    Err(_) => true,
+                })
+            })
+            .map(|(device_name, _)| device_name.clone())
+            .collect();
+        changed.sort();
+        changed
+    }
+
+    /// Log the devices whose nodeset changed while the cycle ran
+    /// The change was deferred and is picked up when the next cycle snapshots the nodesets.
+    /// Only the revisions are read, the nodesets themselves are reloaded by that next cycle
+    pub async fn log_deferred_reloads(&self, devices: &[AcDevice]) {
+        let pool = db::get_pool().await;
+        let mut revisions = HashMap::new();
+        for device in devices {
+            if let Ok(revision) = get_nodeset_revision_for_device(pool, device.as_str()).await {
+                revisions.insert(device.as_str().to_string(), revision);
+            }
+        }
+        for device_name in self.changed_devices(&revisions) {
+            log::info!(
+                "Nodeset for '{}' changed during the control cycle, deferring the reload to the next cycle",
+                device_name
+            );
+        }
+    }
+}
+
+/// Load the nodeset a device runs from the database: its assigned nodeset, or the active nodeset
+/// Returns the nodeset ID and revision with its nodes and edges
+pub(super) async fn load_nodeset_for_device(device_name: &str) -> Result<LoadedNodeset, String> {
+    let pool = db::get_pool().await;
+    let nodeset_id = get_nodeset_id_for_device(pool, device_name).await?;
 
     // Fetch the nodeset
-    let result = sqlx::query_as::<_, (String,)>("SELECT node_json FROM nodesets WHERE id = ?")
-        .bind(active_id)
+    let result = sqlx::query_as::<_, (String, i64)>("SELECT node_json, revision FROM nodesets WHERE id = $1")
+        .bind(nodeset_id)
         .fetch_optional(pool)
         .await;
 
     match result {
-        Ok(Some((node_json,))) => {
-            let parsed: serde_json::Value = serde_json::from_str(&node_json)
-                .map_err(|e| format!("Failed to parse nodeset JSON: {}", e))?;
+        Ok(Some((node_json, revision))) => {
+            let mut parsed = schema::parse_nodeset_json(&node_json).map_err(|e| e.to_string())?;
+            variables::resolve_document(&mut parsed);
             
             let nodes = parsed
                 .get("nodes")
@@ -249,19 +661,44 @@ async fn load_active_nodeset() -> Result<(Vec<serde_json::Value>, Vec<serde_json
                 .and_then(|e| e.as_array())
                 .map(|arr| arr.clone())
                 .unwrap_or_default();
-            
-            Ok((nodes, edges))
+
+            let (nodes, edges) = db::nodesets::expand_calls(pool, Some(nodeset_id), nodes, edges).await?;
+            Ok((nodeset_id, revision, nodes, edges))
         }
         Ok(None) => {
             // No nodeset found - return empty
-            Ok((vec![], vec![]))
+            Ok((nodeset_id, 0, vec![], vec![]))
         }
         Err(e) => Err(format!("Failed to fetch nodeset: {}", e)),
     }
 }
 
+/// The nodeset a device runs: its assigned nodeset, or the active nodeset
+async fn get_nodeset_id_for_device(pool: &crate::db::DbPool, device_name: &str) -> Result<i64, String> {
+    // A nodeset assigned to the device takes precedence over the active nodeset
+    match db::device_nodesets::get_for_device(pool, device_name).await {
+        Ok(Some(id)) => Ok(id),
+        Ok(None) => get_active_nodeset_id(pool)
+            .await
+            .map_err(|e| format!("Failed to get active nodeset id: {}", e)),
+        Err(e) => Err(format!("Failed to get nodeset assignment: {}", e)),
+    }
+}
+
+/// ID and revision of the nodeset a device runs, without loading it
+/// The revision is 0 when the nodeset doesn't exist, like in `load_nodeset_for_device`
+async fn get_nodeset_revision_for_device(pool: &crate::db::DbPool, device_name: &str) -> Result<(i64, i64), String> {
+    let nodeset_id = get_nodeset_id_for_device(pool, device_name).await?;
+    let revision = sqlx::query_scalar::<_, i64>("SELECT revision FROM nodesets WHERE id = $1")
+        .bind(nodeset_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch nodeset revision: {}", e))?;
+    Ok((nodeset_id, revision.unwrap_or(0)))
+}
+
 /// Get the active nodeset ID from settings
-async fn get_active_nodeset_id(pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Error> {
+async fn get_active_nodeset_id(pool: &crate::db::DbPool) -> Result<i64, sqlx::Error> {
     const DEFAULT_NODESET_ID: i64 = 0;
     
     let result = sqlx::query_as::<_, (String,)>(
@@ -284,7 +721,7 @@ async fn get_active_nodeset_id(pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Err
 
 /// Handle reset_active_command flag if set in the execution result
 /// This resets the device state to undefined (as on startup)
-fn handle_reset_active_command_if_needed(device: &AcDevices, result: &ExecutionResult) {
+fn handle_reset_active_command_if_needed(device: &AcDevice, result: &ExecutionResult) {
     if result.reset_active_command {
         let device_name = device.as_str();
         log::info!(
@@ -296,7 +733,7 @@ fn handle_reset_active_command_if_needed(device: &AcDevices, result: &ExecutionR
 }
 
 /// Convert execution result to actual AC commands
-async fn execute_result_to_commands(device: &AcDevices, result: ExecutionResult) -> NodeExecutionResult {
+async fn execute_result_to_commands(device: &AcDevice, result: ExecutionResult) -> NodeExecutionResult {
     let device_name = device.as_str();
 
     // Check for execution errors
@@ -308,6 +745,9 @@ async fn execute_result_to_commands(device: &AcDevices, result: ExecutionResult)
     // Handle reset_active_command flag - reset the device state to undefined
     handle_reset_active_command_if_needed(device, &result);
 
+    // Send notifications queued by Notify nodes
+    notifications::dispatch_notifications(device_name, &result.notifications);
+
     // Handle different terminal types
     match result.terminal_type.as_deref() {
         Some("Do Nothing") => {
@@ -336,41 +776,46 @@ async fn execute_result_to_commands(device: &AcDevices, result: ExecutionResult)
 }
 
 /// Execute an ActionResult by sending the appropriate AC commands
-async fn execute_action_result(device: &AcDevices, action: &ActionResult) -> NodeExecutionResult {
+async fn execute_action_result(device: &AcDevice, action: &ActionResult) -> NodeExecutionResult {
     let device_name = device.as_str();
     let state_manager = get_state_manager();
     let current_state = state_manager.get_state(device_name);
     
-    // Parse the cause_reason to get the ID for logging
-    let cause_id: i32 = match action.cause_reason.parse() {
-        Ok(id) => id,
-        Err(e) => {
+    // Resolve the cause_reason (an ID or a label) to get the ID for logging
+    // Numeric IDs that aren't in the database are kept as-is
+    let cause_id = match resolve_cause_id(&action.cause_reason).await.or_else(|| action.cause_reason.parse().ok()) {
+        Some(id) => id,
+        None => {
             log::warn!(
-                "Failed to parse cause_reason '{}' for device '{}': {}. Using Undefined.",
-                action.cause_reason, device_name, e
+                "Unknown cause_reason '{}' for device '{}'. Using Undefined.",
+                action.cause_reason, device_name
             );
             CauseReason::Undefined.id()
         }
     };
 
-    // Convert the action to a desired AcState
-    let desired_state = action_to_ac_state(action);
+    // Convert the action to a target AcState with the device's setpoint offset,
+    // the command sent is rounded to the temperature step the AC supports
+    let offset = setpoint_offset::get(db::get_pool().await, device_name).await;
+    let config = config::get_config();
+    let target_state = setpoint_offset::apply(action_to_ac_state(action, &config), offset);
+    let desired_state = target_state.clone().with_rounded_temperature(config.temperature_rounding_step);
+    let (desired_state, cause_id) = match limit_to_power_budget(device_name, &desired_state) {
+        Some(limited_state) => (limited_state, CauseReason::PowerBudget.id()),
+        None => (desired_state, cause_id),
+    };
 
-    // Check minimum on-time for turn-off operations
-    if !desired_state.is_on && current_state.is_on {
-        let min_on_time_state = super::min_on_time::get_min_on_time_state();
-        if !min_on_time_state.can_turn_off(device_name) {
-            log::info!(
-                "Device '{}' has not been on for minimum time, not turning off yet",
-                device_name
-            );
-            return NodeExecutionResult::NoAction;
-        }
+    if let Some(reason) = min_time_blocks(device_name, &current_state, &desired_state) {
+        log::info!("Device '{}' {}", device_name, reason);
+        return NodeExecutionResult::NoAction;
     }
+    target_temperature::get_target_temperatures().update(device_name, &target_state);
 
     // Check if state change is needed
     // First execution or state differs requires sending command
     let is_first_execution = !state_manager_is_device_initialized(device_name);
+    let change_policy = config::get_config().get_change_policy(device_name);
+    let requires_change = current_state.requires_change(&desired_state, &change_policy);
     
     // Log state comparison for debugging
     log::info!(
@@ -379,10 +824,10 @@ async fn execute_action_result(device: &AcDevices, action: &ActionResult) -> Nod
         current_state.is_on,
         desired_state.is_on,
         is_first_execution,
-        current_state.requires_change(&desired_state)
+        requires_change
     );
     
-    if !is_first_execution && !current_state.requires_change(&desired_state) {
+    if !is_first_execution && !requires_change {
         log::info!(
             "No state change required for device '{}', skipping command (current matches desired)",
             device_name
@@ -390,6 +835,15 @@ async fn execute_action_result(device: &AcDevices, action: &ActionResult) -> Nod
         return NodeExecutionResult::NoAction;
     }
 
+    // Defer setpoint tweaks while a do-not-disturb window is active
+    if !is_first_execution && super::dnd::should_defer_command(device_name, &current_state, &desired_state) {
+        log::info!(
+            "Do-not-disturb window active for device '{}', deferring setpoint change",
+            device_name
+        );
+        return NodeExecutionResult::NoAction;
+    }
+
     if is_first_execution {
         log::info!(
             "First execution for device '{}', sending command to ensure sync",
@@ -398,39 +852,145 @@ async fn execute_action_result(device: &AcDevices, action: &ActionResult) -> Nod
     }
 
     // Execute the AC command
-    let result = send_ac_command(device_name, &current_state, &desired_state, cause_id, is_first_execution).await;
+    let result = send_ac_command(device_name, &current_state, &desired_state, cause_id, action.annotation.as_deref(), is_first_execution).await;
 
     handle_command_result(device_name, result, &current_state, &desired_state, action, false)
 }
 
+/// Why turning the device on or off must wait for its minimum off or on time, None if it needn't
+fn min_time_blocks(device_name: &str, current_state: &AcState, desired_state: &AcState) -> Option<&'static str> {
+    // Check minimum on-time for turn-off operations
+    if !desired_state.is_on && current_state.is_on && !super::min_on_time::get_min_on_time_state().can_turn_off(device_name) {
+        return Some("has not been on for minimum time, not turning off yet");
+    }
+
+    // Check minimum off-time for turn-on operations, so the compressor doesn't short cycle
+    if desired_state.is_on && !current_state.is_on {
+        let min_off_time_minutes = config::get_config().get_min_off_time_minutes(device_name);
+        if !super::min_off_time::get_min_off_time_state().can_turn_on(device_name, min_off_time_minutes) {
+            return Some("has not been off for minimum time, not turning on yet");
+        }
+    }
+    None
+}
+
+/// Why a command that wasn't applied must not be resent, None if it may
+/// A resend is held back while the user has taken over the device, and otherwise goes through the
+/// power budget, minimum on/off times and do-not-disturb windows like the command did, compared
+/// with the state the device reports
+pub(super) fn resend_blocks(device_name: &str, reported_state: &AcState, state: &AcState) -> Option<&'static str> {
+    let taken_over = user_control_blocks(
+        device_name,
+        manual_override::get_manual_overrides(),
+        shadow_mode::get_shadow_mode(),
+        manual_mode_monitor::get_manual_mode_monitor(),
+    );
+    if taken_over.is_some() {
+        return taken_over;
+    }
+    if limit_to_power_budget(device_name, state).is_some() {
+        return Some("no longer fits the power budget");
+    }
+    if let Some(reason) = min_time_blocks(device_name, reported_state, state) {
+        return Some(reason);
+    }
+    if super::dnd::should_defer_command(device_name, reported_state, state) {
+        return Some("is in a do-not-disturb window");
+    }
+    None
+}
+
+/// Why the user's control of a device must not be undone by a resend, None if nothing was taken over
+/// The unit's own mode only counts once it reported being in manual mode
+fn user_control_blocks(
+    device_name: &str,
+    overrides: &manual_override::ManualOverrides,
+    shadow_mode: &shadow_mode::ShadowMode,
+    manual_mode: &manual_mode_monitor::ManualModeMonitor,
+) -> Option<&'static str> {
+    if overrides.get_active(device_name).is_some() {
+        return Some("has an active manual override");
+    }
+    if shadow_mode.is_enabled() {
+        return Some("is in shadow mode");
+    }
+    if manual_mode.get_mode(device_name) == Some(false) {
+        return Some("is in manual mode");
+    }
+    None
+}
+
+/// Limit a desired state so the combined draw of all devices stays within the power budget
+/// Returns None if the state fits as is
+fn limit_to_power_budget(device_name: &str, desired_state: &AcState) -> Option<AcState> {
+    let budget = power_budget::get_data(&config::get_config(), get_state_manager(), device_name);
+    let limited_state = power_budget::limit_to_budget(&budget, desired_state)?;
+    log::info!(
+        "Limiting device '{}' to {} to stay within the power budget ({} W of {} W left)",
+        device_name,
+        if limited_state.is_on { "normal mode" } else { "off" },
+        budget.remaining_watt(),
+        budget.budget_watt
+    );
+    Some(limited_state)
+}
+
+/// Resolve the cause reason selected by a nodeset, by ID or by label
+/// Returns None if it matches no cause reason in the database
+async fn resolve_cause_id(cause_reason: &str) -> Option<i32> {
+    match db::cause_reasons::get_all(db::get_pool().await, true).await {
+        Ok(cause_reasons) => db::cause_reasons::find_by_reference(&cause_reasons, cause_reason).map(|cr| cr.id),
+        Err(e) => {
+            log::warn!("Failed to load cause reasons: {}", e);
+            None
+        }
+    }
+}
+
+/// With `strict_cause_reasons` enabled, add a warning to the result if its action or Do Nothing node
+/// selects a cause reason that doesn't exist, so it shows up in the execution history
+async fn warn_on_unknown_cause_reason(device_name: &str, result: &mut ExecutionResult) {
+    let cause_reason = match (&result.action, &result.do_nothing) {
+        (Some(action), _) => &action.cause_reason,
+        (None, Some(do_nothing)) => &do_nothing.cause_reason,
+        (None, None) => return,
+    };
+    if resolve_cause_id(cause_reason).await.is_some() {
+        return;
+    }
+
+    let warning = format!("Cause reason '{}' does not exist", cause_reason);
+    log::warn!("{} for device '{}'", warning, device_name);
+    result.warnings.push(warning);
+}
+
 /// Convert an ActionResult to an AcState
-fn action_to_ac_state(action: &ActionResult) -> AcState {
+/// Vane positions are only kept for vanes the device supports
+fn action_to_ac_state(action: &ActionResult, config: &Config) -> AcState {
     // Convert enable_swing boolean to swing integer (0 = off, 1 = on)
     let swing = if action.enable_swing { 1 } else { 0 };
     
-    match action.mode.as_str() {
-        "Off" => AcState::new_off(),
-        "Heat" => {
-            let fan_speed = parse_fan_speed(&action.fan_speed);
-            AcState::new_on(
-                AC_MODE_HEAT,
-                fan_speed,
-                action.temperature,
-                swing,
-                action.is_powerful,
-            )
-        }
-        "Cool" => {
+    if action.mode == "Off" {
+        return AcState::new_off();
+    }
+    match ac_mode_from_name(&action.mode) {
+        Some(mode) => {
             let fan_speed = parse_fan_speed(&action.fan_speed);
+            let (vertical_vane, horizontal_vane) = config.supported_vanes(
+                &action.device,
+                action.vertical_vane.as_deref(),
+                action.horizontal_vane.as_deref(),
+            );
             AcState::new_on(
-                AC_MODE_COOL,
+                mode,
                 fan_speed,
-                action.temperature,
+                Celsius(action.temperature),
                 swing,
                 action.is_powerful,
             )
+            .with_vanes(vertical_vane, horizontal_vane)
         }
-        _ => {
+        None => {
             log::warn!("Unknown action mode '{}', defaulting to Off", action.mode);
             AcState::new_off()
         }
@@ -439,14 +999,7 @@ fn action_to_ac_state(action: &ActionResult) -> AcState {
 
 /// Parse fan speed string to i32
 fn parse_fan_speed(fan_speed: &str) -> i32 {
-    match fan_speed {
-        "Auto" => 0,
-        "High" => 1,
-        "Medium" => 2,
-        "Low" => 3,
-        "Quiet" => 4,
-        _ => 0, // Default to Auto
-    }
+    fan_speed_from_name(fan_speed).unwrap_or(0) // Default to Auto
 }
 
 /// Check if device is initialized in state manager
@@ -455,11 +1008,24 @@ fn state_manager_is_device_initialized(device_name: &str) -> bool {
     state_manager.is_device_initialized(device_name)
 }
 
+/// PIR state as the PIR Detection node gets it: (is_recently_triggered, minutes_ago)
+/// Never detected is `PIR_NEVER_DETECTED` minutes ago
+fn pir_detection_state(last_detection: Option<DateTime<Utc>>, now: DateTime<Utc>, timeout_minutes: u32) -> (bool, Minutes) {
+    match last_detection {
+        Some(last_detection) => {
+            let minutes_ago = now.signed_duration_since(last_detection).num_minutes();
+            (minutes_ago < i64::from(timeout_minutes), Minutes(minutes_ago))
+        }
+        None => (false, Minutes(PIR_NEVER_DETECTED)),
+    }
+}
+
 /// Update state manager with new state
 fn update_state_manager(device_name: &str, state: &AcState) {
     let state_manager = get_state_manager();
     state_manager.set_state(device_name, state.clone());
     state_manager.mark_device_initialized(device_name);
+    live_events::get_live_events().publish(LiveEvent::ac_state(device_name, state));
 }
 
 /// Send AC command based on state transition
@@ -469,18 +1035,21 @@ fn update_state_manager(device_name: &str, state: &AcState) {
 /// * `current_state` - Current tracked state of the device
 /// * `desired_state` - Desired state from nodeset execution
 /// * `cause_id` - ID of the cause reason for logging
+/// * `annotation` - Annotation of the action for logging, if the profile set one
 /// * `is_first_execution` - Whether this is the first command after startup (forces sync)
 async fn send_ac_command(
     device_name: &str,
     current_state: &AcState,
     desired_state: &AcState,
     cause_id: i32,
+    annotation: Option<&str>,
     is_first_execution: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Case 1: Turning off (from on state)
     if !desired_state.is_on && current_state.is_on {
         log::info!("Turning off AC '{}'", device_name);
-        device_requests::ac::turn_off_ac(device_name, cause_id).await?;
+        device_requests::ac::turn_off_ac(device_name, cause_id, annotation).await?;
+        command_verification::schedule(device_name, desired_state, cause_id, annotation);
         return Ok(());
     }
 
@@ -489,47 +1058,29 @@ async fn send_ac_command(
     if !desired_state.is_on && !current_state.is_on {
         if is_first_execution {
             log::info!("Sending OFF command to '{}' to ensure sync with physical device", device_name);
-            device_requests::ac::turn_off_ac(device_name, cause_id).await?;
+            device_requests::ac::turn_off_ac(device_name, cause_id, annotation).await?;
+            command_verification::schedule(device_name, desired_state, cause_id, annotation);
         }
         return Ok(());
     }
 
     // Case 3: Turning on or changing settings
     if desired_state.is_on {
-        // Extract AC parameters with proper error handling
-        let mode = desired_state.mode.ok_or_else(|| {
-            format!("Mode not set when AC is on for device '{}'", device_name)
-        })?;
-        let fan_speed = desired_state.fan_speed.ok_or_else(|| {
-            format!("Fan speed not set when AC is on for device '{}'", device_name)
-        })?;
-        let temperature = desired_state.temperature.ok_or_else(|| {
-            format!("Temperature not set when AC is on for device '{}'", device_name)
-        })?;
-        let swing = desired_state.swing.ok_or_else(|| {
-            format!("Swing not set when AC is on for device '{}'", device_name)
-        })?;
-
-        log::info!(
-            "Turning on AC '{}': mode={}, fan_speed={}, temp={}°C, swing={}",
-            device_name,
-            mode,
-            fan_speed,
-            temperature,
-            swing
-        );
-        device_requests::ac::turn_on_ac(device_name, mode, fan_speed, temperature, swing, cause_id).await?;
+        let command = AcCommand::from_state(desired_state)
+            .map_err(|e| format!("{} for device '{}'", e, device_name))?;
+        device_requests::ac::send_command(device_name, &command, cause_id, annotation).await?;
 
         // Handle powerful mode toggle
         if desired_state.powerful_mode != current_state.powerful_mode {
             if desired_state.powerful_mode {
                 log::info!("Enabling powerful mode for AC '{}'", device_name);
-                device_requests::ac::toggle_powerful(device_name, cause_id).await?;
+                device_requests::ac::toggle_powerful(device_name, cause_id, annotation).await?;
             } else if current_state.powerful_mode {
                 log::info!("Disabling powerful mode for AC '{}'", device_name);
-                device_requests::ac::toggle_powerful(device_name, cause_id).await?;
+                device_requests::ac::toggle_powerful(device_name, cause_id, annotation).await?;
             }
         }
+        command_verification::schedule(device_name, desired_state, cause_id, annotation);
     }
 
     Ok(())
@@ -543,14 +1094,17 @@ async fn send_ac_command(
 /// - State comparison (to immediately sync AC state with nodeset decision)
 ///
 /// The cause_reason is overridden to ManualToAutoTransition for proper logging.
-pub async fn execute_nodeset_for_device_forced(device: &AcDevices) -> NodeExecutionResult {
+pub async fn execute_nodeset_for_device_forced(device: &AcDevice) -> NodeExecutionResult {
     let device_name = device.as_str();
     log::info!("Forced nodeset execution for device '{}' (manual to auto transition)", device_name);
 
     // Execute nodeset core logic (shared with regular execution)
-    let result = execute_nodeset_core(device).await;
+    let result = execute_nodeset_core(device, None).await;
     
     match result {
+        Ok(execution_result) if shadow_mode::get_shadow_mode().is_enabled() => {
+            log_shadow_result(device_name, &execution_result)
+        }
         Ok(execution_result) => {
             // For forced execution, use the forced result handler
             execute_result_to_commands_forced(device, execution_result).await
@@ -562,7 +1116,11 @@ pub async fn execute_nodeset_for_device_forced(device: &AcDevices) -> NodeExecut
 /// Core nodeset execution logic shared between regular and forced execution
 /// 
 /// Returns the ExecutionResult on success, or a NodeExecutionResult::Error on failure
-async fn execute_nodeset_core(device: &AcDevices) -> Result<ExecutionResult, NodeExecutionResult> {
+/// The nodeset engine runs the device's nodeset from `nodesets` if given, otherwise loads it from the database
+async fn execute_nodeset_core(
+    device: &AcDevice,
+    nodesets: Option<&CycleNodesets>,
+) -> Result<ExecutionResult, NodeExecutionResult> {
     let device_name = device.as_str();
 
     // Gather execution inputs
@@ -574,36 +1132,31 @@ async fn execute_nodeset_core(device: &AcDevices) -> Result<ExecutionResult, Nod
         }
     };
 
-    // Load the active nodeset
-    let (nodes, edges) = match load_active_nodeset().await {
-        Ok(data) => data,
-        Err(e) => {
-            log::error!("Failed to load active nodeset: {}", e);
-            return Err(NodeExecutionResult::Error(format!("Failed to load nodeset: {}", e)));
-        }
-    };
-
-    // Validate the nodeset
-    let validation_errors = crate::nodes::validate_nodeset_for_execution(&nodes, &edges);
-    if !validation_errors.is_empty() {
-        log::error!("Nodeset validation failed: {}", validation_errors.join("; "));
-        return Err(NodeExecutionResult::Error(format!("Nodeset validation failed: {}", validation_errors.join("; "))));
+    // Let the device's decision engine decide
+    let engine = config::get_config().get_decision_engine(device_name);
+    let Decision {
+        mut result,
+        nodeset_id,
+        nodeset_revision,
+        snapshot,
+    } = decision_engine::decide(engine, device, inputs, nodesets)
+        .await
+        .map_err(NodeExecutionResult::Error)?;
+
+    if config::get_config().strict_cause_reasons {
+        warn_on_unknown_cause_reason(device_name, &mut result).await;
     }
-
-    // Create and execute the nodeset
-    let mut executor = match NodesetExecutor::new(&nodes, &edges, inputs) {
-        Ok(e) => e,
-        Err(e) => {
-            log::error!("Failed to create executor for {}: {}", device_name, e);
-            return Err(NodeExecutionResult::Error(format!("Failed to create executor: {}", e)));
-        }
-    };
-
-    Ok(executor.execute())
+    let pool = db::get_pool().await;
+    let now = chrono::Utc::now().timestamp();
+    cycle_snapshots::record_cycle(pool, device_name, &snapshot, &result, now).await;
+    let is_shadow = shadow_mode::get_shadow_mode().is_enabled();
+    execution_history::record_execution(pool, device_name, (nodeset_id, nodeset_revision), &snapshot, &result, now, is_shadow).await;
+    live_events::get_live_events().publish(LiveEvent::execution(device_name, &result, is_shadow));
+    Ok(result)
 }
 
 /// Convert execution result to AC commands with forced execution
-async fn execute_result_to_commands_forced(device: &AcDevices, result: ExecutionResult) -> NodeExecutionResult {
+async fn execute_result_to_commands_forced(device: &AcDevice, result: ExecutionResult) -> NodeExecutionResult {
     let device_name = device.as_str();
 
     // Check for execution errors
@@ -615,6 +1168,9 @@ async fn execute_result_to_commands_forced(device: &AcDevices, result: Execution
     // Handle reset_active_command flag - reset the device state to undefined
     handle_reset_active_command_if_needed(device, &result);
 
+    // Send notifications queued by Notify nodes
+    notifications::dispatch_notifications(device_name, &result.notifications);
+
     // Handle different terminal types
     match result.terminal_type.as_deref() {
         Some("Do Nothing") => {
@@ -647,7 +1203,7 @@ async fn execute_result_to_commands_forced(device: &AcDevices, result: Execution
 /// This is used when transitioning from Manual to Auto mode. The state comparison
 /// is bypassed because we want to immediately sync the AC state with the nodeset
 /// decision, regardless of whether the tracked state matches.
-async fn execute_action_result_forced(device: &AcDevices, action: &ActionResult) -> NodeExecutionResult {
+async fn execute_action_result_forced(device: &AcDevice, action: &ActionResult) -> NodeExecutionResult {
     let device_name = device.as_str();
     let state_manager = get_state_manager();
     let current_state = state_manager.get_state(device_name);
@@ -655,11 +1211,20 @@ async fn execute_action_result_forced(device: &AcDevices, action: &ActionResult)
     // Override cause_reason to ManualToAutoTransition for proper logging
     let cause_id = CauseReason::ManualToAutoTransition.id();
 
-    // Convert the action to a desired AcState
-    let desired_state = action_to_ac_state(action);
+    // Convert the action to a target AcState with the device's setpoint offset,
+    // the command sent is rounded to the temperature step the AC supports
+    let offset = setpoint_offset::get(db::get_pool().await, device_name).await;
+    let config = config::get_config();
+    let target_state = setpoint_offset::apply(action_to_ac_state(action, &config), offset);
+    let desired_state = target_state.clone().with_rounded_temperature(config.temperature_rounding_step);
+    let (desired_state, cause_id) = match limit_to_power_budget(device_name, &desired_state) {
+        Some(limited_state) => (limited_state, CauseReason::PowerBudget.id()),
+        None => (desired_state, cause_id),
+    };
+    target_temperature::get_target_temperatures().update(device_name, &target_state);
 
     // Execute the AC command with forced=true to ensure sync
-    let result = send_ac_command(device_name, &current_state, &desired_state, cause_id, true).await;
+    let result = send_ac_command(device_name, &current_state, &desired_state, cause_id, action.annotation.as_deref(), true).await;
 
     handle_command_result(device_name, result, &current_state, &desired_state, action, true)
 }
@@ -679,9 +1244,11 @@ fn handle_command_result(
             // Update state manager
             update_state_manager(device_name, desired_state);
             
-            // Record turn-on time if applicable
+            // Record turn-on or turn-off time if applicable
             if desired_state.is_on && !current_state.is_on {
                 super::min_on_time::get_min_on_time_state().record_turn_on(device_name);
+            } else if !desired_state.is_on && current_state.is_on {
+                super::min_off_time::get_min_off_time_state().record_turn_off(device_name);
             }
             
             let forced_str = if is_forced { "forced " } else { "" };
@@ -704,6 +1271,65 @@ fn handle_command_result(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ac_controller::ac_executor::{AC_MODE_COOL, AC_MODE_DRY, AC_MODE_FAN, AC_MODE_HEAT};
+    use crate::types::AcChangePolicy;
+
+    #[test]
+    fn test_cycle_nodesets_changed_devices() {
+        let nodeset = |id: i64, nodes: Vec<serde_json::Value>| -> Result<LoadedNodeset, String> { Ok((id, 1, nodes, vec![])) };
+        let snapshot = CycleNodesets {
+            nodesets: HashMap::from([
+                ("Bedroom".to_string(), nodeset(1, vec![serde_json::json!({"id": "start"})])),
+                ("Kitchen".to_string(), nodeset(1, vec![serde_json::json!({"id": "start"})])),
+                ("Office".to_string(), nodeset(2, vec![])),
+            ]),
+        };
+        // Bedroom's nodeset was saved, Office was assigned another nodeset and Garage isn't in the cycle
+        let revisions = HashMap::from([
+            ("Bedroom".to_string(), (1, 2)),
+            ("Kitchen".to_string(), (1, 1)),
+            ("Office".to_string(), (3, 1)),
+            ("Garage".to_string(), (1, 2)),
+        ]);
+
+        assert_eq!(snapshot.changed_devices(&revisions), vec!["Bedroom", "Office"]);
+        let unchanged = HashMap::from([("Bedroom".to_string(), (1, 1)), ("Office".to_string(), (2, 1))]);
+        assert!(snapshot.changed_devices(&unchanged).is_empty());
+        let failed = CycleNodesets { nodesets: HashMap::from([("Bedroom".to_string(), Err("unreachable".to_string()))]) };
+        assert_eq!(failed.changed_devices(&unchanged), vec!["Bedroom"]);
+        assert_eq!(snapshot.get("Office"), nodeset(2, vec![]));
+        assert!(snapshot.get("Garage").is_err());
+    }
+
+    #[test]
+    fn test_override_during_verification_is_not_resent() {
+        let verifier = command_verification::CommandVerifier::new();
+        let overrides = manual_override::ManualOverrides::default();
+        let shadow_mode = shadow_mode::ShadowMode::default();
+        let manual_mode = manual_mode_monitor::ManualModeMonitor::new();
+
+        // A control cycle command is waiting to be read back
+        let generation = verifier.start("Veranda");
+        assert_eq!(user_control_blocks("Veranda", &overrides, &shadow_mode, &manual_mode), None);
+
+        // A manual override is sent before the read-back, superseding the verification
+        let override_state = AcState::new_on(AC_MODE_COOL, 0, Celsius(21.0), 0, false);
+        overrides.set("Veranda", override_state, Utc::now() + chrono::Duration::minutes(30));
+        verifier.start("Veranda");
+        assert!(!verifier.is_current("Veranda", generation));
+        assert_eq!(
+            user_control_blocks("Veranda", &overrides, &shadow_mode, &manual_mode),
+            Some("has an active manual override")
+        );
+        assert_eq!(user_control_blocks("Bedroom", &overrides, &shadow_mode, &manual_mode), None);
+
+        // Switching the unit to manual mode holds resends back as well
+        manual_mode.update_mode("Bedroom", false);
+        assert_eq!(
+            user_control_blocks("Bedroom", &overrides, &shadow_mode, &manual_mode),
+            Some("is in manual mode")
+        );
+    }
 
     #[test]
     fn test_action_to_ac_state_off() {
@@ -715,9 +1341,12 @@ mod tests {
             is_powerful: false,
             enable_swing: false,
             cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
         };
         
-        let state = action_to_ac_state(&action);
+        let state = action_to_ac_state(&action, &Config::default());
         assert!(!state.is_on);
         // After fix: mode should be Some(0) to indicate OFF mode was explicitly set
         // This allows is_defined checks to properly detect that a command was sent
@@ -734,12 +1363,15 @@ mod tests {
             is_powerful: false,
             enable_swing: false,
             cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
         };
         
-        let state = action_to_ac_state(&action);
+        let state = action_to_ac_state(&action, &Config::default());
         assert!(state.is_on);
         assert_eq!(state.mode, Some(AC_MODE_HEAT));
-        assert_eq!(state.temperature, Some(24.0));
+        assert_eq!(state.temperature, Some(Celsius(24.0)));
         assert_eq!(state.fan_speed, Some(0)); // Auto
         assert_eq!(state.swing, Some(0)); // Off because enable_swing is false
         assert!(!state.powerful_mode);
@@ -755,17 +1387,46 @@ mod tests {
             is_powerful: true,
             enable_swing: true,
             cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
         };
         
-        let state = action_to_ac_state(&action);
+        let state = action_to_ac_state(&action, &Config::default());
         assert!(state.is_on);
         assert_eq!(state.mode, Some(AC_MODE_COOL));
-        assert_eq!(state.temperature, Some(20.0));
+        assert_eq!(state.temperature, Some(Celsius(20.0)));
         assert_eq!(state.fan_speed, Some(1)); // High
         assert_eq!(state.swing, Some(1)); // On because enable_swing is true
         assert!(state.powerful_mode);
     }
 
+    #[test]
+    fn test_action_to_ac_state_dry_and_fan() {
+        let mut action = ActionResult {
+            device: "TestDevice".to_string(),
+            temperature: 24.0,
+            mode: "Dry".to_string(),
+            fan_speed: "Low".to_string(),
+            is_powerful: false,
+            enable_swing: false,
+            cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
+        };
+
+        let state = action_to_ac_state(&action, &Config::default());
+        assert!(state.is_on);
+        assert_eq!(state.mode, Some(AC_MODE_DRY));
+        assert_eq!(state.fan_speed, Some(3)); // Low
+
+        action.mode = "Fan".to_string();
+        let state = action_to_ac_state(&action, &Config::default());
+        assert!(state.is_on);
+        assert_eq!(state.mode, Some(AC_MODE_FAN));
+    }
+
     #[test]
     fn test_action_to_ac_state_swing_enabled() {
         // Test that enable_swing=true results in swing=1
@@ -777,12 +1438,36 @@ mod tests {
             is_powerful: false,
             enable_swing: true,
             cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
         };
         
-        let state = action_to_ac_state(&action);
+        let state = action_to_ac_state(&action, &Config::default());
         assert_eq!(state.swing, Some(1)); // On because enable_swing is true
     }
 
+    #[test]
+    fn test_action_to_ac_state_drops_unsupported_vanes() {
+        // The test config's devices don't support vane positions, so they are neither sent nor compared
+        let action = ActionResult {
+            device: "LivingRoom".to_string(),
+            temperature: 24.0,
+            mode: "Cool".to_string(),
+            fan_speed: "Auto".to_string(),
+            is_powerful: false,
+            enable_swing: false,
+            cause_reason: "0".to_string(),
+            vertical_vane: Some("Position 1".to_string()),
+            horizontal_vane: Some("Swing".to_string()),
+            annotation: None,
+        };
+        
+        let state = action_to_ac_state(&action, &Config::default());
+        assert!(state.is_on);
+        assert_eq!((state.vertical_vane, state.horizontal_vane), (None, None));
+    }
+
     #[test]
     fn test_action_to_ac_state_swing_disabled() {
         // Test that enable_swing=false results in swing=0
@@ -794,9 +1479,12 @@ mod tests {
             is_powerful: false,
             enable_swing: false,
             cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
         };
         
-        let state = action_to_ac_state(&action);
+        let state = action_to_ac_state(&action, &Config::default());
         assert_eq!(state.swing, Some(0)); // Off because enable_swing is false
     }
 
@@ -827,9 +1515,12 @@ mod tests {
                 is_powerful: false,
                 enable_swing: false,
                 cause_reason: "0".to_string(),
+                vertical_vane: None,
+                horizontal_vane: None,
+                annotation: None,
             };
             
-            let state = action_to_ac_state(&action);
+            let state = action_to_ac_state(&action, &Config::default());
             assert_eq!(state.fan_speed, Some(expected), "Fan speed for {} should be {}", speed_str, expected);
         }
     }
@@ -864,9 +1555,12 @@ mod tests {
             is_powerful: false,
             enable_swing: false,
             cause_reason: "0".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
         };
         
-        let state = action_to_ac_state(&action);
+        let state = action_to_ac_state(&action, &Config::default());
         
         // Verify the state is OFF
         assert!(!state.is_on, "State should be OFF");
@@ -887,13 +1581,13 @@ mod tests {
         let on_state = AcState::new_on(
             AC_MODE_HEAT, // Use imported constant
             0, // Auto fan
-            22.0, // temperature
+            Celsius(22.0), // temperature
             1, // swing on
             false, // not powerful
         );
         
         // OFF to ON should require a change
-        assert!(off_state.requires_change(&on_state), 
+        assert!(off_state.requires_change(&on_state, &AcChangePolicy::default()), 
             "Transitioning from OFF to ON should require a state change");
     }
 
@@ -904,7 +1598,7 @@ mod tests {
         let off_state2 = AcState::new_off();
         
         // Both have mode=Some(0) now, but should still detect no change needed
-        assert!(!off_state1.requires_change(&off_state2), 
+        assert!(!off_state1.requires_change(&off_state2, &AcChangePolicy::default()), 
             "Two OFF states should not require a change");
     }
 
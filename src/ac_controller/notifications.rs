@@ -0,0 +1,139 @@
+//! Delivery of notifications queued by Notify nodes
+//!
+//! A profile is evaluated every few minutes, so a Notify node on a path that stays taken would
+//! send the same notification every cycle. Each node has a cooldown per device: after a
+//! notification was sent, the node stays quiet for that device until the cooldown has passed.
+//! Notifications that weren't delivered don't start a cooldown, so the next cycle tries again.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::{config, device_requests, nodes::NotificationResult};
+
+/// Longest cooldown, longer ones are cut to it
+const MAX_COOLDOWN_MINUTES: i64 = 366 * 24 * 60;
+
+/// Global notification cooldown state
+static NOTIFICATION_STATE: OnceLock<NotificationState> = OnceLock::new();
+
+/// Tracks when each Notify node last sent a notification, per device
+pub struct NotificationState {
+    last_sent: RwLock<HashMap<(String, String), DateTime<Utc>>>,
+}
+
+impl NotificationState {
+    fn new() -> Self {
+        Self {
+            last_sent: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Whether the node's cooldown for the device hasn't passed yet
+    pub fn in_cooldown(&self, device: &str, node_id: &str, cooldown_minutes: i64, now: DateTime<Utc>) -> bool {
+        let last_sent = self.last_sent.read().unwrap();
+        let Some(sent_at) = last_sent.get(&(device.to_string(), node_id.to_string())) else {
+            return false;
+        };
+        TimeDelta::try_minutes(cooldown_minutes.clamp(0, MAX_COOLDOWN_MINUTES))
+            .and_then(|cooldown| sent_at.checked_add_signed(cooldown))
+            .is_none_or(|until| now < until)
+    }
+
+    /// Start the node's cooldown for the device after a notification was sent
+    pub fn record_sent(&self, device: &str, node_id: &str, sent_at: DateTime<Utc>) {
+        self.last_sent
+            .write()
+            .unwrap()
+            .insert((device.to_string(), node_id.to_string()), sent_at);
+    }
+}
+
+/// Get the global notification state instance
+pub fn get_notification_state() -> &'static NotificationState {
+    NOTIFICATION_STATE.get_or_init(NotificationState::new)
+}
+
+/// Send the notifications of an evaluation whose cooldown has passed
+/// Sending happens in the background so AC commands aren't delayed by slow webhooks
+pub fn dispatch_notifications(device_name: &str, notifications: &[NotificationResult]) {
+    let state = get_notification_state();
+    let now = Utc::now();
+
+    for notification in notifications {
+        if state.in_cooldown(device_name, &notification.node_id, notification.cooldown_minutes, now) {
+            log::debug!(
+                "Notification '{}' for {} is in cooldown, not sending",
+                notification.title, device_name
+            );
+            continue;
+        }
+
+        let Some(url) = config::get_config().notification_channels.get(&notification.channel).cloned() else {
+            log::warn!(
+                "Notification channel '{}' is not configured. {}: {}",
+                notification.channel, notification.title, notification.message
+            );
+            continue;
+        };
+
+        log::info!("Notifying '{}' for {}: {}", notification.channel, device_name, notification.title);
+        let device = device_name.to_string();
+        let notification = notification.clone();
+        tokio::spawn(async move {
+            if let Err(e) = device_requests::notification::send_notification(
+                &url,
+                &notification.title,
+                &notification.message,
+                &device,
+            )
+            .await
+            {
+                log::warn!("Failed to send notification to '{}': {}", notification.channel, e);
+                return;
+            }
+            get_notification_state().record_sent(&device, &notification.node_id, now);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_per_device_and_node() {
+        let state = NotificationState::new();
+        let now = Utc::now();
+
+        assert!(!state.in_cooldown("Veranda", "notify-1", 30, now), "nothing sent yet");
+        state.record_sent("Veranda", "notify-1", now);
+        assert!(state.in_cooldown("Veranda", "notify-1", 30, now + TimeDelta::minutes(29)));
+        assert!(!state.in_cooldown("Veranda", "notify-1", 30, now + TimeDelta::minutes(30)));
+
+        // Other devices and other nodes have their own cooldown
+        assert!(!state.in_cooldown("LivingRoom", "notify-1", 30, now));
+        assert!(!state.in_cooldown("Veranda", "notify-2", 30, now));
+    }
+
+    #[test]
+    fn test_zero_cooldown_always_sends() {
+        let state = NotificationState::new();
+        let now = Utc::now();
+
+        state.record_sent("Veranda", "notify-1", now);
+        assert!(!state.in_cooldown("Veranda", "notify-1", 0, now));
+        assert!(!state.in_cooldown("Veranda", "notify-1", -5, now));
+    }
+
+    #[test]
+    fn test_huge_cooldown_is_capped() {
+        let state = NotificationState::new();
+        let now = Utc::now();
+
+        state.record_sent("Veranda", "notify-1", now);
+        assert!(state.in_cooldown("Veranda", "notify-1", i64::MAX, now + TimeDelta::days(365)));
+        assert!(!state.in_cooldown("Veranda", "notify-1", i64::MAX, now + TimeDelta::days(367)));
+    }
+}
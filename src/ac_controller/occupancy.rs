@@ -0,0 +1,192 @@
+//! Occupancy inference
+//!
+//! Raw PIR booleans flip with every detection and timeout, so profiles acting on them flap. The
+//! occupancy model turns the sensor events of each room (the room of a device) into a probability
+//! that it is occupied. A PIR detection or door sensor event raises the room's evidence to at least
+//! the event's weight, after which the evidence decays exponentially with `occupancy.half_life_minutes`.
+//! The probability combines the decayed evidence with the prior for the local hour
+//! (`occupancy.hourly_priors`) as independent signals. The evidence is stored with every event, so
+//! a restart continues the decay instead of starting from an empty house.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::{db::{self, DbPool}, types::OccupancyConfig};
+
+/// Global occupancy model
+static OCCUPANCY_MODEL: OnceLock<Arc<OccupancyModel>> = OnceLock::new();
+
+/// Sensor event that suggests a room is occupied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OccupancyEvent {
+    /// PIR detection
+    Motion,
+    /// Door opened or closed
+    Door,
+}
+
+impl OccupancyEvent {
+    /// Evidence the event gives that the room is occupied
+    fn weight(self, settings: &OccupancyConfig) -> f64 {
+        let weight = match self {
+            OccupancyEvent::Motion => settings.motion_weight,
+            OccupancyEvent::Door => settings.door_weight,
+        };
+        weight.clamp(0.0, 1.0)
+    }
+}
+
+/// Evidence of a room at its last event
+#[derive(Debug, Clone, Copy)]
+struct Evidence {
+    value: f64,
+    updated_at: DateTime<Utc>,
+}
+
+impl Evidence {
+    /// Evidence left at `now`, halved every `half_life_minutes`
+    fn decayed(&self, half_life_minutes: f64, now: DateTime<Utc>) -> f64 {
+        let elapsed_minutes = (now - self.updated_at).num_seconds().max(0) as f64 / 60.0;
+        if half_life_minutes <= 0.0 {
+            return if elapsed_minutes > 0.0 { 0.0 } else { self.value };
+        }
+        self.value * 0.5_f64.powf(elapsed_minutes / half_life_minutes)
+    }
+}
+
+/// Combine the evidence with the prior, both as independent chances the room is occupied
+fn combine(evidence: f64, prior: f64) -> f64 {
+    1.0 - (1.0 - evidence) * (1.0 - prior)
+}
+
+/// Occupancy evidence per room
+#[derive(Default)]
+pub struct OccupancyModel {
+    rooms: RwLock<HashMap<String, Evidence>>,
+}
+
+impl OccupancyModel {
+    /// Restore the stored evidence of every room
+    pub async fn load(&self, pool: &DbPool) -> Result<(), sqlx::Error> {
+        let stored = db::occupancy_state::get_all(pool).await?;
+        let mut rooms = self.rooms.write().unwrap();
+        for (room, value, updated_at) in stored {
+            if let Some(updated_at) = DateTime::from_timestamp(updated_at, 0) {
+                rooms.insert(room, Evidence { value, updated_at });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an event to the evidence of a room
+    /// Returns the evidence right after the event
+    pub fn record(&self, room: &str, event: OccupancyEvent, settings: &OccupancyConfig, now: DateTime<Utc>) -> f64 {
+        let mut rooms = self.rooms.write().unwrap();
+        let remaining = rooms
+            .get(room)
+            .map_or(0.0, |evidence| evidence.decayed(settings.half_life_minutes, now));
+        let value = remaining.max(event.weight(settings));
+        rooms.insert(room.to_string(), Evidence { value, updated_at: now });
+        value
+    }
+
+    /// Add an event to the evidence of a room and store it
+    pub async fn record_and_store(
+        &self,
+        pool: &DbPool,
+        room: &str,
+        event: OccupancyEvent,
+        settings: &OccupancyConfig,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        let value = self.record(room, event, settings, now);
+        db::occupancy_state::upsert(pool, room, value, now.timestamp()).await
+    }
+
+    /// Decayed evidence of a room at `now`, 0 for rooms without events
+    pub fn evidence(&self, room: &str, settings: &OccupancyConfig, now: DateTime<Utc>) -> f64 {
+        self.rooms
+            .read()
+            .unwrap()
+            .get(room)
+            .map_or(0.0, |evidence| evidence.decayed(settings.half_life_minutes, now))
+    }
+
+    /// Probability (0-1) that a room is occupied at `now`, `local_hour` selects the prior
+    pub fn probability(&self, room: &str, settings: &OccupancyConfig, local_hour: u32, now: DateTime<Utc>) -> f64 {
+        combine(self.evidence(room, settings, now), settings.prior(local_hour))
+    }
+}
+
+/// Get the global occupancy model
+pub fn get_occupancy_model() -> &'static Arc<OccupancyModel> {
+    OCCUPANCY_MODEL.get_or_init(Arc::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn settings() -> OccupancyConfig {
+        OccupancyConfig {
+            half_life_minutes: 10.0,
+            motion_weight: 1.0,
+            door_weight: 0.5,
+            hourly_priors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_evidence_decays_and_events_raise_it() {
+        let model = OccupancyModel::default();
+        let settings = settings();
+        let start = Utc::now();
+
+        assert_eq!(model.evidence("LivingRoom", &settings, start), 0.0);
+        assert_eq!(model.record("LivingRoom", OccupancyEvent::Motion, &settings, start), 1.0);
+        let after_half_life = model.evidence("LivingRoom", &settings, start + Duration::minutes(10));
+        assert!((after_half_life - 0.5).abs() < 1e-9);
+
+        // A weaker event doesn't lower the evidence that is left
+        let value = model.record("LivingRoom", OccupancyEvent::Door, &settings, start + Duration::minutes(5));
+        assert!((value - 0.5_f64.sqrt()).abs() < 1e-9);
+        // But raises it once the earlier evidence decayed below its weight
+        let value = model.record("LivingRoom", OccupancyEvent::Door, &settings, start + Duration::minutes(60));
+        assert_eq!(value, 0.5);
+        assert_eq!(model.evidence("Veranda", &settings, start), 0.0, "evidence is per room");
+    }
+
+    #[test]
+    fn test_probability_includes_hourly_prior() {
+        let model = OccupancyModel::default();
+        let mut settings = settings();
+        settings.hourly_priors = vec![0.0; 24];
+        settings.hourly_priors[22] = 0.8;
+        let now = Utc::now();
+
+        assert_eq!(model.probability("LivingRoom", &settings, 12, now), 0.0);
+        assert!((model.probability("LivingRoom", &settings, 22, now) - 0.8).abs() < 1e-9);
+
+        model.record("LivingRoom", OccupancyEvent::Door, &settings, now);
+        assert!((model.probability("LivingRoom", &settings, 12, now) - 0.5).abs() < 1e-9);
+        assert!((model.probability("LivingRoom", &settings, 22, now) - 0.9).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_evidence_survives_reload() {
+        let pool = db::connect_in_memory().await;
+        let settings = settings();
+        let model = OccupancyModel::default();
+        model
+            .record_and_store(&pool, "LivingRoom", OccupancyEvent::Motion, &settings)
+            .await
+            .unwrap();
+
+        let reloaded = OccupancyModel::default();
+        reloaded.load(&pool).await.unwrap();
+        let evidence = reloaded.evidence("LivingRoom", &settings, Utc::now());
+        assert!(evidence > 0.99, "restored evidence was {}", evidence);
+    }
+}
@@ -2,18 +2,25 @@ use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock, RwLock};
 
+/// Most sensors tracked before detections of sensors that aren't in `pir_sensors` are refused
+pub const MAX_TRACKED_SENSORS: usize = 32;
+
 /// Global PIR state manager
 static PIR_STATE: OnceLock<Arc<PirState>> = OnceLock::new();
 
 /// Thread-safe PIR detection state
+/// Detections are tracked per device and per named sensor, see `pir_sensors`
+#[derive(Default)]
 pub struct PirState {
     last_detection: RwLock<HashMap<String, DateTime<Utc>>>,
+    sensor_detection: RwLock<HashMap<String, DateTime<Utc>>>,
 }
 
 impl PirState {
     fn new() -> Self {
         Self {
             last_detection: RwLock::new(HashMap::new()),
+            sensor_detection: RwLock::new(HashMap::new()),
         }
     }
 
@@ -52,6 +59,49 @@ impl PirState {
         let map = self.last_detection.read().unwrap();
         map.get(device).copied()
     }
+
+    /// Forget the last detection of a device, lifting its suppression immediately
+    /// Returns false if the device had no recorded detection
+    pub fn clear_detection(&self, device: &str) -> bool {
+        let mut map = self.last_detection.write().unwrap();
+        let cleared = map.remove(device).is_some();
+        if cleared {
+            log::info!("PIR detection cleared for device: {}", device);
+        }
+        cleared
+    }
+
+    /// Record a detection of a named sensor
+    /// Sensors that aren't configured are registered on their first detection while fewer than
+    /// `MAX_TRACKED_SENSORS` are tracked. Returns None if the detection was refused, otherwise
+    /// whether the sensor wasn't known yet and is registered by this detection
+    pub fn record_sensor_detection(&self, sensor_id: &str, is_configured: bool) -> Option<bool> {
+        let mut map = self.sensor_detection.write().unwrap();
+        if !is_configured && !map.contains_key(sensor_id) && map.len() >= MAX_TRACKED_SENSORS {
+            return None;
+        }
+        let is_new = map.insert(sensor_id.to_string(), Utc::now()).is_none();
+        log::info!("PIR detection recorded for sensor: {}", sensor_id);
+        Some(is_new)
+    }
+
+    /// Get the last detection time of a named sensor
+    pub fn get_sensor_last_detection(&self, sensor_id: &str) -> Option<DateTime<Utc>> {
+        let map = self.sensor_detection.read().unwrap();
+        map.get(sensor_id).copied()
+    }
+
+    /// Get the most recent detection of any of the sensors, e.g. those of a zone
+    pub fn get_zone_last_detection(&self, sensor_ids: &[String]) -> Option<DateTime<Utc>> {
+        let map = self.sensor_detection.read().unwrap();
+        sensor_ids.iter().filter_map(|sensor_id| map.get(sensor_id)).max().copied()
+    }
+
+    /// IDs of all sensors that reported a detection since startup
+    pub fn get_detected_sensors(&self) -> Vec<String> {
+        let map = self.sensor_detection.read().unwrap();
+        map.keys().cloned().collect()
+    }
 }
 
 /// Get the global PIR state instance
@@ -126,4 +176,48 @@ mod tests {
         let diff = now.signed_duration_since(detection_time.unwrap());
         assert!(diff.num_seconds() < 10, "Detection time should be recent, got {} seconds ago", diff.num_seconds());
     }
+
+    #[test]
+    fn test_sensor_and_zone_detection() {
+        let state = PirState::new();
+        let zone = vec!["hall".to_string(), "kitchen".to_string()];
+        assert!(state.get_zone_last_detection(&zone).is_none());
+
+        assert_eq!(state.record_sensor_detection("hall", false), Some(true), "registered on the first detection");
+        assert_eq!(state.record_sensor_detection("hall", false), Some(false));
+        assert_eq!(state.record_sensor_detection("attic", true), Some(true));
+
+        assert_eq!(state.get_zone_last_detection(&zone), state.get_sensor_last_detection("hall"));
+        assert!(state.get_last_detection("hall").is_none(), "sensors are not devices");
+        let mut sensors = state.get_detected_sensors();
+        sensors.sort();
+        assert_eq!(sensors, vec!["attic", "hall"]);
+    }
+
+    #[test]
+    fn test_unconfigured_sensors_are_capped() {
+        let state = PirState::new();
+        for i in 0..MAX_TRACKED_SENSORS {
+            assert_eq!(state.record_sensor_detection(&format!("sensor-{}", i), false), Some(true));
+        }
+        assert_eq!(state.record_sensor_detection("one-too-many", false), None);
+        assert_eq!(state.record_sensor_detection("sensor-0", false), Some(false), "known sensors still report");
+        assert_eq!(state.record_sensor_detection("hall", true), Some(true), "configured sensors are always accepted");
+        assert!(state.get_sensor_last_detection("one-too-many").is_none());
+    }
+
+    #[test]
+    fn test_clear_detection() {
+        let state = PirState::new();
+
+        state.record_detection("Device1");
+        state.record_detection("Device2");
+
+        assert!(state.clear_detection("Device1"));
+        assert!(!state.has_recent_detection("Device1", 5));
+        assert!(state.has_recent_detection("Device2", 5));
+
+        // Nothing left to clear
+        assert!(!state.clear_detection("Device1"));
+    }
 }
@@ -0,0 +1,136 @@
+//! Total AC power budget shared by all devices
+//!
+//! Several units running in powerful mode at the same time can trip the import limit. With
+//! `power_budget_watt` set, the draw of every device is estimated from its tracked state and
+//! commands that would push the combined draw over the budget are limited: powerful mode is
+//! dropped first, otherwise the device stays off. Each cycle evaluates the devices by priority,
+//! so higher priority devices claim the budget first.
+
+use crate::{
+    nodes::PowerBudgetData,
+    types::{AcControllerEndpointProperties, Config},
+};
+
+use super::{
+    ac_executor::{AcState, AcStateManager},
+    AcDevice,
+};
+
+/// Estimated draw of a device in a state, in watts
+fn estimated_draw(props: &AcControllerEndpointProperties, state: &AcState) -> i64 {
+    match (state.is_on, state.powerful_mode) {
+        (false, _) => 0,
+        (true, false) => i64::from(props.power_watt),
+        (true, true) => i64::from(props.powerful_power_watt),
+    }
+}
+
+/// Share of the power budget available to a device, from the tracked state of the other devices
+pub fn get_data(config: &Config, states: &AcStateManager, device_name: &str) -> PowerBudgetData {
+    let other_devices_watt = config
+        .ac_controller_endpoints
+        .iter()
+        .filter(|(name, _)| name.as_str() != device_name)
+        .map(|(name, props)| estimated_draw(props, &states.get_state(name)))
+        .sum();
+    let (device_watt, device_powerful_watt) = config
+        .ac_controller_endpoints
+        .get(device_name)
+        .map_or((0, 0), |props| (i64::from(props.power_watt), i64::from(props.powerful_power_watt)));
+
+    PowerBudgetData {
+        is_enabled: config.power_budget_watt > 0,
+        budget_watt: i64::from(config.power_budget_watt),
+        other_devices_watt,
+        device_watt,
+        device_powerful_watt,
+    }
+}
+
+/// Limit a desired state to the budget: without powerful mode if that fits, otherwise off
+/// Returns None if the state fits as is
+pub fn limit_to_budget(budget: &PowerBudgetData, desired_state: &AcState) -> Option<AcState> {
+    if !desired_state.is_on {
+        return None;
+    }
+    if desired_state.powerful_mode {
+        if budget.fits(budget.device_powerful_watt) {
+            return None;
+        }
+        if budget.fits(budget.device_watt) {
+            return Some(AcState {
+                powerful_mode: false,
+                ..desired_state.clone()
+            });
+        }
+    } else if budget.fits(budget.device_watt) {
+        return None;
+    }
+    Some(AcState::new_off())
+}
+
+/// Devices in the order a cycle evaluates them: highest priority first, then by name
+pub fn by_priority<'a>(config: &Config, devices: &'a [AcDevice]) -> Vec<&'a AcDevice> {
+    let priority = |device: &AcDevice| {
+        config
+            .ac_controller_endpoints
+            .get(device.as_str())
+            .map_or(0, |props| props.priority)
+    };
+    let mut ordered: Vec<&AcDevice> = devices.iter().collect();
+    // Stable sort, the devices are already sorted by name
+    ordered.sort_by_key(|device| std::cmp::Reverse(priority(device)));
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ac_controller::DeviceRegistry, types::Celsius};
+
+    #[test]
+    fn test_get_data_counts_other_devices() {
+        let config = Config {
+            power_budget_watt: 2500,
+            ..Default::default()
+        };
+        let states = AcStateManager::default();
+        states.set_state("Veranda", AcState::new_on(1, 0, Celsius(22.0), 0, true));
+        states.set_state("LivingRoom", AcState::new_on(1, 0, Celsius(22.0), 0, false));
+
+        let data = get_data(&config, &states, "LivingRoom");
+        assert!(data.is_enabled);
+        assert_eq!(data.other_devices_watt, 1500);
+        assert_eq!(data.remaining_watt(), 1000);
+        assert_eq!(get_data(&config, &states, "Veranda").other_devices_watt, 1000);
+    }
+
+    #[test]
+    fn test_limit_to_budget() {
+        let powerful = AcState::new_on(1, 0, Celsius(22.0), 0, true);
+        let budget = |other_devices_watt| PowerBudgetData {
+            is_enabled: true,
+            budget_watt: 2500,
+            other_devices_watt,
+            device_watt: 1000,
+            device_powerful_watt: 1500,
+        };
+
+        assert_eq!(limit_to_budget(&budget(1000), &powerful), None);
+        let limited = limit_to_budget(&budget(1500), &powerful).unwrap();
+        assert!(limited.is_on && !limited.powerful_mode);
+        assert_eq!(limit_to_budget(&budget(2000), &powerful), Some(AcState::new_off()));
+        assert_eq!(limit_to_budget(&budget(2000), &AcState::new_off()), None);
+        assert_eq!(limit_to_budget(&PowerBudgetData::default(), &powerful), None, "no budget configured");
+    }
+
+    #[test]
+    fn test_devices_by_priority() {
+        let mut config = Config::default();
+        config.ac_controller_endpoints.get_mut("Veranda").unwrap().priority = 1;
+        let registry = DeviceRegistry::from_config(&config);
+
+        let names: Vec<&str> = by_priority(&config, registry.all()).iter().map(|d| d.as_str()).collect();
+        assert_eq!(names, vec!["Veranda", "LivingRoom"]);
+    }
+}
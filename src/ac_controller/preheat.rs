@@ -0,0 +1,138 @@
+//! Night-time preheat ahead of cold snaps
+//!
+//! The colder it gets outside, the less heat a heat pump delivers per watt. When extreme cold is
+//! forecast, running the heat pump for a while at night, while it is still mild enough to run
+//! efficiently, stores heat in the building before the cold arrives. While a preheat runs the node
+//! executor skips the device. Preheat state is kept in memory and does not survive restarts.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::types::ColdSnapPreheatConfig;
+
+/// Global cold snap preheat state
+static PREHEAT_STATE: OnceLock<Arc<PreheatState>> = OnceLock::new();
+
+/// A preheat started on a device
+#[derive(Debug, Clone, Copy)]
+struct Preheat {
+    started: DateTime<Utc>,
+    until: DateTime<Utc>,
+}
+
+/// Thread-safe preheat state per device
+#[derive(Default)]
+pub struct PreheatState {
+    preheats: RwLock<HashMap<String, Preheat>>,
+}
+
+impl PreheatState {
+    /// Record that a device started preheating at `now`
+    pub fn start(&self, device: &str, now: DateTime<Utc>, config: &ColdSnapPreheatConfig) {
+        let until = now + chrono::Duration::minutes(i64::from(config.duration_minutes));
+        let mut preheats = self.preheats.write().unwrap();
+        preheats.insert(device.to_string(), Preheat { started: now, until });
+        log::info!("Cold snap preheat started for device '{}' until {}", device, until);
+    }
+
+    /// End time of the preheat a device is running, None if it isn't preheating
+    pub fn active_until(&self, device: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let preheats = self.preheats.read().unwrap();
+        preheats.get(device).map(|p| p.until).filter(|until| now < *until)
+    }
+
+    /// Whether a device should start preheating with the current and forecast outdoor temperatures
+    pub fn should_start(
+        &self,
+        config: &ColdSnapPreheatConfig,
+        device: &str,
+        outdoor_temperature: f64,
+        forecast_min_next_12h_temp: f64,
+        local_hour: u32,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if !config.enabled
+            || forecast_min_next_12h_temp >= config.forecast_below_c
+            || outdoor_temperature < config.min_outdoor_c
+            || !is_night(config, local_hour)
+        {
+            return false;
+        }
+
+        // A cooldown too long to represent never ends
+        let preheats = self.preheats.read().unwrap();
+        let cooldown = chrono::TimeDelta::try_hours(i64::from(config.cooldown_hours));
+        preheats.get(device).is_none_or(|p| {
+            let cooled_down = cooldown.and_then(|c| p.started.checked_add_signed(c)).is_some_and(|end| now >= end);
+            cooled_down && now >= p.until
+        })
+    }
+}
+
+/// Whether a local hour falls in the configured night, which may wrap past midnight
+fn is_night(config: &ColdSnapPreheatConfig, hour: u32) -> bool {
+    if config.night_start_hour <= config.night_end_hour {
+        hour >= config.night_start_hour && hour < config.night_end_hour
+    } else {
+        hour >= config.night_start_hour || hour < config.night_end_hour
+    }
+}
+
+/// Get the global preheat state
+pub fn get_preheat_state() -> &'static Arc<PreheatState> {
+    PREHEAT_STATE.get_or_init(|| Arc::new(PreheatState::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ColdSnapPreheatConfig {
+        ColdSnapPreheatConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_should_start_thresholds() {
+        let state = PreheatState::default();
+        let now = Utc::now();
+        let config = config();
+
+        assert!(state.should_start(&config, "LivingRoom", 1.0, -12.0, 23, now));
+        assert!(state.should_start(&config, "LivingRoom", 1.0, -12.0, 3, now), "the night wraps past midnight");
+        assert!(!state.should_start(&config, "LivingRoom", 1.0, -12.0, 14, now), "only at night");
+        assert!(!state.should_start(&config, "LivingRoom", 1.0, -5.0, 23, now), "no cold snap forecast");
+        assert!(!state.should_start(&config, "LivingRoom", -6.0, -12.0, 23, now), "already too cold to run efficiently");
+        assert!(!state.should_start(&ColdSnapPreheatConfig::default(), "LivingRoom", 1.0, -12.0, 23, now));
+    }
+
+    #[test]
+    fn test_preheat_duration_and_cooldown() {
+        let state = PreheatState::default();
+        let now = Utc::now();
+        let config = config();
+
+        state.start("LivingRoom", now, &config);
+        assert!(state.active_until("LivingRoom", now).is_some());
+        assert!(state.active_until("Veranda", now).is_none());
+        assert!(!state.should_start(&config, "LivingRoom", 1.0, -12.0, 23, now));
+
+        let after_preheat = now + chrono::Duration::minutes(90);
+        assert!(state.active_until("LivingRoom", after_preheat).is_none());
+        assert!(!state.should_start(&config, "LivingRoom", 1.0, -12.0, 23, after_preheat), "cooldown");
+        assert!(state.should_start(&config, "LivingRoom", 1.0, -12.0, 23, now + chrono::Duration::hours(12)));
+    }
+
+    #[test]
+    fn test_huge_cooldown_does_not_panic() {
+        let state = PreheatState::default();
+        let now = Utc::now();
+        let config = ColdSnapPreheatConfig { cooldown_hours: u32::MAX, ..config() };
+
+        state.start("LivingRoom", now, &config);
+        assert!(!state.should_start(&config, "LivingRoom", 1.0, -12.0, 23, now + chrono::Duration::days(365)));
+    }
+}
@@ -0,0 +1,77 @@
+//! Heating/cooling season classification
+//!
+//! The season is classified from the average outdoor temperature in `weather_history` over the
+//! last `season_window_days` days, so a few warm days in winter don't switch a profile to cooling.
+//! Until enough history is recorded (e.g. right after installing) the 24h forecast average is used.
+
+use chrono::Utc;
+
+use crate::{
+    db,
+    types::{Celsius, Config, Season, db_types::WeatherHour},
+};
+
+/// Fewer recorded hours than this in the window fall back to the forecast average
+const MIN_HISTORY_HOURS: usize = 24;
+
+/// Average outdoor temperature of the recorded hours, None if too few hours are recorded
+fn rolling_average(hours: &[WeatherHour]) -> Option<f64> {
+    if hours.len() < MIN_HISTORY_HOURS {
+        return None;
+    }
+    Some(hours.iter().map(|h| h.outdoor_temperature).sum::<f64>() / hours.len() as f64)
+}
+
+/// Classify the current season
+/// `forecast_average` (the average outdoor temperature of the next 24 hours) is used when the
+/// weather history is too short or can't be read
+pub async fn current_season(config: &Config, forecast_average: f64) -> Season {
+    let now = Utc::now().timestamp();
+    let start = now - i64::from(config.season_window_days) * 24 * 60 * 60;
+
+    let average = match db::weather_history::get_range(start, now).await {
+        Ok(hours) => rolling_average(&hours),
+        Err(e) => {
+            log::warn!("Failed to read weather history for season detection: {}", e);
+            None
+        }
+    };
+    let average = average.unwrap_or_else(|| {
+        log::debug!("Not enough weather history for season detection, using the 24h forecast average");
+        forecast_average
+    });
+
+    Season::classify(
+        Celsius(average),
+        Celsius(config.heating_season_below_c),
+        Celsius(config.cooling_season_above_c),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hours(temperatures: &[f64]) -> Vec<WeatherHour> {
+        temperatures
+            .iter()
+            .enumerate()
+            .map(|(i, t)| WeatherHour {
+                hour_timestamp: i as i64 * 3600,
+                outdoor_temperature: *t,
+                source: "live".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_average() {
+        let mut temperatures = vec![10.0; MIN_HISTORY_HOURS];
+        temperatures.extend(vec![20.0; MIN_HISTORY_HOURS]);
+        assert_eq!(rolling_average(&hours(&temperatures)), Some(15.0));
+
+        // Too little history to classify from
+        assert_eq!(rolling_average(&hours(&[25.0; MIN_HISTORY_HOURS - 1])), None);
+        assert_eq!(rolling_average(&[]), None);
+    }
+}
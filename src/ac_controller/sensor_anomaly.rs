@@ -0,0 +1,171 @@
+//! Indoor temperature rate-of-change anomaly detection
+//!
+//! Room temperature can't physically change by several degrees a minute. A reading that
+//! changes faster than `sensor_max_rate_c_per_minute` compared to the last plausible one
+//! (sensor fault, door open next to the unit in winter) is flagged as an anomaly. The
+//! executor then keeps using the last plausible reading instead of acting on it.
+//! Since the allowed change grows with the time since the last plausible reading,
+//! a sensor that really settled at a new temperature is accepted again after a while.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Intervals shorter than this are rounded up, so cached reads seconds apart don't
+/// turn normal sensor noise into huge rates
+const MIN_RATE_INTERVAL_MINUTES: f64 = 1.0;
+
+/// Global sensor anomaly state
+static SENSOR_ANOMALY_STATE: OnceLock<SensorAnomalyState> = OnceLock::new();
+
+/// Last plausible reading and anomaly flag for a device
+#[derive(Debug, Clone, Copy)]
+struct DeviceSensorState {
+    last_plausible_temperature: f64,
+    last_plausible_at: DateTime<Utc>,
+    is_anomalous: bool,
+}
+
+/// Tracks indoor temperature readings per device
+pub struct SensorAnomalyState {
+    devices: RwLock<HashMap<String, DeviceSensorState>>,
+}
+
+impl SensorAnomalyState {
+    fn new() -> Self {
+        Self {
+            devices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Check a new reading and return the temperature the executor should use:
+    /// the reading itself if plausible, otherwise the last plausible reading
+    pub fn filter_reading(&self, device: &str, temperature: f64, max_rate_c_per_minute: f64) -> f64 {
+        self.filter_reading_at(device, temperature, max_rate_c_per_minute, Utc::now())
+    }
+
+    fn filter_reading_at(
+        &self,
+        device: &str,
+        temperature: f64,
+        max_rate_c_per_minute: f64,
+        now: DateTime<Utc>,
+    ) -> f64 {
+        let mut devices = self.devices.write().unwrap();
+
+        let Some(state) = devices.get_mut(device) else {
+            devices.insert(device.to_string(), DeviceSensorState {
+                last_plausible_temperature: temperature,
+                last_plausible_at: now,
+                is_anomalous: false,
+            });
+            return temperature;
+        };
+
+        let minutes = (now - state.last_plausible_at).num_milliseconds() as f64 / 60_000.0;
+        let rate = (temperature - state.last_plausible_temperature).abs() / minutes.max(MIN_RATE_INTERVAL_MINUTES);
+
+        if rate > max_rate_c_per_minute {
+            if !state.is_anomalous {
+                log::warn!(
+                    "Sensor anomaly on {}: temperature changed from {:.1}°C to {:.1}°C ({:.2}°C/min, max {:.2}°C/min). Using last plausible reading.",
+                    device, state.last_plausible_temperature, temperature, rate, max_rate_c_per_minute
+                );
+            }
+            state.is_anomalous = true;
+            return state.last_plausible_temperature;
+        }
+
+        if state.is_anomalous {
+            log::info!("Sensor readings on {} are plausible again ({:.1}°C)", device, temperature);
+        }
+        *state = DeviceSensorState {
+            last_plausible_temperature: temperature,
+            last_plausible_at: now,
+            is_anomalous: false,
+        };
+        temperature
+    }
+
+    /// Check if the latest reading for a device was flagged as an anomaly
+    pub fn is_anomalous(&self, device: &str) -> bool {
+        let devices = self.devices.read().unwrap();
+        devices.get(device).is_some_and(|state| state.is_anomalous)
+    }
+}
+
+/// Get the global sensor anomaly state instance
+pub fn get_sensor_anomaly_state() -> &'static SensorAnomalyState {
+    SENSOR_ANOMALY_STATE.get_or_init(SensorAnomalyState::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const MAX_RATE: f64 = 0.5;
+
+    #[test]
+    fn test_first_reading_is_accepted() {
+        let state = SensorAnomalyState::new();
+        assert_eq!(state.filter_reading_at("LivingRoom", 21.0, MAX_RATE, Utc::now()), 21.0);
+        assert!(!state.is_anomalous("LivingRoom"));
+        assert!(!state.is_anomalous("Veranda"));
+    }
+
+    #[test]
+    fn test_gradual_change_is_accepted() {
+        let state = SensorAnomalyState::new();
+        let start = Utc::now();
+        state.filter_reading_at("LivingRoom", 21.0, MAX_RATE, start);
+
+        // 1°C over 5 minutes = 0.2°C/min
+        let result = state.filter_reading_at("LivingRoom", 22.0, MAX_RATE, start + Duration::minutes(5));
+        assert_eq!(result, 22.0);
+        assert!(!state.is_anomalous("LivingRoom"));
+    }
+
+    #[test]
+    fn test_spike_uses_last_plausible_reading() {
+        let state = SensorAnomalyState::new();
+        let start = Utc::now();
+        state.filter_reading_at("Veranda", 21.0, MAX_RATE, start);
+
+        // 6°C in 1 minute
+        let result = state.filter_reading_at("Veranda", 27.0, MAX_RATE, start + Duration::minutes(1));
+        assert_eq!(result, 21.0);
+        assert!(state.is_anomalous("Veranda"));
+
+        // Spike gone
+        let result = state.filter_reading_at("Veranda", 21.2, MAX_RATE, start + Duration::minutes(2));
+        assert_eq!(result, 21.2);
+        assert!(!state.is_anomalous("Veranda"));
+    }
+
+    #[test]
+    fn test_quick_successive_readings_use_minimum_interval() {
+        let state = SensorAnomalyState::new();
+        let start = Utc::now();
+        state.filter_reading_at("LivingRoom", 21.0, MAX_RATE, start);
+
+        // 0.3°C within a few seconds is sensor noise, not 6°C/min
+        let result = state.filter_reading_at("LivingRoom", 21.3, MAX_RATE, start + Duration::seconds(3));
+        assert_eq!(result, 21.3);
+        assert!(!state.is_anomalous("LivingRoom"));
+    }
+
+    #[test]
+    fn test_sustained_step_is_accepted_eventually() {
+        let state = SensorAnomalyState::new();
+        let start = Utc::now();
+        state.filter_reading_at("LivingRoom", 21.0, MAX_RATE, start);
+
+        assert_eq!(state.filter_reading_at("LivingRoom", 24.0, MAX_RATE, start + Duration::minutes(1)), 21.0);
+        assert!(state.is_anomalous("LivingRoom"));
+
+        // 3°C over 6 minutes since the last plausible reading = 0.5°C/min
+        assert_eq!(state.filter_reading_at("LivingRoom", 24.0, MAX_RATE, start + Duration::minutes(6)), 24.0);
+        assert!(!state.is_anomalous("LivingRoom"));
+    }
+}
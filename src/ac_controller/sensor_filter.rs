@@ -0,0 +1,125 @@
+//! Median filtering of indoor temperature readings
+//!
+//! Single-sample spikes (seen on the Veranda unit) used to reach the profile directly and
+//! trigger needless Powerful bursts. Each evaluation takes the median of the last
+//! `sensor_median_window` readings for the device instead. Both the raw and the filtered
+//! value of the latest reading are kept for the dashboard.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{OnceLock, RwLock};
+
+/// Global sensor filter state
+static SENSOR_FILTER_STATE: OnceLock<SensorFilterState> = OnceLock::new();
+
+/// Latest raw reading and the filtered value used for execution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilteredReading {
+    pub raw: f64,
+    pub filtered: f64,
+}
+
+#[derive(Debug, Default)]
+struct DeviceReadings {
+    samples: VecDeque<f64>,
+    latest: Option<FilteredReading>,
+}
+
+/// Tracks recent indoor temperature readings per device
+pub struct SensorFilterState {
+    devices: RwLock<HashMap<String, DeviceReadings>>,
+}
+
+/// Median of a non-empty set of samples
+fn median(samples: &VecDeque<f64>) -> f64 {
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+impl SensorFilterState {
+    fn new() -> Self {
+        Self {
+            devices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Add a raw reading and return the median of the last `window` readings
+    /// A window of 0 or 1 disables filtering
+    pub fn filter_reading(&self, device: &str, raw: f64, window: usize) -> f64 {
+        let mut devices = self.devices.write().unwrap();
+        let readings = devices.entry(device.to_string()).or_default();
+
+        readings.samples.push_back(raw);
+        while readings.samples.len() > window.max(1) {
+            readings.samples.pop_front();
+        }
+
+        let filtered = median(&readings.samples);
+        if filtered != raw {
+            log::debug!(
+                "Filtered {} sensor temperature {:.1}°C to {:.1}°C (median of {} readings)",
+                device, raw, filtered, readings.samples.len()
+            );
+        }
+        readings.latest = Some(FilteredReading { raw, filtered });
+        filtered
+    }
+
+    /// Get the latest raw and filtered reading for a device
+    pub fn get_latest(&self, device: &str) -> Option<FilteredReading> {
+        let devices = self.devices.read().unwrap();
+        devices.get(device).and_then(|readings| readings.latest)
+    }
+}
+
+/// Get the global sensor filter state instance
+pub fn get_sensor_filter_state() -> &'static SensorFilterState {
+    SENSOR_FILTER_STATE.get_or_init(SensorFilterState::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_spike_is_filtered() {
+        let state = SensorFilterState::new();
+        assert_eq!(state.filter_reading("Veranda", 21.0, 3), 21.0);
+        assert_eq!(state.filter_reading("Veranda", 22.0, 3), 21.5);
+        assert_eq!(state.filter_reading("Veranda", 29.0, 3), 22.0);
+        assert_eq!(state.filter_reading("Veranda", 21.5, 3), 22.0);
+
+        let latest = state.get_latest("Veranda").unwrap();
+        assert_eq!(latest, FilteredReading { raw: 21.5, filtered: 22.0 });
+    }
+
+    #[test]
+    fn test_sustained_change_passes_through() {
+        let state = SensorFilterState::new();
+        state.filter_reading("LivingRoom", 21.0, 3);
+        state.filter_reading("LivingRoom", 21.0, 3);
+        state.filter_reading("LivingRoom", 24.0, 3);
+        assert_eq!(state.filter_reading("LivingRoom", 24.0, 3), 24.0);
+    }
+
+    #[test]
+    fn test_window_of_one_disables_filtering() {
+        let state = SensorFilterState::new();
+        state.filter_reading("LivingRoom", 21.0, 1);
+        assert_eq!(state.filter_reading("LivingRoom", 29.0, 1), 29.0);
+        assert_eq!(state.filter_reading("LivingRoom", 22.0, 0), 22.0);
+    }
+
+    #[test]
+    fn test_devices_are_tracked_separately() {
+        let state = SensorFilterState::new();
+        state.filter_reading("LivingRoom", 21.0, 3);
+        assert_eq!(state.filter_reading("Veranda", 25.0, 3), 25.0);
+        assert!(state.get_latest("Bedroom").is_none());
+    }
+}
@@ -0,0 +1,92 @@
+//! Per-device setpoint offsets
+//!
+//! Local automations (e.g. a wall panel) can nudge a device warmer or cooler without editing its
+//! profile. The offset is added to the temperature of every action the profile selects until it
+//! is reset, and is stored in the settings table so it survives restarts.
+
+use crate::{db::{self, DbPool}, types::Celsius};
+
+use super::{ac_executor::AcState, node_executor, AcDevice};
+
+/// Largest offset in °C in either direction, so repeated nudges can't run away
+pub const MAX_SETPOINT_OFFSET_C: f64 = 3.0;
+
+fn setting_key(device_name: &str) -> String {
+    format!("setpoint_offset_{}", device_name)
+}
+
+/// Limit an offset to ±MAX_SETPOINT_OFFSET_C
+fn clamp_offset(offset: f64) -> f64 {
+    offset.clamp(-MAX_SETPOINT_OFFSET_C, MAX_SETPOINT_OFFSET_C)
+}
+
+/// Current offset of a device, 0 if none is set or it can't be read
+pub async fn get(pool: &DbPool, device_name: &str) -> f64 {
+    match db::settings::get(pool, &setting_key(device_name)).await {
+        Ok(value) => value.and_then(|v| v.parse().ok()).unwrap_or(0.0),
+        Err(e) => {
+            log::warn!("Failed to read setpoint offset for '{}': {}", device_name, e);
+            0.0
+        }
+    }
+}
+
+/// Add `delta` to the offset of a device and return the new offset
+pub async fn nudge(pool: &DbPool, device_name: &str, delta: f64) -> Result<f64, sqlx::Error> {
+    let offset = clamp_offset(get(pool, device_name).await + delta);
+    db::settings::set(pool, &setting_key(device_name), &offset.to_string()).await?;
+    log::info!("Setpoint offset of '{}' is now {:+}°C", device_name, offset);
+    Ok(offset)
+}
+
+/// Remove the offset of a device
+pub async fn reset(pool: &DbPool, device_name: &str) -> Result<(), sqlx::Error> {
+    db::settings::set(pool, &setting_key(device_name), "0").await?;
+    log::info!("Setpoint offset of '{}' reset", device_name);
+    Ok(())
+}
+
+/// Evaluate a device right away, so a changed offset doesn't wait for the next cycle
+pub fn apply_now(device: AcDevice) {
+    tokio::spawn(async move {
+        if let node_executor::NodeExecutionResult::Error(e) = node_executor::execute_nodeset_for_device(&device).await {
+            log::error!("Failed to execute nodeset for {} after setpoint change: {}", device.as_str(), e);
+        }
+    });
+}
+
+/// Add an offset to the target temperature of a desired state; off states are left untouched
+pub fn apply(mut state: AcState, offset: f64) -> AcState {
+    if state.is_on {
+        state.temperature = state.temperature.map(|t| Celsius(t.0 + offset));
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_offset() {
+        let state = apply(AcState::new_on(4, 0, Celsius(21.0), 1, false), 1.5);
+        assert_eq!(state.temperature, Some(Celsius(22.5)));
+        assert_eq!(apply(AcState::new_off(), 1.5), AcState::new_off());
+    }
+
+    #[tokio::test]
+    async fn test_nudge_and_reset() {
+        let pool = db::connect_in_memory().await;
+        assert_eq!(get(&pool, "LivingRoom").await, 0.0);
+
+        assert_eq!(nudge(&pool, "LivingRoom", 1.0).await.unwrap(), 1.0);
+        assert_eq!(nudge(&pool, "LivingRoom", -0.5).await.unwrap(), 0.5);
+        assert_eq!(get(&pool, "Veranda").await, 0.0, "offsets are per device");
+
+        // Repeated nudges stop at the limit
+        assert_eq!(nudge(&pool, "LivingRoom", 10.0).await.unwrap(), MAX_SETPOINT_OFFSET_C);
+
+        reset(&pool, "LivingRoom").await.unwrap();
+        assert_eq!(get(&pool, "LivingRoom").await, 0.0);
+    }
+}
@@ -0,0 +1,75 @@
+//! Shadow mode
+//!
+//! While shadow mode is on, nodesets are evaluated and recorded in the execution history as usual,
+//! but no commands are sent to the ACs. That makes it possible to run a new profile for a few days
+//! and review what it would have done before letting it control the hardware. The tracked AC state
+//! is left alone, so the Active Command node keeps reporting the last command that was really sent.
+//! The setting is stored in the database and survives restarts.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crate::db::{self, DbPool};
+
+/// Settings key of the stored shadow mode flag
+const SHADOW_MODE_KEY: &str = "shadow_mode";
+
+/// Global shadow mode state
+static SHADOW_MODE: OnceLock<Arc<ShadowMode>> = OnceLock::new();
+
+/// Whether AC commands are suppressed
+#[derive(Default)]
+pub struct ShadowMode {
+    enabled: AtomicBool,
+}
+
+impl ShadowMode {
+    /// Check if shadow mode is on
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Load the stored setting, shadow mode is off if it was never set
+    pub async fn load(&self, pool: &DbPool) -> Result<(), sqlx::Error> {
+        let enabled = db::settings::get(pool, SHADOW_MODE_KEY).await?.as_deref() == Some("true");
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if enabled {
+            log::warn!("Shadow mode is on, nodesets are evaluated but no AC commands are sent");
+        }
+        Ok(())
+    }
+
+    /// Turn shadow mode on or off and store the setting
+    pub async fn set_enabled(&self, pool: &DbPool, enabled: bool) -> Result<(), sqlx::Error> {
+        db::settings::set(pool, SHADOW_MODE_KEY, if enabled { "true" } else { "false" }).await?;
+        if self.enabled.swap(enabled, Ordering::SeqCst) != enabled {
+            log::info!("Shadow mode turned {}", if enabled { "on" } else { "off" });
+        }
+        Ok(())
+    }
+}
+
+/// Get the global shadow mode instance
+pub fn get_shadow_mode() -> &'static Arc<ShadowMode> {
+    SHADOW_MODE.get_or_init(Arc::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shadow_mode_survives_reload() {
+        let pool = db::connect_in_memory().await;
+        let shadow_mode = ShadowMode::default();
+        shadow_mode.load(&pool).await.unwrap();
+        assert!(!shadow_mode.is_enabled());
+
+        shadow_mode.set_enabled(&pool, true).await.unwrap();
+        assert!(shadow_mode.is_enabled());
+
+        let reloaded = ShadowMode::default();
+        reloaded.load(&pool).await.unwrap();
+        assert!(reloaded.is_enabled());
+    }
+}
@@ -0,0 +1,240 @@
+//! Consistency check between the dashboard, the tracked AC state and the action history
+//!
+//! The dashboard derives what it shows from the tracked `AcState`, and every command sent is
+//! recorded in the action history. When they disagree, e.g. the dashboard reports cooling while
+//! the last command turned the AC off, one of them was updated without the others. A periodic
+//! check compares them per device, logs the mismatch and publishes a `StateMismatch` live event.
+//! A command is sent and recorded before the tracked state is updated, so a mismatch is only
+//! reported once it is found by two checks in a row.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::{
+    ac_controller::ac_executor::{self, ac_mode_name, AcState, AC_MODE_COOL, AC_MODE_DRY, AC_MODE_FAN, AC_MODE_HEAT, AC_MODE_OFF},
+    config, db,
+    live_events::{self, LiveEvent},
+    types::db_types::AcAction,
+};
+
+/// Interval between consistency checks
+const RECONCILE_INTERVAL_SECS: u64 = 5 * 60;
+
+/// Temperatures closer than this are considered equal, the action history stores them as f32
+const TEMPERATURE_TOLERANCE: f64 = 0.05;
+
+/// Global reconciliation state
+static RECONCILIATION_STATE: OnceLock<ReconciliationState> = OnceLock::new();
+
+/// Mode of a tracked state as reported by the dashboard
+pub fn dashboard_mode(state: &AcState) -> Option<String> {
+    state.mode.map(|m| match m {
+        AC_MODE_OFF => "off".to_string(),
+        AC_MODE_COOL => "cool".to_string(),
+        AC_MODE_DRY => "dry".to_string(),
+        AC_MODE_FAN => "fan".to_string(),
+        AC_MODE_HEAT => "heat".to_string(),
+        _ => format!("mode_{}", m),
+    })
+}
+
+/// Describe how the dashboard, the tracked state and the last on/off command disagree
+/// Returns an empty list when they are consistent
+pub fn find_mismatches(state: &AcState, last_command: Option<&AcAction>) -> Vec<String> {
+    let mut mismatches = Vec::new();
+
+    let reported_mode = dashboard_mode(state);
+    match reported_mode.as_deref() {
+        Some("off") | None if state.is_on => {
+            mismatches.push("the dashboard reports no mode while the AC is tracked as on".to_string());
+        }
+        Some(mode) if mode != "off" && !state.is_on => {
+            mismatches.push(format!("the dashboard reports mode '{}' while the AC is tracked as off", mode));
+        }
+        _ => {}
+    }
+
+    let Some(command) = last_command else {
+        return mismatches;
+    };
+    let command_is_on = command.action_type == "on";
+    if command_is_on != state.is_on {
+        mismatches.push(format!(
+            "the last command turned the AC {} but it is tracked as {}",
+            on_off(command_is_on),
+            on_off(state.is_on)
+        ));
+        return mismatches;
+    }
+    if !command_is_on {
+        return mismatches;
+    }
+
+    if command.mode != state.mode {
+        mismatches.push(format!(
+            "the last command set mode {} but the tracked mode is {}",
+            mode_label(command.mode),
+            mode_label(state.mode)
+        ));
+    }
+    if let (Some(requested), Some(tracked)) = (command.request_temperature, state.temperature)
+        && (f64::from(requested) - tracked.0).abs() > TEMPERATURE_TOLERANCE
+    {
+        mismatches.push(format!(
+            "the last command set {:.1}°C but the tracked setpoint is {:.1}°C",
+            requested, tracked.0
+        ));
+    }
+    mismatches
+}
+
+fn on_off(is_on: bool) -> &'static str {
+    if is_on { "on" } else { "off" }
+}
+
+fn mode_label(mode: Option<i32>) -> String {
+    match mode {
+        Some(m) => ac_mode_name(m).map_or_else(|| m.to_string(), String::from),
+        None => "none".to_string(),
+    }
+}
+
+/// Mismatches found by the last check of each device
+#[derive(Debug, Clone, Default)]
+struct DeviceCheck {
+    mismatches: Vec<String>,
+    is_reported: bool,
+}
+
+/// Tracks the result of the last check per device
+pub struct ReconciliationState {
+    devices: RwLock<HashMap<String, DeviceCheck>>,
+}
+
+impl ReconciliationState {
+    fn new() -> Self {
+        Self {
+            devices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record the mismatches found for a device
+    /// Returns true if they should be reported: found twice in a row and not reported yet
+    pub fn record(&self, device: &str, mismatches: Vec<String>) -> bool {
+        let mut devices = self.devices.write().unwrap();
+        let previous = devices.remove(device).unwrap_or_default();
+
+        if mismatches.is_empty() {
+            if previous.is_reported {
+                log::info!("Tracked state of {} is consistent again", device);
+            }
+            return false;
+        }
+
+        let is_repeated = previous.mismatches == mismatches;
+        let should_report = is_repeated && !previous.is_reported;
+        devices.insert(device.to_string(), DeviceCheck {
+            mismatches,
+            is_reported: is_repeated,
+        });
+        should_report
+    }
+}
+
+/// Get the global reconciliation state
+pub fn get_reconciliation_state() -> &'static ReconciliationState {
+    RECONCILIATION_STATE.get_or_init(ReconciliationState::new)
+}
+
+/// Compare the state of every initialized device with its last command
+/// Devices that were never initialized are re-synced on their next cycle, so their state isn't checked
+async fn check_devices() {
+    let pool = db::get_pool().await;
+    let state_manager = ac_executor::get_state_manager();
+    for device_name in config::get_config().ac_controller_endpoints.keys() {
+        if !state_manager.is_device_initialized(device_name) {
+            continue;
+        }
+        let last_command = match db::ac_actions::get_last_command(pool, device_name).await {
+            Ok(command) => command,
+            Err(e) => {
+                log::warn!("Failed to get the last command of {}: {}", device_name, e);
+                continue;
+            }
+        };
+
+        let state = state_manager.get_state(device_name);
+        let mismatches = find_mismatches(&state, last_command.as_ref());
+        if get_reconciliation_state().record(device_name, mismatches.clone()) {
+            log::warn!("State mismatch on {}: {}", device_name, mismatches.join("; "));
+            live_events::get_live_events().publish(LiveEvent::state_mismatch(device_name, mismatches));
+        }
+    }
+}
+
+/// Periodically check the tracked state of all devices
+pub async fn reconciliation_loop() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(RECONCILE_INTERVAL_SECS)).await;
+        check_devices().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Celsius;
+
+    fn command(action_type: &str, mode: Option<i32>, temperature: Option<f32>) -> AcAction {
+        AcAction::new_for_insert(
+            "LivingRoom".to_string(),
+            action_type.to_string(),
+            mode,
+            Some(0),
+            temperature,
+            Some(0),
+            None,
+            None,
+            None,
+            None,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_find_mismatches() {
+        let heating = AcState::new_on(AC_MODE_HEAT, 0, Celsius(21.5), 0, false);
+        assert!(find_mismatches(&heating, Some(&command("on", Some(AC_MODE_HEAT), Some(21.5)))).is_empty());
+        assert!(find_mismatches(&AcState::new_off(), Some(&command("off", Some(AC_MODE_OFF), None))).is_empty());
+        assert!(find_mismatches(&AcState::new_off(), None).is_empty());
+
+        let mismatches = find_mismatches(&heating, Some(&command("on", Some(AC_MODE_COOL), Some(24.0))));
+        assert_eq!(mismatches.len(), 2, "mode and setpoint: {:?}", mismatches);
+
+        let mismatches = find_mismatches(&heating, Some(&command("off", Some(AC_MODE_OFF), None)));
+        assert_eq!(mismatches, vec!["the last command turned the AC off but it is tracked as on"]);
+
+        // Reports cooling while off
+        let cooling_while_off = AcState {
+            mode: Some(AC_MODE_COOL),
+            ..AcState::new_off()
+        };
+        let mismatches = find_mismatches(&cooling_while_off, Some(&command("off", Some(AC_MODE_OFF), None)));
+        assert_eq!(mismatches, vec!["the dashboard reports mode 'cool' while the AC is tracked as off"]);
+    }
+
+    #[test]
+    fn test_mismatch_is_reported_once_after_two_checks() {
+        let state = ReconciliationState::new();
+        let mismatch = vec!["the last command turned the AC off but it is tracked as on".to_string()];
+
+        assert!(!state.record("LivingRoom", mismatch.clone()), "may be a command in progress");
+        assert!(state.record("LivingRoom", mismatch.clone()));
+        assert!(!state.record("LivingRoom", mismatch.clone()), "already reported");
+
+        assert!(!state.record("LivingRoom", vec![]));
+        assert!(!state.record("LivingRoom", mismatch.clone()));
+        assert!(state.record("LivingRoom", mismatch), "reported again after it was resolved");
+    }
+}
@@ -0,0 +1,71 @@
+//! Per-device target temperatures
+//!
+//! The target is the temperature the controller is aiming for: the temperature of the action the
+//! profile selected plus the device's setpoint offset. The command temperature sent to the AC is
+//! the target rounded to `temperature_rounding_step`, so a target of 21.3°C may be sent as 21.5°C.
+//! UIs show the target, the command temperature stays what the hardware received.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::types::Celsius;
+
+use super::ac_executor::AcState;
+
+/// Global target temperature store
+static TARGET_TEMPERATURES: OnceLock<Arc<TargetTemperatures>> = OnceLock::new();
+
+/// Thread-safe target temperature of each device
+#[derive(Default)]
+pub struct TargetTemperatures {
+    targets: RwLock<HashMap<String, Celsius>>,
+}
+
+impl TargetTemperatures {
+    /// Record the target of a desired state before rounding; a device that is off has no target
+    pub fn update(&self, device: &str, desired_state: &AcState) {
+        let mut targets = self.targets.write().unwrap();
+        match desired_state.temperature.filter(|_| desired_state.is_on) {
+            Some(target) => targets.insert(device.to_string(), target),
+            None => targets.remove(device),
+        };
+    }
+
+    /// Current target of a device, None if it is off or has not been evaluated yet
+    pub fn get(&self, device: &str) -> Option<Celsius> {
+        self.targets.read().unwrap().get(device).copied()
+    }
+
+    /// Forget the target of a device, e.g. when it is turned off outside the nodeset
+    pub fn clear(&self, device: &str) {
+        self.targets.write().unwrap().remove(device);
+    }
+}
+
+/// Get the global target temperature store
+pub fn get_target_temperatures() -> &'static Arc<TargetTemperatures> {
+    TARGET_TEMPERATURES.get_or_init(|| Arc::new(TargetTemperatures::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_and_clear() {
+        let targets = TargetTemperatures::default();
+        assert_eq!(targets.get("LivingRoom"), None);
+
+        // The unrounded temperature is kept
+        targets.update("LivingRoom", &AcState::new_on(4, 0, Celsius(21.3), 1, false));
+        assert_eq!(targets.get("LivingRoom"), Some(Celsius(21.3)));
+        assert_eq!(targets.get("Veranda"), None, "targets are per device");
+
+        targets.update("LivingRoom", &AcState::new_off());
+        assert_eq!(targets.get("LivingRoom"), None);
+
+        targets.update("LivingRoom", &AcState::new_on(1, 0, Celsius(24.0), 0, false));
+        targets.clear("LivingRoom");
+        assert_eq!(targets.get("LivingRoom"), None);
+    }
+}
@@ -1,11 +1,23 @@
 use chrono::{Datelike, Local, Timelike};
 
+use crate::nodes::LocalTimeData;
+
 /// Simple estimate if user is home and awake based on time of day
 /// Can be replaced later with some phone presence detection or other methods
 pub fn is_user_home_and_awake() -> bool {
     is_user_home_time_based()
 }
 
+/// Current local time as provided to nodesets
+pub fn local_time_now() -> LocalTimeData {
+    let now = Local::now();
+    LocalTimeData {
+        hour: i64::from(now.hour()),
+        minute: i64::from(now.minute()),
+        weekday: i64::from(now.weekday().number_from_monday()),
+    }
+}
+
 /// Time-based logic for determining if user is home
 fn is_user_home_time_based() -> bool {
     let now = Local::now();
@@ -29,17 +41,41 @@ fn is_user_home_time_based() -> bool {
     }
 }
 
-/// Async version that checks database override first, then falls back to time-based logic
+/// Async version that checks away mode and the database override first, then presence detection,
+/// then falls back to time-based logic
 pub async fn is_user_home_and_awake_async() -> bool {
+    // Away mode wins over everything else
+    if crate::ac_controller::away_mode::get_away_mode().is_enabled() {
+        return false;
+    }
+
     // Check database override first
     if let Some(override_result) = check_user_home_override().await {
         return override_result;
     }
 
+    // Presence detection knows who is home once anybody has been reported
+    if let Some(people_home) = crate::presence::get_presence_state().people_home_count() {
+        return people_home > 0;
+    }
+
     // Fall back to time-based logic
     is_user_home_time_based()
 }
 
+/// Number of people home according to presence detection
+/// At least 1 while the user is considered home, e.g. without presence data or through the override
+/// Nobody is home while away mode is on
+pub fn people_home_count(is_user_home: bool) -> i64 {
+    if crate::ac_controller::away_mode::get_away_mode().is_enabled() {
+        return 0;
+    }
+    crate::presence::get_presence_state()
+        .people_home_count()
+        .unwrap_or(0)
+        .max(i64::from(is_user_home))
+}
+
 /// Check if there's an active user home override in the database
 /// Returns Some(true) if override is active and user is home
 /// Returns None if override expired or not set (use normal logic)
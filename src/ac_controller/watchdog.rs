@@ -0,0 +1,108 @@
+//! systemd readiness and watchdog notifications
+//!
+//! The service runs as `Type=notify`: systemd is told it is ready once the database is migrated.
+//! With `WatchdogSec=` set, the controller reports it is alive after every control cycle and, from a
+//! separate task, every half watchdog interval unless a cycle has been running for longer than every
+//! device timing out one after another. A cycle that hangs (e.g. on a blocked HTTP call) stops the
+//! reports and systemd restarts the service. Outside systemd the notifications are no-ops.
+
+use sd_notify::NotifyState;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::devices;
+
+/// Allowance on top of the device timeouts for loading nodesets and recording the cycle
+const CYCLE_SLACK: Duration = Duration::from_secs(60);
+
+static CYCLE_STARTED: OnceLock<Mutex<Option<Instant>>> = OnceLock::new();
+
+fn cycle_started_at() -> &'static Mutex<Option<Instant>> {
+    CYCLE_STARTED.get_or_init(|| Mutex::new(None))
+}
+
+fn notify(state: NotifyState) {
+    if let Err(e) = sd_notify::notify(false, &[state]) {
+        log::debug!("Failed to notify systemd: {}", e);
+    }
+}
+
+/// Tell systemd the service has started
+pub fn notify_ready() {
+    notify(NotifyState::Ready);
+}
+
+/// Mark the start of a control cycle
+pub fn cycle_started() {
+    *cycle_started_at().lock().unwrap() = Some(Instant::now());
+}
+
+/// Mark the end of a control cycle and report to the watchdog
+pub fn cycle_completed() {
+    *cycle_started_at().lock().unwrap() = None;
+    notify(NotifyState::Watchdog);
+}
+
+/// Longest a cycle may take before it counts as hung
+fn max_cycle_duration(device_timeout: Duration, device_count: usize) -> Duration {
+    device_timeout * device_count.max(1) as u32 + CYCLE_SLACK
+}
+
+/// How long the cycle in progress has been running for, if it is longer than `limit`
+fn hung_for(started: Option<Instant>, now: Instant, limit: Duration) -> Option<Duration> {
+    started.map(|started| now.duration_since(started)).filter(|running| *running > limit)
+}
+
+/// Report to the watchdog between cycles, while the controller isn't stuck in one
+/// Returns right away when systemd has no watchdog configured for the service
+pub async fn keepalive_loop() {
+    let mut watchdog_usec = 0;
+    if !sd_notify::watchdog_enabled(false, &mut watchdog_usec) {
+        return;
+    }
+    let interval = Duration::from_micros(watchdog_usec) / 2;
+    log::info!("systemd watchdog enabled, reporting every {} s", interval.as_secs());
+
+    let mut reported_hang = false;
+    loop {
+        tokio::time::sleep(interval).await;
+        let config = crate::config::get_config();
+        let limit = max_cycle_duration(
+            Duration::from_secs(config.device_cycle_timeout_seconds),
+            devices::get_device_registry().all().len(),
+        );
+        let started = *cycle_started_at().lock().unwrap();
+        match hung_for(started, Instant::now(), limit) {
+            Some(running) => {
+                if !reported_hang {
+                    log::error!(
+                        "Control cycle has been running for {} s, no longer reporting to the systemd watchdog",
+                        running.as_secs()
+                    );
+                    reported_hang = true;
+                }
+            }
+            None => {
+                reported_hang = false;
+                notify(NotifyState::Watchdog);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_counts_as_hung_after_every_device_timed_out() {
+        let limit = max_cycle_duration(Duration::from_secs(90), 3);
+        assert_eq!(limit, Duration::from_secs(330));
+        assert_eq!(max_cycle_duration(Duration::from_secs(90), 0), Duration::from_secs(150));
+
+        let started = Instant::now();
+        assert_eq!(hung_for(None, started, limit), None, "no cycle running");
+        assert_eq!(hung_for(Some(started), started + Duration::from_secs(200), limit), None);
+        assert_eq!(hung_for(Some(started), started + Duration::from_secs(400), limit), Some(Duration::from_secs(400)));
+    }
+}
@@ -0,0 +1,100 @@
+//! Shared application state
+//!
+//! API handlers get the config, database pool and controller state managers from an
+//! `AppState` (axum `State`) instead of global singletons. In production the state managers
+//! are the same instances the AC controller loop uses. Tests build isolated instances with
+//! their own in-memory database, config and state, so they can run in parallel without
+//! interfering with each other.
+
+use std::sync::Arc;
+
+use crate::{
+    ac_controller::{
+        ac_executor::{self, AcStateManager},
+        away_mode::{self, AwayMode},
+        contact_sensors::{self, ContactSensorState},
+        manual_override::{self, ManualOverrides},
+        min_off_time::{self, MinOffTimeState},
+        occupancy::{self, OccupancyModel},
+        pir_state::{self, PirState},
+        shadow_mode::{self, ShadowMode},
+        DeviceRegistry,
+    },
+    config::{self, ConfigStore},
+    db::{self, DbPool},
+    presence::{self, PresenceState},
+    types::Config,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    /// Reloadable config, read through `config()`
+    pub config_store: Arc<ConfigStore>,
+    pub pool: DbPool,
+    /// Last known state of each AC device
+    pub ac_states: Arc<AcStateManager>,
+    /// States forced through the API until they expire
+    pub manual_overrides: Arc<ManualOverrides>,
+    /// Last PIR detection per device
+    pub pir: Arc<PirState>,
+    /// Occupancy evidence per room
+    pub occupancy: Arc<OccupancyModel>,
+    /// Open/closed state of each window and door contact sensor
+    pub contact_sensors: Arc<ContactSensorState>,
+    /// Turn-off times used for the minimum off-time
+    pub min_off_time: Arc<MinOffTimeState>,
+    /// Whether AC commands are suppressed while nodesets keep being evaluated
+    pub shadow_mode: Arc<ShadowMode>,
+    /// Whether the user is away, flipped through the away webhook
+    pub away_mode: Arc<AwayMode>,
+    /// Who is home, reported by the presence sources or overridden
+    pub presence: Arc<PresenceState>,
+}
+
+impl AppState {
+    /// State backed by the loaded config file, the global database pool and the
+    /// state managers shared with the AC controller loop
+    pub async fn from_globals() -> Self {
+        Self {
+            config_store: config::get_config_store().clone(),
+            pool: db::get_pool().await.clone(),
+            ac_states: ac_executor::get_state_manager().clone(),
+            manual_overrides: manual_override::get_manual_overrides().clone(),
+            pir: pir_state::get_pir_state().clone(),
+            occupancy: occupancy::get_occupancy_model().clone(),
+            contact_sensors: contact_sensors::get_contact_sensor_state().clone(),
+            min_off_time: min_off_time::get_min_off_time_state().clone(),
+            shadow_mode: shadow_mode::get_shadow_mode().clone(),
+            away_mode: away_mode::get_away_mode().clone(),
+            presence: presence::get_presence_state().clone(),
+        }
+    }
+
+    /// Isolated state with the given config, a fresh in-memory database and empty state managers
+    #[cfg(test)]
+    pub async fn for_test(config: Config) -> Self {
+        Self {
+            config_store: Arc::new(ConfigStore::new(config)),
+            pool: db::connect_in_memory().await,
+            ac_states: Arc::default(),
+            manual_overrides: Arc::default(),
+            pir: Arc::default(),
+            occupancy: Arc::default(),
+            contact_sensors: Arc::default(),
+            min_off_time: Arc::default(),
+            shadow_mode: Arc::default(),
+            away_mode: Arc::default(),
+            presence: Arc::default(),
+        }
+    }
+
+    /// Current config, reflects reloads
+    pub fn config(&self) -> Arc<Config> {
+        self.config_store.get()
+    }
+
+    /// Devices of the current config
+    pub fn devices(&self) -> DeviceRegistry {
+        DeviceRegistry::from_config(&self.config())
+    }
+}
@@ -1,27 +1,129 @@
 use crate::types::*;
-use log::info;
+use log::{info, warn};
 use serde_json;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock, RwLock};
 
 #[cfg(test)]
 mod tests;
 
 pub const CONFIG_FILE_PATH: &str = "/etc/power_control_center/config.json";
-static CONFIG: OnceLock<Config> = OnceLock::new();
+static CONFIG: OnceLock<Arc<ConfigStore>> = OnceLock::new();
 
-/// Load and return a reference to the global configuration
-/// Reuse existing config if already loaded from file.
-/// Config cannot be changed at runtime.
-pub fn get_config() -> &'static Config {
+/// Holds the current configuration, which can be replaced at runtime.
+/// Readers get a snapshot that stays consistent while they use it.
+pub struct ConfigStore {
+    current: RwLock<Arc<Config>>,
+}
+
+impl ConfigStore {
+    pub fn new(config: Config) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(config)),
+        }
+    }
+
+    /// Get the current configuration
+    pub fn get(&self) -> Arc<Config> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Replace the configuration, returning the previous one
+    pub fn replace(&self, config: Config) -> Arc<Config> {
+        std::mem::replace(&mut *self.current.write().unwrap(), Arc::new(config))
+    }
+
+    /// Read and parse a config file and replace the configuration with it.
+    /// The current configuration is kept if the file can't be read or parsed.
+    /// Fields that are only read at startup are logged when they changed.
+    pub fn reload_from_file(&self, path: &str) -> Result<(), String> {
+        let config_str =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read config file {}: {}", path, e))?;
+        let config: Config =
+            serde_json::from_str(&config_str).map_err(|e| format!("Failed to parse config JSON: {}", e))?;
+
+        let previous = self.replace(config);
+        for field in restart_required_changes(&previous, &self.get()) {
+            warn!("Config field '{}' changed, restart the service for it to take effect", field);
+        }
+        info!("Config reloaded from {}", path);
+        Ok(())
+    }
+}
+
+/// Get the global configuration store, loading the config file on first use
+pub fn get_config_store() -> &'static Arc<ConfigStore> {
     CONFIG.get_or_init(|| {
         info!("Loading config from {}", CONFIG_FILE_PATH);
         let config_str = std::fs::read_to_string(CONFIG_FILE_PATH).unwrap_or_else(|e| {
             panic!("Failed to read config file {}: {}", CONFIG_FILE_PATH, e);
         });
-        get_config_from_json_str(&config_str)
+        Arc::new(ConfigStore::new(get_config_from_json_str(&config_str)))
     })
 }
 
+/// Get the current configuration.
+/// Loaded from file on first use, and again by `reload_config`.
+/// Don't keep the returned config around longer than needed, or reloads won't be seen.
+pub fn get_config() -> Arc<Config> {
+    get_config_store().get()
+}
+
+/// Reload the config file into the global configuration
+pub fn reload_config() -> Result<(), String> {
+    get_config_store().reload_from_file(CONFIG_FILE_PATH)
+}
+
+/// Fields that are only read at startup and differ between two configs
+fn restart_required_changes(previous: &Config, current: &Config) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    if previous.database_path != current.database_path {
+        changed.push("database_path");
+    }
+    if previous.database_url != current.database_url {
+        changed.push("database_url");
+    }
+    if previous.listen_address != current.listen_address {
+        changed.push("listen_address");
+    }
+    if previous.listen_port != current.listen_port {
+        changed.push("listen_port");
+    }
+    if previous.tls_listen_port != current.tls_listen_port {
+        changed.push("tls_listen_port");
+    }
+    // New certificate paths are picked up by the certificate reload, enabling or disabling TLS is not
+    if previous.tls_enabled() != current.tls_enabled() {
+        changed.push("tls_cert_path");
+    }
+    if previous.nodeset_max_body_bytes != current.nodeset_max_body_bytes {
+        changed.push("nodeset_max_body_bytes");
+    }
+    if previous.api_max_body_bytes != current.api_max_body_bytes {
+        changed.push("api_max_body_bytes");
+    }
+    changed
+}
+
+/// Reload the config whenever the process receives SIGHUP (e.g. `systemctl reload`)
+#[cfg(unix)]
+pub async fn reload_on_sighup() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            warn!("Failed to listen for SIGHUP, config can only be reloaded through the API: {}", e);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received, reloading config");
+        if let Err(e) = reload_config() {
+            log::error!("Config reload failed, keeping the current config: {}", e);
+        }
+    }
+}
+
 /// Parse configuration from a JSON string.
 /// used by `get_config` and tests.
 /// Panics if parsing fails.
@@ -36,14 +138,88 @@ impl Default for Config {
 
         Config {
             database_path: String::new(),
+            database_url: String::new(),
             listen_address: String::new(),
             listen_port: 0,
+            tls_listen_port: 9443,
+            tls_cert_path: String::new(),
+            tls_key_path: String::new(),
             smart_meter_api_endpoint: String::new(),
-            ac_controller_endpoints: HashMap::new(),
+            // The two devices of the original setup; nothing listens on these endpoints
+            ac_controller_endpoints: ["LivingRoom", "Veranda"]
+                .into_iter()
+                .map(|device| {
+                    let props = AcControllerEndpointProperties {
+                        endpoint: "http://127.0.0.1:9".to_string(),
+                        api_key: String::new(),
+                        change_policy: Default::default(),
+                        dnd_windows: Vec::new(),
+                        min_off_time_minutes: None,
+                        latitude: None,
+                        longitude: None,
+                        priority: 0,
+                        power_watt: 1000,
+                        powerful_power_watt: 1500,
+                        mode_power_watt: HashMap::new(),
+                        supports_vertical_vane: false,
+                        supports_horizontal_vane: false,
+                        supports_state_readback: false,
+                        decision_engine: Default::default(),
+                    };
+                    (device.to_string(), props)
+                })
+                .collect(),
             latitude: 0.0,
             longitude: 0.0,
             pir_api_key: String::new(),
             pir_timeout_minutes: 5,
+            pir_sensors: HashMap::new(),
+            contact_sensors: HashMap::new(),
+            float_tolerance: 0.0001,
+            temperature_rounding_step: 0.5,
+            ups_api_key: String::new(),
+            shed_load_on_grid_outage: false,
+            demand_response_api_key: String::new(),
+            rpc_api_key: String::new(),
+            deep_idle_enabled: false,
+            deep_idle_interval_multiplier: 6,
+            telemetry_enabled: false,
+            telemetry_endpoint: String::new(),
+            update_check_enabled: false,
+            self_update_enabled: false,
+            admin_api_key: String::new(),
+            nodeset_max_body_bytes: 8 * 1024 * 1024,
+            api_max_body_bytes: 1024 * 1024,
+            api_rate_limit_per_minute: 600,
+            nodeset_revisions_kept: 20,
+            default_nodeset_enabled: true,
+            sensor_max_rate_c_per_minute: 0.5,
+            sensor_median_window: 3,
+            weather_backfill_days: 30,
+            command_latency_slo_ms: 2000,
+            notification_channels: HashMap::new(),
+            energy_price_bidding_zone: String::new(),
+            battery_api_endpoint: String::new(),
+            strict_cause_reasons: false,
+            season_window_days: 7,
+            heating_season_below_c: 17.0,
+            cooling_season_above_c: 22.0,
+            min_off_time_minutes: 3,
+            presence: Default::default(),
+            power_budget_watt: 0,
+            device_state_max_age_minutes: 60,
+            device_cycle_timeout_seconds: 90,
+            cold_snap_preheat: Default::default(),
+            away_webhook: Default::default(),
+            solar_panels: Default::default(),
+            shutdown_ac_state: Default::default(),
+            energy_meter_correction: false,
+            health_alerts: Default::default(),
+            command_verification: Default::default(),
+            occupancy: Default::default(),
+            mqtt_bridge: Default::default(),
+            backup: Default::default(),
+            retention: Default::default(),
         }
     }
 }
@@ -57,10 +233,10 @@ impl Config {
 
     // Set global config to our customized test config.
     // Now callable by `get_config`
-    pub fn build(self) -> &'static Config {
-        CONFIG
-            .set(self)
-            .expect("set_test_config failed. Config already initialized.");
-        CONFIG.get().unwrap()
+    pub fn build(self) -> Arc<Config> {
+        if CONFIG.set(Arc::new(ConfigStore::new(self))).is_err() {
+            panic!("set_test_config failed. Config already initialized.");
+        }
+        get_config()
     }
 }
@@ -82,6 +82,27 @@ mod tests {
         get_config_from_json_str(json_str); // This should panic
     }
 
+    #[test]
+    fn test_zero_device_cycle_timeout_is_rejected() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {},
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "device_cycle_timeout_seconds": 0
+        }
+        "#;
+
+        let err = serde_json::from_str::<Config>(json_str).unwrap_err();
+        assert!(err.to_string().contains("device_cycle_timeout_seconds must be at least 1"), "{}", err);
+        let config: Config = serde_json::from_str(&json_str.replace(": 0", ": 30")).unwrap();
+        assert_eq!(config.device_cycle_timeout_seconds, 30);
+    }
+
     #[test]
     fn test_pir_settings_have_defaults() {
         // Test that PIR settings are optional and use defaults when not provided
@@ -108,4 +129,262 @@ mod tests {
         assert_eq!(config.pir_api_key, "");
         assert_eq!(config.pir_timeout_minutes, 5);
     }
+
+    #[test]
+    fn test_tolerance_settings_have_defaults() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {},
+            "latitude": 51.5074,
+            "longitude": -0.1278
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        assert_eq!(config.float_tolerance, 0.0001);
+        assert_eq!(config.temperature_rounding_step, 0.5);
+    }
+
+    #[test]
+    fn test_tolerance_settings_custom_values() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {},
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "float_tolerance": 0.01,
+            "temperature_rounding_step": 1.0
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        assert_eq!(config.float_tolerance, 0.01);
+        assert_eq!(config.temperature_rounding_step, 1.0);
+    }
+
+    #[test]
+    fn test_change_policy_defaults_and_overrides() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {
+                "LivingRoom": {
+                    "endpoint": "http://192.168.50.201",
+                    "api_key": "secret123"
+                },
+                "Veranda": {
+                    "endpoint": "http://192.168.50.202",
+                    "api_key": "secret456",
+                    "change_policy": {
+                        "temperature_tolerance": 1.0,
+                        "ignore_swing": true
+                    }
+                }
+            },
+            "latitude": 51.5074,
+            "longitude": -0.1278
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        let living_room = config.get_change_policy("LivingRoom");
+        assert_eq!(living_room, AcChangePolicy::default());
+        assert_eq!(living_room.temperature_tolerance, 0.5);
+
+        let veranda = config.get_change_policy("Veranda");
+        assert_eq!(veranda.temperature_tolerance, 1.0);
+        assert!(veranda.ignore_swing);
+        assert!(!veranda.ignore_fan_speed);
+        assert!(!veranda.ignore_powerful);
+
+        // Unknown devices fall back to the default policy
+        assert_eq!(config.get_change_policy("Unknown"), AcChangePolicy::default());
+    }
+
+    #[test]
+    fn test_dnd_windows_parsing() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {
+                "LivingRoom": {
+                    "endpoint": "http://192.168.50.201",
+                    "api_key": "secret123"
+                },
+                "Veranda": {
+                    "endpoint": "http://192.168.50.202",
+                    "api_key": "secret456",
+                    "dnd_windows": [
+                        { "start": "22:00", "end": "07:00" },
+                        { "start": "13:00", "end": "15:00", "max_setpoint_change": 2.0 }
+                    ]
+                }
+            },
+            "latitude": 51.5074,
+            "longitude": -0.1278
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        assert!(config.get_dnd_windows("LivingRoom").is_empty());
+        assert!(config.get_dnd_windows("Unknown").is_empty());
+
+        let windows = config.get_dnd_windows("Veranda");
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].start, "22:00");
+        assert_eq!(windows[0].end, "07:00");
+        assert_eq!(windows[0].max_setpoint_change, 1.0);
+        assert_eq!(windows[1].max_setpoint_change, 2.0);
+    }
+
+    #[test]
+    fn test_weather_coordinates_per_device() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {
+                "LivingRoom": {
+                    "endpoint": "http://192.168.50.201",
+                    "api_key": "secret123",
+                    "latitude": 51.6
+                },
+                "Veranda": {
+                    "endpoint": "http://192.168.50.202",
+                    "api_key": "secret456",
+                    "latitude": 51.52,
+                    "longitude": -0.15
+                }
+            },
+            "latitude": 51.5074,
+            "longitude": -0.1278
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        assert_eq!(config.get_weather_coordinates("Veranda"), (51.52, -0.15));
+        // Only a latitude is not a location, so the global one is used
+        assert_eq!(config.get_weather_coordinates("LivingRoom"), (51.5074, -0.1278));
+        assert_eq!(config.get_weather_coordinates("Unknown"), (51.5074, -0.1278));
+    }
+
+    #[test]
+    fn test_vane_support_per_device() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {
+                "LivingRoom": {
+                    "endpoint": "http://192.168.50.201",
+                    "api_key": "secret123",
+                    "supports_vertical_vane": true
+                },
+                "Veranda": {
+                    "endpoint": "http://192.168.50.202",
+                    "api_key": "secret456"
+                }
+            },
+            "latitude": 51.5074,
+            "longitude": -0.1278
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        assert_eq!(config.supported_vanes("LivingRoom", Some("Position 2"), Some("Swing")), (Some(2), None));
+        assert_eq!(config.supported_vanes("LivingRoom", Some("Sideways"), None), (None, None));
+        assert_eq!(config.supported_vanes("Veranda", Some("Position 2"), Some("Swing")), (None, None));
+        assert_eq!(config.supported_vanes("Unknown", Some("Auto"), None), (None, None));
+    }
+
+    #[test]
+    fn test_health_alert_channels() {
+        let json_str = r#"
+        {
+            "database_path": "../test.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {},
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "health_alerts": {
+                "channels": [
+                    { "type": "ntfy", "url": "https://ntfy.sh/ac-alerts" },
+                    { "type": "telegram", "bot_token": "123:abc", "chat_id": "42" }
+                ]
+            }
+        }
+        "#;
+
+        let config = get_config_from_json_str(json_str);
+
+        assert_eq!(config.health_alerts.after_minutes, 15);
+        assert_eq!(config.health_alerts.channels, vec![
+            AlertChannel::Ntfy { url: "https://ntfy.sh/ac-alerts".to_string() },
+            AlertChannel::Telegram { bot_token: "123:abc".to_string(), chat_id: "42".to_string() },
+        ]);
+        let debug = format!("{:?}", config.health_alerts);
+        assert!(!debug.contains("123:abc") && !debug.contains("ac-alerts"), "secrets are redacted: {}", debug);
+    }
+
+    #[test]
+    fn test_reload_from_file_keeps_config_on_error() {
+        let path = std::env::temp_dir().join(format!("pcc_config_reload_{}.json", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        let store = ConfigStore::new(Config {
+            pir_timeout_minutes: 5,
+            ..Default::default()
+        });
+        let held = store.get();
+
+        let json = r#"{
+            "database_path": "/tmp/pcc.db",
+            "listen_address": "127.0.0.1",
+            "listen_port": 9040,
+            "smart_meter_api_endpoint": "http://raspberrypi.local:9039",
+            "ac_controller_endpoints": {},
+            "latitude": 52.1,
+            "longitude": 5.1,
+            "pir_timeout_minutes": 15
+        }"#;
+        std::fs::write(&path, json).unwrap();
+        store.reload_from_file(path_str).unwrap();
+        assert_eq!(store.get().pir_timeout_minutes, 15);
+        assert_eq!(store.get().latitude, 52.1);
+        assert_eq!(held.pir_timeout_minutes, 5, "snapshots taken before the reload are unchanged");
+
+        std::fs::write(&path, "{ not json").unwrap();
+        let err = store.reload_from_file(path_str).unwrap_err();
+        assert!(err.contains("Failed to parse config JSON"));
+        assert_eq!(store.get().pir_timeout_minutes, 15);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(store.reload_from_file(path_str).is_err());
+        assert_eq!(store.get().pir_timeout_minutes, 15);
+    }
 }
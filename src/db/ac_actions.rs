@@ -1,3 +1,5 @@
+use crate::db::{DbExecutor, DbPool};
+
 use crate::{db::get_pool, types::db_types};
 
 pub async fn insert(ac_action: db_types::AcAction) -> Result<(), sqlx::Error> {
@@ -5,8 +7,8 @@ pub async fn insert(ac_action: db_types::AcAction) -> Result<(), sqlx::Error> {
 
     sqlx::query(
         r#"
-        INSERT INTO ac_actions (action_timestamp, device_identifier, action_type, mode, fan_speed, request_temperature, swing, measured_temperature, measured_net_power_watt, measured_solar_production_watt, is_human_home, cause_id)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO ac_actions (action_timestamp, device_identifier, action_type, mode, fan_speed, request_temperature, swing, measured_temperature, measured_net_power_watt, measured_solar_production_watt, is_human_home, cause_id, command_latency_ms, annotation, vertical_vane, horizontal_vane)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         "#
     )
     .bind(&ac_action.action_timestamp)
@@ -21,6 +23,10 @@ pub async fn insert(ac_action: db_types::AcAction) -> Result<(), sqlx::Error> {
     .bind(ac_action.measured_solar_production_watt)
     .bind(ac_action.is_human_home)
     .bind(ac_action.cause_id)
+    .bind(ac_action.command_latency_ms)
+    .bind(&ac_action.annotation)
+    .bind(ac_action.vertical_vane)
+    .bind(ac_action.horizontal_vane)
     .execute(pool)
     .await?;
 
@@ -34,7 +40,7 @@ pub async fn get_page(limit: i64, offset: i64) -> Result<Vec<db_types::AcAction>
         r#"
         SELECT * FROM ac_actions
         ORDER BY action_timestamp DESC
-        LIMIT ? OFFSET ?
+        LIMIT $1 OFFSET $2
         "#,
     )
     .bind(limit)
@@ -67,7 +73,7 @@ pub async fn get_last_action_timestamp(device_identifier: &str) -> Result<Option
     let result: Option<(i32,)> = sqlx::query_as(
         r#"
         SELECT action_timestamp FROM ac_actions
-        WHERE device_identifier = ?
+        WHERE device_identifier = $1
         ORDER BY action_timestamp DESC
         LIMIT 1
         "#,
@@ -78,3 +84,59 @@ pub async fn get_last_action_timestamp(device_identifier: &str) -> Result<Option
 
     Ok(result.map(|(ts,)| ts))
 }
+
+/// Get the last on or off command successfully sent to a device
+/// Powerful toggles are skipped, as sending one again would flip the mode back
+pub async fn get_last_command<'e, E>(executor: E, device_identifier: &str) -> Result<Option<db_types::AcAction>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::AcAction>(
+        r#"
+        SELECT * FROM ac_actions
+        WHERE device_identifier = $1 AND action_type IN ('on', 'off')
+        ORDER BY action_timestamp DESC, id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(device_identifier)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Column that tells rows of ac_actions apart
+/// In SQLite `id` isn't an alias of the rowid and is never set, so rows are told apart by rowid
+#[cfg(not(feature = "postgres"))]
+const ROW_ID: &str = "rowid";
+#[cfg(feature = "postgres")]
+const ROW_ID: &str = "id";
+
+/// Delete actions from before `cutoff`, except each device's last action and last on or off command,
+/// which the statistics and command replays start from
+/// Returns the number of deleted actions
+pub async fn delete_older_than(pool: &DbPool, cutoff: i64) -> Result<u64, sqlx::Error> {
+    let query = format!(
+        r#"
+        DELETE FROM ac_actions
+        WHERE action_timestamp < $1 AND {row_id} NOT IN (
+            SELECT kept FROM (
+                SELECT (
+                    SELECT b.{row_id} FROM ac_actions b WHERE b.device_identifier = d.device_identifier
+                    ORDER BY b.action_timestamp DESC, b.{row_id} DESC LIMIT 1
+                ) AS kept
+                FROM (SELECT DISTINCT device_identifier FROM ac_actions) d
+                UNION
+                SELECT (
+                    SELECT b.{row_id} FROM ac_actions b WHERE b.device_identifier = d.device_identifier AND b.action_type IN ('on', 'off')
+                    ORDER BY b.action_timestamp DESC, b.{row_id} DESC LIMIT 1
+                )
+                FROM (SELECT DISTINCT device_identifier FROM ac_actions) d
+            ) AS kept_rows
+            WHERE kept IS NOT NULL
+        )
+        "#,
+        row_id = ROW_ID
+    );
+    let result = sqlx::query(&query).bind(cutoff).execute(pool).await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,332 @@
+//! Database backups and restores
+//!
+//! A backup is a copy of the whole database (nodesets, settings and history) written with
+//! `VACUUM INTO`, which is consistent while the controller keeps writing. `POST /api/admin/backup`
+//! downloads one and, with `backup.directory` set, `backup_loop` writes one there every night.
+//!
+//! A restore can't replace the database under the open pool, so an uploaded backup is checked and
+//! staged as `<database_path>.restore`, and `apply_staged_restore` swaps it in on the next start
+//! before the pool is opened. The replaced database is kept as `<database_path>.before-restore`.
+//!
+//! Only SQLite databases are backed up this way, PostgreSQL ones are backed up with `pg_dump`.
+
+use chrono::NaiveDateTime;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, SqliteConnection};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::DbPool;
+use crate::config;
+
+/// Whether the database backend of this build can be backed up and restored here
+pub const SUPPORTED: bool = cfg!(not(feature = "postgres"));
+
+/// File names of backups in `backup.directory` are this followed by the local time
+const BACKUP_FILE_PREFIX: &str = "pcc-backup-";
+const BACKUP_FILE_EXTENSION: &str = ".db";
+
+/// Wait before checking again when nightly backups are disabled or failed to be scheduled
+const DISABLED_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Write a consistent copy of the database to `target`, which must not exist yet
+pub async fn snapshot(pool: &DbPool, target: &Path) -> Result<(), sqlx::Error> {
+    sqlx::query("VACUUM INTO $1")
+        .bind(target.to_string_lossy().into_owned())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// File name of a backup made at `time`, these sort oldest first
+pub fn backup_file_name(time: NaiveDateTime) -> String {
+    format!("{}{}{}", BACKUP_FILE_PREFIX, time.format("%Y%m%d-%H%M%S"), BACKUP_FILE_EXTENSION)
+}
+
+/// Directory for temporary files next to the database, so they are on the same disk
+pub fn scratch_directory(database_path: &str) -> PathBuf {
+    match Path::new(database_path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        // In-memory databases in tests
+        _ => std::env::temp_dir(),
+    }
+}
+
+/// Why an uploaded file can't be restored
+#[derive(Debug, PartialEq)]
+pub enum InvalidBackup {
+    /// Not a database of this application, or a damaged one
+    Unreadable(String),
+    /// Has migrations this version doesn't know
+    NewerVersion(i64),
+}
+
+impl std::fmt::Display for InvalidBackup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidBackup::Unreadable(reason) => write!(f, "{}", reason),
+            InvalidBackup::NewerVersion(version) => {
+                write!(f, "The backup is from a newer version (migration {}), update first", version)
+            }
+        }
+    }
+}
+
+/// Check that `path` is an intact database from this or an earlier version
+pub async fn validate(path: &Path) -> Result<(), InvalidBackup> {
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .map_err(|e| InvalidBackup::Unreadable(format!("Failed to open the backup: {}", e)))?;
+    let result = check_backup(&mut conn).await;
+    let _ = conn.close().await;
+    result
+}
+
+async fn check_backup(conn: &mut SqliteConnection) -> Result<(), InvalidBackup> {
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| InvalidBackup::Unreadable(format!("Not a SQLite database: {}", e)))?;
+    if integrity != "ok" {
+        return Err(InvalidBackup::Unreadable(format!("The backup is corrupt: {}", integrity)));
+    }
+
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = 1")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|_| InvalidBackup::Unreadable("Not a Power Control Center database".to_string()))?;
+    let known: HashSet<i64> = sqlx::migrate!("./migrations").iter().map(|m| m.version).collect();
+    match applied.into_iter().find(|version| !known.contains(version)) {
+        Some(version) => Err(InvalidBackup::NewerVersion(version)),
+        None => Ok(()),
+    }
+}
+
+fn staged_restore_path(database_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.restore", database_path))
+}
+
+/// Stage a validated backup to replace the database on the next start
+pub async fn stage_restore(upload: &Path, database_path: &str) -> std::io::Result<()> {
+    tokio::fs::rename(upload, staged_restore_path(database_path)).await
+}
+
+/// Replace the database with a staged restore, if there is one. Must run before the pool is opened.
+/// Returns whether a restore was applied.
+#[cfg(not(feature = "postgres"))]
+pub fn apply_staged_restore(database_path: &str) -> std::io::Result<bool> {
+    let staged = staged_restore_path(database_path);
+    if !staged.exists() {
+        return Ok(false);
+    }
+
+    // The WAL holds writes not yet in the main file, so it moves along with it
+    let previous = format!("{}.before-restore", database_path);
+    for suffix in ["", "-wal", "-shm"] {
+        let kept = format!("{}{}", previous, suffix);
+        match std::fs::rename(format!("{}{}", database_path, suffix), &kept) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // Left from an earlier restore, it would be replayed into the wrong database
+                let _ = std::fs::remove_file(&kept);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    std::fs::rename(&staged, database_path)?;
+    Ok(true)
+}
+
+/// Delete all but the newest `keep` backups in `directory` (0 keeps all), returns how many were deleted
+fn prune_backups(directory: &Path, keep: usize) -> std::io::Result<usize> {
+    if keep == 0 {
+        return Ok(0);
+    }
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(BACKUP_FILE_PREFIX) && name.ends_with(BACKUP_FILE_EXTENSION))
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    for path in &backups[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+/// Write a backup to `directory` and delete the oldest ones beyond `keep`
+pub async fn write_backup(pool: &DbPool, directory: &Path, keep: usize) -> Result<PathBuf, String> {
+    tokio::fs::create_dir_all(directory)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+    let target = directory.join(backup_file_name(chrono::Local::now().naive_local()));
+    snapshot(pool, &target).await.map_err(|e| e.to_string())?;
+
+    let directory = directory.to_path_buf();
+    match tokio::task::spawn_blocking(move || prune_backups(&directory, keep)).await {
+        Ok(Ok(0)) => {}
+        Ok(Ok(deleted)) => log::info!("Deleted {} old database backups", deleted),
+        Ok(Err(e)) => log::warn!("Failed to delete old database backups: {}", e),
+        Err(e) => log::warn!("Failed to delete old database backups: {}", e),
+    }
+    Ok(target)
+}
+
+/// The next time the clock reads `hour`:00 after `now`
+fn next_backup_at(now: NaiveDateTime, hour: u32) -> NaiveDateTime {
+    let today = now.date().and_hms_opt(hour.min(23), 0, 0).unwrap();
+    if today > now {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    }
+}
+
+/// Write a backup to `backup.directory` every night while one is configured
+pub async fn backup_loop() {
+    if !SUPPORTED {
+        if !config::get_config().backup.directory.is_empty() {
+            log::warn!("backup.directory is ignored, back up PostgreSQL databases with pg_dump");
+        }
+        return;
+    }
+    loop {
+        let config = config::get_config();
+        if config.backup.directory.is_empty() {
+            tokio::time::sleep(DISABLED_CHECK_INTERVAL).await;
+            continue;
+        }
+
+        let now = chrono::Local::now().naive_local();
+        let wait = (next_backup_at(now, config.backup.hour) - now)
+            .to_std()
+            .unwrap_or(DISABLED_CHECK_INTERVAL);
+        log::debug!("Next database backup in {} minutes", wait.as_secs() / 60);
+        tokio::time::sleep(wait).await;
+
+        // The config may have been reloaded while waiting
+        let config = config::get_config();
+        if config.backup.directory.is_empty() {
+            continue;
+        }
+        let pool = super::get_pool().await;
+        match write_backup(pool, Path::new(&config.backup.directory), config.backup.keep).await {
+            Ok(path) => log::info!("Database backed up to {}", path.display()),
+            Err(e) => log::error!("Database backup failed: {}", e),
+        }
+        // Don't run twice when the backup finished within the same second
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+#[cfg(all(test, not(feature = "postgres")))]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("pcc_backup_{}_{}", name, std::process::id()))
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_passes_validation() {
+        let database = temp_path("source.db");
+        let pool = crate::db::connect_file(&database).await;
+        sqlx::query("INSERT INTO settings (setting_key, setting_value) VALUES ('backup_test', 'kept')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let path = temp_path("snapshot.db");
+        let _ = std::fs::remove_file(&path);
+        snapshot(&pool, &path).await.unwrap();
+        assert_eq!(validate(&path).await, Ok(()));
+
+        let mut conn = SqliteConnectOptions::new().filename(&path).connect().await.unwrap();
+        let value: String = sqlx::query_scalar("SELECT setting_value FROM settings WHERE setting_key = 'backup_test'")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(value, "kept");
+
+        sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (99999999999999, 'future', 1, x'00', 0)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        conn.close().await.unwrap();
+        assert_eq!(validate(&path).await, Err(InvalidBackup::NewerVersion(99999999999999)));
+
+        std::fs::write(&path, b"not a database").unwrap();
+        assert!(matches!(validate(&path).await, Err(InvalidBackup::Unreadable(_))));
+        std::fs::remove_file(&path).unwrap();
+        pool.close().await;
+        std::fs::remove_file(&database).unwrap();
+    }
+
+    #[test]
+    fn test_apply_staged_restore_keeps_previous_database() {
+        let database = temp_path("restore.db");
+        let database_path = database.to_str().unwrap();
+        let previous = format!("{}.before-restore", database_path);
+        assert!(!apply_staged_restore(database_path).unwrap(), "nothing staged");
+
+        std::fs::write(&database, b"current").unwrap();
+        std::fs::write(format!("{}-wal", database_path), b"current wal").unwrap();
+        std::fs::write(format!("{}-wal", previous), b"stale wal").unwrap();
+        std::fs::write(staged_restore_path(database_path), b"uploaded").unwrap();
+
+        assert!(apply_staged_restore(database_path).unwrap());
+        assert_eq!(std::fs::read(&database).unwrap(), b"uploaded");
+        assert_eq!(std::fs::read(&previous).unwrap(), b"current");
+        assert_eq!(std::fs::read(format!("{}-wal", previous)).unwrap(), b"current wal");
+        assert!(!Path::new(&format!("{}-wal", database_path)).exists());
+        assert!(!staged_restore_path(database_path).exists());
+
+        for path in [database_path.to_string(), previous.clone(), format!("{}-wal", previous)] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_backups() {
+        let directory = temp_path("prune");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        for day in 1..=4 {
+            std::fs::write(directory.join(backup_file_name(at(day, 3, 0))), b"").unwrap();
+        }
+        std::fs::write(directory.join("notes.txt"), b"").unwrap();
+
+        assert_eq!(prune_backups(&directory, 0).unwrap(), 0);
+        assert_eq!(prune_backups(&directory, 2).unwrap(), 2);
+        let mut left: Vec<String> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["notes.txt", "pcc-backup-20261003-030000.db", "pcc-backup-20261004-030000.db"]);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_next_backup_at() {
+        assert_eq!(next_backup_at(at(16, 1, 30), 3), at(16, 3, 0));
+        assert_eq!(next_backup_at(at(16, 3, 0), 3), at(17, 3, 0));
+        assert_eq!(next_backup_at(at(16, 22, 0), 3), at(17, 3, 0));
+    }
+}
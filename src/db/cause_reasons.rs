@@ -1,9 +1,10 @@
-use crate::db::get_pool;
+use crate::db::DbPool;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use std::collections::HashSet;
 
 /// Database model for cause_reason
-#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone, utoipa::ToSchema)]
 pub struct CauseReasonRecord {
     pub id: i32,
     pub label: String,
@@ -13,9 +14,7 @@ pub struct CauseReasonRecord {
 }
 
 /// Get all cause reasons (optionally include hidden)
-pub async fn get_all(include_hidden: bool) -> Result<Vec<CauseReasonRecord>, sqlx::Error> {
-    let pool = get_pool().await;
-    
+pub async fn get_all(pool: &DbPool, include_hidden: bool) -> Result<Vec<CauseReasonRecord>, sqlx::Error> {
     if include_hidden {
         sqlx::query_as::<_, CauseReasonRecord>(
             "SELECT id, label, description, is_hidden, is_editable FROM cause_reasons ORDER BY id"
@@ -24,7 +23,7 @@ pub async fn get_all(include_hidden: bool) -> Result<Vec<CauseReasonRecord>, sql
         .await
     } else {
         sqlx::query_as::<_, CauseReasonRecord>(
-            "SELECT id, label, description, is_hidden, is_editable FROM cause_reasons WHERE is_hidden = 0 ORDER BY id"
+            "SELECT id, label, description, is_hidden, is_editable FROM cause_reasons WHERE is_hidden = FALSE ORDER BY id"
         )
         .fetch_all(pool)
         .await
@@ -32,24 +31,37 @@ pub async fn get_all(include_hidden: bool) -> Result<Vec<CauseReasonRecord>, sql
 }
 
 /// Get a single cause reason by ID
-pub async fn get_by_id(id: i32) -> Result<Option<CauseReasonRecord>, sqlx::Error> {
-    let pool = get_pool().await;
-    
+pub async fn get_by_id(pool: &DbPool, id: i32) -> Result<Option<CauseReasonRecord>, sqlx::Error> {
     sqlx::query_as::<_, CauseReasonRecord>(
-        "SELECT id, label, description, is_hidden, is_editable FROM cause_reasons WHERE id = ?"
+        "SELECT id, label, description, is_hidden, is_editable FROM cause_reasons WHERE id = $1"
     )
     .bind(id)
     .fetch_optional(pool)
     .await
 }
 
+/// Find the cause reason a Cause Reason node selects, by ID or by exact label
+pub fn find_by_reference<'a>(records: &'a [CauseReasonRecord], reference: &str) -> Option<&'a CauseReasonRecord> {
+    let reference = reference.trim();
+    records
+        .iter()
+        .find(|cr| cr.id.to_string() == reference)
+        .or_else(|| records.iter().find(|cr| cr.label == reference))
+}
+
+/// Every value a Cause Reason node may select: the IDs and labels of the cause reasons
+pub fn known_references(records: &[CauseReasonRecord]) -> HashSet<String> {
+    records
+        .iter()
+        .flat_map(|cr| [cr.id.to_string(), cr.label.clone()])
+        .collect()
+}
+
 /// Minimum ID for user-created cause reasons (system IDs are 0-99)
 const MIN_USER_CAUSE_REASON_ID: i32 = 100;
 
 /// Create a new cause reason (ID is auto-generated starting at 100, new reasons are always editable)
-pub async fn create(label: &str, description: &str) -> Result<CauseReasonRecord, sqlx::Error> {
-    let pool = get_pool().await;
-    
+pub async fn create(pool: &DbPool, label: &str, description: &str) -> Result<CauseReasonRecord, sqlx::Error> {
     // Get the next available ID (minimum 100 to avoid system ID range)
     let (max_id,): (Option<i32>,) = sqlx::query_as(
         "SELECT MAX(id) FROM cause_reasons"
@@ -63,7 +75,7 @@ pub async fn create(label: &str, description: &str) -> Result<CauseReasonRecord,
     );
     
     sqlx::query(
-        "INSERT INTO cause_reasons (id, label, description, is_hidden, is_editable) VALUES (?, ?, ?, 0, 1)"
+        "INSERT INTO cause_reasons (id, label, description, is_hidden, is_editable) VALUES ($1, $2, $3, FALSE, TRUE)"
     )
     .bind(new_id)
     .bind(label)
@@ -81,11 +93,9 @@ pub async fn create(label: &str, description: &str) -> Result<CauseReasonRecord,
 }
 
 /// Update a cause reason
-pub async fn update(id: i32, label: &str, description: &str) -> Result<bool, sqlx::Error> {
-    let pool = get_pool().await;
-    
+pub async fn update(pool: &DbPool, id: i32, label: &str, description: &str) -> Result<bool, sqlx::Error> {
     let result = sqlx::query(
-        "UPDATE cause_reasons SET label = ?, description = ? WHERE id = ?"
+        "UPDATE cause_reasons SET label = $1, description = $2 WHERE id = $3"
     )
     .bind(label)
     .bind(description)
@@ -97,11 +107,9 @@ pub async fn update(id: i32, label: &str, description: &str) -> Result<bool, sql
 }
 
 /// Set the hidden status of a cause reason
-pub async fn set_hidden(id: i32, is_hidden: bool) -> Result<bool, sqlx::Error> {
-    let pool = get_pool().await;
-    
+pub async fn set_hidden(pool: &DbPool, id: i32, is_hidden: bool) -> Result<bool, sqlx::Error> {
     let result = sqlx::query(
-        "UPDATE cause_reasons SET is_hidden = ? WHERE id = ?"
+        "UPDATE cause_reasons SET is_hidden = $1 WHERE id = $2"
     )
     .bind(is_hidden)
     .bind(id)
@@ -112,16 +120,14 @@ pub async fn set_hidden(id: i32, is_hidden: bool) -> Result<bool, sqlx::Error> {
 }
 
 /// Delete a cause reason (cannot delete ID 0)
-pub async fn delete(id: i32) -> Result<bool, sqlx::Error> {
+pub async fn delete(pool: &DbPool, id: i32) -> Result<bool, sqlx::Error> {
     if id == 0 {
         // Cannot delete the Undefined reason
         return Ok(false);
     }
     
-    let pool = get_pool().await;
-    
     let result = sqlx::query(
-        "DELETE FROM cause_reasons WHERE id = ?"
+        "DELETE FROM cause_reasons WHERE id = $1"
     )
     .bind(id)
     .execute(pool)
@@ -130,6 +136,58 @@ pub async fn delete(id: i32) -> Result<bool, sqlx::Error> {
     Ok(result.rows_affected() > 0)
 }
 
+/// Executed actions and estimated AC use attributed to a cause reason on a device during one day (UTC)
+#[derive(Debug, FromRow, Serialize, Clone, PartialEq, utoipa::ToSchema)]
+pub struct CauseReasonDayStats {
+    pub day_start: i64, // Unix timestamp of the start of the day (UTC)
+    pub device_identifier: String,
+    pub cause_id: i64,
+    /// Commands sent to the device for this cause
+    pub actions: i64,
+    /// Minutes the AC ran while this cause set its state
+    pub runtime_minutes: f64,
+    pub estimated_kwh: f64,
+}
+
+/// Get the actions sent in [start, end) and the estimated energy of the hours starting in [start, end),
+/// per day, device and cause reason, oldest first
+/// Energy is estimated once an hour is rolled up into the statistics, so the current hour has actions but no runtime yet
+pub async fn get_daily_stats(
+    pool: &DbPool,
+    start: i64,
+    end: i64,
+    device_identifier: Option<&str>,
+) -> Result<Vec<CauseReasonDayStats>, sqlx::Error> {
+    sqlx::query_as::<_, CauseReasonDayStats>(
+        r#"
+        SELECT day_start, device_identifier, cause_id,
+            SUM(actions) AS actions, SUM(runtime_minutes) AS runtime_minutes, SUM(estimated_kwh) AS estimated_kwh
+        FROM (
+            SELECT (action_timestamp / 86400) * 86400 AS day_start, device_identifier, cause_id,
+                1 AS actions, 0.0 AS runtime_minutes, 0.0 AS estimated_kwh
+            FROM ac_actions
+            WHERE action_timestamp >= $1 AND action_timestamp < $2 AND ($3 IS NULL OR device_identifier = $4)
+            UNION ALL
+            SELECT (period_start / 86400) * 86400, device_identifier, cause_id, 0, runtime_minutes, estimated_kwh
+            FROM device_energy
+            WHERE period_start >= $5 AND period_start < $6 AND ($7 IS NULL OR device_identifier = $8)
+        ) AS day_rows
+        GROUP BY day_start, device_identifier, cause_id
+        ORDER BY day_start ASC, device_identifier ASC, cause_id ASC
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .bind(device_identifier)
+    .bind(device_identifier)
+    .bind(start)
+    .bind(end)
+    .bind(device_identifier)
+    .bind(device_identifier)
+    .fetch_all(pool)
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +211,24 @@ mod tests {
         assert_eq!(record.is_hidden, deserialized.is_hidden);
         assert_eq!(record.is_editable, deserialized.is_editable);
     }
+
+    #[test]
+    fn test_find_by_reference() {
+        let record = |id: i32, label: &str| CauseReasonRecord {
+            id,
+            label: label.to_string(),
+            description: String::new(),
+            is_hidden: false,
+            is_editable: false,
+        };
+        // A label that looks like an ID never shadows the ID itself
+        let records = vec![record(3, "Nobody Home"), record(100, "3")];
+
+        assert_eq!(find_by_reference(&records, "3").map(|cr| cr.id), Some(3));
+        assert_eq!(find_by_reference(&records, "Nobody Home").map(|cr| cr.id), Some(3));
+        assert_eq!(find_by_reference(&records, " 100 ").map(|cr| cr.id), Some(100));
+        assert!(find_by_reference(&records, "nobody home").is_none());
+        assert!(find_by_reference(&records, "42").is_none());
+        assert!(known_references(&records).contains("Nobody Home"));
+    }
 }
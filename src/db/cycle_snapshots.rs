@@ -0,0 +1,58 @@
+use crate::db::DbPool;
+
+use crate::types::db_types;
+
+/// Record the snapshot of a control cycle
+pub async fn insert(
+    pool: &DbPool,
+    device_identifier: &str,
+    evaluated_at: i64,
+    inputs: &str,
+    decision: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO cycle_snapshots (device_identifier, evaluated_at, inputs, decision)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(evaluated_at)
+    .bind(inputs)
+    .bind(decision)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the most recent snapshots of a device, newest first
+pub async fn get_recent(
+    pool: &DbPool,
+    device_identifier: &str,
+    limit: i64,
+) -> Result<Vec<db_types::CycleSnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::CycleSnapshot>(
+        r#"
+        SELECT * FROM cycle_snapshots
+        WHERE device_identifier = $1
+        ORDER BY evaluated_at DESC, id DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete a device's snapshots evaluated before `cutoff`
+/// Returns the number of deleted snapshots
+pub async fn delete_older_than(pool: &DbPool, device_identifier: &str, cutoff: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM cycle_snapshots WHERE device_identifier = $1 AND evaluated_at < $2")
+        .bind(device_identifier)
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
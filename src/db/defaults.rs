@@ -5,7 +5,7 @@
 //! on startup to ensure consistency.
 
 use rust_embed::Embed;
-use sqlx::SqlitePool;
+use crate::db::DbPool;
 
 /// Embedded default data files
 #[derive(Embed)]
@@ -23,7 +23,7 @@ const DEFAULT_NODESET_ID: i64 = 0;
 /// This function updates system cause_reasons and the default nodeset
 /// from embedded JSON files. System defaults are always updated to ensure
 /// consistency across versions.
-pub async fn initialize_defaults(pool: &SqlitePool) {
+pub async fn initialize_defaults(pool: &DbPool) {
     if let Err(e) = update_system_cause_reasons(pool).await {
         log::error!("Failed to update system cause_reasons: {}", e);
     }
@@ -35,7 +35,7 @@ pub async fn initialize_defaults(pool: &SqlitePool) {
 
 /// Update system cause_reasons with values from embedded JSON
 /// System cause_reasons (IDs 0-99) are always updated on startup
-async fn update_system_cause_reasons(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+async fn update_system_cause_reasons(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
     // Load the embedded JSON file
     let file = DefaultsAssets::get("cause_reasons.json")
         .ok_or("cause_reasons.json not found in embedded assets")?;
@@ -43,7 +43,7 @@ async fn update_system_cause_reasons(pool: &SqlitePool) -> Result<(), Box<dyn st
     let json_str = std::str::from_utf8(&file.data)?;
     let reasons: Vec<CauseReasonDefault> = serde_json::from_str(json_str)?;
 
-    // Update each system cause_reason using INSERT OR REPLACE
+    // Insert or update each system cause_reason
     for reason in &reasons {
         if reason.id > SYSTEM_CAUSE_REASON_MAX_ID {
             log::warn!(
@@ -55,7 +55,12 @@ async fn update_system_cause_reasons(pool: &SqlitePool) -> Result<(), Box<dyn st
         }
 
         sqlx::query(
-            "INSERT OR REPLACE INTO cause_reasons (id, label, description, is_hidden, is_editable) VALUES (?, ?, ?, ?, ?)"
+            r#"
+            INSERT INTO cause_reasons (id, label, description, is_hidden, is_editable) VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(id) DO UPDATE SET
+                label = excluded.label, description = excluded.description,
+                is_hidden = excluded.is_hidden, is_editable = excluded.is_editable
+            "#
         )
         .bind(reason.id)
         .bind(&reason.label)
@@ -74,7 +79,7 @@ async fn update_system_cause_reasons(pool: &SqlitePool) -> Result<(), Box<dyn st
 }
 
 /// Update the default nodeset (ID 0) with the embedded default profile
-async fn update_default_nodeset(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+async fn update_default_nodeset(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
     // Load the embedded JSON file
     let file = DefaultsAssets::get("default_nodeset.json")
         .ok_or("default_nodeset.json not found in embedded assets")?;
@@ -83,7 +88,7 @@ async fn update_default_nodeset(pool: &SqlitePool) -> Result<(), Box<dyn std::er
 
     // Check if the default nodeset exists and has user modifications
     let result: Option<(String,)> =
-        sqlx::query_as("SELECT node_json FROM nodesets WHERE id = ?")
+        sqlx::query_as("SELECT node_json FROM nodesets WHERE id = $1")
             .bind(DEFAULT_NODESET_ID)
             .fetch_optional(pool)
             .await?;
@@ -115,11 +120,16 @@ async fn update_default_nodeset(pool: &SqlitePool) -> Result<(), Box<dyn std::er
     }
 
     // Update or insert the default nodeset
-    sqlx::query("INSERT OR REPLACE INTO nodesets (id, name, node_json) VALUES (?, 'Default', ?)")
-        .bind(DEFAULT_NODESET_ID)
-        .bind(json_str)
-        .execute(pool)
-        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO nodesets (id, name, node_json) VALUES ($1, 'Default', $2)
+        ON CONFLICT(id) DO UPDATE SET name = excluded.name, node_json = excluded.node_json
+        "#,
+    )
+    .bind(DEFAULT_NODESET_ID)
+    .bind(json_str)
+    .execute(pool)
+    .await?;
 
     log::info!("Updated default nodeset with system defaults");
     Ok(())
@@ -0,0 +1,58 @@
+use crate::db::DbExecutor;
+use std::collections::HashMap;
+
+use crate::types::db_types;
+
+/// Get all device display names, sorted by device
+pub async fn get_all<'e, E>(executor: E) -> Result<Vec<db_types::DeviceAlias>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::DeviceAlias>("SELECT * FROM device_aliases ORDER BY device_identifier ASC")
+        .fetch_all(executor)
+        .await
+}
+
+/// Get the display names keyed by device identifier
+pub async fn get_display_names<'e, E>(executor: E) -> Result<HashMap<String, String>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    Ok(get_all(executor)
+        .await?
+        .into_iter()
+        .map(|alias| (alias.device_identifier, alias.display_name))
+        .collect())
+}
+
+/// Set the display name of a device, replacing an earlier one
+/// Fails with a unique violation if another device already uses the name (case-insensitive)
+pub async fn set<'e, E>(executor: E, device_identifier: &str, display_name: &str) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO device_aliases (device_identifier, display_name) VALUES ($1, $2)
+        ON CONFLICT(device_identifier) DO UPDATE SET display_name = excluded.display_name
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(display_name)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Remove the display name of a device so its identifier is shown again
+/// Returns false if the device had no display name
+pub async fn remove<'e, E>(executor: E, device_identifier: &str) -> Result<bool, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let result = sqlx::query("DELETE FROM device_aliases WHERE device_identifier = $1")
+        .bind(device_identifier)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
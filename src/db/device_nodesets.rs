@@ -0,0 +1,116 @@
+use crate::db::DbExecutor;
+
+use crate::types::db_types;
+
+/// Get all per-device nodeset assignments, sorted by device
+pub async fn get_all<'e, E>(executor: E) -> Result<Vec<db_types::DeviceNodeset>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::DeviceNodeset>("SELECT * FROM device_nodesets ORDER BY device_identifier ASC")
+        .fetch_all(executor)
+        .await
+}
+
+/// Get the nodeset assigned to a device, None if the device runs the active nodeset
+pub async fn get_for_device<'e, E>(executor: E, device_identifier: &str) -> Result<Option<i64>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let result = sqlx::query_as::<_, (i64,)>("SELECT nodeset_id FROM device_nodesets WHERE device_identifier = $1")
+        .bind(device_identifier)
+        .fetch_optional(executor)
+        .await?;
+    Ok(result.map(|(id,)| id))
+}
+
+/// Get the devices a nodeset is assigned to, sorted by device
+pub async fn get_devices_for_nodeset<'e, E>(executor: E, nodeset_id: i64) -> Result<Vec<String>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let rows = sqlx::query_as::<_, (String,)>(
+        "SELECT device_identifier FROM device_nodesets WHERE nodeset_id = $1 ORDER BY device_identifier ASC",
+    )
+    .bind(nodeset_id)
+    .fetch_all(executor)
+    .await?;
+    Ok(rows.into_iter().map(|(device,)| device).collect())
+}
+
+/// Assign a nodeset to a device, replacing an earlier assignment
+pub async fn assign<'e, E>(executor: E, device_identifier: &str, nodeset_id: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO device_nodesets (device_identifier, nodeset_id, assigned_at) VALUES ($1, $2, $3)
+        ON CONFLICT(device_identifier) DO UPDATE SET nodeset_id = excluded.nodeset_id, assigned_at = excluded.assigned_at
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(nodeset_id)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Remove the assignment of a device so it runs the active nodeset again
+/// Returns false if the device had no assignment
+pub async fn unassign<'e, E>(executor: E, device_identifier: &str) -> Result<bool, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let result = sqlx::query("DELETE FROM device_nodesets WHERE device_identifier = $1")
+        .bind(device_identifier)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the evaluation interval (minutes) of every device that has one, sorted by device
+pub async fn get_intervals<'e, E>(executor: E) -> Result<Vec<(String, i32)>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, (String, i32)>(
+        "SELECT device_identifier, evaluate_every_minutes FROM device_intervals ORDER BY device_identifier ASC",
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// Set the evaluation interval of a device, replacing an earlier one
+pub async fn set_interval<'e, E>(executor: E, device_identifier: &str, evaluate_every_minutes: i32) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO device_intervals (device_identifier, evaluate_every_minutes, updated_at) VALUES ($1, $2, $3)
+        ON CONFLICT(device_identifier) DO UPDATE SET
+            evaluate_every_minutes = excluded.evaluate_every_minutes, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(evaluate_every_minutes)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Remove the evaluation interval of a device so it follows the active nodeset again
+/// Returns false if the device had no interval
+pub async fn clear_interval<'e, E>(executor: E, device_identifier: &str) -> Result<bool, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let result = sqlx::query("DELETE FROM device_intervals WHERE device_identifier = $1")
+        .bind(device_identifier)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,105 @@
+use crate::db::DbExecutor;
+
+use crate::{ac_controller::ac_executor::AcState, types::Celsius};
+
+/// Row of the device_states table: device, is_on, mode, fan_speed, temperature, swing, vertical_vane, horizontal_vane,
+/// powerful_mode, updated_at
+type DeviceStateRow = (String, bool, Option<i32>, Option<i32>, Option<f64>, Option<i32>, Option<i32>, Option<i32>, bool, i64);
+
+/// Get the stored state of every device with the unix timestamp it was stored at
+pub async fn get_all<'e, E>(executor: E) -> Result<Vec<(String, AcState, i64)>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let rows = sqlx::query_as::<_, DeviceStateRow>(
+        "SELECT device_identifier, is_on, mode, fan_speed, temperature, swing, vertical_vane, horizontal_vane, powerful_mode, updated_at FROM device_states",
+    )
+    .fetch_all(executor)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(device, is_on, mode, fan_speed, temperature, swing, vertical_vane, horizontal_vane, powerful_mode, updated_at)| {
+            let state = AcState {
+                is_on,
+                mode,
+                fan_speed,
+                temperature: temperature.map(Celsius),
+                swing,
+                vertical_vane,
+                horizontal_vane,
+                powerful_mode,
+            };
+            (device, state, updated_at)
+        })
+        .collect())
+}
+
+/// Store the state of a device, replacing the earlier state
+pub async fn upsert<'e, E>(executor: E, device_identifier: &str, state: &AcState, updated_at: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO device_states (device_identifier, is_on, mode, fan_speed, temperature, swing, vertical_vane, horizontal_vane, powerful_mode, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT(device_identifier) DO UPDATE SET
+            is_on = excluded.is_on, mode = excluded.mode, fan_speed = excluded.fan_speed,
+            temperature = excluded.temperature, swing = excluded.swing,
+            vertical_vane = excluded.vertical_vane, horizontal_vane = excluded.horizontal_vane,
+            powerful_mode = excluded.powerful_mode, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(state.is_on)
+    .bind(state.mode)
+    .bind(state.fan_speed)
+    .bind(state.temperature.map(|t| t.0))
+    .bind(state.swing)
+    .bind(state.vertical_vane)
+    .bind(state.horizontal_vane)
+    .bind(state.powerful_mode)
+    .bind(updated_at)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Forget the stored state of a device, so it is undefined after a restart
+pub async fn delete<'e, E>(executor: E, device_identifier: &str) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query("DELETE FROM device_states WHERE device_identifier = $1")
+        .bind(device_identifier)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_upsert_and_delete() {
+        let pool = crate::db::connect_in_memory().await;
+        let heating = AcState::new_on(4, 0, Celsius(21.5), 1, true).with_vanes(Some(2), None);
+        upsert(&pool, "LivingRoom", &AcState::new_off(), 100).await.unwrap();
+        upsert(&pool, "LivingRoom", &heating, 200).await.unwrap();
+        upsert(&pool, "Veranda", &AcState::new_off(), 300).await.unwrap();
+
+        let mut states = get_all(&pool).await.unwrap();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            states,
+            vec![
+                ("LivingRoom".to_string(), heating, 200),
+                ("Veranda".to_string(), AcState::new_off(), 300),
+            ]
+        );
+
+        delete(&pool, "LivingRoom").await.unwrap();
+        assert_eq!(get_all(&pool).await.unwrap().len(), 1);
+    }
+}
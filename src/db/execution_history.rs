@@ -0,0 +1,112 @@
+use crate::db::DbPool;
+
+use crate::types::db_types;
+
+/// Record a nodeset execution, the id of the record is ignored
+pub async fn insert(pool: &DbPool, record: &db_types::ExecutionRecord) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO execution_history (device_identifier, executed_at, nodeset_id, terminal_type, action, cause_reason, error, inputs, warnings, is_shadow, nodeset_revision)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#,
+    )
+    .bind(&record.device_identifier)
+    .bind(record.executed_at)
+    .bind(record.nodeset_id)
+    .bind(&record.terminal_type)
+    .bind(&record.action)
+    .bind(&record.cause_reason)
+    .bind(&record.error)
+    .bind(&record.inputs)
+    .bind(&record.warnings)
+    .bind(record.is_shadow)
+    .bind(record.nodeset_revision)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record the read-back result of a command on the execution that sent it
+/// That is the device's latest non-shadow Execute Action recorded between `since` and `sent_at`
+/// Returns whether an execution was found, commands not sent by a nodeset have none
+pub async fn set_command_discrepancies(
+    pool: &DbPool,
+    device_identifier: &str,
+    since: i64,
+    sent_at: i64,
+    discrepancies: &[String],
+) -> Result<bool, sqlx::Error> {
+    let discrepancies = serde_json::to_string(discrepancies).unwrap_or_else(|_| "[]".to_string());
+    let result = sqlx::query(
+        r#"
+        UPDATE execution_history SET command_discrepancies = $1
+        WHERE id = (
+            SELECT id FROM execution_history
+            WHERE device_identifier = $2 AND executed_at >= $3 AND executed_at <= $4
+                AND terminal_type = 'Execute Action' AND is_shadow = FALSE
+            ORDER BY executed_at DESC, id DESC
+            LIMIT 1
+        )
+        "#,
+    )
+    .bind(discrepancies)
+    .bind(device_identifier)
+    .bind(since)
+    .bind(sent_at)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the most recent executions, newest first, of one device or of all devices
+pub async fn get_recent(
+    pool: &DbPool,
+    device_identifier: Option<&str>,
+    limit: i64,
+) -> Result<Vec<db_types::ExecutionRecord>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::ExecutionRecord>(
+        r#"
+        SELECT * FROM execution_history
+        WHERE $1 IS NULL OR device_identifier = $2
+        ORDER BY executed_at DESC, id DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(device_identifier)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the executions of a device recorded from `start` up to and including `end`, oldest first
+pub async fn get_range(
+    pool: &DbPool,
+    device_identifier: &str,
+    start: i64,
+    end: i64,
+) -> Result<Vec<db_types::ExecutionRecord>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::ExecutionRecord>(
+        r#"
+        SELECT * FROM execution_history
+        WHERE device_identifier = $1 AND executed_at >= $2 AND executed_at <= $3
+        ORDER BY executed_at ASC, id ASC
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete executions recorded before `cutoff`
+/// Returns the number of deleted executions
+pub async fn delete_older_than(pool: &DbPool, cutoff: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM execution_history WHERE executed_at < $1")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,79 @@
+use crate::db::{DbExecutor, DbPool};
+use std::collections::HashMap;
+
+/// Get the latched state of the Hysteresis nodes of a nodeset for a device, keyed by node id
+pub async fn get<'e, E>(executor: E, nodeset_id: i64, device_identifier: &str) -> Result<HashMap<String, bool>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let rows = sqlx::query_as::<_, (String, bool)>(
+        "SELECT node_id, is_on FROM hysteresis_state WHERE nodeset_id = $1 AND device_identifier = $2",
+    )
+    .bind(nodeset_id)
+    .bind(device_identifier)
+    .fetch_all(executor)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Replace the latched state of the Hysteresis nodes of a nodeset for a device
+/// Nodes missing from `state` are forgotten
+pub async fn replace(
+    pool: &DbPool,
+    nodeset_id: i64,
+    device_identifier: &str,
+    state: &HashMap<String, bool>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM hysteresis_state WHERE nodeset_id = $1 AND device_identifier = $2")
+        .bind(nodeset_id)
+        .bind(device_identifier)
+        .execute(&mut *tx)
+        .await?;
+    for (node_id, is_on) in state {
+        sqlx::query("INSERT INTO hysteresis_state (nodeset_id, device_identifier, node_id, is_on) VALUES ($1, $2, $3, $4)")
+            .bind(nodeset_id)
+            .bind(device_identifier)
+            .bind(node_id)
+            .bind(is_on)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+/// Remove the state of every device for a nodeset, e.g. when it is deleted
+pub async fn delete_for_nodeset<'e, E>(executor: E, nodeset_id: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query("DELETE FROM hysteresis_state WHERE nodeset_id = $1")
+        .bind(nodeset_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replace_and_get() {
+        let pool = crate::db::connect_in_memory().await;
+        let state: HashMap<String, bool> = [("hysteresis-1".to_string(), true), ("hysteresis-2".to_string(), false)]
+            .into_iter()
+            .collect();
+        replace(&pool, 1, "LivingRoom", &state).await.unwrap();
+        assert_eq!(get(&pool, 1, "LivingRoom").await.unwrap(), state);
+        assert!(get(&pool, 1, "Veranda").await.unwrap().is_empty(), "state is per device");
+
+        // Nodes missing from the new state are forgotten
+        let state: HashMap<String, bool> = [("hysteresis-1".to_string(), false)].into_iter().collect();
+        replace(&pool, 1, "LivingRoom", &state).await.unwrap();
+        assert_eq!(get(&pool, 1, "LivingRoom").await.unwrap(), state);
+
+        delete_for_nodeset(&pool, 1).await.unwrap();
+        assert!(get(&pool, 1, "LivingRoom").await.unwrap().is_empty());
+    }
+}
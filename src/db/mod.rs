@@ -1,52 +1,271 @@
 pub mod ac_actions;
 
+pub mod backup;
+
 pub mod cause_reasons;
 
+pub mod cycle_snapshots;
+
 pub mod defaults;
 
+pub mod device_aliases;
+
+pub mod device_nodesets;
+
+pub mod cooldown_state;
+
+pub mod device_states;
+
+pub mod execution_history;
+
+pub mod hysteresis_state;
+
+pub mod nodeset_revisions;
+
+pub mod nodeset_testcases;
+
 pub mod nodesets;
 
+pub mod occupancy_state;
+
+pub mod scheduled_activations;
+
+pub mod schedules;
+
+pub mod sensor_history;
+
+pub mod settings;
+
+pub mod statistics;
+
+pub mod weather_history;
+
 use crate::config;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::SqlitePool;
 use std::str::FromStr;
 use std::time::Duration;
 use tokio::sync::OnceCell;
 
-static POOL: OnceCell<SqlitePool> = OnceCell::const_new();
-
-pub async fn get_pool() -> &'static SqlitePool {
-    POOL.get_or_init(|| async {
-        let cfg = config::get_config();
-        // Ensure the directory for the database file exists
-        if let Some(parent) = std::path::Path::new(&cfg.database_path).parent() {
-            tokio::fs::create_dir_all(parent)
-                .await
-                .expect("Failed to create directory directory");
-        }
-        // Check if we have access to database file
-        tokio::fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(&cfg.database_path)
-            .await
-            .expect("Insufficient permissions to access database file");
-
-        // Create connection options with extended timeouts for slow devices
-        let conn_str = format!("sqlite://{}", cfg.database_path);
-        let connect_options = SqliteConnectOptions::from_str(&conn_str)
-            .expect("Invalid database connection string")
-            .busy_timeout(Duration::from_secs(30)) // Wait up to 30 seconds if database is locked
-            .create_if_missing(true);
-
-        // Create connection pool with appropriate settings
-        SqlitePoolOptions::new()
-            .max_connections(5)
-            .acquire_timeout(Duration::from_secs(30)) // Wait up to 30 seconds to acquire a connection
-            .connect_with(connect_options)
+#[cfg(not(feature = "postgres"))]
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+#[cfg(feature = "postgres")]
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+/// Database backend of this build: SQLite, or PostgreSQL when built with the `postgres` feature.
+/// Queries are written for both, with `$1` style placeholders.
+#[cfg(not(feature = "postgres"))]
+pub type Db = sqlx::Sqlite;
+#[cfg(feature = "postgres")]
+pub type Db = sqlx::Postgres;
+
+pub type DbPool = sqlx::Pool<Db>;
+
+pub type DbConnection = <Db as sqlx::Database>::Connection;
+
+/// A pool, connection or transaction of the database backend
+pub trait DbExecutor<'c>: sqlx::Executor<'c, Database = Db> {}
+
+impl<'c, T: sqlx::Executor<'c, Database = Db>> DbExecutor<'c> for T {}
+
+/// Migrations of the database backend, a change to the schema needs a migration for each
+#[cfg(not(feature = "postgres"))]
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+#[cfg(feature = "postgres")]
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations/postgres");
+
+static POOL: OnceCell<DbPool> = OnceCell::const_new();
+
+pub async fn get_pool() -> &'static DbPool {
+    POOL.get_or_init(|| async { connect(&config::get_config()).await }).await
+}
+
+#[cfg(not(feature = "postgres"))]
+async fn connect(cfg: &crate::types::Config) -> DbPool {
+    if !cfg.database_url.is_empty() {
+        log::warn!("database_url is ignored, this build only supports SQLite (build with the postgres feature)");
+    }
+    // Ensure the directory for the database file exists
+    if let Some(parent) = std::path::Path::new(&cfg.database_path).parent() {
+        tokio::fs::create_dir_all(parent)
             .await
-            .expect("Failed to create database pool")
-    })
-    .await
+            .expect("Failed to create directory directory");
+    }
+    // Check if we have access to database file
+    tokio::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(&cfg.database_path)
+        .await
+        .expect("Insufficient permissions to access database file");
+
+    // Create connection options with extended timeouts for slow devices
+    let conn_str = format!("sqlite://{}", cfg.database_path);
+    let connect_options = SqliteConnectOptions::from_str(&conn_str)
+        .expect("Invalid database connection string")
+        .busy_timeout(Duration::from_secs(30)) // Wait up to 30 seconds if database is locked
+        .create_if_missing(true);
+
+    // Create connection pool with appropriate settings
+    SqlitePoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(Duration::from_secs(30)) // Wait up to 30 seconds to acquire a connection
+        .connect_with(connect_options)
+        .await
+        .expect("Failed to create database pool")
+}
+
+#[cfg(feature = "postgres")]
+async fn connect(cfg: &crate::types::Config) -> DbPool {
+    if cfg.database_url.is_empty() {
+        panic!("database_url must be set, this build uses PostgreSQL");
+    }
+    let connect_options = PgConnectOptions::from_str(&cfg.database_url).expect("Invalid database_url");
+    PgPoolOptions::new()
+        .max_connections(10)
+        .acquire_timeout(Duration::from_secs(30))
+        .connect_with(connect_options)
+        .await
+        .expect("Failed to connect to PostgreSQL")
+}
+
+/// Create a fresh in-memory database with migrations and defaults applied.
+/// Every call returns an independent database, so tests using it can run in parallel.
+#[cfg(all(test, not(feature = "postgres")))]
+pub async fn connect_in_memory() -> DbPool {
+    // Each in-memory connection is its own database, so keep exactly one that never expires
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to create in-memory database");
+    MIGRATOR
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations on in-memory database");
+    defaults::initialize_defaults(&pool).await;
+    pool
+}
+
+/// Create a fresh schema in the PostgreSQL database of `PCC_TEST_DATABASE_URL` with migrations
+/// and defaults applied. Every call returns an independent schema, so tests using it can run in parallel.
+#[cfg(all(test, feature = "postgres"))]
+pub async fn connect_in_memory() -> DbPool {
+    use sqlx::{ConnectOptions, Connection};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT_SCHEMA: AtomicUsize = AtomicUsize::new(0);
+
+    let url = std::env::var("PCC_TEST_DATABASE_URL")
+        .expect("PCC_TEST_DATABASE_URL must point to a PostgreSQL database to test the postgres feature");
+    let options = PgConnectOptions::from_str(&url).expect("Invalid PCC_TEST_DATABASE_URL");
+    let schema = format!("pcc_test_{}_{}", std::process::id(), NEXT_SCHEMA.fetch_add(1, Ordering::Relaxed));
+
+    let mut conn = options.connect().await.expect("Failed to connect to the test database");
+    sqlx::query(&format!("CREATE SCHEMA {}", schema))
+        .execute(&mut conn)
+        .await
+        .expect("Failed to create test schema");
+    let _ = conn.close().await;
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .acquire_timeout(Duration::from_secs(30))
+        .connect_with(options.options([("search_path", schema.as_str())]))
+        .await
+        .expect("Failed to create test database pool");
+    MIGRATOR
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations on test schema");
+    defaults::initialize_defaults(&pool).await;
+    pool
+}
+
+/// Create a database file at `path` with migrations and defaults applied, replacing any existing one.
+/// For tests that need a database on disk, e.g. `VACUUM INTO` doesn't write files from in-memory ones.
+#[cfg(all(test, not(feature = "postgres")))]
+pub async fn connect_file(path: &std::path::Path) -> DbPool {
+    let _ = std::fs::remove_file(path);
+    let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .expect("Failed to create database file");
+    MIGRATOR
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations on database file");
+    defaults::initialize_defaults(&pool).await;
+    pool
+}
+
+/// Begin a transaction for reading and then writing, that fails or waits rather than
+/// interleaving with another one.
+/// SQLite: a deferred transaction that reads before writing can fail with SQLITE_BUSY when another
+/// connection writes first; taking the lock up front makes concurrent writers wait instead.
+/// PostgreSQL: the transaction is serializable, a conflicting one fails as a lock conflict.
+pub async fn begin_write(pool: &DbPool) -> Result<sqlx::Transaction<'static, Db>, sqlx::Error> {
+    #[cfg(not(feature = "postgres"))]
+    let statement = "BEGIN IMMEDIATE";
+    #[cfg(feature = "postgres")]
+    let statement = "BEGIN ISOLATION LEVEL SERIALIZABLE";
+    pool.begin_with(statement).await
+}
+
+/// Check if an error is the database refusing a write because of a concurrent one:
+/// SQLite reporting the database as busy or locked, or a PostgreSQL serialization failure or deadlock
+#[cfg(not(feature = "postgres"))]
+pub fn is_lock_conflict(e: &sqlx::Error) -> bool {
+    const SQLITE_BUSY: i32 = 5;
+    const SQLITE_LOCKED: i32 = 6;
+
+    e.as_database_error()
+        .and_then(|db_err| db_err.code())
+        .and_then(|code| code.parse::<i32>().ok())
+        // Extended result codes keep the primary code in the lowest byte
+        .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED))
+}
+
+#[cfg(feature = "postgres")]
+pub fn is_lock_conflict(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .and_then(|db_err| db_err.code())
+        .is_some_and(|code| matches!(code.as_ref(), "40001" | "40P01" | "55P03"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "postgres"))]
+    #[tokio::test]
+    async fn test_begin_write_blocks_second_writer() {
+        // In-memory databases aren't shared across connections, so use a temp file
+        let path = std::env::temp_dir().join(format!("pcc_begin_write_{}.db", std::process::id()));
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .busy_timeout(Duration::ZERO);
+        let pool = SqlitePoolOptions::new().max_connections(2).connect_with(options).await.unwrap();
+
+        let first = begin_write(&pool).await.unwrap();
+        let second = begin_write(&pool).await;
+        assert!(is_lock_conflict(&second.unwrap_err()));
+
+        first.rollback().await.unwrap();
+        assert!(begin_write(&pool).await.is_ok());
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_non_database_error_is_not_lock_conflict() {
+        assert!(!is_lock_conflict(&sqlx::Error::RowNotFound));
+    }
 }
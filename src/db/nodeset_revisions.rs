@@ -0,0 +1,118 @@
+use crate::db::{DbConnection, DbExecutor};
+
+use crate::types::db_types;
+
+/// Record the stored content of a nodeset as its next revision and make that the current revision
+/// Revisions beyond the newest `keep` are deleted
+/// Returns the new revision number
+pub async fn record(conn: &mut DbConnection, nodeset_id: i64, keep: u32, now: i64) -> Result<i64, sqlx::Error> {
+    let (revision,) = sqlx::query_as::<_, (i64,)>(
+        "SELECT COALESCE(MAX(revision), 0) + 1 FROM nodeset_revisions WHERE nodeset_id = $1",
+    )
+    .bind(nodeset_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO nodeset_revisions (nodeset_id, revision, node_json, created_at)
+        SELECT id, $1, node_json, $2 FROM nodesets WHERE id = $3
+        "#,
+    )
+    .bind(revision)
+    .bind(now)
+    .bind(nodeset_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("UPDATE nodesets SET revision = $1 WHERE id = $2")
+        .bind(revision)
+        .bind(nodeset_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("DELETE FROM nodeset_revisions WHERE nodeset_id = $1 AND revision <= $2")
+        .bind(nodeset_id)
+        .bind(revision - i64::from(keep.max(1)))
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(revision)
+}
+
+/// Get the kept revisions of a nodeset, newest first
+pub async fn get_all<'e, E>(executor: E, nodeset_id: i64) -> Result<Vec<db_types::NodesetRevision>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::NodesetRevision>(
+        "SELECT revision, created_at FROM nodeset_revisions WHERE nodeset_id = $1 ORDER BY revision DESC",
+    )
+    .bind(nodeset_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Get the stored nodeset JSON of a revision, None if it doesn't exist or is no longer kept
+pub async fn get_node_json<'e, E>(executor: E, nodeset_id: i64, revision: i64) -> Result<Option<String>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let row = sqlx::query_as::<_, (String,)>(
+        "SELECT node_json FROM nodeset_revisions WHERE nodeset_id = $1 AND revision = $2",
+    )
+    .bind(nodeset_id)
+    .bind(revision)
+    .fetch_optional(executor)
+    .await?;
+    Ok(row.map(|(node_json,)| node_json))
+}
+
+/// Delete every revision of a nodeset
+pub async fn delete_all<'e, E>(executor: E, nodeset_id: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query("DELETE FROM nodeset_revisions WHERE nodeset_id = $1")
+        .bind(nodeset_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_keeps_the_newest_revisions() {
+        let pool = crate::db::connect_in_memory().await;
+        let mut conn = pool.acquire().await.unwrap();
+        let (id,) = sqlx::query_as::<_, (i64,)>("INSERT INTO nodesets (name, node_json) VALUES ('Night', '{}') RETURNING id")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+
+        for (i, now) in (1000..1004).enumerate() {
+            sqlx::query("UPDATE nodesets SET node_json = $1 WHERE id = $2")
+                .bind(format!("{{\"version\": {}}}", i))
+                .bind(id)
+                .execute(&mut *conn)
+                .await
+                .unwrap();
+            record(&mut conn, id, 3, now).await.unwrap();
+        }
+
+        let revisions: Vec<i64> = get_all(&mut *conn, id).await.unwrap().iter().map(|r| r.revision).collect();
+        assert_eq!(revisions, vec![4, 3, 2]);
+        assert_eq!(get_node_json(&mut *conn, id, 3).await.unwrap().as_deref(), Some("{\"version\": 2}"));
+        assert_eq!(get_node_json(&mut *conn, id, 1).await.unwrap(), None);
+
+        let (current,) = sqlx::query_as::<_, (i64,)>("SELECT revision FROM nodesets WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(current, 4);
+    }
+}
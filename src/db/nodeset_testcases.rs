@@ -0,0 +1,73 @@
+use crate::db::DbExecutor;
+
+use crate::types::db_types;
+
+/// Content of a test case as set through the API
+pub struct TestCaseFields<'a> {
+    pub nodeset_id: i64,
+    pub name: &'a str,
+    pub inputs: &'a str,
+    pub expected_terminal: &'a str,
+    pub expected_action: Option<&'a str>,
+}
+
+/// Get the test cases of a nodeset, sorted by id
+pub async fn get_for_nodeset<'e, E>(executor: E, nodeset_id: i64) -> Result<Vec<db_types::NodesetTestCase>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::NodesetTestCase>(
+        "SELECT * FROM nodeset_testcases WHERE nodeset_id = $1 ORDER BY id ASC",
+    )
+    .bind(nodeset_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Create a test case
+/// Returns the created test case
+pub async fn insert<'e, E>(executor: E, fields: &TestCaseFields<'_>) -> Result<db_types::NodesetTestCase, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::NodesetTestCase>(
+        r#"
+        INSERT INTO nodeset_testcases (nodeset_id, name, inputs, expected_terminal, expected_action, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(fields.nodeset_id)
+    .bind(fields.name)
+    .bind(fields.inputs)
+    .bind(fields.expected_terminal)
+    .bind(fields.expected_action)
+    .bind(chrono::Utc::now().timestamp())
+    .fetch_one(executor)
+    .await
+}
+
+/// Delete a test case
+/// Returns true if it existed
+pub async fn delete<'e, E>(executor: E, id: i64) -> Result<bool, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let result = sqlx::query("DELETE FROM nodeset_testcases WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete every test case of a nodeset
+pub async fn delete_all<'e, E>(executor: E, nodeset_id: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query("DELETE FROM nodeset_testcases WHERE nodeset_id = $1")
+        .bind(nodeset_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
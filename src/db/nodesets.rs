@@ -3,7 +3,12 @@
 //! This module provides functions to query nodeset data from the database,
 //! particularly for extracting configuration values like evaluate_every_minutes.
 
-use crate::nodes::flow_nodes::MAX_EVALUATE_EVERY_MINUTES;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::db::DbExecutor;
+
+use crate::nodes::{calls::{self, CallLibrary, CallableNodeset}, flow_nodes::MAX_EVALUATE_EVERY_MINUTES, schema, variables};
 
 /// Default evaluation interval in minutes if not specified in the nodeset
 /// This matches the original hardcoded 5-minute interval
@@ -12,6 +17,24 @@ pub const DEFAULT_EVALUATE_EVERY_MINUTES: i32 = 5;
 /// ID for the default nodeset that is loaded when no other nodeset is active
 const DEFAULT_NODESET_ID: i64 = 0;
 
+/// SQL expression of the `variables` of a stored nodeset as JSON text,
+/// NULL for nodesets saved before variables existed
+#[cfg(not(feature = "postgres"))]
+pub const VARIABLES_SQL: &str = "json_extract(node_json, '$.variables')";
+#[cfg(feature = "postgres")]
+pub const VARIABLES_SQL: &str = "(node_json::jsonb -> 'variables')::text";
+
+/// SQL expression of the nodeset JSON `node_json` with its `variables` replaced by the JSON `variables`
+#[cfg(not(feature = "postgres"))]
+pub fn with_variables_sql(node_json: &str, variables: &str) -> String {
+    format!("json_set({}, '$.variables', json({}))", node_json, variables)
+}
+
+#[cfg(feature = "postgres")]
+pub fn with_variables_sql(node_json: &str, variables: &str) -> String {
+    format!("jsonb_set(({})::jsonb, '{{variables}}', ({})::jsonb)::text", node_json, variables)
+}
+
 /// Extract the evaluate_every_minutes value from a list of node JSON values
 /// 
 /// This function finds the Start node and extracts its primitiveValue,
@@ -84,7 +107,7 @@ pub async fn get_evaluate_every_minutes() -> i32 {
     
     // Fetch the nodeset JSON
     let result = sqlx::query_as::<_, (String,)>(
-        "SELECT node_json FROM nodesets WHERE id = ?"
+        "SELECT node_json FROM nodesets WHERE id = $1"
     )
     .bind(active_id)
     .fetch_optional(pool)
@@ -105,8 +128,176 @@ pub async fn get_evaluate_every_minutes() -> i32 {
     }
 }
 
+/// Upgrade all stored nodesets to the current schema version
+///
+/// Nodesets saved by a newer version are left untouched and will be refused when loaded.
+pub async fn migrate_stored_nodesets(pool: &crate::db::DbPool) {
+    let rows = match sqlx::query_as::<_, (i64, String)>("SELECT id, node_json FROM nodesets")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Failed to fetch nodesets for schema migration: {}", e);
+            return;
+        }
+    };
+
+    for (id, node_json) in rows {
+        let mut doc: serde_json::Value = match serde_json::from_str(&node_json) {
+            Ok(doc) => doc,
+            Err(e) => {
+                log::warn!("Skipping schema migration of nodeset {}: {}", id, e);
+                continue;
+            }
+        };
+
+        match schema::migrate_nodeset(&mut doc) {
+            Ok(false) => {}
+            Ok(true) => {
+                let result = sqlx::query("UPDATE nodesets SET node_json = $1 WHERE id = $2")
+                    .bind(doc.to_string())
+                    .bind(id)
+                    .execute(pool)
+                    .await;
+                match result {
+                    Ok(_) => log::info!(
+                        "Nodeset {} upgraded to schema version {}",
+                        id, schema::CURRENT_SCHEMA_VERSION
+                    ),
+                    Err(e) => log::error!("Failed to store upgraded nodeset {}: {}", id, e),
+                }
+            }
+            Err(e) => log::warn!("Skipping schema migration of nodeset {}: {}", id, e),
+        }
+    }
+}
+
+/// Stored nodesets with their id and name, parsed and upgraded to the current schema version
+/// Nodesets that fail to parse are logged and left out
+async fn load_documents<'e, E>(executor: E) -> Result<Vec<(i64, String, serde_json::Value)>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let rows = sqlx::query_as::<_, (i64, String, String)>("SELECT id, name, node_json FROM nodesets")
+        .fetch_all(executor)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, name, node_json)| match schema::parse_nodeset_json(&node_json) {
+            Ok(doc) => Some((id, name, doc)),
+            Err(e) => {
+                log::warn!("Skipping nodeset {} for profile calls: {}", id, e);
+                None
+            }
+        })
+        .collect())
+}
+
+fn document_array(doc: &serde_json::Value, key: &str) -> Vec<serde_json::Value> {
+    doc.get(key).and_then(|v| v.as_array()).cloned().unwrap_or_default()
+}
+
+/// A parsed callable nodeset with the revision and stored JSON it was parsed from
+struct CachedCallable {
+    revision: i64,
+    node_json: String,
+    nodeset: CallableNodeset,
+}
+
+/// Callable nodesets by id, so evaluations only parse the nodesets saved since the last one
+static CALL_LIBRARY_CACHE: OnceLock<Mutex<HashMap<i64, CachedCallable>>> = OnceLock::new();
+
+/// Load every stored nodeset with its variables resolved, for expanding Call Profile nodes
+/// Nodesets are only parsed again when their revision changed. The stored JSON is compared as
+/// well, since ids and revisions repeat across databases, e.g. the in-memory ones of tests.
+pub async fn load_call_library<'e, E>(executor: E) -> Result<CallLibrary, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let rows = sqlx::query_as::<_, (i64, i64, String)>("SELECT id, revision, node_json FROM nodesets")
+        .fetch_all(executor)
+        .await?;
+
+    let mut cache = CALL_LIBRARY_CACHE.get_or_init(Mutex::default).lock().unwrap();
+    let mut library = CallLibrary::new();
+    for (id, revision, node_json) in rows {
+        let cached = cache.get(&id).filter(|c| c.revision == revision && c.node_json == node_json);
+        if let Some(cached) = cached {
+            library.insert(id, cached.nodeset.clone());
+            continue;
+        }
+
+        let mut doc = match schema::parse_nodeset_json(&node_json) {
+            Ok(doc) => doc,
+            Err(e) => {
+                log::warn!("Skipping nodeset {} for profile calls: {}", id, e);
+                cache.remove(&id);
+                continue;
+            }
+        };
+        variables::resolve_document(&mut doc);
+        let nodeset = CallableNodeset {
+            nodes: document_array(&doc, "nodes"),
+            edges: document_array(&doc, "edges"),
+        };
+        library.insert(id, nodeset.clone());
+        cache.insert(id, CachedCallable { revision, node_json, nodeset });
+    }
+    cache.retain(|id, _| library.contains_key(id));
+    Ok(library)
+}
+
+/// Expand the Call Profile nodes of a nodeset, loading the nodesets it calls
+/// `nodeset_id` is the id of the nodeset if it is saved. Nodesets without calls are returned
+/// unchanged without a query.
+pub async fn expand_calls<'e, E>(
+    executor: E,
+    nodeset_id: Option<i64>,
+    nodes: Vec<serde_json::Value>,
+    edges: Vec<serde_json::Value>,
+) -> Result<(Vec<serde_json::Value>, Vec<serde_json::Value>), String>
+where
+    E: DbExecutor<'e>,
+{
+    if !calls::has_calls(&nodes) {
+        return Ok((nodes, edges));
+    }
+    let library = load_call_library(executor)
+        .await
+        .map_err(|e| format!("Failed to load called profiles: {}", e))?;
+    calls::expand_calls(nodeset_id, &nodes, &edges, &library)
+}
+
+/// The other nodesets with a Call Profile node that calls a nodeset, with their name
+pub async fn get_callers<'e, E>(executor: E, nodeset_id: i64) -> Result<Vec<(String, CallableNodeset)>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    Ok(load_documents(executor)
+        .await?
+        .into_iter()
+        .filter(|(id, _, doc)| *id != nodeset_id && calls::called_nodeset_ids(&document_array(doc, "nodes")).contains(&nodeset_id))
+        .map(|(_, name, doc)| {
+            let nodeset = CallableNodeset {
+                nodes: document_array(&doc, "nodes"),
+                edges: document_array(&doc, "edges"),
+            };
+            (name, nodeset)
+        })
+        .collect())
+}
+
+/// Names of the other nodesets with a Call Profile node that calls a nodeset
+pub async fn get_caller_names<'e, E>(executor: E, nodeset_id: i64) -> Result<Vec<String>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    Ok(get_callers(executor, nodeset_id).await?.into_iter().map(|(name, _)| name).collect())
+}
+
 /// Helper function to get the active nodeset ID from the database
-async fn get_active_nodeset_id(pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Error> {
+async fn get_active_nodeset_id(pool: &crate::db::DbPool) -> Result<i64, sqlx::Error> {
     let result = sqlx::query_as::<_, (String,)>(
         "SELECT setting_value FROM settings WHERE setting_key = 'active_nodeset'"
     )
@@ -130,11 +321,11 @@ async fn get_active_nodeset_id(pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Err
 /// Extract the evaluate_every_minutes value from nodeset JSON
 /// Returns the default value if extraction fails
 fn extract_evaluate_minutes_from_json(node_json: &str) -> i32 {
-    // Parse the JSON
-    let parsed: serde_json::Value = match serde_json::from_str(node_json) {
+    // Parse the JSON, upgrading it to the current schema version
+    let parsed = match schema::parse_nodeset_json(node_json) {
         Ok(v) => v,
         Err(e) => {
-            log::warn!("Failed to parse nodeset JSON: {}. Using default interval.", e);
+            log::warn!("Failed to load nodeset: {}. Using default interval.", e);
             return DEFAULT_EVALUATE_EVERY_MINUTES;
         }
     };
@@ -0,0 +1,32 @@
+use crate::db::DbExecutor;
+
+/// Get the stored occupancy evidence of every room with the unix timestamp of its last event
+pub async fn get_all<'e, E>(executor: E) -> Result<Vec<(String, f64, i64)>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, (String, f64, i64)>("SELECT device_identifier, evidence, updated_at FROM occupancy_state")
+        .fetch_all(executor)
+        .await
+}
+
+/// Store the occupancy evidence of a room, replacing the earlier evidence
+pub async fn upsert<'e, E>(executor: E, device_identifier: &str, evidence: f64, updated_at: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO occupancy_state (device_identifier, evidence, updated_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(device_identifier) DO UPDATE SET
+            evidence = excluded.evidence, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(device_identifier)
+    .bind(evidence)
+    .bind(updated_at)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
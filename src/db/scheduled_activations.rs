@@ -0,0 +1,93 @@
+use crate::db::{DbExecutor, DbPool};
+
+use crate::types::db_types;
+
+/// Activation is waiting for its time
+pub const STATUS_PENDING: &str = "pending";
+/// Nodeset was activated
+pub const STATUS_DONE: &str = "done";
+/// Activation was attempted but the nodeset could not be activated
+pub const STATUS_FAILED: &str = "failed";
+/// Activation was cancelled before its time
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+/// Queue a nodeset activation
+/// Returns the created activation
+pub async fn insert(
+    pool: &DbPool,
+    nodeset_id: i64,
+    activate_at: i64,
+    revert_after_minutes: Option<i64>,
+) -> Result<db_types::ScheduledActivation, sqlx::Error> {
+    sqlx::query_as::<_, db_types::ScheduledActivation>(
+        r#"
+        INSERT INTO scheduled_activations (nodeset_id, activate_at, revert_after_minutes, status, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(nodeset_id)
+    .bind(activate_at)
+    .bind(revert_after_minutes)
+    .bind(STATUS_PENDING)
+    .bind(chrono::Utc::now().timestamp())
+    .fetch_one(pool)
+    .await
+}
+
+/// Get all pending activations, soonest first
+pub async fn get_pending(pool: &DbPool) -> Result<Vec<db_types::ScheduledActivation>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::ScheduledActivation>(
+        "SELECT * FROM scheduled_activations WHERE status = $1 ORDER BY activate_at ASC, id ASC",
+    )
+    .bind(STATUS_PENDING)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get pending activations that are due at `now`, oldest first
+pub async fn get_due(pool: &DbPool, now: i64) -> Result<Vec<db_types::ScheduledActivation>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::ScheduledActivation>(
+        "SELECT * FROM scheduled_activations WHERE status = $1 AND activate_at <= $2 ORDER BY activate_at ASC, id ASC",
+    )
+    .bind(STATUS_PENDING)
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+/// Set the outcome of an activation
+pub async fn set_status(pool: &DbPool, id: i64, status: &str, error: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE scheduled_activations SET status = $1, error = $2 WHERE id = $3")
+        .bind(status)
+        .bind(error)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Remove the pending activations of a nodeset, e.g. when it is deleted
+pub async fn delete_pending_for_nodeset<'e, E>(executor: E, nodeset_id: i64) -> Result<(), sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query("DELETE FROM scheduled_activations WHERE nodeset_id = $1 AND status = $2")
+        .bind(nodeset_id)
+        .bind(STATUS_PENDING)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Cancel a pending activation
+/// Returns false if there is no pending activation with this id
+pub async fn cancel(pool: &DbPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE scheduled_activations SET status = $1 WHERE id = $2 AND status = $3")
+        .bind(STATUS_CANCELLED)
+        .bind(id)
+        .bind(STATUS_PENDING)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,103 @@
+use crate::db::DbExecutor;
+
+use crate::types::db_types;
+
+/// Content of a schedule as set through the API
+pub struct ScheduleFields<'a> {
+    pub name: &'a str,
+    pub nodeset_id: i64,
+    pub device_identifier: Option<&'a str>,
+    pub days: &'a str,
+    pub start_time: &'a str,
+    pub end_time: &'a str,
+    pub priority: i64,
+    pub enabled: bool,
+}
+
+/// Get all schedules, sorted by id
+pub async fn get_all<'e, E>(executor: E) -> Result<Vec<db_types::Schedule>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::Schedule>("SELECT * FROM schedules ORDER BY id ASC")
+        .fetch_all(executor)
+        .await
+}
+
+/// Create a schedule
+/// Returns the created schedule
+pub async fn insert<'e, E>(executor: E, fields: &ScheduleFields<'_>) -> Result<db_types::Schedule, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::Schedule>(
+        r#"
+        INSERT INTO schedules (name, nodeset_id, device_identifier, days, start_time, end_time, priority, enabled, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(fields.name)
+    .bind(fields.nodeset_id)
+    .bind(fields.device_identifier)
+    .bind(fields.days)
+    .bind(fields.start_time)
+    .bind(fields.end_time)
+    .bind(fields.priority)
+    .bind(fields.enabled)
+    .bind(chrono::Utc::now().timestamp())
+    .fetch_one(executor)
+    .await
+}
+
+/// Replace the content of a schedule
+/// Returns the updated schedule, None if it doesn't exist
+pub async fn update<'e, E>(executor: E, id: i64, fields: &ScheduleFields<'_>) -> Result<Option<db_types::Schedule>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    sqlx::query_as::<_, db_types::Schedule>(
+        r#"
+        UPDATE schedules
+        SET name = $1, nodeset_id = $2, device_identifier = $3, days = $4, start_time = $5, end_time = $6, priority = $7, enabled = $8
+        WHERE id = $9
+        RETURNING *
+        "#,
+    )
+    .bind(fields.name)
+    .bind(fields.nodeset_id)
+    .bind(fields.device_identifier)
+    .bind(fields.days)
+    .bind(fields.start_time)
+    .bind(fields.end_time)
+    .bind(fields.priority)
+    .bind(fields.enabled)
+    .bind(id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Delete a schedule
+/// Returns false if it doesn't exist
+pub async fn delete<'e, E>(executor: E, id: i64) -> Result<bool, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let result = sqlx::query("DELETE FROM schedules WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the names of the schedules that switch a nodeset in, sorted by id
+pub async fn get_names_for_nodeset<'e, E>(executor: E, nodeset_id: i64) -> Result<Vec<String>, sqlx::Error>
+where
+    E: DbExecutor<'e>,
+{
+    let rows = sqlx::query_as::<_, (String,)>("SELECT name FROM schedules WHERE nodeset_id = $1 ORDER BY id ASC")
+        .bind(nodeset_id)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
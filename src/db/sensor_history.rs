@@ -0,0 +1,106 @@
+use crate::db::DbPool;
+
+/// A sensor reading recorded in the telemetry table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    pub metric: &'static str,
+    /// Empty for household metrics
+    pub device_identifier: String,
+    pub value: f64,
+}
+
+/// Store the readings of one sample as raw rows
+pub async fn insert_raw(pool: &DbPool, timestamp: i64, readings: &[Reading]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for reading in readings {
+        sqlx::query(
+            r#"
+            INSERT INTO telemetry (metric, device_identifier, resolution, timestamp, value, samples)
+            VALUES ($1, $2, 'raw', $3, $4, 1)
+            ON CONFLICT(metric, device_identifier, resolution, timestamp) DO UPDATE SET value = excluded.value, samples = excluded.samples
+            "#,
+        )
+        .bind(reading.metric)
+        .bind(&reading.device_identifier)
+        .bind(timestamp)
+        .bind(reading.value)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Average the rows of `from` resolution in [start, end) into periods of `period_secs` with resolution `to`
+/// Periods that were downsampled before are replaced, so recent periods can be recomputed
+/// Returns the number of periods written
+pub async fn downsample(
+    pool: &DbPool,
+    from: &str,
+    to: &str,
+    period_secs: i64,
+    start: i64,
+    end: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO telemetry (metric, device_identifier, resolution, timestamp, value, samples)
+        SELECT metric, device_identifier, $1, timestamp - timestamp % $2, SUM(value * samples) / SUM(samples), SUM(samples)
+        FROM telemetry
+        WHERE resolution = $3 AND timestamp >= $4 AND timestamp < $5
+        GROUP BY metric, device_identifier, timestamp - timestamp % $2
+        ON CONFLICT(metric, device_identifier, resolution, timestamp) DO UPDATE SET value = excluded.value, samples = excluded.samples
+        "#,
+    )
+    .bind(to)
+    .bind(period_secs)
+    .bind(from)
+    .bind(start)
+    .bind(end)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete the rows of a resolution older than `before`
+/// Returns the number of rows deleted
+pub async fn delete_before(pool: &DbPool, resolution: &str, before: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM telemetry WHERE resolution = $1 AND timestamp < $2")
+        .bind(resolution)
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete the rows of every resolution older than `before`
+/// Returns the number of rows deleted
+pub async fn delete_all_before(pool: &DbPool, before: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM telemetry WHERE timestamp < $1")
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Get the (timestamp, value) points of a metric at a resolution from `start` on, oldest first
+pub async fn get_points(
+    pool: &DbPool,
+    metric: &str,
+    device_identifier: &str,
+    resolution: &str,
+    start: i64,
+) -> Result<Vec<(i64, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (i64, f64)>(
+        r#"
+        SELECT timestamp, value FROM telemetry
+        WHERE metric = $1 AND device_identifier = $2 AND resolution = $3 AND timestamp >= $4
+        ORDER BY timestamp ASC
+        "#,
+    )
+    .bind(metric)
+    .bind(device_identifier)
+    .bind(resolution)
+    .bind(start)
+    .fetch_all(pool)
+    .await
+}
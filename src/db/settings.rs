@@ -0,0 +1,23 @@
+use crate::db::DbPool;
+
+/// Get a setting value, None if it was never stored
+pub async fn get(pool: &DbPool, key: &str) -> Result<Option<String>, sqlx::Error> {
+    let result = sqlx::query_as::<_, (String,)>("SELECT setting_value FROM settings WHERE setting_key = $1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(result.map(|(value,)| value))
+}
+
+/// Store a setting value, replacing the previous value
+pub async fn set(pool: &DbPool, key: &str, value: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO settings (setting_key, setting_value) VALUES ($1, $2)
+         ON CONFLICT(setting_key) DO UPDATE SET setting_value = excluded.setting_value",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use crate::db::DbPool;
+
+use crate::types::db_types;
+
+/// Settings key of the end of the last rolled up hour
+const ROLLED_UP_UNTIL_KEY: &str = "statistics_rolled_up_until";
+
+/// Period length of stored statistics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Granularity {
+    Hourly,
+    Daily,
+}
+
+impl Granularity {
+    fn device_table(self) -> &'static str {
+        match self {
+            Granularity::Hourly => "device_stats_hourly",
+            Granularity::Daily => "device_stats_daily",
+        }
+    }
+
+    fn energy_table(self) -> &'static str {
+        match self {
+            Granularity::Hourly => "energy_hourly",
+            Granularity::Daily => "energy_daily",
+        }
+    }
+}
+
+/// Get the end of the last rolled up hour, None if nothing was rolled up yet
+pub async fn get_rolled_up_until(pool: &DbPool) -> Result<Option<i64>, sqlx::Error> {
+    let value = super::settings::get(pool, ROLLED_UP_UNTIL_KEY).await?;
+    Ok(value.and_then(|value| value.parse().ok()))
+}
+
+/// Store the end of the last rolled up hour
+pub async fn set_rolled_up_until(pool: &DbPool, timestamp: i64) -> Result<(), sqlx::Error> {
+    super::settings::set(pool, ROLLED_UP_UNTIL_KEY, &timestamp.to_string()).await
+}
+
+/// Get the time of the oldest stored cycle snapshot
+pub async fn get_oldest_snapshot_time(pool: &DbPool) -> Result<Option<i64>, sqlx::Error> {
+    let (oldest,) = sqlx::query_as::<_, (Option<i64>,)>("SELECT MIN(evaluated_at) FROM cycle_snapshots")
+        .fetch_one(pool)
+        .await?;
+    Ok(oldest)
+}
+
+/// Get the cycle snapshots of all devices evaluated in [start, end), oldest first
+pub async fn get_snapshots_between(
+    pool: &DbPool,
+    start: i64,
+    end: i64,
+) -> Result<Vec<db_types::CycleSnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::CycleSnapshot>(
+        "SELECT * FROM cycle_snapshots WHERE evaluated_at >= $1 AND evaluated_at < $2 ORDER BY evaluated_at ASC, id ASC",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+}
+
+/// Count the AC commands per device sent in [start, end)
+pub async fn count_actions_between(pool: &DbPool, start: i64, end: i64) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, i64)>(
+        "SELECT device_identifier, COUNT(*) FROM ac_actions
+         WHERE action_timestamp >= $1 AND action_timestamp < $2
+         GROUP BY device_identifier",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the commands per device that set the state of [start, end), oldest first
+/// Includes the last command of each device before `start`, since that one set the state the period starts in
+pub async fn get_command_causes_between(pool: &DbPool, start: i64, end: i64) -> Result<Vec<(String, i64, i64)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, i64, i64)>(
+        r#"
+        SELECT a.device_identifier, CAST(a.action_timestamp AS BIGINT), CAST(a.cause_id AS BIGINT) FROM ac_actions a
+        WHERE a.action_timestamp < $1 AND a.action_timestamp >= (
+            SELECT COALESCE(MAX(b.action_timestamp), 0) FROM ac_actions b
+            WHERE b.device_identifier = a.device_identifier AND b.action_timestamp <= $2
+        )
+        ORDER BY a.action_timestamp ASC
+        "#,
+    )
+    .bind(end)
+    .bind(start)
+    .fetch_all(pool)
+    .await
+}
+
+/// Store the statistics of an hour, replacing earlier rows for that hour
+pub async fn store_hour(
+    pool: &DbPool,
+    period_start: i64,
+    devices: &[db_types::DeviceStats],
+    energy: Option<&db_types::EnergyStats>,
+    device_energy: &[db_types::DeviceEnergy],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM device_stats_hourly WHERE period_start = $1")
+        .bind(period_start)
+        .execute(&mut *tx)
+        .await?;
+    for stats in devices {
+        sqlx::query(
+            r#"
+            INSERT INTO device_stats_hourly
+                (period_start, device_identifier, samples, avg_indoor_temperature, avg_outdoor_temperature, runtime_minutes, actions)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(stats.period_start)
+        .bind(&stats.device_identifier)
+        .bind(stats.samples)
+        .bind(stats.avg_indoor_temperature)
+        .bind(stats.avg_outdoor_temperature)
+        .bind(stats.runtime_minutes)
+        .bind(stats.actions)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("DELETE FROM energy_hourly WHERE period_start = $1")
+        .bind(period_start)
+        .execute(&mut *tx)
+        .await?;
+    if let Some(energy) = energy {
+        sqlx::query(
+            r#"
+            INSERT INTO energy_hourly (period_start, covered_minutes, grid_import_kwh, grid_export_kwh, solar_kwh)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(energy.period_start)
+        .bind(energy.covered_minutes)
+        .bind(energy.grid_import_kwh)
+        .bind(energy.grid_export_kwh)
+        .bind(energy.solar_kwh)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("DELETE FROM device_energy WHERE period_start = $1")
+        .bind(period_start)
+        .execute(&mut *tx)
+        .await?;
+    for estimate in device_energy {
+        sqlx::query(
+            r#"
+            INSERT INTO device_energy
+                (period_start, device_identifier, cause_id, mode, is_powerful, runtime_minutes, estimated_kwh)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(estimate.period_start)
+        .bind(&estimate.device_identifier)
+        .bind(estimate.cause_id)
+        .bind(&estimate.mode)
+        .bind(estimate.is_powerful)
+        .bind(estimate.runtime_minutes)
+        .bind(estimate.estimated_kwh)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Recompute the daily rows of the day starting at `day_start` from its hourly rows
+pub async fn rollup_day(pool: &DbPool, day_start: i64) -> Result<(), sqlx::Error> {
+    let day_end = day_start + 24 * 60 * 60;
+    let mut tx = pool.begin().await?;
+
+    // Averages are weighted by the number of samples of each hour
+    sqlx::query(
+        r#"
+        INSERT INTO device_stats_daily
+            (period_start, device_identifier, samples, avg_indoor_temperature, avg_outdoor_temperature, runtime_minutes, actions)
+        SELECT
+            $1,
+            device_identifier,
+            SUM(samples),
+            SUM(avg_indoor_temperature * samples) / SUM(CASE WHEN avg_indoor_temperature IS NOT NULL THEN samples END),
+            SUM(avg_outdoor_temperature * samples) / SUM(CASE WHEN avg_outdoor_temperature IS NOT NULL THEN samples END),
+            SUM(runtime_minutes),
+            SUM(actions)
+        FROM device_stats_hourly
+        WHERE period_start >= $2 AND period_start < $3
+        GROUP BY device_identifier
+        ON CONFLICT(period_start, device_identifier) DO UPDATE SET
+            samples = excluded.samples, avg_indoor_temperature = excluded.avg_indoor_temperature,
+            avg_outdoor_temperature = excluded.avg_outdoor_temperature, runtime_minutes = excluded.runtime_minutes,
+            actions = excluded.actions
+        "#,
+    )
+    .bind(day_start)
+    .bind(day_start)
+    .bind(day_end)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO energy_daily (period_start, covered_minutes, grid_import_kwh, grid_export_kwh, solar_kwh)
+        SELECT $1, SUM(covered_minutes), SUM(grid_import_kwh), SUM(grid_export_kwh), SUM(solar_kwh)
+        FROM energy_hourly
+        WHERE period_start >= $2 AND period_start < $3
+        HAVING COUNT(*) > 0
+        ON CONFLICT(period_start) DO UPDATE SET
+            covered_minutes = excluded.covered_minutes, grid_import_kwh = excluded.grid_import_kwh,
+            grid_export_kwh = excluded.grid_export_kwh, solar_kwh = excluded.solar_kwh
+        "#,
+    )
+    .bind(day_start)
+    .bind(day_start)
+    .bind(day_end)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Get device statistics with periods starting in [start, end], oldest first
+pub async fn get_device_stats(
+    pool: &DbPool,
+    granularity: Granularity,
+    start: i64,
+    end: i64,
+    device_identifier: Option<&str>,
+) -> Result<Vec<db_types::DeviceStats>, sqlx::Error> {
+    let query = format!(
+        "SELECT * FROM {} WHERE period_start BETWEEN $1 AND $2 AND ($3 IS NULL OR device_identifier = $4)
+         ORDER BY period_start ASC, device_identifier ASC",
+        granularity.device_table()
+    );
+    sqlx::query_as::<_, db_types::DeviceStats>(&query)
+        .bind(start)
+        .bind(end)
+        .bind(device_identifier)
+        .bind(device_identifier)
+        .fetch_all(pool)
+        .await
+}
+
+/// Get household energy with periods starting in [start, end], oldest first
+pub async fn get_energy_stats(
+    pool: &DbPool,
+    granularity: Granularity,
+    start: i64,
+    end: i64,
+) -> Result<Vec<db_types::EnergyStats>, sqlx::Error> {
+    let query = format!(
+        "SELECT * FROM {} WHERE period_start BETWEEN $1 AND $2 ORDER BY period_start ASC",
+        granularity.energy_table()
+    );
+    sqlx::query_as::<_, db_types::EnergyStats>(&query)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await
+}
+
+/// Get the estimated AC energy of the hours starting in [start, end), summed per device, cause, mode
+/// and powerful state, largest first per device
+pub async fn get_energy_breakdown(
+    pool: &DbPool,
+    start: i64,
+    end: i64,
+    device_identifier: Option<&str>,
+) -> Result<Vec<db_types::EnergyBreakdown>, sqlx::Error> {
+    sqlx::query_as::<_, db_types::EnergyBreakdown>(
+        r#"
+        SELECT e.device_identifier, e.cause_id, c.label AS cause_label, e.mode, e.is_powerful,
+            SUM(e.runtime_minutes) AS runtime_minutes, SUM(e.estimated_kwh) AS estimated_kwh
+        FROM device_energy e
+        LEFT JOIN cause_reasons c ON c.id = e.cause_id
+        WHERE e.period_start >= $1 AND e.period_start < $2 AND ($3 IS NULL OR e.device_identifier = $4)
+        GROUP BY e.device_identifier, e.cause_id, c.label, e.mode, e.is_powerful
+        ORDER BY e.device_identifier ASC, estimated_kwh DESC
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .bind(device_identifier)
+    .bind(device_identifier)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,70 @@
+use crate::{db::get_pool, types::db_types};
+
+/// Source of hours recorded from the live weather API
+pub const SOURCE_LIVE: &str = "live";
+/// Source of hours backfilled from the weather archive
+pub const SOURCE_ARCHIVE: &str = "archive";
+
+/// Record the live outdoor temperature for an hour, replacing an earlier value for that hour
+pub async fn record_live(hour_timestamp: i64, outdoor_temperature: f64) -> Result<(), sqlx::Error> {
+    let pool = get_pool().await;
+
+    sqlx::query(
+        r#"
+        INSERT INTO weather_history (hour_timestamp, outdoor_temperature, source)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(hour_timestamp) DO UPDATE SET outdoor_temperature = excluded.outdoor_temperature, source = excluded.source
+        "#,
+    )
+    .bind(hour_timestamp)
+    .bind(outdoor_temperature)
+    .bind(SOURCE_LIVE)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert archive hours, keeping any hours that were already recorded
+/// Returns the number of hours inserted
+pub async fn insert_archive(hours: &[(i64, f64)]) -> Result<u64, sqlx::Error> {
+    let pool = get_pool().await;
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+
+    for (hour_timestamp, outdoor_temperature) in hours {
+        inserted += sqlx::query(
+            r#"
+            INSERT INTO weather_history (hour_timestamp, outdoor_temperature, source)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(hour_timestamp) DO NOTHING
+            "#,
+        )
+        .bind(hour_timestamp)
+        .bind(outdoor_temperature)
+        .bind(SOURCE_ARCHIVE)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Get the recorded hours between start and end (inclusive), oldest first
+pub async fn get_range(start: i64, end: i64) -> Result<Vec<db_types::WeatherHour>, sqlx::Error> {
+    let pool = get_pool().await;
+
+    sqlx::query_as::<_, db_types::WeatherHour>(
+        r#"
+        SELECT * FROM weather_history
+        WHERE hour_timestamp BETWEEN $1 AND $2
+        ORDER BY hour_timestamp ASC
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+}
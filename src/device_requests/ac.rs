@@ -1,18 +1,39 @@
-use super::common;
+use super::common::{self, CircuitOpen, RetryPolicy, TransientError};
+use super::health::EndpointKind;
 use super::cache::DataCache;
+use crate::ac_controller::ac_executor::ac_mode_name;
+use crate::types::AcCommand;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 // Public data types
 #[derive(Debug, Deserialize, Clone)]
 pub struct SensorData {
     pub temperature: f64,
+    /// Relative humidity (%), only reported by controllers with a humidity sensor
+    #[serde(default)]
+    pub humidity: Option<f64>,
     #[serde(rename = "isAutomaticMode")]
     pub is_automatic_mode: bool,
 }
 
+/// State the unit reports on `/api/state`, only for controllers with `supports_state_readback`
+/// Settings the unit doesn't report are None
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ReportedState {
+    #[serde(rename = "isOn")]
+    pub is_on: bool,
+    #[serde(default)]
+    pub mode: Option<i32>,
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(rename = "fanSpeed", default)]
+    pub fan_speed: Option<i32>,
+}
+
 // Request types
 #[derive(Debug, Serialize)]
 pub struct TurnOnRequest {
@@ -21,6 +42,11 @@ pub struct TurnOnRequest {
     pub fan_speed: i32,
     pub temperature: f64,
     pub swing: i32,
+    /// Vane positions (`VANE_*`), left out for controllers that don't support them
+    #[serde(rename = "verticalVane", skip_serializing_if = "Option::is_none")]
+    pub vertical_vane: Option<i32>,
+    #[serde(rename = "horizontalVane", skip_serializing_if = "Option::is_none")]
+    pub horizontal_vane: Option<i32>,
 }
 
 // Custom error type
@@ -30,6 +56,10 @@ pub enum AcError {
     NetworkError(reqwest::Error),
     EndpointNotFound(String),
     DatabaseError(String),
+    /// Not sent because the AC failed repeatedly, see `common::CircuitOpen`
+    Unavailable(CircuitOpen),
+    /// The controller rejected the request (4xx), e.g. for a wrong API key
+    Rejected(String),
 }
 
 impl fmt::Display for AcError {
@@ -41,6 +71,8 @@ impl fmt::Display for AcError {
                 write!(f, "AC endpoint '{}' not found in config", name)
             }
             AcError::DatabaseError(msg) => write!(f, "Database Error: {}", msg),
+            AcError::Unavailable(open) => write!(f, "AC unavailable: {}", open),
+            AcError::Rejected(msg) => write!(f, "Request rejected: {}", msg),
         }
     }
 }
@@ -53,192 +85,129 @@ impl From<reqwest::Error> for AcError {
     }
 }
 
+impl From<CircuitOpen> for AcError {
+    fn from(open: CircuitOpen) -> Self {
+        AcError::Unavailable(open)
+    }
+}
+
+impl TransientError for AcError {
+    /// Failed requests and errors reported by the controller are retried, like before the breaker
+    fn is_transient(&self) -> bool {
+        match self {
+            AcError::ApiError(_) => true,
+            AcError::NetworkError(e) => common::is_transient_request_error(e),
+            _ => false,
+        }
+    }
+}
+
 // API functions
-pub async fn turn_off_ac(endpoint_name: &str, cause_id: i32) -> Result<bool, AcError> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY_SECS: u64 = 5;
-    
+// Every command supersedes the pending read-back of the device's previous command, except the
+// resend of a command that wasn't applied, which is part of that read-back
+
+/// `annotation` is the optional text the profile attached to the command, logged with it
+pub async fn turn_off_ac(endpoint_name: &str, cause_id: i32, annotation: Option<&str>) -> Result<bool, AcError> {
+    supersede_verification(endpoint_name);
+    turn_off(endpoint_name, cause_id, annotation).await
+}
+
+async fn turn_off(endpoint_name: &str, cause_id: i32, annotation: Option<&str>) -> Result<bool, AcError> {
     let (base_url, api_key) = get_ac_endpoint_config(endpoint_name)?;
 
     info!("Turning off AC '{}'", endpoint_name);
     let url = format!("{}/api/ir/off", base_url);
-    
-    // Retry loop
-    for attempt in 1..=MAX_RETRIES {
-        let client = common::get_client().await;
-        
-        match client
-            .post(&url)
-            .header("Authorization", format!("ApiKey {}", api_key))
-            .send()
-            .await
-        {
-            Ok(response) => {
-                match handle_response(response).await {
-                    Ok(result) => {
-                        // Success - log to database (enqueued if DB unavailable)
-                        log_ac_command(endpoint_name, "off", None, None, None, None, cause_id).await;
-                        return Ok(result);
-                    }
-                    Err(e) => {
-                        if attempt < MAX_RETRIES {
-                            log::warn!("AC turn off failed (attempt {}/{}): {}. Retrying in {}s...", 
-                                attempt, MAX_RETRIES, e, RETRY_DELAY_SECS);
-                            tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS)).await;
-                        } else {
-                            log::error!("AC turn off failed after {} attempts: {}", MAX_RETRIES, e);
-                            return Err(e);
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                if attempt < MAX_RETRIES {
-                    log::warn!("AC turn off network error (attempt {}/{}): {}. Retrying in {}s...", 
-                        attempt, MAX_RETRIES, e, RETRY_DELAY_SECS);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS)).await;
-                } else {
-                    log::error!("AC turn off network error after {} attempts: {}", MAX_RETRIES, e);
-                    return Err(AcError::from(e));
-                }
-            }
-        }
-    }
-    
-    unreachable!("Retry loop should have returned within MAX_RETRIES attempts")
+    let (result, latency) = post_command(endpoint_name, &url, &api_key, None::<&()>).await?;
+
+    // Success - log to database (enqueued if DB unavailable)
+    log_ac_command(endpoint_name, "off", None, latency, cause_id, annotation).await;
+    Ok(result)
 }
 
-pub async fn turn_on_ac(
+/// Turn on the AC with the request's settings
+/// `request.mode` is Cool, Dry, Fan or Heat (see `ac_executor::AC_MODE_*`). In fan-only mode the unit
+/// ignores the temperature, but it is still sent as the controller requires it
+async fn turn_on_ac(
     endpoint_name: &str,
-    mode: i32,
-    fan_speed: i32,
-    temperature: f64,
-    swing: i32,
+    request: TurnOnRequest,
     cause_id: i32,
+    annotation: Option<&str>,
 ) -> Result<bool, AcError> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY_SECS: u64 = 5;
-    
+    let Some(mode_name) = ac_mode_name(request.mode) else {
+        return Err(AcError::ApiError(format!("Unsupported AC mode {}", request.mode)));
+    };
     let (base_url, api_key) = get_ac_endpoint_config(endpoint_name)?;
 
     info!(
-        "Turning on AC '{}': mode={}, fan_speed={}, temp={}°C, swing={}",
-        endpoint_name, mode, fan_speed, temperature, swing
+        "Turning on AC '{}': mode={} ({}), fan_speed={}, temp={}°C, swing={}, vanes={:?}/{:?}",
+        endpoint_name,
+        request.mode,
+        mode_name,
+        request.fan_speed,
+        request.temperature,
+        request.swing,
+        request.vertical_vane,
+        request.horizontal_vane
     );
     let url = format!("{}/api/ir/on", base_url);
-    let request = TurnOnRequest {
-        mode,
-        fan_speed,
-        temperature,
-        swing,
-    };
-    
-    // Retry loop
-    for attempt in 1..=MAX_RETRIES {
-        let client = common::get_client().await;
-        
-        match client
-            .post(&url)
-            .header("Authorization", format!("ApiKey {}", api_key))
-            .json(&request)
-            .send()
-            .await
-        {
-            Ok(response) => {
-                match handle_response(response).await {
-                    Ok(result) => {
-                        // Success - log to database (enqueued if DB unavailable)
-                        log_ac_command(
-                            endpoint_name,
-                            "on",
-                            Some(mode),
-                            Some(fan_speed),
-                            Some(temperature as f32),
-                            Some(swing),
-                            cause_id,
-                        ).await;
-                        return Ok(result);
-                    }
-                    Err(e) => {
-                        if attempt < MAX_RETRIES {
-                            log::warn!("AC turn on failed (attempt {}/{}): {}. Retrying in {}s...", 
-                                attempt, MAX_RETRIES, e, RETRY_DELAY_SECS);
-                            tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS)).await;
-                        } else {
-                            log::error!("AC turn on failed after {} attempts: {}", MAX_RETRIES, e);
-                            return Err(e);
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                if attempt < MAX_RETRIES {
-                    log::warn!("AC turn on network error (attempt {}/{}): {}. Retrying in {}s...", 
-                        attempt, MAX_RETRIES, e, RETRY_DELAY_SECS);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS)).await;
-                } else {
-                    log::error!("AC turn on network error after {} attempts: {}", MAX_RETRIES, e);
-                    return Err(AcError::from(e));
-                }
-            }
+    let (result, latency) = post_command(endpoint_name, &url, &api_key, Some(&request)).await?;
+
+    // Success - log to database (enqueued if DB unavailable)
+    log_ac_command(endpoint_name, "on", Some(&request), latency, cause_id, annotation).await;
+    Ok(result)
+}
+
+/// Send a command to the AC, turning it off or on with the command's settings
+pub async fn send_command(endpoint_name: &str, command: &AcCommand, cause_id: i32, annotation: Option<&str>) -> Result<bool, AcError> {
+    supersede_verification(endpoint_name);
+    deliver_command(endpoint_name, command, cause_id, annotation).await
+}
+
+/// Send a command that wasn't applied again, its read-back goes on
+pub async fn resend_command(endpoint_name: &str, command: &AcCommand, cause_id: i32, annotation: Option<&str>) -> Result<bool, AcError> {
+    deliver_command(endpoint_name, command, cause_id, annotation).await
+}
+
+async fn deliver_command(endpoint_name: &str, command: &AcCommand, cause_id: i32, annotation: Option<&str>) -> Result<bool, AcError> {
+    match *command {
+        AcCommand::Off => turn_off(endpoint_name, cause_id, annotation).await,
+        AcCommand::On {
+            mode,
+            fan_speed,
+            temperature,
+            swing,
+            vertical_vane,
+            horizontal_vane,
+        } => {
+            let request = TurnOnRequest {
+                mode,
+                fan_speed,
+                temperature: temperature.0,
+                swing,
+                vertical_vane,
+                horizontal_vane,
+            };
+            turn_on_ac(endpoint_name, request, cause_id, annotation).await
         }
     }
-    
-    unreachable!("Retry loop should have returned within MAX_RETRIES attempts")
 }
 
-pub async fn toggle_powerful(endpoint_name: &str, cause_id: i32) -> Result<bool, AcError> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY_SECS: u64 = 5;
-    
+/// A new command makes the read-back of the previous one meaningless, see `command_verification`
+fn supersede_verification(endpoint_name: &str) {
+    crate::ac_controller::command_verification::get_command_verifier().start(endpoint_name);
+}
+
+pub async fn toggle_powerful(endpoint_name: &str, cause_id: i32, annotation: Option<&str>) -> Result<bool, AcError> {
+    supersede_verification(endpoint_name);
     let (base_url, api_key) = get_ac_endpoint_config(endpoint_name)?;
 
     info!("Toggling powerful mode for AC '{}'", endpoint_name);
     let url = format!("{}/api/ir/toggle-powerful", base_url);
-    
-    // Retry loop
-    for attempt in 1..=MAX_RETRIES {
-        let client = common::get_client().await;
-        
-        match client
-            .post(&url)
-            .header("Authorization", format!("ApiKey {}", api_key))
-            .send()
-            .await
-        {
-            Ok(response) => {
-                match handle_response(response).await {
-                    Ok(result) => {
-                        // Success - log to database (enqueued if DB unavailable)
-                        log_ac_command(endpoint_name, "toggle-powerful", None, None, None, None, cause_id).await;
-                        return Ok(result);
-                    }
-                    Err(e) => {
-                        if attempt < MAX_RETRIES {
-                            log::warn!("Toggle powerful failed (attempt {}/{}): {}. Retrying in {}s...", 
-                                attempt, MAX_RETRIES, e, RETRY_DELAY_SECS);
-                            tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS)).await;
-                        } else {
-                            log::error!("Toggle powerful failed after {} attempts: {}", MAX_RETRIES, e);
-                            return Err(e);
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                if attempt < MAX_RETRIES {
-                    log::warn!("Toggle powerful network error (attempt {}/{}): {}. Retrying in {}s...", 
-                        attempt, MAX_RETRIES, e, RETRY_DELAY_SECS);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS)).await;
-                } else {
-                    log::error!("Toggle powerful network error after {} attempts: {}", MAX_RETRIES, e);
-                    return Err(AcError::from(e));
-                }
-            }
-        }
-    }
-    
-    unreachable!("Retry loop should have returned within MAX_RETRIES attempts")
+    let (result, latency) = post_command(endpoint_name, &url, &api_key, None::<&()>).await?;
+
+    // Success - log to database (enqueued if DB unavailable)
+    log_ac_command(endpoint_name, "toggle-powerful", None, latency, cause_id, annotation).await;
+    Ok(result)
 }
 
 pub async fn get_sensors(endpoint_name: &str) -> Result<SensorData, AcError> {
@@ -246,11 +215,26 @@ pub async fn get_sensors(endpoint_name: &str) -> Result<SensorData, AcError> {
 
     debug!("Fetching sensor data from AC '{}'", endpoint_name);
     let url = format!("{}/api/sensors", base_url);
-    let client = common::get_client().await;
 
-    let response = client.get(&url).send().await?;
+    common::with_retry(EndpointKind::AcController, endpoint_name, RetryPolicy::READ, || async {
+        let client = common::get_client().await;
+        let response = client.get(&url).send().await?;
+        handle_response(response).await
+    }).await
+}
+
+/// Read the state the unit reports, to verify a command took effect
+pub async fn get_reported_state(endpoint_name: &str) -> Result<ReportedState, AcError> {
+    let (base_url, _api_key) = get_ac_endpoint_config(endpoint_name)?;
+
+    debug!("Reading back the state of AC '{}'", endpoint_name);
+    let url = format!("{}/api/state", base_url);
 
-    handle_response(response).await
+    common::with_retry(EndpointKind::AcController, endpoint_name, RetryPolicy::READ, || async {
+        let client = common::get_client().await;
+        let response = client.get(&url).send().await?;
+        handle_response(response).await
+    }).await
 }
 
 // Cache for sensor data (30 second TTL)
@@ -271,8 +255,49 @@ pub async fn get_sensors_cached(endpoint_name: &str) -> Result<SensorData, AcErr
     }).await
 }
 
+/// Time since the cached sensor data of a device was read, None if it was never read
+pub async fn get_sensors_age(endpoint_name: &str) -> Option<Duration> {
+    get_sensor_cache().age(&format!("sensor_{}", endpoint_name)).await
+}
+
+// Helper to post a command, retrying with backoff while the AC's circuit breaker allows it
+// Returns the result with the round-trip time of the attempt that succeeded
+async fn post_command<B: Serialize>(
+    endpoint_name: &str,
+    url: &str,
+    api_key: &str,
+    body: Option<&B>,
+) -> Result<(bool, Duration), AcError> {
+    // Retries must not outlast the device's evaluation in the control cycle
+    let cycle_timeout = Duration::from_secs(crate::config::get_config().device_cycle_timeout_seconds);
+    let policy = RetryPolicy::COMMAND.within(cycle_timeout);
+    common::with_retry(EndpointKind::AcController, endpoint_name, policy, || async {
+        let client = common::get_client().await;
+        let mut request = client.post(url).header("Authorization", format!("ApiKey {}", api_key));
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let started = Instant::now();
+        let result = match request.send().await {
+            Ok(response) => handle_response(response).await,
+            Err(e) => Err(AcError::from(e)),
+        };
+        let latency = record_command_latency(endpoint_name, started);
+        result.map(|sent| (sent, latency))
+    }).await
+}
+
+// Helper to record the round-trip time of a command attempt
+fn record_command_latency(endpoint_name: &str, started: Instant) -> Duration {
+    let latency = started.elapsed();
+    debug!("Command to AC '{}' took {} ms", endpoint_name, latency.as_millis());
+    super::latency::get_latency_tracker().record(endpoint_name, latency);
+    latency
+}
+
 // Helper to get endpoint config
-fn get_ac_endpoint_config(endpoint_name: &str) -> Result<(&str, &str), AcError> {
+fn get_ac_endpoint_config(endpoint_name: &str) -> Result<(String, String), AcError> {
     let config = crate::config::get_config();
 
     match config.ac_controller_endpoints.get(endpoint_name) {
@@ -281,7 +306,7 @@ fn get_ac_endpoint_config(endpoint_name: &str) -> Result<(&str, &str), AcError>
                 "Found AC endpoint '{}' at {}",
                 endpoint_name, props.endpoint
             );
-            Ok((&props.endpoint, &props.api_key))
+            Ok((props.endpoint.clone(), props.api_key.clone()))
         }
         None => {
             error!("AC endpoint '{}' not found in config", endpoint_name);
@@ -294,7 +319,17 @@ fn get_ac_endpoint_config(endpoint_name: &str) -> Result<(&str, &str), AcError>
 async fn handle_response<T: for<'de> Deserialize<'de>>(
     response: reqwest::Response,
 ) -> Result<T, AcError> {
-    let api_response: common::ApiResponse<T> = response.json().await?;
+    let status = response.status();
+    let body = super::raw_payloads::read_body(super::raw_payloads::RawSource::AcBridge, response).await?;
+    if status.is_client_error() && !common::is_transient_status(status) {
+        let message = serde_json::from_str::<common::ApiResponse<serde_json::Value>>(&body)
+            .map(|r| format!("HTTP {}: {}", status, r.error))
+            .unwrap_or_else(|_| format!("HTTP {}", status));
+        error!("API request rejected: {}", message);
+        return Err(AcError::Rejected(message));
+    }
+    let api_response: common::ApiResponse<T> = serde_json::from_str(&body)
+        .map_err(|e| AcError::ApiError(format!("Failed to parse response: {}", e)))?;
 
     if api_response.success {
         debug!("API request successful");
@@ -314,14 +349,15 @@ async fn handle_response<T: for<'de> Deserialize<'de>>(
 /// Log AC command to database with environmental context
 /// On failure, enqueues the log entry for retry instead of returning an error
 /// This decouples physical device commands from database logging
+/// `settings` are the settings of an "on" command, None for other commands
+/// `latency` is the round-trip time of the request that delivered the command
 async fn log_ac_command(
     endpoint_name: &str,
     action_type: &str,
-    mode: Option<i32>,
-    fan_speed: Option<i32>,
-    temperature: Option<f32>,
-    swing: Option<i32>,
+    settings: Option<&TurnOnRequest>,
+    latency: Duration,
     cause_id: i32,
+    annotation: Option<&str>,
 ) {
     // Try to get indoor temperature from the device
     let measured_temp = match get_sensors(endpoint_name).await {
@@ -357,19 +393,23 @@ async fn log_ac_command(
     // This could be enhanced in the future with presence detection
     let is_human_home = None;
     
-    let ac_action = crate::types::db_types::AcAction::new_for_insert(
+    let mut ac_action = crate::types::db_types::AcAction::new_for_insert(
         endpoint_name.to_string(),
         action_type.to_string(),
-        mode,
-        fan_speed,
-        temperature,
-        swing,
+        settings.map(|s| s.mode),
+        settings.map(|s| s.fan_speed),
+        settings.map(|s| s.temperature as f32),
+        settings.map(|s| s.swing),
         measured_temp,
         net_power,
         solar_production,
         is_human_home,
         cause_id,
     );
+    ac_action.command_latency_ms = Some(latency.as_millis().min(i32::MAX as u128) as i32);
+    ac_action.annotation = annotation.map(str::to_string);
+    ac_action.vertical_vane = settings.and_then(|s| s.vertical_vane);
+    ac_action.horizontal_vane = settings.and_then(|s| s.horizontal_vane);
     
     // Log to database - if it fails, enqueue for retry instead of failing the command
     // Clone before insert so we can reuse the action if database fails
@@ -0,0 +1,110 @@
+use super::{cache::DataCache, common};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+#[derive(Debug)]
+pub enum BatteryError {
+    RequestFailed(String),
+    ParseError(String),
+}
+
+impl std::fmt::Display for BatteryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatteryError::RequestFailed(msg) => write!(f, "Battery API request failed: {}", msg),
+            BatteryError::ParseError(msg) => write!(f, "Failed to parse battery status: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BatteryError {}
+
+/// State of the home battery as returned by `battery_api_endpoint`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BatteryStatus {
+    /// State of charge in %
+    pub soc_percent: f64,
+    /// Power in watts, positive while discharging into the home, negative while charging
+    pub power_watt: f64,
+}
+
+/// Get the current battery status from the configured endpoint
+pub async fn get_battery_status(endpoint: &str) -> Result<BatteryStatus, BatteryError> {
+    let client = common::get_client().await;
+    let response = client
+        .get(endpoint)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| BatteryError::RequestFailed(e.to_string()))?;
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| BatteryError::RequestFailed(e.to_string()))?;
+
+    parse_status(&body)
+}
+
+fn parse_status(body: &str) -> Result<BatteryStatus, BatteryError> {
+    let status: BatteryStatus = serde_json::from_str(body).map_err(|e| BatteryError::ParseError(e.to_string()))?;
+    if !(0.0..=100.0).contains(&status.soc_percent) || !status.power_watt.is_finite() {
+        return Err(BatteryError::ParseError(format!(
+            "Out of range reading: {}% at {} W",
+            status.soc_percent, status.power_watt
+        )));
+    }
+    Ok(status)
+}
+
+static BATTERY_CACHE: OnceLock<DataCache<BatteryStatus>> = OnceLock::new();
+
+fn get_battery_cache() -> &'static DataCache<BatteryStatus> {
+    BATTERY_CACHE.get_or_init(|| DataCache::new(30)) // 30 seconds, power changes quickly
+}
+
+/// Get the battery status with caching (30 second TTL)
+/// No stale fallback, an outdated charge/discharge power is worse than none
+pub async fn get_battery_status_cached(endpoint: &str) -> Result<BatteryStatus, BatteryError> {
+    let cache = get_battery_cache();
+
+    cache.get_or_fetch(endpoint, || async {
+        get_battery_status(endpoint).await
+    }).await
+}
+
+/// Get the battery status for nodesets
+/// None if no endpoint is configured or the battery can't be read
+pub async fn get_battery_data(endpoint: &str) -> Option<BatteryStatus> {
+    if endpoint.is_empty() {
+        return None;
+    }
+
+    match get_battery_status_cached(endpoint).await {
+        Ok(status) => Some(status),
+        Err(e) => {
+            log::warn!("Failed to get battery status: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = parse_status(r#"{"soc_percent": 76.5, "power_watt": -1200, "temperature": 21}"#).unwrap();
+        assert_eq!(
+            status,
+            BatteryStatus {
+                soc_percent: 76.5,
+                power_watt: -1200.0,
+            }
+        );
+
+        assert!(matches!(parse_status(r#"{"soc_percent": 101, "power_watt": 0}"#), Err(BatteryError::ParseError(_))));
+        assert!(matches!(parse_status(r#"{"soc": 50}"#), Err(BatteryError::ParseError(_))));
+    }
+}
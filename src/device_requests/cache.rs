@@ -83,6 +83,13 @@ impl<T: Clone> DataCache<T> {
         cache.get(key).map(|entry| entry.data.clone())
     }
 
+    /// Time since the value for a key was stored, including expired values
+    /// None if nothing was ever stored for the key
+    pub async fn age(&self, key: &str) -> Option<Duration> {
+        let cache = self.cache.read().await;
+        cache.get(key).map(|entry| entry.timestamp.elapsed())
+    }
+
     /// Get or fetch with stale fallback: tries to fetch new data, but returns stale cache on error
     pub async fn get_or_fetch_with_stale_fallback<F, Fut, E>(
         &self,
@@ -189,6 +196,20 @@ mod tests {
         assert_eq!(cache.get_stale("test").await, Some("value".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_age() {
+        let cache = DataCache::<i32>::new(1); // 1 second TTL
+
+        assert!(cache.age("test").await.is_none());
+
+        cache.set("test".to_string(), 42).await;
+        assert!(cache.age("test").await.unwrap() < Duration::from_secs(1));
+
+        // Expired values still report their age
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(cache.age("test").await.unwrap() >= Duration::from_secs(2));
+    }
+
     #[tokio::test]
     async fn test_get_or_fetch_with_stale_fallback_success() {
         let cache = DataCache::<i32>::new(60);
@@ -1,8 +1,31 @@
+//! Shared HTTP client, retries and circuit breakers for upstream requests
+//!
+//! Transient failures (connection errors, timeouts, server errors) are retried with exponential
+//! backoff. A request the endpoint rejects (4xx) would be rejected again, so it isn't retried and
+//! doesn't count as a failure of the endpoint. Every endpoint also has a circuit breaker: after `FAILURE_THRESHOLD` failed attempts in
+//! a row it opens and requests fail right away for `OPEN_DURATION`, so an AC bridge that is down
+//! costs the control cycle nothing instead of its retries and timeouts. After that one trial
+//! request is let through, which closes the breaker again if it succeeds. Breaker states are
+//! reported on `/api/status/details`. Every attempt is also recorded in the endpoint's health, see `health`.
+
 use reqwest::Client;
-use serde::Deserialize;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::OnceCell;
 
+use super::health::{get_health_monitor, EndpointKind, HealthMonitor};
+
+/// Failed attempts in a row that open the breaker of an endpoint
+pub(super) const FAILURE_THRESHOLD: u32 = 5;
+/// How long an open breaker rejects requests before letting a trial request through
+const OPEN_DURATION: Duration = Duration::from_secs(60);
+/// Longest a single request may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 // Internal response types (not exposed)
 #[derive(Debug, Deserialize)]
 pub(super) struct ApiResponse<T> {
@@ -17,9 +40,438 @@ pub(super) async fn get_client() -> &'static Client {
     CLIENT
         .get_or_init(|| async {
             Client::builder()
-                .timeout(Duration::from_secs(30))
+                .timeout(REQUEST_TIMEOUT)
                 .build()
                 .unwrap()
         })
         .await
 }
+
+/// How often and how long apart a request is attempted
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RetryPolicy {
+    pub(super) max_attempts: u32,
+    /// Delay after the first failed attempt, doubled after each further one
+    pub(super) initial_delay: Duration,
+    pub(super) max_delay: Duration,
+    /// Time all attempts together may take, a retry is only made if it can finish in it
+    pub(super) max_total: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Commands that change a device, worth waiting for
+    pub(super) const COMMAND: Self = Self {
+        max_attempts: 3,
+        initial_delay: Duration::from_secs(2),
+        max_delay: Duration::from_secs(8),
+        max_total: None,
+    };
+
+    /// Reads, which mostly have a cached value to fall back on
+    pub(super) const READ: Self = Self {
+        max_attempts: 2,
+        initial_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(2),
+        max_total: None,
+    };
+
+    /// The same policy, with all attempts together taking at most `max_total`
+    pub(super) const fn within(self, max_total: Duration) -> Self {
+        Self { max_total: Some(max_total), ..self }
+    }
+
+    /// Delay before the attempt after failed attempt `attempt` (1-based)
+    fn delay_after(&self, attempt: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+/// Errors that may go away when the request is repeated
+pub(super) trait TransientError {
+    fn is_transient(&self) -> bool;
+}
+
+/// Whether a response with `status` may turn out differently when the request is repeated
+pub(super) fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::REQUEST_TIMEOUT
+}
+
+/// Whether a failed request may succeed when repeated: timeouts, connection errors and transient statuses
+pub(super) fn is_transient_request_error(e: &reqwest::Error) -> bool {
+    match e.status() {
+        Some(status) => is_transient_status(status),
+        None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+    }
+}
+
+/// A request that wasn't sent because the breaker of its endpoint is open
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitOpen {
+    pub endpoint: String,
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' failed repeatedly, requests are paused for {}s",
+            self.endpoint,
+            self.retry_in.as_secs()
+        )
+    }
+}
+
+/// State of an endpoint's circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Requests are sent
+    Closed,
+    /// Requests fail without being sent
+    Open,
+    /// One trial request is in flight, others fail without being sent
+    HalfOpen,
+}
+
+/// Circuit breaker of an endpoint, as reported on `/api/status/details`
+#[derive(Debug, Clone, Serialize, PartialEq, utoipa::ToSchema)]
+pub struct CircuitBreakerStatus {
+    pub endpoint: String,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    /// Seconds until an open breaker lets a trial request through
+    pub retry_in_secs: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+}
+
+impl Breaker {
+    fn retry_in(&self, now: Instant) -> Option<Duration> {
+        self.opened_at.map(|opened_at| OPEN_DURATION.saturating_sub(now.duration_since(opened_at)))
+    }
+}
+
+/// Circuit breakers of all endpoints that were requested
+#[derive(Default)]
+pub struct CircuitBreakers {
+    breakers: RwLock<HashMap<String, Breaker>>,
+}
+
+impl CircuitBreakers {
+    /// Check if a request to an endpoint may be sent
+    /// Turns an open breaker half-open once it has been open long enough, letting this request through.
+    /// A trial request that never reports back is replaced by a new one after the same time.
+    fn allow(&self, endpoint: &str, now: Instant) -> Result<(), CircuitOpen> {
+        let mut breakers = self.breakers.write().unwrap();
+        let breaker = breakers.entry(endpoint.to_string()).or_default();
+        match breaker.state {
+            BreakerState::Closed => Ok(()),
+            BreakerState::Open | BreakerState::HalfOpen if breaker.retry_in(now).is_some_and(|d| d.is_zero()) => {
+                log::info!("Sending a trial request to '{}'", endpoint);
+                breaker.state = BreakerState::HalfOpen;
+                breaker.opened_at = Some(now);
+                Ok(())
+            }
+            BreakerState::Open | BreakerState::HalfOpen => Err(CircuitOpen {
+                endpoint: endpoint.to_string(),
+                retry_in: breaker.retry_in(now).unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// The endpoint answered, close its breaker
+    fn record_success(&self, endpoint: &str) {
+        let mut breakers = self.breakers.write().unwrap();
+        let breaker = breakers.entry(endpoint.to_string()).or_default();
+        if breaker.state != BreakerState::Closed {
+            log::info!("'{}' is answering again, resuming requests", endpoint);
+        }
+        *breaker = Breaker::default();
+    }
+
+    /// A request to the endpoint failed, open its breaker if it keeps failing
+    fn record_failure(&self, endpoint: &str, now: Instant) {
+        let mut breakers = self.breakers.write().unwrap();
+        let breaker = breakers.entry(endpoint.to_string()).or_default();
+        breaker.consecutive_failures += 1;
+        let trial_failed = breaker.state == BreakerState::HalfOpen;
+        if trial_failed || breaker.consecutive_failures >= FAILURE_THRESHOLD {
+            if breaker.state == BreakerState::Closed {
+                log::warn!(
+                    "'{}' failed {} times in a row, pausing requests for {}s",
+                    endpoint,
+                    breaker.consecutive_failures,
+                    OPEN_DURATION.as_secs()
+                );
+            }
+            breaker.state = BreakerState::Open;
+            breaker.opened_at = Some(now);
+        }
+    }
+
+    /// Breaker states of all endpoints, sorted by endpoint
+    pub fn get_all(&self) -> Vec<CircuitBreakerStatus> {
+        let now = Instant::now();
+        let breakers = self.breakers.read().unwrap();
+        let mut statuses: Vec<CircuitBreakerStatus> = breakers
+            .iter()
+            .map(|(endpoint, breaker)| CircuitBreakerStatus {
+                endpoint: endpoint.clone(),
+                state: breaker.state,
+                consecutive_failures: breaker.consecutive_failures,
+                retry_in_secs: match breaker.state {
+                    BreakerState::Open => breaker.retry_in(now).map(|d| d.as_secs()),
+                    _ => None,
+                },
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        statuses
+    }
+}
+
+/// Global circuit breakers
+static CIRCUIT_BREAKERS: OnceLock<CircuitBreakers> = OnceLock::new();
+
+/// Get the global circuit breakers instance
+pub fn get_circuit_breakers() -> &'static CircuitBreakers {
+    CIRCUIT_BREAKERS.get_or_init(CircuitBreakers::default)
+}
+
+/// Make a request to `endpoint`, retrying transient failures with exponential backoff
+/// Fails without sending anything while the endpoint's breaker is open
+pub(super) async fn with_retry<T, E, F, Fut>(
+    kind: EndpointKind,
+    endpoint: &str,
+    policy: RetryPolicy,
+    attempt: F,
+) -> Result<T, E>
+where
+    E: TransientError + From<CircuitOpen> + fmt::Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_with(get_circuit_breakers(), get_health_monitor(), kind, endpoint, policy, attempt).await
+}
+
+async fn retry_with<T, E, F, Fut>(
+    breakers: &CircuitBreakers,
+    health: &HealthMonitor,
+    kind: EndpointKind,
+    endpoint: &str,
+    policy: RetryPolicy,
+    attempt: F,
+) -> Result<T, E>
+where
+    E: TransientError + From<CircuitOpen> + fmt::Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let started = Instant::now();
+    let mut attempt_number = 1;
+    loop {
+        breakers.allow(endpoint, Instant::now())?;
+        match attempt().await {
+            Err(e) if e.is_transient() => {
+                breakers.record_failure(endpoint, Instant::now());
+                health.record_failure(kind, endpoint, &e.to_string(), chrono::Utc::now());
+                if attempt_number >= policy.max_attempts {
+                    log::error!("Request to '{}' failed after {} attempts: {}", endpoint, attempt_number, e);
+                    return Err(e);
+                }
+                let delay = policy.delay_after(attempt_number);
+                if policy.max_total.is_some_and(|max_total| started.elapsed() + delay + REQUEST_TIMEOUT > max_total) {
+                    log::error!("Request to '{}' failed after {} attempts, no time left to retry: {}", endpoint, attempt_number, e);
+                    return Err(e);
+                }
+                log::warn!(
+                    "Request to '{}' failed (attempt {}/{}): {}. Retrying in {:?}...",
+                    endpoint, attempt_number, policy.max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt_number += 1;
+            }
+            // Anything else means the endpoint answered
+            result => {
+                breakers.record_success(endpoint);
+                health.record_success(kind, endpoint, chrono::Utc::now());
+                return result;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug)]
+    enum TestError {
+        Transient,
+        Permanent,
+        Open,
+    }
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl TransientError for TestError {
+        fn is_transient(&self) -> bool {
+            matches!(self, TestError::Transient)
+        }
+    }
+
+    impl From<CircuitOpen> for TestError {
+        fn from(_: CircuitOpen) -> Self {
+            TestError::Open
+        }
+    }
+
+    const FAST: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(2),
+        max_total: None,
+    };
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let delays: Vec<Duration> = (1..=4).map(|attempt| RetryPolicy::COMMAND.delay_after(attempt)).collect();
+        assert_eq!(delays, vec![
+            Duration::from_secs(2),
+            Duration::from_secs(4),
+            Duration::from_secs(8),
+            Duration::from_secs(8),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_only() {
+        let breakers = CircuitBreakers::default();
+        let health = HealthMonitor::default();
+        let attempts = AtomicU32::new(0);
+        let result = retry_with(&breakers, &health, EndpointKind::AcController, "LivingRoom", FAST, || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(TestError::Transient),
+                _ => Ok(42),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), _> = retry_with(&breakers, &health, EndpointKind::AcController, "LivingRoom", FAST, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(TestError::Permanent)
+        })
+        .await;
+        assert!(matches!(result, Err(TestError::Permanent)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(breakers.get_all()[0].consecutive_failures, 0, "the endpoint answered");
+    }
+
+    #[test]
+    fn test_only_server_errors_and_timeouts_are_transient() {
+        use reqwest::StatusCode;
+
+        assert!(is_transient_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient_status(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_transient_status(StatusCode::BAD_REQUEST));
+        assert!(!is_transient_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+        assert!(!is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_within_max_total() {
+        let breakers = CircuitBreakers::default();
+        let health = HealthMonitor::default();
+        let attempts = AtomicU32::new(0);
+        let failing = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(TestError::Transient)
+        };
+
+        // A retry that could time out after max_total isn't made
+        let result = retry_with(&breakers, &health, EndpointKind::AcController, "LivingRoom", FAST.within(REQUEST_TIMEOUT), failing).await;
+        assert!(matches!(result, Err(TestError::Transient)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let result = retry_with(&breakers, &health, EndpointKind::AcController, "Bedroom", FAST.within(REQUEST_TIMEOUT * 2), failing).await;
+        assert!(matches!(result, Err(TestError::Transient)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1 + FAST.max_attempts);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_and_fails_fast() {
+        let breakers = CircuitBreakers::default();
+        let health = HealthMonitor::default();
+        let attempts = AtomicU32::new(0);
+        let failing = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(TestError::Transient)
+        };
+
+        // 3 attempts, then 2 more open the breaker, after which nothing is sent
+        assert!(matches!(retry_with(&breakers, &health, EndpointKind::AcController, "Veranda", FAST, failing).await, Err(TestError::Transient)));
+        assert!(matches!(retry_with(&breakers, &health, EndpointKind::AcController, "Veranda", FAST, failing).await, Err(TestError::Open)));
+        assert_eq!(attempts.load(Ordering::SeqCst), FAILURE_THRESHOLD);
+        assert!(matches!(retry_with(&breakers, &health, EndpointKind::AcController, "Veranda", FAST, failing).await, Err(TestError::Open)));
+        assert_eq!(attempts.load(Ordering::SeqCst), FAILURE_THRESHOLD);
+
+        let status = &breakers.get_all()[0];
+        assert_eq!(status.state, BreakerState::Open);
+        assert!(status.retry_in_secs.is_some());
+
+        // Other endpoints are unaffected
+        assert_eq!(retry_with(&breakers, &health, EndpointKind::AcController, "Bedroom", FAST, || async { Ok::<_, TestError>(1) }).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_breaker_half_open_trial() {
+        let breakers = CircuitBreakers::default();
+        let start = Instant::now();
+        for _ in 0..FAILURE_THRESHOLD {
+            breakers.record_failure("Veranda", start);
+        }
+        assert!(breakers.allow("Veranda", start + Duration::from_secs(30)).is_err());
+
+        // One trial request after the open period, a failed trial reopens the breaker
+        let after_open = start + OPEN_DURATION;
+        assert!(breakers.allow("Veranda", after_open).is_ok());
+        assert!(breakers.allow("Veranda", after_open).is_err(), "only one trial request");
+        breakers.record_failure("Veranda", after_open);
+        assert!(breakers.allow("Veranda", after_open + Duration::from_secs(1)).is_err());
+
+        // A successful trial closes it
+        let after_reopen = after_open + OPEN_DURATION;
+        assert!(breakers.allow("Veranda", after_reopen).is_ok());
+        breakers.record_success("Veranda");
+        assert!(breakers.allow("Veranda", after_reopen).is_ok());
+        assert_eq!(breakers.get_all()[0].state, BreakerState::Closed);
+    }
+}
@@ -0,0 +1,237 @@
+use super::{cache::DataCache, common};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+use crate::nodes::EnergyPriceData;
+
+const HOUR_SECS: i64 = 60 * 60;
+
+#[derive(Debug)]
+pub enum EnergyPriceError {
+    RequestFailed(String),
+    ParseError(String),
+}
+
+impl std::fmt::Display for EnergyPriceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnergyPriceError::RequestFailed(msg) => write!(f, "Energy price API request failed: {}", msg),
+            EnergyPriceError::ParseError(msg) => write!(f, "Failed to parse energy prices: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for EnergyPriceError {}
+
+/// Day-ahead price response of the Energy-Charts API (EPEX spot prices)
+#[derive(Debug, Deserialize)]
+struct EnergyChartsPriceResponse {
+    unix_seconds: Vec<i64>,
+    /// Price per interval in EUR/MWh, null for intervals without a price
+    price: Vec<Option<f64>>,
+    unit: String,
+}
+
+/// Price of the interval starting at `start` (hourly or quarter-hourly, depending on the market)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricePoint {
+    pub start: i64,
+    pub eur_kwh: f64,
+}
+
+/// Get the day-ahead prices of a bidding zone (e.g. "NL", "DE-LU") from the current hour
+/// through tomorrow, oldest first. Tomorrow's prices are published around 13:00 CET.
+pub async fn get_day_ahead_prices(bidding_zone: &str) -> Result<Vec<PricePoint>, EnergyPriceError> {
+    let start = hour_start(chrono::Utc::now().timestamp());
+    let url = format!(
+        "https://api.energy-charts.info/price?bzn={}&start={}&end={}",
+        bidding_zone,
+        start,
+        start + 48 * HOUR_SECS
+    );
+
+    let client = common::get_client().await;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| EnergyPriceError::RequestFailed(e.to_string()))?;
+
+    let data: EnergyChartsPriceResponse = response
+        .json()
+        .await
+        .map_err(|e| EnergyPriceError::ParseError(e.to_string()))?;
+
+    parse_prices(data)
+}
+
+fn parse_prices(data: EnergyChartsPriceResponse) -> Result<Vec<PricePoint>, EnergyPriceError> {
+    let per_kwh_divisor = match data.unit.replace(' ', "").as_str() {
+        "EUR/MWh" => 1000.0,
+        "EUR/kWh" => 1.0,
+        unit => return Err(EnergyPriceError::ParseError(format!("Unexpected price unit '{}'", unit))),
+    };
+
+    if data.unix_seconds.len() != data.price.len() {
+        return Err(EnergyPriceError::ParseError("Mismatched price and timestamp counts".to_string()));
+    }
+
+    Ok(data
+        .unix_seconds
+        .into_iter()
+        .zip(data.price)
+        .filter_map(|(start, price)| {
+            price.map(|price| PricePoint {
+                start,
+                eur_kwh: price / per_kwh_divisor,
+            })
+        })
+        .collect())
+}
+
+/// Start of the UTC hour containing a timestamp
+fn hour_start(timestamp: i64) -> i64 {
+    timestamp - timestamp.rem_euclid(HOUR_SECS)
+}
+
+/// Average price per hour, keyed by hour start
+fn hourly_averages(prices: &[PricePoint]) -> Vec<(i64, f64)> {
+    let mut hours: Vec<(i64, f64, u32)> = Vec::new();
+    for point in prices {
+        let hour = hour_start(point.start);
+        match hours.last_mut() {
+            Some((last_hour, sum, count)) if *last_hour == hour => {
+                *sum += point.eur_kwh;
+                *count += 1;
+            }
+            _ => hours.push((hour, point.eur_kwh, 1)),
+        }
+    }
+    hours.into_iter().map(|(hour, sum, count)| (hour, sum / f64::from(count))).collect()
+}
+
+/// Average price of the hour containing `now` in EUR/kWh, None if unknown
+pub fn current_price_eur_kwh(prices: &[PricePoint], now: i64) -> Option<f64> {
+    let current_hour = hour_start(now);
+    hourly_averages(prices)
+        .into_iter()
+        .find(|(hour, _)| *hour == current_hour)
+        .map(|(_, price)| price)
+}
+
+/// Hours of the next 24 with a known price as offsets from the current hour, cheapest first
+/// Equally priced hours keep their chronological order
+pub fn cheapest_hours_next_24h(prices: &[PricePoint], now: i64) -> Vec<i64> {
+    let current_hour = hour_start(now);
+    let mut hours: Vec<(i64, f64)> = hourly_averages(prices)
+        .into_iter()
+        .map(|(hour, price)| ((hour - current_hour) / HOUR_SECS, price))
+        .filter(|(offset, _)| (0..24).contains(offset))
+        .collect();
+    hours.sort_by(|a, b| a.1.total_cmp(&b.1));
+    hours.into_iter().map(|(offset, _)| offset).collect()
+}
+
+static PRICE_CACHE: OnceLock<DataCache<Vec<PricePoint>>> = OnceLock::new();
+
+fn get_price_cache() -> &'static DataCache<Vec<PricePoint>> {
+    PRICE_CACHE.get_or_init(|| DataCache::new(3600)) // 1 hour, picks up tomorrow's prices soon after publication
+}
+
+/// Get the day-ahead prices with caching (1 hour TTL)
+/// Falls back to stale cache if API request fails
+pub async fn get_day_ahead_prices_cached(bidding_zone: &str) -> Result<Vec<PricePoint>, EnergyPriceError> {
+    let cache = get_price_cache();
+
+    cache.get_or_fetch_with_stale_fallback(bidding_zone, || async {
+        get_day_ahead_prices(bidding_zone).await
+    }).await
+}
+
+/// Get the price inputs for nodesets
+/// Empty if no bidding zone is configured or the prices can't be fetched
+pub async fn get_energy_price_data(bidding_zone: &str, now: i64) -> EnergyPriceData {
+    if bidding_zone.is_empty() {
+        return EnergyPriceData::default();
+    }
+
+    match get_day_ahead_prices_cached(bidding_zone).await {
+        Ok(prices) => EnergyPriceData {
+            current_price_eur_kwh: current_price_eur_kwh(&prices, now),
+            cheapest_hours_next_24h: cheapest_hours_next_24h(&prices, now),
+        },
+        Err(e) => {
+            log::warn!("Failed to get energy prices: {}", e);
+            EnergyPriceData::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-11-24T00:00:00Z
+    const DAY_START: i64 = 1763942400;
+
+    fn hourly(prices: &[f64]) -> Vec<PricePoint> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, price)| PricePoint {
+                start: DAY_START + i as i64 * HOUR_SECS,
+                eur_kwh: *price,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_energy_charts_response() {
+        let json = r#"{
+            "license_info": "CC BY 4.0 (creativecommons.org/licenses/by/4.0) from Bundesnetzagentur | SMARD.de",
+            "unix_seconds": [1763942400, 1763946000, 1763949600],
+            "price": [95.5, null, -3.2],
+            "unit": "EUR / MWh",
+            "deprecated": false
+        }"#;
+
+        let data: EnergyChartsPriceResponse = serde_json::from_str(json).unwrap();
+        let prices = parse_prices(data).unwrap();
+        assert_eq!(prices, vec![
+            PricePoint { start: 1763942400, eur_kwh: 0.0955 },
+            PricePoint { start: 1763949600, eur_kwh: -0.0032 },
+        ]);
+    }
+
+    #[test]
+    fn test_current_price_averages_quarter_hours() {
+        let prices: Vec<PricePoint> = [0.10, 0.20, 0.30, 0.40]
+            .iter()
+            .enumerate()
+            .map(|(i, price)| PricePoint {
+                start: DAY_START + i as i64 * 900,
+                eur_kwh: *price,
+            })
+            .collect();
+
+        let price = current_price_eur_kwh(&prices, DAY_START + 1800).unwrap();
+        assert!((price - 0.25).abs() < 1e-9);
+        assert_eq!(current_price_eur_kwh(&prices, DAY_START + HOUR_SECS), None);
+    }
+
+    #[test]
+    fn test_cheapest_hours_next_24h() {
+        let prices = hourly(&[0.30, 0.25, 0.10, 0.25, -0.02]);
+
+        // From the second hour on; the first hour is in the past
+        let now = DAY_START + HOUR_SECS + 600;
+        assert_eq!(cheapest_hours_next_24h(&prices, now), vec![3, 1, 0, 2]);
+
+        let mut day = vec![0.2; 30];
+        day[25] = 0.0;
+        // Hour 25 is beyond the next 24 hours
+        assert_eq!(cheapest_hours_next_24h(&hourly(&day), DAY_START).len(), 24);
+        assert_eq!(cheapest_hours_next_24h(&hourly(&day), DAY_START)[0], 0);
+    }
+}
@@ -0,0 +1,356 @@
+//! Health of the upstream endpoints
+//!
+//! Every request attempt to an AC controller, the meter or the weather API is recorded next to its
+//! circuit breaker. An endpoint that is failing is degraded, and offline once it failed as often
+//! as it takes to open its breaker. States are reported on `/api/status/health`.
+//!
+//! When an AC controller has been failing for `health_alerts.after_minutes`, an alert is sent to
+//! the configured channels (webhook, ntfy or Telegram), and another one once it answers again.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use super::common::FAILURE_THRESHOLD;
+use crate::{config, device_requests::notification, types::AlertChannel};
+
+/// How often failing AC controllers are checked for alerts
+const ALERT_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Kind of upstream endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointKind {
+    AcController,
+    Meter,
+    Weather,
+}
+
+/// Health of an endpoint, from its recent requests
+/// Ordered from best to worst, except that Unknown counts for nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// Not requested since startup
+    Unknown,
+    /// The last request succeeded
+    Healthy,
+    /// The last requests failed, but fewer than open the circuit breaker
+    Degraded,
+    /// Failed often enough in a row to open the circuit breaker
+    Offline,
+}
+
+/// Health of an endpoint, as reported on `/api/status/health`
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct EndpointHealthStatus {
+    pub endpoint: String,
+    pub kind: EndpointKind,
+    pub status: HealthStatus,
+    pub consecutive_failures: u32,
+    /// Unix timestamp of the first failure in a row, None while healthy
+    pub failing_since: Option<i64>,
+    /// Unix timestamp of the last successful request
+    pub last_success: Option<i64>,
+    /// Error of the last failed request, None while healthy
+    pub last_error: Option<String>,
+}
+
+/// Worst status of the endpoints, Unknown if none of them was requested
+pub fn overall_status(statuses: &[EndpointHealthStatus]) -> HealthStatus {
+    statuses.iter().map(|status| status.status).max().unwrap_or(HealthStatus::Unknown)
+}
+
+/// Alert about an AC controller, see `HealthMonitor::take_alerts`
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthAlert {
+    Unreachable { endpoint: String, minutes: i64 },
+    Recovered { endpoint: String },
+}
+
+impl HealthAlert {
+    /// Title and message of the alert
+    fn text(&self) -> (String, String) {
+        match self {
+            HealthAlert::Unreachable { endpoint, minutes } => (
+                format!("AC '{}' is unreachable", endpoint),
+                format!("The AC controller '{}' has not answered for {} minutes, commands are not reaching it.", endpoint, minutes),
+            ),
+            HealthAlert::Recovered { endpoint } => (
+                format!("AC '{}' is reachable again", endpoint),
+                format!("The AC controller '{}' is answering again.", endpoint),
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EndpointHealth {
+    kind: EndpointKind,
+    consecutive_failures: u32,
+    failing_since: Option<DateTime<Utc>>,
+    last_success: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    /// Whether an unreachable alert was sent for the current failures
+    alerted: bool,
+}
+
+impl EndpointHealth {
+    fn new(kind: EndpointKind) -> Self {
+        Self {
+            kind,
+            consecutive_failures: 0,
+            failing_since: None,
+            last_success: None,
+            last_error: None,
+            alerted: false,
+        }
+    }
+
+    fn status(&self) -> HealthStatus {
+        match self.consecutive_failures {
+            0 if self.last_success.is_none() => HealthStatus::Unknown,
+            0 => HealthStatus::Healthy,
+            n if n < FAILURE_THRESHOLD => HealthStatus::Degraded,
+            _ => HealthStatus::Offline,
+        }
+    }
+}
+
+/// Tracks request outcomes per endpoint
+#[derive(Default)]
+pub struct HealthMonitor {
+    endpoints: RwLock<HashMap<String, EndpointHealth>>,
+    /// AC controllers that answered again after an unreachable alert
+    recovered: RwLock<Vec<String>>,
+}
+
+impl HealthMonitor {
+    /// The endpoint answered
+    pub fn record_success(&self, kind: EndpointKind, endpoint: &str, now: DateTime<Utc>) {
+        let mut endpoints = self.endpoints.write().unwrap();
+        let health = endpoints.entry(endpoint.to_string()).or_insert_with(|| EndpointHealth::new(kind));
+        if health.alerted {
+            self.recovered.write().unwrap().push(endpoint.to_string());
+        }
+        *health = EndpointHealth {
+            last_success: Some(now),
+            ..EndpointHealth::new(kind)
+        };
+    }
+
+    /// A request to the endpoint failed
+    pub fn record_failure(&self, kind: EndpointKind, endpoint: &str, error: &str, now: DateTime<Utc>) {
+        let mut endpoints = self.endpoints.write().unwrap();
+        let health = endpoints.entry(endpoint.to_string()).or_insert_with(|| EndpointHealth::new(kind));
+        health.consecutive_failures += 1;
+        health.failing_since.get_or_insert(now);
+        health.last_error = Some(error.to_string());
+    }
+
+    /// Health of all endpoints that were requested, plus the configured AC controllers that weren't
+    /// Sorted by kind, then endpoint
+    pub fn get_all(&self, ac_devices: &[String]) -> Vec<EndpointHealthStatus> {
+        let endpoints = self.endpoints.read().unwrap();
+        let mut statuses: Vec<EndpointHealthStatus> = endpoints
+            .iter()
+            .map(|(endpoint, health)| EndpointHealthStatus {
+                endpoint: endpoint.clone(),
+                kind: health.kind,
+                status: health.status(),
+                consecutive_failures: health.consecutive_failures,
+                failing_since: health.failing_since.map(|t| t.timestamp()),
+                last_success: health.last_success.map(|t| t.timestamp()),
+                last_error: health.last_error.clone(),
+            })
+            .collect();
+        for device in ac_devices.iter().filter(|device| !endpoints.contains_key(*device)) {
+            statuses.push(EndpointHealthStatus {
+                endpoint: device.clone(),
+                kind: EndpointKind::AcController,
+                status: HealthStatus::Unknown,
+                consecutive_failures: 0,
+                failing_since: None,
+                last_success: None,
+                last_error: None,
+            });
+        }
+        statuses.sort_by(|a, b| (a.kind as u8, &a.endpoint).cmp(&(b.kind as u8, &b.endpoint)));
+        statuses
+    }
+
+    /// Alerts that are due: AC controllers failing for at least `after_minutes` that weren't
+    /// alerted about yet, and alerted ones that answered again since the last call
+    pub fn take_alerts(&self, after_minutes: u32, now: DateTime<Utc>) -> Vec<HealthAlert> {
+        let mut alerts: Vec<HealthAlert> = self
+            .recovered
+            .write()
+            .unwrap()
+            .drain(..)
+            .map(|endpoint| HealthAlert::Recovered { endpoint })
+            .collect();
+
+        let mut endpoints = self.endpoints.write().unwrap();
+        let mut unreachable: Vec<HealthAlert> = endpoints
+            .iter_mut()
+            .filter(|(_, health)| health.kind == EndpointKind::AcController && !health.alerted)
+            .filter_map(|(endpoint, health)| {
+                let failing_for = now - health.failing_since?;
+                if failing_for < Duration::minutes(i64::from(after_minutes)) {
+                    return None;
+                }
+                health.alerted = true;
+                Some(HealthAlert::Unreachable {
+                    endpoint: endpoint.clone(),
+                    minutes: failing_for.num_minutes(),
+                })
+            })
+            .collect();
+        unreachable.sort_by_key(|alert| format!("{:?}", alert));
+        alerts.extend(unreachable);
+        alerts
+    }
+}
+
+/// Global health monitor
+static HEALTH_MONITOR: OnceLock<HealthMonitor> = OnceLock::new();
+
+/// Get the global health monitor instance
+pub fn get_health_monitor() -> &'static HealthMonitor {
+    HEALTH_MONITOR.get_or_init(HealthMonitor::default)
+}
+
+/// Send an alert to a channel
+async fn send_alert(channel: &AlertChannel, title: &str, message: &str, endpoint: &str) -> Result<(), reqwest::Error> {
+    match channel {
+        AlertChannel::Webhook { url } => notification::send_notification(url, title, message, endpoint).await,
+        AlertChannel::Ntfy { url } => notification::send_ntfy(url, title, message).await,
+        AlertChannel::Telegram { bot_token, chat_id } => {
+            notification::send_telegram(bot_token, chat_id, &format!("{}\n{}", title, message)).await
+        }
+    }
+}
+
+/// Periodically send the due alerts to the configured channels
+/// Without channels the alerts are only logged
+pub async fn health_alert_loop() {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(ALERT_CHECK_INTERVAL_SECS)).await;
+
+        let cfg = config::get_config();
+        for alert in get_health_monitor().take_alerts(cfg.health_alerts.after_minutes, Utc::now()) {
+            let (title, message) = alert.text();
+            log::warn!("{}: {}", title, message);
+            let endpoint = match &alert {
+                HealthAlert::Unreachable { endpoint, .. } | HealthAlert::Recovered { endpoint } => endpoint,
+            };
+            for channel in &cfg.health_alerts.channels {
+                if let Err(e) = send_alert(channel, &title, &message, endpoint).await {
+                    log::warn!("Failed to send health alert to {}: {}", channel.kind(), e.without_url());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_follows_consecutive_failures() {
+        let monitor = HealthMonitor::default();
+        let now = Utc::now();
+        let status = |monitor: &HealthMonitor| monitor.get_all(&["LivingRoom".to_string()])[0].clone();
+
+        assert_eq!(status(&monitor).status, HealthStatus::Unknown);
+        monitor.record_success(EndpointKind::AcController, "LivingRoom", now);
+        assert_eq!(status(&monitor).status, HealthStatus::Healthy);
+
+        monitor.record_failure(EndpointKind::AcController, "LivingRoom", "timeout", now + Duration::minutes(1));
+        let degraded = status(&monitor);
+        assert_eq!(degraded.status, HealthStatus::Degraded);
+        assert_eq!(degraded.failing_since, Some((now + Duration::minutes(1)).timestamp()));
+        assert_eq!(degraded.last_error.as_deref(), Some("timeout"));
+
+        for _ in 1..FAILURE_THRESHOLD {
+            monitor.record_failure(EndpointKind::AcController, "LivingRoom", "timeout", now + Duration::minutes(2));
+        }
+        let offline = status(&monitor);
+        assert_eq!(offline.status, HealthStatus::Offline);
+        assert_eq!(offline.failing_since, degraded.failing_since, "The first failure in a row is kept");
+        assert_eq!(offline.last_success, Some(now.timestamp()));
+
+        monitor.record_success(EndpointKind::AcController, "LivingRoom", now + Duration::minutes(3));
+        let recovered = status(&monitor);
+        assert_eq!((recovered.status, recovered.failing_since, recovered.last_error), (HealthStatus::Healthy, None, None));
+    }
+
+    #[test]
+    fn test_get_all_sorted_by_kind() {
+        let monitor = HealthMonitor::default();
+        let now = Utc::now();
+        monitor.record_success(EndpointKind::Weather, "api.open-meteo.com", now);
+        monitor.record_success(EndpointKind::Meter, "meter", now);
+        monitor.record_success(EndpointKind::AcController, "Veranda", now);
+
+        let endpoints: Vec<(String, HealthStatus)> = monitor
+            .get_all(&["LivingRoom".to_string(), "Veranda".to_string()])
+            .into_iter()
+            .map(|status| (status.endpoint, status.status))
+            .collect();
+        assert_eq!(endpoints, vec![
+            ("LivingRoom".to_string(), HealthStatus::Unknown),
+            ("Veranda".to_string(), HealthStatus::Healthy),
+            ("meter".to_string(), HealthStatus::Healthy),
+            ("api.open-meteo.com".to_string(), HealthStatus::Healthy),
+        ]);
+    }
+
+    #[test]
+    fn test_overall_status_is_worst() {
+        let monitor = HealthMonitor::default();
+        let now = Utc::now();
+        assert_eq!(overall_status(&monitor.get_all(&["Veranda".to_string()])), HealthStatus::Unknown);
+
+        monitor.record_success(EndpointKind::Meter, "meter", now);
+        assert_eq!(overall_status(&monitor.get_all(&["Veranda".to_string()])), HealthStatus::Healthy);
+
+        monitor.record_failure(EndpointKind::AcController, "Veranda", "timeout", now);
+        assert_eq!(overall_status(&monitor.get_all(&[])), HealthStatus::Degraded);
+    }
+
+    #[test]
+    fn test_alerts_once_per_outage_and_on_recovery() {
+        let monitor = HealthMonitor::default();
+        let now = Utc::now();
+        monitor.record_failure(EndpointKind::AcController, "Veranda", "timeout", now);
+        monitor.record_failure(EndpointKind::Meter, "meter", "timeout", now);
+
+        assert!(monitor.take_alerts(15, now + Duration::minutes(14)).is_empty());
+        assert_eq!(
+            monitor.take_alerts(15, now + Duration::minutes(16)),
+            vec![HealthAlert::Unreachable { endpoint: "Veranda".to_string(), minutes: 16 }],
+            "Only AC controllers are alerted about"
+        );
+        assert!(monitor.take_alerts(15, now + Duration::minutes(30)).is_empty(), "Alerted once per outage");
+
+        monitor.record_success(EndpointKind::AcController, "Veranda", now + Duration::minutes(31));
+        assert_eq!(
+            monitor.take_alerts(15, now + Duration::minutes(32)),
+            vec![HealthAlert::Recovered { endpoint: "Veranda".to_string() }]
+        );
+        assert!(monitor.take_alerts(15, now + Duration::minutes(33)).is_empty());
+    }
+
+    #[test]
+    fn test_no_recovery_alert_without_unreachable_alert() {
+        let monitor = HealthMonitor::default();
+        let now = Utc::now();
+        monitor.record_failure(EndpointKind::AcController, "Veranda", "timeout", now);
+        monitor.record_success(EndpointKind::AcController, "Veranda", now + Duration::minutes(1));
+
+        assert!(monitor.take_alerts(15, now + Duration::minutes(20)).is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+use super::common;
+use serde::Deserialize;
+
+#[derive(Debug)]
+pub enum HomeAssistantError {
+    RequestFailed(String),
+    ParseError(String),
+}
+
+impl std::fmt::Display for HomeAssistantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HomeAssistantError::RequestFailed(msg) => write!(f, "Home Assistant request failed: {}", msg),
+            HomeAssistantError::ParseError(msg) => write!(f, "Failed to parse Home Assistant state: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HomeAssistantError {}
+
+/// State object of an entity as returned by `GET /api/states/<entity_id>`
+#[derive(Debug, Deserialize)]
+struct EntityState {
+    state: String,
+}
+
+/// Get the state of an entity, e.g. "home" or "not_home" for a person entity
+pub async fn get_entity_state(base_url: &str, token: &str, entity_id: &str) -> Result<String, HomeAssistantError> {
+    let url = format!("{}/api/states/{}", base_url.trim_end_matches('/'), entity_id);
+
+    let client = common::get_client().await;
+    let response = client
+        .get(&url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| HomeAssistantError::RequestFailed(e.to_string()))?;
+
+    let entity: EntityState = response
+        .json()
+        .await
+        .map_err(|e| HomeAssistantError::ParseError(e.to_string()))?;
+
+    Ok(entity.state)
+}
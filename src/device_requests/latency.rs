@@ -0,0 +1,137 @@
+//! AC command round-trip latency tracking
+//!
+//! Every command attempt to an AC endpoint records how long the request took, including
+//! attempts that failed with a network error (a timeout is the slowest possible answer).
+//! The last `LATENCY_WINDOW_SIZE` samples per endpoint give a rolling p95, and endpoints whose
+//! p95 exceeds `command_latency_slo_ms` are reported on `/api/status/details`.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+/// Number of recent command latencies kept per endpoint
+const LATENCY_WINDOW_SIZE: usize = 100;
+
+/// Global latency tracker
+static LATENCY_TRACKER: OnceLock<LatencyTracker> = OnceLock::new();
+
+/// Rolling latency stats for an endpoint
+#[derive(Debug, Clone, Serialize, PartialEq, utoipa::ToSchema)]
+pub struct EndpointLatency {
+    pub endpoint: String,
+    pub p95_ms: u64,
+    pub samples: usize,
+}
+
+/// Tracks recent command latencies per AC endpoint
+pub struct LatencyTracker {
+    endpoints: RwLock<HashMap<String, VecDeque<u64>>>,
+}
+
+/// 95th percentile (nearest rank) of a non-empty set of samples
+fn p95(samples: &VecDeque<u64>) -> u64 {
+    let mut sorted: Vec<u64> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    let rank = (sorted.len() * 95).div_ceil(100);
+    sorted[rank.saturating_sub(1)]
+}
+
+impl LatencyTracker {
+    fn new() -> Self {
+        Self {
+            endpoints: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record the round-trip latency of a command attempt
+    pub fn record(&self, endpoint: &str, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        let mut endpoints = self.endpoints.write().unwrap();
+        let samples = endpoints.entry(endpoint.to_string()).or_default();
+
+        samples.push_back(latency_ms);
+        if samples.len() > LATENCY_WINDOW_SIZE {
+            samples.pop_front();
+        }
+    }
+
+    /// Rolling latency stats for all endpoints with at least one command, sorted by endpoint
+    pub fn get_all(&self) -> Vec<EndpointLatency> {
+        let endpoints = self.endpoints.read().unwrap();
+        let mut stats: Vec<EndpointLatency> = endpoints
+            .iter()
+            .filter(|(_, samples)| !samples.is_empty())
+            .map(|(endpoint, samples)| EndpointLatency {
+                endpoint: endpoint.clone(),
+                p95_ms: p95(samples),
+                samples: samples.len(),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        stats
+    }
+
+    /// Endpoints whose rolling p95 exceeds the SLO. An SLO of 0 disables the check.
+    pub fn get_slo_violations(&self, slo_ms: u64) -> Vec<EndpointLatency> {
+        if slo_ms == 0 {
+            return Vec::new();
+        }
+        self.get_all().into_iter().filter(|stats| stats.p95_ms > slo_ms).collect()
+    }
+}
+
+/// Get the global latency tracker instance
+pub fn get_latency_tracker() -> &'static LatencyTracker {
+    LATENCY_TRACKER.get_or_init(LatencyTracker::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p95() {
+        let samples: VecDeque<u64> = (1..=100).collect();
+        assert_eq!(p95(&samples), 95);
+
+        let samples: VecDeque<u64> = VecDeque::from(vec![40]);
+        assert_eq!(p95(&samples), 40);
+
+        // With fewer than 20 samples the slowest one is the p95, from 20 on one outlier is ignored
+        let samples: VecDeque<u64> = VecDeque::from(vec![100, 110, 90, 105, 95, 100, 5000, 98, 102, 99]);
+        assert_eq!(p95(&samples), 5000);
+        let samples: VecDeque<u64> = (0..20).map(|i| if i == 0 { 5000 } else { 100 }).collect();
+        assert_eq!(p95(&samples), 100);
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let tracker = LatencyTracker::new();
+        for _ in 0..LATENCY_WINDOW_SIZE {
+            tracker.record("Veranda", Duration::from_millis(5000));
+        }
+        for _ in 0..LATENCY_WINDOW_SIZE {
+            tracker.record("Veranda", Duration::from_millis(200));
+        }
+
+        let stats = tracker.get_all();
+        assert_eq!(stats[0].samples, LATENCY_WINDOW_SIZE);
+        assert_eq!(stats[0].p95_ms, 200);
+    }
+
+    #[test]
+    fn test_slo_violations() {
+        let tracker = LatencyTracker::new();
+        tracker.record("LivingRoom", Duration::from_millis(300));
+        tracker.record("Veranda", Duration::from_millis(4000));
+
+        let violations = tracker.get_slo_violations(2000);
+        assert_eq!(violations, vec![EndpointLatency {
+            endpoint: "Veranda".to_string(),
+            p95_ms: 4000,
+            samples: 1,
+        }]);
+        assert!(tracker.get_slo_violations(0).is_empty());
+    }
+}
@@ -1,8 +1,12 @@
-use super::common;
+use super::common::{self, CircuitOpen, RetryPolicy, TransientError};
+use super::health::EndpointKind;
 use super::cache::DataCache;
+use super::raw_payloads::{self, RawSource};
+use crate::live_events::{self, LiveEvent};
 use log::{debug, error, info};
 use serde::Deserialize;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 // Public data types
 #[derive(Debug, Deserialize, Clone)]
@@ -58,20 +62,46 @@ pub enum SmartMeterError {
     ApiError(String),
     NetworkError(reqwest::Error),
     NoReadingsAvailable,
+    /// Not requested because the meter failed repeatedly, see `common::CircuitOpen`
+    Unavailable(CircuitOpen),
+    /// The meter API rejected the request (4xx)
+    Rejected(String),
 }
 
+/// Circuit breaker name of the smart meter API
+const METER_ENDPOINT: &str = "meter";
+
 impl From<reqwest::Error> for SmartMeterError {
     fn from(err: reqwest::Error) -> Self {
         SmartMeterError::NetworkError(err)
     }
 }
 
+impl From<CircuitOpen> for SmartMeterError {
+    fn from(open: CircuitOpen) -> Self {
+        SmartMeterError::Unavailable(open)
+    }
+}
+
+impl TransientError for SmartMeterError {
+    /// Failed requests and server errors, parse errors happen after the retries
+    fn is_transient(&self) -> bool {
+        match self {
+            SmartMeterError::ApiError(_) => true,
+            SmartMeterError::NetworkError(e) => common::is_transient_request_error(e),
+            _ => false,
+        }
+    }
+}
+
 impl std::fmt::Display for SmartMeterError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SmartMeterError::ApiError(msg) => write!(f, "API error: {}", msg),
             SmartMeterError::NetworkError(err) => write!(f, "Network error: {}", err),
             SmartMeterError::NoReadingsAvailable => write!(f, "No readings available yet"),
+            SmartMeterError::Unavailable(open) => write!(f, "Meter unavailable: {}", open),
+            SmartMeterError::Rejected(msg) => write!(f, "Request rejected: {}", msg),
         }
     }
 }
@@ -83,28 +113,37 @@ pub async fn get_latest_reading() -> Result<RawMeterReading, SmartMeterError> {
     let url = format!("{}/latest", base_url);
 
     info!("Fetching latest smart meter reading");
-    let client = common::get_client().await;
+    let body = common::with_retry(EndpointKind::Meter, METER_ENDPOINT, RetryPolicy::READ, || async {
+        let client = common::get_client().await;
+        let response = client.get(&url).send().await?;
 
-    let response = client.get(&url).send().await?;
-
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        error!("No smart meter readings available yet");
-        return Err(SmartMeterError::NoReadingsAvailable);
-    }
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            error!("No smart meter readings available yet");
+            return Err(SmartMeterError::NoReadingsAvailable);
+        }
 
-    if !response.status().is_success() {
-        error!(
-            "Smart meter API returned error status: {}",
-            response.status()
-        );
-        return Err(SmartMeterError::ApiError(format!(
-            "HTTP {}",
-            response.status()
-        )));
-    }
+        if !response.status().is_success() {
+            error!(
+                "Smart meter API returned error status: {}",
+                response.status()
+            );
+            let message = format!("HTTP {}", response.status());
+            return Err(if common::is_transient_status(response.status()) {
+                SmartMeterError::ApiError(message)
+            } else {
+                SmartMeterError::Rejected(message)
+            });
+        }
 
-    let reading: RawMeterReading = response.json().await?;
+        Ok(raw_payloads::read_body(RawSource::Meter, response).await?)
+    }).await?;
+    let reading: RawMeterReading = serde_json::from_str(&body)
+        .map_err(|e| SmartMeterError::ApiError(format!("Failed to parse reading: {}", e)))?;
     debug!("Successfully fetched smart meter reading");
+    live_events::get_live_events().publish(LiveEvent::meter_reading(
+        reading.current_consumption_kw,
+        reading.current_production_kw,
+    ));
     Ok(reading)
 }
 
@@ -113,24 +152,32 @@ pub async fn get_solar_production() -> Result<SolarProduction, SmartMeterError>
     let url = format!("{}/solar", base_url);
 
     info!("Fetching current solar production");
-    let client = common::get_client().await;
-
-    let response = client.get(&url).send().await?;
-
-    if !response.status().is_success() {
-        error!("Solar API returned error status: {}", response.status());
-
-        // Try to extract error message from response body
-        if let Ok(error_body) = response.json::<serde_json::Value>().await {
-            if let Some(error_msg) = error_body.get("error").and_then(|e| e.as_str()) {
-                return Err(SmartMeterError::ApiError(error_msg.to_string()));
+    let body = common::with_retry(EndpointKind::Meter, METER_ENDPOINT, RetryPolicy::READ, || async {
+        let client = common::get_client().await;
+        let response = client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            error!("Solar API returned error status: {}", response.status());
+            let transient = common::is_transient_status(response.status());
+
+            // Try to extract error message from response body
+            let error_msg = match response.json::<serde_json::Value>().await {
+                Ok(error_body) => error_body.get("error").and_then(|e| e.as_str()).map(str::to_string),
+                Err(_) => None,
             }
-        }
+            .unwrap_or_else(|| "Unknown error".to_string());
 
-        return Err(SmartMeterError::ApiError("Unknown error".to_string()));
-    }
+            return Err(if transient {
+                SmartMeterError::ApiError(error_msg)
+            } else {
+                SmartMeterError::Rejected(error_msg)
+            });
+        }
 
-    let production: SolarProduction = response.json().await?;
+        Ok(raw_payloads::read_body(RawSource::Meter, response).await?)
+    }).await?;
+    let production: SolarProduction = serde_json::from_str(&body)
+        .map_err(|e| SmartMeterError::ApiError(format!("Failed to parse solar production: {}", e)))?;
     debug!(
         "Successfully fetched solar production: {} W",
         production.current_production
@@ -166,6 +213,11 @@ pub async fn get_latest_reading_cached() -> Result<RawMeterReading, SmartMeterEr
     }).await
 }
 
+/// Time since the cached meter reading was read, None if it was never read
+pub async fn get_latest_reading_age() -> Option<Duration> {
+    get_meter_reading_cache().age("latest").await
+}
+
 /// Get solar production with caching (10 second TTL)
 /// Recommended for dashboard use to reduce API calls
 pub async fn get_solar_production_cached() -> Result<SolarProduction, SmartMeterError> {
@@ -1,6 +1,14 @@
 pub mod ac;
+pub mod battery;
 mod cache;
-mod common;
+pub mod common;
+pub mod energy_price;
+pub mod health;
+pub mod home_assistant;
+pub mod latency;
 pub mod logging_queue;
 pub mod meter;
+pub mod notification;
+pub mod raw_payloads;
+pub mod solar_forecast;
 pub mod weather;
@@ -0,0 +1,64 @@
+use super::common;
+use log::debug;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct NotificationPayload<'a> {
+    title: &'a str,
+    message: &'a str,
+    device: &'a str,
+}
+
+/// Post a notification to a channel's webhook URL
+pub async fn send_notification(url: &str, title: &str, message: &str, device: &str) -> Result<(), reqwest::Error> {
+    debug!("Sending notification '{}' to {}", title, url);
+    let client = common::get_client().await;
+
+    client
+        .post(url)
+        .json(&NotificationPayload { title, message, device })
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Publish a message to an ntfy topic URL
+pub async fn send_ntfy(url: &str, title: &str, message: &str) -> Result<(), reqwest::Error> {
+    debug!("Sending ntfy message '{}' to {}", title, url);
+    let client = common::get_client().await;
+
+    client
+        .post(url)
+        .header("Title", title)
+        .body(message.to_string())
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct TelegramMessage<'a> {
+    chat_id: &'a str,
+    text: &'a str,
+}
+
+/// Send a message to a Telegram chat through a bot
+/// Errors don't include the URL, which contains the bot token
+pub async fn send_telegram(bot_token: &str, chat_id: &str, text: &str) -> Result<(), reqwest::Error> {
+    debug!("Sending Telegram message to chat {}", chat_id);
+    let client = common::get_client().await;
+
+    client
+        .post(format!("https://api.telegram.org/bot{}/sendMessage", bot_token))
+        .json(&TelegramMessage { chat_id, text })
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.without_url())?;
+
+    Ok(())
+}
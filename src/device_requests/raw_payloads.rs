@@ -0,0 +1,118 @@
+//! Last raw payloads received from upstream services
+//!
+//! Responses from the AC bridges, the smart meter and the weather provider are kept as received,
+//! before parsing, so a field that is parsed wrongly can be diagnosed through
+//! `GET /api/debug/raw/:source` instead of a packet capture. Only the last few payloads per
+//! source are kept, in memory.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Payloads kept per source
+const MAX_PAYLOADS_PER_SOURCE: usize = 20;
+
+/// Global raw payload store
+static RAW_PAYLOADS: OnceLock<Arc<RawPayloads>> = OnceLock::new();
+
+/// An upstream service whose payloads are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawSource {
+    AcBridge,
+    Meter,
+    Weather,
+}
+
+impl RawSource {
+    /// Source for its name in the API path: "ac", "meter" or "weather"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ac" => Some(RawSource::AcBridge),
+            "meter" => Some(RawSource::Meter),
+            "weather" => Some(RawSource::Weather),
+            _ => None,
+        }
+    }
+}
+
+/// A response body as received
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct RawPayload {
+    pub url: String,
+    /// HTTP status code of the response
+    pub status: u16,
+    /// Unix timestamp the response was received at
+    pub received_at: i64,
+    /// The body as JSON, or as a string if it isn't valid JSON
+    pub body: serde_json::Value,
+}
+
+/// Thread-safe store of the last payloads of each source
+#[derive(Default)]
+pub struct RawPayloads {
+    payloads: RwLock<HashMap<RawSource, VecDeque<RawPayload>>>,
+}
+
+impl RawPayloads {
+    /// Keep a response body, dropping the oldest payload of the source if it has too many
+    pub fn record(&self, source: RawSource, url: &str, status: u16, body: &str) {
+        let body = serde_json::from_str(body).unwrap_or_else(|_| serde_json::Value::String(body.to_string()));
+        let payload = RawPayload {
+            url: url.to_string(),
+            status,
+            received_at: chrono::Utc::now().timestamp(),
+            body,
+        };
+        let mut payloads = self.payloads.write().unwrap();
+        let source_payloads = payloads.entry(source).or_default();
+        if source_payloads.len() >= MAX_PAYLOADS_PER_SOURCE {
+            source_payloads.pop_front();
+        }
+        source_payloads.push_back(payload);
+    }
+
+    /// Payloads of a source, newest first
+    pub fn get(&self, source: RawSource) -> Vec<RawPayload> {
+        let payloads = self.payloads.read().unwrap();
+        payloads
+            .get(&source)
+            .map(|p| p.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Get the global raw payload store
+pub fn get_raw_payloads() -> &'static Arc<RawPayloads> {
+    RAW_PAYLOADS.get_or_init(Arc::default)
+}
+
+/// Read the body of a response and keep it as a raw payload of `source`
+pub(super) async fn read_body(source: RawSource, response: reqwest::Response) -> Result<String, reqwest::Error> {
+    let url = response.url().to_string();
+    let status = response.status().as_u16();
+    let body = response.text().await?;
+    get_raw_payloads().record(source, &url, status, &body);
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_keeps_newest_payloads() {
+        let payloads = RawPayloads::default();
+        for i in 0..MAX_PAYLOADS_PER_SOURCE + 5 {
+            payloads.record(RawSource::Meter, "http://meter/latest", 200, &format!(r#"{{"reading": {}}}"#, i));
+        }
+        payloads.record(RawSource::AcBridge, "http://ac/api/sensors", 500, "Internal Server Error");
+
+        let meter = payloads.get(RawSource::Meter);
+        assert_eq!(meter.len(), MAX_PAYLOADS_PER_SOURCE);
+        assert_eq!(meter[0].body, serde_json::json!({"reading": MAX_PAYLOADS_PER_SOURCE + 4}), "newest first");
+
+        let ac = payloads.get(RawSource::AcBridge);
+        assert_eq!(ac[0].body, serde_json::json!("Internal Server Error"), "invalid JSON is kept as a string");
+        assert!(payloads.get(RawSource::Weather).is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! PV production forecast for the configured solar panels
+//!
+//! Open-Meteo forecasts the global tilted irradiance, the radiation reaching a surface with the
+//! panels' tilt and orientation. Panels are rated at 1000 W/m², so a panel of 1 kWp produces about
+//! 1 W per W/m² before losses. Radiation values are the mean of the preceding hour, so the three
+//! values after the current hour cover the current and the next two hours.
+
+use super::cache::DataCache;
+use super::weather::{self, WeatherError};
+use crate::types::{Config, SolarPanelsConfig};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Hours of production summed into the expected production
+const FORECAST_HOURS: usize = 3;
+
+/// Share of the panels' rating that reaches the meter, after inverter, cable, heat and soiling losses
+const PERFORMANCE_RATIO: f64 = 0.85;
+
+#[derive(Debug, Deserialize)]
+struct OpenMeteoTiltedResponse {
+    current: Option<CurrentTime>,
+    hourly: TiltedHourlyData,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurrentTime {
+    time: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TiltedHourlyData {
+    time: Vec<String>,
+    /// Radiation on the panels in W/m², hours without data are null
+    global_tilted_irradiance: Vec<Option<f64>>,
+}
+
+/// Open-Meteo measures the azimuth from the south, -90 is east and 90 west
+fn open_meteo_azimuth(compass_azimuth: f64) -> f64 {
+    let azimuth = (compass_azimuth - 180.0).rem_euclid(360.0);
+    if azimuth > 180.0 { azimuth - 360.0 } else { azimuth }
+}
+
+/// Get the expected production (Wh) of the current and next two hours from Open-Meteo API
+pub async fn get_expected_next_3h_wh(latitude: f64, longitude: f64, panels: &SolarPanelsConfig) -> Result<f64, WeatherError> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=global_tilted_irradiance&tilt={}&azimuth={}&forecast_days=2&current=temperature_2m",
+        latitude,
+        longitude,
+        panels.tilt,
+        open_meteo_azimuth(panels.azimuth)
+    );
+
+    let body = weather::fetch_body(&url).await?;
+    let data: OpenMeteoTiltedResponse = serde_json::from_str(&body)
+        .map_err(|e| WeatherError::ParseError(e.to_string()))?;
+
+    parse_expected_wh(&data, panels.kwp)
+}
+
+/// Expected production of the hours after the current one in a response
+fn parse_expected_wh(data: &OpenMeteoTiltedResponse, kwp: f64) -> Result<f64, WeatherError> {
+    let current_time = data.current
+        .as_ref()
+        .map(|c| c.time.as_str())
+        .ok_or_else(|| WeatherError::ParseError("No current time data available".to_string()))?;
+    let current_hour_idx = weather::hour_index(&data.hourly.time, current_time)?;
+
+    let irradiance = data.hourly.global_tilted_irradiance.iter()
+        .skip(current_hour_idx + 1)
+        .take(FORECAST_HOURS)
+        .copied()
+        .collect::<Option<Vec<f64>>>()
+        .filter(|irradiance| irradiance.len() == FORECAST_HOURS)
+        .ok_or_else(|| WeatherError::ParseError("No radiation forecast for the next 3 hours".to_string()))?;
+
+    // W/m² over one hour on 1 kWp is 1 Wh per W/m²
+    Ok(irradiance.iter().map(|w_per_m2| w_per_m2.max(0.0) * kwp * PERFORMANCE_RATIO).sum())
+}
+
+// Cache for the solar forecast (14 minute TTL, like the weather data it is based on)
+static SOLAR_FORECAST_CACHE: OnceLock<DataCache<f64>> = OnceLock::new();
+
+fn get_solar_forecast_cache() -> &'static DataCache<f64> {
+    SOLAR_FORECAST_CACHE.get_or_init(|| DataCache::new(840)) // 14 minutes
+}
+
+/// Get the expected production of the next 3 hours with caching (14 minute TTL)
+/// Falls back to stale cache if API request fails
+pub async fn get_expected_next_3h_wh_cached(latitude: f64, longitude: f64, panels: &SolarPanelsConfig) -> Result<f64, WeatherError> {
+    let cache = get_solar_forecast_cache();
+    let cache_key = format!(
+        "solar_{}_{}_{}_{}",
+        weather::coordinates_key(latitude, longitude),
+        panels.kwp,
+        panels.azimuth,
+        panels.tilt
+    );
+
+    cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
+        get_expected_next_3h_wh(latitude, longitude, panels).await
+    }).await
+}
+
+/// Get the expected production of the next 3 hours for nodesets
+/// None if no panels are configured or the forecast can't be read
+pub async fn get_expected_solar_next_3h_wh(config: &Config) -> Option<f64> {
+    if config.solar_panels.kwp <= 0.0 {
+        return None;
+    }
+
+    match get_expected_next_3h_wh_cached(config.latitude, config.longitude, &config.solar_panels).await {
+        Ok(wh) => Some(wh),
+        Err(e) => {
+            log::warn!("Failed to get the solar forecast: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_meteo_azimuth() {
+        assert_eq!(open_meteo_azimuth(180.0), 0.0);
+        assert_eq!(open_meteo_azimuth(90.0), -90.0);
+        assert_eq!(open_meteo_azimuth(270.0), 90.0);
+        assert_eq!(open_meteo_azimuth(0.0), 180.0);
+    }
+
+    #[test]
+    fn test_parse_expected_wh() {
+        let json = r#"{
+            "current": {"time": "2025-04-02T09:15", "interval": 900, "temperature_2m": 9.1},
+            "hourly": {
+                "time": ["2025-04-02T08:00", "2025-04-02T09:00", "2025-04-02T10:00", "2025-04-02T11:00", "2025-04-02T12:00", "2025-04-02T13:00"],
+                "global_tilted_irradiance": [120.0, 210.0, 300.0, 450.0, 600.0, 700.0]
+            }
+        }"#;
+        let data: OpenMeteoTiltedResponse = serde_json::from_str(json).unwrap();
+
+        // 4 kWp: (300 + 450 + 600) W/m² * 4 * 0.85
+        let wh = parse_expected_wh(&data, 4.0).unwrap();
+        assert!((wh - 4590.0).abs() < 1e-9, "got {}", wh);
+    }
+
+    #[test]
+    fn test_parse_expected_wh_rejects_missing_hours() {
+        let json = r#"{
+            "current": {"time": "2025-04-02T09:15"},
+            "hourly": {
+                "time": ["2025-04-02T09:00", "2025-04-02T10:00", "2025-04-02T11:00", "2025-04-02T12:00"],
+                "global_tilted_irradiance": [210.0, 300.0, null, 600.0]
+            }
+        }"#;
+        let data: OpenMeteoTiltedResponse = serde_json::from_str(json).unwrap();
+        assert!(matches!(parse_expected_wh(&data, 4.0), Err(WeatherError::ParseError(_))));
+    }
+}
@@ -1,11 +1,30 @@
 use super::cache::DataCache;
+use super::common::{self, CircuitOpen, RetryPolicy, TransientError};
+use super::health::EndpointKind;
+use super::raw_payloads::{self, RawSource};
+use crate::nodes::WeatherForecastData;
 use serde::Deserialize;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum WeatherError {
     RequestFailed(String),
     ParseError(String),
+    /// Not requested because the weather API failed repeatedly, see `common::CircuitOpen`
+    Unavailable(CircuitOpen),
+    /// The API rejected the request (4xx), repeating it wouldn't help
+    Rejected(String),
+}
+
+impl WeatherError {
+    fn from_request(e: reqwest::Error) -> Self {
+        if common::is_transient_request_error(&e) {
+            WeatherError::RequestFailed(e.to_string())
+        } else {
+            WeatherError::Rejected(e.to_string())
+        }
+    }
 }
 
 impl std::fmt::Display for WeatherError {
@@ -13,12 +32,26 @@ impl std::fmt::Display for WeatherError {
         match self {
             WeatherError::RequestFailed(msg) => write!(f, "Weather API request failed: {}", msg),
             WeatherError::ParseError(msg) => write!(f, "Failed to parse weather data: {}", msg),
+            WeatherError::Unavailable(open) => write!(f, "Weather API unavailable: {}", open),
+            WeatherError::Rejected(msg) => write!(f, "Weather API rejected the request: {}", msg),
         }
     }
 }
 
 impl std::error::Error for WeatherError {}
 
+impl From<CircuitOpen> for WeatherError {
+    fn from(open: CircuitOpen) -> Self {
+        WeatherError::Unavailable(open)
+    }
+}
+
+impl TransientError for WeatherError {
+    fn is_transient(&self) -> bool {
+        matches!(self, WeatherError::RequestFailed(_))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenMeteoResponse {
     current: Option<CurrentData>,
@@ -29,12 +62,41 @@ struct OpenMeteoResponse {
 struct CurrentData {
     time: String,
     temperature_2m: f64,
+    /// Only present when requested in the `current` parameter
+    #[serde(default)]
+    relative_humidity_2m: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct HourlyData {
     time: Vec<String>,
     temperature_2m: Vec<f64>,
+    /// Only present when requested in the `hourly` parameter, hours without data are null
+    #[serde(default)]
+    shortwave_radiation: Option<Vec<Option<f64>>>,
+}
+
+/// Hours of hourly forecast provided to the nodesets
+const HOURLY_FORECAST_HOURS: usize = 48;
+
+/// Get a response body from Open-Meteo, retrying with backoff
+/// Each API host (forecast, archive) has its own circuit breaker
+pub(super) async fn fetch_body(url: &str) -> Result<String, WeatherError> {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "weather".to_string());
+
+    common::with_retry(EndpointKind::Weather, &host, RetryPolicy::READ, || async {
+        let response = reqwest::get(url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(WeatherError::from_request)?;
+
+        raw_payloads::read_body(RawSource::Weather, response)
+            .await
+            .map_err(WeatherError::from_request)
+    }).await
 }
 
 /// Get current outdoor temperature from Open-Meteo API
@@ -44,13 +106,8 @@ pub async fn get_current_outdoor_temp(latitude: f64, longitude: f64) -> Result<f
         latitude, longitude
     );
     
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| WeatherError::RequestFailed(e.to_string()))?;
-    
-    let data: OpenMeteoResponse = response
-        .json()
-        .await
+    let body = fetch_body(&url).await?;
+    let data: OpenMeteoResponse = serde_json::from_str(&body)
         .map_err(|e| WeatherError::ParseError(e.to_string()))?;
     
     // Get current temperature from the dedicated current field
@@ -59,28 +116,35 @@ pub async fn get_current_outdoor_temp(latitude: f64, longitude: f64) -> Result<f
         .ok_or_else(|| WeatherError::ParseError("No current temperature data available".to_string()))
 }
 
-/// Get average outdoor temperature for next 24 hours from Open-Meteo API
-pub async fn get_avg_next_24h_outdoor_temp(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
+/// Get current outdoor relative humidity (%) from Open-Meteo API
+pub async fn get_current_outdoor_humidity(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
     let url = format!(
-        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m&forecast_days=2&current=temperature_2m",
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m&forecast_days=1&current=temperature_2m,relative_humidity_2m",
         latitude, longitude
     );
     
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| WeatherError::RequestFailed(e.to_string()))?;
-    
-    let data: OpenMeteoResponse = response
-        .json()
-        .await
+    let body = fetch_body(&url).await?;
+    let data: OpenMeteoResponse = serde_json::from_str(&body)
         .map_err(|e| WeatherError::ParseError(e.to_string()))?;
     
+    data.current
+        .and_then(|c| c.relative_humidity_2m)
+        .ok_or_else(|| WeatherError::ParseError("No current humidity data available".to_string()))
+}
+
+/// Index of the current hour in the hourly data of a response
+fn current_hour_index(data: &OpenMeteoResponse) -> Result<usize, WeatherError> {
     // Get the current time from the API response
     let current_time = data.current
         .as_ref()
         .map(|c| &c.time)
         .ok_or_else(|| WeatherError::ParseError("No current time data available".to_string()))?;
     
+    hour_index(&data.hourly.time, current_time)
+}
+
+/// Index of the hour containing `current_time` in the hourly times of an Open-Meteo response
+pub(super) fn hour_index(times: &[String], current_time: &str) -> Result<usize, WeatherError> {
     // Extract hour prefix (YYYY-MM-DDTHH) - validate length first
     let hour_prefix = current_time.get(..13)
         .ok_or_else(|| WeatherError::ParseError(
@@ -88,40 +152,110 @@ pub async fn get_avg_next_24h_outdoor_temp(latitude: f64, longitude: f64) -> Res
         ))?;
     
     // Find the index of the current hour in the hourly data
-    let current_hour_idx = data.hourly.time.iter()
+    times.iter()
         .position(|t| t.starts_with(hour_prefix))
-        .ok_or_else(|| WeatherError::ParseError("Current hour not found in hourly data".to_string()))?;
+        .ok_or_else(|| WeatherError::ParseError("Current hour not found in hourly data".to_string()))
+}
+
+/// Get the hourly forecast for up to `hours` hours after the current hour from Open-Meteo API
+async fn get_next_hours_forecast(latitude: f64, longitude: f64, hours: usize) -> Result<Vec<f64>, WeatherError> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m&forecast_days=2&current=temperature_2m",
+        latitude, longitude
+    );
+    
+    let body = fetch_body(&url).await?;
+    let data: OpenMeteoResponse = serde_json::from_str(&body)
+        .map_err(|e| WeatherError::ParseError(e.to_string()))?;
+    
+    let current_hour_idx = current_hour_index(&data)?;
     
-    // Calculate average for next 24 hours (starting from the next hour after current)
     let temps = &data.hourly.temperature_2m;
     if temps.len() <= current_hour_idx {
         return Err(WeatherError::ParseError("Insufficient forecast data after current hour".to_string()));
     }
     
-    // Take hours from current_hour_idx+1 to current_hour_idx+24 (next 24 hours)
+    // Take the hours starting from the next hour after current
     let forecast_temps: Vec<f64> = temps.iter()
         .skip(current_hour_idx + 1)
-        .take(24)
+        .take(hours)
         .copied()
         .collect();
     
-    // We need at least some forecast data (even if less than 24 hours)
+    // We need at least some forecast data (even if less than requested)
     if forecast_temps.is_empty() {
         return Err(WeatherError::ParseError("No forecast data available for next hours".to_string()));
     }
     
-    // Log a warning if we have less than 24 hours of forecast
-    if forecast_temps.len() < 24 {
+    // Log a warning if we have less forecast than requested
+    if forecast_temps.len() < hours {
         log::warn!(
-            "Only {} hours of forecast available (requested 24). Using available data for average.",
-            forecast_temps.len()
+            "Only {} hours of forecast available (requested {}). Using available data.",
+            forecast_temps.len(),
+            hours
         );
     }
     
+    Ok(forecast_temps)
+}
+
+/// Get the hourly temperature and solar radiation forecast for the next 48 hours from Open-Meteo API
+pub async fn get_hourly_forecast(latitude: f64, longitude: f64) -> Result<WeatherForecastData, WeatherError> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m,shortwave_radiation&forecast_days=3&current=temperature_2m",
+        latitude, longitude
+    );
+    
+    let body = fetch_body(&url).await?;
+    let data: OpenMeteoResponse = serde_json::from_str(&body)
+        .map_err(|e| WeatherError::ParseError(e.to_string()))?;
+    
+    parse_hourly_forecast(&data)
+}
+
+/// The forecast of the hours after the current one in a response
+/// Solar radiation is left out entirely if any of the hours is missing it, so its hours match the temperatures
+fn parse_hourly_forecast(data: &OpenMeteoResponse) -> Result<WeatherForecastData, WeatherError> {
+    let current_hour_idx = current_hour_index(data)?;
+    let hourly_temperatures: Vec<f64> = data.hourly.temperature_2m.iter()
+        .skip(current_hour_idx + 1)
+        .take(HOURLY_FORECAST_HOURS)
+        .copied()
+        .collect();
+    if hourly_temperatures.is_empty() {
+        return Err(WeatherError::ParseError("No forecast data available for next hours".to_string()));
+    }
+    
+    let hourly_solar_radiation = data.hourly.shortwave_radiation.as_ref()
+        .and_then(|radiation| {
+            radiation.iter()
+                .skip(current_hour_idx + 1)
+                .take(hourly_temperatures.len())
+                .copied()
+                .collect::<Option<Vec<f64>>>()
+        })
+        .filter(|radiation| radiation.len() == hourly_temperatures.len())
+        .unwrap_or_default();
+    
+    Ok(WeatherForecastData {
+        hourly_temperatures,
+        hourly_solar_radiation,
+    })
+}
+
+/// Get average outdoor temperature for next 24 hours from Open-Meteo API
+pub async fn get_avg_next_24h_outdoor_temp(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
+    let forecast_temps = get_next_hours_forecast(latitude, longitude, 24).await?;
     let sum: f64 = forecast_temps.iter().sum();
     Ok(sum / forecast_temps.len() as f64)
 }
 
+/// Get the lowest outdoor temperature forecast for the next 12 hours from Open-Meteo API
+pub async fn get_min_next_12h_outdoor_temp(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
+    let forecast_temps = get_next_hours_forecast(latitude, longitude, 12).await?;
+    Ok(forecast_temps.into_iter().fold(f64::INFINITY, f64::min))
+}
+
 /// Compute temperature trend: returns the difference between average next 24h temp and current temp
 /// Positive value means it's getting warmer, negative means it's getting colder
 pub async fn compute_temperature_trend(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
@@ -130,11 +264,62 @@ pub async fn compute_temperature_trend(latitude: f64, longitude: f64) -> Result<
     Ok(avg_next_24h - current_temp)
 }
 
+#[derive(Debug, Deserialize)]
+struct OpenMeteoArchiveResponse {
+    hourly: ArchiveHourlyData,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveHourlyData {
+    time: Vec<String>,
+    // The archive lags a few days behind, so the most recent hours can be null
+    temperature_2m: Vec<Option<f64>>,
+}
+
+/// Convert archive hourly data to (unix timestamp, temperature) pairs, skipping hours without data
+fn parse_archive_hours(hourly: ArchiveHourlyData) -> Result<Vec<(i64, f64)>, WeatherError> {
+    let mut hours = Vec::new();
+    for (time, temperature) in hourly.time.iter().zip(hourly.temperature_2m) {
+        let Some(temperature) = temperature else {
+            continue;
+        };
+        let timestamp = chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M")
+            .map_err(|e| WeatherError::ParseError(format!("Invalid archive time '{}': {}", time, e)))?
+            .and_utc()
+            .timestamp();
+        hours.push((timestamp, temperature));
+    }
+    Ok(hours)
+}
+
+/// Get historical hourly outdoor temperatures from the Open-Meteo archive API
+/// Returns (unix timestamp, temperature) pairs for each UTC hour between the dates (inclusive)
+pub async fn get_archive_hourly_temps(
+    latitude: f64,
+    longitude: f64,
+    start_date: chrono::NaiveDate,
+    end_date: chrono::NaiveDate,
+) -> Result<Vec<(i64, f64)>, WeatherError> {
+    let url = format!(
+        "https://archive-api.open-meteo.com/v1/archive?latitude={}&longitude={}&start_date={}&end_date={}&hourly=temperature_2m&timezone=UTC",
+        latitude, longitude, start_date, end_date
+    );
+
+    let body = fetch_body(&url).await?;
+    let data: OpenMeteoArchiveResponse = serde_json::from_str(&body)
+        .map_err(|e| WeatherError::ParseError(e.to_string()))?;
+
+    parse_archive_hours(data.hourly)
+}
+
 // Cache for weather data (14 minute TTL to avoid excessive API calls)
 // 14 minutes ensures we don't query more than once per loop cycle (5 minute intervals)
 static WEATHER_TEMP_CACHE: OnceLock<DataCache<f64>> = OnceLock::new();
 static WEATHER_TREND_CACHE: OnceLock<DataCache<f64>> = OnceLock::new();
 static WEATHER_AVG_24H_CACHE: OnceLock<DataCache<f64>> = OnceLock::new();
+static WEATHER_MIN_12H_CACHE: OnceLock<DataCache<f64>> = OnceLock::new();
+static WEATHER_HUMIDITY_CACHE: OnceLock<DataCache<f64>> = OnceLock::new();
+static WEATHER_HOURLY_CACHE: OnceLock<DataCache<WeatherForecastData>> = OnceLock::new();
 
 fn get_weather_temp_cache() -> &'static DataCache<f64> {
     WEATHER_TEMP_CACHE.get_or_init(|| DataCache::new(840)) // 14 minutes
@@ -148,24 +333,69 @@ fn get_weather_avg_24h_cache() -> &'static DataCache<f64> {
     WEATHER_AVG_24H_CACHE.get_or_init(|| DataCache::new(840)) // 14 minutes
 }
 
+fn get_weather_min_12h_cache() -> &'static DataCache<f64> {
+    WEATHER_MIN_12H_CACHE.get_or_init(|| DataCache::new(840)) // 14 minutes
+}
+
+fn get_weather_humidity_cache() -> &'static DataCache<f64> {
+    WEATHER_HUMIDITY_CACHE.get_or_init(|| DataCache::new(840)) // 14 minutes
+}
+
+fn get_weather_hourly_cache() -> &'static DataCache<WeatherForecastData> {
+    WEATHER_HOURLY_CACHE.get_or_init(|| DataCache::new(840)) // 14 minutes
+}
+
+/// Cache key part for a location, rounded to 0.01° (about 1 km) so devices with nearly the same
+/// coordinates share cached data instead of each fetching it. Open-Meteo's grid is coarser anyway.
+pub(super) fn coordinates_key(latitude: f64, longitude: f64) -> String {
+    // Adding 0.0 turns -0.0 into 0.0, so both sides of the equator/meridian share a key
+    let round = |degrees: f64| (degrees * 100.0).round() / 100.0 + 0.0;
+    format!("{:.2}_{:.2}", round(latitude), round(longitude))
+}
+
+/// Fetch weather data for `coordinates`, retrying with `fallback` if that fails and they differ
+/// Used for devices with their own coordinates, so they still get the main location's weather
+pub async fn with_failover<T, F, Fut>(coordinates: (f64, f64), fallback: (f64, f64), fetch: F) -> Result<T, WeatherError>
+where
+    F: Fn(f64, f64) -> Fut,
+    Fut: std::future::Future<Output = Result<T, WeatherError>>,
+{
+    match fetch(coordinates.0, coordinates.1).await {
+        Err(e) if coordinates_key(coordinates.0, coordinates.1) != coordinates_key(fallback.0, fallback.1) => {
+            log::warn!(
+                "Weather request for {:?} failed: {}. Falling back to {:?}",
+                coordinates, e, fallback
+            );
+            fetch(fallback.0, fallback.1).await
+        }
+        result => result,
+    }
+}
+
 /// Get current outdoor temperature with caching (14 minute TTL)
 /// Recommended for dashboard use to reduce API calls
 /// Falls back to stale cache if API request fails
 pub async fn get_current_outdoor_temp_cached(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
     let cache = get_weather_temp_cache();
-    let cache_key = format!("temp_{}_{}", latitude, longitude);
+    let cache_key = format!("temp_{}", coordinates_key(latitude, longitude));
     
     cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
         get_current_outdoor_temp(latitude, longitude).await
     }).await
 }
 
+/// Time since the cached outdoor temperature was fetched, None if it was never fetched
+/// Older than the cache TTL while the stale fallback is in use
+pub async fn get_current_outdoor_temp_age(latitude: f64, longitude: f64) -> Option<Duration> {
+    get_weather_temp_cache().age(&format!("temp_{}", coordinates_key(latitude, longitude))).await
+}
+
 /// Get temperature trend with caching (14 minute TTL)
 /// Recommended for dashboard use to reduce API calls
 /// Falls back to stale cache if API request fails
 pub async fn compute_temperature_trend_cached(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
     let cache = get_weather_trend_cache();
-    let cache_key = format!("trend_{}_{}", latitude, longitude);
+    let cache_key = format!("trend_{}", coordinates_key(latitude, longitude));
     
     cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
         compute_temperature_trend(latitude, longitude).await
@@ -177,13 +407,46 @@ pub async fn compute_temperature_trend_cached(latitude: f64, longitude: f64) ->
 /// Falls back to stale cache if API request fails
 pub async fn get_avg_next_24h_outdoor_temp_cached(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
     let cache = get_weather_avg_24h_cache();
-    let cache_key = format!("avg24h_{}_{}", latitude, longitude);
+    let cache_key = format!("avg24h_{}", coordinates_key(latitude, longitude));
     
     cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
         get_avg_next_24h_outdoor_temp(latitude, longitude).await
     }).await
 }
 
+/// Get the lowest outdoor temperature for the next 12 hours with caching (14 minute TTL)
+/// Falls back to stale cache if API request fails
+pub async fn get_min_next_12h_outdoor_temp_cached(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
+    let cache = get_weather_min_12h_cache();
+    let cache_key = format!("min12h_{}", coordinates_key(latitude, longitude));
+    
+    cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
+        get_min_next_12h_outdoor_temp(latitude, longitude).await
+    }).await
+}
+
+/// Get current outdoor relative humidity with caching (14 minute TTL)
+/// Falls back to stale cache if API request fails
+pub async fn get_current_outdoor_humidity_cached(latitude: f64, longitude: f64) -> Result<f64, WeatherError> {
+    let cache = get_weather_humidity_cache();
+    let cache_key = format!("humidity_{}", coordinates_key(latitude, longitude));
+    
+    cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
+        get_current_outdoor_humidity(latitude, longitude).await
+    }).await
+}
+
+/// Get the hourly forecast for the next 48 hours with caching (14 minute TTL)
+/// Falls back to stale cache if API request fails
+pub async fn get_hourly_forecast_cached(latitude: f64, longitude: f64) -> Result<WeatherForecastData, WeatherError> {
+    let cache = get_weather_hourly_cache();
+    let cache_key = format!("hourly_{}", coordinates_key(latitude, longitude));
+    
+    cache.get_or_fetch_with_stale_fallback(&cache_key, || async {
+        get_hourly_forecast(latitude, longitude).await
+    }).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,6 +489,24 @@ mod tests {
         assert_eq!(response.hourly.time.len(), 3);
     }
 
+    #[test]
+    fn test_deserialize_api_response_with_humidity() {
+        let json = r#"{
+            "current": {
+                "time": "2025-11-24T11:00",
+                "temperature_2m": 5.7,
+                "relative_humidity_2m": 87.0
+            },
+            "hourly": {
+                "time": ["2025-11-24T00:00"],
+                "temperature_2m": [4.1]
+            }
+        }"#;
+        
+        let response: OpenMeteoResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.current.unwrap().relative_humidity_2m, Some(87.0));
+    }
+
     #[test]
     fn test_deserialize_api_response_without_current() {
         // Test that we can parse a response without current (for backwards compatibility)
@@ -281,6 +562,29 @@ mod tests {
         assert_eq!(forecast[23], 34.0); // Last value should be hour 34
     }
 
+    #[test]
+    fn test_parse_hourly_forecast() {
+        let json = r#"{
+            "current": {"time": "2025-11-24T01:15", "temperature_2m": 4.2},
+            "hourly": {
+                "time": ["2025-11-24T00:00", "2025-11-24T01:00", "2025-11-24T02:00", "2025-11-24T03:00"],
+                "temperature_2m": [4.1, 4.3, 3.9, 3.5],
+                "shortwave_radiation": [0.0, 0.0, 12.0, 40.0]
+            }
+        }"#;
+        let response: OpenMeteoResponse = serde_json::from_str(json).unwrap();
+        let forecast = parse_hourly_forecast(&response).unwrap();
+        assert_eq!(forecast.hourly_temperatures, vec![3.9, 3.5], "starts at the hour after the current one");
+        assert_eq!(forecast.hourly_solar_radiation, vec![12.0, 40.0]);
+
+        // Missing radiation values drop the radiation, not the temperatures
+        let json = json.replace("[0.0, 0.0, 12.0, 40.0]", "[0.0, 0.0, 12.0, null]");
+        let response: OpenMeteoResponse = serde_json::from_str(&json).unwrap();
+        let forecast = parse_hourly_forecast(&response).unwrap();
+        assert_eq!(forecast.hourly_temperatures, vec![3.9, 3.5]);
+        assert!(forecast.hourly_solar_radiation.is_empty());
+    }
+
     #[test]
     fn test_next_24_hours_with_limited_data() {
         // Test that we handle cases where less than 24 hours are available
@@ -300,6 +604,51 @@ mod tests {
         assert_eq!(forecast[13], 29.0);
     }
 
+    #[test]
+    fn test_parse_archive_hours_skips_missing_data() {
+        let json = r#"{
+            "hourly": {
+                "time": ["2025-11-24T00:00", "2025-11-24T01:00", "2025-11-24T02:00"],
+                "temperature_2m": [4.1, null, 4.3]
+            }
+        }"#;
+
+        let response: OpenMeteoArchiveResponse = serde_json::from_str(json).unwrap();
+        let hours = parse_archive_hours(response.hourly).unwrap();
+        assert_eq!(hours, vec![(1763942400, 4.1), (1763949600, 4.3)]);
+    }
+
+    #[test]
+    fn test_coordinates_key_rounds_nearby_locations_together() {
+        assert_eq!(coordinates_key(51.50741, -0.12781), coordinates_key(51.50702, -0.12849));
+        assert_ne!(coordinates_key(51.5074, -0.1278), coordinates_key(51.5174, -0.1278));
+        assert_eq!(coordinates_key(-0.001, 0.0), "0.00_0.00");
+    }
+
+    #[tokio::test]
+    async fn test_with_failover() {
+        let fetch = |latitude: f64, _longitude: f64| async move {
+            if latitude > 60.0 {
+                Err(WeatherError::RequestFailed("unreachable".to_string()))
+            } else {
+                Ok(latitude)
+            }
+        };
+        assert_eq!(with_failover((52.0, 4.0), (51.0, 4.0), fetch).await.unwrap(), 52.0);
+        assert_eq!(with_failover((61.0, 4.0), (51.0, 4.0), fetch).await.unwrap(), 51.0);
+        // No point in retrying the same location
+        assert!(with_failover((61.0, 4.0), (61.0, 4.0), fetch).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_archive_hours_rejects_invalid_time() {
+        let hourly = ArchiveHourlyData {
+            time: vec!["yesterday".to_string()],
+            temperature_2m: vec![Some(4.1)],
+        };
+        assert!(parse_archive_hours(hourly).is_err());
+    }
+
     // Note: Integration tests with actual API calls are not included here
     // as they would require network access and could be flaky.
     // In a production environment, you might want to:
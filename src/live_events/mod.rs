@@ -0,0 +1,239 @@
+//! Live event stream for the dashboard
+//!
+//! Execution results, AC state changes, PIR detections and meter readings are published to a
+//! broadcast channel as they happen. `GET /api/ws` forwards them to connected WebSocket clients,
+//! so the dashboard shows changes right away instead of on its next poll.
+//! Publishing while nobody is connected is a no-op.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::{
+    ac_controller::{ac_executor::{ac_mode_name, AcState}, cycle_snapshots},
+    nodes::ExecutionResult,
+    types::db_types::Schedule,
+};
+
+/// Events buffered per subscriber; a subscriber that falls further behind skips the oldest events
+const EVENT_BUFFER_SIZE: usize = 64;
+
+/// An event pushed to dashboard clients, serialized with its kind in `type`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// The nodeset of a device was evaluated
+    Execution {
+        device: String,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+        /// Short description of the decision, e.g. "Execute Action: Heat 21°C"
+        decision: String,
+        error: Option<String>,
+        /// Evaluated in shadow mode, no command was sent
+        is_shadow: bool,
+    },
+    /// A command changed the tracked state of an AC
+    AcState {
+        device: String,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+        is_on: bool,
+        mode: Option<String>,
+        temperature_setpoint: Option<f64>,
+        fan_speed: Option<i32>,
+        swing: Option<i32>,
+        powerful_mode: bool,
+    },
+    /// Motion was detected for a device
+    PirDetection {
+        device: String,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+    },
+    /// A new smart meter reading was fetched
+    MeterReading {
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+        consumption_watt: i32,
+        production_watt: i32,
+        /// Positive while importing from the grid
+        net_power_watt: i32,
+    },
+    /// The dashboard, the tracked state and the last command of an AC disagree
+    StateMismatch {
+        device: String,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+        mismatches: Vec<String>,
+    },
+    /// A command was read back and the AC still disagreed after it was resent
+    CommandNotApplied {
+        device: String,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+        discrepancies: Vec<String>,
+    },
+    /// A schedule came into effect and switched the nodeset of its target
+    ScheduleTransition {
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        timestamp: i64,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        schedule_id: i64,
+        schedule_name: String,
+        /// Device whose assignment was switched, None for the active nodeset
+        device: Option<String>,
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        nodeset_id: i64,
+        /// Why the nodeset could not be switched
+        error: Option<String>,
+    },
+}
+
+impl LiveEvent {
+    /// Event for the result of a nodeset evaluation
+    pub fn execution(device: &str, result: &ExecutionResult, is_shadow: bool) -> Self {
+        LiveEvent::Execution {
+            device: device.to_string(),
+            timestamp: now(),
+            decision: cycle_snapshots::describe_decision(result),
+            error: result.error.clone(),
+            is_shadow,
+        }
+    }
+
+    /// Event for the new tracked state of an AC
+    pub fn ac_state(device: &str, state: &AcState) -> Self {
+        LiveEvent::AcState {
+            device: device.to_string(),
+            timestamp: now(),
+            is_on: state.is_on,
+            mode: state.mode.and_then(ac_mode_name).map(String::from),
+            temperature_setpoint: state.temperature.map(|t| t.0),
+            fan_speed: state.fan_speed,
+            swing: state.swing,
+            powerful_mode: state.powerful_mode,
+        }
+    }
+
+    /// Event for a PIR detection
+    pub fn pir_detection(device: &str) -> Self {
+        LiveEvent::PirDetection {
+            device: device.to_string(),
+            timestamp: now(),
+        }
+    }
+
+    /// Event for a smart meter reading in kW
+    pub fn meter_reading(consumption_kw: f64, production_kw: f64) -> Self {
+        let consumption_watt = (consumption_kw * 1000.0) as i32;
+        let production_watt = (production_kw * 1000.0) as i32;
+        LiveEvent::MeterReading {
+            timestamp: now(),
+            consumption_watt,
+            production_watt,
+            net_power_watt: consumption_watt - production_watt,
+        }
+    }
+
+    /// Event for a device whose tracked state is inconsistent
+    pub fn state_mismatch(device: &str, mismatches: Vec<String>) -> Self {
+        LiveEvent::StateMismatch {
+            device: device.to_string(),
+            timestamp: now(),
+            mismatches,
+        }
+    }
+
+    /// Event for a command the AC didn't apply
+    pub fn command_not_applied(device: &str, discrepancies: Vec<String>) -> Self {
+        LiveEvent::CommandNotApplied {
+            device: device.to_string(),
+            timestamp: now(),
+            discrepancies,
+        }
+    }
+
+    /// Event for a schedule that came into effect
+    pub fn schedule_transition(schedule: &Schedule, error: Option<String>) -> Self {
+        LiveEvent::ScheduleTransition {
+            timestamp: now(),
+            schedule_id: schedule.id,
+            schedule_name: schedule.name.clone(),
+            device: schedule.device_identifier.clone(),
+            nodeset_id: schedule.nodeset_id,
+            error,
+        }
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// Broadcast channel of live events
+pub struct LiveEvents {
+    sender: broadcast::Sender<LiveEvent>,
+}
+
+impl LiveEvents {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+        Self { sender }
+    }
+
+    /// Send an event to all current subscribers
+    pub fn publish(&self, event: LiveEvent) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive all events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+}
+
+static LIVE_EVENTS: OnceLock<LiveEvents> = OnceLock::new();
+
+/// Get the global live event channel
+pub fn get_live_events() -> &'static LiveEvents {
+    LIVE_EVENTS.get_or_init(LiveEvents::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Celsius;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let events = LiveEvents::new();
+        // Publishing without subscribers is fine
+        events.publish(LiveEvent::pir_detection("Veranda"));
+
+        let mut first = events.subscribe();
+        let mut second = events.subscribe();
+        let event = LiveEvent::ac_state("LivingRoom", &AcState::new_on(4, 0, Celsius(21.5), 1, false));
+        events.publish(event.clone());
+
+        assert_eq!(first.recv().await.unwrap(), event);
+        assert_eq!(second.recv().await.unwrap(), event);
+        assert!(first.try_recv().is_err(), "events from before subscribing are not received");
+    }
+
+    #[test]
+    fn test_event_json_is_tagged_with_type() {
+        let json = serde_json::to_value(LiveEvent::ac_state("LivingRoom", &AcState::new_on(4, 0, Celsius(21.5), 1, false))).unwrap();
+        assert_eq!(json["type"], "ac_state");
+        assert_eq!(json["device"], "LivingRoom");
+        assert_eq!(json["mode"], "Heat");
+        assert_eq!(json["temperature_setpoint"], 21.5);
+
+        let json = serde_json::to_value(LiveEvent::meter_reading(1.25, 2.0)).unwrap();
+        assert_eq!(json["type"], "meter_reading");
+        assert_eq!(json["net_power_watt"], -750);
+    }
+}
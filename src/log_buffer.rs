@@ -0,0 +1,226 @@
+//! Recent log records for the dashboard
+//!
+//! Every record that passes the log filter (`RUST_LOG`, info by default) is written by env_logger
+//! as before, and also kept in a ring buffer of the last `BUFFER_CAPACITY` records and published to
+//! subscribers. `GET /api/logs` returns the buffer and `GET /api/logs/stream` tails it, so recent
+//! warnings and errors can be read from the dashboard instead of with journalctl on the Pi.
+//! Nothing is persisted, the buffer starts empty after a restart.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use log::{Log, Metadata, Record};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Records kept in memory, older records are dropped
+const BUFFER_CAPACITY: usize = 1000;
+
+/// Records buffered per subscriber; a subscriber that falls further behind skips the oldest records
+const SUBSCRIBER_BUFFER_SIZE: usize = 256;
+
+/// Severity of a log record, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace => LogLevel::Trace,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Error => LogLevel::Error,
+        }
+    }
+}
+
+/// A log record as returned by `GET /api/logs`
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct LogEntry {
+    /// Increases by one per record since startup, used as the SSE event ID
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub id: u64,
+    /// Unix timestamp in milliseconds
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub timestamp_ms: i64,
+    pub level: LogLevel,
+    /// Module path the record was logged from, e.g. "power_control_center::ac_controller::ac_executor"
+    pub module: String,
+    pub message: String,
+}
+
+/// Which records to return or tail
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Least severe level to include, all levels if None
+    pub min_level: Option<LogLevel>,
+    /// Only records whose module path contains this
+    pub module: Option<String>,
+    /// Only records logged at or after this unix timestamp in milliseconds
+    pub since_ms: Option<i64>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.min_level.is_none_or(|level| entry.level >= level)
+            && self.module.as_deref().is_none_or(|module| entry.module.contains(module))
+            && self.since_ms.is_none_or(|since| entry.timestamp_ms >= since)
+    }
+}
+
+struct Entries {
+    records: VecDeque<LogEntry>,
+    next_id: u64,
+}
+
+/// Ring buffer of the most recent log records
+pub struct LogBuffer {
+    entries: Mutex<Entries>,
+    capacity: usize,
+    sender: broadcast::Sender<LogEntry>,
+}
+
+impl LogBuffer {
+    fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(SUBSCRIBER_BUFFER_SIZE);
+        Self {
+            entries: Mutex::new(Entries { records: VecDeque::with_capacity(capacity), next_id: 1 }),
+            capacity,
+            sender,
+        }
+    }
+
+    /// Add a record, dropping the oldest one when full, and send it to all current subscribers
+    pub fn push(&self, level: LogLevel, module: &str, message: String) {
+        // Must not log, this runs inside the logger
+        let entry = {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = LogEntry {
+                id: entries.next_id,
+                timestamp_ms: chrono::Utc::now().timestamp_millis(),
+                level,
+                module: module.to_string(),
+                message,
+            };
+            entries.next_id += 1;
+            if entries.records.len() >= self.capacity {
+                entries.records.pop_front();
+            }
+            entries.records.push_back(entry.clone());
+            entry
+        };
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(entry);
+    }
+
+    /// Buffered records matching the filter, oldest first
+    pub fn query(&self, filter: &LogFilter) -> Vec<LogEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.records.iter().filter(|entry| filter.matches(entry)).cloned().collect()
+    }
+
+    /// Receive all records logged from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.sender.subscribe()
+    }
+}
+
+static LOG_BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+
+/// Get the global log buffer
+pub fn get_log_buffer() -> &'static LogBuffer {
+    LOG_BUFFER.get_or_init(|| LogBuffer::new(BUFFER_CAPACITY))
+}
+
+/// Logger that writes with env_logger and keeps what it writes in the log buffer
+struct BufferingLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for BufferingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            get_log_buffer().push(record.level().into(), record.target(), record.args().to_string());
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install `logger` as the global logger, keeping its records in the log buffer
+pub fn init(logger: env_logger::Logger) {
+    let max_level = logger.filter();
+    log::set_boxed_logger(Box::new(BufferingLogger { inner: logger })).expect("Logger already initialized");
+    log::set_max_level(max_level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(buffer: &LogBuffer, level: LogLevel, module: &str) {
+        buffer.push(level, module, format!("{:?} from {}", level, module));
+    }
+
+    #[test]
+    fn test_buffer_keeps_most_recent_records() {
+        let buffer = LogBuffer::new(3);
+        for module in ["a", "b", "c", "d"] {
+            entry(&buffer, LogLevel::Info, module);
+        }
+
+        let records = buffer.query(&LogFilter::default());
+        let modules: Vec<&str> = records.iter().map(|record| record.module.as_str()).collect();
+        assert_eq!(modules, ["b", "c", "d"]);
+        assert_eq!(records.iter().map(|record| record.id).collect::<Vec<_>>(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn test_filter_by_level_module_and_time() {
+        let buffer = LogBuffer::new(10);
+        entry(&buffer, LogLevel::Info, "power_control_center::ac_controller");
+        entry(&buffer, LogLevel::Warn, "power_control_center::ac_controller::ac_executor");
+        entry(&buffer, LogLevel::Error, "power_control_center::device_requests");
+
+        let at_least_warn = LogFilter { min_level: Some(LogLevel::Warn), ..Default::default() };
+        assert_eq!(buffer.query(&at_least_warn).len(), 2);
+
+        let module = LogFilter { module: Some("ac_controller".to_string()), ..Default::default() };
+        assert_eq!(buffer.query(&module).len(), 2);
+
+        let both = LogFilter { min_level: Some(LogLevel::Warn), module: Some("ac_controller".to_string()), since_ms: None };
+        assert_eq!(buffer.query(&both)[0].level, LogLevel::Warn);
+
+        let future = LogFilter { since_ms: Some(chrono::Utc::now().timestamp_millis() + 60_000), ..Default::default() };
+        assert!(buffer.query(&future).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_new_records() {
+        let buffer = LogBuffer::new(10);
+        entry(&buffer, LogLevel::Info, "before");
+        let mut records = buffer.subscribe();
+        entry(&buffer, LogLevel::Error, "after");
+
+        let record = records.recv().await.unwrap();
+        assert_eq!(record.module, "after");
+        assert!(records.try_recv().is_err());
+    }
+}
@@ -1,12 +1,25 @@
 mod ac_controller;
+mod app_state;
 mod config;
 mod db;
 mod device_requests;
-mod nodes;
+mod live_events;
+mod log_buffer;
+mod maintenance;
+mod mqtt_bridge;
+mod presence;
+mod sensor_history;
+mod shutdown;
+mod startup_report;
+mod statistics;
+mod telemetry;
+mod updater;
 mod types;
+mod weather_history;
 mod webserver;
 
 use env_logger::Env;
+use power_control_center_core::nodes;
 use log::{debug, error};
 use tokio;
 
@@ -15,10 +28,18 @@ async fn main() {
     // Set up logging
     init_logging();
 
+    // Swap in a database uploaded through POST /api/admin/restore, before the pool opens it
+    #[cfg(not(feature = "postgres"))]
+    match db::backup::apply_staged_restore(&config::get_config().database_path) {
+        Ok(true) => log::info!("Database restored from an uploaded backup"),
+        Ok(false) => {}
+        Err(e) => panic!("Failed to restore the uploaded database backup: {}", e),
+    }
+
     // Prepare database
     {
         let pool = db::get_pool().await;
-        if let Err(e) = sqlx::migrate!("./migrations").run(pool).await {
+        if let Err(e) = db::MIGRATOR.run(pool).await {
             panic!("Failed to run database migrations: {}", e);
         } else {
             debug!("Database migrations OK.");
@@ -26,13 +47,106 @@ async fn main() {
 
         // Initialize defaults for empty tables (cause_reasons, nodesets)
         db::defaults::initialize_defaults(pool).await;
+
+        // Upgrade stored nodesets to the current schema version
+        db::nodesets::migrate_stored_nodesets(pool).await;
+
+        // Restore shadow mode before the controller sends its first commands
+        if let Err(e) = ac_controller::shadow_mode::get_shadow_mode().load(pool).await {
+            panic!("Failed to load shadow mode setting: {}", e);
+        }
+        if let Err(e) = ac_controller::away_mode::get_away_mode().load(pool).await {
+            panic!("Failed to load away mode setting: {}", e);
+        }
+
+        if let Err(e) = ac_controller::occupancy::get_occupancy_model().load(pool).await {
+            error!("Failed to restore occupancy state: {}", e);
+        }
+
+        // Restore the tracked AC states, so a restart doesn't force re-sync commands
+        let state_manager = ac_controller::ac_executor::get_state_manager();
+        let max_age_minutes = config::get_config().device_state_max_age_minutes;
+        if let Err(e) = state_manager.load(pool, max_age_minutes).await {
+            error!("Failed to restore AC device states: {}", e);
+        }
+        state_manager.persist_to(pool.clone());
+
+        // Log the build, config and database in use, for bug reports
+        startup_report::EnvironmentReport::collect(pool, &config::get_config()).await.log();
     }
 
+    // Tell systemd the database is ready and keep its watchdog fed while the controller isn't stuck
+    ac_controller::watchdog::notify_ready();
+    tokio::spawn(async move {
+        ac_controller::watchdog::keepalive_loop().await;
+    });
+
     // Start AC controller
     let bg_handle = tokio::spawn(async move {
         ac_controller::start_ac_controller().await;
     });
 
+    // Start telemetry reporting (no-op unless opted in)
+    tokio::spawn(async move {
+        telemetry::telemetry_loop().await;
+    });
+
+    // Start release update checks (no-op unless enabled)
+    tokio::spawn(async move {
+        updater::update_check_loop().await;
+    });
+
+    // Record outdoor temperature history and backfill gaps from the weather archive
+    tokio::spawn(async move {
+        weather_history::weather_history_loop().await;
+    });
+
+    // Average the sensor history into 5 minute and hourly periods and drop old samples
+    tokio::spawn(async move {
+        sensor_history::downsample_loop().await;
+    });
+
+    // Track who is home over MQTT and/or Home Assistant (no-op unless configured)
+    tokio::spawn(async move {
+        presence::presence_loop().await;
+    });
+
+    // Publish the state to MQTT and take commands from it (no-op unless configured)
+    tokio::spawn(async move {
+        mqtt_bridge::bridge_loop().await;
+    });
+
+    // Write nightly database backups (no-op unless a backup directory is configured)
+    tokio::spawn(async move {
+        db::backup::backup_loop().await;
+    });
+
+    // Delete history past its retention and optimize the database once a day
+    tokio::spawn(async move {
+        maintenance::maintenance_loop().await;
+    });
+
+    // Roll up cycle history into hourly and daily statistics
+    tokio::spawn(async move {
+        statistics::rollup_loop().await;
+    });
+
+    // Check that the dashboard, tracked AC states and action history agree
+    tokio::spawn(async move {
+        ac_controller::state_reconciliation::reconciliation_loop().await;
+    });
+
+    // Alert when an AC controller stays unreachable
+    tokio::spawn(async move {
+        device_requests::health::health_alert_loop().await;
+    });
+
+    // Reload the config file on SIGHUP
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        config::reload_on_sighup().await;
+    });
+
     // Start webserver
     let webserver_handle = tokio::spawn(async move {
         if let Err(err) = webserver::start_webserver().await {
@@ -40,11 +154,18 @@ async fn main() {
         }
     });
 
-    let _ = tokio::join!(bg_handle, webserver_handle);
+    // Stop the controller, hand off the ACs and flush pending writes on SIGTERM/SIGINT,
+    // or when the service restarts itself (e.g. after a self-update)
+    tokio::select! {
+        _ = shutdown::wait_for_signal() => {}
+        _ = shutdown::requested() => {}
+    }
+    shutdown::shut_down(bg_handle, webserver_handle).await;
 }
 
 fn init_logging() {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+    // Also keeps the recent records for GET /api/logs
+    log_buffer::init(env_logger::Builder::from_env(Env::default().default_filter_or("info")).build());
     debug!("Logging initialized");
 
     // Set up panic logging
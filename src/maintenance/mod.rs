@@ -0,0 +1,146 @@
+//! Database maintenance
+//!
+//! The AC commands, nodeset executions and sensor history grow with every control cycle, and on a
+//! Pi an SQLite file that keeps growing eventually wears out the SD card. Once a day the rows older
+//! than their `retention` window are deleted and `PRAGMA optimize` (`ANALYZE` on PostgreSQL)
+//! refreshes the query planner statistics. SQLite reuses the freed pages for new rows, so the file
+//! stops growing rather than shrinking.
+
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::{config, db::{self, DbPool}, types::RetentionConfig};
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// How often maintenance runs, the first run is at startup
+const MAINTENANCE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// Refreshes the query planner statistics
+#[cfg(not(feature = "postgres"))]
+const OPTIMIZE_SQL: &str = "PRAGMA optimize";
+#[cfg(feature = "postgres")]
+const OPTIMIZE_SQL: &str = "ANALYZE";
+
+/// Rows deleted by a maintenance run
+#[derive(Debug, Default, PartialEq)]
+pub struct Pruned {
+    pub ac_actions: u64,
+    pub executions: u64,
+    pub sensor_history: u64,
+}
+
+/// Start of the retention window of `days` back from `now`, None to keep everything
+fn cutoff(now: i64, days: u32) -> Option<i64> {
+    (days > 0).then(|| now - i64::from(days) * DAY_SECS)
+}
+
+/// Delete history older than the retention windows and optimize the database
+pub async fn run_maintenance(pool: &DbPool, retention: &RetentionConfig, now: i64) -> Result<Pruned, sqlx::Error> {
+    let mut pruned = Pruned::default();
+    if let Some(cutoff) = cutoff(now, retention.ac_actions_days) {
+        pruned.ac_actions = db::ac_actions::delete_older_than(pool, cutoff).await?;
+    }
+    if let Some(cutoff) = cutoff(now, retention.execution_history_days) {
+        pruned.executions = db::execution_history::delete_older_than(pool, cutoff).await?;
+    }
+    if let Some(cutoff) = cutoff(now, retention.sensor_history_days) {
+        pruned.sensor_history = db::sensor_history::delete_all_before(pool, cutoff).await?;
+    }
+
+    sqlx::query(OPTIMIZE_SQL).execute(pool).await?;
+    Ok(pruned)
+}
+
+/// Run maintenance at startup and once a day after
+pub async fn maintenance_loop() {
+    loop {
+        let pool = db::get_pool().await;
+        let config = config::get_config();
+        match run_maintenance(pool, &config.retention, Utc::now().timestamp()).await {
+            Ok(pruned) => log::info!(
+                "Database maintenance done, deleted {} AC actions, {} executions and {} sensor history rows",
+                pruned.ac_actions,
+                pruned.executions,
+                pruned.sensor_history
+            ),
+            Err(e) => log::warn!("Database maintenance failed: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(MAINTENANCE_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-12-01T00:00:00Z
+    const NOW: i64 = 1_764_547_200;
+
+    async fn insert_action(pool: &DbPool, days_ago: i64, device: &str, action_type: &str) {
+        sqlx::query("INSERT INTO ac_actions (action_timestamp, device_identifier, action_type) VALUES ($1, $2, $3)")
+            .bind(NOW - days_ago * DAY_SECS)
+            .bind(device)
+            .bind(action_type)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn remaining_actions(pool: &DbPool) -> Vec<(String, i64, String)> {
+        sqlx::query_as("SELECT device_identifier, CAST(action_timestamp AS BIGINT), action_type FROM ac_actions ORDER BY action_timestamp, device_identifier")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_old_rows_are_pruned() {
+        let pool = db::connect_in_memory().await;
+        insert_action(&pool, 20, "Veranda", "on").await;
+        insert_action(&pool, 1, "Veranda", "off").await;
+        // The only commands of the living room are expired, the last on/off and the last action stay
+        insert_action(&pool, 30, "LivingRoom", "off").await;
+        insert_action(&pool, 25, "LivingRoom", "on").await;
+        insert_action(&pool, 24, "LivingRoom", "toggle-powerful").await;
+        for days_ago in [40, 2] {
+            sqlx::query("INSERT INTO execution_history (device_identifier, executed_at, nodeset_id, inputs, warnings) VALUES ('Veranda', $1, 1, '{}', '[]')")
+                .bind(NOW - days_ago * DAY_SECS)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        for days_ago in [400, 3] {
+            sqlx::query("INSERT INTO telemetry (metric, device_identifier, resolution, timestamp, value, samples) VALUES ('solar_watt', '', '1h', $1, 1.0, 1)")
+                .bind(NOW - days_ago * DAY_SECS)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let retention = RetentionConfig {
+            ac_actions_days: 10,
+            ..Default::default()
+        };
+        let pruned = run_maintenance(&pool, &retention, NOW).await.unwrap();
+        assert_eq!(pruned, Pruned { ac_actions: 2, executions: 1, sensor_history: 1 });
+
+        let day = |days_ago: i64| NOW - days_ago * DAY_SECS;
+        assert_eq!(
+            remaining_actions(&pool).await,
+            vec![
+                ("LivingRoom".to_string(), day(25), "on".to_string()),
+                ("LivingRoom".to_string(), day(24), "toggle-powerful".to_string()),
+                ("Veranda".to_string(), day(1), "off".to_string()),
+            ]
+        );
+
+        let keep_all = RetentionConfig {
+            ac_actions_days: 0,
+            execution_history_days: 0,
+            sensor_history_days: 0,
+        };
+        assert_eq!(run_maintenance(&pool, &keep_all, NOW + 1000 * DAY_SECS).await.unwrap(), Pruned::default());
+    }
+}
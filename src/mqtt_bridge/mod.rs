@@ -0,0 +1,305 @@
+//! MQTT bridge
+//!
+//! Lets Home Assistant and other MQTT clients follow and control the system without polling the
+//! REST API. Every live event is published under `mqtt_bridge.topic_prefix`: AC states to
+//! `<prefix>/<device>/state` and the meter to `<prefix>/meter` (both retained), decisions to
+//! `<prefix>/<device>/decision`, PIR detections to `<prefix>/<device>/pir`, state mismatches and
+//! commands that weren't applied to `<prefix>/<device>/alert` and schedule transitions to
+//! `<prefix>/schedule`. Sensor readings are published (retained) to `<prefix>/<device>/sensors` and
+//! `<prefix>/sensors` every `sensor_interval_seconds`, and the shadow mode to `<prefix>/shadow_mode`.
+//!
+//! Commands are taken from `<prefix>/<device>/set/override` (a JSON override like
+//! `POST /api/ac/:device/override`, or `clear`) and `<prefix>/set/shadow_mode` (`on`/`off`).
+//! Access to them is controlled by the broker.
+
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    ac_controller::manual_override,
+    app_state::AppState,
+    config, device_requests,
+    live_events::{self, LiveEvent},
+    types::{AcCommandRequest, MqttBridgeConfig},
+};
+
+/// Client id of the bridge, separate from the presence subscriber's so both can connect to one broker
+const MQTT_CLIENT_ID: &str = "power_control_center_bridge";
+
+/// Wait before reconnecting after the connection to the broker failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Shortest interval between sensor publishes
+const MIN_SENSOR_INTERVAL_SECS: u64 = 10;
+
+/// A message to publish
+#[derive(Debug, Clone, PartialEq)]
+struct Message {
+    topic: String,
+    payload: String,
+    retain: bool,
+}
+
+impl Message {
+    fn new(topic: String, payload: String, retain: bool) -> Self {
+        Self { topic, payload, retain }
+    }
+}
+
+/// Topic and payload a live event is published with, the payload is the event as the WebSocket sends it
+fn event_message(prefix: &str, event: &LiveEvent) -> Message {
+    let (topic, retain) = match event {
+        LiveEvent::AcState { device, .. } => (format!("{}/{}/state", prefix, device), true),
+        LiveEvent::Execution { device, .. } => (format!("{}/{}/decision", prefix, device), false),
+        LiveEvent::PirDetection { device, .. } => (format!("{}/{}/pir", prefix, device), false),
+        LiveEvent::StateMismatch { device, .. } | LiveEvent::CommandNotApplied { device, .. } => {
+            (format!("{}/{}/alert", prefix, device), false)
+        }
+        LiveEvent::MeterReading { .. } => (format!("{}/meter", prefix), true),
+        LiveEvent::ScheduleTransition { .. } => (format!("{}/schedule", prefix), false),
+    };
+    let payload = serde_json::to_string(event).unwrap_or_default();
+    Message::new(topic, payload, retain)
+}
+
+/// Override as sent to `<prefix>/<device>/set/override`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct OverrideCommand {
+    #[serde(flatten)]
+    command: AcCommandRequest,
+    minutes: u32,
+}
+
+/// A command received from the broker
+#[derive(Debug, Clone, PartialEq)]
+enum BridgeCommand {
+    SetOverride { device: String, command: OverrideCommand },
+    ClearOverride { device: String },
+    SetShadowMode(bool),
+}
+
+/// Parse a message on a command topic, or explain why it isn't a valid command
+fn parse_command(prefix: &str, topic: &str, payload: &str) -> Result<BridgeCommand, String> {
+    let levels: Vec<&str> = topic
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .map(|rest| rest.split('/').collect())
+        .unwrap_or_default();
+    let payload = payload.trim();
+    match levels.as_slice() {
+        ["set", "shadow_mode"] => match payload.to_lowercase().as_str() {
+            "on" | "true" | "1" => Ok(BridgeCommand::SetShadowMode(true)),
+            "off" | "false" | "0" => Ok(BridgeCommand::SetShadowMode(false)),
+            _ => Err(format!("expected on or off, got '{}'", payload)),
+        },
+        [device, "set", "override"] if payload.is_empty() || payload.eq_ignore_ascii_case("clear") => {
+            Ok(BridgeCommand::ClearOverride {
+                device: device.to_string(),
+            })
+        }
+        [device, "set", "override"] => serde_json::from_str(payload)
+            .map(|command| BridgeCommand::SetOverride {
+                device: device.to_string(),
+                command,
+            })
+            .map_err(|e| format!("invalid override: {}", e)),
+        _ => Err("unknown command topic".to_string()),
+    }
+}
+
+/// Carry out a command received from the broker
+async fn handle_command(state: &AppState, client: &AsyncClient, prefix: &str, command: BridgeCommand) {
+    match command {
+        BridgeCommand::SetOverride { device, command } => {
+            match manual_override::start(state, &device, &command.command, command.minutes).await {
+                Ok(until) => log::info!("Manual override of {} set over MQTT until {}", device, until),
+                Err(e) => log::warn!("Failed to set the manual override of {} over MQTT: {:?}", device, e),
+            }
+        }
+        BridgeCommand::ClearOverride { device } => match manual_override::stop(state, &device) {
+            Ok(()) => log::info!("Manual override of {} cleared over MQTT", device),
+            Err(e) => log::warn!("Failed to clear the manual override of {} over MQTT: {:?}", device, e),
+        },
+        BridgeCommand::SetShadowMode(enabled) => {
+            if let Err(e) = state.shadow_mode.set_enabled(&state.pool, enabled).await {
+                log::error!("Failed to store shadow mode set over MQTT: {}", e);
+                return;
+            }
+            publish(client, shadow_mode_message(prefix, enabled)).await;
+        }
+    }
+}
+
+fn shadow_mode_message(prefix: &str, enabled: bool) -> Message {
+    let payload = if enabled { "on" } else { "off" };
+    Message::new(format!("{}/shadow_mode", prefix), payload.to_string(), true)
+}
+
+async fn publish(client: &AsyncClient, message: Message) {
+    if let Err(e) = client
+        .publish(message.topic.clone(), QoS::AtLeastOnce, message.retain, message.payload)
+        .await
+    {
+        log::warn!("Failed to publish to MQTT topic '{}': {}", message.topic, e);
+    }
+}
+
+/// Current sensor readings of every device and of the household
+async fn sensor_messages(state: &AppState, prefix: &str) -> Vec<Message> {
+    let mut messages = Vec::new();
+    for device in state.devices().all() {
+        let device = device.as_str();
+        let sensors = device_requests::ac::get_sensors_cached(device).await.ok();
+        let payload = json!({
+            "temperature": sensors.as_ref().map(|s| s.temperature),
+            "humidity": sensors.as_ref().and_then(|s| s.humidity),
+            "is_auto_mode": sensors.as_ref().map(|s| s.is_automatic_mode),
+            "override_until": state.manual_overrides.get_active(device).map(|o| o.until.timestamp()),
+        });
+        messages.push(Message::new(format!("{}/{}/sensors", prefix, device), payload.to_string(), true));
+    }
+
+    let cfg = state.config();
+    let outdoor_temperature = device_requests::weather::get_current_outdoor_temp_cached(cfg.latitude, cfg.longitude)
+        .await
+        .ok();
+    let solar_watt = device_requests::meter::get_solar_production_cached()
+        .await
+        .ok()
+        .map(|production| production.current_production.max(0));
+    let payload = json!({
+        "outdoor_temperature": outdoor_temperature,
+        "solar_watt": solar_watt,
+    });
+    messages.push(Message::new(format!("{}/sensors", prefix), payload.to_string(), true));
+    messages
+}
+
+/// Forward live events to the broker
+async fn publish_events(client: AsyncClient, prefix: String) {
+    let mut events = live_events::get_live_events().subscribe();
+    loop {
+        match events.recv().await {
+            Ok(event) => publish(&client, event_message(&prefix, &event)).await,
+            Err(RecvError::Lagged(skipped)) => log::warn!("MQTT bridge skipped {} live events", skipped),
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Publish the sensor readings and shadow mode periodically
+async fn publish_sensors(state: AppState, client: AsyncClient, bridge: MqttBridgeConfig) {
+    let interval = Duration::from_secs(bridge.sensor_interval_seconds.max(MIN_SENSOR_INTERVAL_SECS));
+    loop {
+        for message in sensor_messages(&state, &bridge.topic_prefix).await {
+            publish(&client, message).await;
+        }
+        publish(&client, shadow_mode_message(&bridge.topic_prefix, state.shadow_mode.is_enabled())).await;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Run the MQTT bridge if a broker is configured, reconnecting on errors
+pub async fn bridge_loop() {
+    let bridge = config::get_config().mqtt_bridge.clone();
+    if bridge.host.is_empty() {
+        return;
+    }
+    let state = AppState::from_globals().await;
+    let prefix = bridge.topic_prefix.clone();
+
+    let mut options = MqttOptions::new(MQTT_CLIENT_ID, bridge.host.clone(), bridge.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if !bridge.username.is_empty() {
+        options.set_credentials(bridge.username.clone(), bridge.password.clone());
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options, 64);
+    tokio::spawn(publish_events(client.clone(), prefix.clone()));
+    tokio::spawn(publish_sensors(state.clone(), client.clone(), bridge.clone()));
+
+    // Nothing in this loop may wait on the client: only `poll` drains its request queue, so
+    // waiting for room in a queue filled by the publish tasks would never return
+    let command_topics = [format!("{}/+/set/override", prefix), format!("{}/set/shadow_mode", prefix)];
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                log::info!("MQTT bridge connected to broker {}:{}", bridge.host, bridge.port);
+                // Subscribe on every connect, a clean session forgets the subscriptions
+                let (client, command_topics) = (client.clone(), command_topics.clone());
+                tokio::spawn(async move {
+                    for topic in command_topics {
+                        if let Err(e) = client.subscribe(topic.clone(), QoS::AtLeastOnce).await {
+                            log::error!("Failed to subscribe to MQTT topic '{}': {}", topic, e);
+                        }
+                    }
+                });
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let payload = String::from_utf8_lossy(&publish.payload);
+                match parse_command(&prefix, &publish.topic, &payload) {
+                    // Overrides wait on the device, which would also hold up the keep-alive pings
+                    Ok(command) => {
+                        let (state, client, prefix) = (state.clone(), client.clone(), prefix.clone());
+                        tokio::spawn(async move { handle_command(&state, &client, &prefix, command).await });
+                    }
+                    Err(e) => log::warn!("Ignoring MQTT command on '{}': {}", publish.topic, e),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("MQTT bridge connection error: {}. Reconnecting in {}s", e, RECONNECT_DELAY.as_secs());
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: &str = "pcc";
+
+    #[test]
+    fn test_event_topics() {
+        let state = LiveEvent::pir_detection("Veranda");
+        assert_eq!(event_message(PREFIX, &state).topic, "pcc/Veranda/pir");
+        let meter = event_message(PREFIX, &LiveEvent::meter_reading(1.5, 0.5));
+        assert_eq!(meter.topic, "pcc/meter");
+        assert!(meter.retain);
+        let payload: serde_json::Value = serde_json::from_str(&meter.payload).unwrap();
+        assert_eq!(payload["type"], "meter_reading");
+        assert_eq!(payload["net_power_watt"], 1000);
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse_command(PREFIX, "pcc/set/shadow_mode", " ON "),
+            Ok(BridgeCommand::SetShadowMode(true))
+        );
+        assert_eq!(
+            parse_command(PREFIX, "pcc/LivingRoom/set/override", "clear"),
+            Ok(BridgeCommand::ClearOverride {
+                device: "LivingRoom".to_string()
+            })
+        );
+        match parse_command(PREFIX, "pcc/LivingRoom/set/override", r#"{"mode": "Heat", "temperature": 21, "minutes": 30}"#) {
+            Ok(BridgeCommand::SetOverride { device, command }) => {
+                assert_eq!(device, "LivingRoom");
+                assert_eq!(command.minutes, 30);
+                assert_eq!(command.command.temperature, Some(21.0));
+            }
+            other => panic!("Expected an override, got {:?}", other),
+        }
+
+        assert!(parse_command(PREFIX, "pcc/LivingRoom/set/override", r#"{"mode": "Heat"}"#).is_err());
+        assert!(parse_command(PREFIX, "pcc/set/shadow_mode", "maybe").is_err());
+        assert!(parse_command(PREFIX, "other/LivingRoom/set/override", "clear").is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Per-person presence detection
+//!
+//! Who is home is reported over MQTT (e.g. a phone geofence app or Home Assistant publishing
+//! `home`/`not_home` to `presence/<person>`) and/or by polling Home Assistant person entities.
+//! Once anybody has been reported, nodesets get `is_user_home` from presence instead of the
+//! time-based schedule, together with `people_home_count`. A person's presence can be
+//! overridden through `/api/presence`.
+
+mod mqtt;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{config, device_requests, types::PresenceConfig};
+
+/// Shortest time between Home Assistant polls, whatever the config says
+const MIN_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Global presence state
+static PRESENCE_STATE: OnceLock<Arc<PresenceState>> = OnceLock::new();
+
+/// Where a person's presence came from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceSource {
+    Mqtt,
+    HomeAssistant,
+    Override,
+}
+
+#[derive(Debug, Clone)]
+struct PresenceReport {
+    is_home: bool,
+    source: PresenceSource,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct PresenceOverride {
+    is_home: bool,
+    /// None keeps the override until it is cleared
+    until: Option<DateTime<Utc>>,
+}
+
+impl PresenceOverride {
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_none_or(|until| now < until)
+    }
+}
+
+/// Presence of one person as returned by `GET /api/presence`
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PersonPresence {
+    pub person: String,
+    /// Effective presence, the override if one is active
+    pub is_home: bool,
+    pub source: PresenceSource,
+    /// Unix timestamp (seconds) of the last report, None if the person was only overridden
+    pub reported_at: Option<i64>,
+    /// Unix timestamp (seconds) at which the override ends, None if not overridden or kept until cleared
+    pub override_until: Option<i64>,
+}
+
+/// Thread-safe presence of every known person
+#[derive(Default)]
+pub struct PresenceState {
+    reports: RwLock<HashMap<String, PresenceReport>>,
+    overrides: RwLock<HashMap<String, PresenceOverride>>,
+}
+
+impl PresenceState {
+    /// Record a person's presence as reported by a source
+    pub fn report(&self, person: &str, is_home: bool, source: PresenceSource) {
+        let mut reports = self.reports.write().unwrap();
+        let changed = reports.get(person).is_none_or(|r| r.is_home != is_home);
+        reports.insert(
+            person.to_string(),
+            PresenceReport {
+                is_home,
+                source,
+                updated_at: Utc::now(),
+            },
+        );
+        if changed {
+            log::info!("{} is {} ({:?})", person, if is_home { "home" } else { "away" }, source);
+        }
+    }
+
+    /// Override a person's presence, until `until` or until cleared if None
+    pub fn set_override(&self, person: &str, is_home: bool, until: Option<DateTime<Utc>>) {
+        let mut overrides = self.overrides.write().unwrap();
+        overrides.insert(person.to_string(), PresenceOverride { is_home, until });
+        log::info!("Presence of {} overridden to {}", person, if is_home { "home" } else { "away" });
+    }
+
+    /// Remove a person's override, returns false if there was no active override
+    pub fn clear_override(&self, person: &str) -> bool {
+        let mut overrides = self.overrides.write().unwrap();
+        overrides.remove(person).is_some_and(|o| o.is_active(Utc::now()))
+    }
+
+    /// Effective presence of every known person, sorted by name
+    pub fn people(&self) -> Vec<PersonPresence> {
+        let now = Utc::now();
+        let reports = self.reports.read().unwrap();
+        let overrides = self.overrides.read().unwrap();
+
+        let mut people: BTreeMap<&str, PersonPresence> = BTreeMap::new();
+        for (person, report) in reports.iter() {
+            people.insert(
+                person,
+                PersonPresence {
+                    person: person.clone(),
+                    is_home: report.is_home,
+                    source: report.source,
+                    reported_at: Some(report.updated_at.timestamp()),
+                    override_until: None,
+                },
+            );
+        }
+        for (person, presence_override) in overrides.iter().filter(|(_, o)| o.is_active(now)) {
+            let entry = people.entry(person).or_insert_with(|| PersonPresence {
+                person: person.clone(),
+                is_home: presence_override.is_home,
+                source: PresenceSource::Override,
+                reported_at: None,
+                override_until: None,
+            });
+            entry.is_home = presence_override.is_home;
+            entry.source = PresenceSource::Override;
+            entry.override_until = presence_override.until.map(|until| until.timestamp());
+        }
+        people.into_values().collect()
+    }
+
+    /// Number of people home, None if nobody has been reported or overridden yet
+    pub fn people_home_count(&self) -> Option<i64> {
+        let people = self.people();
+        if people.is_empty() {
+            return None;
+        }
+        Some(people.iter().filter(|p| p.is_home).count() as i64)
+    }
+}
+
+/// Get the global presence state
+pub fn get_presence_state() -> &'static Arc<PresenceState> {
+    PRESENCE_STATE.get_or_init(|| Arc::new(PresenceState::default()))
+}
+
+/// Parse a presence message payload, None if it isn't recognized
+/// Accepts Home Assistant states ("home"/"not_home") as well as on/off style values
+fn parse_presence_payload(payload: &str) -> Option<bool> {
+    match payload.trim().to_ascii_lowercase().as_str() {
+        "home" | "present" | "on" | "true" | "1" => Some(true),
+        "not_home" | "away" | "absent" | "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Person name of a presence topic, its last level ("presence/alex" -> "alex")
+fn person_from_topic(topic: &str) -> Option<&str> {
+    topic.rsplit('/').next().filter(|person| !person.is_empty())
+}
+
+/// Person name of a Home Assistant entity ("person.alex" -> "alex")
+fn person_from_entity(entity_id: &str) -> &str {
+    entity_id.split_once('.').map_or(entity_id, |(_, name)| name)
+}
+
+/// Poll the configured Home Assistant entities once
+async fn poll_home_assistant(presence: &PresenceConfig) {
+    let state = get_presence_state();
+    for entity_id in &presence.home_assistant_entities {
+        match device_requests::home_assistant::get_entity_state(
+            &presence.home_assistant_url,
+            &presence.home_assistant_token,
+            entity_id,
+        )
+        .await
+        {
+            // Zones other than home count as away, an unavailable tracker keeps the last report
+            Ok(entity_state) if entity_state == "unknown" || entity_state == "unavailable" => {
+                log::debug!("Presence of {} is {}", entity_id, entity_state);
+            }
+            Ok(entity_state) => {
+                state.report(person_from_entity(entity_id), entity_state == "home", PresenceSource::HomeAssistant)
+            }
+            Err(e) => log::warn!("Failed to get presence of {}: {}", entity_id, e),
+        }
+    }
+}
+
+/// Listen for MQTT presence messages and poll Home Assistant, as configured
+/// MQTT settings are read once at startup, Home Assistant settings on every poll
+pub async fn presence_loop() {
+    let presence = config::get_config().presence.clone();
+    if !presence.mqtt_host.is_empty() {
+        tokio::spawn(mqtt::mqtt_loop(presence));
+    }
+
+    loop {
+        let presence = config::get_config().presence.clone();
+        if !presence.home_assistant_url.is_empty() {
+            poll_home_assistant(&presence).await;
+        }
+        let interval = presence.poll_interval_seconds.max(MIN_POLL_INTERVAL_SECS);
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_people_home_count_and_overrides() {
+        let state = PresenceState::default();
+        assert_eq!(state.people_home_count(), None, "unknown until somebody is reported");
+
+        state.report("alex", true, PresenceSource::Mqtt);
+        state.report("sam", false, PresenceSource::HomeAssistant);
+        assert_eq!(state.people_home_count(), Some(1));
+
+        // Overrides win over reports and can add people
+        state.set_override("alex", false, None);
+        state.set_override("guest", true, Some(Utc::now() + chrono::Duration::hours(1)));
+        assert_eq!(state.people_home_count(), Some(1));
+        let people = state.people();
+        let names: Vec<&str> = people.iter().map(|p| p.person.as_str()).collect();
+        assert_eq!(names, vec!["alex", "guest", "sam"]);
+        assert_eq!(people[0].source, PresenceSource::Override);
+
+        // Expired overrides are ignored
+        state.set_override("sam", true, Some(Utc::now() - chrono::Duration::minutes(1)));
+        assert!(!state.people()[2].is_home);
+
+        assert!(state.clear_override("alex"));
+        assert!(!state.clear_override("alex"));
+        assert!(!state.clear_override("sam"), "an expired override is not active");
+        assert_eq!(state.people_home_count(), Some(2));
+    }
+
+    #[test]
+    fn test_parse_presence_messages() {
+        assert_eq!(parse_presence_payload("home"), Some(true));
+        assert_eq!(parse_presence_payload(" NOT_HOME\n"), Some(false));
+        assert_eq!(parse_presence_payload("ON"), Some(true));
+        assert_eq!(parse_presence_payload("work"), None);
+
+        assert_eq!(person_from_topic("presence/alex"), Some("alex"));
+        assert_eq!(person_from_topic("presence/"), None);
+        assert_eq!(person_from_entity("person.alex"), "alex");
+        assert_eq!(person_from_entity("alex"), "alex");
+    }
+}
@@ -0,0 +1,58 @@
+//! MQTT presence subscriber
+//!
+//! Subscribes to `mqtt_topic` and records a presence report for every message, with the last
+//! topic level as the person. Retained messages give the current presence right after connecting.
+
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+
+use super::{PresenceSource, get_presence_state, parse_presence_payload, person_from_topic};
+use crate::types::PresenceConfig;
+
+/// Client id the controller connects to the broker with
+const MQTT_CLIENT_ID: &str = "power_control_center";
+
+/// Wait before reconnecting after the connection to the broker failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Handle one message published on the presence topic
+fn handle_message(topic: &str, payload: &[u8]) {
+    let Some(person) = person_from_topic(topic) else {
+        log::warn!("Ignoring presence message on topic '{}' without a person", topic);
+        return;
+    };
+    let payload = String::from_utf8_lossy(payload);
+    match parse_presence_payload(&payload) {
+        Some(is_home) => get_presence_state().report(person, is_home, PresenceSource::Mqtt),
+        None => log::warn!("Ignoring unrecognized presence '{}' for {}", payload.trim(), person),
+    }
+}
+
+/// Stay connected to the broker and record presence messages, reconnecting on errors
+pub async fn mqtt_loop(presence: PresenceConfig) {
+    let mut options = MqttOptions::new(MQTT_CLIENT_ID, presence.mqtt_host.clone(), presence.mqtt_port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if !presence.mqtt_username.is_empty() {
+        options.set_credentials(presence.mqtt_username.clone(), presence.mqtt_password.clone());
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options, 10);
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                log::info!("Connected to MQTT broker {}:{}", presence.mqtt_host, presence.mqtt_port);
+                // Subscribe on every connect, a clean session forgets the subscription
+                if let Err(e) = client.subscribe(presence.mqtt_topic.clone(), QoS::AtLeastOnce).await {
+                    log::error!("Failed to subscribe to MQTT topic '{}': {}", presence.mqtt_topic, e);
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => handle_message(&publish.topic, &publish.payload),
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("MQTT connection error: {}. Reconnecting in {}s", e, RECONNECT_DELAY.as_secs());
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
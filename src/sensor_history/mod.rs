@@ -0,0 +1,277 @@
+//! Sensor history
+//!
+//! After every control cycle the indoor temperature of each device, the outdoor temperature, solar
+//! production and net power are sampled into the `telemetry` table, so profiles can be tuned
+//! against past days. Every 5 minutes the raw samples are averaged into 5 minute and hourly
+//! periods and old rows are dropped: raw samples are kept for 48 hours and 5 minute averages for
+//! 30 days. Hourly averages are kept for `retention.sensor_history_days`, see `maintenance`.
+//! `GET /api/dashboard/history` returns a metric at the resolution that fits the requested range.
+
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::{
+    ac_controller::devices,
+    config,
+    db::{self, DbPool, sensor_history::Reading},
+    device_requests,
+};
+
+const MINUTE_SECS: i64 = 60;
+const HOUR_SECS: i64 = 60 * MINUTE_SECS;
+const DAY_SECS: i64 = 24 * HOUR_SECS;
+
+/// How often samples are downsampled and old rows dropped
+const DOWNSAMPLE_INTERVAL_SECS: u64 = 5 * 60;
+
+/// Periods this far back are recomputed on every downsample, to pick up late samples
+const RECOMPUTE_WINDOW_SECS: i64 = 2 * HOUR_SECS;
+
+/// A recorded sensor metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Indoor temperature (°C) of a device
+    IndoorTemperature,
+    /// Outdoor temperature (°C)
+    OutdoorTemperature,
+    /// Solar production (W)
+    SolarWatt,
+    /// Net power (W), positive while importing from the grid
+    NetPowerWatt,
+}
+
+impl Metric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::IndoorTemperature => "indoor_temperature",
+            Metric::OutdoorTemperature => "outdoor_temperature",
+            Metric::SolarWatt => "solar_watt",
+            Metric::NetPowerWatt => "net_power_watt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Metric::IndoorTemperature, Metric::OutdoorTemperature, Metric::SolarWatt, Metric::NetPowerWatt]
+            .into_iter()
+            .find(|metric| metric.as_str() == name)
+    }
+
+    /// Whether the metric is recorded per device
+    pub fn is_per_device(self) -> bool {
+        self == Metric::IndoorTemperature
+    }
+}
+
+/// Resolution of the stored rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Every control cycle
+    Raw,
+    FiveMinutes,
+    Hourly,
+}
+
+impl Resolution {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Raw => "raw",
+            Resolution::FiveMinutes => "5m",
+            Resolution::Hourly => "1h",
+        }
+    }
+
+    /// How long rows of this resolution are kept by the downsampling
+    /// None for hourly averages, those are pruned by the daily maintenance
+    fn retention_secs(self) -> Option<i64> {
+        match self {
+            Resolution::Raw => Some(2 * DAY_SECS),
+            Resolution::FiveMinutes => Some(30 * DAY_SECS),
+            Resolution::Hourly => None,
+        }
+    }
+
+    /// Finest resolution that still covers a range reaching `range_secs` back from now
+    /// Raw samples are used up to 48 hours, 5 minute averages up to a week
+    pub fn for_range(range_secs: i64) -> Self {
+        if Resolution::Raw.retention_secs().is_some_and(|retention| range_secs <= retention) {
+            Resolution::Raw
+        } else if range_secs <= 7 * DAY_SECS {
+            Resolution::FiveMinutes
+        } else {
+            Resolution::Hourly
+        }
+    }
+}
+
+/// Read the current values from the caches, metrics that can't be read are skipped
+async fn read_sample() -> Vec<Reading> {
+    let mut readings = Vec::new();
+    for device in devices::get_device_registry().all() {
+        if let Ok(sensors) = device_requests::ac::get_sensors_cached(device.as_str()).await {
+            readings.push(Reading {
+                metric: Metric::IndoorTemperature.as_str(),
+                device_identifier: device.as_str().to_string(),
+                value: sensors.temperature,
+            });
+        }
+    }
+
+    let mut household = |metric: Metric, value: f64| {
+        readings.push(Reading {
+            metric: metric.as_str(),
+            device_identifier: String::new(),
+            value,
+        });
+    };
+    let cfg = config::get_config();
+    if let Ok(temperature) = device_requests::weather::get_current_outdoor_temp_cached(cfg.latitude, cfg.longitude).await {
+        household(Metric::OutdoorTemperature, temperature);
+    }
+    if let Ok(production) = device_requests::meter::get_solar_production_cached().await {
+        household(Metric::SolarWatt, f64::from(production.current_production.max(0)));
+    }
+    if let Ok(reading) = device_requests::meter::get_latest_reading_cached().await {
+        household(
+            Metric::NetPowerWatt,
+            (reading.current_consumption_kw - reading.current_production_kw) * 1000.0,
+        );
+    }
+    readings
+}
+
+/// Sample the sensors into the history in the background, called after every control cycle
+pub fn record_cycle() {
+    tokio::spawn(async move {
+        let readings = read_sample().await;
+        if readings.is_empty() {
+            return;
+        }
+        let pool = db::get_pool().await;
+        if let Err(e) = db::sensor_history::insert_raw(pool, Utc::now().timestamp(), &readings).await {
+            log::warn!("Failed to record sensor history: {}", e);
+        }
+    });
+}
+
+/// Average recent samples into 5 minute and hourly periods and drop rows past their retention
+/// Only complete periods are written
+async fn downsample(pool: &DbPool, now: i64) -> Result<(), sqlx::Error> {
+    let five_minutes = 5 * MINUTE_SECS;
+    let end = now - now.rem_euclid(five_minutes);
+    db::sensor_history::downsample(
+        pool,
+        Resolution::Raw.as_str(),
+        Resolution::FiveMinutes.as_str(),
+        five_minutes,
+        end - RECOMPUTE_WINDOW_SECS,
+        end,
+    )
+    .await?;
+
+    let end = now - now.rem_euclid(HOUR_SECS);
+    db::sensor_history::downsample(
+        pool,
+        Resolution::FiveMinutes.as_str(),
+        Resolution::Hourly.as_str(),
+        HOUR_SECS,
+        end - RECOMPUTE_WINDOW_SECS,
+        end,
+    )
+    .await?;
+
+    for resolution in [Resolution::Raw, Resolution::FiveMinutes, Resolution::Hourly] {
+        let Some(retention_secs) = resolution.retention_secs() else {
+            continue;
+        };
+        let deleted = db::sensor_history::delete_before(pool, resolution.as_str(), now - retention_secs).await?;
+        if deleted > 0 {
+            log::debug!("Dropped {} {} sensor history rows past their retention", deleted, resolution.as_str());
+        }
+    }
+    Ok(())
+}
+
+/// Downsample the sensor history every 5 minutes
+pub async fn downsample_loop() {
+    loop {
+        let pool = db::get_pool().await;
+        if let Err(e) = downsample(pool, Utc::now().timestamp()).await {
+            log::warn!("Failed to downsample sensor history: {}", e);
+        }
+        tokio::time::sleep(Duration::from_secs(DOWNSAMPLE_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-11-24T00:00:00Z
+    const DAY_START: i64 = 1763942400;
+
+    fn reading(metric: Metric, device: &str, value: f64) -> Reading {
+        Reading {
+            metric: metric.as_str(),
+            device_identifier: device.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_metric_names_and_resolution_for_range() {
+        assert_eq!(Metric::from_name("solar_watt"), Some(Metric::SolarWatt));
+        assert_eq!(Metric::from_name("humidity"), None);
+        assert!(Metric::IndoorTemperature.is_per_device());
+        assert!(!Metric::OutdoorTemperature.is_per_device());
+
+        assert_eq!(Resolution::for_range(24 * HOUR_SECS), Resolution::Raw);
+        assert_eq!(Resolution::for_range(48 * HOUR_SECS), Resolution::Raw);
+        assert_eq!(Resolution::for_range(7 * DAY_SECS), Resolution::FiveMinutes);
+        assert_eq!(Resolution::for_range(30 * DAY_SECS), Resolution::Hourly);
+    }
+
+    #[tokio::test]
+    async fn test_downsample_averages_complete_periods() {
+        let pool = db::connect_in_memory().await;
+        let samples = [(0, 20.0), (90, 21.0), (240, 22.0), (300, 30.0)];
+        for (offset, value) in samples {
+            let readings = [
+                reading(Metric::IndoorTemperature, "LivingRoom", value),
+                reading(Metric::SolarWatt, "", 100.0),
+            ];
+            db::sensor_history::insert_raw(&pool, DAY_START + offset, &readings).await.unwrap();
+        }
+
+        // The period starting at 300 isn't complete yet
+        downsample(&pool, DAY_START + 400).await.unwrap();
+        let points = db::sensor_history::get_points(&pool, "indoor_temperature", "LivingRoom", "5m", 0)
+            .await
+            .unwrap();
+        assert_eq!(points, vec![(DAY_START, 21.0)]);
+
+        // Hourly averages weigh each 5 minute period by its samples
+        downsample(&pool, DAY_START + HOUR_SECS).await.unwrap();
+        let points = db::sensor_history::get_points(&pool, "indoor_temperature", "LivingRoom", "1h", 0)
+            .await
+            .unwrap();
+        assert_eq!(points, vec![(DAY_START, 23.25)]);
+        let points = db::sensor_history::get_points(&pool, "solar_watt", "", "1h", 0).await.unwrap();
+        assert_eq!(points, vec![(DAY_START, 100.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_downsample_drops_rows_past_retention() {
+        let pool = db::connect_in_memory().await;
+        let readings = [reading(Metric::OutdoorTemperature, "", 5.0)];
+        db::sensor_history::insert_raw(&pool, DAY_START, &readings).await.unwrap();
+        downsample(&pool, DAY_START + HOUR_SECS).await.unwrap();
+
+        // Two days later the raw sample is gone, the averages are kept
+        downsample(&pool, DAY_START + 2 * DAY_SECS + HOUR_SECS).await.unwrap();
+        let raw = db::sensor_history::get_points(&pool, "outdoor_temperature", "", "raw", 0).await.unwrap();
+        assert!(raw.is_empty());
+        let five_minutes = db::sensor_history::get_points(&pool, "outdoor_temperature", "", "5m", 0).await.unwrap();
+        assert_eq!(five_minutes, vec![(DAY_START, 5.0)]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Graceful shutdown
+//!
+//! On SIGTERM (systemd stop/restart) or SIGINT (Ctrl+C) the controller loop stops after its
+//! current cycle instead of being killed halfway through sending a command. The ACs are then put
+//! in the configured `shutdown_ac_state`, so a restart doesn't leave one running in powerful mode
+//! unattended, pending database writes are flushed and the web server finishes its open requests.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::{
+    ac_controller::{ac_executor::{self, AcState}, devices, manual_mode_monitor, shadow_mode},
+    config, db, device_requests,
+    live_events::{self, LiveEvent},
+    types::{CauseReason, ShutdownAcState},
+};
+
+/// Longest wait for a running control cycle to finish, it may be retrying commands
+const CONTROLLER_STOP_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest wait for open HTTP requests, long-lived websockets are cut off after this
+const WEBSERVER_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Set once shutdown has started
+static SHUTDOWN: OnceLock<watch::Sender<bool>> = OnceLock::new();
+
+fn get_shutdown() -> &'static watch::Sender<bool> {
+    SHUTDOWN.get_or_init(|| watch::channel(false).0)
+}
+
+/// Start shutting down, wakes everything waiting in `requested`
+pub fn request() {
+    get_shutdown().send_replace(true);
+}
+
+/// Wait until shutdown has started
+pub async fn requested() {
+    let mut receiver = get_shutdown().subscribe();
+    let _ = receiver.wait_for(|requested| *requested).await;
+}
+
+/// Wait for SIGTERM or SIGINT
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => log::info!("SIGTERM received, shutting down"),
+                    _ = tokio::signal::ctrl_c() => log::info!("SIGINT received, shutting down"),
+                }
+                return;
+            }
+            Err(e) => log::warn!("Failed to listen for SIGTERM, only SIGINT shuts down gracefully: {}", e),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        log::error!("Failed to listen for SIGINT: {}", e);
+        std::future::pending::<()>().await;
+    }
+    log::info!("SIGINT received, shutting down");
+}
+
+/// What to do with an AC on shutdown
+#[derive(Debug, PartialEq)]
+enum Handoff {
+    TurnOff,
+    DisablePowerful,
+}
+
+/// Command needed to put an AC in the shutdown state, None if it already is
+fn handoff_for(state: &AcState, shutdown_state: ShutdownAcState) -> Option<Handoff> {
+    match shutdown_state {
+        ShutdownAcState::Off if state.is_on => Some(Handoff::TurnOff),
+        ShutdownAcState::NoPowerful if state.is_on && state.powerful_mode => Some(Handoff::DisablePowerful),
+        _ => None,
+    }
+}
+
+/// Put every AC in the configured shutdown state
+/// Devices in manual mode are left alone, and nothing is sent in shadow mode
+async fn hand_off_devices(shutdown_state: ShutdownAcState) {
+    if shutdown_state == ShutdownAcState::Leave {
+        return;
+    }
+    if shadow_mode::get_shadow_mode().is_enabled() {
+        log::info!("Shadow mode is on, leaving the ACs as they are");
+        return;
+    }
+
+    let state_manager = ac_executor::get_state_manager();
+    for device in devices::get_device_registry().all() {
+        let device_name = device.as_str();
+        if manual_mode_monitor::get_manual_mode_monitor().is_manual_mode(device_name) {
+            continue;
+        }
+        let state = state_manager.get_state(device_name);
+        let result = match handoff_for(&state, shutdown_state) {
+            None => continue,
+            Some(Handoff::TurnOff) => ac_executor::turn_off_device(device, CauseReason::Shutdown).await.map(|_| ()),
+            Some(Handoff::DisablePowerful) => {
+                log::info!("Disabling powerful mode for AC '{}' before shutting down", device_name);
+                match device_requests::ac::toggle_powerful(device_name, CauseReason::Shutdown.id(), None).await {
+                    Ok(_) => {
+                        let state = AcState {
+                            powerful_mode: false,
+                            ..state
+                        };
+                        live_events::get_live_events().publish(LiveEvent::ac_state(device_name, &state));
+                        state_manager.set_state(device_name, state);
+                        Ok(())
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
+        };
+        if let Err(e) = result {
+            log::error!("Failed to put AC '{}' in its shutdown state: {}", device_name, e);
+        }
+    }
+}
+
+/// Stop the controller and web server, hand off the ACs and flush pending database writes
+pub async fn shut_down(controller: JoinHandle<()>, webserver: JoinHandle<()>) {
+    request();
+
+    if tokio::time::timeout(CONTROLLER_STOP_TIMEOUT, controller).await.is_err() {
+        log::warn!("The control cycle didn't finish within {:?}, stopping anyway", CONTROLLER_STOP_TIMEOUT);
+    }
+
+    hand_off_devices(config::get_config().shutdown_ac_state).await;
+
+    // Commands that couldn't be logged yet get one last attempt
+    device_requests::logging_queue::get_logging_queue().process_queue().await;
+    ac_executor::get_state_manager().flush().await;
+
+    if tokio::time::timeout(WEBSERVER_STOP_TIMEOUT, webserver).await.is_err() {
+        log::warn!("Web server connections still open after {:?}, closing them", WEBSERVER_STOP_TIMEOUT);
+    }
+
+    db::get_pool().await.close().await;
+    log::info!("Shutdown complete");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Celsius;
+
+    #[test]
+    fn test_handoff_for() {
+        let powerful = AcState::new_on(4, 0, Celsius(22.0), 0, true);
+        let normal = AcState::new_on(4, 0, Celsius(22.0), 0, false);
+        let off = AcState::new_off();
+
+        assert_eq!(handoff_for(&powerful, ShutdownAcState::Leave), None);
+        assert_eq!(handoff_for(&powerful, ShutdownAcState::NoPowerful), Some(Handoff::DisablePowerful));
+        assert_eq!(handoff_for(&normal, ShutdownAcState::NoPowerful), None);
+        assert_eq!(handoff_for(&normal, ShutdownAcState::Off), Some(Handoff::TurnOff));
+        assert_eq!(handoff_for(&off, ShutdownAcState::Off), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Environment report
+//!
+//! Logged once at startup and included in `/api/status/details`, so a bug report with a journal snippet
+//! or the status output shows which build, config, database and optional features were in use.
+
+use serde::Serialize;
+
+use crate::{
+    ac_controller::{away_mode, devices::DeviceRegistry, shadow_mode},
+    config,
+    db::DbPool,
+    types::{Config, ShutdownAcState},
+};
+
+/// Build and environment the service is running in
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct EnvironmentReport {
+    pub version: &'static str,
+    /// Short hash of the commit the binary was built from, "unknown" outside a git checkout
+    pub git_hash: &'static str,
+    pub config_path: &'static str,
+    /// Configured AC devices
+    pub devices: Vec<String>,
+    pub database_path: String,
+    /// None if the database file can't be read
+    pub database_size_bytes: Option<u64>,
+    /// Version of the last applied migration
+    pub migration_version: Option<i64>,
+    /// Optional features that are turned on
+    pub features: Vec<&'static str>,
+}
+
+/// The database file, or the PostgreSQL server and database without credentials
+#[cfg(not(feature = "postgres"))]
+fn database_location(config: &Config) -> String {
+    config.database_path.clone()
+}
+
+#[cfg(feature = "postgres")]
+fn database_location(config: &Config) -> String {
+    use std::str::FromStr;
+    match sqlx::postgres::PgConnectOptions::from_str(&config.database_url) {
+        Ok(options) => format!(
+            "postgres://{}:{}/{}",
+            options.get_host(),
+            options.get_port(),
+            options.get_database().unwrap_or_default()
+        ),
+        Err(_) => "PostgreSQL".to_string(),
+    }
+}
+
+/// Size of the database, None if it can't be read
+#[cfg(not(feature = "postgres"))]
+async fn database_size(_pool: &DbPool, config: &Config) -> Option<u64> {
+    tokio::fs::metadata(&config.database_path).await.ok().map(|m| m.len())
+}
+
+#[cfg(feature = "postgres")]
+async fn database_size(pool: &DbPool, _config: &Config) -> Option<u64> {
+    sqlx::query_scalar::<_, i64>("SELECT pg_database_size(current_database())")
+        .fetch_one(pool)
+        .await
+        .ok()
+        .and_then(|size| u64::try_from(size).ok())
+}
+
+impl EnvironmentReport {
+    /// Collect the report for the current config and database
+    pub async fn collect(pool: &DbPool, config: &Config) -> Self {
+        let migration_version = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT MAX(version) FROM _sqlx_migrations WHERE success",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the migration version: {}", e);
+            None
+        });
+
+        Self {
+            version: crate::updater::CURRENT_VERSION,
+            git_hash: env!("GIT_HASH"),
+            config_path: config::CONFIG_FILE_PATH,
+            devices: DeviceRegistry::from_config(config).names(),
+            database_path: database_location(config),
+            database_size_bytes: database_size(pool, config).await,
+            migration_version,
+            features: enabled_features(
+                config,
+                shadow_mode::get_shadow_mode().is_enabled(),
+                away_mode::get_away_mode().is_enabled(),
+            ),
+        }
+    }
+
+    /// Log the report, one line per topic so it survives journal grepping
+    pub fn log(&self) {
+        log::info!("power_control_center {} ({})", self.version, self.git_hash);
+        log::info!("  config: {}", self.config_path);
+        log::info!(
+            "  database: {} ({}, migration {})",
+            self.database_path,
+            self.database_size_bytes.map_or("size unknown".to_string(), |size| format!("{} bytes", size)),
+            self.migration_version.map_or("none".to_string(), |version| version.to_string())
+        );
+        log::info!("  devices: {}", list_or_none(&self.devices));
+        log::info!("  features: {}", list_or_none(&self.features));
+    }
+}
+
+fn list_or_none<T: AsRef<str>>(items: &[T]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Names of the optional features that are turned on in a config
+fn enabled_features(config: &Config, shadow_mode: bool, away_mode: bool) -> Vec<&'static str> {
+    [
+        ("shadow_mode", shadow_mode),
+        ("away_mode", away_mode),
+        ("deep_idle", config.deep_idle_enabled),
+        ("shed_load_on_grid_outage", config.shed_load_on_grid_outage),
+        ("telemetry", config.telemetry_enabled),
+        ("update_check", config.update_check_enabled),
+        ("self_update", config.self_update_enabled),
+        ("admin_api", !config.admin_api_key.is_empty()),
+        ("default_nodeset", config.default_nodeset_enabled),
+        ("strict_cause_reasons", config.strict_cause_reasons),
+        ("energy_price", !config.energy_price_bidding_zone.is_empty()),
+        ("battery", !config.battery_api_endpoint.is_empty()),
+        ("power_budget", config.power_budget_watt > 0),
+        ("presence_mqtt", !config.presence.mqtt_host.is_empty()),
+        ("presence_home_assistant", !config.presence.home_assistant_url.is_empty()),
+        ("cold_snap_preheat", config.cold_snap_preheat.enabled),
+        ("away_webhook", !config.away_webhook.keys.is_empty()),
+        ("solar_forecast", config.solar_panels.kwp > 0.0),
+        ("shutdown_handoff", config.shutdown_ac_state != ShutdownAcState::Leave),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_features() {
+        let config = Config {
+            deep_idle_enabled: true,
+            default_nodeset_enabled: false,
+            energy_price_bidding_zone: "NL".to_string(),
+            ..Default::default()
+        };
+        let features = enabled_features(&config, true, false);
+        assert_eq!(features, vec!["shadow_mode", "deep_idle", "energy_price"]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_reads_migration_version() {
+        let pool = crate::db::connect_in_memory().await;
+        let report = EnvironmentReport::collect(&pool, &Config::default()).await;
+        assert!(report.migration_version.is_some_and(|version| version >= 20251226000000));
+        assert!(!report.git_hash.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+//! Estimated AC energy per device and cause
+//!
+//! The smart meter only measures the whole household, so the energy of each AC is estimated from
+//! the minutes it ran, its mode and whether powerful mode was on, using the device's configured
+//! draw (`power_watt`, `mode_power_watt`, `powerful_power_watt`). The minutes are attributed to the
+//! cause of the last command sent to the device. With `energy_meter_correction` the estimates of an
+//! hour are scaled down when together they exceed the household consumption the meter measured.
+
+use std::collections::BTreeMap;
+
+use power_control_center_core::ac_state::ac_mode_name;
+
+use super::{covered_seconds, Sample, HOUR_SECS};
+use crate::types::{Config, db_types::{DeviceEnergy, EnergyStats}};
+
+/// Mode recorded when the mode of the active command isn't known
+const UNKNOWN_MODE: &str = "Unknown";
+
+/// Cause of the last command sent to a device at or before `at`, 0 (Undefined) if there is none
+/// `commands` are (device, timestamp, cause id) sorted by time
+fn cause_at(commands: &[(String, i64, i64)], device: &str, at: i64) -> i64 {
+    commands
+        .iter()
+        .rev()
+        .find(|(command_device, timestamp, _)| command_device == device && *timestamp <= at)
+        .map_or(0, |(_, _, cause_id)| *cause_id)
+}
+
+/// Estimate the energy each AC used in the hour starting at `hour`
+/// `samples` are sorted by time like for `rollup_hour`; devices that are no longer configured are skipped
+pub(super) fn estimate_hour(
+    hour: i64,
+    samples: &[Sample],
+    commands: &[(String, i64, i64)],
+    config: &Config,
+    household: Option<&EnergyStats>,
+) -> Vec<DeviceEnergy> {
+    let mut by_device: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        by_device.entry(sample.device.as_str()).or_default().push(sample);
+    }
+
+    // Seconds and watt-seconds per (device, cause, mode, powerful)
+    let mut totals: BTreeMap<(&str, i64, &str, bool), (i64, f64)> = BTreeMap::new();
+    for (device, device_samples) in &by_device {
+        for (sample, seconds) in covered_seconds(device_samples, hour) {
+            if !sample.is_on || seconds == 0 {
+                continue;
+            }
+            let mode = ac_mode_name(sample.mode);
+            let Some(watts) = config.estimated_power_watt(device, mode, sample.is_powerful) else {
+                continue;
+            };
+            let cause_id = cause_at(commands, device, sample.evaluated_at);
+            let total = totals
+                .entry((device, cause_id, mode.unwrap_or(UNKNOWN_MODE), sample.is_powerful))
+                .or_default();
+            total.0 += seconds;
+            total.1 += f64::from(watts) * seconds as f64;
+        }
+    }
+
+    let mut estimates: Vec<DeviceEnergy> = totals
+        .into_iter()
+        .map(|((device, cause_id, mode, is_powerful), (seconds, watt_seconds))| DeviceEnergy {
+            period_start: hour,
+            device_identifier: device.to_string(),
+            cause_id,
+            mode: mode.to_string(),
+            is_powerful,
+            runtime_minutes: seconds as f64 / 60.0,
+            estimated_kwh: watt_seconds / 3_600_000.0,
+        })
+        .collect();
+
+    if config.energy_meter_correction
+        && let Some(household) = household
+    {
+        correct_to_meter(&mut estimates, household);
+    }
+    estimates
+}
+
+/// Scale the estimates of an hour down to the household consumption the meter measured in it
+/// Hours the meter only partly covered are extrapolated to the full hour first
+fn correct_to_meter(estimates: &mut [DeviceEnergy], household: &EnergyStats) {
+    if household.covered_minutes <= 0.0 {
+        return;
+    }
+    let measured_kwh = household.grid_import_kwh + household.solar_kwh - household.grid_export_kwh;
+    let consumption_kwh = measured_kwh.max(0.0) * (HOUR_SECS as f64 / 60.0) / household.covered_minutes;
+    let estimated_kwh: f64 = estimates.iter().map(|estimate| estimate.estimated_kwh).sum();
+
+    if estimated_kwh > consumption_kwh {
+        let factor = consumption_kwh / estimated_kwh;
+        for estimate in estimates {
+            estimate.estimated_kwh *= factor;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-11-24T01:00:00Z
+    const HOUR: i64 = 1763946000;
+
+    fn sample(device: &str, evaluated_at: i64, mode: i32, is_powerful: bool) -> Sample {
+        Sample {
+            device: device.to_string(),
+            evaluated_at,
+            indoor_temperature: None,
+            outdoor_temperature: None,
+            is_on: mode != 0,
+            mode,
+            is_powerful,
+            net_power_watt: None,
+            solar_watt: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_hour_by_cause_mode_and_powerful() {
+        let mut config = Config::default();
+        config
+            .ac_controller_endpoints
+            .get_mut("Veranda")
+            .unwrap()
+            .mode_power_watt
+            .insert("Fan".to_string(), 60);
+
+        // Veranda heats for 10 minutes, runs powerful for 10 minutes, then fans for 5 minutes
+        let samples = vec![
+            sample("Veranda", HOUR - 300, 4, false),
+            sample("Veranda", HOUR, 4, false),
+            sample("Veranda", HOUR + 300, 4, false),
+            sample("Veranda", HOUR + 600, 4, true),
+            sample("Veranda", HOUR + 900, 4, true),
+            sample("Veranda", HOUR + 1200, 3, false),
+            sample("Veranda", HOUR + 1500, 0, false),
+            sample("Attic", HOUR, 4, false),
+        ];
+        let commands = vec![
+            ("Veranda".to_string(), HOUR - 5000, 2),
+            ("Veranda".to_string(), HOUR + 600, 7),
+        ];
+
+        let estimates = estimate_hour(HOUR, &samples, &commands, &config, None);
+        let summary: Vec<(i64, &str, bool, f64, f64)> = estimates
+            .iter()
+            .map(|e| (e.cause_id, e.mode.as_str(), e.is_powerful, e.runtime_minutes, e.estimated_kwh))
+            .collect();
+        assert_eq!(summary, vec![
+            (2, "Heat", false, 10.0, 600_000.0 / 3_600_000.0),
+            (7, "Fan", false, 5.0, 18_000.0 / 3_600_000.0),
+            (7, "Heat", true, 10.0, 0.25),
+        ]);
+        // The unconfigured Attic isn't estimated
+        assert!(estimates.iter().all(|e| e.device_identifier == "Veranda"));
+    }
+
+    #[test]
+    fn test_meter_correction_caps_the_estimates() {
+        let mut config = Config::default();
+        let samples = vec![sample("Veranda", HOUR, 4, false), sample("LivingRoom", HOUR, 4, false)];
+        // Each device is estimated at 1000 W for 10 minutes, 1/3 kWh together
+        // Half an hour measured at 0.05 kWh extrapolates to 0.1 kWh for the hour
+        let household = EnergyStats {
+            period_start: HOUR,
+            covered_minutes: 30.0,
+            grid_import_kwh: 0.04,
+            grid_export_kwh: 0.01,
+            solar_kwh: 0.02,
+        };
+
+        let estimates = estimate_hour(HOUR, &samples, &[], &config, Some(&household));
+        let total: f64 = estimates.iter().map(|e| e.estimated_kwh).sum();
+        assert!((total - 1.0 / 3.0).abs() < 1e-9);
+
+        config.energy_meter_correction = true;
+        let estimates = estimate_hour(HOUR, &samples, &[], &config, Some(&household));
+        assert_eq!(estimates.len(), 2);
+        for estimate in &estimates {
+            assert!((estimate.estimated_kwh - 0.05).abs() < 1e-9);
+            assert_eq!(estimate.runtime_minutes, 10.0);
+        }
+    }
+}
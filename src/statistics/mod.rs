@@ -0,0 +1,352 @@
+//! Hourly and daily statistics
+//!
+//! Cycle snapshots are only kept for a week, too short and too many rows for year-scale charts.
+//! Once an hour is complete its snapshots and AC commands are rolled up into `device_stats_hourly`
+//! (average temperatures, runtime, commands) and `energy_hourly` (grid import/export and solar
+//! kWh), and the UTC day containing it is re-summed into the daily tables. The energy each AC used
+//! is estimated into `device_energy`, see `energy`.
+//! `GET /api/statistics/hourly` and `GET /api/statistics/daily` return the stored periods.
+
+mod energy;
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::{
+    ac_controller::cycle_snapshots::InputSnapshot,
+    db::{self, DbPool},
+    types::{Config, db_types::{self, DeviceStats, EnergyStats}},
+    weather_history::hour_start,
+};
+
+const HOUR_SECS: i64 = 60 * 60;
+const DAY_SECS: i64 = 24 * HOUR_SECS;
+
+/// How often complete hours are rolled up
+const ROLLUP_INTERVAL_SECS: u64 = 10 * 60;
+
+/// A cycle counts for the time until the next cycle, but at most this long
+/// Longer gaps mean the controller was down, those minutes aren't counted
+const MAX_SAMPLE_GAP_SECS: i64 = 10 * 60;
+
+/// Start of the UTC day containing a timestamp
+pub fn day_start(timestamp: i64) -> i64 {
+    timestamp - timestamp.rem_euclid(DAY_SECS)
+}
+
+/// Values of a cycle snapshot used for statistics
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    device: String,
+    evaluated_at: i64,
+    indoor_temperature: Option<f64>,
+    outdoor_temperature: Option<f64>,
+    is_on: bool,
+    /// AC mode of the active command, see `ac_mode_name`
+    mode: i32,
+    is_powerful: bool,
+    net_power_watt: Option<f64>,
+    solar_watt: Option<f64>,
+}
+
+impl Sample {
+    fn from_snapshot(snapshot: &db_types::CycleSnapshot) -> Option<Self> {
+        let inputs: InputSnapshot = match serde_json::from_str(&snapshot.inputs) {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                log::warn!("Skipping unreadable cycle snapshot {}: {}", snapshot.id, e);
+                return None;
+            }
+        };
+        let number = |key: &str| inputs.get(key).and_then(|value| value.as_f64());
+
+        Some(Sample {
+            device: snapshot.device_identifier.clone(),
+            evaluated_at: snapshot.evaluated_at,
+            indoor_temperature: number("device_sensor_temperature"),
+            outdoor_temperature: number("outdoor_temperature"),
+            is_on: inputs.get("active_command.is_on").and_then(|value| value.as_bool()).unwrap_or(false),
+            mode: number("active_command.mode").map_or(0, |mode| mode as i32),
+            is_powerful: inputs.get("active_command.is_powerful").and_then(|value| value.as_bool()).unwrap_or(false),
+            net_power_watt: number("net_power_watt"),
+            solar_watt: number("raw_solar_watt"),
+        })
+    }
+}
+
+/// Seconds of [hour, hour + 1h) that each sample stands for
+/// `samples` must be sorted by time; each sample lasts until the next one, capped at `MAX_SAMPLE_GAP_SECS`
+fn covered_seconds<'a>(samples: &[&'a Sample], hour: i64) -> Vec<(&'a Sample, i64)> {
+    let hour_end = hour + HOUR_SECS;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let mut until = sample.evaluated_at + MAX_SAMPLE_GAP_SECS;
+            if let Some(next) = samples.get(i + 1) {
+                until = until.min(next.evaluated_at);
+            }
+            let seconds = until.min(hour_end) - sample.evaluated_at.max(hour);
+            (*sample, seconds.max(0))
+        })
+        .collect()
+}
+
+fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0u32), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / f64::from(count))
+}
+
+/// Compute the statistics of the hour starting at `hour`
+/// `samples` are sorted by time and include the samples of the `MAX_SAMPLE_GAP_SECS` before the hour,
+/// since those still cover its first minutes
+fn rollup_hour(hour: i64, samples: &[Sample], actions: &[(String, i64)]) -> (Vec<DeviceStats>, Option<EnergyStats>) {
+    let hour_end = hour + HOUR_SECS;
+
+    let mut by_device: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        by_device.entry(sample.device.as_str()).or_default().push(sample);
+    }
+    for (device, _) in actions {
+        by_device.entry(device.as_str()).or_default();
+    }
+
+    let devices = by_device
+        .into_iter()
+        .filter_map(|(device, device_samples)| {
+            let in_hour: Vec<&Sample> = device_samples
+                .iter()
+                .copied()
+                .filter(|sample| (hour..hour_end).contains(&sample.evaluated_at))
+                .collect();
+            let runtime_secs: i64 = covered_seconds(&device_samples, hour)
+                .into_iter()
+                .filter(|(sample, _)| sample.is_on)
+                .map(|(_, seconds)| seconds)
+                .sum();
+            let device_actions = actions
+                .iter()
+                .find(|(action_device, _)| action_device == device)
+                .map_or(0, |(_, count)| *count);
+
+            if in_hour.is_empty() && runtime_secs == 0 && device_actions == 0 {
+                return None;
+            }
+            Some(DeviceStats {
+                period_start: hour,
+                device_identifier: device.to_string(),
+                samples: in_hour.len() as i64,
+                avg_indoor_temperature: average(in_hour.iter().filter_map(|sample| sample.indoor_temperature)),
+                avg_outdoor_temperature: average(in_hour.iter().filter_map(|sample| sample.outdoor_temperature)),
+                runtime_minutes: runtime_secs as f64 / 60.0,
+                actions: device_actions,
+            })
+        })
+        .collect();
+
+    // The meter measures the whole household, so every device's cycles read the same meter
+    let meter_samples: Vec<&Sample> = samples.iter().filter(|sample| sample.net_power_watt.is_some()).collect();
+    let mut covered_secs = 0;
+    let (mut import_ws, mut export_ws, mut solar_ws) = (0.0, 0.0, 0.0);
+    for (sample, seconds) in covered_seconds(&meter_samples, hour) {
+        let net = sample.net_power_watt.unwrap_or(0.0);
+        let seconds_f = seconds as f64;
+        covered_secs += seconds;
+        import_ws += net.max(0.0) * seconds_f;
+        export_ws += (-net).max(0.0) * seconds_f;
+        solar_ws += sample.solar_watt.unwrap_or(0.0).max(0.0) * seconds_f;
+    }
+    let energy = (covered_secs > 0).then(|| EnergyStats {
+        period_start: hour,
+        covered_minutes: covered_secs as f64 / 60.0,
+        grid_import_kwh: import_ws / 3_600_000.0,
+        grid_export_kwh: export_ws / 3_600_000.0,
+        solar_kwh: solar_ws / 3_600_000.0,
+    });
+
+    (devices, energy)
+}
+
+/// Roll up every complete hour since the last rollup
+/// Returns the number of hours rolled up
+pub async fn run_rollups(pool: &DbPool, config: &Config, now: i64) -> Result<u32, sqlx::Error> {
+    let mut hour = match db::statistics::get_rolled_up_until(pool).await? {
+        Some(until) => until,
+        None => match db::statistics::get_oldest_snapshot_time(pool).await? {
+            Some(oldest) => hour_start(oldest),
+            None => return Ok(0),
+        },
+    };
+
+    let mut rolled_up = 0;
+    while hour + HOUR_SECS <= hour_start(now) {
+        let samples: Vec<Sample> =
+            db::statistics::get_snapshots_between(pool, hour - MAX_SAMPLE_GAP_SECS, hour + HOUR_SECS)
+                .await?
+                .iter()
+                .filter_map(Sample::from_snapshot)
+                .collect();
+        let actions = db::statistics::count_actions_between(pool, hour, hour + HOUR_SECS).await?;
+
+        let commands =
+            db::statistics::get_command_causes_between(pool, hour - MAX_SAMPLE_GAP_SECS, hour + HOUR_SECS).await?;
+
+        let (devices, energy) = rollup_hour(hour, &samples, &actions);
+        let device_energy = energy::estimate_hour(hour, &samples, &commands, config, energy.as_ref());
+        db::statistics::store_hour(pool, hour, &devices, energy.as_ref(), &device_energy).await?;
+        db::statistics::rollup_day(pool, day_start(hour)).await?;
+
+        hour += HOUR_SECS;
+        db::statistics::set_rolled_up_until(pool, hour).await?;
+        rolled_up += 1;
+    }
+
+    Ok(rolled_up)
+}
+
+/// Roll up complete hours into the statistics tables every few minutes
+pub async fn rollup_loop() {
+    loop {
+        let pool = db::get_pool().await;
+        match run_rollups(pool, &crate::config::get_config(), Utc::now().timestamp()).await {
+            Ok(0) => log::debug!("Statistics up to date"),
+            Ok(hours) => log::info!("Rolled up {} hours of statistics", hours),
+            Err(e) => log::warn!("Statistics rollup failed: {}", e),
+        }
+
+        tokio::time::sleep(Duration::from_secs(ROLLUP_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::AppState;
+
+    /// 2025-11-24T00:00:00Z
+    const DAY_START: i64 = 1763942400;
+
+    fn sample(device: &str, evaluated_at: i64, is_on: bool, net_power_watt: f64) -> Sample {
+        Sample {
+            device: device.to_string(),
+            evaluated_at,
+            indoor_temperature: Some(20.0),
+            outdoor_temperature: Some(5.0),
+            is_on,
+            mode: 4,
+            is_powerful: false,
+            net_power_watt: Some(net_power_watt),
+            solar_watt: Some(0.0),
+        }
+    }
+
+    #[test]
+    fn test_day_start() {
+        assert_eq!(day_start(DAY_START), DAY_START);
+        assert_eq!(day_start(DAY_START + DAY_SECS - 1), DAY_START);
+        assert_eq!(day_start(DAY_START + DAY_SECS), DAY_START + DAY_SECS);
+    }
+
+    #[test]
+    fn test_rollup_hour_runtime_and_averages() {
+        let hour = DAY_START + HOUR_SECS;
+        let mut samples: Vec<Sample> = (0..12)
+            .map(|i| sample("Veranda", hour - 300 + i * 300, i >= 6, 1000.0))
+            .collect();
+        samples[1].indoor_temperature = Some(22.0);
+        samples[2].indoor_temperature = None;
+
+        let (devices, energy) = rollup_hour(hour, &samples, &[("Veranda".to_string(), 2)]);
+        assert_eq!(devices.len(), 1);
+        let stats = &devices[0];
+        // The sample before the hour only covers it through runtime and energy
+        assert_eq!(stats.samples, 11);
+        assert_eq!(stats.avg_indoor_temperature, Some(20.2));
+        assert_eq!(stats.avg_outdoor_temperature, Some(5.0));
+        // On from 25 minutes into the hour, the last sample lasts until the hour ends
+        assert_eq!(stats.runtime_minutes, 35.0);
+        assert_eq!(stats.actions, 2);
+
+        let energy = energy.unwrap();
+        assert_eq!(energy.covered_minutes, 60.0);
+        assert_eq!(energy.grid_import_kwh, 1.0);
+        assert_eq!(energy.grid_export_kwh, 0.0);
+    }
+
+    #[test]
+    fn test_rollup_hour_energy_gaps_and_export() {
+        let hour = DAY_START;
+        let samples = vec![
+            sample("LivingRoom", hour, false, -3000.0),
+            sample("Veranda", hour + 300, false, 500.0),
+            // Controller down after this one, counts for MAX_SAMPLE_GAP_SECS only
+            sample("LivingRoom", hour + 600, false, 500.0),
+        ];
+
+        let (_, energy) = rollup_hour(hour, &samples, &[]);
+        let energy = energy.unwrap();
+        assert_eq!(energy.covered_minutes, 20.0);
+        assert_eq!(energy.grid_export_kwh, 0.25);
+        assert_eq!(energy.grid_import_kwh, 0.125);
+
+        let (devices, energy) = rollup_hour(hour, &[], &[]);
+        assert!(devices.is_empty());
+        assert_eq!(energy, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_rollups_stores_hours_and_days() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+        let inputs = r#"{"device_sensor_temperature":21.0,"outdoor_temperature":4.0,"active_command.is_on":true,"net_power_watt":600,"raw_solar_watt":0}"#;
+        for i in 0..24 {
+            db::cycle_snapshots::insert(pool, "Veranda", DAY_START + i * 300, inputs, "Do Nothing").await.unwrap();
+        }
+
+        sqlx::query("INSERT INTO ac_actions (action_timestamp, device_identifier, action_type, cause_id) VALUES ($1, 'Veranda', 'on', 3)")
+            .bind(DAY_START - 3600)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        // Only the first hour is complete
+        let now = DAY_START + HOUR_SECS + 1800;
+        assert_eq!(run_rollups(pool, &state.config(), now).await.unwrap(), 1);
+        assert_eq!(run_rollups(pool, &state.config(), now).await.unwrap(), 0);
+
+        let hourly = db::statistics::get_device_stats(pool, db::statistics::Granularity::Hourly, DAY_START, now, None)
+            .await
+            .unwrap();
+        assert_eq!(hourly.len(), 1);
+        assert_eq!(hourly[0].samples, 12);
+        assert_eq!(hourly[0].runtime_minutes, 60.0);
+
+        assert_eq!(run_rollups(pool, &state.config(), DAY_START + 3 * HOUR_SECS).await.unwrap(), 2);
+        let daily =
+            db::statistics::get_device_stats(pool, db::statistics::Granularity::Daily, DAY_START, DAY_START, Some("Veranda"))
+                .await
+                .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].samples, 24);
+        assert_eq!(daily[0].avg_indoor_temperature, Some(21.0));
+        // The last snapshot still covers the first five minutes of the third hour
+        assert_eq!(daily[0].runtime_minutes, 125.0);
+
+        let energy = db::statistics::get_energy_stats(pool, db::statistics::Granularity::Daily, DAY_START, DAY_START)
+            .await
+            .unwrap();
+        assert_eq!(energy.len(), 1);
+        assert_eq!(energy[0].covered_minutes, 125.0);
+        assert!((energy[0].grid_import_kwh - 1.25).abs() < 1e-9);
+
+        // Estimated at the default 1000 W; the mode isn't part of these snapshots
+        let device_energy = db::statistics::get_energy_breakdown(pool, DAY_START, now + 2 * HOUR_SECS, None).await.unwrap();
+        assert_eq!(device_energy.len(), 1);
+        assert_eq!(device_energy[0].cause_id, 3);
+        assert_eq!(device_energy[0].mode, "Unknown");
+        assert_eq!(device_energy[0].runtime_minutes, 125.0);
+        assert!((device_energy[0].estimated_kwh - 125.0 / 60.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,149 @@
+//! Anonymous telemetry
+//!
+//! Strictly opt-in. When `telemetry_enabled` is set and a `telemetry_endpoint` is configured,
+//! a small report with aggregate stats is sent once a day. Nothing identifying is included:
+//! the installation ID is random and no device names, temperatures or locations are sent.
+//! `GET /api/telemetry/preview` shows exactly what would be sent.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::{ac_controller::devices, config, db};
+
+/// How often a report is sent
+const REPORT_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// Device executions since the last successful report
+static CYCLE_COUNT: AtomicU64 = AtomicU64::new(0);
+/// Failed device executions since the last successful report
+static CYCLE_ERROR_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// The exact payload sent to the telemetry endpoint
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct TelemetryReport {
+    /// Random ID generated on first startup
+    pub installation_id: String,
+    pub version: String,
+    pub device_count: usize,
+    pub cycles: u64,
+    pub cycle_errors: u64,
+    /// Fraction of device executions that failed (0.0 - 1.0)
+    pub cycle_error_rate: f64,
+}
+
+/// Record the outcome of one device execution in a control cycle
+pub fn record_cycle(is_error: bool) {
+    CYCLE_COUNT.fetch_add(1, Ordering::Relaxed);
+    if is_error {
+        CYCLE_ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Compute the error rate, rounded to 3 decimals
+fn error_rate(cycles: u64, errors: u64) -> f64 {
+    if cycles == 0 {
+        return 0.0;
+    }
+    ((errors as f64 / cycles as f64) * 1000.0).round() / 1000.0
+}
+
+/// Get the persistent installation ID
+async fn get_installation_id() -> Result<String, sqlx::Error> {
+    let pool = db::get_pool().await;
+    let result = sqlx::query_as::<_, (String,)>(
+        "SELECT setting_value FROM settings WHERE setting_key = 'installation_id'"
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(result.map(|(id,)| id).unwrap_or_default())
+}
+
+/// Build the report from the current counters
+pub async fn build_report() -> Result<TelemetryReport, sqlx::Error> {
+    let cycles = CYCLE_COUNT.load(Ordering::Relaxed);
+    let cycle_errors = CYCLE_ERROR_COUNT.load(Ordering::Relaxed);
+
+    Ok(TelemetryReport {
+        installation_id: get_installation_id().await?,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        device_count: devices::get_device_registry().all().len(),
+        cycles,
+        cycle_errors,
+        cycle_error_rate: error_rate(cycles, cycle_errors),
+    })
+}
+
+/// Check if telemetry is enabled and has somewhere to report to
+pub fn is_enabled() -> bool {
+    let cfg = config::get_config();
+    cfg.telemetry_enabled && !cfg.telemetry_endpoint.is_empty()
+}
+
+/// Send one report and reset the counters on success
+async fn send_report() -> Result<(), String> {
+    let report = build_report().await.map_err(|e| e.to_string())?;
+    let endpoint = &config::get_config().telemetry_endpoint;
+
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .json(&report)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("Telemetry endpoint returned {}", response.status()));
+    }
+
+    CYCLE_COUNT.fetch_sub(report.cycles, Ordering::Relaxed);
+    CYCLE_ERROR_COUNT.fetch_sub(report.cycle_errors, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Send a report once a day while telemetry is enabled
+pub async fn telemetry_loop() {
+    if !is_enabled() {
+        log::info!("Telemetry disabled");
+        return;
+    }
+
+    log::info!("Telemetry enabled, reporting daily to {}", config::get_config().telemetry_endpoint);
+    loop {
+        tokio::time::sleep(Duration::from_secs(REPORT_INTERVAL_SECS)).await;
+        match send_report().await {
+            Ok(()) => log::debug!("Telemetry report sent"),
+            Err(e) => log::warn!("Failed to send telemetry report: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_rate() {
+        assert_eq!(error_rate(0, 0), 0.0);
+        assert_eq!(error_rate(10, 0), 0.0);
+        assert_eq!(error_rate(10, 1), 0.1);
+        assert_eq!(error_rate(3, 1), 0.333);
+    }
+
+    #[test]
+    fn test_report_serialization_has_no_extra_fields() {
+        let report = TelemetryReport {
+            installation_id: "abc".to_string(),
+            version: "0.1.0".to_string(),
+            device_count: 2,
+            cycles: 10,
+            cycle_errors: 1,
+            cycle_error_rate: 0.1,
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(keys.len(), 6);
+    }
+}
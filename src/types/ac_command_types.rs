@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use power_control_center_core::ac_state::{ac_mode_from_name, ac_mode_name, vane_from_name, vane_name, AcState};
+use power_control_center_core::types::Celsius;
+
+use super::AcAction;
+
+/// A command sent to an AC device
+/// Shared by the `/api/ac` handlers and the node executor, so both encode commands and track
+/// the resulting state the same way. Powerful mode is toggled separately.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AcCommand {
+    Off,
+    On {
+        /// AC mode constant (`AC_MODE_*`), never Off
+        mode: i32,
+        fan_speed: i32,
+        temperature: Celsius,
+        swing: i32,
+        /// Vane positions (`VANE_*`), None leaves the vane to the controller
+        vertical_vane: Option<i32>,
+        horizontal_vane: Option<i32>,
+    },
+}
+
+impl AcCommand {
+    /// Command that brings a device to a state
+    /// Returns an error naming the missing parameter if an on state is incomplete
+    pub fn from_state(state: &AcState) -> Result<Self, String> {
+        if !state.is_on {
+            return Ok(AcCommand::Off);
+        }
+        Ok(AcCommand::On {
+            mode: state.mode.ok_or("Mode not set when AC is on")?,
+            fan_speed: state.fan_speed.ok_or("Fan speed not set when AC is on")?,
+            temperature: state.temperature.ok_or("Temperature not set when AC is on")?,
+            swing: state.swing.ok_or("Swing not set when AC is on")?,
+            vertical_vane: state.vertical_vane,
+            horizontal_vane: state.horizontal_vane,
+        })
+    }
+
+    /// Command recorded in the AC action history, None for other actions or missing parameters
+    pub fn from_action(action: &AcAction) -> Option<Self> {
+        match action.action_type.as_str() {
+            "off" => Some(AcCommand::Off),
+            "on" => Some(AcCommand::On {
+                mode: action.mode?,
+                fan_speed: action.fan_speed?,
+                temperature: Celsius(f64::from(action.request_temperature?)),
+                swing: action.swing?,
+                vertical_vane: action.vertical_vane,
+                horizontal_vane: action.horizontal_vane,
+            }),
+            _ => None,
+        }
+    }
+
+    /// State of the device after the command, with powerful mode off
+    pub fn to_state(&self) -> AcState {
+        match *self {
+            AcCommand::Off => AcState::new_off(),
+            AcCommand::On {
+                mode,
+                fan_speed,
+                temperature,
+                swing,
+                vertical_vane,
+                horizontal_vane,
+            } => AcState::new_on(mode, fan_speed, temperature, swing, false).with_vanes(vertical_vane, horizontal_vane),
+        }
+    }
+}
+
+/// An AC command as sent to and returned by the API, with mode and fan speed by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AcCommandRequest {
+    /// "Off", "Heat", "Cool", "Dry" or "Fan"
+    pub mode: String,
+    /// Required unless the mode is "Off"
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// "Auto", "High", "Medium", "Low" or "Quiet"
+    #[serde(default = "default_fan_speed_name")]
+    pub fan_speed: String,
+    #[serde(default)]
+    pub swing: bool,
+    /// Vane position names ("Auto", "Position 1" to "Position 5" or "Swing"), omitted to leave them to the controller
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertical_vane: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub horizontal_vane: Option<String>,
+}
+
+fn default_fan_speed_name() -> String {
+    "Auto".to_string()
+}
+
+impl AcCommandRequest {
+    /// Parse the request into a command, or a message explaining why it is invalid
+    pub fn to_command(&self) -> Result<AcCommand, String> {
+        if self.mode == "Off" {
+            return Ok(AcCommand::Off);
+        }
+        let mode = ac_mode_from_name(&self.mode)
+            .ok_or_else(|| format!("Unknown mode '{}', expected Off, Heat, Cool, Dry or Fan", self.mode))?;
+        let fan_speed = fan_speed_from_name(&self.fan_speed)
+            .ok_or_else(|| format!("Unknown fan speed '{}', expected Auto, High, Medium, Low or Quiet", self.fan_speed))?;
+        let temperature = match self.temperature {
+            Some(t) if t.is_finite() => Celsius(t),
+            _ => return Err(format!("A temperature is required for mode {}", self.mode)),
+        };
+        let vane = |name: &Option<String>| match name {
+            Some(name) => vane_from_name(name)
+                .map(Some)
+                .ok_or_else(|| format!("Unknown vane position '{}', expected Auto, Position 1 to Position 5 or Swing", name)),
+            None => Ok(None),
+        };
+        Ok(AcCommand::On {
+            mode,
+            fan_speed,
+            temperature,
+            swing: i32::from(self.swing),
+            vertical_vane: vane(&self.vertical_vane)?,
+            horizontal_vane: vane(&self.horizontal_vane)?,
+        })
+    }
+}
+
+impl From<&AcCommand> for AcCommandRequest {
+    fn from(command: &AcCommand) -> Self {
+        match *command {
+            AcCommand::Off => AcCommandRequest {
+                mode: "Off".to_string(),
+                temperature: None,
+                fan_speed: default_fan_speed_name(),
+                swing: false,
+                vertical_vane: None,
+                horizontal_vane: None,
+            },
+            AcCommand::On {
+                mode,
+                fan_speed,
+                temperature,
+                swing,
+                vertical_vane,
+                horizontal_vane,
+            } => AcCommandRequest {
+                mode: ac_mode_name(mode).unwrap_or("Off").to_string(),
+                temperature: Some(temperature.0),
+                fan_speed: fan_speed_name(fan_speed).to_string(),
+                swing: swing != 0,
+                vertical_vane: vertical_vane.and_then(vane_name).map(str::to_string),
+                horizontal_vane: horizontal_vane.and_then(vane_name).map(str::to_string),
+            },
+        }
+    }
+}
+
+/// Fan speed constant for a fan speed name ("Auto", "High", "Medium", "Low" or "Quiet")
+pub fn fan_speed_from_name(fan_speed: &str) -> Option<i32> {
+    match fan_speed {
+        "Auto" => Some(0),
+        "High" => Some(1),
+        "Medium" => Some(2),
+        "Low" => Some(3),
+        "Quiet" => Some(4),
+        _ => None,
+    }
+}
+
+/// Fan speed name for a fan speed constant, the inverse of `fan_speed_from_name`
+/// Unknown speeds are reported as "Auto"
+pub fn fan_speed_name(fan_speed: i32) -> &'static str {
+    match fan_speed {
+        1 => "High",
+        2 => "Medium",
+        3 => "Low",
+        4 => "Quiet",
+        _ => "Auto",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use power_control_center_core::ac_state::{AC_MODE_COOL, AC_MODE_HEAT};
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = AcCommandRequest {
+            mode: "Cool".to_string(),
+            temperature: Some(23.5),
+            fan_speed: "High".to_string(),
+            swing: true,
+            vertical_vane: Some("Position 5".to_string()),
+            horizontal_vane: None,
+        };
+        let command = request.to_command().unwrap();
+        assert_eq!(
+            command,
+            AcCommand::On {
+                mode: AC_MODE_COOL,
+                fan_speed: 1,
+                temperature: Celsius(23.5),
+                swing: 1,
+                vertical_vane: Some(5),
+                horizontal_vane: None,
+            }
+        );
+        assert_eq!(AcCommandRequest::from(&command), request);
+        assert_eq!(AcCommand::from_state(&command.to_state()), Ok(command));
+
+        let off: AcCommandRequest = serde_json::from_str(r#"{"mode": "Off"}"#).unwrap();
+        assert_eq!(off.to_command(), Ok(AcCommand::Off));
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let request = |json: &str| serde_json::from_str::<AcCommandRequest>(json).unwrap().to_command();
+        assert!(request(r#"{"mode": "Heat"}"#).is_err(), "temperature is required when on");
+        assert!(request(r#"{"mode": "Turbo", "temperature": 21}"#).is_err());
+        assert!(request(r#"{"mode": "Heat", "temperature": 21, "fan_speed": "Max"}"#).is_err());
+        assert!(request(r#"{"mode": "Heat", "temperature": 21, "vertical_vane": "Up"}"#).is_err());
+
+        let mut incomplete = AcState::new_on(AC_MODE_HEAT, 0, Celsius(21.0), 0, false);
+        incomplete.swing = None;
+        assert!(AcCommand::from_state(&incomplete).is_err());
+    }
+}
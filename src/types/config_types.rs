@@ -1,11 +1,25 @@
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Deserialize)]
+use power_control_center_core::{ac_state::vane_from_name, types::AcChangePolicy};
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub database_path: String,
+    /// PostgreSQL connection URL, used instead of `database_path` by builds with the `postgres` feature
+    #[serde(default)]
+    pub database_url: String,
     pub listen_address: String,
     pub listen_port: u16,
+    /// Port of the HTTPS listener, only used when TLS is configured
+    #[serde(default = "default_tls_listen_port")]
+    pub tls_listen_port: u16,
+    /// PEM certificate chain for HTTPS (TLS is disabled unless both paths are set)
+    #[serde(default)]
+    pub tls_cert_path: String,
+    /// PEM private key for HTTPS
+    #[serde(default)]
+    pub tls_key_path: String,
     pub smart_meter_api_endpoint: String,
     pub ac_controller_endpoints: HashMap<String, AcControllerEndpointProperties>,
     pub latitude: f64,
@@ -14,6 +28,149 @@ pub struct Config {
     pub pir_api_key: String,
     #[serde(default = "default_pir_timeout_minutes")]
     pub pir_timeout_minutes: u32,
+    /// Named PIR sensors by sensor ID, with their zone and the device they keep off
+    /// Sensors that aren't configured are registered on their first detection, without a zone
+    #[serde(default)]
+    pub pir_sensors: HashMap<String, PirSensorConfig>,
+    /// Window and door contact sensors by name, with the device whose room they are in
+    /// Their state is reported through `POST /api/sensors/contact/{name}`
+    #[serde(default)]
+    pub contact_sensors: HashMap<String, ContactSensorConfig>,
+    /// Tolerance used for float equality in nodes (Equals, Evaluate Number)
+    #[serde(default = "default_float_tolerance")]
+    pub float_tolerance: f64,
+    /// Step that target temperatures are rounded to before sending commands (0 disables rounding)
+    #[serde(default = "default_temperature_rounding_step")]
+    pub temperature_rounding_step: f64,
+    /// API key for UPS status signals (empty means no auth)
+    #[serde(default)]
+    pub ups_api_key: String,
+    /// Turn off all ACs while a grid outage is detected and the UPS reports running on battery
+    #[serde(default)]
+    pub shed_load_on_grid_outage: bool,
+    /// API key for demand-response events from the utility aggregator (empty means no auth)
+    #[serde(default)]
+    pub demand_response_api_key: String,
+    /// API key for the JSON-RPC and wall-panel adjust endpoints used by local automations (empty means no auth)
+    #[serde(default)]
+    pub rpc_api_key: String,
+    /// Lengthen polling intervals while all devices are off and the user is away
+    #[serde(default)]
+    pub deep_idle_enabled: bool,
+    /// Factor by which polling intervals are multiplied while in deep idle
+    #[serde(default = "default_deep_idle_interval_multiplier")]
+    pub deep_idle_interval_multiplier: u32,
+    /// Opt in to sending anonymous aggregate stats
+    #[serde(default)]
+    pub telemetry_enabled: bool,
+    /// URL that telemetry reports are posted to (nothing is sent if empty)
+    #[serde(default)]
+    pub telemetry_endpoint: String,
+    /// Periodically check GitHub for a newer release
+    #[serde(default)]
+    pub update_check_enabled: bool,
+    /// Allow installing updates through POST /api/admin/self-update
+    #[serde(default)]
+    pub self_update_enabled: bool,
+    /// API key for admin endpoints (admin endpoints are disabled if empty)
+    #[serde(default)]
+    pub admin_api_key: String,
+    /// Maximum request body size in bytes for nodeset routes
+    #[serde(default = "default_nodeset_max_body_bytes")]
+    pub nodeset_max_body_bytes: usize,
+    /// Maximum request body size in bytes for the other API routes
+    #[serde(default = "default_api_max_body_bytes")]
+    pub api_max_body_bytes: usize,
+    /// API requests each client IP may make per minute (0 disables the limit)
+    #[serde(default = "default_api_rate_limit_per_minute")]
+    pub api_rate_limit_per_minute: u32,
+    /// Saved revisions kept per nodeset for restoring earlier versions
+    #[serde(default = "default_nodeset_revisions_kept")]
+    pub nodeset_revisions_kept: u32,
+    /// Allow activating the built-in default profile
+    #[serde(default = "default_true")]
+    pub default_nodeset_enabled: bool,
+    /// Indoor temperature changes faster than this (°C per minute) are treated as sensor anomalies (0 disables)
+    #[serde(default = "default_sensor_max_rate_c_per_minute")]
+    pub sensor_max_rate_c_per_minute: f64,
+    /// Number of recent indoor temperature readings to take the median of (1 disables filtering)
+    #[serde(default = "default_sensor_median_window")]
+    pub sensor_median_window: usize,
+    /// Days of outdoor temperature history to backfill from the weather archive (0 disables)
+    #[serde(default = "default_weather_backfill_days")]
+    pub weather_backfill_days: u32,
+    /// Endpoints whose rolling p95 command latency exceeds this (ms) are reported on /api/status/details (0 disables)
+    #[serde(default = "default_command_latency_slo_ms")]
+    pub command_latency_slo_ms: u64,
+    /// Webhook URL per notification channel name, used by Notify nodes
+    #[serde(default)]
+    pub notification_channels: HashMap<String, String>,
+    /// Day-ahead price bidding zone for the Energy Price node, e.g. "NL" or "DE-LU" (empty disables)
+    #[serde(default)]
+    pub energy_price_bidding_zone: String,
+    /// URL returning the home battery's state of charge and power for the Battery node (empty disables)
+    #[serde(default)]
+    pub battery_api_endpoint: String,
+    /// Reject profiles that select unknown cause reasons and record a warning when one is used at runtime
+    #[serde(default)]
+    pub strict_cause_reasons: bool,
+    /// Days of outdoor temperature history averaged to classify the season
+    #[serde(default = "default_season_window_days")]
+    pub season_window_days: u32,
+    /// Rolling average outdoor temperatures below this (°C) are the heating season
+    #[serde(default = "default_heating_season_below_c")]
+    pub heating_season_below_c: f64,
+    /// Rolling average outdoor temperatures above this (°C) are the cooling season
+    #[serde(default = "default_cooling_season_above_c")]
+    pub cooling_season_above_c: f64,
+    /// Minutes a device must stay off before it may be turned on again (0 disables)
+    #[serde(default = "default_min_off_time_minutes")]
+    pub min_off_time_minutes: u32,
+    /// Per-person presence detection through MQTT and/or Home Assistant (disabled unless configured)
+    #[serde(default)]
+    pub presence: PresenceConfig,
+    /// Most power in watts all AC devices may draw together, 0 disables the budget
+    #[serde(default)]
+    pub power_budget_watt: u32,
+    /// Minutes a stored device state stays valid across a restart, older states are re-synced
+    #[serde(default = "default_device_state_max_age_minutes")]
+    pub device_state_max_age_minutes: u32,
+    /// Seconds a device may take in a control cycle before its evaluation is abandoned, at least 1
+    #[serde(default = "default_device_cycle_timeout_seconds", deserialize_with = "deserialize_cycle_timeout")]
+    pub device_cycle_timeout_seconds: u64,
+    /// Briefly heat at night ahead of a forecast cold snap (disabled by default)
+    #[serde(default)]
+    pub cold_snap_preheat: ColdSnapPreheatConfig,
+    /// Signed webhook that turns away mode on or off (disabled unless keys are configured)
+    #[serde(default)]
+    pub away_webhook: AwayWebhookConfig,
+    /// PV panels the Solar Forecast node predicts the production of (disabled while kwp is 0)
+    #[serde(default)]
+    pub solar_panels: SolarPanelsConfig,
+    /// What is sent to the ACs when the service stops
+    #[serde(default)]
+    pub shutdown_ac_state: ShutdownAcState,
+    /// Scale hourly AC energy estimates down to the consumption measured by the smart meter
+    #[serde(default)]
+    pub energy_meter_correction: bool,
+    /// Alerts when an AC controller is unreachable, see `device_requests::health`
+    #[serde(default)]
+    pub health_alerts: HealthAlertConfig,
+    /// Read-back of the unit's state after a command, for devices with `supports_state_readback`
+    #[serde(default)]
+    pub command_verification: CommandVerificationConfig,
+    /// How PIR and door sensor events are turned into an occupancy probability, see `ac_controller::occupancy`
+    #[serde(default)]
+    pub occupancy: OccupancyConfig,
+    /// MQTT bridge publishing the state and taking commands, e.g. for Home Assistant, see `mqtt_bridge`
+    #[serde(default)]
+    pub mqtt_bridge: MqttBridgeConfig,
+    /// Nightly database snapshots, see `db::backup`
+    #[serde(default)]
+    pub backup: BackupConfig,
+    /// How long history is kept before the daily maintenance deletes it, see `maintenance`
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 fn default_pir_api_key() -> String {
@@ -24,8 +181,725 @@ fn default_pir_timeout_minutes() -> u32 {
     5
 }
 
-#[derive(Debug, Deserialize)]
+fn default_float_tolerance() -> f64 {
+    0.0001
+}
+
+fn default_temperature_rounding_step() -> f64 {
+    0.5
+}
+
+fn default_deep_idle_interval_multiplier() -> u32 {
+    6
+}
+
+fn default_tls_listen_port() -> u16 {
+    9443
+}
+
+fn default_nodeset_max_body_bytes() -> usize {
+    8 * 1024 * 1024
+}
+
+fn default_api_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_api_rate_limit_per_minute() -> u32 {
+    600
+}
+
+fn default_nodeset_revisions_kept() -> u32 {
+    20
+}
+
+fn default_sensor_max_rate_c_per_minute() -> f64 {
+    0.5
+}
+
+fn default_sensor_median_window() -> usize {
+    3
+}
+
+fn default_weather_backfill_days() -> u32 {
+    30
+}
+
+fn default_command_latency_slo_ms() -> u64 {
+    2000
+}
+
+fn default_season_window_days() -> u32 {
+    7
+}
+
+fn default_heating_season_below_c() -> f64 {
+    17.0
+}
+
+fn default_cooling_season_above_c() -> f64 {
+    22.0
+}
+
+fn default_min_off_time_minutes() -> u32 {
+    3
+}
+
+fn default_device_state_max_age_minutes() -> u32 {
+    60
+}
+
+fn default_device_cycle_timeout_seconds() -> u64 {
+    90
+}
+
+/// A timeout of 0 would abandon every device on every cycle
+fn deserialize_cycle_timeout<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let seconds = u64::deserialize(deserializer)?;
+    if seconds == 0 {
+        return Err(serde::de::Error::custom("device_cycle_timeout_seconds must be at least 1"));
+    }
+    Ok(seconds)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Sources that report who is home, see `presence`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PresenceConfig {
+    /// MQTT broker host (empty disables MQTT presence)
+    #[serde(default)]
+    pub mqtt_host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub mqtt_port: u16,
+    #[serde(default)]
+    pub mqtt_username: String,
+    #[serde(default)]
+    pub mqtt_password: String,
+    /// Topic filter to subscribe to, the last topic level is the person's name
+    #[serde(default = "default_mqtt_presence_topic")]
+    pub mqtt_topic: String,
+    /// Home Assistant base URL, e.g. "http://homeassistant.local:8123" (empty disables polling)
+    #[serde(default)]
+    pub home_assistant_url: String,
+    /// Long-lived access token for Home Assistant
+    #[serde(default)]
+    pub home_assistant_token: String,
+    /// Person or device tracker entities to poll, e.g. "person.alex"
+    #[serde(default)]
+    pub home_assistant_entities: Vec<String>,
+    /// Seconds between Home Assistant polls
+    #[serde(default = "default_presence_poll_interval_seconds")]
+    pub poll_interval_seconds: u64,
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            mqtt_host: String::new(),
+            mqtt_port: default_mqtt_port(),
+            mqtt_username: String::new(),
+            mqtt_password: String::new(),
+            mqtt_topic: default_mqtt_presence_topic(),
+            home_assistant_url: String::new(),
+            home_assistant_token: String::new(),
+            home_assistant_entities: Vec::new(),
+            poll_interval_seconds: default_presence_poll_interval_seconds(),
+        }
+    }
+}
+
+/// Broker and topics of the MQTT bridge, see `mqtt_bridge`
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttBridgeConfig {
+    /// MQTT broker host (empty disables the bridge)
+    #[serde(default)]
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// First level of every published and subscribed topic
+    #[serde(default = "default_mqtt_bridge_topic_prefix")]
+    pub topic_prefix: String,
+    /// Seconds between publishes of the sensor readings
+    #[serde(default = "default_mqtt_bridge_sensor_interval_seconds")]
+    pub sensor_interval_seconds: u64,
+}
+
+impl Default for MqttBridgeConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: default_mqtt_port(),
+            username: String::new(),
+            password: String::new(),
+            topic_prefix: default_mqtt_bridge_topic_prefix(),
+            sensor_interval_seconds: default_mqtt_bridge_sensor_interval_seconds(),
+        }
+    }
+}
+
+fn default_mqtt_bridge_topic_prefix() -> String {
+    "power_control_center".to_string()
+}
+
+fn default_mqtt_bridge_sensor_interval_seconds() -> u64 {
+    60
+}
+
+/// Where and when the nightly database backups are written, see `backup`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupConfig {
+    /// Directory the backups are written to (empty disables nightly backups)
+    #[serde(default)]
+    pub directory: String,
+    /// Local hour (0-23) the backup is made at
+    #[serde(default = "default_backup_hour")]
+    pub hour: u32,
+    /// Newest backups kept in `directory`, older ones are deleted (0 keeps all)
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            directory: String::new(),
+            hour: default_backup_hour(),
+            keep: default_backup_keep(),
+        }
+    }
+}
+
+fn default_backup_hour() -> u32 {
+    3
+}
+
+fn default_backup_keep() -> usize {
+    7
+}
+
+/// Days of history kept per table, see `retention` (0 keeps everything)
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionConfig {
+    /// Commands sent to the ACs, the last one of each device is always kept
+    #[serde(default = "default_retention_ac_actions_days")]
+    pub ac_actions_days: u32,
+    /// Nodeset executions with their inputs
+    #[serde(default = "default_retention_execution_history_days")]
+    pub execution_history_days: u32,
+    /// Sensor history (hourly averages, raw samples and 5 minute averages are dropped sooner)
+    #[serde(default = "default_retention_sensor_history_days")]
+    pub sensor_history_days: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            ac_actions_days: default_retention_ac_actions_days(),
+            execution_history_days: default_retention_execution_history_days(),
+            sensor_history_days: default_retention_sensor_history_days(),
+        }
+    }
+}
+
+fn default_retention_ac_actions_days() -> u32 {
+    365
+}
+
+fn default_retention_execution_history_days() -> u32 {
+    30
+}
+
+fn default_retention_sensor_history_days() -> u32 {
+    365
+}
+
+/// Thresholds for heating ahead of a cold snap, see `cold_snap_preheat`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColdSnapPreheatConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Preheat when the lowest temperature forecast for the next 12 hours is below this
+    #[serde(default = "default_preheat_forecast_below_c")]
+    pub forecast_below_c: f64,
+    /// Only preheat while the current outdoor temperature is at least this, so the heat pump still runs efficiently
+    #[serde(default = "default_preheat_min_outdoor_c")]
+    pub min_outdoor_c: f64,
+    /// Local hour the night starts, preheating only starts at night
+    #[serde(default = "default_preheat_night_start_hour")]
+    pub night_start_hour: u32,
+    /// Local hour the night ends
+    #[serde(default = "default_preheat_night_end_hour")]
+    pub night_end_hour: u32,
+    /// Minutes the heat pump runs, the nodeset is skipped meanwhile
+    #[serde(default = "default_preheat_duration_minutes")]
+    pub duration_minutes: u32,
+    /// Heat setpoint in Celsius while preheating
+    #[serde(default = "default_preheat_temperature")]
+    pub temperature: f64,
+    /// Hours after a preheat starts before the device may preheat again
+    #[serde(default = "default_preheat_cooldown_hours")]
+    pub cooldown_hours: u32,
+}
+
+impl Default for ColdSnapPreheatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            forecast_below_c: default_preheat_forecast_below_c(),
+            min_outdoor_c: default_preheat_min_outdoor_c(),
+            night_start_hour: default_preheat_night_start_hour(),
+            night_end_hour: default_preheat_night_end_hour(),
+            duration_minutes: default_preheat_duration_minutes(),
+            temperature: default_preheat_temperature(),
+            cooldown_hours: default_preheat_cooldown_hours(),
+        }
+    }
+}
+
+fn default_preheat_forecast_below_c() -> f64 {
+    -8.0
+}
+
+fn default_preheat_min_outdoor_c() -> f64 {
+    -2.0
+}
+
+fn default_preheat_night_start_hour() -> u32 {
+    22
+}
+
+fn default_preheat_night_end_hour() -> u32 {
+    6
+}
+
+fn default_preheat_duration_minutes() -> u32 {
+    60
+}
+
+fn default_preheat_temperature() -> f64 {
+    22.0
+}
+
+fn default_preheat_cooldown_hours() -> u32 {
+    12
+}
+
+/// Keys and rate limit of the away webhook, see `away_webhook`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AwayWebhookConfig {
+    /// Shared secret per key ID, e.g. one per phone (empty disables the webhook)
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+    /// Requests each key may make per minute, including rejected ones
+    #[serde(default = "default_away_webhook_max_requests_per_minute")]
+    pub max_requests_per_minute: u32,
+}
+
+impl Default for AwayWebhookConfig {
+    fn default() -> Self {
+        Self {
+            keys: HashMap::new(),
+            max_requests_per_minute: default_away_webhook_max_requests_per_minute(),
+        }
+    }
+}
+
+fn default_away_webhook_max_requests_per_minute() -> u32 {
+    6
+}
+
+/// Size and orientation of the PV panels, see `solar_panels`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SolarPanelsConfig {
+    /// Peak power of all panels in kWp (0 disables the solar forecast)
+    #[serde(default)]
+    pub kwp: f64,
+    /// Compass direction the panels face in degrees, 90 is east, 180 south and 270 west
+    #[serde(default = "default_solar_azimuth")]
+    pub azimuth: f64,
+    /// Tilt of the panels in degrees, 0 is flat and 90 vertical
+    #[serde(default = "default_solar_tilt")]
+    pub tilt: f64,
+}
+
+impl Default for SolarPanelsConfig {
+    fn default() -> Self {
+        Self {
+            kwp: 0.0,
+            azimuth: default_solar_azimuth(),
+            tilt: default_solar_tilt(),
+        }
+    }
+}
+
+/// Zone and device of a named PIR sensor, see `pir_sensors`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PirSensorConfig {
+    /// Room or zone the sensor is in, e.g. "Downstairs"; a zone is triggered when any of its sensors is
+    #[serde(default)]
+    pub zone: Option<String>,
+    /// Device whose AC a detection turns off, like `POST /api/pir/detect` for that device
+    #[serde(default)]
+    pub device: Option<String>,
+}
+
+/// Room of a window or door contact sensor, see `contact_sensors`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContactSensorConfig {
+    /// Device whose room the window or door is in
+    pub device: String,
+}
+
+/// When and where unreachable AC controllers are alerted about, see `health_alerts`
+#[derive(Debug, Clone, Deserialize)]
+pub struct HealthAlertConfig {
+    /// Minutes an AC controller must be failing before an alert is sent
+    #[serde(default = "default_health_alert_after_minutes")]
+    pub after_minutes: u32,
+    /// Channels the alerts are sent to (empty only logs them)
+    #[serde(default)]
+    pub channels: Vec<AlertChannel>,
+}
+
+impl Default for HealthAlertConfig {
+    fn default() -> Self {
+        Self {
+            after_minutes: default_health_alert_after_minutes(),
+            channels: Vec::new(),
+        }
+    }
+}
+
+fn default_health_alert_after_minutes() -> u32 {
+    15
+}
+
+/// When commands are verified and how often they are resent, see `command_verification`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandVerificationConfig {
+    /// Seconds after a command before the unit's state is read back
+    #[serde(default = "default_verification_delay_seconds")]
+    pub delay_seconds: u64,
+    /// Times a command that didn't take effect is resent before it is reported as not applied
+    #[serde(default = "default_verification_max_retries")]
+    pub max_retries: u32,
+}
+
+impl Default for CommandVerificationConfig {
+    fn default() -> Self {
+        Self {
+            delay_seconds: default_verification_delay_seconds(),
+            max_retries: default_verification_max_retries(),
+        }
+    }
+}
+
+fn default_verification_delay_seconds() -> u64 {
+    30
+}
+
+fn default_verification_max_retries() -> u32 {
+    1
+}
+
+/// Weights and decay of the occupancy model, see `occupancy`
+#[derive(Debug, Clone, Deserialize)]
+pub struct OccupancyConfig {
+    /// Minutes after which the evidence of an event has decayed to half
+    #[serde(default = "default_occupancy_half_life_minutes")]
+    pub half_life_minutes: f64,
+    /// Evidence (0-1) a PIR detection gives that the room is occupied
+    #[serde(default = "default_occupancy_motion_weight")]
+    pub motion_weight: f64,
+    /// Evidence (0-1) a door opening or closing gives that the room is occupied
+    #[serde(default = "default_occupancy_door_weight")]
+    pub door_weight: f64,
+    /// Prior probability (0-1) of the room being occupied for each local hour, starting at midnight
+    /// Hours that are missing have no prior
+    #[serde(default)]
+    pub hourly_priors: Vec<f64>,
+}
+
+impl OccupancyConfig {
+    /// Prior probability of the room being occupied at a local hour, 0 if none is configured
+    pub fn prior(&self, hour: u32) -> f64 {
+        self.hourly_priors
+            .get(hour as usize)
+            .map_or(0.0, |prior| prior.clamp(0.0, 1.0))
+    }
+}
+
+impl Default for OccupancyConfig {
+    fn default() -> Self {
+        Self {
+            half_life_minutes: default_occupancy_half_life_minutes(),
+            motion_weight: default_occupancy_motion_weight(),
+            door_weight: default_occupancy_door_weight(),
+            hourly_priors: Vec::new(),
+        }
+    }
+}
+
+fn default_occupancy_half_life_minutes() -> f64 {
+    15.0
+}
+
+fn default_occupancy_motion_weight() -> f64 {
+    1.0
+}
+
+fn default_occupancy_door_weight() -> f64 {
+    0.6
+}
+
+/// Where a health alert is sent
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertChannel {
+    /// JSON post like the Notify node's channels
+    Webhook { url: String },
+    /// ntfy topic URL, e.g. "https://ntfy.sh/my-topic"
+    Ntfy { url: String },
+    /// Telegram bot message to a chat
+    Telegram { bot_token: String, chat_id: String },
+}
+
+impl AlertChannel {
+    /// Name of the channel type, safe to log
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AlertChannel::Webhook { .. } => "webhook",
+            AlertChannel::Ntfy { .. } => "ntfy",
+            AlertChannel::Telegram { .. } => "telegram",
+        }
+    }
+}
+
+/// Leaves out the URLs and the bot token, which grant access to the channel
+impl std::fmt::Debug for AlertChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertChannel::Webhook { .. } => f.debug_struct("Webhook").finish_non_exhaustive(),
+            AlertChannel::Ntfy { .. } => f.debug_struct("Ntfy").finish_non_exhaustive(),
+            AlertChannel::Telegram { chat_id, .. } => {
+                f.debug_struct("Telegram").field("chat_id", chat_id).finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+fn default_solar_azimuth() -> f64 {
+    180.0
+}
+
+fn default_solar_tilt() -> f64 {
+    35.0
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_presence_topic() -> String {
+    "presence/+".to_string()
+}
+
+fn default_presence_poll_interval_seconds() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct AcControllerEndpointProperties {
     pub endpoint: String,
     pub api_key: String,
+    /// Which state differences cause a command to be resent to this device
+    #[serde(default)]
+    pub change_policy: AcChangePolicy,
+    /// Do-not-disturb windows during which setpoint tweaks are deferred
+    #[serde(default)]
+    pub dnd_windows: Vec<DndWindow>,
+    /// Overrides `min_off_time_minutes` for this device
+    #[serde(default)]
+    pub min_off_time_minutes: Option<u32>,
+    /// Coordinates used for this device's outdoor weather, when it has its own microclimate
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    /// Devices with a higher priority are evaluated first and get the power budget first
+    #[serde(default)]
+    pub priority: i32,
+    /// Estimated draw in watts while running, used for the power budget
+    #[serde(default = "default_device_power_watt")]
+    pub power_watt: u32,
+    /// Estimated draw in watts in powerful mode, used for the power budget
+    #[serde(default = "default_device_powerful_power_watt")]
+    pub powerful_power_watt: u32,
+    /// Estimated draw in watts per mode (e.g. "Fan"), used instead of `power_watt` for energy estimates
+    #[serde(default)]
+    pub mode_power_watt: HashMap<String, u32>,
+    /// Whether the controller can aim the vertical vane, otherwise vane positions are not sent
+    #[serde(default)]
+    pub supports_vertical_vane: bool,
+    /// Whether the controller can aim the horizontal vane, otherwise vane positions are not sent
+    #[serde(default)]
+    pub supports_horizontal_vane: bool,
+    /// Whether the controller reports the unit's state on `/api/state`, so commands can be verified
+    #[serde(default)]
+    pub supports_state_readback: bool,
+    /// Engine that decides what this device does
+    #[serde(default)]
+    pub decision_engine: DecisionEngineKind,
+}
+
+/// State the ACs are left in when the service stops, see `shutdown`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownAcState {
+    /// Leave every AC as it is
+    #[default]
+    Leave,
+    /// Leave every AC as it is, but take it out of powerful mode
+    NoPowerful,
+    /// Turn every AC off
+    Off,
+}
+
+/// Decision engines a device can use, see `ac_controller::decision_engine`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionEngineKind {
+    /// Run the device's nodeset (its assigned nodeset, or the active nodeset)
+    #[default]
+    Nodeset,
+}
+
+fn default_device_power_watt() -> u32 {
+    1000
+}
+
+fn default_device_powerful_power_watt() -> u32 {
+    1500
+}
+
+/// A daily do-not-disturb window for a device (local time, may wrap past midnight).
+/// While active, commands that only tweak the setpoint are deferred until the window ends.
+/// Turning on/off and mode changes are always sent.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DndWindow {
+    /// Start time in "HH:MM" format
+    pub start: String,
+    /// End time in "HH:MM" format
+    pub end: String,
+    /// Largest setpoint change in °C that is still deferred
+    #[serde(default = "default_dnd_max_setpoint_change")]
+    pub max_setpoint_change: f64,
+}
+
+fn default_dnd_max_setpoint_change() -> f64 {
+    1.0
+}
+
+impl Config {
+    /// Check if the HTTPS listener is configured
+    pub fn tls_enabled(&self) -> bool {
+        !self.tls_cert_path.is_empty() && !self.tls_key_path.is_empty()
+    }
+
+    /// Estimated draw in watts of a running device, used for energy estimates
+    /// Powerful mode uses `powerful_power_watt`, other modes their `mode_power_watt` or `power_watt`
+    pub fn estimated_power_watt(&self, device_name: &str, mode: Option<&str>, is_powerful: bool) -> Option<u32> {
+        let props = self.ac_controller_endpoints.get(device_name)?;
+        if is_powerful {
+            return Some(props.powerful_power_watt);
+        }
+        Some(mode.and_then(|mode| props.mode_power_watt.get(mode)).copied().unwrap_or(props.power_watt))
+    }
+
+    /// Get the do-not-disturb windows for a device (empty if none configured)
+    pub fn get_dnd_windows(&self, device_name: &str) -> &[DndWindow] {
+        self.ac_controller_endpoints
+            .get(device_name)
+            .map(|e| e.dnd_windows.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Get the coordinates used for a device's outdoor weather, falling back to the global location
+    /// The device's own coordinates are only used when both latitude and longitude are set
+    pub fn get_weather_coordinates(&self, device_name: &str) -> (f64, f64) {
+        self.ac_controller_endpoints
+            .get(device_name)
+            .and_then(|e| e.latitude.zip(e.longitude))
+            .unwrap_or((self.latitude, self.longitude))
+    }
+
+    /// Get the minimum off-time in minutes for a device, falling back to the global setting
+    pub fn get_min_off_time_minutes(&self, device_name: &str) -> u32 {
+        self.ac_controller_endpoints
+            .get(device_name)
+            .and_then(|e| e.min_off_time_minutes)
+            .unwrap_or(self.min_off_time_minutes)
+    }
+
+    /// Sensor IDs of each configured PIR zone, sorted
+    pub fn pir_zones(&self) -> BTreeMap<String, Vec<String>> {
+        let mut zones: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (sensor_id, sensor) in &self.pir_sensors {
+            if let Some(zone) = &sensor.zone {
+                zones.entry(zone.clone()).or_default().push(sensor_id.clone());
+            }
+        }
+        for sensors in zones.values_mut() {
+            sensors.sort();
+        }
+        zones
+    }
+
+    /// Whether commands to a device are read back, false for unknown devices
+    pub fn supports_state_readback(&self, device_name: &str) -> bool {
+        self.ac_controller_endpoints
+            .get(device_name)
+            .is_some_and(|e| e.supports_state_readback)
+    }
+
+    /// Get the decision engine for a device, the nodeset engine for unknown devices
+    pub fn get_decision_engine(&self, device_name: &str) -> DecisionEngineKind {
+        self.ac_controller_endpoints
+            .get(device_name)
+            .map(|e| e.decision_engine)
+            .unwrap_or_default()
+    }
+
+    /// Get the change policy for a device, falling back to the default policy
+    pub fn get_change_policy(&self, device_name: &str) -> AcChangePolicy {
+        self.ac_controller_endpoints
+            .get(device_name)
+            .map(|e| e.change_policy.clone())
+            .unwrap_or_default()
+    }
+
+    /// Vertical and horizontal vane positions for a device from their names (e.g. "Position 2")
+    /// Vanes the device doesn't support and unknown names are None, leaving the vane to the controller
+    pub fn supported_vanes(
+        &self,
+        device_name: &str,
+        vertical_vane: Option<&str>,
+        horizontal_vane: Option<&str>,
+    ) -> (Option<i32>, Option<i32>) {
+        let Some(props) = self.ac_controller_endpoints.get(device_name) else {
+            return (None, None);
+        };
+        let position = |supported: bool, name: Option<&str>| name.filter(|_| supported).and_then(vane_from_name);
+        (
+            position(props.supports_vertical_vane, vertical_vane),
+            position(props.supports_horizontal_vane, horizontal_vane),
+        )
+    }
 }
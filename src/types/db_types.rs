@@ -1,7 +1,7 @@
 use serde::Serialize;
 use sqlx::prelude::FromRow;
 
-#[derive(Debug, FromRow, Serialize, Clone)]
+#[derive(Debug, FromRow, Serialize, Clone, utoipa::ToSchema)]
 pub struct AcAction {
     pub id: i32,
     pub action_timestamp: i32, // Unix timestamp
@@ -16,6 +16,10 @@ pub struct AcAction {
     pub measured_solar_production_watt: Option<i32>,
     pub is_human_home: Option<bool>,
     pub cause_id: i32, // Reason for the action (see CauseReason enum)
+    pub command_latency_ms: Option<i32>, // Round trip of the command to the AC endpoint
+    pub annotation: Option<String>, // Text from the annotation input of the node that sent the command
+    pub vertical_vane: Option<i32>, // Vane position (see ac_state::VANE_*), None if not requested
+    pub horizontal_vane: Option<i32>,
 }
 
 impl AcAction {
@@ -47,6 +51,153 @@ impl AcAction {
             measured_solar_production_watt,
             is_human_home,
             cause_id,
+            command_latency_ms: None,
+            annotation: None,
+            vertical_vane: None,
+            horizontal_vane: None,
         }
     }
 }
+
+/// Outdoor temperature for one hour of weather history
+#[derive(Debug, FromRow, Serialize, Clone, utoipa::ToSchema)]
+pub struct WeatherHour {
+    pub hour_timestamp: i64, // Unix timestamp of the start of the hour (UTC)
+    pub outdoor_temperature: f64,
+    pub source: String, // live, archive
+}
+
+/// Display name of a device, independent of its identifier in config and nodesets
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct DeviceAlias {
+    pub device_identifier: String,
+    pub display_name: String,
+}
+
+/// Nodeset assigned to a single device instead of the active nodeset
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct DeviceNodeset {
+    pub device_identifier: String,
+    pub nodeset_id: i64,
+    pub assigned_at: i64, // Unix timestamp
+}
+
+/// Snapshot of the execution inputs and decision of one control cycle for a device
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct CycleSnapshot {
+    pub id: i64,
+    pub device_identifier: String,
+    pub evaluated_at: i64, // Unix timestamp
+    pub inputs: String, // JSON object of flattened input values
+    pub decision: String,
+}
+
+/// Result of one nodeset execution for a device
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct ExecutionRecord {
+    pub id: i64,
+    pub device_identifier: String,
+    pub executed_at: i64, // Unix timestamp
+    pub nodeset_id: i64,
+    pub terminal_type: Option<String>,
+    pub action: Option<String>, // JSON of the ActionResult
+    pub cause_reason: Option<String>,
+    pub error: Option<String>,
+    pub inputs: String, // JSON object of flattened input values
+    pub warnings: String, // JSON array of strings
+    pub is_shadow: bool, // Executed in shadow mode, the action was not sent
+    pub nodeset_revision: Option<i64>, // None for executions recorded before revisions existed
+    pub command_discrepancies: Option<String>, // JSON array of strings, None if the command wasn't read back
+}
+
+/// A saved version of a nodeset, without its content
+#[derive(Debug, FromRow, Serialize, Clone, utoipa::ToSchema)]
+pub struct NodesetRevision {
+    pub revision: i64,
+    pub created_at: i64, // Unix timestamp
+}
+
+/// A saved test case of a nodeset: inputs and the decision the nodeset should make on them
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct NodesetTestCase {
+    pub id: i64,
+    pub nodeset_id: i64,
+    pub name: String,
+    pub inputs: String, // JSON of the execution inputs
+    pub expected_terminal: String, // "Execute Action" or "Do Nothing"
+    pub expected_action: Option<String>, // JSON of the action fields to compare
+    pub created_at: i64, // Unix timestamp
+}
+
+/// A nodeset queued to become the active nodeset at a future time
+#[derive(Debug, FromRow, Serialize, Clone, utoipa::ToSchema)]
+pub struct ScheduledActivation {
+    pub id: i64,
+    pub nodeset_id: i64,
+    pub activate_at: i64, // Unix timestamp
+    pub revert_after_minutes: Option<i64>,
+    pub status: String, // pending, done, failed, cancelled
+    pub error: Option<String>,
+    pub created_at: i64,
+}
+
+/// A recurring rule that switches a nodeset in during a weekly time window
+#[derive(Debug, FromRow, Serialize, Clone, utoipa::ToSchema)]
+pub struct Schedule {
+    pub id: i64,
+    pub name: String,
+    pub nodeset_id: i64,
+    pub device_identifier: Option<String>, // None switches the active nodeset
+    pub days: String, // e.g. "mon-fri"
+    pub start_time: String, // Local "HH:MM"
+    pub end_time: String, // Local "HH:MM"
+    pub priority: i64,
+    pub enabled: bool,
+    pub created_at: i64,
+}
+
+/// Statistics of a device for one hour or day
+#[derive(Debug, FromRow, Serialize, Clone, PartialEq, utoipa::ToSchema)]
+pub struct DeviceStats {
+    pub period_start: i64, // Unix timestamp of the start of the hour or day (UTC)
+    pub device_identifier: String,
+    pub samples: i64,
+    pub avg_indoor_temperature: Option<f64>,
+    pub avg_outdoor_temperature: Option<f64>,
+    pub runtime_minutes: f64,
+    pub actions: i64,
+}
+
+/// Estimated energy of a device in one hour, for one cause, mode and powerful state
+#[derive(Debug, FromRow, Serialize, Clone, PartialEq)]
+pub struct DeviceEnergy {
+    pub period_start: i64, // Unix timestamp of the start of the hour (UTC)
+    pub device_identifier: String,
+    pub cause_id: i64,
+    pub mode: String,
+    pub is_powerful: bool,
+    pub runtime_minutes: f64,
+    pub estimated_kwh: f64,
+}
+
+/// Estimated energy of a device over a range, for one cause, mode and powerful state
+#[derive(Debug, FromRow, Serialize, Clone, PartialEq, utoipa::ToSchema)]
+pub struct EnergyBreakdown {
+    pub device_identifier: String,
+    pub cause_id: i64,
+    pub cause_label: Option<String>, // None if the cause reason was deleted
+    pub mode: String,
+    pub is_powerful: bool,
+    pub runtime_minutes: f64,
+    pub estimated_kwh: f64,
+}
+
+/// Household energy for one hour or day
+#[derive(Debug, FromRow, Serialize, Clone, PartialEq, utoipa::ToSchema)]
+pub struct EnergyStats {
+    pub period_start: i64, // Unix timestamp of the start of the hour or day (UTC)
+    pub covered_minutes: f64,
+    pub grid_import_kwh: f64,
+    pub grid_export_kwh: f64,
+    pub solar_kwh: f64,
+}
@@ -1,11 +1,9 @@
+pub mod ac_command_types;
+pub use ac_command_types::*;
 pub mod config_types;
 pub use config_types::*;
 
-pub use webserver_types::*;
-pub mod webserver_types;
+pub use power_control_center_core::types::*;
 
 pub use db_types::*;
 pub mod db_types;
-
-pub use cause_reason::*;
-pub mod cause_reason;
@@ -0,0 +1,221 @@
+//! Release update checker and self-updater
+//!
+//! When `update_check_enabled` is set, the latest GitHub release is checked periodically
+//! and `update_available` is exposed through `/api/status/details`. With `self_update_enabled`,
+//! `POST /api/admin/self-update` downloads the release binary for this architecture and
+//! stages it in `STAGED_BINARY_PATH`. The process then exits and systemd installs the
+//! staged binary before restarting (see `ExecStartPre` in the service file).
+
+use serde::Deserialize;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::config;
+
+/// GitHub repository releases are fetched from
+const GITHUB_REPO: &str = "NotCoffee418/power_control_center";
+
+/// User agent for GitHub requests (required by the GitHub API)
+const USER_AGENT: &str = concat!("power_control_center/", env!("CARGO_PKG_VERSION"));
+
+/// How often to check for a new release
+const UPDATE_CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+/// Where a downloaded binary is staged for systemd to install on restart
+pub const STAGED_BINARY_PATH: &str = "/var/lib/power_control_center/staged/power_control_center";
+
+/// Version of the running binary
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Latest release tag seen by the update checker
+static LATEST_RELEASE: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+
+fn latest_release() -> &'static RwLock<Option<String>> {
+    LATEST_RELEASE.get_or_init(|| RwLock::new(None))
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Parse a version like "v1.2.3" or "1.2.3" into its numeric parts
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    // Ignore pre-release/build suffixes on the patch version (e.g. "3-beta")
+    let patch = parts.next()?.split(['-', '+']).next()?.parse().ok()?;
+    Some((major, minor, patch))
+}
+
+/// Check if the latest version is newer than the current one
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(l), Some(c)) => l > c,
+        _ => false,
+    }
+}
+
+/// Architecture name used in release asset names
+fn release_arch() -> Option<&'static str> {
+    match std::env::consts::ARCH {
+        "x86_64" => Some("amd64"),
+        "aarch64" => Some("arm64"),
+        "arm" => Some("armv7"),
+        _ => None,
+    }
+}
+
+/// Name of the release archive for a tag and architecture
+fn asset_name(tag: &str, arch: &str) -> String {
+    format!("power_control_center-{}-linux-{}.tar.gz", tag, arch)
+}
+
+/// Get the latest release tag if it is newer than the running version
+pub fn get_available_update() -> Option<String> {
+    let latest = latest_release().read().unwrap();
+    latest.clone().filter(|tag| is_newer(tag, CURRENT_VERSION))
+}
+
+async fn fetch_latest_release() -> Result<GithubRelease, String> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", GITHUB_REPO);
+    reqwest::Client::new()
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("Release request failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Release request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse release: {}", e))
+}
+
+/// Check GitHub for the latest release and remember it
+async fn check_for_update() -> Result<(), String> {
+    let release = fetch_latest_release().await?;
+    if is_newer(&release.tag_name, CURRENT_VERSION) {
+        log::info!("Update available: {} (running {})", release.tag_name, CURRENT_VERSION);
+    }
+    *latest_release().write().unwrap() = Some(release.tag_name);
+    Ok(())
+}
+
+/// Periodically check for new releases while enabled
+pub async fn update_check_loop() {
+    if !config::get_config().update_check_enabled {
+        return;
+    }
+
+    loop {
+        if let Err(e) = check_for_update().await {
+            log::warn!("Update check failed: {}", e);
+        }
+        tokio::time::sleep(Duration::from_secs(UPDATE_CHECK_INTERVAL_SECS)).await;
+    }
+}
+
+/// Download the latest release binary and stage it for installation on restart
+/// Returns the staged release tag
+pub async fn stage_update() -> Result<String, String> {
+    let arch = release_arch()
+        .ok_or_else(|| format!("No release builds for architecture {}", std::env::consts::ARCH))?;
+
+    let release = fetch_latest_release().await?;
+    if !is_newer(&release.tag_name, CURRENT_VERSION) {
+        return Err(format!("Already running the latest version ({})", CURRENT_VERSION));
+    }
+
+    let name = asset_name(&release.tag_name, arch);
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| format!("Release {} has no asset {}", release.tag_name, name))?;
+
+    log::info!("Downloading {}", asset.browser_download_url);
+    let archive = reqwest::Client::new()
+        .get(&asset.browser_download_url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .timeout(Duration::from_secs(300))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Download failed: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Download failed: {}", e))?;
+
+    let staged_path = std::path::Path::new(STAGED_BINARY_PATH);
+    let staging_dir = staged_path.parent().ok_or("Invalid staging path")?;
+    let extract_dir = staging_dir.join("extract");
+    let archive_path = staging_dir.join(&name);
+
+    let _ = tokio::fs::remove_dir_all(&extract_dir).await;
+    tokio::fs::create_dir_all(&extract_dir)
+        .await
+        .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+    tokio::fs::write(&archive_path, &archive)
+        .await
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    let status = tokio::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(&extract_dir)
+        .status()
+        .await
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    let _ = tokio::fs::remove_file(&archive_path).await;
+    if !status.success() {
+        return Err(format!("Failed to extract archive (tar exited with {})", status));
+    }
+
+    tokio::fs::rename(extract_dir.join("power_control_center"), staged_path)
+        .await
+        .map_err(|e| format!("Archive did not contain the expected binary: {}", e))?;
+    let _ = tokio::fs::remove_dir_all(&extract_dir).await;
+
+    log::info!("Release {} staged at {}", release.tag_name, STAGED_BINARY_PATH);
+    Ok(release.tag_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.1.0"), Some((0, 1, 0)));
+        assert_eq!(parse_version("v2.0.1-beta"), Some((2, 0, 1)));
+        assert_eq!(parse_version("latest"), None);
+        assert_eq!(parse_version("v1.2"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("v0.1.1", "0.1.0"));
+        assert!(is_newer("v1.0.0", "0.9.9"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("v0.0.9", "0.1.0"));
+        assert!(!is_newer("garbage", "0.1.0"));
+    }
+
+    #[test]
+    fn test_asset_name_matches_release_workflow() {
+        assert_eq!(asset_name("v1.2.3", "arm64"), "power_control_center-v1.2.3-linux-arm64.tar.gz");
+    }
+}
@@ -0,0 +1,146 @@
+//! Hourly outdoor temperature history
+//!
+//! Once an hour the current outdoor temperature is stored in `weather_history`, so reports
+//! over past weeks have outdoor context. Hours missed while the controller was down are
+//! backfilled once a day from the Open-Meteo archive API, looking back `weather_backfill_days`.
+//! `GET /api/weather/history` returns the stored hours.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::{config, db, device_requests};
+
+const HOUR_SECS: i64 = 60 * 60;
+
+/// How often the current outdoor temperature is recorded
+const RECORD_INTERVAL_SECS: u64 = HOUR_SECS as u64;
+
+/// Run the backfill every this many recordings (once a day)
+const BACKFILL_EVERY_RECORDINGS: u32 = 24;
+
+/// Start of the UTC hour containing a timestamp
+pub fn hour_start(timestamp: i64) -> i64 {
+    timestamp - timestamp.rem_euclid(HOUR_SECS)
+}
+
+/// Dates (UTC) with at least one unrecorded hour between two hour timestamps (inclusive)
+fn missing_dates(recorded: &HashSet<i64>, start_hour: i64, end_hour: i64) -> Vec<NaiveDate> {
+    let mut dates: Vec<NaiveDate> = Vec::new();
+    let mut hour = hour_start(start_hour);
+    while hour <= end_hour {
+        if !recorded.contains(&hour)
+            && let Some(date) = DateTime::<Utc>::from_timestamp(hour, 0).map(|dt| dt.date_naive())
+            && dates.last() != Some(&date)
+        {
+            dates.push(date);
+        }
+        hour += HOUR_SECS;
+    }
+    dates
+}
+
+/// Store the current outdoor temperature for this hour
+async fn record_current_hour() -> Result<(), String> {
+    let cfg = config::get_config();
+    let temperature = device_requests::weather::get_current_outdoor_temp_cached(cfg.latitude, cfg.longitude)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    db::weather_history::record_live(hour_start(Utc::now().timestamp()), temperature)
+        .await
+        .map_err(|e| format!("Failed to store outdoor temperature: {}", e))
+}
+
+/// Fill unrecorded hours of the last `days` days from the weather archive
+/// Returns the number of hours added
+async fn backfill(days: u32) -> Result<u64, String> {
+    let cfg = config::get_config();
+    let end = hour_start(Utc::now().timestamp()) - HOUR_SECS;
+    let start = end - i64::from(days) * 24 * HOUR_SECS;
+
+    let recorded: HashSet<i64> = db::weather_history::get_range(start, end)
+        .await
+        .map_err(|e| format!("Failed to read weather history: {}", e))?
+        .into_iter()
+        .map(|hour| hour.hour_timestamp)
+        .collect();
+
+    let dates = missing_dates(&recorded, start, end);
+    let (Some(first), Some(last)) = (dates.first(), dates.last()) else {
+        return Ok(0);
+    };
+
+    // One request for the whole span; hours that are already recorded are kept
+    let mut hours =
+        device_requests::weather::get_archive_hourly_temps(cfg.latitude, cfg.longitude, *first, *last)
+            .await
+            .map_err(|e| e.to_string())?;
+    hours.retain(|(timestamp, _)| (start..=end).contains(timestamp));
+
+    db::weather_history::insert_archive(&hours)
+        .await
+        .map_err(|e| format!("Failed to store archive weather: {}", e))
+}
+
+/// Record the outdoor temperature every hour and backfill gaps once a day
+pub async fn weather_history_loop() {
+    let mut recordings = 0u32;
+
+    loop {
+        if let Err(e) = record_current_hour().await {
+            log::warn!("Failed to record outdoor temperature history: {}", e);
+        }
+
+        let backfill_days = config::get_config().weather_backfill_days;
+        if backfill_days > 0 && recordings.is_multiple_of(BACKFILL_EVERY_RECORDINGS) {
+            match backfill(backfill_days).await {
+                Ok(0) => log::debug!("Weather history complete, nothing to backfill"),
+                Ok(added) => log::info!("Backfilled {} hours of weather history from the archive", added),
+                Err(e) => log::warn!("Weather history backfill failed: {}", e),
+            }
+        }
+
+        recordings = recordings.wrapping_add(1);
+        tokio::time::sleep(Duration::from_secs(RECORD_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-11-24T00:00:00Z
+    const DAY_START: i64 = 1763942400;
+
+    #[test]
+    fn test_hour_start() {
+        assert_eq!(hour_start(DAY_START), DAY_START);
+        assert_eq!(hour_start(DAY_START + 3599), DAY_START);
+        assert_eq!(hour_start(DAY_START + 3600), DAY_START + 3600);
+    }
+
+    #[test]
+    fn test_missing_dates_complete_history() {
+        let end = DAY_START + 47 * HOUR_SECS;
+        let recorded: HashSet<i64> = (0..48).map(|h| DAY_START + h * HOUR_SECS).collect();
+        assert!(missing_dates(&recorded, DAY_START, end).is_empty());
+    }
+
+    #[test]
+    fn test_missing_dates_reports_each_date_once() {
+        let end = DAY_START + 71 * HOUR_SECS;
+        // Second day has a gap of several hours
+        let recorded: HashSet<i64> = (0..72)
+            .filter(|h| !(30..35).contains(h))
+            .map(|h| DAY_START + h * HOUR_SECS)
+            .collect();
+
+        let dates = missing_dates(&recorded, DAY_START, end);
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2025, 11, 25).unwrap()]);
+
+        let dates = missing_dates(&HashSet::new(), DAY_START, end);
+        assert_eq!(dates.len(), 3);
+    }
+}
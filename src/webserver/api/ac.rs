@@ -1,26 +1,37 @@
 use axum::{
     Json, Router,
-    extract::Query,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    ac_controller::{AcDevices, ac_executor},
+    ac_controller::manual_override::{self, OverrideError},
+    app_state::AppState,
     db,
-    types::{ApiError, ApiResponse},
+    device_requests,
+    live_events::{self, LiveEvent},
+    types::{AcCommand, AcCommandRequest, ApiError, ApiResponse, CauseReason, db_types},
 };
 
-pub fn ac_routes() -> Router {
+pub fn ac_routes(state: AppState) -> Router {
     Router::new()
         .route("/get_history_page", get(get_history_page))
         .route("/get_history_count", get(get_history_count))
         .route("/reset_device_state", post(reset_device_state))
+        .route("/shadow-mode", get(get_shadow_mode).put(set_shadow_mode))
+        .route("/min-off-time", get(get_min_off_time))
+        .route("/min-off-time/:device", delete(clear_min_off_time))
+        .route("/:device/replay-last", post(replay_last_command))
+        .route("/:device/override", post(set_manual_override).delete(clear_manual_override))
+        .with_state(state)
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[serde(default)]
 struct HistoryPageRequest {
     page_size: i64,
@@ -38,6 +49,16 @@ impl Default for HistoryPageRequest {
 
 // GET /api/ac/get_history_page?page_size=10&page_num=1
 // Returns Vec<db_types::AcAction>
+#[utoipa::path(
+    get,
+    path = "/api/ac/get_history_page",
+    tag = "ac",
+    params(HistoryPageRequest),
+    responses(
+        (status = 200, body = ApiResponse<Vec<db_types::AcAction>>),
+        (status = 400, description = "Page size not in 1-100 or page number below 1", body = ApiError),
+    )
+)]
 async fn get_history_page(Query(params): Query<HistoryPageRequest>) -> Response {
     // Validate parameters
     if params.page_size <= 0 || params.page_size > 100 {
@@ -68,6 +89,7 @@ async fn get_history_page(Query(params): Query<HistoryPageRequest>) -> Response
 
 /// GET /api/ac/get_history_count
 /// Use to determine page count in frontend
+#[utoipa::path(get, path = "/api/ac/get_history_count", tag = "ac", responses((status = 200, body = ApiResponse<i64>)))]
 async fn get_history_count() -> Response {
     match db::ac_actions::get_count().await {
         Ok(count) => {
@@ -82,12 +104,12 @@ async fn get_history_count() -> Response {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ResetDeviceStateRequest {
     device: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ResetDeviceStateResponse {
     success: bool,
     message: String,
@@ -97,9 +119,22 @@ struct ResetDeviceStateResponse {
 /// Resets the tracked state for a specific AC device
 /// This is useful when the tracked state gets out of sync with the physical device
 /// After reset, the next control cycle will treat it as first execution and force sync
-async fn reset_device_state(Json(req): Json<ResetDeviceStateRequest>) -> Response {
+#[utoipa::path(
+    post,
+    path = "/api/ac/reset_device_state",
+    tag = "ac",
+    request_body = ResetDeviceStateRequest,
+    responses(
+        (status = 200, body = ApiResponse<ResetDeviceStateResponse>),
+        (status = 400, description = "Unknown device", body = ApiError),
+    )
+)]
+async fn reset_device_state(
+    State(state): State<AppState>,
+    Json(req): Json<ResetDeviceStateRequest>,
+) -> Response {
     // Validate device name
-    let device = match AcDevices::from_str(&req.device) {
+    let device = match state.devices().get(&req.device).cloned() {
         Some(d) => d,
         None => {
             let response = ApiError::error(&format!("Unknown device: {}", req.device));
@@ -108,7 +143,7 @@ async fn reset_device_state(Json(req): Json<ResetDeviceStateRequest>) -> Respons
     };
     
     // Reset the device state
-    ac_executor::reset_device_state(&device);
+    state.ac_states.reset_device(device.as_str());
     
     log::info!("Device state reset via API for device: {}", req.device);
     
@@ -119,3 +154,435 @@ async fn reset_device_state(Json(req): Json<ResetDeviceStateRequest>) -> Respons
     
     (StatusCode::OK, Json(response)).into_response()
 }
+
+/// Whether the controller runs in shadow mode, see `ac_controller::shadow_mode`
+#[derive(Serialize, Deserialize, ToSchema)]
+struct ShadowModeStatus {
+    enabled: bool,
+}
+
+/// GET /api/ac/shadow-mode
+/// Returns whether nodesets are evaluated without sending AC commands (ShadowModeStatus)
+#[utoipa::path(get, path = "/api/ac/shadow-mode", tag = "ac", responses((status = 200, body = ApiResponse<ShadowModeStatus>)))]
+async fn get_shadow_mode(State(state): State<AppState>) -> Response {
+    let response = ApiResponse::success(ShadowModeStatus {
+        enabled: state.shadow_mode.is_enabled(),
+    });
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// PUT /api/ac/shadow-mode
+/// Turns shadow mode on or off, body: {"enabled": true}
+#[utoipa::path(
+    put,
+    path = "/api/ac/shadow-mode",
+    tag = "ac",
+    request_body = ShadowModeStatus,
+    responses((status = 200, body = ApiResponse<ShadowModeStatus>))
+)]
+async fn set_shadow_mode(State(state): State<AppState>, Json(req): Json<ShadowModeStatus>) -> Response {
+    match state.shadow_mode.set_enabled(&state.pool, req.enabled).await {
+        Ok(()) => {
+            let response = ApiResponse::success(ShadowModeStatus { enabled: req.enabled });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(err) => {
+            log::error!("Database error in set_shadow_mode: {}", err);
+            let response = ApiError::error("Database error has occurred");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// Minimum off-time lockout of a device, see `ac_controller::min_off_time`
+#[derive(Serialize, ToSchema)]
+struct MinOffTimeDeviceState {
+    device: String,
+    /// Unix timestamp (seconds) of the last turn-off, None if there was none since startup
+    last_turn_off: Option<i64>,
+    /// Whether the device was turned off too recently to be turned on again
+    is_locked_out: bool,
+    /// Unix timestamp (seconds) at which the lockout ends, None if not locked out
+    locked_out_until: Option<i64>,
+}
+
+/// GET /api/ac/min-off-time
+/// Returns the minimum off-time lockout of every device (Vec<MinOffTimeDeviceState>)
+#[utoipa::path(
+    get,
+    path = "/api/ac/min-off-time",
+    tag = "ac",
+    responses((status = 200, body = ApiResponse<Vec<MinOffTimeDeviceState>>))
+)]
+async fn get_min_off_time(State(state): State<AppState>) -> Response {
+    let config = state.config();
+    let devices: Vec<MinOffTimeDeviceState> = state
+        .devices()
+        .all()
+        .iter()
+        .map(|device| {
+            let device = device.as_str();
+            let minimum_minutes = config.get_min_off_time_minutes(device);
+            let last_turn_off = state.min_off_time.get_last_turn_off(device);
+            let is_locked_out = state.min_off_time.is_locked_out(device, minimum_minutes);
+            MinOffTimeDeviceState {
+                device: device.to_string(),
+                last_turn_off: last_turn_off.map(|dt| dt.timestamp()),
+                is_locked_out,
+                locked_out_until: last_turn_off
+                    .filter(|_| is_locked_out)
+                    .map(|dt| dt.timestamp() + i64::from(minimum_minutes) * 60),
+            }
+        })
+        .collect();
+
+    let response = ApiResponse::success(devices);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// DELETE /api/ac/min-off-time/LivingRoom
+/// Clears the last turn-off of a device so it may be turned on right away
+#[utoipa::path(
+    delete,
+    path = "/api/ac/min-off-time/{device}",
+    tag = "ac",
+    params(("device" = String, Path)),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 404, description = "Unknown device or no recorded turn-off", body = ApiError),
+    )
+)]
+async fn clear_min_off_time(State(state): State<AppState>, Path(device): Path<String>) -> Response {
+    if state.devices().get(&device).is_none() {
+        let response = ApiError::error(format!("Unknown device '{}'", device));
+        return (StatusCode::NOT_FOUND, Json(response)).into_response();
+    }
+
+    if state.min_off_time.clear_turn_off_time(&device) {
+        log::info!("Minimum off-time lockout cleared via API for device: {}", device);
+        let response = ApiResponse::success("Minimum off-time lockout cleared");
+        (StatusCode::OK, Json(response)).into_response()
+    } else {
+        let response = ApiError::error("Device has no recorded turn-off");
+        (StatusCode::NOT_FOUND, Json(response)).into_response()
+    }
+}
+
+/// POST /api/ac/LivingRoom/replay-last
+/// Re-sends the last on or off command that reached the device, with cause ManualReplay
+/// Returns the replayed command (db_types::AcAction)
+#[utoipa::path(
+    post,
+    path = "/api/ac/{device}/replay-last",
+    tag = "ac",
+    params(("device" = String, Path)),
+    responses(
+        (status = 200, body = ApiResponse<db_types::AcAction>),
+        (status = 404, description = "Unknown device or no command sent yet", body = ApiError),
+        (status = 409, description = "The last command can't be replayed", body = ApiError),
+        (status = 502, description = "The AC controller couldn't be reached", body = ApiError),
+    )
+)]
+async fn replay_last_command(State(state): State<AppState>, Path(device): Path<String>) -> Response {
+    let (last_command, command) = match get_replayable_command(&state, &device).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = device_requests::ac::send_command(&device, &command, CauseReason::ManualReplay.id(), None).await {
+        log::error!("Failed to replay the last command to {}: {}", device, e);
+        let response = ApiError::error(format!("Failed to send the command: {}", e));
+        return (StatusCode::BAD_GATEWAY, Json(response)).into_response();
+    }
+
+    log::info!("Replayed the last {} command to {} via API", last_command.action_type, device);
+    track_replayed_command(&state, &device, &command);
+
+    let response = ApiResponse::success(last_command);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// The last command sent to a device with the command that repeats it, or the error response
+async fn get_replayable_command(state: &AppState, device: &str) -> Result<(db_types::AcAction, AcCommand), Response> {
+    if state.devices().get(device).is_none() {
+        let response = ApiError::error(format!("Unknown device '{}'", device));
+        return Err((StatusCode::NOT_FOUND, Json(response)).into_response());
+    }
+
+    let last_command = match db::ac_actions::get_last_command(&state.pool, device).await {
+        Ok(Some(command)) => command,
+        Ok(None) => {
+            let response = ApiError::error("No command has been sent to this device yet");
+            return Err((StatusCode::NOT_FOUND, Json(response)).into_response());
+        }
+        Err(e) => return Err(super::nodes::db_error_response(e, "Failed to get the last command")),
+    };
+
+    let Some(command) = AcCommand::from_action(&last_command) else {
+        let response = ApiError::error("The last command is missing parameters and can't be replayed");
+        return Err((StatusCode::CONFLICT, Json(response)).into_response());
+    };
+    Ok((last_command, command))
+}
+
+/// The device now runs the replayed command, track it so the next control cycle corrects it if needed
+fn track_replayed_command(state: &AppState, device: &str, command: &AcCommand) {
+    let replayed_state = command.to_state();
+    live_events::get_live_events().publish(LiveEvent::ac_state(device, &replayed_state));
+    state.ac_states.set_state(device, replayed_state);
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct ManualOverrideRequest {
+    #[serde(flatten)]
+    command: AcCommandRequest,
+    /// How long the nodeset is skipped for the device
+    minutes: u32,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ManualOverrideResponse {
+    #[serde(flatten)]
+    request: ManualOverrideRequest,
+    /// Unix timestamp (seconds) at which the nodeset takes control again
+    until: i64,
+}
+
+/// Response for an override that could not be started or cleared
+fn override_error_response(device: &str, error: OverrideError) -> Response {
+    let (status, message) = match error {
+        OverrideError::UnknownDevice => (StatusCode::NOT_FOUND, format!("Unknown device '{}'", device)),
+        OverrideError::Invalid(message) => (StatusCode::BAD_REQUEST, message),
+        OverrideError::SendFailed(e) => (StatusCode::BAD_GATEWAY, format!("Failed to send the command: {}", e)),
+        OverrideError::NoOverride => (StatusCode::NOT_FOUND, "Device has no manual override".to_string()),
+    };
+    (status, Json(ApiError::error(message))).into_response()
+}
+
+/// POST /api/ac/LivingRoom/override
+/// Forces a state on the device for `minutes`, the nodeset skips the device until then
+/// Returns the override with the time it ends (ManualOverrideResponse)
+#[utoipa::path(
+    post,
+    path = "/api/ac/{device}/override",
+    tag = "ac",
+    params(("device" = String, Path)),
+    request_body = ManualOverrideRequest,
+    responses(
+        (status = 200, body = ApiResponse<ManualOverrideResponse>),
+        (status = 400, description = "Invalid command", body = ApiError),
+        (status = 404, description = "Unknown device", body = ApiError),
+        (status = 502, description = "The AC controller couldn't be reached", body = ApiError),
+    )
+)]
+async fn set_manual_override(
+    State(state): State<AppState>,
+    Path(device): Path<String>,
+    Json(request): Json<ManualOverrideRequest>,
+) -> Response {
+    match manual_override::start(&state, &device, &request.command, request.minutes).await {
+        Ok(until) => {
+            let response = ApiResponse::success(ManualOverrideResponse {
+                request,
+                until: until.timestamp(),
+            });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => override_error_response(&device, e),
+    }
+}
+
+/// DELETE /api/ac/LivingRoom/override
+/// Ends the override early and returns control to the nodeset right away
+#[utoipa::path(
+    delete,
+    path = "/api/ac/{device}/override",
+    tag = "ac",
+    params(("device" = String, Path)),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 404, description = "Unknown device or no override", body = ApiError),
+    )
+)]
+async fn clear_manual_override(State(state): State<AppState>, Path(device): Path<String>) -> Response {
+    match manual_override::stop(&state, &device) {
+        Ok(()) => {
+            let response = ApiResponse::success("Manual override cleared");
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => override_error_response(&device, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+    use crate::ac_controller::ac_executor::AcState;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_reset_device_state_uses_injected_state() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let mut on_state = AcState::new_off();
+        on_state.is_on = true;
+        state.ac_states.set_state("LivingRoom", on_state);
+        state.ac_states.mark_device_initialized("LivingRoom");
+        state.min_off_time.record_turn_off("LivingRoom");
+
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/reset_device_state")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(r#"{"device": "LivingRoom"}"#))
+            .unwrap();
+        let response = ac_routes(state.clone()).oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!state.ac_states.get_state("LivingRoom").is_on);
+        assert!(!state.ac_states.is_device_initialized("LivingRoom"));
+        assert!(state.min_off_time.get_last_turn_off("LivingRoom").is_some(), "the minimum off-time still applies");
+    }
+
+    #[tokio::test]
+    async fn test_set_shadow_mode() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let request = axum::http::Request::builder()
+            .method("PUT")
+            .uri("/shadow-mode")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(r#"{"enabled": true}"#))
+            .unwrap();
+        let response = ac_routes(state.clone()).oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(state.shadow_mode.is_enabled());
+        assert_eq!(db::settings::get(&state.pool, "shadow_mode").await.unwrap().as_deref(), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn test_query_and_clear_min_off_time() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        state.min_off_time.record_turn_off("LivingRoom");
+
+        let send = |method: &str, uri: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        let (status, body) = test_helpers::send_json(ac_routes(state.clone()), send("GET", "/min-off-time")).await;
+        assert_eq!(status, StatusCode::OK);
+        let living_room = body["data"].as_array().unwrap().iter().find(|d| d["device"] == "LivingRoom").unwrap();
+        assert_eq!(living_room["is_locked_out"], true);
+        assert!(living_room["locked_out_until"].is_i64());
+
+        let response = ac_routes(state.clone()).oneshot(send("DELETE", "/min-off-time/LivingRoom")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(state.min_off_time.can_turn_on("LivingRoom", 3));
+
+        let response = ac_routes(state).oneshot(send("DELETE", "/min-off-time/Attic")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_replay_last_requires_a_previous_command() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let replay = |device: &str| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(format!("/{}/replay-last", device))
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        let response = ac_routes(state.clone()).oneshot(replay("Attic")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = ac_routes(state.clone()).oneshot(replay("LivingRoom")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(!state.ac_states.get_state("LivingRoom").is_on);
+    }
+
+    #[tokio::test]
+    async fn test_replay_last_repeats_the_last_on_or_off_command() {
+        use crate::ac_controller::ac_executor::AC_MODE_COOL;
+        use crate::types::Celsius;
+
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let insert = |timestamp: i32, action_type: &str, mode: Option<i32>| {
+            sqlx::query(
+                "INSERT INTO ac_actions (action_timestamp, device_identifier, action_type, mode, fan_speed, request_temperature, swing, cause_id, vertical_vane) \
+                 VALUES ($1, 'LivingRoom', $2, $3, 0, 23.5, 1, 1, 2)",
+            )
+            .bind(timestamp)
+            .bind(action_type.to_string())
+            .bind(mode)
+            .execute(&state.pool)
+        };
+        insert(100, "off", None).await.unwrap();
+        insert(200, "on", Some(AC_MODE_COOL)).await.unwrap();
+        // Toggling powerful mode again would flip it back, so it isn't what gets replayed
+        insert(300, "toggle-powerful", None).await.unwrap();
+
+        let (last_command, command) = get_replayable_command(&state, "LivingRoom").await.ok().unwrap();
+        assert_eq!(last_command.action_timestamp, 200);
+        let expected = AcCommand::On {
+            mode: AC_MODE_COOL,
+            fan_speed: 0,
+            temperature: Celsius(23.5),
+            swing: 1,
+            vertical_vane: Some(2),
+            horizontal_vane: None,
+        };
+        assert_eq!(command, expected);
+
+        track_replayed_command(&state, "LivingRoom", &command);
+        let tracked = state.ac_states.get_state("LivingRoom");
+        assert!(tracked.is_on);
+        assert_eq!(tracked, expected.to_state());
+
+        // An on command recorded without its mode can't be sent again
+        insert(400, "on", None).await.unwrap();
+        let response = ac_routes(state).oneshot(
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/LivingRoom/replay-last")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        ).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_manual_override_validation() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let send = |method: &str, uri: &str, body: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+        let valid = r#"{"mode": "Cool", "temperature": 24, "minutes": 60}"#;
+        let response = ac_routes(state.clone()).oneshot(send("POST", "/Attic/override", valid)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        for invalid in [
+            r#"{"mode": "Cool", "temperature": 24, "minutes": 0}"#,
+            r#"{"mode": "Cool", "minutes": 60}"#,
+            r#"{"mode": "Turbo", "temperature": 24, "minutes": 60}"#,
+            r#"{"mode": "Heat", "temperature": 24, "fan_speed": "Max", "minutes": 60}"#,
+        ] {
+            let response = ac_routes(state.clone()).oneshot(send("POST", "/LivingRoom/override", invalid)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", invalid);
+        }
+
+        let response = ac_routes(state).oneshot(send("DELETE", "/LivingRoom/override", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
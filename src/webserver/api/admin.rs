@@ -0,0 +1,294 @@
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::State,
+    http::{StatusCode, HeaderMap, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use futures_util::StreamExt;
+use log::warn;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    app_state::AppState,
+    db::backup::{self, InvalidBackup},
+    types::{ApiError, ApiResponse},
+    updater,
+};
+
+/// Largest backup accepted by POST /api/admin/restore, it is streamed to disk so
+/// `api_max_body_bytes` doesn't apply
+const MAX_RESTORE_BYTES: usize = 1024 * 1024 * 1024;
+
+pub fn admin_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/self-update", post(self_update))
+        .route("/backup", post(download_backup))
+        .route("/restore", post(restore_backup))
+        .with_state(state)
+}
+
+/// The 403 or 401 response unless the request has the configured admin key
+pub(super) fn reject_admin_key(headers: &HeaderMap, admin_api_key: &str, action: &str) -> Option<Response> {
+    // Unlike other keys, an empty admin key does not mean "no auth"
+    if admin_api_key.is_empty() {
+        let response = ApiError::error(format!("{} requires admin_api_key to be configured", action));
+        return Some((StatusCode::FORBIDDEN, Json(response)).into_response());
+    }
+    if !super::auth::verify_api_key_header(headers, admin_api_key) {
+        warn!("Unauthorized {} attempt", action.to_lowercase());
+        let response = ApiError::error("Unauthorized");
+        return Some((StatusCode::UNAUTHORIZED, Json(response)).into_response());
+    }
+    None
+}
+
+/// Backups and restores of PostgreSQL databases are left to `pg_dump` and `pg_restore`
+fn backup_unsupported() -> Response {
+    let response = ApiError::error("Backups are only supported with SQLite, use pg_dump and pg_restore for PostgreSQL");
+    (StatusCode::NOT_IMPLEMENTED, Json(response)).into_response()
+}
+
+/// Path for a temporary file next to the database, unique per request
+fn scratch_file(database_path: &str, kind: &str) -> PathBuf {
+    let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    backup::scratch_directory(database_path).join(format!(".pcc-{}-{}.tmp", kind, nanos))
+}
+
+/// POST /api/admin/self-update
+/// Downloads the latest release binary and stages it, then shuts down gracefully so systemd
+/// restarts the service with the new version. Requires `self_update_enabled` and `admin_api_key`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/self-update",
+    tag = "admin",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Update staged, the service restarts", body = ApiResponse<String>),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "Self-update is disabled or `admin_api_key` isn't configured", body = ApiError),
+    )
+)]
+async fn self_update(headers: HeaderMap) -> Response {
+    let config = crate::config::get_config();
+    if !config.self_update_enabled {
+        let response = ApiError::error("Self-update is disabled");
+        return (StatusCode::FORBIDDEN, Json(response)).into_response();
+    }
+
+    if let Some(response) = reject_admin_key(&headers, &config.admin_api_key, "Self-update") {
+        return response;
+    }
+
+    match updater::stage_update().await {
+        Ok(tag) => {
+            // The web server finishes sending this response; systemd installs the staged binary on restart
+            log::info!("Shutting down to apply staged update");
+            crate::shutdown::request();
+            let response = ApiResponse::success(format!("Update {} staged, restarting", tag));
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Self-update failed: {}", e);
+            let response = ApiError::error(format!("Self-update failed: {}", e));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// POST /api/admin/backup
+/// Downloads a consistent snapshot of the database (nodesets, settings and history) as a SQLite
+/// file, which POST /api/admin/restore accepts. Requires `admin_api_key`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/backup",
+    tag = "admin",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "SQLite database file as an attachment", content_type = "application/vnd.sqlite3", body = Vec<u8>),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "`admin_api_key` isn't configured", body = ApiError),
+        (status = 500, description = "The snapshot failed", body = ApiError),
+        (status = 501, description = "The database is PostgreSQL", body = ApiError),
+    )
+)]
+async fn download_backup(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let config = state.config();
+    if let Some(response) = reject_admin_key(&headers, &config.admin_api_key, "Backup") {
+        return response;
+    }
+    if !backup::SUPPORTED {
+        return backup_unsupported();
+    }
+
+    let path = scratch_file(&config.database_path, "backup");
+    let result = match backup::snapshot(&state.pool, &path).await {
+        Ok(()) => tokio::fs::File::open(&path).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    // The open file stays readable after it's removed, so nothing is left behind
+    let _ = tokio::fs::remove_file(&path).await;
+    let file = match result {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!("Database backup failed: {}", e);
+            let response = ApiError::error(format!("Backup failed: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let file_name = backup::backup_file_name(chrono::Local::now().naive_local());
+    log::info!("Database backup {} downloaded", file_name);
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/vnd.sqlite3".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response()
+}
+
+/// Write the request body to `path`, failing once it exceeds `max_bytes`
+async fn receive_upload(body: Body, path: &Path, max_bytes: usize) -> Result<(), Response> {
+    let failed = |e: std::io::Error| {
+        log::error!("Failed to save uploaded backup: {}", e);
+        let response = ApiError::error(format!("Failed to save the backup: {}", e));
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+    };
+
+    let mut file = tokio::fs::File::create(path).await.map_err(failed)?;
+    let mut received = 0;
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let response = ApiError::error(format!("Failed to receive the backup: {}", e));
+                return Err((StatusCode::BAD_REQUEST, Json(response)).into_response());
+            }
+        };
+        received += chunk.len();
+        if received > max_bytes {
+            let response = ApiError::error(format!("Backups over {} bytes can't be restored", max_bytes));
+            return Err((StatusCode::PAYLOAD_TOO_LARGE, Json(response)).into_response());
+        }
+        file.write_all(&chunk).await.map_err(failed)?;
+    }
+    file.sync_all().await.map_err(failed)
+}
+
+/// POST /api/admin/restore
+/// Replaces the database with an uploaded backup (the raw SQLite file as the body). The file is
+/// checked and staged, then the service shuts down gracefully so systemd restarts it with the restored database.
+/// The replaced database is kept next to it as `<database_path>.before-restore`. Requires `admin_api_key`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/restore",
+    tag = "admin",
+    security(("api_key" = [])),
+    request_body(content = Vec<u8>, content_type = "application/vnd.sqlite3", description = "A file from POST /api/admin/backup or a nightly backup"),
+    responses(
+        (status = 200, description = "Backup staged, the service restarts", body = ApiResponse<String>),
+        (status = 400, description = "Not a Power Control Center database, or a damaged one", body = ApiError),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "`admin_api_key` isn't configured", body = ApiError),
+        (status = 409, description = "Backup from a newer version", body = ApiError),
+        (status = 413, description = "Backup over 1 GiB", body = ApiError),
+        (status = 501, description = "The database is PostgreSQL", body = ApiError),
+    )
+)]
+async fn restore_backup(State(state): State<AppState>, headers: HeaderMap, body: Body) -> Response {
+    let config = state.config();
+    if let Some(response) = reject_admin_key(&headers, &config.admin_api_key, "Restore") {
+        return response;
+    }
+    if !backup::SUPPORTED {
+        return backup_unsupported();
+    }
+
+    let upload = scratch_file(&config.database_path, "restore");
+    let staged = match receive_upload(body, &upload, MAX_RESTORE_BYTES).await {
+        Ok(()) => match backup::validate(&upload).await {
+            Ok(()) => backup::stage_restore(&upload, &config.database_path).await.map_err(|e| {
+                log::error!("Failed to stage uploaded backup: {}", e);
+                let response = ApiError::error(format!("Failed to stage the backup: {}", e));
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+            }),
+            Err(e) => {
+                let status = match e {
+                    InvalidBackup::NewerVersion(_) => StatusCode::CONFLICT,
+                    InvalidBackup::Unreadable(_) => StatusCode::BAD_REQUEST,
+                };
+                Err((status, Json(ApiError::error(e.to_string()))).into_response())
+            }
+        },
+        Err(response) => Err(response),
+    };
+    if let Err(response) = staged {
+        let _ = tokio::fs::remove_file(&upload).await;
+        return response;
+    }
+
+    // The web server finishes this response before the process exits; the staged database is
+    // swapped in on restart
+    log::info!("Shutting down to restore the uploaded database backup");
+    crate::shutdown::request();
+    let response = ApiResponse::success("Backup staged, restarting to restore it".to_string());
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(all(test, not(feature = "postgres")))]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn post(state: &AppState, uri: &str, api_key: Option<&str>, body: Body) -> Response {
+        let mut request = Request::builder().method("POST").uri(uri);
+        if let Some(api_key) = api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        admin_routes(state.clone()).oneshot(request.body(body).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_backup_requires_admin_key_and_is_a_database() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        assert_eq!(post(&state, "/backup", Some("anything"), Body::empty()).await.status(), StatusCode::FORBIDDEN);
+
+        let mut state = AppState::for_test(crate::types::Config {
+            admin_api_key: "admin".to_string(),
+            ..Default::default()
+        })
+        .await;
+        let database = std::env::temp_dir().join(format!("pcc_admin_backup_{}.db", std::process::id()));
+        state.pool = crate::db::connect_file(&database).await;
+        assert_eq!(post(&state, "/backup", None, Body::empty()).await.status(), StatusCode::UNAUTHORIZED);
+
+        let response = post(&state, "/backup", Some("admin"), Body::empty()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let disposition = response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().to_string();
+        assert!(disposition.starts_with("attachment; filename=\"pcc-backup-"));
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(bytes.starts_with(b"SQLite format 3\0"));
+
+        state.pool.close().await;
+        std::fs::remove_file(&database).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_invalid_upload() {
+        let state = AppState::for_test(crate::types::Config {
+            admin_api_key: "admin".to_string(),
+            ..Default::default()
+        })
+        .await;
+        let response = post(&state, "/restore", Some("admin"), Body::from("not a database")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
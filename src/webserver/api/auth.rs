@@ -0,0 +1,57 @@
+use axum::http::HeaderMap;
+
+/// Verify an API key from the Authorization header against the expected key
+/// Supports "Bearer <key>", "ApiKey <key>" and raw "<key>" formats.
+/// An empty expected key allows access (backward compatibility).
+pub fn verify_api_key_header(headers: &HeaderMap, expected_key: &str) -> bool {
+    if expected_key.is_empty() {
+        return true;
+    }
+
+    // Check for Authorization header
+    if let Some(auth_header) = headers.get("Authorization") {
+        if let Ok(auth_str) = auth_header.to_str() {
+            // Support both "Bearer <key>" and "ApiKey <key>" formats
+            let key = if auth_str.starts_with("Bearer ") {
+                &auth_str[7..]
+            } else if auth_str.starts_with("ApiKey ") {
+                &auth_str[7..]
+            } else {
+                auth_str
+            };
+
+            return key == expected_key;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers_with(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_empty_key_allows_access() {
+        assert!(verify_api_key_header(&HeaderMap::new(), ""));
+    }
+
+    #[test]
+    fn test_missing_header_is_rejected() {
+        assert!(!verify_api_key_header(&HeaderMap::new(), "secret"));
+    }
+
+    #[test]
+    fn test_supported_header_formats() {
+        assert!(verify_api_key_header(&headers_with("Bearer secret"), "secret"));
+        assert!(verify_api_key_header(&headers_with("ApiKey secret"), "secret"));
+        assert!(verify_api_key_header(&headers_with("secret"), "secret"));
+        assert!(!verify_api_key_header(&headers_with("Bearer wrong"), "secret"));
+    }
+}
@@ -1,49 +1,156 @@
 use axum::{
     Json, Router,
-    extract::Path,
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{delete, get, post, put},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::{db, types::ApiResponse};
+use super::nodes::{self, NodeConfiguration, DEFAULT_NODESET_ID};
+use crate::{app_state::AppState, db::{self, DbPool}, types::{ApiError, ApiResponse}};
 
-pub fn cause_reasons_routes() -> Router {
+pub fn cause_reasons_routes(state: AppState) -> Router {
     Router::new()
         .route("/", get(list_cause_reasons))
         .route("/all", get(list_all_cause_reasons))
+        .route("/stats", get(get_cause_reason_stats))
         .route("/", post(create_cause_reason))
         .route("/:id", get(get_cause_reason))
         .route("/:id", put(update_cause_reason))
         .route("/:id", delete(delete_cause_reason))
         .route("/:id/hidden", put(set_hidden_status))
+        .route("/remap", post(remap_cause_reason))
+        .with_state(state)
 }
 
 /// Request for creating a new cause reason
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateCauseReasonRequest {
     pub label: String,
     pub description: String,
 }
 
 /// Request for updating a cause reason
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct UpdateCauseReasonRequest {
     pub label: String,
     pub description: String,
 }
 
 /// Request for setting hidden status
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SetHiddenRequest {
     pub is_hidden: bool,
 }
 
+/// Request for replacing a cause reason in all profiles
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RemapCauseReasonRequest {
+    /// Cause reason ID to replace (usually one that was deleted)
+    pub from_id: i32,
+    /// Existing cause reason ID to use instead
+    pub to_id: i32,
+}
+
+/// Result of a cause reason remap
+#[derive(Serialize, ToSchema)]
+pub struct RemapCauseReasonResponse {
+    pub updated_nodesets: usize,
+    pub updated_nodes: usize,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CauseReasonStatsQuery {
+    /// How far back from now, e.g. "24h" or "30d"
+    #[serde(default = "default_stats_range")]
+    pub range: String,
+    /// Only count the actions and energy of this device
+    pub device: Option<String>,
+}
+
+fn default_stats_range() -> String {
+    "30d".to_string()
+}
+
+/// Actions and estimated AC use of a cause reason on one device over the range
+#[derive(Serialize, ToSchema)]
+pub struct CauseReasonDeviceTotal {
+    pub device_identifier: String,
+    pub actions: i64,
+    pub runtime_minutes: f64,
+    pub estimated_kwh: f64,
+}
+
+/// Actions and estimated AC use of a cause reason over the range
+#[derive(Serialize, ToSchema)]
+pub struct CauseReasonTotal {
+    pub cause_id: i64,
+    /// None if the cause reason was deleted
+    pub cause_label: Option<String>,
+    pub actions: i64,
+    pub runtime_minutes: f64,
+    pub estimated_kwh: f64,
+    /// Totals per device, sorted by device
+    pub devices: Vec<CauseReasonDeviceTotal>,
+}
+
+/// How much each cause reason drove the ACs from `start` to `end`
+#[derive(Serialize, ToSchema)]
+pub struct CauseReasonStats {
+    pub start: i64,
+    pub end: i64,
+    /// Totals per cause reason, most actions first
+    pub causes: Vec<CauseReasonTotal>,
+    /// Actions and estimated AC use per day (UTC), device and cause reason, oldest first
+    pub days: Vec<db::cause_reasons::CauseReasonDayStats>,
+}
+
+/// Roll the daily rows up into totals per cause reason and device
+fn cause_totals(days: &[db::cause_reasons::CauseReasonDayStats], labels: &HashMap<i64, String>) -> Vec<CauseReasonTotal> {
+    let mut causes: BTreeMap<i64, BTreeMap<&str, CauseReasonDeviceTotal>> = BTreeMap::new();
+    for day in days {
+        let total = causes
+            .entry(day.cause_id)
+            .or_default()
+            .entry(&day.device_identifier)
+            .or_insert_with(|| CauseReasonDeviceTotal {
+                device_identifier: day.device_identifier.clone(),
+                actions: 0,
+                runtime_minutes: 0.0,
+                estimated_kwh: 0.0,
+            });
+        total.actions += day.actions;
+        total.runtime_minutes += day.runtime_minutes;
+        total.estimated_kwh += day.estimated_kwh;
+    }
+
+    let mut totals: Vec<CauseReasonTotal> = causes
+        .into_iter()
+        .map(|(cause_id, devices)| {
+            let devices: Vec<CauseReasonDeviceTotal> = devices.into_values().collect();
+            CauseReasonTotal {
+                cause_id,
+                cause_label: labels.get(&cause_id).cloned(),
+                actions: devices.iter().map(|device| device.actions).sum(),
+                runtime_minutes: devices.iter().map(|device| device.runtime_minutes).sum(),
+                estimated_kwh: devices.iter().map(|device| device.estimated_kwh).sum(),
+                devices,
+            }
+        })
+        .collect();
+    // Stable, so causes with as many actions stay ordered by ID
+    totals.sort_by_key(|total| std::cmp::Reverse(total.actions));
+    totals
+}
+
 /// Helper to check if a cause reason exists and is editable
 /// Returns Ok(record) if editable, or an error Response if not
-async fn check_editable(id: i32, action: &str) -> Result<db::cause_reasons::CauseReasonRecord, Response> {
-    match db::cause_reasons::get_by_id(id).await {
+async fn check_editable(pool: &DbPool, id: i32, action: &str) -> Result<db::cause_reasons::CauseReasonRecord, Response> {
+    match db::cause_reasons::get_by_id(pool, id).await {
         Ok(Some(reason)) => {
             if !reason.is_editable {
                 let response = ApiResponse::<()>::error(&format!("This cause reason cannot be {}", action));
@@ -66,8 +173,9 @@ async fn check_editable(id: i32, action: &str) -> Result<db::cause_reasons::Caus
 
 /// GET /api/cause-reasons
 /// Returns all visible cause reasons
-async fn list_cause_reasons() -> Response {
-    match db::cause_reasons::get_all(false).await {
+#[utoipa::path(get, path = "/api/cause-reasons", tag = "cause-reasons", responses((status = 200, body = ApiResponse<Vec<db::cause_reasons::CauseReasonRecord>>)))]
+async fn list_cause_reasons(State(state): State<AppState>) -> Response {
+    match db::cause_reasons::get_all(&state.pool, false).await {
         Ok(reasons) => {
             let response = ApiResponse::success(reasons);
             (StatusCode::OK, Json(response)).into_response()
@@ -82,8 +190,9 @@ async fn list_cause_reasons() -> Response {
 
 /// GET /api/cause-reasons/all
 /// Returns all cause reasons including hidden ones
-async fn list_all_cause_reasons() -> Response {
-    match db::cause_reasons::get_all(true).await {
+#[utoipa::path(get, path = "/api/cause-reasons/all", tag = "cause-reasons", responses((status = 200, body = ApiResponse<Vec<db::cause_reasons::CauseReasonRecord>>)))]
+async fn list_all_cause_reasons(State(state): State<AppState>) -> Response {
+    match db::cause_reasons::get_all(&state.pool, true).await {
         Ok(reasons) => {
             let response = ApiResponse::success(reasons);
             (StatusCode::OK, Json(response)).into_response()
@@ -96,10 +205,66 @@ async fn list_all_cause_reasons() -> Response {
     }
 }
 
+/// GET /api/cause-reasons/stats?range=30d&device=Veranda
+/// Returns how often each cause reason drove the ACs (CauseReasonStats): the actions sent for it and
+/// the runtime and estimated energy while it set the AC's state
+#[utoipa::path(
+    get,
+    path = "/api/cause-reasons/stats",
+    tag = "cause-reasons",
+    params(CauseReasonStatsQuery),
+    responses(
+        (status = 200, body = ApiResponse<CauseReasonStats>),
+        (status = 400, description = "Invalid range", body = ApiError),
+    )
+)]
+async fn get_cause_reason_stats(State(state): State<AppState>, Query(params): Query<CauseReasonStatsQuery>) -> Response {
+    let Some(range_secs) = super::dashboard::parse_range(&params.range) else {
+        let response = ApiResponse::<()>::error("Invalid range, expected hours or days up to a year, e.g. \"24h\" or \"30d\"");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+    let end = chrono::Utc::now().timestamp();
+    let start = end - range_secs;
+
+    let loaded = async {
+        let days = db::cause_reasons::get_daily_stats(&state.pool, start, end, params.device.as_deref()).await?;
+        let labels: HashMap<i64, String> = db::cause_reasons::get_all(&state.pool, true)
+            .await?
+            .into_iter()
+            .map(|reason| (reason.id as i64, reason.label))
+            .collect();
+        Ok::<_, sqlx::Error>((days, labels))
+    }
+    .await;
+
+    match loaded {
+        Ok((days, labels)) => {
+            let causes = cause_totals(&days, &labels);
+            let response = ApiResponse::success(CauseReasonStats { start, end, causes, days });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to get cause reason statistics: {}", e);
+            let response = ApiResponse::<()>::error("Failed to get cause reason statistics");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
 /// GET /api/cause-reasons/:id
 /// Returns a specific cause reason
-async fn get_cause_reason(Path(id): Path<i32>) -> Response {
-    match db::cause_reasons::get_by_id(id).await {
+#[utoipa::path(
+    get,
+    path = "/api/cause-reasons/{id}",
+    tag = "cause-reasons",
+    params(("id" = i32, Path, description = "Cause reason ID")),
+    responses(
+        (status = 200, body = ApiResponse<db::cause_reasons::CauseReasonRecord>),
+        (status = 404, description = "Cause reason not found", body = ApiError),
+    )
+)]
+async fn get_cause_reason(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    match db::cause_reasons::get_by_id(&state.pool, id).await {
         Ok(Some(reason)) => {
             let response = ApiResponse::success(reason);
             (StatusCode::OK, Json(response)).into_response()
@@ -118,7 +283,17 @@ async fn get_cause_reason(Path(id): Path<i32>) -> Response {
 
 /// POST /api/cause-reasons
 /// Creates a new cause reason
-async fn create_cause_reason(Json(request): Json<CreateCauseReasonRequest>) -> Response {
+#[utoipa::path(
+    post,
+    path = "/api/cause-reasons",
+    tag = "cause-reasons",
+    request_body = CreateCauseReasonRequest,
+    responses(
+        (status = 201, body = ApiResponse<db::cause_reasons::CauseReasonRecord>),
+        (status = 400, description = "Empty label or description", body = ApiError),
+    )
+)]
+async fn create_cause_reason(State(state): State<AppState>, Json(request): Json<CreateCauseReasonRequest>) -> Response {
     // Validate input
     if request.label.trim().is_empty() {
         let response = ApiResponse::<()>::error("Label cannot be empty");
@@ -130,7 +305,7 @@ async fn create_cause_reason(Json(request): Json<CreateCauseReasonRequest>) -> R
         return (StatusCode::BAD_REQUEST, Json(response)).into_response();
     }
     
-    match db::cause_reasons::create(&request.label, &request.description).await {
+    match db::cause_reasons::create(&state.pool, &request.label, &request.description).await {
         Ok(reason) => {
             log::info!("Created cause reason with id {}", reason.id);
             let response = ApiResponse::success(reason);
@@ -146,7 +321,21 @@ async fn create_cause_reason(Json(request): Json<CreateCauseReasonRequest>) -> R
 
 /// PUT /api/cause-reasons/:id
 /// Updates a cause reason
+#[utoipa::path(
+    put,
+    path = "/api/cause-reasons/{id}",
+    tag = "cause-reasons",
+    params(("id" = i32, Path, description = "Cause reason ID")),
+    request_body = UpdateCauseReasonRequest,
+    responses(
+        (status = 200, body = ApiResponse<db::cause_reasons::CauseReasonRecord>),
+        (status = 400, description = "Empty label or description", body = ApiError),
+        (status = 403, description = "Built-in cause reasons can't be edited", body = ApiError),
+        (status = 404, description = "Cause reason not found", body = ApiError),
+    )
+)]
 async fn update_cause_reason(
+    State(state): State<AppState>,
     Path(id): Path<i32>,
     Json(request): Json<UpdateCauseReasonRequest>,
 ) -> Response {
@@ -162,15 +351,15 @@ async fn update_cause_reason(
     }
     
     // Check if the cause reason is editable
-    if let Err(response) = check_editable(id, "modified").await {
+    if let Err(response) = check_editable(&state.pool, id, "modified").await {
         return response;
     }
     
-    match db::cause_reasons::update(id, &request.label, &request.description).await {
+    match db::cause_reasons::update(&state.pool, id, &request.label, &request.description).await {
         Ok(true) => {
             log::info!("Updated cause reason {}", id);
             // Fetch the updated record
-            match db::cause_reasons::get_by_id(id).await {
+            match db::cause_reasons::get_by_id(&state.pool, id).await {
                 Ok(Some(reason)) => {
                     let response = ApiResponse::success(reason);
                     (StatusCode::OK, Json(response)).into_response()
@@ -195,7 +384,18 @@ async fn update_cause_reason(
 
 /// DELETE /api/cause-reasons/:id
 /// Deletes a cause reason
-async fn delete_cause_reason(Path(id): Path<i32>) -> Response {
+#[utoipa::path(
+    delete,
+    path = "/api/cause-reasons/{id}",
+    tag = "cause-reasons",
+    params(("id" = i32, Path, description = "Cause reason ID")),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 403, description = "Built-in cause reasons can't be deleted", body = ApiError),
+        (status = 404, description = "Cause reason not found", body = ApiError),
+    )
+)]
+async fn delete_cause_reason(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
     // Cannot delete the Undefined reason (ID 0)
     if id == 0 {
         let response = ApiResponse::<()>::error("Cannot delete the Undefined cause reason");
@@ -203,11 +403,11 @@ async fn delete_cause_reason(Path(id): Path<i32>) -> Response {
     }
     
     // Check if the cause reason is editable
-    if let Err(response) = check_editable(id, "deleted").await {
+    if let Err(response) = check_editable(&state.pool, id, "deleted").await {
         return response;
     }
     
-    match db::cause_reasons::delete(id).await {
+    match db::cause_reasons::delete(&state.pool, id).await {
         Ok(true) => {
             log::info!("Deleted cause reason {}", id);
             let response = ApiResponse::success("Cause reason deleted");
@@ -227,7 +427,20 @@ async fn delete_cause_reason(Path(id): Path<i32>) -> Response {
 
 /// PUT /api/cause-reasons/:id/hidden
 /// Sets the hidden status of a cause reason
+#[utoipa::path(
+    put,
+    path = "/api/cause-reasons/{id}/hidden",
+    tag = "cause-reasons",
+    params(("id" = i32, Path, description = "Cause reason ID")),
+    request_body = SetHiddenRequest,
+    responses(
+        (status = 200, body = ApiResponse<db::cause_reasons::CauseReasonRecord>),
+        (status = 403, description = "Built-in cause reasons can't be hidden", body = ApiError),
+        (status = 404, description = "Cause reason not found", body = ApiError),
+    )
+)]
 async fn set_hidden_status(
+    State(state): State<AppState>,
     Path(id): Path<i32>,
     Json(request): Json<SetHiddenRequest>,
 ) -> Response {
@@ -240,16 +453,16 @@ async fn set_hidden_status(
     // Check if the cause reason is editable (non-editable items cannot be hidden)
     // Only check editability when trying to hide (showing is always allowed)
     if request.is_hidden {
-        if let Err(response) = check_editable(id, "hidden").await {
+        if let Err(response) = check_editable(&state.pool, id, "hidden").await {
             return response;
         }
     }
     
-    match db::cause_reasons::set_hidden(id, request.is_hidden).await {
+    match db::cause_reasons::set_hidden(&state.pool, id, request.is_hidden).await {
         Ok(true) => {
             log::info!("Set cause reason {} hidden status to {}", id, request.is_hidden);
             // Fetch the updated record
-            match db::cause_reasons::get_by_id(id).await {
+            match db::cause_reasons::get_by_id(&state.pool, id).await {
                 Ok(Some(reason)) => {
                     let response = ApiResponse::success(reason);
                     (StatusCode::OK, Json(response)).into_response()
@@ -271,3 +484,166 @@ async fn set_hidden_status(
         }
     }
 }
+
+/// POST /api/cause-reasons/remap
+/// Points every Cause Reason node that uses `from_id` to `to_id` instead, across all
+/// profiles except the read-only default. Used to fix profiles after a cause reason was deleted.
+#[utoipa::path(
+    post,
+    path = "/api/cause-reasons/remap",
+    tag = "cause-reasons",
+    request_body = RemapCauseReasonRequest,
+    responses(
+        (status = 200, body = ApiResponse<RemapCauseReasonResponse>),
+        (status = 400, description = "Both IDs are the same", body = ApiError),
+        (status = 404, description = "The cause reason to use doesn't exist", body = ApiError),
+        (status = 409, description = "Nodesets modified by a concurrent request, retry", body = ApiError),
+    )
+)]
+async fn remap_cause_reason(State(state): State<AppState>, Json(request): Json<RemapCauseReasonRequest>) -> Response {
+    if request.from_id == request.to_id {
+        let response = ApiResponse::<()>::error("from_id and to_id must be different");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+    
+    let mut tx = match db::begin_write(&state.pool).await {
+        Ok(tx) => tx,
+        Err(e) => return nodes::db_error_response(e, "Failed to remap cause reason"),
+    };
+    
+    let target = sqlx::query_as::<_, (i32,)>("SELECT id FROM cause_reasons WHERE id = $1")
+        .bind(request.to_id)
+        .fetch_optional(&mut *tx)
+        .await;
+    match target {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let response = ApiResponse::<()>::error(format!("Cause reason {} does not exist", request.to_id));
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to check cause reason: {}", e);
+            return nodes::db_error_response(e, "Failed to remap cause reason");
+        }
+    }
+    
+    let nodesets = match sqlx::query_as::<_, (i64, String)>("SELECT id, node_json FROM nodesets WHERE id != $1")
+        .bind(DEFAULT_NODESET_ID)
+        .fetch_all(&mut *tx)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Failed to fetch nodesets: {}", e);
+            return nodes::db_error_response(e, "Failed to remap cause reason");
+        }
+    };
+    
+    let from_id = request.from_id.to_string();
+    let to_id = request.to_id.to_string();
+    let mut result = RemapCauseReasonResponse { updated_nodesets: 0, updated_nodes: 0 };
+    
+    for (id, node_json) in nodesets {
+        let mut config = match NodeConfiguration::from_stored_json(&node_json) {
+            Ok(config) => config,
+            Err(e) => return nodes::schema_error_response(e, &format!("nodeset {}", id)),
+        };
+        
+        let changed = nodes::remap_cause_reason(&mut config.nodes, &from_id, &to_id);
+        if changed == 0 {
+            continue;
+        }
+        
+        let json_str = match serde_json::to_string(&NodeConfiguration::new(config.nodes, config.edges).with_variables(config.variables)) {
+            Ok(s) => s,
+            Err(e) => {
+                log::error!("Failed to serialize nodeset configuration: {}", e);
+                let response = ApiResponse::<()>::error("Failed to serialize nodeset configuration");
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            }
+        };
+        
+        if let Err(e) = sqlx::query("UPDATE nodesets SET node_json = $1 WHERE id = $2")
+            .bind(&json_str)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            log::error!("Failed to update nodeset {}: {}", id, e);
+            return nodes::db_error_response(e, "Failed to remap cause reason");
+        }
+        if let Err(e) = nodes::record_revision(&mut tx, &state.config(), id).await {
+            log::error!("Failed to record revision of nodeset {}: {}", id, e);
+            return nodes::db_error_response(e, "Failed to remap cause reason");
+        }
+        
+        result.updated_nodesets += 1;
+        result.updated_nodes += changed;
+    }
+    
+    if let Err(e) = tx.commit().await {
+        log::error!("Failed to commit cause reason remap: {}", e);
+        return nodes::db_error_response(e, "Failed to remap cause reason");
+    }
+    
+    log::info!(
+        "Remapped cause reason {} to {} in {} nodes across {} nodesets",
+        request.from_id, request.to_id, result.updated_nodes, result.updated_nodesets
+    );
+    let response = ApiResponse::success(result);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+    use crate::types::db_types;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_stats_count_actions_and_energy_per_cause() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let now = chrono::Utc::now().timestamp();
+        let hour = crate::weather_history::hour_start(now) - 2 * 60 * 60;
+
+        for (device, cause_id, action_timestamp) in [("Veranda", 6, now - 60), ("Veranda", 6, now - 120), ("LivingRoom", 1, now - 60)] {
+            sqlx::query("INSERT INTO ac_actions (action_timestamp, device_identifier, action_type, cause_id) VALUES ($1, $2, 'on', $3)")
+                .bind(action_timestamp)
+                .bind(device)
+                .bind(cause_id)
+                .execute(&state.pool)
+                .await
+                .unwrap();
+        }
+        let estimate = |device: &str, cause_id: i64, runtime_minutes: f64, estimated_kwh: f64| db_types::DeviceEnergy {
+            period_start: hour,
+            device_identifier: device.to_string(),
+            cause_id,
+            mode: "Heat".to_string(),
+            is_powerful: false,
+            runtime_minutes,
+            estimated_kwh,
+        };
+        let estimates = [estimate("Veranda", 6, 45.0, 1.5), estimate("LivingRoom", 1, 15.0, 0.25)];
+        db::statistics::store_hour(&state.pool, hour, &[], None, &estimates).await.unwrap();
+
+        let request = axum::http::Request::builder().uri("/stats?range=7d").body(axum::body::Body::empty()).unwrap();
+        let (status, body) = test_helpers::send_json(cause_reasons_routes(state.clone()), request).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let causes = body["data"]["causes"].as_array().unwrap();
+        assert_eq!(causes.len(), 2);
+        assert_eq!(causes[0]["cause_label"], "Excessive Solar Power");
+        assert_eq!(causes[0]["actions"], 2);
+        assert_eq!(causes[0]["estimated_kwh"], 1.5);
+        assert_eq!(causes[1]["cause_label"], "Ice Exception");
+        assert_eq!(causes[1]["devices"], serde_json::json!([
+            { "device_identifier": "LivingRoom", "actions": 1, "runtime_minutes": 15.0, "estimated_kwh": 0.25 }
+        ]));
+
+        let request = axum::http::Request::builder().uri("/stats?range=forever").body(axum::body::Body::empty()).unwrap();
+        let response = cause_reasons_routes(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,85 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+
+use crate::{
+    app_state::AppState,
+    config::CONFIG_FILE_PATH,
+    types::{ApiError, ApiResponse},
+};
+
+pub fn config_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/reload", post(reload_config))
+        .with_state(state)
+}
+
+/// POST /api/config/reload
+/// Re-reads the config file without restarting the service, same as sending SIGHUP.
+/// The current config is kept if the file is invalid. Requires `admin_api_key`.
+#[utoipa::path(
+    post,
+    path = "/api/config/reload",
+    tag = "admin",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 400, description = "The config file is invalid, the current config is kept", body = ApiError),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "`admin_api_key` isn't configured", body = ApiError),
+    )
+)]
+async fn reload_config(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(response) = super::admin::reject_admin_key(&headers, &state.config().admin_api_key, "Config reload") {
+        return response;
+    }
+
+    match state.config_store.reload_from_file(CONFIG_FILE_PATH) {
+        Ok(()) => {
+            let response = ApiResponse::success("Config reloaded");
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Config reload failed, keeping the current config: {}", e);
+            let response = ApiError::error(format!("Config reload failed: {}", e));
+            (StatusCode::BAD_REQUEST, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    async fn post_reload(state: &AppState, api_key: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().method("POST").uri("/reload");
+        if let Some(api_key) = api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        let response = config_routes(state.clone())
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        response.status()
+    }
+
+    #[tokio::test]
+    async fn test_reload_requires_admin_key() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        assert_eq!(post_reload(&state, Some("anything")).await, StatusCode::FORBIDDEN);
+
+        let state = AppState::for_test(crate::types::Config {
+            admin_api_key: "admin".to_string(),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(post_reload(&state, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post_reload(&state, Some("wrong")).await, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,27 +1,40 @@
 use axum::{
     Json, Router,
-    extract::Query,
+    extract::{Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::get,
 };
 use serde::{Serialize, Deserialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    ac_controller::ac_executor::{get_state_manager, AC_MODE_OFF, AC_MODE_COOL, AC_MODE_HEAT},
-    config,
+    ac_controller::{
+        state_reconciliation,
+        cycle_snapshots::{diff_inputs, InputChange, InputSnapshot},
+    },
+    app_state::AppState,
     db,
     device_requests,
-    types::ApiResponse,
+    sensor_history::{Metric, Resolution},
+    types::{ApiError, ApiResponse, db_types},
 };
 
-pub fn dashboard_routes() -> Router {
+/// Longest range of an energy or history request (a year)
+const MAX_RANGE_SECS: i64 = 366 * 24 * 60 * 60;
+
+pub fn dashboard_routes(state: AppState) -> Router {
     Router::new()
         .route("/status", get(get_dashboard_status))
         .route("/recent-commands", get(get_recent_commands))
+        .route("/cycles", get(get_cycles))
+        .route("/energy", get(get_energy))
+        .route("/history", get(get_history))
+        .with_state(state)
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct DashboardStatus {
     pub devices: Vec<DeviceStatus>,
     pub outdoor_temp: Option<f64>,
@@ -32,21 +45,42 @@ pub struct DashboardStatus {
     pub net_power_w: Option<i32>,
     pub pir_timeout_minutes: u32,
     pub user_is_home: bool,
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
     pub user_home_override_until: Option<i64>,
+    pub possible_grid_outage: bool,
+    /// Whether the UPS reports running on battery (None if no recent UPS signal)
+    pub ups_on_battery: Option<bool>,
+    /// Whether the controller is in deep idle with lengthened polling intervals
+    pub deep_idle: bool,
+    /// Whether nodesets are evaluated without sending AC commands
+    pub shadow_mode: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct DeviceStatus {
     pub name: String,
+    /// Name shown to the user, `name` if no display name is set
+    pub display_name: String,
     pub is_on: bool,
     pub mode: Option<String>,
+    /// Temperature sent to the AC, rounded to the step it supports
     pub temperature_setpoint: Option<f64>,
+    /// Temperature the controller aims for, may differ from `temperature_setpoint` by the rounding
+    pub target_temperature: Option<f64>,
     pub indoor_temperature: Option<f64>,
+    /// Median-filtered indoor temperature used by the profile at the last evaluation
+    pub filtered_indoor_temperature: Option<f64>,
     pub fan_speed: Option<i32>,
     pub swing: Option<i32>,
     pub powerful_mode: bool,
     pub is_automatic_mode: bool,
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
     pub last_pir_detection: Option<i64>, // Unix timestamp in seconds
+    /// Whether the latest indoor temperature changed implausibly fast and is being ignored
+    pub sensor_anomaly: bool,
+    /// How the AC disagreed with its last command after it was read back, empty if it was applied
+    pub command_not_applied: Vec<String>,
 }
 
 const KW_TO_W_MULTIPLIER: f64 = 1000.0;
@@ -56,17 +90,25 @@ const DEFAULT_IS_AUTOMATIC_MODE: bool = false;
 
 /// GET /api/dashboard/status
 /// Returns current status of all configured devices and environmental data
-async fn get_dashboard_status() -> Response {
-    let cfg = config::get_config();
-    
-    // Get PIR state once for all devices (cheap operation, just returns a static reference)
-    let pir_state = crate::ac_controller::pir_state::get_pir_state();
+#[utoipa::path(
+    get,
+    path = "/api/dashboard/status",
+    tag = "dashboard",
+    responses((status = 200, body = ApiResponse<DashboardStatus>))
+)]
+async fn get_dashboard_status(State(app_state): State<AppState>) -> Response {
+    let cfg = app_state.config();
     
     // Gather device statuses
     let mut devices = Vec::new();
+    let display_names = db::device_aliases::get_display_names(&app_state.pool).await.unwrap_or_else(|e| {
+        log::warn!("Failed to get device display names: {}", e);
+        Default::default()
+    });
     
-    for device_name in cfg.ac_controller_endpoints.keys() {
-        let state = get_state_manager().get_state(device_name);
+    for device in app_state.devices().all() {
+        let device_name = device.as_str();
+        let state = app_state.ac_states.get_state(device_name);
         
         // Try to get current indoor temperature and automatic mode from the device (using cache)
         let (indoor_temp, is_automatic_mode) = match device_requests::ac::get_sensors_cached(device_name).await {
@@ -77,29 +119,33 @@ async fn get_dashboard_status() -> Response {
             }
         };
         
-        // Convert mode integer to string (AC_MODE_OFF=0, AC_MODE_COOL=1, AC_MODE_HEAT=4)
-        let mode_str = state.mode.map(|m| match m {
-            AC_MODE_OFF => "off".to_string(),
-            AC_MODE_COOL => "cool".to_string(),
-            AC_MODE_HEAT => "heat".to_string(),
-            _ => format!("mode_{}", m),
-        });
+        let mode_str = state_reconciliation::dashboard_mode(&state);
         
         // Get last PIR detection time
-        let last_pir_detection = pir_state.get_last_detection(device_name)
+        let last_pir_detection = app_state.pir.get_last_detection(device_name)
             .map(|dt| dt.timestamp());
         
         devices.push(DeviceStatus {
-            name: device_name.clone(),
+            name: device_name.to_string(),
+            display_name: super::devices::display_name(&display_names, device_name),
             is_on: state.is_on,
             mode: mode_str,
-            temperature_setpoint: state.temperature,
+            temperature_setpoint: state.temperature.map(|t| t.0),
+            target_temperature: crate::ac_controller::target_temperature::get_target_temperatures()
+                .get(device_name)
+                .map(|t| t.0),
             indoor_temperature: indoor_temp,
+            filtered_indoor_temperature: crate::ac_controller::sensor_filter::get_sensor_filter_state()
+                .get_latest(device_name)
+                .map(|reading| reading.filtered),
             fan_speed: state.fan_speed,
             swing: state.swing,
             powerful_mode: state.powerful_mode,
             is_automatic_mode,
             last_pir_detection,
+            sensor_anomaly: crate::ac_controller::sensor_anomaly::get_sensor_anomaly_state().is_anomalous(device_name),
+            command_not_applied: crate::ac_controller::command_verification::get_command_verifier()
+                .get_not_applied(device_name),
         });
     }
     
@@ -163,6 +209,10 @@ async fn get_dashboard_status() -> Response {
         _ => None,
     };
     
+    // Get grid outage status
+    let possible_grid_outage = crate::ac_controller::grid_state::check_possible_grid_outage().await;
+    let ups_on_battery = crate::ac_controller::grid_state::get_grid_state().get_ups_on_battery();
+    
     let status = DashboardStatus {
         devices,
         outdoor_temp,
@@ -174,13 +224,18 @@ async fn get_dashboard_status() -> Response {
         pir_timeout_minutes: cfg.pir_timeout_minutes,
         user_is_home,
         user_home_override_until,
+        possible_grid_outage,
+        ups_on_battery,
+        deep_idle: crate::ac_controller::idle_state::get_idle_state().is_deep_idle(),
+        shadow_mode: app_state.shadow_mode.is_enabled(),
     };
     
     let response = ApiResponse::success(status);
     (StatusCode::OK, Json(response)).into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct RecentCommandsQuery {
     #[serde(default = "default_page")]
     pub page: i64,
@@ -196,7 +251,7 @@ fn default_per_page() -> i64 {
     10
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct AcActionWithCause {
     #[serde(flatten)]
     pub action: crate::types::db_types::AcAction,
@@ -204,7 +259,7 @@ pub struct AcActionWithCause {
     pub cause_description: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct RecentCommandsResponse {
     pub commands: Vec<AcActionWithCause>,
     pub total_count: i64,
@@ -233,7 +288,17 @@ async fn get_user_home_override() -> Result<i64, Box<dyn std::error::Error>> {
 
 /// GET /api/dashboard/recent-commands?page=1&per_page=10
 /// Returns recent AC commands with pagination
-async fn get_recent_commands(Query(params): Query<RecentCommandsQuery>) -> Response {
+#[utoipa::path(
+    get,
+    path = "/api/dashboard/recent-commands",
+    tag = "dashboard",
+    params(RecentCommandsQuery),
+    responses((status = 200, body = ApiResponse<RecentCommandsResponse>))
+)]
+async fn get_recent_commands(
+    State(state): State<AppState>,
+    Query(params): Query<RecentCommandsQuery>,
+) -> Response {
     let page = params.page.max(1);
     let per_page = params.per_page.clamp(1, 100);
     let offset = (page - 1) * per_page;
@@ -251,7 +316,7 @@ async fn get_recent_commands(Query(params): Query<RecentCommandsQuery>) -> Respo
     let default_cause = ("Undefined".to_string(), "No specific reason recorded".to_string());
     
     // Fetch all cause reasons from database in a single query
-    let cause_map: std::collections::HashMap<i32, (String, String)> = match db::cause_reasons::get_all(true).await {
+    let cause_map: std::collections::HashMap<i32, (String, String)> = match db::cause_reasons::get_all(&state.pool, true).await {
         Ok(reasons) => {
             reasons.into_iter()
                 .map(|reason| (reason.id, (reason.label, reason.description)))
@@ -294,3 +359,393 @@ async fn get_recent_commands(Query(params): Query<RecentCommandsQuery>) -> Respo
     let response = ApiResponse::success(response_data);
     (StatusCode::OK, Json(response)).into_response()
 }
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CyclesQuery {
+    pub device: String,
+    #[serde(default = "default_cycles_limit")]
+    pub limit: i64,
+}
+
+fn default_cycles_limit() -> i64 {
+    20
+}
+
+/// A control cycle with the inputs that changed since the previous cycle of the device
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct CycleEntry {
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub evaluated_at: i64, // Unix timestamp in seconds
+    pub decision: String,
+    #[schema(value_type = BTreeMap<String, serde_json::Value>)]
+    pub inputs: InputSnapshot,
+    /// Inputs that changed since the previous cycle, None if there is no earlier cycle
+    pub changed: Option<Vec<InputChange>>,
+}
+
+/// GET /api/dashboard/cycles?device=LivingRoom&limit=20
+/// Returns the most recent control cycles of a device, newest first, with the inputs that changed
+/// since the cycle before each of them
+#[utoipa::path(
+    get,
+    path = "/api/dashboard/cycles",
+    tag = "dashboard",
+    params(CyclesQuery),
+    responses((status = 200, body = ApiResponse<Vec<CycleEntry>>))
+)]
+async fn get_cycles(
+    State(state): State<AppState>,
+    Query(params): Query<CyclesQuery>,
+) -> Response {
+    let limit = params.limit.clamp(1, 200);
+
+    // Fetch one extra cycle so the oldest returned cycle can be compared too
+    let snapshots = match db::cycle_snapshots::get_recent(&state.pool, &params.device, limit + 1).await {
+        Ok(snapshots) => snapshots,
+        Err(e) => {
+            log::error!("Failed to fetch cycle snapshots: {}", e);
+            let response = ApiResponse::<()>::error("Failed to fetch cycles");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let inputs: Vec<InputSnapshot> = snapshots
+        .iter()
+        .map(|snapshot| serde_json::from_str(&snapshot.inputs).unwrap_or_default())
+        .collect();
+
+    let cycles: Vec<CycleEntry> = snapshots
+        .iter()
+        .zip(&inputs)
+        .enumerate()
+        .take(limit as usize)
+        .map(|(i, (snapshot, current))| CycleEntry {
+            evaluated_at: snapshot.evaluated_at,
+            decision: snapshot.decision.clone(),
+            inputs: current.clone(),
+            changed: inputs.get(i + 1).map(|previous| diff_inputs(previous, current)),
+        })
+        .collect();
+
+    let response = ApiResponse::success(cycles);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EnergyQuery {
+    /// How far back from now, e.g. "24h" or "30d"
+    #[serde(default = "default_energy_range")]
+    pub range: String,
+    /// Only return the energy of this device
+    pub device: Option<String>,
+}
+
+fn default_energy_range() -> String {
+    "7d".to_string()
+}
+
+/// Parse a range like "24h" or "30d" into seconds
+pub(super) fn parse_range(range: &str) -> Option<i64> {
+    let unit_secs = match range.chars().last()? {
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    let count: i64 = range[..range.len() - 1].parse().ok()?;
+    let secs = count.checked_mul(unit_secs)?;
+    (1..=MAX_RANGE_SECS).contains(&secs).then_some(secs)
+}
+
+/// Estimated energy of a device over the range
+#[derive(Serialize, ToSchema)]
+pub struct DeviceEnergyTotal {
+    pub device_identifier: String,
+    pub runtime_minutes: f64,
+    pub estimated_kwh: f64,
+    /// Part of `estimated_kwh` used in powerful mode
+    pub powerful_kwh: f64,
+}
+
+/// Estimated AC energy of the hours starting in [start, end)
+#[derive(Serialize, ToSchema)]
+pub struct EnergyReport {
+    pub start: i64,
+    pub end: i64,
+    /// Totals per device, sorted by device
+    pub devices: Vec<DeviceEnergyTotal>,
+    /// Energy per device, cause, mode and powerful state, largest first per device
+    pub breakdown: Vec<db_types::EnergyBreakdown>,
+}
+
+/// GET /api/dashboard/energy?range=30d&device=Veranda
+/// Returns the estimated AC energy per device and cause (EnergyReport)
+/// Hours are estimated once they are rolled up into the statistics, so the current hour is missing
+#[utoipa::path(
+    get,
+    path = "/api/dashboard/energy",
+    tag = "dashboard",
+    params(EnergyQuery),
+    responses(
+        (status = 200, body = ApiResponse<EnergyReport>),
+        (status = 400, description = "Invalid range", body = ApiError),
+    )
+)]
+async fn get_energy(State(state): State<AppState>, Query(params): Query<EnergyQuery>) -> Response {
+    let Some(range_secs) = parse_range(&params.range) else {
+        let response = ApiResponse::<()>::error("Invalid range, expected hours or days up to a year, e.g. \"24h\" or \"30d\"");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+    let end = chrono::Utc::now().timestamp();
+    let start = end - range_secs;
+
+    let breakdown = match db::statistics::get_energy_breakdown(&state.pool, start, end, params.device.as_deref()).await {
+        Ok(breakdown) => breakdown,
+        Err(e) => {
+            log::error!("Failed to fetch device energy: {}", e);
+            let response = ApiResponse::<()>::error("Failed to fetch device energy");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let mut devices: Vec<DeviceEnergyTotal> = Vec::new();
+    for row in &breakdown {
+        if devices.last().is_none_or(|total| total.device_identifier != row.device_identifier) {
+            devices.push(DeviceEnergyTotal {
+                device_identifier: row.device_identifier.clone(),
+                runtime_minutes: 0.0,
+                estimated_kwh: 0.0,
+                powerful_kwh: 0.0,
+            });
+        }
+        let total = devices.last_mut().expect("a total was just pushed");
+        total.runtime_minutes += row.runtime_minutes;
+        total.estimated_kwh += row.estimated_kwh;
+        if row.is_powerful {
+            total.powerful_kwh += row.estimated_kwh;
+        }
+    }
+
+    let response = ApiResponse::success(EnergyReport { start, end, devices, breakdown });
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+    /// indoor_temperature, outdoor_temperature, solar_watt or net_power_watt
+    pub metric: String,
+    /// How far back from now, e.g. "24h" or "30d"
+    #[serde(default = "default_history_range")]
+    pub range: String,
+    /// Device of a per-device metric (indoor_temperature)
+    pub device: Option<String>,
+}
+
+fn default_history_range() -> String {
+    "24h".to_string()
+}
+
+/// Recorded values of a metric, see `sensor_history`
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct SensorHistory {
+    pub metric: String,
+    pub device: Option<String>,
+    /// raw (every control cycle), 5m or 1h averages, chosen from the range
+    pub resolution: String,
+    pub points: Vec<HistoryPoint>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct HistoryPoint {
+    /// Unix timestamp of the sample, or of the start of the averaged period
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+/// GET /api/dashboard/history?metric=indoor_temperature&range=24h&device=Veranda
+/// Returns the recorded values of a metric (SensorHistory), oldest first
+/// Ranges up to 48 hours return every sample, up to a week 5 minute averages, longer ranges hourly averages
+#[utoipa::path(
+    get,
+    path = "/api/dashboard/history",
+    tag = "dashboard",
+    params(HistoryQuery),
+    responses(
+        (status = 200, body = ApiResponse<SensorHistory>),
+        (status = 400, description = "Unknown metric or invalid range", body = ApiError),
+    )
+)]
+async fn get_history(State(state): State<AppState>, Query(params): Query<HistoryQuery>) -> Response {
+    let Some(metric) = Metric::from_name(&params.metric) else {
+        let response = ApiResponse::<()>::error(
+            "Unknown metric, expected indoor_temperature, outdoor_temperature, solar_watt or net_power_watt",
+        );
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+    let Some(range_secs) = parse_range(&params.range) else {
+        let response = ApiResponse::<()>::error("Invalid range, expected hours or days up to a year, e.g. \"24h\" or \"30d\"");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+    let device = if metric.is_per_device() {
+        match params.device {
+            Some(device) => Some(device),
+            None => {
+                let response = ApiResponse::<()>::error("This metric is recorded per device, the device is required");
+                return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+            }
+        }
+    } else {
+        None
+    };
+
+    let resolution = Resolution::for_range(range_secs);
+    let start = chrono::Utc::now().timestamp() - range_secs;
+    let rows = db::sensor_history::get_points(
+        &state.pool,
+        metric.as_str(),
+        device.as_deref().unwrap_or(""),
+        resolution.as_str(),
+        start,
+    )
+    .await;
+    let points = match rows {
+        Ok(rows) => rows.into_iter().map(|(timestamp, value)| HistoryPoint { timestamp, value }).collect(),
+        Err(e) => {
+            log::error!("Failed to fetch sensor history: {}", e);
+            let response = ApiResponse::<()>::error("Failed to fetch sensor history");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let history = SensorHistory {
+        metric: metric.as_str().to_string(),
+        device,
+        resolution: resolution.as_str().to_string(),
+        points,
+    };
+    let response = ApiResponse::success(history);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+    use tower::ServiceExt;
+
+    async fn get_json(state: &AppState, uri: &str) -> serde_json::Value {
+        let (status, body) = test_helpers::api_request(dashboard_routes(state.clone()), "GET", uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        body
+    }
+
+    #[tokio::test]
+    async fn test_cycles_diff_against_previous_cycle() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+
+        let cycles = [
+            (100, r#"{"is_user_home":false,"outdoor_temperature":8.0}"#, "Do Nothing"),
+            (400, r#"{"is_user_home":true,"outdoor_temperature":8.0}"#, "Execute Action: Heat 21°C"),
+            (700, r#"{"is_user_home":true,"outdoor_temperature":7.5}"#, "Execute Action: Heat 21°C"),
+        ];
+        for (evaluated_at, inputs, decision) in cycles {
+            db::cycle_snapshots::insert(pool, "Veranda", evaluated_at, inputs, decision).await.unwrap();
+        }
+        db::cycle_snapshots::insert(pool, "LivingRoom", 500, "{}", "Do Nothing").await.unwrap();
+
+        let body = get_json(&state, "/cycles?device=Veranda&limit=2").await;
+        let cycles = body["data"].as_array().unwrap();
+        assert_eq!(cycles.len(), 2);
+
+        assert_eq!(cycles[0]["evaluated_at"], 700);
+        assert_eq!(cycles[0]["changed"], serde_json::json!([
+            { "key": "outdoor_temperature", "previous": 8.0, "current": 7.5 }
+        ]));
+
+        // The oldest returned cycle is still compared with the cycle before it
+        assert_eq!(cycles[1]["decision"], "Execute Action: Heat 21°C");
+        assert_eq!(cycles[1]["changed"], serde_json::json!([
+            { "key": "is_user_home", "previous": false, "current": true }
+        ]));
+
+        let body = get_json(&state, "/cycles?device=LivingRoom").await;
+        assert_eq!(body["data"][0]["changed"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_energy_totals_and_breakdown() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let hour = crate::weather_history::hour_start(chrono::Utc::now().timestamp()) - 2 * 60 * 60;
+        let estimate = |device: &str, cause_id: i64, is_powerful: bool, estimated_kwh: f64| db_types::DeviceEnergy {
+            period_start: hour,
+            device_identifier: device.to_string(),
+            cause_id,
+            mode: "Heat".to_string(),
+            is_powerful,
+            runtime_minutes: 10.0,
+            estimated_kwh,
+        };
+        let estimates = [
+            estimate("Veranda", 0, false, 0.25),
+            estimate("Veranda", 0, true, 0.5),
+            estimate("LivingRoom", 1, false, 0.125),
+        ];
+        db::statistics::store_hour(&state.pool, hour, &[], None, &estimates).await.unwrap();
+
+        let body = get_json(&state, "/energy?range=24h").await;
+        assert_eq!(body["data"]["devices"], serde_json::json!([
+            { "device_identifier": "LivingRoom", "runtime_minutes": 10.0, "estimated_kwh": 0.125, "powerful_kwh": 0.0 },
+            { "device_identifier": "Veranda", "runtime_minutes": 20.0, "estimated_kwh": 0.75, "powerful_kwh": 0.5 },
+        ]));
+        let breakdown = &body["data"]["breakdown"];
+        assert_eq!(breakdown[0]["cause_label"], "Ice Exception");
+        assert_eq!(breakdown[1]["is_powerful"], true);
+        assert_eq!(breakdown[1]["cause_label"], "Undefined");
+
+        let body = get_json(&state, "/energy?range=1h&device=Veranda").await;
+        assert_eq!(body["data"]["devices"], serde_json::json!([]));
+        assert_eq!(parse_range("30d"), Some(30 * 24 * 60 * 60));
+        assert_eq!(parse_range("367d"), None);
+        assert_eq!(parse_range("week"), None);
+    }
+
+    #[tokio::test]
+    async fn test_history_picks_resolution_from_range() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let now = chrono::Utc::now().timestamp();
+        let reading = |value: f64| db::sensor_history::Reading {
+            metric: "indoor_temperature",
+            device_identifier: "Veranda".to_string(),
+            value,
+        };
+        db::sensor_history::insert_raw(&state.pool, now - 600, &[reading(20.5)]).await.unwrap();
+        db::sensor_history::insert_raw(&state.pool, now - 60, &[reading(21.0)]).await.unwrap();
+
+        let body = get_json(&state, "/history?metric=indoor_temperature&device=Veranda&range=1h").await;
+        assert_eq!(body["data"]["resolution"], "raw");
+        assert_eq!(body["data"]["points"], serde_json::json!([
+            { "timestamp": now - 600, "value": 20.5 },
+            { "timestamp": now - 60, "value": 21.0 },
+        ]));
+
+        let body = get_json(&state, "/history?metric=indoor_temperature&device=Veranda&range=30d").await;
+        assert_eq!(body["data"]["resolution"], "1h");
+        assert_eq!(body["data"]["points"], serde_json::json!([]));
+
+        let body = get_json(&state, "/history?metric=outdoor_temperature").await;
+        assert_eq!(body["data"]["device"], serde_json::Value::Null);
+
+        for uri in ["/history?metric=humidity", "/history?metric=indoor_temperature", "/history?metric=solar_watt&range=2y"] {
+            let request = axum::http::Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap();
+            let response = dashboard_routes(state.clone()).oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        }
+    }
+}
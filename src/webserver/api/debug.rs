@@ -0,0 +1,86 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+use crate::{
+    app_state::AppState,
+    device_requests::raw_payloads::{get_raw_payloads, RawPayload, RawSource},
+    types::{ApiError, ApiResponse},
+};
+
+pub fn debug_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/raw/:source", get(get_raw_payloads_for_source))
+        .with_state(state)
+}
+
+/// GET /api/debug/raw/meter
+/// Returns the last raw payloads received from "ac" (the AC bridges), "meter" or "weather",
+/// newest first, to diagnose parsing problems. Requires `admin_api_key`.
+#[utoipa::path(
+    get,
+    path = "/api/debug/raw/{source}",
+    tag = "admin",
+    params(("source" = String, Path, description = "ac, meter or weather")),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<Vec<RawPayload>>),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "`admin_api_key` isn't configured", body = ApiError),
+        (status = 404, description = "Unknown source", body = ApiError),
+    )
+)]
+async fn get_raw_payloads_for_source(
+    State(state): State<AppState>,
+    Path(source): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(response) = super::admin::reject_admin_key(&headers, &state.config().admin_api_key, "Reading raw payloads") {
+        return response;
+    }
+
+    let Some(source) = RawSource::from_name(&source) else {
+        let response = ApiError::error(format!("Unknown source '{}', expected ac, meter or weather", source));
+        return (StatusCode::NOT_FOUND, Json(response)).into_response();
+    };
+    let response = ApiResponse::success(get_raw_payloads().get(source));
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    async fn get_raw(state: &AppState, source: &str, api_key: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri(format!("/raw/{}", source));
+        if let Some(api_key) = api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        let response = debug_routes(state.clone())
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        response.status()
+    }
+
+    #[tokio::test]
+    async fn test_raw_payloads_require_admin_key() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        assert_eq!(get_raw(&state, "meter", Some("anything")).await, StatusCode::FORBIDDEN);
+
+        let state = AppState::for_test(crate::types::Config {
+            admin_api_key: "admin".to_string(),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(get_raw(&state, "meter", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(get_raw(&state, "meter", Some("admin")).await, StatusCode::OK);
+        assert_eq!(get_raw(&state, "solar", Some("admin")).await, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,195 @@
+use axum::{
+    Json, Router,
+    http::{StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use log::warn;
+
+use crate::{
+    ac_controller::demand_response::{self, DrEvent},
+    nodes::DemandResponseData,
+    types::{ApiError, ApiResponse},
+};
+
+/// Longest event accepted, utilities announce events of a few hours
+const MAX_EVENT_MINUTES: i64 = 7 * 24 * 60;
+
+pub fn demand_response_routes() -> Router {
+    Router::new()
+        .route("/event", post(set_event).delete(clear_event))
+        .route("/status", get(get_status))
+}
+
+#[derive(Deserialize, ToSchema)]
+struct DrEventRequest {
+    /// Curtailment level requested by the utility
+    level: i64,
+    /// Unix timestamp (seconds) when the event starts. Defaults to now.
+    start_timestamp: Option<i64>,
+    /// How long the event lasts
+    duration_minutes: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+struct DrStatusResponse {
+    #[serde(flatten)]
+    data: DemandResponseData,
+    /// Unix timestamp (seconds) of the current or upcoming event start
+    starts_at: Option<i64>,
+    /// Unix timestamp (seconds) of the current or upcoming event end
+    ends_at: Option<i64>,
+}
+
+/// The 403 or 401 response unless the request has the configured demand-response key
+fn reject_key(headers: &HeaderMap, api_key: &str) -> Option<Response> {
+    // Events change what every device does, so an empty key doesn't mean "no auth" here
+    if api_key.is_empty() {
+        let response = ApiError::error("Demand-response events require demand_response_api_key to be configured");
+        return Some((StatusCode::FORBIDDEN, Json(response)).into_response());
+    }
+    if super::auth::verify_api_key_header(headers, api_key) {
+        return None;
+    }
+    warn!("Unauthorized demand-response attempt");
+    let response = ApiError::error("Unauthorized");
+    Some((StatusCode::UNAUTHORIZED, Json(response)).into_response())
+}
+
+fn unauthorized(headers: &HeaderMap) -> Option<Response> {
+    reject_key(headers, &crate::config::get_config().demand_response_api_key)
+}
+
+/// The event of a request, Err with the message of an invalid one
+fn event_from_request(request: &DrEventRequest, now: DateTime<Utc>) -> Result<DrEvent, String> {
+    if request.level < 0 || request.duration_minutes <= 0 || request.duration_minutes > MAX_EVENT_MINUTES {
+        return Err(format!(
+            "level must be >= 0 and duration_minutes between 1 and {}",
+            MAX_EVENT_MINUTES
+        ));
+    }
+    let starts_at = match request.start_timestamp {
+        Some(ts) => DateTime::<Utc>::from_timestamp(ts, 0).ok_or("Invalid start_timestamp")?,
+        None => now,
+    };
+    let ends_at = TimeDelta::try_minutes(request.duration_minutes)
+        .and_then(|duration| starts_at.checked_add_signed(duration))
+        .ok_or("Invalid start_timestamp")?;
+    Ok(DrEvent {
+        level: request.level,
+        starts_at,
+        ends_at,
+    })
+}
+
+/// POST /api/demand-response/event
+/// Announces a demand-response event from the utility aggregator.
+/// Replaces any previously announced event.
+#[utoipa::path(
+    post,
+    path = "/api/demand-response/event",
+    tag = "demand-response",
+    request_body = DrEventRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 400, description = "Invalid level, duration or start", body = ApiError),
+        (status = 401, description = "Missing or wrong `demand_response_api_key`", body = ApiError),
+        (status = 403, description = "`demand_response_api_key` isn't configured", body = ApiError),
+    )
+)]
+async fn set_event(
+    headers: HeaderMap,
+    Json(request): Json<DrEventRequest>,
+) -> Response {
+    if let Some(response) = unauthorized(&headers) {
+        return response;
+    }
+
+    let event = match event_from_request(&request, Utc::now()) {
+        Ok(event) => event,
+        Err(message) => return (StatusCode::BAD_REQUEST, Json(ApiError::error(message))).into_response(),
+    };
+    demand_response::get_demand_response_state().set_event(event);
+
+    let response = ApiResponse::success("Demand-response event recorded");
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// DELETE /api/demand-response/event
+/// Cancels the current or upcoming demand-response event
+#[utoipa::path(
+    delete,
+    path = "/api/demand-response/event",
+    tag = "demand-response",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 401, description = "Missing or wrong `demand_response_api_key`", body = ApiError),
+        (status = 403, description = "`demand_response_api_key` isn't configured", body = ApiError),
+    )
+)]
+async fn clear_event(headers: HeaderMap) -> Response {
+    if let Some(response) = unauthorized(&headers) {
+        return response;
+    }
+
+    demand_response::get_demand_response_state().clear_event();
+
+    let response = ApiResponse::success("Demand-response event cleared");
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// GET /api/demand-response/status
+/// Returns the demand-response values exposed to the nodeset
+#[utoipa::path(
+    get,
+    path = "/api/demand-response/status",
+    tag = "demand-response",
+    responses((status = 200, body = ApiResponse<DrStatusResponse>))
+)]
+async fn get_status() -> Response {
+    let state = demand_response::get_demand_response_state();
+    let event = state.get_event();
+
+    let response = ApiResponse::success(DrStatusResponse {
+        data: state.get_data(),
+        starts_at: event.as_ref().map(|e| e.starts_at.timestamp()),
+        ends_at: event.as_ref().map(|e| e.ends_at.timestamp()),
+    });
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(start_timestamp: Option<i64>, duration_minutes: i64) -> DrEventRequest {
+        DrEventRequest { level: 2, start_timestamp, duration_minutes }
+    }
+
+    #[test]
+    fn test_event_bounds() {
+        let now = DateTime::<Utc>::from_timestamp(1_764_547_200, 0).unwrap();
+        let event = event_from_request(&request(None, 90), now).unwrap();
+        assert_eq!((event.starts_at, event.ends_at), (now, now + TimeDelta::minutes(90)));
+
+        assert!(event_from_request(&request(None, 0), now).is_err());
+        assert!(event_from_request(&request(None, i64::MAX), now).is_err());
+        assert!(event_from_request(&request(Some(i64::MAX), 60), now).is_err());
+        let last_second = DateTime::<Utc>::MAX_UTC.timestamp();
+        assert!(event_from_request(&request(Some(last_second), 60), now).is_err());
+    }
+
+    #[test]
+    fn test_events_require_a_configured_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(reject_key(&headers, "").unwrap().status(), StatusCode::FORBIDDEN);
+        assert_eq!(reject_key(&headers, "dr").unwrap().status(), StatusCode::UNAUTHORIZED);
+        headers.insert("Authorization", "Bearer dr".parse().unwrap());
+        assert!(reject_key(&headers, "dr").is_none());
+    }
+}
@@ -0,0 +1,247 @@
+//! Device display names
+//!
+//! Devices are identified by their key in `ac_controller_endpoints`, which is also what nodesets,
+//! assignments and history refer to. Renaming that key breaks all of those, so a device can get a
+//! display name instead: "Veranda" keeps working everywhere while the dashboard shows "Sunroom".
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::{
+    app_state::AppState,
+    db,
+    types::{ApiError, ApiResponse},
+};
+
+/// Longest accepted display name
+const MAX_DISPLAY_NAME_LENGTH: usize = 64;
+
+pub fn devices_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_devices))
+        .route("/:device/display-name", put(set_display_name).delete(remove_display_name))
+        .with_state(state)
+}
+
+/// Identifier and display name of a device
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct DeviceInfo {
+    /// Identifier used in config, nodesets and history
+    pub device: String,
+    /// Name shown to the user, the identifier if no display name is set
+    pub display_name: String,
+    /// Base URL of the device's IR controller
+    pub endpoint: String,
+}
+
+/// Request for setting the display name of a device
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetDisplayNameRequest {
+    pub display_name: String,
+}
+
+/// Display name of a device, falling back to its identifier
+pub fn display_name(display_names: &HashMap<String, String>, device: &str) -> String {
+    display_names.get(device).cloned().unwrap_or_else(|| device.to_string())
+}
+
+/// Respond with 404 for device names that aren't AC devices
+fn unknown_device_response(device: &str) -> Response {
+    let response = ApiResponse::<()>::error(format!("Unknown device '{}'", device));
+    (StatusCode::NOT_FOUND, Json(response)).into_response()
+}
+
+/// GET /api/devices
+/// Returns the identifier and display name of every device (Vec<DeviceInfo>)
+#[utoipa::path(get, path = "/api/devices", tag = "devices", responses((status = 200, body = ApiResponse<Vec<DeviceInfo>>)))]
+async fn list_devices(State(state): State<AppState>) -> Response {
+    match db::device_aliases::get_display_names(&state.pool).await {
+        Ok(display_names) => {
+            let devices: Vec<DeviceInfo> = state
+                .devices()
+                .all()
+                .iter()
+                .map(|device| DeviceInfo {
+                    device: device.as_str().to_string(),
+                    display_name: display_name(&display_names, device.as_str()),
+                    endpoint: device.endpoint().to_string(),
+                })
+                .collect();
+            let response = ApiResponse::success(devices);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to list devices: {}", e);
+            let response = ApiResponse::<()>::error("Failed to list devices");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// PUT /api/devices/:device/display-name
+/// Sets the name the device is shown as. Must be unique and can't be another device's identifier.
+#[utoipa::path(
+    put,
+    path = "/api/devices/{device}/display-name",
+    tag = "devices",
+    params(("device" = String, Path)),
+    request_body = SetDisplayNameRequest,
+    responses(
+        (status = 200, body = ApiResponse<DeviceInfo>),
+        (status = 400, description = "Empty or too long display name", body = ApiError),
+        (status = 404, description = "Unknown device", body = ApiError),
+        (status = 409, description = "The name is taken by another device", body = ApiError),
+    )
+)]
+async fn set_display_name(
+    State(state): State<AppState>,
+    Path(device): Path<String>,
+    Json(request): Json<SetDisplayNameRequest>,
+) -> Response {
+    let registry = state.devices();
+    if registry.get(&device).is_none() {
+        return unknown_device_response(&device);
+    }
+
+    let display_name = request.display_name.trim();
+    if display_name.is_empty() {
+        let response = ApiResponse::<()>::error("Display name cannot be empty");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+    if display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+        let response = ApiResponse::<()>::error(format!(
+            "Display name cannot be longer than {} characters",
+            MAX_DISPLAY_NAME_LENGTH
+        ));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+    let is_other_identifier = registry
+        .all()
+        .iter()
+        .any(|other| other.as_str() != device && other.as_str().eq_ignore_ascii_case(display_name));
+    if is_other_identifier {
+        let response = ApiResponse::<()>::error(format!("'{}' is the identifier of another device", display_name));
+        return (StatusCode::CONFLICT, Json(response)).into_response();
+    }
+
+    match db::device_aliases::set(&state.pool, &device, display_name).await {
+        Ok(()) => {
+            log::info!("Device {} is now shown as '{}'", device, display_name);
+            let response = ApiResponse::success(DeviceInfo {
+                endpoint: registry.get(&device).map(|d| d.endpoint().to_string()).unwrap_or_default(),
+                device,
+                display_name: display_name.to_string(),
+            });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) if e.as_database_error().is_some_and(|e| e.is_unique_violation()) => {
+            let response = ApiResponse::<()>::error(format!("Another device is already named '{}'", display_name));
+            (StatusCode::CONFLICT, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to set display name: {}", e);
+            let response = ApiResponse::<()>::error("Failed to set display name");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// DELETE /api/devices/:device/display-name
+/// Removes the display name so the device is shown by its identifier again
+#[utoipa::path(
+    delete,
+    path = "/api/devices/{device}/display-name",
+    tag = "devices",
+    params(("device" = String, Path)),
+    responses(
+        (status = 200, description = "The device whose display name was removed", body = ApiResponse<String>),
+        (status = 404, description = "Unknown device or no display name", body = ApiError),
+    )
+)]
+async fn remove_display_name(State(state): State<AppState>, Path(device): Path<String>) -> Response {
+    if state.devices().get(&device).is_none() {
+        return unknown_device_response(&device);
+    }
+
+    match db::device_aliases::remove(&state.pool, &device).await {
+        Ok(true) => {
+            log::info!("Display name of {} removed", device);
+            let response = ApiResponse::success(device);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::<()>::error("Device has no display name");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to remove display name: {}", e);
+            let response = ApiResponse::<()>::error("Failed to remove display name");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+
+    async fn api_request(
+        state: &AppState,
+        method: &str,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        test_helpers::api_request(devices_routes(state.clone()), method, uri, body).await
+    }
+
+    #[tokio::test]
+    async fn test_set_list_and_remove_display_name() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let sunroom = Some(serde_json::json!({ "display_name": " Sunroom " }));
+
+        let (status, body) = api_request(&state, "PUT", "/Veranda/display-name", sunroom.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["display_name"], "Sunroom");
+
+        let (status, body) = api_request(&state, "GET", "/", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"], serde_json::json!([
+            { "device": "LivingRoom", "display_name": "LivingRoom", "endpoint": "http://127.0.0.1:9" },
+            { "device": "Veranda", "display_name": "Sunroom", "endpoint": "http://127.0.0.1:9" },
+        ]));
+
+        let (status, _) = api_request(&state, "DELETE", "/Veranda/display-name", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(db::device_aliases::get_all(&state.pool).await.unwrap().is_empty());
+        let (status, _) = api_request(&state, "DELETE", "/Veranda/display-name", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_display_name_conflicts() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        db::device_aliases::set(&state.pool, "LivingRoom", "Lounge").await.unwrap();
+
+        let name = |name: &str| Some(serde_json::json!({ "display_name": name }));
+        let (status, _) = api_request(&state, "PUT", "/Veranda/display-name", name("lounge")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = api_request(&state, "PUT", "/Veranda/display-name", name("livingroom")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = api_request(&state, "PUT", "/Veranda/display-name", name("  ")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = api_request(&state, "PUT", "/Attic/display-name", name("Attic")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = api_request(&state, "PUT", "/LivingRoom/display-name", name("LOUNGE")).await;
+        assert_eq!(status, StatusCode::OK, "a device can change the case of its own name");
+    }
+}
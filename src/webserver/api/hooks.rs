@@ -0,0 +1,315 @@
+//! Signed webhooks for phone automations
+//!
+//! `POST /api/hooks/away?state=on` turns away mode on or off, e.g. from a location automation on a
+//! phone, without handing the phone a key to the rest of the API. Each phone gets its own key ID and
+//! secret in `away_webhook.keys` and signs the request with HMAC-SHA256 over `<timestamp>.<state>`.
+//! Requests more than 5 minutes old are rejected and a signed request is only accepted once, so a
+//! captured request can't be replayed, and each key may only make `max_requests_per_minute`
+//! requests, including rejected ones.
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::Instant;
+use utoipa::{IntoParams, ToSchema};
+
+use super::limits::{RateLimiter, too_many_requests};
+use crate::{
+    ac_controller::away_mode,
+    app_state::AppState,
+    types::{ApiError, ApiResponse},
+};
+
+/// Header with the key ID from `away_webhook.keys`
+const KEY_HEADER: &str = "X-Webhook-Key";
+/// Header with the unix time in seconds the request was signed at
+const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// Header with the hex encoded HMAC-SHA256 of `<timestamp>.<state>`
+const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Signed requests older or further in the future than this are rejected
+const MAX_CLOCK_SKEW_SECS: i64 = 5 * 60;
+
+pub fn hooks_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/away", post(set_away_mode))
+        .with_state(HooksState {
+            app: state,
+            rate_limiter: Arc::default(),
+            seen_requests: Arc::default(),
+        })
+}
+
+#[derive(Clone)]
+struct HooksState {
+    app: AppState,
+    rate_limiter: Arc<RateLimiter>,
+    seen_requests: Arc<SeenRequests>,
+}
+
+/// Signed requests accepted within the clock skew window, keyed by key ID, timestamp and state
+/// A valid signature is unique to these, so a replay within the window is caught here
+#[derive(Default)]
+struct SeenRequests {
+    signed_at: Mutex<HashMap<(String, i64, bool), i64>>,
+}
+
+impl SeenRequests {
+    /// Record a signed request
+    /// Returns false if it was already accepted
+    fn record(&self, key_id: &str, timestamp: i64, enabled: bool, now: i64) -> bool {
+        let mut signed_at = self.signed_at.lock().unwrap();
+        // Requests outside the window are rejected by their timestamp, no need to remember them
+        signed_at.retain(|_, t| now.abs_diff(*t) <= MAX_CLOCK_SKEW_SECS.unsigned_abs());
+        signed_at.insert((key_id.to_string(), timestamp, enabled), timestamp).is_none()
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct AwayQuery {
+    /// "on" or "off"
+    state: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct AwayModeResponse {
+    away_mode: bool,
+}
+
+/// Check a hex encoded signature in constant time
+fn verify_signature(secret: &str, timestamp: &str, state: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature.trim()) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, state).as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn unauthorized(message: &str) -> Response {
+    (StatusCode::UNAUTHORIZED, Json(ApiError::error(message))).into_response()
+}
+
+/// POST /api/hooks/away?state=on
+/// Turns away mode on or off, requires a signature from a key in `away_webhook.keys`
+#[utoipa::path(
+    post,
+    path = "/api/hooks/away",
+    tag = "presence",
+    params(
+        AwayQuery,
+        ("X-Webhook-Key" = String, Header, description = "Key ID from `away_webhook.keys`"),
+        ("X-Webhook-Timestamp" = i64, Header, description = "Unix time in seconds the request was signed at"),
+        ("X-Webhook-Signature" = String, Header, description = "Hex encoded HMAC-SHA256 of `<timestamp>.<state>`"),
+    ),
+    responses(
+        (status = 200, body = ApiResponse<AwayModeResponse>),
+        (status = 400, description = "state isn't on or off", body = ApiError),
+        (status = 401, description = "Unknown key, expired timestamp, wrong or already used signature", body = ApiError),
+        (status = 403, description = "`away_webhook.keys` isn't configured", body = ApiError),
+        (status = 429, description = "The key is over `max_requests_per_minute`", body = ApiError),
+    )
+)]
+async fn set_away_mode(State(state): State<HooksState>, headers: HeaderMap, Query(query): Query<AwayQuery>) -> Response {
+    let config = state.app.config();
+    let webhook = &config.away_webhook;
+    if webhook.keys.is_empty() {
+        let response = ApiError::error("The away webhook requires away_webhook.keys to be configured");
+        return (StatusCode::FORBIDDEN, Json(response)).into_response();
+    }
+
+    let enabled = match query.state.as_deref() {
+        Some("on") => true,
+        Some("off") => false,
+        _ => {
+            let response = ApiError::error("state must be 'on' or 'off'");
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    let Some((key_id, secret)) = header_str(&headers, KEY_HEADER).and_then(|id| webhook.keys.get_key_value(id)) else {
+        log::warn!("Away webhook called with an unknown key");
+        return unauthorized("Unknown webhook key");
+    };
+
+    if let Err(retry_after) = state.rate_limiter.check(key_id, webhook.max_requests_per_minute, Instant::now()) {
+        log::warn!("Away webhook key '{}' is over its rate limit", key_id);
+        return too_many_requests(retry_after);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let timestamp = header_str(&headers, TIMESTAMP_HEADER).unwrap_or_default();
+    let Some(signed_at) = timestamp
+        .parse::<i64>()
+        .ok()
+        .filter(|t| now.abs_diff(*t) <= MAX_CLOCK_SKEW_SECS.unsigned_abs())
+    else {
+        return unauthorized("Missing or expired timestamp");
+    };
+
+    let state_param = if enabled { "on" } else { "off" };
+    let signature = header_str(&headers, SIGNATURE_HEADER).unwrap_or_default();
+    if !verify_signature(secret, timestamp, state_param, signature) {
+        log::warn!("Away webhook called with an invalid signature for key '{}'", key_id);
+        return unauthorized("Invalid signature");
+    }
+    if !state.seen_requests.record(key_id, signed_at, enabled, now) {
+        log::warn!("Away webhook called with an already used signature for key '{}'", key_id);
+        return unauthorized("Request was already used");
+    }
+
+    match state.app.away_mode.set_enabled(&state.app.pool, enabled).await {
+        Ok(changed) => {
+            log::info!("Away mode set to {} through the webhook by key '{}'", state_param, key_id);
+            if changed {
+                away_mode::apply_now(state.app.devices().all().to_vec());
+            }
+            let response = ApiResponse::success(AwayModeResponse { away_mode: enabled });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to store away mode: {}", e);
+            let response = ApiError::error("Failed to store away mode");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header;
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    /// Signature as a phone automation computes it
+    fn sign(secret: &str, timestamp: &str, state: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}.{}", timestamp, state).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn config_with_key(max_requests_per_minute: u32) -> crate::types::Config {
+        crate::types::Config {
+            // No devices, so nothing is evaluated after a change
+            ac_controller_endpoints: HashMap::new(),
+            away_webhook: crate::types::AwayWebhookConfig {
+                keys: [("phone".to_string(), "secret".to_string())].into_iter().collect(),
+                max_requests_per_minute,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn request(state: &str, key: &str, timestamp: i64, signature: &str) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri(format!("/away?state={}", state))
+            .header(KEY_HEADER, key)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    }
+
+    fn signed(state: &str) -> axum::http::Request<axum::body::Body> {
+        let timestamp = chrono::Utc::now().timestamp();
+        request(state, "phone", timestamp, &sign("secret", &timestamp.to_string(), state))
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signature = sign("secret", "1700000000", "on");
+        assert!(verify_signature("secret", "1700000000", "on", &signature));
+        assert!(!verify_signature("secret", "1700000000", "off", &signature));
+        assert!(!verify_signature("other", "1700000000", "on", &signature));
+        assert!(!verify_signature("secret", "1700000000", "on", "not hex"));
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_toggles_away_mode() {
+        let state = AppState::for_test(config_with_key(6)).await;
+        let routes = hooks_routes(state.clone());
+
+        let response = routes.clone().oneshot(signed("on")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(state.away_mode.is_enabled());
+
+        let response = routes.oneshot(signed("off")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!state.away_mode.is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_is_only_accepted_once() {
+        let state = AppState::for_test(config_with_key(6)).await;
+        let routes = hooks_routes(state.clone());
+        let now = chrono::Utc::now().timestamp();
+        let on = || request("on", "phone", now, &sign("secret", &now.to_string(), "on"));
+
+        let response = routes.clone().oneshot(on()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = routes.clone().oneshot(request("off", "phone", now, &sign("secret", &now.to_string(), "off"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Replaying the captured "on" request within the window doesn't turn away mode back on
+        let response = routes.oneshot(on()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(!state.away_mode.is_enabled());
+    }
+
+    #[test]
+    fn test_seen_requests_are_forgotten_after_the_window() {
+        let seen = SeenRequests::default();
+        assert!(seen.record("phone", 1_000, true, 1_000));
+        assert!(!seen.record("phone", 1_000, true, 1_000 + MAX_CLOCK_SKEW_SECS));
+        assert!(seen.record("phone", 1_000, false, 1_000));
+        assert!(seen.record("tablet", 1_000, true, 1_000));
+
+        assert!(seen.record("phone", 2_000, true, 2_000));
+        assert_eq!(seen.signed_at.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_requests() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let response = hooks_routes(state).oneshot(signed("on")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "no keys configured");
+
+        let state = AppState::for_test(config_with_key(3)).await;
+        let routes = hooks_routes(state.clone());
+        let now = chrono::Utc::now().timestamp();
+
+        let response = routes.clone().oneshot(request("on", "laptop", now, &sign("secret", &now.to_string(), "on"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "unknown key");
+
+        let stale = now - 10 * 60;
+        let response = routes.clone().oneshot(request("on", "phone", stale, &sign("secret", &stale.to_string(), "on"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "replayed request");
+
+        let response = routes.clone().oneshot(request("on", "phone", now, &sign("secret", &now.to_string(), "off"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "signature of another state");
+
+        let far_future = i64::MAX;
+        let response = routes.clone().oneshot(request("on", "phone", far_future, &sign("secret", &far_future.to_string(), "on"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "timestamp far in the future");
+
+        let response = routes.clone().oneshot(signed("on")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS, "rejected requests count too");
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        assert!(!state.away_mode.is_enabled());
+    }
+}
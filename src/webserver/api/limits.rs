@@ -0,0 +1,173 @@
+//! Request rate and size limits
+//!
+//! Every client IP may make `api_rate_limit_per_minute` API requests per minute, so a misbehaving
+//! dashboard tab polling in a loop can't starve the database pool for the controller. Request bodies
+//! are limited to `api_max_body_bytes`, except on the nodeset routes which have their own
+//! `nodeset_max_body_bytes`. Both limits are answered with a JSON error like every other endpoint.
+
+use axum::{
+    Json,
+    extract::{ConnectInfo, Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::{app_state::AppState, types::ApiError};
+
+/// Window the request limits apply to
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+/// Clients whose requests all left the window are forgotten once this many are tracked
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+/// Sliding window of recent request times per client, e.g. per IP or per webhook key
+#[derive(Default)]
+pub struct RateLimiter {
+    requests: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Count a request of a client
+    /// Returns how long to wait before the next request if the client is over its limit
+    pub fn check(&self, client: &str, max_per_window: u32, now: Instant) -> Result<(), Duration> {
+        let mut requests = self.requests.lock().unwrap();
+        if requests.len() >= MAX_TRACKED_CLIENTS {
+            requests.retain(|_, times| times.back().is_some_and(|t| now.duration_since(*t) < RATE_LIMIT_WINDOW));
+        }
+        let times = requests.entry(client.to_string()).or_default();
+        while times.front().is_some_and(|t| now.duration_since(*t) >= RATE_LIMIT_WINDOW) {
+            times.pop_front();
+        }
+        if times.len() >= max_per_window as usize {
+            let oldest = times.front().copied().unwrap_or(now);
+            return Err(RATE_LIMIT_WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+/// 429 response telling the client when to retry
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    let response = ApiError::error("Too many requests");
+    let retry_after = retry_after.as_secs().max(1).to_string();
+    (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, retry_after)], Json(response)).into_response()
+}
+
+/// State of the per-IP rate limit middleware
+#[derive(Clone)]
+pub struct RateLimitState {
+    app: AppState,
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitState {
+    pub fn new(app: AppState) -> Self {
+        Self { app, limiter: Arc::default() }
+    }
+}
+
+/// Reject requests of an IP over `api_rate_limit_per_minute`
+/// Requests without a known peer address, e.g. from tests, aren't limited
+pub async fn limit_requests_per_ip(State(state): State<RateLimitState>, request: Request, next: Next) -> Response {
+    let max_per_minute = state.app.config().api_rate_limit_per_minute;
+    let client = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+    if let Some(ip) = client.filter(|_| max_per_minute > 0)
+        && let Err(retry_after) = state.limiter.check(&ip.to_string(), max_per_minute, Instant::now())
+    {
+        log::debug!("{} is over the API rate limit, rejected {} {}", ip, request.method(), request.uri().path());
+        return too_many_requests(retry_after);
+    }
+    next.run(request).await
+}
+
+/// Replace axum's plain text response to a body over `api_max_body_bytes` with a JSON error
+pub async fn json_payload_too_large(State(max_body_bytes): State<usize>, request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE || is_json {
+        return response;
+    }
+    let response = ApiError::error(format!(
+        "Request body exceeds the configured limit of {} bytes (api_max_body_bytes)",
+        max_body_bytes
+    ));
+    (StatusCode::PAYLOAD_TOO_LARGE, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Bytes, extract::DefaultBodyLimit, middleware, routing::{get, post}};
+    use tower::ServiceExt;
+
+    fn request_from(ip: [u8; 4], method: &str, body: &'static str) -> axum::http::Request<axum::body::Body> {
+        let mut request = axum::http::Request::builder()
+            .method(method)
+            .uri("/")
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        request.extensions_mut().insert(ConnectInfo(SocketAddr::from((ip, 50000))));
+        request
+    }
+
+    #[test]
+    fn test_rate_limiter_is_per_client() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        assert!(limiter.check("phone", 2, now).is_ok());
+        assert!(limiter.check("phone", 2, now + Duration::from_secs(10)).is_ok());
+        assert_eq!(limiter.check("phone", 2, now + Duration::from_secs(20)), Err(Duration::from_secs(40)));
+        assert!(limiter.check("tablet", 2, now + Duration::from_secs(20)).is_ok());
+        assert!(limiter.check("phone", 2, now + Duration::from_secs(60)).is_ok(), "first request left the window");
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_limited_per_ip() {
+        let config = crate::types::Config {
+            api_rate_limit_per_minute: 2,
+            ..Default::default()
+        };
+        let state = RateLimitState::new(AppState::for_test(config).await);
+        let routes = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(state, limit_requests_per_ip));
+
+        for _ in 0..2 {
+            let response = routes.clone().oneshot(request_from([192, 168, 1, 20], "GET", "")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = routes.clone().oneshot(request_from([192, 168, 1, 20], "GET", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        let response = routes.oneshot(request_from([192, 168, 1, 21], "GET", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "other IPs have their own limit");
+    }
+
+    #[tokio::test]
+    async fn test_payload_too_large_is_json() {
+        let routes = Router::new()
+            .route("/", post(|body: Bytes| async move { body.len().to_string() }))
+            .layer(DefaultBodyLimit::max(8))
+            .layer(middleware::from_fn_with_state(8, json_payload_too_large));
+
+        let response = routes.clone().oneshot(request_from([127, 0, 0, 1], "POST", "small")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = routes.oneshot(request_from([127, 0, 0, 1], "POST", "far too large")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("api_max_body_bytes"));
+    }
+}
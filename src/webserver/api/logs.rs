@@ -0,0 +1,187 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use futures_util::{StreamExt, stream};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::IntoParams;
+
+use crate::{
+    app_state::AppState,
+    log_buffer::{self, LogEntry, LogFilter},
+    types::{ApiError, ApiResponse},
+};
+
+pub fn logs_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_logs))
+        .route("/stream", get(stream_logs))
+        .with_state(state)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LogsQuery {
+    /// Least severe level to include: error, warn, info, debug or trace
+    level: Option<String>,
+    /// Only records whose module path contains this, e.g. "ac_executor"
+    module: Option<String>,
+    /// Unix timestamp, only records logged at or after it
+    since: Option<i64>,
+}
+
+impl LogsQuery {
+    /// Err with the message of an unknown level
+    fn into_filter(self) -> Result<LogFilter, String> {
+        let min_level = match self.level.as_deref() {
+            Some(level) => match level.parse::<log::Level>() {
+                Ok(level) => Some(level.into()),
+                Err(_) => return Err(format!("Unknown level '{}', expected error, warn, info, debug or trace", level)),
+            },
+            None => None,
+        };
+        Ok(LogFilter {
+            min_level,
+            module: self.module.filter(|module| !module.is_empty()),
+            since_ms: self.since.map(|since| since.saturating_mul(1000)),
+        })
+    }
+}
+
+/// GET /api/logs?level=warn&module=ac_executor&since=1764547200
+/// Returns the recent log records kept in memory (Vec<LogEntry>), oldest first. Requires `admin_api_key`.
+#[utoipa::path(
+    get,
+    path = "/api/logs",
+    tag = "logs",
+    params(LogsQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<Vec<LogEntry>>),
+        (status = 400, description = "Unknown level", body = ApiError),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "`admin_api_key` isn't configured", body = ApiError),
+    )
+)]
+async fn get_logs(State(state): State<AppState>, headers: HeaderMap, Query(query): Query<LogsQuery>) -> Response {
+    if let Some(response) = super::admin::reject_admin_key(&headers, &state.config().admin_api_key, "Reading logs") {
+        return response;
+    }
+    let filter = match query.into_filter() {
+        Ok(filter) => filter,
+        Err(message) => return (StatusCode::BAD_REQUEST, Json(ApiError::error(message))).into_response(),
+    };
+    let response = ApiResponse::success(log_buffer::get_log_buffer().query(&filter));
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// GET /api/logs/stream?level=warn&module=ac_executor
+/// Server-sent events with every new log record matching the filter as JSON (LogEntry),
+/// `since` is ignored. Fetch `GET /api/logs` first for the records from before connecting.
+/// Requires `admin_api_key`.
+#[utoipa::path(
+    get,
+    path = "/api/logs/stream",
+    tag = "logs",
+    params(LogsQuery),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "`text/event-stream` of LogEntry records"),
+        (status = 400, description = "Unknown level", body = ApiError),
+        (status = 401, description = "Missing or wrong `admin_api_key`", body = ApiError),
+        (status = 403, description = "`admin_api_key` isn't configured", body = ApiError),
+    )
+)]
+async fn stream_logs(State(state): State<AppState>, headers: HeaderMap, Query(query): Query<LogsQuery>) -> Response {
+    if let Some(response) = super::admin::reject_admin_key(&headers, &state.config().admin_api_key, "Reading logs") {
+        return response;
+    }
+    let filter = match query.into_filter() {
+        Ok(filter) => LogFilter { since_ms: None, ..filter },
+        Err(message) => return (StatusCode::BAD_REQUEST, Json(ApiError::error(message))).into_response(),
+    };
+    let records = log_buffer::get_log_buffer().subscribe();
+
+    let events = stream::unfold(records, move |mut records| {
+        let filter = filter.clone();
+        async move {
+            loop {
+                match records.recv().await {
+                    Ok(entry) if filter.matches(&entry) => {
+                        let event = Event::default().id(entry.id.to_string()).json_data(&entry);
+                        return Some((event, records));
+                    }
+                    // Not logged, that would add a record for every record skipped
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    })
+    // Open streams would otherwise hold up the graceful shutdown
+    .take_until(crate::shutdown::requested());
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+
+    async fn get(state: &AppState, uri: &str, api_key: Option<&str>) -> (StatusCode, serde_json::Value) {
+        let mut request = axum::http::Request::builder().uri(uri);
+        if let Some(api_key) = api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        test_helpers::send_json(logs_routes(state.clone()), request.body(axum::body::Body::empty()).unwrap()).await
+    }
+
+    async fn admin_state() -> AppState {
+        AppState::for_test(crate::types::Config {
+            admin_api_key: "admin".to_string(),
+            ..Default::default()
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_logs_are_filtered() {
+        let state = admin_state().await;
+        let buffer = log_buffer::get_log_buffer();
+        buffer.push(log::Level::Info.into(), "power_control_center::logs_test", "started".to_string());
+        buffer.push(log::Level::Error.into(), "power_control_center::logs_test", "failed".to_string());
+
+        let (status, body) = get(&state, "/?level=WARN&module=logs_test", Some("admin")).await;
+        assert_eq!(status, StatusCode::OK);
+        let records = body["data"].as_array().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["level"], "error");
+        assert_eq!(records[0]["message"], "failed");
+
+        let (status, body) = get(&state, "/?level=loud", Some("admin")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+    }
+
+    #[tokio::test]
+    async fn test_logs_require_admin_key() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        for uri in ["/", "/stream"] {
+            assert_eq!(get(&state, uri, Some("anything")).await.0, StatusCode::FORBIDDEN, "{}", uri);
+        }
+
+        let state = admin_state().await;
+        for uri in ["/", "/stream"] {
+            assert_eq!(get(&state, uri, None).await.0, StatusCode::UNAUTHORIZED, "{}", uri);
+            assert_eq!(get(&state, uri, Some("wrong")).await.0, StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+    }
+}
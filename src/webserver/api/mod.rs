@@ -1,29 +1,193 @@
 mod ac;
+mod auth;
 mod pir;
 mod dashboard;
+mod devices;
+mod hooks;
+mod limits;
+mod logs;
+pub mod openapi;
 pub mod nodes;
+mod node_executions;
+pub mod nodeset_assignments;
+pub mod nodeset_calendar;
+mod nodeset_payload;
+pub mod nodeset_schedule;
+mod nodeset_tests;
+mod nodeset_transfer;
+mod occupancy;
+mod panel;
+mod presence;
+mod sensors;
 mod simulator;
+mod simulator_replay;
 mod cause_reasons;
 mod user_home;
+mod ups;
+mod demand_response;
+mod telemetry;
+mod admin;
+mod config;
+mod debug;
+mod weather;
+mod statistics;
+mod rpc;
+mod ws;
+#[cfg(test)]
+mod test_helpers;
 
+use crate::ac_controller::cycle_timing::{self, CycleTiming};
+use crate::app_state::AppState;
+use crate::device_requests::common::{CircuitBreakerStatus, get_circuit_breakers};
+use crate::device_requests::health::{self, EndpointHealthStatus, HealthStatus, get_health_monitor};
+use crate::device_requests::latency::{EndpointLatency, get_latency_tracker};
+use crate::startup_report::EnvironmentReport;
+use crate::types::ApiResponse;
 use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::Method,
+    middleware::{self, Next},
+    response::Response,
     routing::get,
     Router,
 };
 
 /// Build the API routes
-pub fn api_routes() -> Router {
+pub fn api_routes(state: AppState) -> Router {
+    let max_body_bytes = state.config().api_max_body_bytes;
+    let rate_limit = limits::RateLimitState::new(state.clone());
     Router::new()
         .route("/status", get(status_handler))
-        .nest("/ac", ac::ac_routes())
-        .nest("/pir", pir::pir_routes())
-        .nest("/dashboard", dashboard::dashboard_routes())
-        .nest("/nodes", nodes::nodes_routes())
-        .nest("/simulator", simulator::simulator_routes())
-        .nest("/cause-reasons", cause_reasons::cause_reasons_routes())
+        .route("/status/details", get(status_details_handler))
+        .route("/status/health", get(health_handler))
+        .route("/ws", get(ws::ws_handler))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .nest("/ac", ac::ac_routes(state.clone()))
+        .nest("/pir", pir::pir_routes(state.clone()))
+        .nest("/occupancy", occupancy::occupancy_routes(state.clone()))
+        .nest("/sensors", sensors::sensors_routes(state.clone()))
+        .nest("/dashboard", dashboard::dashboard_routes(state.clone()))
+        .nest("/devices", devices::devices_routes(state.clone()))
+        .nest("/nodes", nodes::nodes_routes(state.clone()))
+        .nest("/simulator", simulator::simulator_routes(state.clone()))
+        .nest("/cause-reasons", cause_reasons::cause_reasons_routes(state.clone()))
+        .nest("/statistics", statistics::statistics_routes(state.clone()))
+        .nest("/rpc", rpc::rpc_routes(state.clone()))
+        .nest("/panel", panel::panel_routes(state.clone()))
+        .nest("/presence", presence::presence_routes(state.clone()))
+        .nest("/config", config::config_routes(state.clone()))
+        .nest("/hooks", hooks::hooks_routes(state.clone()))
+        .nest("/debug", debug::debug_routes(state.clone()))
         .nest("/user-home", user_home::user_home_routes())
+        .nest("/ups", ups::ups_routes())
+        .nest("/demand-response", demand_response::demand_response_routes())
+        .nest("/telemetry", telemetry::telemetry_routes())
+        .nest("/logs", logs::logs_routes(state.clone()))
+        .nest("/admin", admin::admin_routes(state))
+        .nest("/weather", weather::weather_routes())
+        .layer(middleware::from_fn(wake_from_deep_idle))
+        // The nodeset routes override this with their own limit
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(middleware::from_fn_with_state(max_body_bytes, limits::json_payload_too_large))
+        .layer(middleware::from_fn_with_state(rate_limit, limits::limit_requests_per_ip))
 }
 
-async fn status_handler() -> axum::Json<crate::types::ApiResponse<&'static str>> {
-    axum::Json(crate::types::ApiResponse::success("API is running"))
+/// Wake the controller from deep idle on any request that changes something.
+/// GET requests are ignored so dashboard polling doesn't keep it awake.
+async fn wake_from_deep_idle(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        let reason = format!("{} {}", request.method(), request.uri().path());
+        crate::ac_controller::idle_state::get_idle_state().wake(&reason);
+    }
+    next.run(request).await
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct ApiStatus {
+    version: &'static str,
+    /// Whether a newer release is available (always false unless update checks are enabled)
+    update_available: bool,
+    /// Tag of the newer release, if any
+    latest_version: Option<String>,
+    /// Rolling p95 command latency per AC endpoint
+    endpoint_latency: Vec<EndpointLatency>,
+    /// Endpoints whose p95 command latency exceeds `command_latency_slo_ms`
+    slow_endpoints: Vec<EndpointLatency>,
+    /// Duration of the last control cycle and each device in it
+    last_cycle: Option<CycleTiming>,
+    /// Unix timestamp the last control cycle finished at, None before the first one finished
+    /// Older than the evaluation interval (longer in deep idle) when the control loop stalled
+    last_cycle_completed_at: Option<i64>,
+    /// Circuit breaker per upstream endpoint, open ones are not requested for now
+    circuit_breakers: Vec<CircuitBreakerStatus>,
+    /// Build, config and database in use
+    environment: EnvironmentReport,
+}
+
+/// GET /api/status
+/// Returns "API is running", kept as a plain string for existing clients
+#[utoipa::path(
+    get,
+    path = "/api/status",
+    tag = "status",
+    responses((status = 200, body = ApiResponse<String>))
+)]
+async fn status_handler() -> axum::Json<ApiResponse<&'static str>> {
+    axum::Json(ApiResponse::success("API is running"))
+}
+
+/// GET /api/status/details
+/// Returns the version, command latencies, circuit breakers and environment of the service
+#[utoipa::path(
+    get,
+    path = "/api/status/details",
+    tag = "status",
+    responses((status = 200, body = ApiResponse<ApiStatus>))
+)]
+async fn status_details_handler() -> axum::Json<ApiResponse<ApiStatus>> {
+    let latest_version = crate::updater::get_available_update();
+    let latency_tracker = get_latency_tracker();
+    let config = crate::config::get_config();
+    let environment = EnvironmentReport::collect(crate::db::get_pool().await, &config).await;
+    let last_cycle = cycle_timing::get_last_cycle();
+    axum::Json(ApiResponse::success(ApiStatus {
+        version: crate::updater::CURRENT_VERSION,
+        update_available: latest_version.is_some(),
+        latest_version,
+        endpoint_latency: latency_tracker.get_all(),
+        slow_endpoints: latency_tracker.get_slo_violations(config.command_latency_slo_ms),
+        last_cycle_completed_at: last_cycle.as_ref().map(|cycle| cycle.finished_at),
+        last_cycle,
+        circuit_breakers: get_circuit_breakers().get_all(),
+        environment,
+    }))
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct ApiHealth {
+    /// Worst status of all endpoints
+    status: HealthStatus,
+    /// Minutes an AC controller must be failing before an alert is sent
+    alert_after_minutes: u32,
+    /// Health per upstream endpoint, configured AC controllers that weren't requested yet are unknown
+    endpoints: Vec<EndpointHealthStatus>,
+}
+
+/// GET /api/status/health
+/// Returns the health of every upstream endpoint
+#[utoipa::path(
+    get,
+    path = "/api/status/health",
+    tag = "status",
+    responses((status = 200, body = ApiResponse<ApiHealth>))
+)]
+async fn health_handler() -> axum::Json<ApiResponse<ApiHealth>> {
+    let config = crate::config::get_config();
+    let ac_devices: Vec<String> = config.ac_controller_endpoints.keys().cloned().collect();
+    let endpoints = get_health_monitor().get_all(&ac_devices);
+    axum::Json(ApiResponse::success(ApiHealth {
+        status: health::overall_status(&endpoints),
+        alert_after_minutes: config.health_alerts.after_minutes,
+        endpoints,
+    }))
 }
@@ -0,0 +1,111 @@
+//! Nodeset execution history
+//!
+//! `GET /api/nodes/executions` returns the recorded outcome of recent nodeset executions, see
+//! `ac_controller::execution_history`.
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    ac_controller::cycle_snapshots::InputSnapshot,
+    app_state::AppState,
+    db,
+    nodes::ActionResult,
+    types::{ApiResponse, db_types},
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExecutionsQuery {
+    /// Only return executions of this device
+    pub device: Option<String>,
+    #[serde(default = "default_executions_limit")]
+    pub limit: i64,
+}
+
+fn default_executions_limit() -> i64 {
+    50
+}
+
+/// Outcome of one nodeset execution
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct ExecutionEntry {
+    pub device: String,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub executed_at: i64, // Unix timestamp in seconds
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub nodeset_id: i64,
+    /// Revision of the nodeset that ran, None for executions recorded before revisions existed
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub nodeset_revision: Option<i64>,
+    /// Terminal node reached, None if the execution didn't reach one
+    pub terminal_type: Option<String>,
+    /// Action parameters when an Execute Action node was reached
+    pub action: Option<ActionResult>,
+    /// Cause reason ID of the action or Do Nothing node
+    pub cause_reason: Option<String>,
+    pub error: Option<String>,
+    #[schema(value_type = BTreeMap<String, serde_json::Value>)]
+    pub inputs: InputSnapshot,
+    /// Warnings of the execution, e.g. disconnected nodes or an unknown cause reason
+    pub warnings: Vec<String>,
+    /// Executed in shadow mode, the action was not sent to the AC
+    pub is_shadow: bool,
+    /// How the state the AC reported after the command differed from it, empty if the command was
+    /// applied and None if it wasn't read back
+    pub command_discrepancies: Option<Vec<String>>,
+}
+
+impl From<db_types::ExecutionRecord> for ExecutionEntry {
+    fn from(record: db_types::ExecutionRecord) -> Self {
+        Self {
+            device: record.device_identifier,
+            executed_at: record.executed_at,
+            nodeset_id: record.nodeset_id,
+            nodeset_revision: record.nodeset_revision,
+            terminal_type: record.terminal_type,
+            action: record.action.and_then(|action| serde_json::from_str(&action).ok()),
+            cause_reason: record.cause_reason,
+            error: record.error,
+            inputs: serde_json::from_str(&record.inputs).unwrap_or_default(),
+            warnings: serde_json::from_str(&record.warnings).unwrap_or_default(),
+            is_shadow: record.is_shadow,
+            command_discrepancies: record
+                .command_discrepancies
+                .and_then(|discrepancies| serde_json::from_str(&discrepancies).ok()),
+        }
+    }
+}
+
+/// GET /api/nodes/executions?device=Veranda&limit=50
+/// Returns the most recent nodeset executions, newest first (Vec<ExecutionEntry>)
+#[utoipa::path(
+    get,
+    path = "/api/nodes/executions",
+    tag = "nodes",
+    params(ExecutionsQuery),
+    responses((status = 200, body = ApiResponse<Vec<ExecutionEntry>>))
+)]
+pub async fn list_executions(State(state): State<AppState>, Query(params): Query<ExecutionsQuery>) -> Response {
+    let limit = params.limit.clamp(1, 1000);
+
+    match db::execution_history::get_recent(&state.pool, params.device.as_deref(), limit).await {
+        Ok(records) => {
+            let executions: Vec<ExecutionEntry> = records.into_iter().map(ExecutionEntry::from).collect();
+            let response = ApiResponse::success(executions);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to fetch execution history: {}", e);
+            let response = ApiResponse::<()>::error("Failed to fetch executions");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
@@ -1,21 +1,26 @@
 use axum::{
     Json, Router,
-    extract::Path,
+    body::Bytes,
+    extract::{Path, State, rejection::BytesRejection},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
 };
 use serde::{Serialize, Deserialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use utoipa::ToSchema;
 
+use super::{node_executions, nodeset_assignments, nodeset_calendar, nodeset_payload, nodeset_schedule, nodeset_tests, nodeset_transfer};
 use crate::{
-    db,
-    nodes::{self, flow_nodes::MAX_EVALUATE_EVERY_MINUTES},
-    types::ApiResponse,
+    ac_controller::DeviceRegistry,
+    app_state::AppState,
+    db::{self, DbPool},
+    nodes::{self, flow_nodes::MAX_EVALUATE_EVERY_MINUTES, schema::{self, SchemaError}, variables::{self, Variables}},
+    types::{ApiError, ApiResponse, db_types},
 };
 
 /// ID for a new unsaved nodeset (not yet in database)
-const NEW_NODESET_ID: i64 = -1;
+pub const NEW_NODESET_ID: i64 = -1;
 /// ID for the default nodeset that cannot be modified or deleted
 pub const DEFAULT_NODESET_ID: i64 = 0;
 
@@ -44,10 +49,11 @@ fn is_terminal_node(node_type: &str) -> bool {
 
 /// Validates that a nodeset has exactly one Start node and at least one terminal node
 /// (Execute Action, Do Nothing, or Turn Off).
-/// Also validates the evaluate_every_minutes value on the Start node.
+/// Also validates the evaluate_every_minutes value on the Start node, and that every connection
+/// joins pins of compatible types by the node definitions (an Integer may feed a Float).
 /// Note: Disconnected nodes are allowed and will be treated as "Do Nothing" at runtime.
 /// Returns a validation result with counts and any errors
-pub fn validate_nodeset(nodes: &[serde_json::Value]) -> NodesetValidationResult {
+pub fn validate_nodeset(nodes: &[serde_json::Value], edges: &[serde_json::Value]) -> NodesetValidationResult {
     let mut start_count = 0;
     let mut terminal_count = 0;
     let mut errors = Vec::new();
@@ -96,6 +102,12 @@ pub fn validate_nodeset(nodes: &[serde_json::Value]) -> NodesetValidationResult
         errors.push("Profile must have at least one terminal node (Execute Action, Do Nothing, or Turn Off)".to_string());
     }
 
+    errors.extend(
+        nodes::find_type_mismatches(nodes, edges)
+            .into_iter()
+            .map(|mismatch| format!("Incompatible connection: {}", mismatch.message)),
+    );
+
     NodesetValidationResult {
         is_valid: start_count == 1 && terminal_count >= 1 && errors.is_empty(),
         start_count,
@@ -104,13 +116,154 @@ pub fn validate_nodeset(nodes: &[serde_json::Value]) -> NodesetValidationResult
     }
 }
 
-/// Gets node definitions enriched with cause reasons from the database.
-/// This ensures CauseReason nodes have their dropdown options populated.
-async fn get_enriched_node_definitions() -> Vec<nodes::NodeDefinition> {
+/// Validates that a nodeset works for every configured device before it becomes active.
+/// Device nodes must select one of `devices`, and a dry run with default inputs must
+/// complete without errors for each device.
+/// Returns a list of errors (empty if valid)
+pub fn validate_nodeset_for_devices(
+    nodes: &[serde_json::Value],
+    edges: &[serde_json::Value],
+    devices: &[&str],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for node in nodes {
+        let data = node.get("data");
+        let node_type = data
+            .and_then(|d| d.get("definition"))
+            .and_then(|def| def.get("node_type"))
+            .and_then(|nt| nt.as_str());
+        if node_type != Some(nodes::execution::NODE_TYPE_DEVICE) {
+            continue;
+        }
+
+        let node_id = node.get("id").and_then(|id| id.as_str()).unwrap_or("unknown");
+        match data.and_then(|d| d.get("enumValue")).and_then(|v| v.as_str()) {
+            Some(device) if devices.contains(&device) => {}
+            Some(device) if !device.is_empty() => {
+                errors.push(format!("Device node '{}' uses unknown device '{}'", node_id, device));
+            }
+            _ => errors.push(format!("Device node '{}' has no device selected", node_id)),
+        }
+    }
+
+    for device in devices {
+        let inputs = nodes::ExecutionInputs {
+            device: device.to_string(),
+            ..Default::default()
+        };
+        let result = match nodes::NodesetExecutor::new(nodes, edges, inputs) {
+            Ok(mut executor) => executor.execute(),
+            Err(e) => {
+                errors.push(e.to_string());
+                break;
+            }
+        };
+        if let Some(error) = result.error {
+            errors.push(format!("Fails for device {}: {}", device, error));
+        }
+    }
+
+    errors
+}
+
+/// Get the IDs of the Cause Reason nodes and the cause reason ID each one selects
+fn cause_reason_references(nodes: &[serde_json::Value]) -> Vec<(&str, &str)> {
+    nodes
+        .iter()
+        .filter(|node| {
+            node.get("data")
+                .and_then(|d| d.get("definition"))
+                .and_then(|def| def.get("node_type"))
+                .and_then(|nt| nt.as_str())
+                == Some(nodes::execution::NODE_TYPE_CAUSE_REASON)
+        })
+        .map(|node| {
+            let node_id = node.get("id").and_then(|id| id.as_str()).unwrap_or("unknown");
+            let cause_id = node
+                .get("data")
+                .and_then(|d| d.get("enumValue"))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            (node_id, cause_id)
+        })
+        .collect()
+}
+
+/// Check that every cause reason referenced by the nodeset still exists.
+/// `existing` holds the IDs and labels of the cause reasons, see `db::cause_reasons::known_references`.
+/// Disconnected Cause Reason nodes are ignored since they never affect execution.
+/// Returns one error per Cause Reason node that references a missing cause (empty if valid)
+pub fn find_missing_cause_reasons(
+    nodes: &[serde_json::Value],
+    edges: &[serde_json::Value],
+    existing_ids: &HashSet<String>,
+) -> Vec<String> {
+    let connected: HashSet<&str> = edges
+        .iter()
+        .filter_map(|edge| edge.get("source").and_then(|s| s.as_str()))
+        .collect();
+
+    cause_reason_references(nodes)
+        .into_iter()
+        .filter(|(node_id, cause_id)| connected.contains(node_id) && !existing_ids.contains(*cause_id))
+        .map(|(node_id, cause_id)| {
+            if cause_id.is_empty() {
+                format!("Cause Reason node '{}' has no cause selected", node_id)
+            } else {
+                format!("Cause Reason node '{}' uses cause reason {} which no longer exists", node_id, cause_id)
+            }
+        })
+        .collect()
+}
+
+/// Point every Cause Reason node that selects `from_id` to `to_id` instead.
+/// Returns the number of nodes changed
+pub fn remap_cause_reason(nodes: &mut [serde_json::Value], from_id: &str, to_id: &str) -> usize {
+    let mut changed = 0;
+    for node in nodes.iter_mut() {
+        let Some(data) = node.get_mut("data") else { continue };
+        let is_cause_reason_node = data
+            .get("definition")
+            .and_then(|def| def.get("node_type"))
+            .and_then(|nt| nt.as_str())
+            == Some(nodes::execution::NODE_TYPE_CAUSE_REASON);
+        if is_cause_reason_node && data.get("enumValue").and_then(|v| v.as_str()) == Some(from_id) {
+            data["enumValue"] = serde_json::Value::String(to_id.to_string());
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// Gets node definitions enriched with cause reasons from the database and devices from the registry.
+/// This ensures CauseReason and Device dropdowns have their options populated.
+async fn get_enriched_node_definitions(pool: &DbPool, devices: &DeviceRegistry) -> Vec<nodes::NodeDefinition> {
     let mut definitions = nodes::get_all_node_definitions();
+
+    // Device selections (Device node, Start and PIR Detection device ports) list the configured devices,
+    // zone and sensor selections (PIR Zone, PIR Sensor and PIR Detection ports) the configured PIR zones and sensors
+    let device_type = nodes::ValueType::Enum(devices.names());
+    let zone_type = nodes::ValueType::Enum(devices.pir_zone_names().to_vec());
+    let sensor_type = nodes::ValueType::Enum(devices.pir_sensor_names().to_vec());
+    for definition in definitions.iter_mut() {
+        let outputs = definition.outputs.iter_mut().map(|output| (&output.id, &mut output.value_type));
+        let inputs = definition.inputs.iter_mut().map(|input| (&input.id, &mut input.value_type));
+        for (id, value_type) in outputs.chain(inputs) {
+            if !matches!(value_type, nodes::ValueType::Enum(_)) {
+                continue;
+            }
+            match id.as_str() {
+                "device" => *value_type = device_type.clone(),
+                "zone" => *value_type = zone_type.clone(),
+                "sensor" => *value_type = sensor_type.clone(),
+                _ => {}
+            }
+        }
+    }
     
     // Load cause reasons from database and inject them into node definitions
-    if let Ok(cause_reasons) = db::cause_reasons::get_all(false).await {
+    if let Ok(cause_reasons) = db::cause_reasons::get_all(pool, false).await {
         let options: Vec<nodes::EnumOption> = cause_reasons.iter().map(|cr| {
             nodes::EnumOption {
                 id: cr.id.to_string(),
@@ -154,8 +307,12 @@ async fn get_enriched_node_definitions() -> Vec<nodes::NodeDefinition> {
 /// - Removes nodes whose type no longer exists (returns list of removed node IDs)
 /// 
 /// Returns tuple of (updated_nodes, removed_node_ids)
-async fn update_node_definitions(nodes: Vec<serde_json::Value>) -> (Vec<serde_json::Value>, Vec<String>) {
-    let definitions = get_enriched_node_definitions().await;
+pub async fn update_node_definitions(
+    pool: &DbPool,
+    devices: &DeviceRegistry,
+    nodes: Vec<serde_json::Value>,
+) -> (Vec<serde_json::Value>, Vec<String>) {
+    let definitions = get_enriched_node_definitions(pool, devices).await;
     update_node_definitions_with_defs(nodes, definitions)
 }
 
@@ -214,7 +371,7 @@ fn update_node_definitions_with_defs(
 }
 
 /// Removes edges that reference removed nodes
-fn remove_orphaned_edges(edges: Vec<serde_json::Value>, removed_node_ids: &[String]) -> Vec<serde_json::Value> {
+pub fn remove_orphaned_edges(edges: Vec<serde_json::Value>, removed_node_ids: &[String]) -> Vec<serde_json::Value> {
     if removed_node_ids.is_empty() {
         return edges;
     }
@@ -233,63 +390,179 @@ fn remove_orphaned_edges(edges: Vec<serde_json::Value>, removed_node_ids: &[Stri
         .collect()
 }
 
-pub fn nodes_routes() -> Router {
+pub fn nodes_routes(state: AppState) -> Router {
     Router::new()
         // Legacy endpoint for backwards compatibility - returns active nodeset configuration
         .route("/configuration", get(get_node_configuration))
         // Nodeset management endpoints
+        .route("/analyze", post(analyze_nodeset))
         .route("/nodesets", get(list_nodesets))
         .route("/nodesets", post(create_nodeset))
+        .route("/nodesets/import", post(nodeset_transfer::import_nodeset))
         .route("/nodesets/active", get(get_active_nodeset))
         .route("/nodesets/active/:id", put(set_active_nodeset))
+        .route("/nodesets/assignments", get(nodeset_assignments::list_assignments))
+        .route("/nodesets/assignments/:device", delete(nodeset_assignments::unassign_nodeset))
+        .route(
+            "/nodesets/assignments/:device/interval",
+            put(nodeset_assignments::set_interval).delete(nodeset_assignments::clear_interval),
+        )
+        .route("/nodesets/assignments/:device/:id", put(nodeset_assignments::assign_nodeset))
         .route("/nodesets/:id", get(get_nodeset))
         .route("/nodesets/:id", put(update_nodeset))
         .route("/nodesets/:id", delete(delete_nodeset))
+        .route("/nodesets/:id/activate-at", post(nodeset_schedule::schedule_activation))
+        .route("/nodesets/:id/export", get(nodeset_transfer::export_nodeset))
+        .route("/nodesets/:id/interface", get(get_nodeset_interface))
+        .route("/nodesets/:id/variables", patch(update_nodeset_variables))
+        .route("/nodesets/:id/revisions", get(list_nodeset_revisions))
+        .route("/nodesets/:id/revisions/:revision/restore", post(restore_nodeset_revision))
+        .route("/nodesets/:id/run-tests", post(nodeset_tests::run_tests))
+        .route("/scheduled-activations", get(nodeset_schedule::list_scheduled_activations))
+        .route("/scheduled-activations/:id", delete(nodeset_schedule::cancel_scheduled_activation))
+        .route("/schedules", get(nodeset_calendar::list_schedules))
+        .route("/schedules", post(nodeset_calendar::create_schedule))
+        .route("/schedules/:id", put(nodeset_calendar::update_schedule))
+        .route("/schedules/:id", delete(nodeset_calendar::delete_schedule))
         .route("/definitions", get(get_node_definitions))
+        .route("/executions", get(node_executions::list_executions))
+        .route("/payload-metrics", get(get_payload_metrics))
+        .layer(nodeset_payload::body_limit_layer(state.config().nodeset_max_body_bytes))
+        .with_state(state)
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct NodeConfiguration {
+    /// Schema version the nodeset was saved with (0 for unversioned legacy nodesets)
+    #[serde(default)]
+    pub schema_version: u32,
     pub nodes: Vec<serde_json::Value>,
     pub edges: Vec<serde_json::Value>,
+    /// Named constants read by Variable nodes
+    #[serde(default)]
+    pub variables: Variables,
+}
+
+impl NodeConfiguration {
+    /// Create a configuration stamped with the current schema version
+    pub fn new(nodes: Vec<serde_json::Value>, edges: Vec<serde_json::Value>) -> Self {
+        Self {
+            schema_version: schema::CURRENT_SCHEMA_VERSION,
+            nodes,
+            edges,
+            variables: Variables::new(),
+        }
+    }
+
+    pub fn with_variables(mut self, variables: Variables) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Nodes with the variable values resolved into the Variable nodes, ready to execute
+    pub fn resolved_nodes(&self) -> Vec<serde_json::Value> {
+        let mut nodes = self.nodes.clone();
+        variables::resolve_variables(&mut nodes, &self.variables);
+        nodes
+    }
+
+    /// Parse stored nodeset JSON, upgrading it to the current schema version
+    pub fn from_stored_json(json: &str) -> Result<Self, SchemaError> {
+        let doc = schema::parse_nodeset_json(json)?;
+        serde_json::from_value(doc).map_err(|e| SchemaError::Invalid(e.to_string()))
+    }
+}
+
+/// Build the error response for a stored nodeset that could not be loaded
+/// Nodesets from a newer version are reported as a conflict with a clear message
+pub fn schema_error_response(e: SchemaError, context: &str) -> Response {
+    match e {
+        SchemaError::NewerVersion { .. } => {
+            log::warn!("Refusing to load {}: {}", context, e);
+            let response = ApiResponse::<()>::error(e.to_string());
+            (StatusCode::CONFLICT, Json(response)).into_response()
+        }
+        SchemaError::Invalid(_) => {
+            log::error!("Failed to parse {}: {}", context, e);
+            let response = ApiResponse::<()>::error(format!("Failed to parse {}", context));
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// Build an error response for a failed database operation.
+/// Lock conflicts with a concurrent nodeset change are reported as 409 so the client can retry.
+pub fn db_error_response(e: sqlx::Error, message: &str) -> Response {
+    if db::is_lock_conflict(&e) {
+        let response = ApiResponse::<()>::error(format!("{}: the nodesets are being modified by another request, please retry", message));
+        return (StatusCode::CONFLICT, Json(response)).into_response();
+    }
+    let response = ApiResponse::<()>::error(message);
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
 }
 
 /// Nodeset with id and name
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct Nodeset {
     pub id: i64,
     pub name: String,
     pub nodes: Vec<serde_json::Value>,
     pub edges: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub variables: Variables,
 }
 
 /// Nodeset summary for list view
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct NodesetSummary {
     pub id: i64,
     pub name: String,
 }
 
 /// Request for creating a new nodeset
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateNodesetRequest {
     pub name: String,
     pub nodes: Vec<serde_json::Value>,
     pub edges: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub variables: Variables,
 }
 
 /// Request for updating a nodeset
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct UpdateNodesetRequest {
     pub name: Option<String>,
     pub nodes: Vec<serde_json::Value>,
     pub edges: Vec<serde_json::Value>,
+    /// Replaces the variables when provided, otherwise the stored variables are kept
+    #[serde(default)]
+    pub variables: Option<Variables>,
 }
 
+/// Request for analyzing a nodeset as it is in the editor
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AnalyzeNodesetRequest {
+    pub nodes: Vec<serde_json::Value>,
+    pub edges: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub variables: Variables,
+}
+
+/// Request for changing nodeset variables
+/// A number sets the variable, null removes it. Variables not mentioned are kept.
+pub type UpdateVariablesRequest = BTreeMap<String, Option<f64>>;
+
 /// GET /api/nodes/configuration
 /// Returns the current active nodeset configuration (backwards compatibility)
-async fn get_node_configuration() -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(
+    get,
+    path = "/api/nodes/configuration",
+    tag = "nodes",
+    responses((status = 200, description = "Nodes and edges of the active nodeset", body = ApiResponse<NodeConfiguration>))
+)]
+async fn get_node_configuration(State(state): State<AppState>) -> Response {
+    let pool = &state.pool;
     
     // Get the active nodeset id
     let active_id = match get_active_nodeset_id(pool).await {
@@ -303,7 +576,7 @@ async fn get_node_configuration() -> Response {
     
     // Fetch the nodeset
     let result = sqlx::query_as::<_, (String,)>(
-        "SELECT node_json FROM nodesets WHERE id = ?"
+        "SELECT node_json FROM nodesets WHERE id = $1"
     )
     .bind(active_id)
     .fetch_optional(pool)
@@ -311,28 +584,23 @@ async fn get_node_configuration() -> Response {
     
     match result {
         Ok(Some(record)) => {
-            match serde_json::from_str::<NodeConfiguration>(&record.0) {
+            match NodeConfiguration::from_stored_json(&record.0) {
                 Ok(config) => {
                     // Update node definitions to current version
-                    let (updated_nodes, removed_node_ids) = update_node_definitions(config.nodes).await;
+                    let (updated_nodes, removed_node_ids) = update_node_definitions(pool, &state.devices(), config.nodes).await;
                     let updated_edges = remove_orphaned_edges(config.edges, &removed_node_ids);
                     
-                    let updated_config = NodeConfiguration {
-                        nodes: updated_nodes,
-                        edges: updated_edges,
-                    };
+                    let updated_config = NodeConfiguration::new(updated_nodes, updated_edges).with_variables(config.variables);
                     let response = ApiResponse::success(updated_config);
                     (StatusCode::OK, Json(response)).into_response()
                 }
                 Err(e) => {
-                    log::error!("Failed to parse node configuration: {}", e);
-                    let response = ApiResponse::<()>::error("Failed to parse node configuration");
-                    (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+                    schema_error_response(e, "node configuration")
                 }
             }
         }
         Ok(None) => {
-            let response = ApiResponse::success(NodeConfiguration::default());
+            let response = ApiResponse::success(NodeConfiguration::new(vec![], vec![]));
             (StatusCode::OK, Json(response)).into_response()
         }
         Err(e) => {
@@ -345,8 +613,9 @@ async fn get_node_configuration() -> Response {
 
 /// GET /api/nodes/nodesets
 /// Returns a list of all nodesets
-async fn list_nodesets() -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(get, path = "/api/nodes/nodesets", tag = "nodes", responses((status = 200, body = ApiResponse<Vec<NodesetSummary>>)))]
+async fn list_nodesets(State(state): State<AppState>) -> Response {
+    let pool = &state.pool;
     
     let result = sqlx::query_as::<_, (i64, String)>(
         "SELECT id, name FROM nodesets ORDER BY id"
@@ -373,11 +642,22 @@ async fn list_nodesets() -> Response {
 
 /// GET /api/nodes/nodesets/:id
 /// Returns a specific nodeset by id
-async fn get_nodeset(Path(id): Path<i64>) -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(
+    get,
+    path = "/api/nodes/nodesets/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, body = ApiResponse<Nodeset>),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+        (status = 409, description = "Saved by a newer version", body = ApiError),
+    )
+)]
+async fn get_nodeset(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let pool = &state.pool;
     
     let result = sqlx::query_as::<_, (i64, String, String)>(
-        "SELECT id, name, node_json FROM nodesets WHERE id = ?"
+        "SELECT id, name, node_json FROM nodesets WHERE id = $1"
     )
     .bind(id)
     .fetch_optional(pool)
@@ -385,10 +665,10 @@ async fn get_nodeset(Path(id): Path<i64>) -> Response {
     
     match result {
         Ok(Some((id, name, node_json))) => {
-            match serde_json::from_str::<NodeConfiguration>(&node_json) {
+            match NodeConfiguration::from_stored_json(&node_json) {
                 Ok(config) => {
                     // Update node definitions to current version
-                    let (updated_nodes, removed_node_ids) = update_node_definitions(config.nodes).await;
+                    let (updated_nodes, removed_node_ids) = update_node_definitions(pool, &state.devices(), config.nodes).await;
                     let updated_edges = remove_orphaned_edges(config.edges, &removed_node_ids);
                     
                     let nodeset = Nodeset {
@@ -396,14 +676,13 @@ async fn get_nodeset(Path(id): Path<i64>) -> Response {
                         name,
                         nodes: updated_nodes,
                         edges: updated_edges,
+                        variables: config.variables,
                     };
                     let response = ApiResponse::success(nodeset);
                     (StatusCode::OK, Json(response)).into_response()
                 }
                 Err(e) => {
-                    log::error!("Failed to parse nodeset configuration: {}", e);
-                    let response = ApiResponse::<()>::error("Failed to parse nodeset configuration");
-                    (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+                    schema_error_response(e, "nodeset configuration")
                 }
             }
         }
@@ -419,23 +698,215 @@ async fn get_nodeset(Path(id): Path<i64>) -> Response {
     }
 }
 
+/// GET /api/nodes/nodesets/:id/interface
+/// Returns the input and output pins a Call Profile node shows for the nodeset
+#[utoipa::path(
+    get,
+    path = "/api/nodes/nodesets/{id}/interface",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, body = ApiResponse<nodes::calls::NodesetInterface>),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+async fn get_nodeset_interface(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let result = sqlx::query_as::<_, (String,)>("SELECT node_json FROM nodesets WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await;
+
+    match result {
+        Ok(Some((node_json,))) => match NodeConfiguration::from_stored_json(&node_json) {
+            Ok(config) => {
+                let response = ApiResponse::success(nodes::calls::interface(&config.nodes));
+                (StatusCode::OK, Json(response)).into_response()
+            }
+            Err(e) => schema_error_response(e, "nodeset configuration"),
+        },
+        Ok(None) => {
+            let response = ApiResponse::<()>::error("Nodeset not found");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset: {}", e);
+            db_error_response(e, "Failed to fetch nodeset")
+        }
+    }
+}
+
+/// With `strict_cause_reasons` enabled, reject nodesets that select cause reasons that don't exist
+/// Returns the error response, or None if the nodeset may be saved
+pub async fn reject_unknown_cause_reasons(
+    state: &AppState,
+    nodes: &[serde_json::Value],
+    edges: &[serde_json::Value],
+) -> Option<Response> {
+    if !state.config().strict_cause_reasons {
+        return None;
+    }
+
+    let cause_reasons = match db::cause_reasons::get_all(&state.pool, true).await {
+        Ok(cause_reasons) => cause_reasons,
+        Err(e) => return Some(db_error_response(e, "Failed to load cause reasons")),
+    };
+    let errors = find_missing_cause_reasons(nodes, edges, &db::cause_reasons::known_references(&cause_reasons));
+    if errors.is_empty() {
+        return None;
+    }
+
+    let response = ApiResponse::<()>::error(format!("Unknown cause reasons: {}", errors.join("; ")));
+    Some((StatusCode::BAD_REQUEST, Json(response)).into_response())
+}
+
+/// Reject nodesets whose Call Profile nodes can't be expanded, e.g. because they call a missing
+/// profile or the calls form a cycle. `nodeset_id` is None for nodesets that aren't saved yet.
+/// Returns the error response, or None if the nodeset may be saved
+pub async fn reject_invalid_calls(
+    state: &AppState,
+    nodeset_id: Option<i64>,
+    nodes: &[serde_json::Value],
+    edges: &[serde_json::Value],
+) -> Option<Response> {
+    if !nodes::calls::has_calls(nodes) {
+        return None;
+    }
+
+    let library = match db::nodesets::load_call_library(&state.pool).await {
+        Ok(library) => library,
+        Err(e) => return Some(db_error_response(e, "Failed to load called profiles")),
+    };
+    let error = nodes::calls::expand_calls(nodeset_id, nodes, edges, &library).err()?;
+    let response = ApiResponse::<()>::error(format!("Invalid profile calls: {}", error));
+    Some((StatusCode::BAD_REQUEST, Json(response)).into_response())
+}
+
+/// Reject a change to a called nodeset that removes pins its callers have connected, the edges
+/// to those pins would be dropped when the callers run
+/// Returns the error response, or None if the nodeset may be saved
+async fn reject_broken_callers(
+    conn: &mut crate::db::DbConnection,
+    nodeset_id: i64,
+    nodes: &[serde_json::Value],
+) -> Option<Response> {
+    let callers = match db::nodesets::get_callers(&mut *conn, nodeset_id).await {
+        Ok(callers) => callers,
+        Err(e) => return Some(db_error_response(e, "Failed to load calling profiles")),
+    };
+    let interface = nodes::calls::interface(nodes);
+    let broken: Vec<String> = callers
+        .iter()
+        .filter_map(|(name, caller)| {
+            let pins = nodes::calls::missing_pins(&caller.nodes, &caller.edges, nodeset_id, &interface);
+            (!pins.is_empty()).then(|| format!("{} ({})", name, pins.join(", ")))
+        })
+        .collect();
+    if broken.is_empty() {
+        return None;
+    }
+    let response = ApiResponse::<()>::error(format!(
+        "Cannot remove pins connected in the calling profiles {}. Please disconnect them first.",
+        broken.join(", ")
+    ));
+    Some((StatusCode::BAD_REQUEST, Json(response)).into_response())
+}
+
+/// Reject variables with an invalid name or a non-finite value
+/// Returns the error response, or None if the variables may be saved
+fn reject_invalid_variables(variables: &Variables) -> Option<Response> {
+    let errors = variables::validate_variables(variables);
+    if errors.is_empty() {
+        return None;
+    }
+    let response = ApiResponse::<()>::error(format!("Invalid variables: {}", errors.join("; ")));
+    Some((StatusCode::BAD_REQUEST, Json(response)).into_response())
+}
+
+/// Parse the variables extracted from stored nodeset JSON, None for nodesets saved before variables existed
+fn parse_stored_variables(json: Option<&str>) -> Variables {
+    json.and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+}
+
+/// Record the stored content of a nodeset as its next revision
+/// Returns the new revision number
+pub async fn record_revision(
+    conn: &mut crate::db::DbConnection,
+    config: &crate::types::Config,
+    id: i64,
+) -> Result<i64, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp();
+    db::nodeset_revisions::record(conn, id, config.nodeset_revisions_kept, now).await
+}
+
+/// Insert a new nodeset with its first revision
+/// Returns the id of the new nodeset
+pub async fn insert_nodeset(
+    pool: &DbPool,
+    config: &crate::types::Config,
+    name: &str,
+    node_json: &str,
+) -> Result<i64, sqlx::Error> {
+    let mut tx = db::begin_write(pool).await?;
+    let (id,) = sqlx::query_as::<_, (i64,)>("INSERT INTO nodesets (name, node_json) VALUES ($1, $2) RETURNING id")
+        .bind(name)
+        .bind(node_json)
+        .fetch_one(&mut *tx)
+        .await?;
+    record_revision(&mut tx, config, id).await?;
+    tx.commit().await?;
+    Ok(id)
+}
+
 /// POST /api/nodes/nodesets
 /// Creates a new nodeset
-async fn create_nodeset(Json(request): Json<CreateNodesetRequest>) -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(
+    post,
+    path = "/api/nodes/nodesets",
+    tag = "nodes",
+    request_body = CreateNodesetRequest,
+    responses(
+        (status = 201, body = ApiResponse<Nodeset>),
+        (status = 400, description = "Invalid nodeset, variables, calls or cause reasons", body = ApiError),
+        (status = 413, description = "Body over `nodeset_max_body_bytes`", body = ApiError),
+    )
+)]
+async fn create_nodeset(State(state): State<AppState>, body: Result<Bytes, BytesRejection>) -> Response {
+    let request: CreateNodesetRequest = match nodeset_payload::parse_json_body(state.config().nodeset_max_body_bytes, body).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let pool = &state.pool;
     
     // Validate name is not empty
     if request.name.trim().is_empty() {
         let response = ApiResponse::<()>::error("Nodeset name cannot be empty");
         return (StatusCode::BAD_REQUEST, Json(response)).into_response();
     }
+
+    if let Some(response) = reject_invalid_variables(&request.variables) {
+        return response;
+    }
+
+    if let Some(response) = reject_unknown_cause_reasons(&state, &request.nodes, &request.edges).await {
+        return response;
+    }
+
+    if let Some(response) = reject_invalid_calls(&state, None, &request.nodes, &request.edges).await {
+        return response;
+    }
     
-    // Serialize the configuration
-    let config = NodeConfiguration {
-        nodes: request.nodes.clone(),
-        edges: request.edges.clone(),
+    // Serialize the configuration off the async runtime
+    let serialized = nodeset_payload::run_blocking(move || {
+        let config = NodeConfiguration::new(request.nodes.clone(), request.edges.clone()).with_variables(request.variables.clone());
+        let json_result = serde_json::to_string(&config);
+        (json_result, request)
+    })
+    .await;
+    let (json_result, request) = match serialized {
+        Ok(result) => result,
+        Err(response) => return response,
     };
-    let json_str = match serde_json::to_string(&config) {
+    let json_str = match json_result {
         Ok(s) => s,
         Err(e) => {
             log::error!("Failed to serialize nodeset configuration: {}", e);
@@ -444,40 +915,52 @@ async fn create_nodeset(Json(request): Json<CreateNodesetRequest>) -> Response {
         }
     };
     
-    // Insert the new nodeset
-    let result = sqlx::query(
-        "INSERT INTO nodesets (name, node_json) VALUES (?, ?)"
-    )
-    .bind(&request.name)
-    .bind(&json_str)
-    .execute(pool)
-    .await;
+    // Insert the new nodeset with its first revision
+    let result = insert_nodeset(pool, &state.config(), &request.name, &json_str).await;
     
     match result {
-        Ok(query_result) => {
-            let new_id = query_result.last_insert_rowid();
+        Ok(new_id) => {
             log::info!("Nodeset created with id {}", new_id);
             let nodeset = Nodeset {
                 id: new_id,
                 name: request.name,
                 nodes: request.nodes,
                 edges: request.edges,
+                variables: request.variables,
             };
             let response = ApiResponse::success(nodeset);
             (StatusCode::CREATED, Json(response)).into_response()
         }
         Err(e) => {
             log::error!("Failed to create nodeset: {}", e);
-            let response = ApiResponse::<()>::error("Failed to create nodeset");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+            db_error_response(e, "Failed to create nodeset")
         }
     }
 }
 
 /// PUT /api/nodes/nodesets/:id
 /// Updates an existing nodeset
-async fn update_nodeset(Path(id): Path<i64>, Json(request): Json<UpdateNodesetRequest>) -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(
+    put,
+    path = "/api/nodes/nodesets/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    request_body = UpdateNodesetRequest,
+    responses(
+        (status = 200, body = ApiResponse<Nodeset>),
+        (status = 400, description = "Invalid nodeset, variables, calls or cause reasons", body = ApiError),
+        (status = 403, description = "The default nodeset can't be modified", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+        (status = 409, description = "Modified by a concurrent request, retry", body = ApiError),
+        (status = 413, description = "Body over `nodeset_max_body_bytes`", body = ApiError),
+    )
+)]
+async fn update_nodeset(State(state): State<AppState>, Path(id): Path<i64>, body: Result<Bytes, BytesRejection>) -> Response {
+    let request: UpdateNodesetRequest = match nodeset_payload::parse_json_body(state.config().nodeset_max_body_bytes, body).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let pool = &state.pool;
     
     // Prevent modifying the default nodeset
     if id == DEFAULT_NODESET_ID {
@@ -485,126 +968,488 @@ async fn update_nodeset(Path(id): Path<i64>, Json(request): Json<UpdateNodesetRe
         return (StatusCode::FORBIDDEN, Json(response)).into_response();
     }
     
-    // Check if nodeset exists
-    let exists = sqlx::query_as::<_, (i64,)>(
-        "SELECT id FROM nodesets WHERE id = ?"
+    if request.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+        let response = ApiResponse::<()>::error("Nodeset name cannot be empty");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    if let Some(response) = request.variables.as_ref().and_then(reject_invalid_variables) {
+        return response;
+    }
+
+    if let Some(response) = reject_unknown_cause_reasons(&state, &request.nodes, &request.edges).await {
+        return response;
+    }
+
+    if let Some(response) = reject_invalid_calls(&state, Some(id), &request.nodes, &request.edges).await {
+        return response;
+    }
+    
+    // Validate and serialize off the async runtime, before taking the write lock.
+    // Validation only blocks the update if this turns out to be the active nodeset.
+    // The variables are set in the update itself, so a missing `variables` keeps the stored ones.
+    let processed = nodeset_payload::run_blocking(move || {
+        let validation = validate_nodeset(&request.nodes, &request.edges);
+        let json_result = serde_json::to_string(&NodeConfiguration::new(request.nodes.clone(), request.edges.clone()));
+        (validation, json_result, request)
+    })
+    .await;
+    let (validation, json_result, request) = match processed {
+        Ok(result) => result,
+        Err(response) => return response,
+    };
+    
+    let json_str = match json_result {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to serialize nodeset configuration: {}", e);
+            let response = ApiResponse::<()>::error("Failed to serialize nodeset configuration");
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+    
+    // Existence check, active check, update and re-fetch happen in one transaction
+    // so the nodeset can't be activated or deleted in between
+    let mut tx = match db::begin_write(pool).await {
+        Ok(tx) => tx,
+        Err(e) => return db_error_response(e, "Failed to update nodeset"),
+    };
+    
+    let existing = sqlx::query_as::<_, (Option<String>,)>(
+        &format!("SELECT {} FROM nodesets WHERE id = $1", db::nodesets::VARIABLES_SQL)
     )
     .bind(id)
-    .fetch_optional(pool)
+    .fetch_optional(&mut *tx)
     .await;
     
-    match exists {
+    let stored_variables = match existing {
         Ok(None) => {
             let response = ApiResponse::<()>::error("Nodeset not found");
             return (StatusCode::NOT_FOUND, Json(response)).into_response();
         }
         Err(e) => {
             log::error!("Failed to check nodeset existence: {}", e);
-            let response = ApiResponse::<()>::error("Failed to update nodeset");
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            return db_error_response(e, "Failed to update nodeset");
         }
-        Ok(Some(_)) => {}
-    }
+        Ok(Some((stored,))) => parse_stored_variables(stored.as_deref()),
+    };
+    let variables = request.variables.clone().unwrap_or(stored_variables);
+    let variables_json = match serde_json::to_string(&variables) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to serialize nodeset variables: {}", e);
+            let response = ApiResponse::<()>::error("Failed to serialize nodeset configuration");
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
     
-    // Check if this is the active nodeset - if so, validate before allowing update
-    let active_id = match get_active_nodeset_id(pool).await {
+    let active_id = match get_active_nodeset_id(&mut *tx).await {
         Ok(aid) => aid,
         Err(e) => {
             log::error!("Failed to get active nodeset id: {}", e);
-            let response = ApiResponse::<()>::error("Failed to update nodeset");
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            return db_error_response(e, "Failed to update nodeset");
         }
     };
     
-    if id == active_id {
-        // Validate the nodeset since it's the active one
-        let validation = validate_nodeset(&request.nodes);
-        if !validation.is_valid {
-            let error_message = validation.errors.join("; ");
-            let response = ApiResponse::<()>::error(format!("Cannot save active profile with invalid configuration: {}", error_message));
-            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    if id == active_id && !validation.is_valid {
+        let error_message = validation.errors.join("; ");
+        let response = ApiResponse::<()>::error(format!("Cannot save active profile with invalid configuration: {}", error_message));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+    
+    if !validation.is_valid {
+        match db::device_nodesets::get_devices_for_nodeset(&mut *tx, id).await {
+            Ok(devices) if !devices.is_empty() => {
+                let error_message = validation.errors.join("; ");
+                let response = ApiResponse::<()>::error(format!(
+                    "Cannot save profile assigned to {} with invalid configuration: {}",
+                    devices.join(", "), error_message
+                ));
+                return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("Failed to get devices for nodeset: {}", e);
+                return db_error_response(e, "Failed to update nodeset");
+            }
+        }
+    }
+    
+    if let Some(response) = reject_broken_callers(&mut tx, id, &request.nodes).await {
+        return response;
+    }
+    
+    // Build update query based on whether name is provided
+    let result = if let Some(ref name) = request.name {
+        sqlx::query(
+            &format!("UPDATE nodesets SET name = $1, node_json = {} WHERE id = $4", db::nodesets::with_variables_sql("$2", "$3"))
+        )
+        .bind(name)
+        .bind(&json_str)
+        .bind(&variables_json)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+    } else {
+        sqlx::query(
+            &format!("UPDATE nodesets SET node_json = {} WHERE id = $3", db::nodesets::with_variables_sql("$1", "$2"))
+        )
+        .bind(&json_str)
+        .bind(&variables_json)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+    };
+    
+    if let Err(e) = result {
+        log::error!("Failed to update nodeset: {}", e);
+        return db_error_response(e, "Failed to update nodeset");
+    }
+    if let Err(e) = record_revision(&mut tx, &state.config(), id).await {
+        log::error!("Failed to record nodeset revision: {}", e);
+        return db_error_response(e, "Failed to update nodeset");
+    }
+    
+    // Fetch the updated nodeset to return
+    let updated = sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, name FROM nodesets WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await;
+    
+    let (id, name) = match updated {
+        Ok(row) => row,
+        Err(e) => {
+            log::error!("Failed to fetch updated nodeset: {}", e);
+            return db_error_response(e, "Failed to update nodeset");
         }
+    };
+    
+    if let Err(e) = tx.commit().await {
+        log::error!("Failed to commit nodeset update: {}", e);
+        return db_error_response(e, "Failed to update nodeset");
     }
     
-    // Serialize the configuration
-    let config = NodeConfiguration {
-        nodes: request.nodes.clone(),
-        edges: request.edges.clone(),
+    log::info!("Nodeset {} updated", id);
+    let nodeset = Nodeset {
+        id,
+        name,
+        nodes: request.nodes,
+        edges: request.edges,
+        variables,
+    };
+    let response = ApiResponse::success(nodeset);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// PATCH /api/nodes/nodesets/:id/variables
+/// Sets or removes nodeset variables without touching the nodes and edges
+/// Returns all variables of the nodeset after the change
+#[utoipa::path(
+    patch,
+    path = "/api/nodes/nodesets/{id}/variables",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    request_body(content = BTreeMap<String, Option<f64>>, description = "A number sets the variable, null removes it"),
+    responses(
+        (status = 200, description = "All variables of the nodeset", body = ApiResponse<BTreeMap<String, f64>>),
+        (status = 400, description = "Invalid variable name or value", body = ApiError),
+        (status = 403, description = "The default nodeset can't be modified", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+async fn update_nodeset_variables(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateVariablesRequest>,
+) -> Response {
+    if id == DEFAULT_NODESET_ID {
+        let response = ApiResponse::<()>::error("Cannot modify the default nodeset. Please create a new profile instead.");
+        return (StatusCode::FORBIDDEN, Json(response)).into_response();
+    }
+
+    let mut tx = match db::begin_write(&state.pool).await {
+        Ok(tx) => tx,
+        Err(e) => return db_error_response(e, "Failed to update variables"),
+    };
+
+    let existing = sqlx::query_as::<_, (Option<String>,)>(
+        &format!("SELECT {} FROM nodesets WHERE id = $1", db::nodesets::VARIABLES_SQL)
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await;
+    let mut variables = match existing {
+        Ok(Some((stored,))) => parse_stored_variables(stored.as_deref()),
+        Ok(None) => {
+            let response = ApiResponse::<()>::error("Nodeset not found");
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset variables: {}", e);
+            return db_error_response(e, "Failed to update variables");
+        }
+    };
+
+    for (name, value) in request {
+        match value {
+            Some(value) => variables.insert(name, value),
+            None => variables.remove(&name),
+        };
+    }
+    if let Some(response) = reject_invalid_variables(&variables) {
+        return response;
+    }
+
+    let variables_json = match serde_json::to_string(&variables) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to serialize nodeset variables: {}", e);
+            let response = ApiResponse::<()>::error("Failed to serialize nodeset variables");
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+    let query = format!("UPDATE nodesets SET node_json = {} WHERE id = $2", db::nodesets::with_variables_sql("node_json", "$1"));
+    if let Err(e) = sqlx::query(&query)
+        .bind(&variables_json)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+    {
+        log::error!("Failed to update nodeset variables: {}", e);
+        return db_error_response(e, "Failed to update variables");
+    }
+    if let Err(e) = record_revision(&mut tx, &state.config(), id).await {
+        log::error!("Failed to record nodeset revision: {}", e);
+        return db_error_response(e, "Failed to update variables");
+    }
+
+    // A nodeset that is running must keep working, e.g. a removed variable may still be in use
+    let in_use = match running_devices(&mut tx, &state.config(), id).await {
+        Ok(devices) => devices,
+        Err(e) => {
+            log::error!("Failed to get devices for nodeset: {}", e);
+            return db_error_response(e, "Failed to update variables");
+        }
+    };
+    if !in_use.is_empty() {
+        let devices: Vec<&str> = in_use.iter().map(String::as_str).collect();
+        if let Err(e) = validate_for_devices(&mut tx, &state.config(), id, &devices).await {
+            return e.into_response("Failed to update variables");
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        log::error!("Failed to commit nodeset variables: {}", e);
+        return db_error_response(e, "Failed to update variables");
+    }
+
+    log::info!("Variables of nodeset {} updated", id);
+    let response = ApiResponse::success(variables);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// Devices that run a nodeset: all devices without an assignment if it is the active nodeset,
+/// and the devices it is assigned to
+async fn running_devices(
+    conn: &mut crate::db::DbConnection,
+    config: &crate::types::Config,
+    id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut devices = db::device_nodesets::get_devices_for_nodeset(&mut *conn, id).await?;
+    if get_active_nodeset_id(&mut *conn).await? == id {
+        let assignments = db::device_nodesets::get_all(&mut *conn).await?;
+        devices.extend(
+            DeviceRegistry::from_config(config)
+                .names()
+                .into_iter()
+                .filter(|device| !assignments.iter().any(|a| &a.device_identifier == device)),
+        );
+    }
+    Ok(devices)
+}
+
+/// Saved revisions of a nodeset
+#[derive(Serialize, ToSchema)]
+pub struct NodesetRevisions {
+    /// Revision the nodeset is currently at
+    pub current_revision: i64,
+    /// Kept revisions, newest first
+    pub revisions: Vec<db_types::NodesetRevision>,
+}
+
+/// GET /api/nodes/nodesets/:id/revisions
+/// Returns the kept revisions of a nodeset
+#[utoipa::path(
+    get,
+    path = "/api/nodes/nodesets/{id}/revisions",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, body = ApiResponse<NodesetRevisions>),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+async fn list_nodeset_revisions(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let pool = &state.pool;
+
+    let current = sqlx::query_as::<_, (i64,)>("SELECT revision FROM nodesets WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+    let current_revision = match current {
+        Ok(Some((revision,))) => revision,
+        Ok(None) => {
+            let response = ApiResponse::<()>::error("Nodeset not found");
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset revision: {}", e);
+            return db_error_response(e, "Failed to fetch revisions");
+        }
+    };
+
+    match db::nodeset_revisions::get_all(pool, id).await {
+        Ok(revisions) => {
+            let response = ApiResponse::success(NodesetRevisions { current_revision, revisions });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset revisions: {}", e);
+            db_error_response(e, "Failed to fetch revisions")
+        }
+    }
+}
+
+/// POST /api/nodes/nodesets/:id/revisions/:revision/restore
+/// Makes an earlier revision the current version of the nodeset, recorded as a new revision
+#[utoipa::path(
+    post,
+    path = "/api/nodes/nodesets/{id}/revisions/{revision}/restore",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID"), ("revision" = i64, Path)),
+    responses(
+        (status = 200, body = ApiResponse<Nodeset>),
+        (status = 400, description = "The revision is invalid for the devices running the nodeset, or lacks pins its callers connect", body = ApiError),
+        (status = 403, description = "The default nodeset can't be modified", body = ApiError),
+        (status = 404, description = "Nodeset or revision not found", body = ApiError),
+    )
+)]
+async fn restore_nodeset_revision(
+    State(state): State<AppState>,
+    Path((id, revision)): Path<(i64, i64)>,
+) -> Response {
+    if id == DEFAULT_NODESET_ID {
+        let response = ApiResponse::<()>::error("Cannot modify the default nodeset. Please create a new profile instead.");
+        return (StatusCode::FORBIDDEN, Json(response)).into_response();
+    }
+
+    let mut tx = match db::begin_write(&state.pool).await {
+        Ok(tx) => tx,
+        Err(e) => return db_error_response(e, "Failed to restore revision"),
     };
+
+    let node_json = match db::nodeset_revisions::get_node_json(&mut *tx, id, revision).await {
+        Ok(Some(node_json)) => node_json,
+        Ok(None) => {
+            let response = ApiResponse::<()>::error(format!("Revision {} of nodeset {} not found", revision, id));
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset revision: {}", e);
+            return db_error_response(e, "Failed to restore revision");
+        }
+    };
+    // Revisions saved with an older schema are upgraded like any stored nodeset
+    let config = match NodeConfiguration::from_stored_json(&node_json) {
+        Ok(config) => config,
+        Err(e) => return schema_error_response(e, "nodeset revision"),
+    };
+    if let Some(response) = reject_broken_callers(&mut tx, id, &config.nodes).await {
+        return response;
+    }
     let json_str = match serde_json::to_string(&config) {
         Ok(s) => s,
         Err(e) => {
-            log::error!("Failed to serialize nodeset configuration: {}", e);
-            let response = ApiResponse::<()>::error("Failed to serialize nodeset configuration");
-            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+            log::error!("Failed to serialize nodeset configuration: {}", e);
+            let response = ApiResponse::<()>::error("Failed to serialize nodeset configuration");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let updated = sqlx::query_as::<_, (String,)>("UPDATE nodesets SET node_json = $1 WHERE id = $2 RETURNING name")
+        .bind(&json_str)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await;
+    let name = match updated {
+        Ok(Some((name,))) => name,
+        Ok(None) => {
+            let response = ApiResponse::<()>::error("Nodeset not found");
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to restore nodeset revision: {}", e);
+            return db_error_response(e, "Failed to restore revision");
         }
     };
-    
-    // Build update query based on whether name is provided
-    let result = if let Some(ref name) = request.name {
-        if name.trim().is_empty() {
-            let response = ApiResponse::<()>::error("Nodeset name cannot be empty");
-            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+
+    // A nodeset that is running must keep working after the restore
+    let in_use = match running_devices(&mut tx, &state.config(), id).await {
+        Ok(devices) => devices,
+        Err(e) => {
+            log::error!("Failed to get devices for nodeset: {}", e);
+            return db_error_response(e, "Failed to restore revision");
         }
-        sqlx::query(
-            "UPDATE nodesets SET name = ?, node_json = ? WHERE id = ?"
-        )
-        .bind(name)
-        .bind(&json_str)
-        .bind(id)
-        .execute(pool)
-        .await
-    } else {
-        sqlx::query(
-            "UPDATE nodesets SET node_json = ? WHERE id = ?"
-        )
-        .bind(&json_str)
-        .bind(id)
-        .execute(pool)
-        .await
     };
-    
-    match result {
-        Ok(_) => {
-            log::info!("Nodeset {} updated", id);
-            // Fetch the updated nodeset to return
-            let updated = sqlx::query_as::<_, (i64, String, String)>(
-                "SELECT id, name, node_json FROM nodesets WHERE id = ?"
-            )
-            .bind(id)
-            .fetch_one(pool)
-            .await;
-            
-            match updated {
-                Ok((id, name, _)) => {
-                    let nodeset = Nodeset {
-                        id,
-                        name,
-                        nodes: request.nodes,
-                        edges: request.edges,
-                    };
-                    let response = ApiResponse::success(nodeset);
-                    (StatusCode::OK, Json(response)).into_response()
-                }
-                Err(e) => {
-                    log::error!("Failed to fetch updated nodeset: {}", e);
-                    let response = ApiResponse::<()>::error("Nodeset updated but failed to retrieve");
-                    (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
-                }
-            }
+    if !in_use.is_empty() {
+        let devices: Vec<&str> = in_use.iter().map(String::as_str).collect();
+        if let Err(e) = validate_for_devices(&mut tx, &state.config(), id, &devices).await {
+            return e.into_response("Failed to restore revision");
         }
+    }
+
+    let new_revision = match record_revision(&mut tx, &state.config(), id).await {
+        Ok(new_revision) => new_revision,
         Err(e) => {
-            log::error!("Failed to update nodeset: {}", e);
-            let response = ApiResponse::<()>::error("Failed to update nodeset");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+            log::error!("Failed to record nodeset revision: {}", e);
+            return db_error_response(e, "Failed to restore revision");
         }
+    };
+    if let Err(e) = tx.commit().await {
+        log::error!("Failed to commit nodeset restore: {}", e);
+        return db_error_response(e, "Failed to restore revision");
     }
+
+    log::info!("Nodeset {} restored to revision {} as revision {}", id, revision, new_revision);
+    let nodeset = Nodeset {
+        id,
+        name,
+        nodes: config.nodes,
+        edges: config.edges,
+        variables: config.variables,
+    };
+    let response = ApiResponse::success(nodeset);
+    (StatusCode::OK, Json(response)).into_response()
 }
 
 /// DELETE /api/nodes/nodesets/:id
 /// Deletes a nodeset
-async fn delete_nodeset(Path(id): Path<i64>) -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(
+    delete,
+    path = "/api/nodes/nodesets/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 403, description = "The default nodeset can't be deleted", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+        (status = 409, description = "The nodeset is active, assigned, scheduled or called", body = ApiError),
+    )
+)]
+async fn delete_nodeset(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let pool = &state.pool;
     
     // Prevent deleting the default nodeset
     if id == DEFAULT_NODESET_ID {
@@ -612,13 +1457,17 @@ async fn delete_nodeset(Path(id): Path<i64>) -> Response {
         return (StatusCode::FORBIDDEN, Json(response)).into_response();
     }
     
-    // Check if this is the active nodeset
-    let active_id = match get_active_nodeset_id(pool).await {
+    // Active check and delete happen in one transaction so the nodeset can't be activated in between
+    let mut tx = match db::begin_write(pool).await {
+        Ok(tx) => tx,
+        Err(e) => return db_error_response(e, "Failed to delete nodeset"),
+    };
+    
+    let active_id = match get_active_nodeset_id(&mut *tx).await {
         Ok(aid) => aid,
         Err(e) => {
             log::error!("Failed to get active nodeset id: {}", e);
-            let response = ApiResponse::<()>::error("Failed to delete nodeset");
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            return db_error_response(e, "Failed to delete nodeset");
         }
     };
     
@@ -627,36 +1476,128 @@ async fn delete_nodeset(Path(id): Path<i64>) -> Response {
         return (StatusCode::CONFLICT, Json(response)).into_response();
     }
     
+    match db::device_nodesets::get_devices_for_nodeset(&mut *tx, id).await {
+        Ok(devices) if !devices.is_empty() => {
+            let response = ApiResponse::<()>::error(format!(
+                "Cannot delete a nodeset assigned to {}. Please remove the assignment first.",
+                devices.join(", ")
+            ));
+            return (StatusCode::CONFLICT, Json(response)).into_response();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            log::error!("Failed to get devices for nodeset: {}", e);
+            return db_error_response(e, "Failed to delete nodeset");
+        }
+    }
+    
+    match db::schedules::get_names_for_nodeset(&mut *tx, id).await {
+        Ok(schedules) if !schedules.is_empty() => {
+            let response = ApiResponse::<()>::error(format!(
+                "Cannot delete a nodeset used by the schedules {}. Please remove the schedules first.",
+                schedules.join(", ")
+            ));
+            return (StatusCode::CONFLICT, Json(response)).into_response();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            log::error!("Failed to get schedules for nodeset: {}", e);
+            return db_error_response(e, "Failed to delete nodeset");
+        }
+    }
+    
+    match db::nodesets::get_caller_names(&mut *tx, id).await {
+        Ok(callers) if !callers.is_empty() => {
+            let response = ApiResponse::<()>::error(format!(
+                "Cannot delete a nodeset called by {}. Please remove the Call Profile nodes first.",
+                callers.join(", ")
+            ));
+            return (StatusCode::CONFLICT, Json(response)).into_response();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            log::error!("Failed to get callers of nodeset: {}", e);
+            return db_error_response(e, "Failed to delete nodeset");
+        }
+    }
+    
+    // Without the default profile as a fallback, at least one profile must remain
+    if !state.config().default_nodeset_enabled {
+        let remaining = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*) FROM nodesets WHERE id != $1 AND id != $2"
+        )
+        .bind(DEFAULT_NODESET_ID)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await;
+        
+        match remaining {
+            Ok((0,)) => {
+                let response = ApiResponse::<()>::error("Cannot delete the last profile while the default profile is disabled (default_nodeset_enabled is false). Create another profile first.");
+                return (StatusCode::CONFLICT, Json(response)).into_response();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("Failed to count nodesets: {}", e);
+                return db_error_response(e, "Failed to delete nodeset");
+            }
+        }
+    }
+    
+    if let Err(e) = db::hysteresis_state::delete_for_nodeset(&mut *tx, id).await {
+        log::error!("Failed to delete hysteresis state of nodeset {}: {}", id, e);
+        return db_error_response(e, "Failed to delete nodeset");
+    }
+    if let Err(e) = db::cooldown_state::delete_for_nodeset(&mut *tx, id).await {
+        log::error!("Failed to delete cooldown state of nodeset {}: {}", id, e);
+        return db_error_response(e, "Failed to delete nodeset");
+    }
+    if let Err(e) = db::nodeset_revisions::delete_all(&mut *tx, id).await {
+        log::error!("Failed to delete revisions of nodeset {}: {}", id, e);
+        return db_error_response(e, "Failed to delete nodeset");
+    }
+    if let Err(e) = db::nodeset_testcases::delete_all(&mut *tx, id).await {
+        log::error!("Failed to delete test cases of nodeset {}: {}", id, e);
+        return db_error_response(e, "Failed to delete nodeset");
+    }
+    if let Err(e) = db::scheduled_activations::delete_pending_for_nodeset(&mut *tx, id).await {
+        log::error!("Failed to delete scheduled activations of nodeset {}: {}", id, e);
+        return db_error_response(e, "Failed to delete nodeset");
+    }
+    
     let result = sqlx::query(
-        "DELETE FROM nodesets WHERE id = ?"
+        "DELETE FROM nodesets WHERE id = $1"
     )
     .bind(id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await;
     
     match result {
-        Ok(query_result) => {
-            if query_result.rows_affected() == 0 {
-                let response = ApiResponse::<()>::error("Nodeset not found");
-                (StatusCode::NOT_FOUND, Json(response)).into_response()
-            } else {
-                log::info!("Nodeset {} deleted", id);
-                let response = ApiResponse::success("Nodeset deleted");
-                (StatusCode::OK, Json(response)).into_response()
+        Ok(query_result) if query_result.rows_affected() == 0 => {
+            let response = ApiResponse::<()>::error("Nodeset not found");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Ok(_) => {
+            if let Err(e) = tx.commit().await {
+                log::error!("Failed to commit nodeset delete: {}", e);
+                return db_error_response(e, "Failed to delete nodeset");
             }
+            log::info!("Nodeset {} deleted", id);
+            let response = ApiResponse::success("Nodeset deleted");
+            (StatusCode::OK, Json(response)).into_response()
         }
         Err(e) => {
             log::error!("Failed to delete nodeset: {}", e);
-            let response = ApiResponse::<()>::error("Failed to delete nodeset");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+            db_error_response(e, "Failed to delete nodeset")
         }
     }
 }
 
 /// GET /api/nodes/nodesets/active
 /// Returns the currently active nodeset with full details
-async fn get_active_nodeset() -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(get, path = "/api/nodes/nodesets/active", tag = "nodes", responses((status = 200, body = ApiResponse<Nodeset>)))]
+async fn get_active_nodeset(State(state): State<AppState>) -> Response {
+    let pool = &state.pool;
     
     let active_id = match get_active_nodeset_id(pool).await {
         Ok(id) => id,
@@ -668,7 +1609,7 @@ async fn get_active_nodeset() -> Response {
     };
     
     let result = sqlx::query_as::<_, (i64, String, String)>(
-        "SELECT id, name, node_json FROM nodesets WHERE id = ?"
+        "SELECT id, name, node_json FROM nodesets WHERE id = $1"
     )
     .bind(active_id)
     .fetch_optional(pool)
@@ -676,10 +1617,10 @@ async fn get_active_nodeset() -> Response {
     
     match result {
         Ok(Some((id, name, node_json))) => {
-            match serde_json::from_str::<NodeConfiguration>(&node_json) {
+            match NodeConfiguration::from_stored_json(&node_json) {
                 Ok(config) => {
                     // Update node definitions to current version
-                    let (updated_nodes, removed_node_ids) = update_node_definitions(config.nodes).await;
+                    let (updated_nodes, removed_node_ids) = update_node_definitions(pool, &state.devices(), config.nodes).await;
                     let updated_edges = remove_orphaned_edges(config.edges, &removed_node_ids);
                     
                     let nodeset = Nodeset {
@@ -687,14 +1628,13 @@ async fn get_active_nodeset() -> Response {
                         name,
                         nodes: updated_nodes,
                         edges: updated_edges,
+                        variables: config.variables,
                     };
                     let response = ApiResponse::success(nodeset);
                     (StatusCode::OK, Json(response)).into_response()
                 }
                 Err(e) => {
-                    log::error!("Failed to parse active nodeset configuration: {}", e);
-                    let response = ApiResponse::<()>::error("Failed to parse active nodeset configuration");
-                    (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+                    schema_error_response(e, "active nodeset configuration")
                 }
             }
         }
@@ -705,6 +1645,7 @@ async fn get_active_nodeset() -> Response {
                 name: "New".to_string(),
                 nodes: vec![],
                 edges: vec![],
+                variables: Variables::new(),
             };
             let response = ApiResponse::success(nodeset);
             (StatusCode::OK, Json(response)).into_response()
@@ -717,81 +1658,173 @@ async fn get_active_nodeset() -> Response {
     }
 }
 
-/// PUT /api/nodes/nodesets/active/:id
-/// Sets the active nodeset
-async fn set_active_nodeset(Path(id): Path<i64>) -> Response {
-    let pool = db::get_pool().await;
-    
-    // Check if nodeset exists (unless id is NEW_NODESET_ID for new unsaved nodeset)
-    if id != NEW_NODESET_ID {
-        // Fetch nodeset to validate it
-        let result = sqlx::query_as::<_, (String,)>(
-            "SELECT node_json FROM nodesets WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_optional(pool)
-        .await;
-        
-        match result {
-            Ok(None) => {
-                let response = ApiResponse::<()>::error("Nodeset not found");
-                return (StatusCode::NOT_FOUND, Json(response)).into_response();
+/// Reasons a nodeset can't become the active nodeset
+#[derive(Debug)]
+pub enum ActivationError {
+    /// The default nodeset was requested while `default_nodeset_enabled` is false
+    DefaultDisabled,
+    NotFound,
+    /// The nodeset failed validation
+    Invalid(String),
+    Schema(SchemaError),
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for ActivationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivationError::DefaultDisabled => write!(f, "The default profile is disabled (default_nodeset_enabled is false)"),
+            ActivationError::NotFound => write!(f, "Nodeset not found"),
+            ActivationError::Invalid(errors) => write!(f, "Invalid profile: {}", errors),
+            ActivationError::Schema(e) => write!(f, "{}", e),
+            ActivationError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl ActivationError {
+    /// Build the API error response for a failed activation
+    pub fn into_response(self, message: &str) -> Response {
+        match self {
+            ActivationError::DefaultDisabled | ActivationError::Invalid(_) => {
+                let response = ApiResponse::<()>::error(self.to_string());
+                (StatusCode::BAD_REQUEST, Json(response)).into_response()
             }
-            Err(e) => {
-                log::error!("Failed to check nodeset existence: {}", e);
-                let response = ApiResponse::<()>::error("Failed to set active nodeset");
-                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            ActivationError::NotFound => {
+                let response = ApiResponse::<()>::error(self.to_string());
+                (StatusCode::NOT_FOUND, Json(response)).into_response()
             }
-            Ok(Some((node_json,))) => {
-                // Parse and validate the nodeset
-                match serde_json::from_str::<NodeConfiguration>(&node_json) {
-                    Ok(config) => {
-                        let validation = validate_nodeset(&config.nodes);
-                        if !validation.is_valid {
-                            let error_message = validation.errors.join("; ");
-                            let response = ApiResponse::<()>::error(format!("Invalid profile: {}", error_message));
-                            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Failed to parse nodeset configuration: {}", e);
-                        let response = ApiResponse::<()>::error("Failed to parse nodeset configuration");
-                        return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
-                    }
-                }
+            ActivationError::Schema(e) => schema_error_response(e, "nodeset configuration"),
+            ActivationError::Database(e) => {
+                log::error!("{}: {}", message, e);
+                db_error_response(e, message)
             }
         }
     }
+}
+
+/// Check that a nodeset can become the active nodeset: it must exist, have a valid structure,
+/// work for every device and only reference existing cause reasons
+pub async fn validate_for_activation(
+    conn: &mut crate::db::DbConnection,
+    config: &crate::types::Config,
+    id: i64,
+) -> Result<(), ActivationError> {
+    let registry = DeviceRegistry::from_config(config);
+    let devices: Vec<&str> = registry.all().iter().map(|d| d.as_str()).collect();
+    validate_for_devices(conn, config, id, &devices).await
+}
+
+/// Check that a nodeset can run for the given devices, like `validate_for_activation`
+pub async fn validate_for_devices(
+    conn: &mut crate::db::DbConnection,
+    config: &crate::types::Config,
+    id: i64,
+    devices: &[&str],
+) -> Result<(), ActivationError> {
+    if id == DEFAULT_NODESET_ID && !config.default_nodeset_enabled {
+        return Err(ActivationError::DefaultDisabled);
+    }
+    
+    // New unsaved nodesets (NEW_NODESET_ID) have nothing to validate yet
+    if id == NEW_NODESET_ID {
+        return Ok(());
+    }
+    
+    let (node_json,) = sqlx::query_as::<_, (String,)>(
+        "SELECT node_json FROM nodesets WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(ActivationError::Database)?
+    .ok_or(ActivationError::NotFound)?;
+    
+    let config = NodeConfiguration::from_stored_json(&node_json).map_err(ActivationError::Schema)?;
+    let mut errors = validate_nodeset(&config.nodes, &config.edges).errors;
+    if errors.is_empty() {
+        match db::nodesets::expand_calls(&mut *conn, Some(id), config.resolved_nodes(), config.edges.clone()).await {
+            Ok((nodes, edges)) => errors = validate_nodeset_for_devices(&nodes, &edges, devices),
+            Err(e) => errors.push(e),
+        }
+    }
     
-    // Update the active nodeset setting
-    let result = sqlx::query(
-        "INSERT INTO settings (setting_key, setting_value) VALUES ('active_nodeset', ?)
+    // Cause reasons can be deleted after the profile was saved
+    let cause_reasons = sqlx::query_as::<_, db::cause_reasons::CauseReasonRecord>(
+        "SELECT id, label, description, is_hidden, is_editable FROM cause_reasons",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(ActivationError::Database)?;
+    let existing = db::cause_reasons::known_references(&cause_reasons);
+    errors.extend(find_missing_cause_reasons(&config.nodes, &config.edges, &existing));
+    
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ActivationError::Invalid(errors.join("; ")))
+    }
+}
+
+/// Validate a nodeset and make it the active nodeset
+/// Validation and activation happen in one transaction so the nodeset can't change in between
+/// Returns the id of the previously active nodeset
+pub async fn activate_nodeset(
+    pool: &DbPool,
+    config: &crate::types::Config,
+    id: i64,
+) -> Result<i64, ActivationError> {
+    let mut tx = db::begin_write(pool).await.map_err(ActivationError::Database)?;
+    
+    validate_for_activation(&mut tx, config, id).await?;
+    let previous_id = get_active_nodeset_id(&mut *tx).await.map_err(ActivationError::Database)?;
+    
+    sqlx::query(
+        "INSERT INTO settings (setting_key, setting_value) VALUES ('active_nodeset', $1)
          ON CONFLICT(setting_key) DO UPDATE SET setting_value = excluded.setting_value"
     )
     .bind(id.to_string())
-    .execute(pool)
-    .await;
+    .execute(&mut *tx)
+    .await
+    .map_err(ActivationError::Database)?;
+    tx.commit().await.map_err(ActivationError::Database)?;
     
-    match result {
+    log::info!("Active nodeset set to {}", id);
+    Ok(previous_id)
+}
+
+/// PUT /api/nodes/nodesets/active/:id
+/// Sets the active nodeset
+#[utoipa::path(
+    put,
+    path = "/api/nodes/nodesets/active/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, description = "The active nodeset ID", body = ApiResponse<i64>),
+        (status = 400, description = "The nodeset is invalid or the default nodeset is disabled", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+async fn set_active_nodeset(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    match activate_nodeset(&state.pool, &state.config(), id).await {
         Ok(_) => {
-            log::info!("Active nodeset set to {}", id);
             let response = ApiResponse::success(id);
             (StatusCode::OK, Json(response)).into_response()
         }
-        Err(e) => {
-            log::error!("Failed to set active nodeset: {}", e);
-            let response = ApiResponse::<()>::error("Failed to set active nodeset");
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
-        }
+        Err(e) => e.into_response("Failed to set active nodeset"),
     }
 }
 
 /// Helper function to get the active nodeset id
-pub async fn get_active_nodeset_id(pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Error> {
+pub async fn get_active_nodeset_id<'e, E>(executor: E) -> Result<i64, sqlx::Error>
+where
+    E: crate::db::DbExecutor<'e>,
+{
     let result = sqlx::query_as::<_, (String,)>(
         "SELECT setting_value FROM settings WHERE setting_key = 'active_nodeset'"
     )
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await?;
     
     match result {
@@ -808,10 +1841,54 @@ pub async fn get_active_nodeset_id(pool: &sqlx::SqlitePool) -> Result<i64, sqlx:
     }
 }
 
+/// POST /api/nodes/analyze
+/// Returns the errors that stop a nodeset from running and warnings about parts of it that can't matter
+#[utoipa::path(
+    post,
+    path = "/api/nodes/analyze",
+    tag = "nodes",
+    request_body = AnalyzeNodesetRequest,
+    responses((status = 200, body = ApiResponse<nodes::analysis::NodesetAnalysis>))
+)]
+async fn analyze_nodeset(State(state): State<AppState>, body: Result<Bytes, BytesRejection>) -> Response {
+    let request: AnalyzeNodesetRequest = match nodeset_payload::parse_json_body(state.config().nodeset_max_body_bytes, body).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let analysis = nodeset_payload::run_blocking(move || {
+        let config = NodeConfiguration::new(request.nodes, request.edges).with_variables(request.variables);
+        nodes::analyze_nodeset(&config.resolved_nodes(), &config.edges)
+    })
+    .await;
+    match analysis {
+        Ok(analysis) => (StatusCode::OK, Json(ApiResponse::success(analysis))).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// GET /api/nodes/payload-metrics
+/// Returns request body size statistics for nodeset routes
+#[utoipa::path(
+    get,
+    path = "/api/nodes/payload-metrics",
+    tag = "nodes",
+    responses((status = 200, body = ApiResponse<nodeset_payload::PayloadMetricsSnapshot>))
+)]
+async fn get_payload_metrics(State(state): State<AppState>) -> Response {
+    let response = ApiResponse::success(nodeset_payload::get_metrics_snapshot(state.config().nodeset_max_body_bytes));
+    (StatusCode::OK, Json(response)).into_response()
+}
+
 /// GET /api/nodes/definitions
 /// Returns all available node type definitions
-async fn get_node_definitions() -> Response {
-    let definitions = get_enriched_node_definitions().await;
+#[utoipa::path(
+    get,
+    path = "/api/nodes/definitions",
+    tag = "nodes",
+    responses((status = 200, body = ApiResponse<Vec<nodes::NodeDefinition>>))
+)]
+async fn get_node_definitions(State(state): State<AppState>) -> Response {
+    let definitions = get_enriched_node_definitions(&state.pool, &state.devices()).await;
     let response = ApiResponse::success(definitions);
     (StatusCode::OK, Json(response)).into_response()
 }
@@ -819,6 +1896,7 @@ async fn get_node_definitions() -> Response {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::test_helpers;
     use serde_json::json;
 
     fn create_node(node_type: &str) -> serde_json::Value {
@@ -844,7 +1922,7 @@ mod tests {
     #[test]
     fn test_validate_nodeset_empty() {
         let nodes: Vec<serde_json::Value> = vec![];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert_eq!(result.start_count, 0);
@@ -861,7 +1939,7 @@ mod tests {
             create_node(NODE_TYPE_START),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -874,7 +1952,7 @@ mod tests {
         let nodes = vec![
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert_eq!(result.start_count, 0);
@@ -887,7 +1965,7 @@ mod tests {
         let nodes = vec![
             create_node(NODE_TYPE_START),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -902,7 +1980,7 @@ mod tests {
             create_node(NODE_TYPE_START),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert_eq!(result.start_count, 2);
@@ -918,7 +1996,7 @@ mod tests {
             create_node(NODE_TYPE_EXECUTE_ACTION),
             create_node(NODE_TYPE_DO_NOTHING),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -933,7 +2011,7 @@ mod tests {
             create_node(NODE_TYPE_START),
             create_node(NODE_TYPE_DO_NOTHING),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -948,7 +2026,7 @@ mod tests {
             create_node(NODE_TYPE_START),
             create_node(NODE_TYPE_TURN_OFF),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -966,7 +2044,7 @@ mod tests {
             create_node("primitive_float"),
             create_node("device"),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -984,7 +2062,7 @@ mod tests {
             create_node(NODE_TYPE_START),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -999,7 +2077,7 @@ mod tests {
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
         // No edges connecting them - this is allowed
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert_eq!(result.start_count, 1);
@@ -1007,6 +2085,101 @@ mod tests {
         assert!(result.errors.is_empty());
     }
 
+    fn create_device_node(id: &str, device: &str) -> serde_json::Value {
+        let mut node = create_node("device");
+        node["id"] = json!(id);
+        node["data"]["enumValue"] = json!(device);
+        node
+    }
+
+    #[test]
+    fn test_validate_nodeset_for_devices_default_nodeset() {
+        let config = NodeConfiguration::from_stored_json(include_str!("../../../defaults/default_nodeset.json")).unwrap();
+        let errors = validate_nodeset_for_devices(&config.nodes, &config.edges, &["LivingRoom", "Veranda"]);
+        assert!(errors.is_empty(), "Default nodeset should work for all devices: {:?}", errors);
+    }
+
+    #[test]
+    fn test_validate_nodeset_for_devices_unknown_device() {
+        let nodes = vec![
+            create_node(NODE_TYPE_START),
+            create_node(NODE_TYPE_DO_NOTHING),
+            create_device_node("device-1", "LivingRoom"),
+            create_device_node("device-2", "Garage"),
+            create_device_node("device-3", ""),
+        ];
+        let errors = validate_nodeset_for_devices(&nodes, &[], &["LivingRoom", "Veranda"]);
+
+        assert!(errors.iter().any(|e| e.contains("'device-2'") && e.contains("Garage")));
+        assert!(errors.iter().any(|e| e.contains("'device-3'") && e.contains("no device selected")));
+        assert!(!errors.iter().any(|e| e.contains("'device-1'")));
+    }
+
+    #[test]
+    fn test_validate_nodeset_for_devices_reports_execution_errors() {
+        // Without a Start node the dry run fails for every device
+        let nodes = vec![create_node(NODE_TYPE_DO_NOTHING)];
+        let errors = validate_nodeset_for_devices(&nodes, &[], &["LivingRoom", "Veranda"]);
+        assert!(!errors.is_empty());
+    }
+
+    fn create_cause_reason_node(id: &str, cause_id: &str) -> serde_json::Value {
+        let mut node = create_node(nodes::execution::NODE_TYPE_CAUSE_REASON);
+        node["id"] = json!(id);
+        node["data"]["enumValue"] = json!(cause_id);
+        node
+    }
+
+    #[test]
+    fn test_find_missing_cause_reasons() {
+        let nodes = vec![
+            create_node(NODE_TYPE_START),
+            create_cause_reason_node("cause-1", "1"),
+            create_cause_reason_node("cause-2", "105"),
+            create_cause_reason_node("cause-3", ""),
+            // Disconnected, so ignored
+            create_cause_reason_node("cause-4", "106"),
+        ];
+        let edges = vec![
+            json!({ "source": "cause-1", "target": "execute-1" }),
+            json!({ "source": "cause-2", "target": "execute-1" }),
+            json!({ "source": "cause-3", "target": "execute-1" }),
+        ];
+        let existing: HashSet<String> = ["0", "1", "2"].iter().map(|s| s.to_string()).collect();
+
+        let errors = find_missing_cause_reasons(&nodes, &edges, &existing);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("'cause-2'") && errors[0].contains("105"));
+        assert!(errors[1].contains("'cause-3'"));
+    }
+
+    #[test]
+    fn test_default_nodeset_cause_reasons_exist() {
+        let config = NodeConfiguration::from_stored_json(include_str!("../../../defaults/default_nodeset.json")).unwrap();
+        let defaults: Vec<serde_json::Value> = serde_json::from_str(include_str!("../../../defaults/cause_reasons.json")).unwrap();
+        let existing: HashSet<String> = defaults.iter().map(|cr| cr["id"].to_string()).collect();
+
+        let errors = find_missing_cause_reasons(&config.nodes, &config.edges, &existing);
+        assert!(errors.is_empty(), "Default nodeset must only use system cause reasons: {:?}", errors);
+    }
+
+    #[test]
+    fn test_remap_cause_reason() {
+        let mut nodes = vec![
+            create_cause_reason_node("cause-1", "105"),
+            create_cause_reason_node("cause-2", "1"),
+            create_cause_reason_node("cause-3", "105"),
+            // Other enum nodes with the same value are left alone
+            create_device_node("device-1", "105"),
+        ];
+
+        assert_eq!(remap_cause_reason(&mut nodes, "105", "2"), 2);
+        assert_eq!(nodes[0]["data"]["enumValue"], "2");
+        assert_eq!(nodes[1]["data"]["enumValue"], "1");
+        assert_eq!(nodes[2]["data"]["enumValue"], "2");
+        assert_eq!(nodes[3]["data"]["enumValue"], "105");
+    }
+
     // -------------------------------------------------------------------------
     // Tests for update_node_definitions
     // -------------------------------------------------------------------------
@@ -1302,6 +2475,28 @@ mod tests {
         assert_eq!(dynamic_inputs.len(), 4);
     }
 
+    #[test]
+    fn test_comment_nodes_are_kept_and_not_counted() {
+        let mut comment = create_node("annotation_comment");
+        comment["width"] = json!(480);
+        comment["height"] = json!(240);
+        comment["zIndex"] = json!(-1);
+        comment["data"]["comment"] = json!("Weekend comfort");
+        let nodes = vec![create_node("flow_start"), create_node("flow_do_nothing"), comment];
+
+        let result = validate_nodeset(&nodes, &[]);
+        assert!(result.is_valid);
+        assert_eq!(result.terminal_count, 1);
+
+        let (updated_nodes, removed_ids) = update_node_definitions_with_defs(nodes, nodes::get_all_node_definitions());
+        assert!(removed_ids.is_empty());
+        let comment = &updated_nodes[2];
+        assert_eq!(comment["width"], 480);
+        assert_eq!(comment["height"], 240);
+        assert_eq!(comment["data"]["comment"], "Weekend comfort");
+        assert_eq!(comment["data"]["definition"]["category"], "Annotations");
+    }
+
     // -------------------------------------------------------------------------
     // Tests for evaluate_every_minutes validation
     // -------------------------------------------------------------------------
@@ -1333,7 +2528,7 @@ mod tests {
             create_start_node_with_evaluate_minutes(5),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert!(result.errors.is_empty());
@@ -1345,7 +2540,7 @@ mod tests {
             create_start_node_with_evaluate_minutes(MAX_EVALUATE_EVERY_MINUTES as i64),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(result.is_valid);
         assert!(result.errors.is_empty());
@@ -1357,7 +2552,7 @@ mod tests {
             create_start_node_with_evaluate_minutes(1441), // 1 more than max
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert!(result.errors.iter().any(|e| e.contains("cannot exceed 1440")));
@@ -1369,7 +2564,7 @@ mod tests {
             create_start_node_with_evaluate_minutes(0),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert!(result.errors.iter().any(|e| e.contains("must be at least 1")));
@@ -1381,9 +2576,322 @@ mod tests {
             create_start_node_with_evaluate_minutes(-5),
             create_node(NODE_TYPE_EXECUTE_ACTION),
         ];
-        let result = validate_nodeset(&nodes);
+        let result = validate_nodeset(&nodes, &[]);
         
         assert!(!result.is_valid);
         assert!(result.errors.iter().any(|e| e.contains("must be at least 1")));
     }
+
+    /// Send a request to the nodes API backed by the given state
+    async fn api_request(
+        state: &AppState,
+        method: &str,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        test_helpers::api_request(nodes_routes(state.clone()), method, uri, body).await
+    }
+
+    #[tokio::test]
+    async fn test_api_analyze_nodeset() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        // The default nodeset is clean
+        let config = NodeConfiguration::from_stored_json(include_str!("../../../defaults/default_nodeset.json")).unwrap();
+        let body = serde_json::json!({ "nodes": config.nodes, "edges": config.edges });
+        let (status, analysis) = api_request(&state, "POST", "/analyze", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(analysis["data"], serde_json::json!({ "errors": [], "warnings": [] }));
+
+        let mut start = create_node("flow_start");
+        start["id"] = serde_json::json!("start");
+        let mut orphan = create_node("flow_do_nothing");
+        orphan["id"] = serde_json::json!("orphan");
+        let body = serde_json::json!({ "nodes": [start, orphan], "edges": [] });
+        let (status, analysis) = api_request(&state, "POST", "/analyze", Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(analysis["data"]["warnings"][0]["code"], "unreachable_node");
+        assert_eq!(analysis["data"]["warnings"][0]["node_ids"], serde_json::json!(["orphan"]));
+    }
+
+    #[tokio::test]
+    async fn test_api_rejects_incompatible_connections_in_active_profile() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let config = NodeConfiguration::from_stored_json(include_str!("../../../defaults/default_nodeset.json")).unwrap();
+        let body = serde_json::json!({ "name": "Typed", "nodes": config.nodes, "edges": config.edges });
+        let (_, created) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        let id = created["data"]["id"].as_i64().unwrap();
+        let (status, _) = api_request(&state, "PUT", &format!("/nodesets/active/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // A temperature can't be an If condition
+        let mut edges = config.edges.clone();
+        edges.push(serde_json::json!({
+            "id": "temperature-condition",
+            "source": "flow_start-102",
+            "sourceHandle": "outdoor_temperature",
+            "target": "logic_if-114",
+            "targetHandle": "condition"
+        }));
+        let body = serde_json::json!({ "nodes": config.nodes, "edges": edges });
+        let (status, response) = api_request(&state, "PUT", &format!("/nodesets/{}", id), Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response["error"].as_str().unwrap().contains("Incompatible connection"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_api_rejects_payload_over_the_configured_limit() {
+        let config = crate::types::Config { nodeset_max_body_bytes: 64, ..Default::default() };
+        let state = AppState::for_test(config).await;
+
+        let body = serde_json::json!({ "name": "x".repeat(100), "nodes": [], "edges": [] });
+        let (status, response) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(response["error"].as_str().unwrap().contains("limit of 64 bytes"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_api_create_get_and_delete_nodeset() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let body = serde_json::json!({ "name": "Night", "nodes": [], "edges": [] });
+        let (status, created) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = created["data"]["id"].as_i64().unwrap();
+
+        let (status, fetched) = api_request(&state, "GET", &format!("/nodesets/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(fetched["data"]["name"], "Night");
+
+        // Pending activations of the nodeset go with it, the ones of other nodesets stay
+        db::scheduled_activations::insert(&state.pool, id, i64::MAX, None).await.unwrap();
+        db::scheduled_activations::insert(&state.pool, DEFAULT_NODESET_ID, i64::MAX, None).await.unwrap();
+
+        let (status, _) = api_request(&state, "DELETE", &format!("/nodesets/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = api_request(&state, "GET", &format!("/nodesets/{}", id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let pending = db::scheduled_activations::get_pending(&state.pool).await.unwrap();
+        assert_eq!(pending.iter().map(|a| a.nodeset_id).collect::<Vec<_>>(), vec![DEFAULT_NODESET_ID]);
+    }
+
+    #[tokio::test]
+    async fn test_api_patch_variables() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let body = serde_json::json!({ "name": "Comfort", "nodes": [], "edges": [], "variables": { "comfort_min": 20.0 } });
+        let (status, created) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = created["data"]["id"].as_i64().unwrap();
+
+        let patch = serde_json::json!({ "comfort_min": 21.5, "comfort_max": 24.0 });
+        let (status, updated) = api_request(&state, "PATCH", &format!("/nodesets/{}/variables", id), Some(patch)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["data"], serde_json::json!({ "comfort_max": 24.0, "comfort_min": 21.5 }));
+
+        let patch = serde_json::json!({ "comfort_max": null });
+        let (status, updated) = api_request(&state, "PATCH", &format!("/nodesets/{}/variables", id), Some(patch)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["data"], serde_json::json!({ "comfort_min": 21.5 }));
+
+        // Saving the graph without variables keeps the stored ones
+        let body = serde_json::json!({ "nodes": [], "edges": [] });
+        let (status, _) = api_request(&state, "PUT", &format!("/nodesets/{}", id), Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, fetched) = api_request(&state, "GET", &format!("/nodesets/{}", id), None).await;
+        assert_eq!(fetched["data"]["variables"], serde_json::json!({ "comfort_min": 21.5 }));
+
+        let patch = serde_json::json!({ "not a name": 1.0 });
+        let (status, _) = api_request(&state, "PATCH", &format!("/nodesets/{}/variables", id), Some(patch)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let patch = serde_json::json!({ "comfort_min": 1.0 });
+        let (status, _) = api_request(&state, "PATCH", &format!("/nodesets/{}/variables", DEFAULT_NODESET_ID), Some(patch)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_api_revisions_and_restore() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let body = serde_json::json!({ "name": "Night", "nodes": [], "edges": [], "variables": { "setpoint": 19.0 } });
+        let (_, created) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        let id = created["data"]["id"].as_i64().unwrap();
+        let body = serde_json::json!({ "nodes": [create_node("flow_start")], "edges": [], "variables": { "setpoint": 21.0 } });
+        let (status, _) = api_request(&state, "PUT", &format!("/nodesets/{}", id), Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, revisions) = api_request(&state, "GET", &format!("/nodesets/{}/revisions", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(revisions["data"]["current_revision"], 2);
+        let numbers: Vec<i64> = revisions["data"]["revisions"].as_array().unwrap().iter().map(|r| r["revision"].as_i64().unwrap()).collect();
+        assert_eq!(numbers, vec![2, 1]);
+
+        let (status, restored) = api_request(&state, "POST", &format!("/nodesets/{}/revisions/1/restore", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(restored["data"]["nodes"], serde_json::json!([]));
+        assert_eq!(restored["data"]["variables"], serde_json::json!({ "setpoint": 19.0 }));
+        let (_, revisions) = api_request(&state, "GET", &format!("/nodesets/{}/revisions", id), None).await;
+        assert_eq!(revisions["data"]["current_revision"], 3);
+
+        let (status, _) = api_request(&state, "POST", &format!("/nodesets/{}/revisions/9/restore", id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_api_states_are_isolated() {
+        let first = AppState::for_test(crate::types::Config::default()).await;
+        let second = AppState::for_test(crate::types::Config::default()).await;
+
+        let body = serde_json::json!({ "name": "Only in first", "nodes": [], "edges": [] });
+        let (status, _) = api_request(&first, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (_, first_list) = api_request(&first, "GET", "/nodesets", None).await;
+        let (_, second_list) = api_request(&second, "GET", "/nodesets", None).await;
+        assert_eq!(first_list["data"].as_array().unwrap().len(), 2);
+        assert_eq!(second_list["data"].as_array().unwrap().len(), 1);
+        assert_eq!(second_list["data"][0]["id"], DEFAULT_NODESET_ID);
+    }
+
+    #[tokio::test]
+    async fn test_api_uses_injected_config() {
+        let config = crate::types::Config {
+            default_nodeset_enabled: false,
+            ..Default::default()
+        };
+        let state = AppState::for_test(config).await;
+
+        let uri = format!("/nodesets/active/{}", DEFAULT_NODESET_ID);
+        let (status, _) = api_request(&state, "PUT", &uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let (status, _) = api_request(&state, "PUT", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_api_definitions_list_configured_devices() {
+        let mut config = crate::types::Config::default();
+        let props = config.ac_controller_endpoints["Veranda"].clone();
+        config.ac_controller_endpoints.insert("Bedroom".to_string(), props);
+        let state = AppState::for_test(config).await;
+
+        let (status, body) = api_request(&state, "GET", "/definitions", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let definitions = body["data"].as_array().unwrap();
+        let device_type = |node_type: &str| {
+            let definition = definitions.iter().find(|d| d["node_type"] == node_type).unwrap();
+            let ports = definition["outputs"].as_array().unwrap().iter().chain(definition["inputs"].as_array().unwrap());
+            ports.into_iter().find(|port| port["id"] == "device").unwrap()["value_type"].clone()
+        };
+        let expected = json!({ "type": "Enum", "value": ["Bedroom", "LivingRoom", "Veranda"] });
+        assert_eq!(device_type("device"), expected);
+        assert_eq!(device_type("flow_start"), expected);
+        assert_eq!(device_type("pir_detection"), expected);
+    }
+
+    #[tokio::test]
+    async fn test_api_strict_cause_reasons() {
+        let nodes = vec![create_cause_reason_node("cause-1", "PIR Detection"), create_cause_reason_node("cause-2", "9999")];
+        let edges = vec![
+            json!({ "source": "cause-1", "target": "execute-1" }),
+            json!({ "source": "cause-2", "target": "execute-1" }),
+        ];
+        let body = json!({ "name": "Strict", "nodes": nodes, "edges": edges });
+
+        // Unknown cause reasons fall back to Undefined at runtime unless strict mode is enabled
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let (status, _) = api_request(&state, "POST", "/nodesets", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let config = crate::types::Config {
+            strict_cause_reasons: true,
+            ..Default::default()
+        };
+        let state = AppState::for_test(config).await;
+        let (status, response) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error = response["error"].as_str().unwrap();
+        assert!(error.contains("'cause-2'") && !error.contains("'cause-1'"), "labels are accepted: {}", error);
+    }
+
+    #[tokio::test]
+    async fn test_api_profile_calls() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let typed_node = |id: &str, node_type: &str, data: serde_json::Value| {
+            let mut node = create_node(node_type);
+            node["id"] = json!(id);
+            for (key, value) in data.as_object().unwrap() {
+                node["data"][key] = value.clone();
+            }
+            node
+        };
+
+        let callable = json!({
+            "name": "Solar decision",
+            "nodes": [
+                typed_node("input-1", nodes::execution::NODE_TYPE_NODESET_INPUT, json!({ "pinName": "solar_watt" })),
+                typed_node("output-1", nodes::execution::NODE_TYPE_NODESET_OUTPUT, json!({ "pinName": "use_solar" })),
+            ],
+            "edges": [],
+        });
+        let (status, created) = api_request(&state, "POST", "/nodesets", Some(callable)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let callee_id = created["data"]["id"].as_i64().unwrap();
+
+        let (status, interface) = api_request(&state, "GET", &format!("/nodesets/{}/interface", callee_id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(interface["data"], json!({ "inputs": ["solar_watt"], "outputs": ["use_solar"] }));
+
+        let call = |called: i64| {
+            typed_node("call-1", nodes::execution::NODE_TYPE_CALL_NODESET, json!({ "calledNodesetId": called }))
+        };
+        let caller_nodes = json!([typed_node("float-1", "primitive_float", json!({})), call(callee_id)]);
+        let caller_edges = json!([{
+            "id": "float-call",
+            "source": "float-1",
+            "sourceHandle": "value",
+            "target": "call-1",
+            "targetHandle": "in_solar_watt"
+        }]);
+        let body = json!({ "name": "Caller", "nodes": caller_nodes, "edges": caller_edges });
+        let (status, created) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let caller_id = created["data"]["id"].as_i64().unwrap();
+
+        // The callee can't drop a pin the caller connects until the caller disconnects it
+        let output = typed_node("output-1", nodes::execution::NODE_TYPE_NODESET_OUTPUT, json!({ "pinName": "use_solar" }));
+        let body = json!({ "nodes": [output.clone()], "edges": [] });
+        let (status, response) = api_request(&state, "PUT", &format!("/nodesets/{}", callee_id), Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response["error"].as_str().unwrap().contains("Caller (in_solar_watt)"), "{}", response);
+        let (status, _) = api_request(&state, "PUT", &format!("/nodesets/{}", caller_id), Some(json!({ "nodes": caller_nodes, "edges": [] }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = api_request(&state, "PUT", &format!("/nodesets/{}", callee_id), Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The call library picks up the saved callee
+        let library = db::nodesets::load_call_library(&state.pool).await.unwrap();
+        assert_eq!(library[&callee_id].nodes, vec![output]);
+
+        // A profile can't call a missing profile or end up calling itself
+        let body = json!({ "name": "Broken", "nodes": [call(9999)], "edges": [] });
+        let (status, _) = api_request(&state, "POST", "/nodesets", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body = json!({ "nodes": [call(caller_id)], "edges": [] });
+        let (status, response) = api_request(&state, "PUT", &format!("/nodesets/{}", callee_id), Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response["error"].as_str().unwrap().contains("already calling it"));
+
+        // A called profile can only be deleted once nothing calls it
+        let (status, response) = api_request(&state, "DELETE", &format!("/nodesets/{}", callee_id), None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(response["error"].as_str().unwrap().contains("Caller"));
+        let (status, _) = api_request(&state, "DELETE", &format!("/nodesets/{}", caller_id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = api_request(&state, "DELETE", &format!("/nodesets/{}", callee_id), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
 }
@@ -0,0 +1,365 @@
+//! Per-device nodeset assignment
+//!
+//! By default every device runs the active nodeset. `PUT /api/nodes/nodesets/assignments/:device/:id`
+//! makes a device run a different nodeset instead, e.g. a bedroom profile that only heats at night
+//! while the living room keeps the regular one. Removing the assignment makes the device fall back
+//! to the active nodeset again.
+//!
+//! A device can also get its own evaluation interval through
+//! `PUT /api/nodes/nodesets/assignments/:device/interval`, e.g. to evaluate the veranda every 15
+//! minutes while the living room follows the active nodeset's Start node. Each device runs on its
+//! own timer in the AC controller.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::nodes::{get_active_nodeset_id, validate_for_devices, ActivationError, NEW_NODESET_ID};
+use crate::{
+    app_state::AppState,
+    db::{self, DbPool},
+    nodes::flow_nodes::MAX_EVALUATE_EVERY_MINUTES,
+    types::{ApiError, ApiResponse},
+};
+
+/// Nodeset a device runs
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct DeviceNodesetAssignment {
+    pub device: String,
+    /// Name shown to the user, `device` if no display name is set
+    pub display_name: String,
+    /// Nodeset assigned to the device, None if it runs the active nodeset
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub nodeset_id: Option<i64>,
+    /// Nodeset the device actually runs
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub effective_nodeset_id: i64,
+    /// Evaluation interval of the device, None if it follows the Start node of the active nodeset
+    pub evaluate_every_minutes: Option<i32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct IntervalRequest {
+    minutes: i32,
+}
+
+/// Respond with 404 for device names that aren't AC devices
+fn unknown_device_response(device: &str) -> Response {
+    let response = ApiResponse::<()>::error(format!("Unknown device '{}'", device));
+    (StatusCode::NOT_FOUND, Json(response)).into_response()
+}
+
+/// GET /api/nodes/nodesets/assignments
+/// Returns the nodeset of every device (Vec<DeviceNodesetAssignment>)
+#[utoipa::path(
+    get,
+    path = "/api/nodes/nodesets/assignments",
+    tag = "nodes",
+    responses((status = 200, body = ApiResponse<Vec<DeviceNodesetAssignment>>))
+)]
+pub async fn list_assignments(State(state): State<AppState>) -> Response {
+    let pool = &state.pool;
+
+    let loaded = async {
+        let active_id = get_active_nodeset_id(pool).await?;
+        let assignments = db::device_nodesets::get_all(pool).await?;
+        let display_names = db::device_aliases::get_display_names(pool).await?;
+        let intervals = db::device_nodesets::get_intervals(pool).await?;
+        Ok::<_, sqlx::Error>((active_id, assignments, display_names, intervals))
+    }
+    .await;
+    let (active_id, assignments, display_names, intervals) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            log::error!("Failed to list nodeset assignments: {}", e);
+            let response = ApiResponse::<()>::error("Failed to list nodeset assignments");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let devices: Vec<DeviceNodesetAssignment> = state
+        .devices()
+        .all()
+        .iter()
+        .map(|device| {
+            let nodeset_id = assignments
+                .iter()
+                .find(|assignment| assignment.device_identifier == device.as_str())
+                .map(|assignment| assignment.nodeset_id);
+            DeviceNodesetAssignment {
+                device: device.as_str().to_string(),
+                display_name: super::devices::display_name(&display_names, device.as_str()),
+                nodeset_id,
+                effective_nodeset_id: nodeset_id.unwrap_or(active_id),
+                evaluate_every_minutes: intervals
+                    .iter()
+                    .find(|(interval_device, _)| interval_device == device.as_str())
+                    .map(|(_, minutes)| *minutes),
+            }
+        })
+        .collect();
+
+    let response = ApiResponse::success(devices);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// Validate a nodeset for a device and assign it
+/// Validation and assignment happen in one transaction so the nodeset can't change in between
+pub async fn assign_to_device(
+    pool: &DbPool,
+    config: &crate::types::Config,
+    device: &str,
+    id: i64,
+) -> Result<(), ActivationError> {
+    let mut tx = db::begin_write(pool).await.map_err(ActivationError::Database)?;
+    validate_for_devices(&mut tx, config, id, &[device]).await?;
+    db::device_nodesets::assign(&mut *tx, device, id).await.map_err(ActivationError::Database)?;
+    tx.commit().await.map_err(ActivationError::Database)
+}
+
+/// PUT /api/nodes/nodesets/assignments/:device/:id
+/// Makes the device run the nodeset instead of the active nodeset
+#[utoipa::path(
+    put,
+    path = "/api/nodes/nodesets/assignments/{device}/{id}",
+    tag = "nodes",
+    params(("device" = String, Path), ("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, description = "The assigned nodeset ID", body = ApiResponse<i64>),
+        (status = 400, description = "The nodeset is unsaved or invalid for the device", body = ApiError),
+        (status = 404, description = "Unknown device or nodeset", body = ApiError),
+    )
+)]
+pub async fn assign_nodeset(State(state): State<AppState>, Path((device, id)): Path<(String, i64)>) -> Response {
+    if state.devices().get(&device).is_none() {
+        return unknown_device_response(&device);
+    }
+    if id == NEW_NODESET_ID {
+        let response = ApiResponse::<()>::error("Save the profile before assigning it to a device");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    match assign_to_device(&state.pool, &state.config(), &device, id).await {
+        Ok(()) => {
+            log::info!("Nodeset {} assigned to {}", id, device);
+            let response = ApiResponse::success(id);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => e.into_response("Failed to assign nodeset"),
+    }
+}
+
+/// DELETE /api/nodes/nodesets/assignments/:device
+/// Removes the device's assignment so it runs the active nodeset again
+#[utoipa::path(
+    delete,
+    path = "/api/nodes/nodesets/assignments/{device}",
+    tag = "nodes",
+    params(("device" = String, Path)),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 404, description = "Unknown device or no assignment", body = ApiError),
+    )
+)]
+pub async fn unassign_nodeset(State(state): State<AppState>, Path(device): Path<String>) -> Response {
+    if state.devices().get(&device).is_none() {
+        return unknown_device_response(&device);
+    }
+
+    match db::device_nodesets::unassign(&state.pool, &device).await {
+        Ok(true) => {
+            log::info!("Nodeset assignment of {} removed, falling back to the active nodeset", device);
+            let response = ApiResponse::success(device);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::<()>::error("Device has no nodeset assignment");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to remove nodeset assignment: {}", e);
+            let response = ApiResponse::<()>::error("Failed to remove nodeset assignment");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// PUT /api/nodes/nodesets/assignments/:device/interval
+/// Evaluates the device every `minutes` (1-1440) instead of on the active nodeset's interval, body: {"minutes": 15}
+#[utoipa::path(
+    put,
+    path = "/api/nodes/nodesets/assignments/{device}/interval",
+    tag = "nodes",
+    params(("device" = String, Path)),
+    request_body = IntervalRequest,
+    responses(
+        (status = 200, description = "The interval in minutes", body = ApiResponse<i32>),
+        (status = 400, description = "Minutes not in 1-1440", body = ApiError),
+        (status = 404, description = "Unknown device", body = ApiError),
+    )
+)]
+pub async fn set_interval(
+    State(state): State<AppState>,
+    Path(device): Path<String>,
+    Json(request): Json<IntervalRequest>,
+) -> Response {
+    if state.devices().get(&device).is_none() {
+        return unknown_device_response(&device);
+    }
+    if !(1..=MAX_EVALUATE_EVERY_MINUTES).contains(&request.minutes) {
+        let response = ApiResponse::<()>::error(format!("Minutes must be between 1 and {}", MAX_EVALUATE_EVERY_MINUTES));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    match db::device_nodesets::set_interval(&state.pool, &device, request.minutes).await {
+        Ok(()) => {
+            log::info!("{} is evaluated every {} minutes from its next evaluation", device, request.minutes);
+            let response = ApiResponse::success(request.minutes);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to set the evaluation interval: {}", e);
+            let response = ApiResponse::<()>::error("Failed to set the evaluation interval");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// DELETE /api/nodes/nodesets/assignments/:device/interval
+/// Removes the device's interval so it follows the active nodeset's interval again
+#[utoipa::path(
+    delete,
+    path = "/api/nodes/nodesets/assignments/{device}/interval",
+    tag = "nodes",
+    params(("device" = String, Path)),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 404, description = "Unknown device or no interval", body = ApiError),
+    )
+)]
+pub async fn clear_interval(State(state): State<AppState>, Path(device): Path<String>) -> Response {
+    if state.devices().get(&device).is_none() {
+        return unknown_device_response(&device);
+    }
+
+    match db::device_nodesets::clear_interval(&state.pool, &device).await {
+        Ok(true) => {
+            log::info!("Evaluation interval of {} removed, following the active nodeset", device);
+            let response = ApiResponse::success(device);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::<()>::error("Device has no evaluation interval");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to remove the evaluation interval: {}", e);
+            let response = ApiResponse::<()>::error("Failed to remove the evaluation interval");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+    use crate::webserver::api::nodes::{nodes_routes, DEFAULT_NODESET_ID};
+    use tower::ServiceExt;
+
+    async fn api_request(state: &AppState, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        test_helpers::api_request(nodes_routes(state.clone()), method, uri, None).await
+    }
+
+    #[tokio::test]
+    async fn test_assign_list_and_unassign() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        sqlx::query("UPDATE settings SET setting_value = '7' WHERE setting_key = 'active_nodeset'")
+            .execute(&state.pool)
+            .await
+            .unwrap();
+        db::device_aliases::set(&state.pool, "Veranda", "Sunroom").await.unwrap();
+
+        let uri = format!("/nodesets/assignments/Veranda/{}", DEFAULT_NODESET_ID);
+        let (status, _) = api_request(&state, "PUT", &uri).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = api_request(&state, "GET", "/nodesets/assignments").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"], serde_json::json!([
+            { "device": "LivingRoom", "display_name": "LivingRoom", "nodeset_id": null, "effective_nodeset_id": 7, "evaluate_every_minutes": null },
+            { "device": "Veranda", "display_name": "Sunroom", "nodeset_id": DEFAULT_NODESET_ID, "effective_nodeset_id": DEFAULT_NODESET_ID, "evaluate_every_minutes": null },
+        ]));
+
+        let (status, _) = api_request(&state, "DELETE", "/nodesets/assignments/Veranda").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(db::device_nodesets::get_for_device(&state.pool, "Veranda").await.unwrap(), None);
+        let (status, _) = api_request(&state, "DELETE", "/nodesets/assignments/Veranda").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_assign_rejects_unknown_device_and_nodeset() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let (status, _) = api_request(&state, "PUT", "/nodesets/assignments/Attic/0").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, body) = api_request(&state, "PUT", "/nodesets/assignments/Veranda/4242").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Nodeset not found");
+        assert!(db::device_nodesets::get_all(&state.pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_assigned_nodeset_cannot_be_deleted() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let (id,) = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO nodesets (name, node_json) VALUES ('Bedroom', '{\"nodes\":[],\"edges\":[]}') RETURNING id",
+        )
+        .fetch_one(&state.pool)
+        .await
+        .unwrap();
+        db::device_nodesets::assign(&state.pool, "LivingRoom", id).await.unwrap();
+
+        let (status, body) = api_request(&state, "DELETE", &format!("/nodesets/{}", id)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["error"].as_str().unwrap().contains("LivingRoom"));
+    }
+
+    #[tokio::test]
+    async fn test_set_and_clear_interval() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let put_interval = |device: &str, body: &str| {
+            axum::http::Request::builder()
+                .method("PUT")
+                .uri(format!("/nodesets/assignments/{}/interval", device))
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = nodes_routes(state.clone()).oneshot(put_interval("Veranda", r#"{"minutes": 15}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = nodes_routes(state.clone()).oneshot(put_interval("Veranda", r#"{"minutes": 0}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = nodes_routes(state.clone()).oneshot(put_interval("Attic", r#"{"minutes": 15}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let (_, body) = api_request(&state, "GET", "/nodesets/assignments").await;
+        let devices = body["data"].as_array().unwrap();
+        assert_eq!(devices[0]["evaluate_every_minutes"], serde_json::Value::Null);
+        assert_eq!(devices[1]["evaluate_every_minutes"], 15);
+
+        let (status, _) = api_request(&state, "DELETE", "/nodesets/assignments/Veranda/interval").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(db::device_nodesets::get_intervals(&state.pool).await.unwrap().is_empty());
+        let (status, _) = api_request(&state, "DELETE", "/nodesets/assignments/Veranda/interval").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
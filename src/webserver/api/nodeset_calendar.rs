@@ -0,0 +1,534 @@
+//! Recurring nodeset schedules
+//!
+//! Schedules switch a nodeset in during a weekly time window in local time, e.g. "Workday profile
+//! mon-fri 07:00-17:00" and "Night profile *" with a lower priority for the rest of the week.
+//! Without a device a schedule switches the active nodeset, with a device it switches that device's
+//! assignment. The scheduler loop only acts on transitions: when the schedule in effect for a target
+//! changes, so a profile picked by hand stays until the next transition. On startup the schedules
+//! in effect are applied once.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{Datelike, NaiveDateTime, Timelike, Weekday};
+use serde::Deserialize;
+use std::{collections::HashMap, time::Duration};
+use utoipa::ToSchema;
+
+use super::{
+    nodes::{activate_nodeset, validate_for_activation, validate_for_devices, ActivationError, NEW_NODESET_ID},
+    nodeset_assignments::assign_to_device,
+};
+use crate::{
+    app_state::AppState,
+    db::{self, schedules::ScheduleFields},
+    live_events::{get_live_events, LiveEvent},
+    types::{ApiError, ApiResponse, db_types::Schedule},
+};
+
+/// How often the scheduler checks for transitions
+const SCHEDULER_INTERVAL_SECS: u64 = 30;
+
+/// Day names of the `days` field, Monday first
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Request for creating or replacing a schedule
+#[derive(Deserialize, ToSchema)]
+pub struct ScheduleRequest {
+    pub name: String,
+    pub nodeset_id: i64,
+    /// Device whose assignment is switched, the active nodeset if omitted
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Cron-like day field, e.g. "mon-fri", "sat,sun" or "*"
+    pub days: String,
+    /// Local "HH:MM" at which the window starts
+    pub start_time: String,
+    /// Local "HH:MM" at which the window ends, before `start_time` for windows past midnight
+    /// and equal to it for the whole day
+    pub end_time: String,
+    /// Wins over lower priorities when several schedules of the same target are in effect
+    #[serde(default)]
+    pub priority: i64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Weekly time window of a schedule
+#[derive(Debug, PartialEq)]
+pub struct Window {
+    /// Days the window starts on, Monday first
+    days: [bool; 7],
+    /// Minutes since local midnight
+    start: u32,
+    end: u32,
+}
+
+impl Window {
+    /// Parse the day field and times of a schedule
+    pub fn parse(days: &str, start_time: &str, end_time: &str) -> Result<Self, String> {
+        Ok(Window {
+            days: parse_days(days)?,
+            start: parse_time(start_time)?,
+            end: parse_time(end_time)?,
+        })
+    }
+
+    /// Check if the window contains a local weekday and minute since midnight
+    /// A window past midnight belongs to the day it starts on
+    pub fn contains(&self, weekday: Weekday, minute: u32) -> bool {
+        let today = self.days[weekday.num_days_from_monday() as usize];
+        let yesterday = self.days[weekday.pred().num_days_from_monday() as usize];
+        match self.start.cmp(&self.end) {
+            std::cmp::Ordering::Less => today && (self.start..self.end).contains(&minute),
+            std::cmp::Ordering::Greater => (today && minute >= self.start) || (yesterday && minute < self.end),
+            std::cmp::Ordering::Equal => today,
+        }
+    }
+}
+
+/// Parse a day name like "mon" to its index, Monday first
+fn parse_day(name: &str) -> Result<usize, String> {
+    DAY_NAMES
+        .iter()
+        .position(|day| day.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Unknown day '{}', expected one of {}", name.trim(), DAY_NAMES.join(", ")))
+}
+
+/// Parse a cron-like day field: "*", day names and ranges separated by commas, e.g. "mon-fri,sun"
+/// Ranges may wrap around the week, e.g. "fri-mon"
+fn parse_days(days: &str) -> Result<[bool; 7], String> {
+    let mut parsed = [false; 7];
+    for part in days.split(',') {
+        if part.trim() == "*" {
+            parsed = [true; 7];
+            continue;
+        }
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                let mut day = from;
+                loop {
+                    parsed[day] = true;
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            None => parsed[parse_day(part)?] = true,
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parse a local "HH:MM" to minutes since midnight
+fn parse_time(time: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid time '{}', expected HH:MM", time);
+    let (hour, minute) = time.split_once(':').ok_or_else(invalid)?;
+    let hour: u32 = hour.parse().map_err(|_| invalid())?;
+    let minute: u32 = minute.parse().map_err(|_| invalid())?;
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok(hour * 60 + minute)
+}
+
+/// Get the schedule in effect for each target at a local time, keyed by device (None for the active nodeset)
+/// Of several schedules in effect for a target the highest priority wins, then the oldest
+pub fn schedules_in_effect(schedules: &[Schedule], at: NaiveDateTime) -> HashMap<Option<String>, &Schedule> {
+    let minute = at.hour() * 60 + at.minute();
+    let mut in_effect: HashMap<Option<String>, &Schedule> = HashMap::new();
+
+    for schedule in schedules.iter().filter(|s| s.enabled) {
+        let window = match Window::parse(&schedule.days, &schedule.start_time, &schedule.end_time) {
+            Ok(window) => window,
+            Err(e) => {
+                log::warn!("Skipping schedule {} ({}): {}", schedule.id, schedule.name, e);
+                continue;
+            }
+        };
+        if !window.contains(at.weekday(), minute) {
+            continue;
+        }
+        in_effect
+            .entry(schedule.device_identifier.clone())
+            .and_modify(|current| {
+                if schedule.priority > current.priority
+                    || (schedule.priority == current.priority && schedule.id < current.id)
+                {
+                    *current = schedule;
+                }
+            })
+            .or_insert(schedule);
+    }
+
+    in_effect
+}
+
+/// Schedule id in effect per target when the scheduler last checked
+pub type AppliedSchedules = HashMap<Option<String>, i64>;
+
+/// Switch the nodeset of every target whose schedule in effect changed since the last check
+/// Each transition is broadcast as a live event, including failed ones
+/// Returns the number of nodesets switched
+pub async fn run_schedule_transitions(
+    state: &AppState,
+    applied: &mut AppliedSchedules,
+    at: NaiveDateTime,
+) -> Result<usize, sqlx::Error> {
+    let pool = &state.pool;
+    let config = state.config();
+    let schedules = db::schedules::get_all(pool).await?;
+    let in_effect = schedules_in_effect(&schedules, at);
+    let mut switched = 0;
+
+    for (target, schedule) in &in_effect {
+        if applied.get(target) == Some(&schedule.id) {
+            continue;
+        }
+
+        let result = match target {
+            Some(device) if state.devices().get(device).is_none() => Err(format!("Unknown device '{}'", device)),
+            Some(device) => assign_to_device(pool, &config, device, schedule.nodeset_id).await.map_err(|e| e.to_string()),
+            None => activate_nodeset(pool, &config, schedule.nodeset_id).await.map(|_| ()).map_err(|e| e.to_string()),
+        };
+        let target_name = target.as_deref().unwrap_or("the active nodeset");
+        match &result {
+            Ok(()) => {
+                log::info!("Schedule '{}' switched {} to nodeset {}", schedule.name, target_name, schedule.nodeset_id);
+                switched += 1;
+            }
+            Err(e) => log::warn!("Schedule '{}' failed to switch {} to nodeset {}: {}", schedule.name, target_name, schedule.nodeset_id, e),
+        }
+        get_live_events().publish(LiveEvent::schedule_transition(schedule, result.err()));
+    }
+
+    *applied = in_effect.into_iter().map(|(target, schedule)| (target, schedule.id)).collect();
+    Ok(switched)
+}
+
+/// Switch nodesets when their schedules come into effect
+pub async fn schedule_loop(state: AppState) {
+    let mut applied = AppliedSchedules::new();
+    loop {
+        match run_schedule_transitions(&state, &mut applied, chrono::Local::now().naive_local()).await {
+            // A new profile should take effect without waiting out a deep idle interval
+            Ok(switched) if switched > 0 => {
+                crate::ac_controller::idle_state::get_idle_state().wake("nodeset schedule transition");
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to run nodeset schedules: {}", e),
+        }
+
+        tokio::time::sleep(Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+    }
+}
+
+/// Check a schedule request, including that its nodeset can run for its target right now
+/// The nodeset is validated again at each transition
+async fn validate_request(state: &AppState, request: &ScheduleRequest) -> Result<(), Response> {
+    let bad_request = |error: String| {
+        let response = ApiResponse::<()>::error(error);
+        (StatusCode::BAD_REQUEST, Json(response)).into_response()
+    };
+
+    if request.name.trim().is_empty() {
+        return Err(bad_request("Schedule name cannot be empty".to_string()));
+    }
+    Window::parse(&request.days, &request.start_time, &request.end_time).map_err(bad_request)?;
+    if request.nodeset_id == NEW_NODESET_ID {
+        return Err(bad_request("Save the profile before scheduling it".to_string()));
+    }
+    if let Some(device) = &request.device
+        && state.devices().get(device).is_none()
+    {
+        let response = ApiResponse::<()>::error(format!("Unknown device '{}'", device));
+        return Err((StatusCode::NOT_FOUND, Json(response)).into_response());
+    }
+
+    let validation = match state.pool.acquire().await {
+        Ok(mut conn) => match &request.device {
+            Some(device) => validate_for_devices(&mut conn, &state.config(), request.nodeset_id, &[device.as_str()]).await,
+            None => validate_for_activation(&mut conn, &state.config(), request.nodeset_id).await,
+        },
+        Err(e) => Err(ActivationError::Database(e)),
+    };
+    validation.map_err(|e| e.into_response("Failed to save schedule"))
+}
+
+impl ScheduleRequest {
+    fn fields(&self) -> ScheduleFields<'_> {
+        ScheduleFields {
+            name: self.name.trim(),
+            nodeset_id: self.nodeset_id,
+            device_identifier: self.device.as_deref(),
+            days: self.days.trim(),
+            start_time: &self.start_time,
+            end_time: &self.end_time,
+            priority: self.priority,
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// GET /api/nodes/schedules
+/// Returns all schedules (Vec<db_types::Schedule>)
+#[utoipa::path(get, path = "/api/nodes/schedules", tag = "nodes", responses((status = 200, body = ApiResponse<Vec<Schedule>>)))]
+pub async fn list_schedules(State(state): State<AppState>) -> Response {
+    match db::schedules::get_all(&state.pool).await {
+        Ok(schedules) => {
+            let response = ApiResponse::success(schedules);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to list schedules: {}", e);
+            let response = ApiResponse::<()>::error("Failed to list schedules");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// POST /api/nodes/schedules
+/// Creates a schedule and returns it (db_types::Schedule)
+#[utoipa::path(
+    post,
+    path = "/api/nodes/schedules",
+    tag = "nodes",
+    request_body = ScheduleRequest,
+    responses(
+        (status = 201, body = ApiResponse<Schedule>),
+        (status = 400, description = "Invalid window or the nodeset is invalid for its target", body = ApiError),
+        (status = 404, description = "Unknown device or nodeset", body = ApiError),
+    )
+)]
+pub async fn create_schedule(State(state): State<AppState>, Json(request): Json<ScheduleRequest>) -> Response {
+    if let Err(response) = validate_request(&state, &request).await {
+        return response;
+    }
+
+    match db::schedules::insert(&state.pool, &request.fields()).await {
+        Ok(schedule) => {
+            log::info!("Created schedule {} ({})", schedule.id, schedule.name);
+            let response = ApiResponse::success(schedule);
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to create schedule: {}", e);
+            let response = ApiResponse::<()>::error("Failed to create schedule");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// PUT /api/nodes/schedules/:id
+/// Replaces a schedule and returns it (db_types::Schedule)
+#[utoipa::path(
+    put,
+    path = "/api/nodes/schedules/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Schedule ID")),
+    request_body = ScheduleRequest,
+    responses(
+        (status = 200, body = ApiResponse<Schedule>),
+        (status = 400, description = "Invalid window or the nodeset is invalid for its target", body = ApiError),
+        (status = 404, description = "Unknown schedule, device or nodeset", body = ApiError),
+    )
+)]
+pub async fn update_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<ScheduleRequest>,
+) -> Response {
+    if let Err(response) = validate_request(&state, &request).await {
+        return response;
+    }
+
+    match db::schedules::update(&state.pool, id, &request.fields()).await {
+        Ok(Some(schedule)) => {
+            log::info!("Updated schedule {} ({})", schedule.id, schedule.name);
+            let response = ApiResponse::success(schedule);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(None) => {
+            let response = ApiResponse::<()>::error("Schedule not found");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to update schedule: {}", e);
+            let response = ApiResponse::<()>::error("Failed to update schedule");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// DELETE /api/nodes/schedules/:id
+/// Deletes a schedule, the nodesets it switched stay as they are
+#[utoipa::path(
+    delete,
+    path = "/api/nodes/schedules/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Schedule ID")),
+    responses(
+        (status = 200, description = "The deleted schedule ID", body = ApiResponse<i64>),
+        (status = 404, description = "Schedule not found", body = ApiError),
+    )
+)]
+pub async fn delete_schedule(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    match db::schedules::delete(&state.pool, id).await {
+        Ok(true) => {
+            log::info!("Deleted schedule {}", id);
+            let response = ApiResponse::success(id);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::<()>::error("Schedule not found");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to delete schedule: {}", e);
+            let response = ApiResponse::<()>::error("Failed to delete schedule");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+    use crate::webserver::api::nodes::{get_active_nodeset_id, nodes_routes, DEFAULT_NODESET_ID};
+
+    /// Local time on Monday 29 December 2025
+    fn monday_at(hour: u32, minute: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2025, 12, 29).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    /// Copy the default nodeset so there is a second valid profile
+    async fn create_valid_nodeset(state: &AppState, name: &str) -> i64 {
+        let (id,) = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO nodesets (name, node_json) SELECT $1, node_json FROM nodesets WHERE id = 0 RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&state.pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    fn fields<'a>(name: &'a str, nodeset_id: i64, days: &'a str, start_time: &'a str, end_time: &'a str) -> ScheduleFields<'a> {
+        ScheduleFields {
+            name,
+            nodeset_id,
+            device_identifier: None,
+            days,
+            start_time,
+            end_time,
+            priority: 0,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_window_parse_and_contains() {
+        let workday = Window::parse("mon-fri", "07:00", "17:00").unwrap();
+        assert!(workday.contains(Weekday::Mon, 7 * 60));
+        assert!(!workday.contains(Weekday::Mon, 17 * 60));
+        assert!(!workday.contains(Weekday::Sat, 12 * 60));
+
+        // Past midnight the window belongs to the day it started on
+        let night = Window::parse("fri,sat", "22:00", "06:00").unwrap();
+        assert!(night.contains(Weekday::Sat, 3 * 60));
+        assert!(night.contains(Weekday::Sun, 5 * 60 + 59));
+        assert!(!night.contains(Weekday::Fri, 3 * 60));
+        assert!(!night.contains(Weekday::Sun, 22 * 60));
+
+        let weekend = Window::parse("sat-mon", "00:00", "00:00").unwrap();
+        assert!(weekend.contains(Weekday::Mon, 23 * 60));
+        assert!(!weekend.contains(Weekday::Tue, 0));
+        assert_eq!(Window::parse("*", "00:00", "00:00").unwrap().days, [true; 7]);
+
+        assert!(Window::parse("mon-fry", "07:00", "17:00").is_err());
+        assert!(Window::parse("mon", "24:00", "17:00").is_err());
+        assert!(Window::parse("mon", "7", "17:00").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transitions_switch_the_active_nodeset_once() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+        let workday = create_valid_nodeset(&state, "Workday").await;
+        db::schedules::insert(pool, &ScheduleFields { priority: 1, ..fields("Workday", workday, "mon-fri", "07:00", "17:00") })
+            .await
+            .unwrap();
+        db::schedules::insert(pool, &fields("Night", DEFAULT_NODESET_ID, "*", "00:00", "00:00")).await.unwrap();
+        let mut applied = AppliedSchedules::new();
+
+        assert_eq!(run_schedule_transitions(&state, &mut applied, monday_at(8, 0)).await.unwrap(), 1);
+        assert_eq!(get_active_nodeset_id(pool).await.unwrap(), workday);
+
+        // A profile picked by hand stays until the next transition
+        activate_nodeset(pool, &state.config(), DEFAULT_NODESET_ID).await.unwrap();
+        assert_eq!(run_schedule_transitions(&state, &mut applied, monday_at(12, 0)).await.unwrap(), 0);
+        assert_eq!(get_active_nodeset_id(pool).await.unwrap(), DEFAULT_NODESET_ID);
+
+        activate_nodeset(pool, &state.config(), workday).await.unwrap();
+        assert_eq!(run_schedule_transitions(&state, &mut applied, monday_at(17, 0)).await.unwrap(), 1);
+        assert_eq!(get_active_nodeset_id(pool).await.unwrap(), DEFAULT_NODESET_ID);
+    }
+
+    #[tokio::test]
+    async fn test_device_schedule_assigns_the_nodeset() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+        let bedroom = create_valid_nodeset(&state, "Bedroom").await;
+        db::schedules::insert(pool, &ScheduleFields {
+            device_identifier: Some("Veranda"),
+            ..fields("Bedroom nights", bedroom, "*", "22:00", "07:00")
+        })
+        .await
+        .unwrap();
+        let mut applied = AppliedSchedules::new();
+
+        assert_eq!(run_schedule_transitions(&state, &mut applied, monday_at(12, 0)).await.unwrap(), 0);
+        assert_eq!(run_schedule_transitions(&state, &mut applied, monday_at(23, 0)).await.unwrap(), 1);
+        assert_eq!(db::device_nodesets::get_for_device(pool, "Veranda").await.unwrap(), Some(bedroom));
+        assert_eq!(get_active_nodeset_id(pool).await.unwrap(), DEFAULT_NODESET_ID);
+    }
+
+    #[tokio::test]
+    async fn test_api_create_and_delete() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let workday = create_valid_nodeset(&state, "Workday").await;
+
+        let body = serde_json::json!({ "name": "Workday", "nodeset_id": workday, "days": "mon-fri", "start_time": "7:00", "end_time": "25:00" });
+        let (status, body) = test_helpers::api_request(nodes_routes(state.clone()), "POST", "/schedules", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Invalid time '25:00', expected HH:MM");
+
+        let body = serde_json::json!({ "name": "Workday", "nodeset_id": workday, "days": "mon-fri", "start_time": "07:00", "end_time": "17:00" });
+        let (status, created) = test_helpers::api_request(nodes_routes(state.clone()), "POST", "/schedules", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["data"]["enabled"], true);
+        assert_eq!(created["data"]["device_identifier"], serde_json::Value::Null);
+
+        // Nodesets used by a schedule can't be deleted
+        let (status, _) = test_helpers::api_request(nodes_routes(state.clone()), "DELETE", &format!("/nodesets/{}", workday), None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let id = created["data"]["id"].as_i64().unwrap();
+        let (status, _) = test_helpers::api_request(nodes_routes(state.clone()), "DELETE", &format!("/schedules/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = test_helpers::api_request(nodes_routes(state.clone()), "DELETE", &format!("/schedules/{}", id), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = test_helpers::api_request(nodes_routes(state.clone()), "DELETE", &format!("/nodesets/{}", workday), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
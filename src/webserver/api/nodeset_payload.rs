@@ -0,0 +1,192 @@
+//! Large nodeset payload handling
+//!
+//! Profiles with hundreds of nodes can exceed axum's default 2 MB body limit, and
+//! parsing them on the async runtime stalls other requests on a Pi. Nodeset routes use
+//! the configurable `nodeset_max_body_bytes` limit and parse request bodies on the
+//! blocking thread pool. Payload sizes are tracked for `GET /api/nodes/payload-metrics`.
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{DefaultBodyLimit, rejection::BytesRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::types::ApiResponse;
+
+/// Global payload metrics for nodeset routes
+static PAYLOAD_METRICS: PayloadMetrics = PayloadMetrics::new();
+
+/// Counters for nodeset request bodies
+pub struct PayloadMetrics {
+    requests: AtomicU64,
+    total_bytes: AtomicU64,
+    max_bytes: AtomicU64,
+    last_bytes: AtomicU64,
+    rejected_too_large: AtomicU64,
+    rejected_invalid: AtomicU64,
+}
+
+/// Point-in-time view of the payload metrics
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PayloadMetricsSnapshot {
+    pub max_body_bytes: usize,
+    pub requests: u64,
+    pub total_bytes: u64,
+    pub average_bytes: u64,
+    pub max_bytes: u64,
+    pub last_bytes: u64,
+    pub rejected_too_large: u64,
+    pub rejected_invalid: u64,
+}
+
+impl PayloadMetrics {
+    const fn new() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            total_bytes: AtomicU64::new(0),
+            max_bytes: AtomicU64::new(0),
+            last_bytes: AtomicU64::new(0),
+            rejected_too_large: AtomicU64::new(0),
+            rejected_invalid: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a received body
+    fn record(&self, bytes: u64) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.max_bytes.fetch_max(bytes, Ordering::Relaxed);
+        self.last_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self, max_body_bytes: usize) -> PayloadMetricsSnapshot {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let total_bytes = self.total_bytes.load(Ordering::Relaxed);
+        PayloadMetricsSnapshot {
+            max_body_bytes,
+            requests,
+            total_bytes,
+            average_bytes: total_bytes.checked_div(requests).unwrap_or(0),
+            max_bytes: self.max_bytes.load(Ordering::Relaxed),
+            last_bytes: self.last_bytes.load(Ordering::Relaxed),
+            rejected_too_large: self.rejected_too_large.load(Ordering::Relaxed),
+            rejected_invalid: self.rejected_invalid.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Body limit layer for routes that accept whole nodesets
+pub fn body_limit_layer(max_body_bytes: usize) -> DefaultBodyLimit {
+    DefaultBodyLimit::max(max_body_bytes)
+}
+
+/// Get the current payload metrics
+pub fn get_metrics_snapshot(max_body_bytes: usize) -> PayloadMetricsSnapshot {
+    PAYLOAD_METRICS.snapshot(max_body_bytes)
+}
+
+/// Run CPU-heavy work (parsing, validation, serialization) on the blocking thread pool
+pub async fn run_blocking<T, F>(f: F) -> Result<T, Response>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.map_err(|e| {
+        log::error!("Nodeset processing task failed: {}", e);
+        let response = ApiResponse::<()>::error("Failed to process nodeset");
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+    })
+}
+
+/// Parse a JSON request body on the blocking thread pool, recording its size
+/// `max_body_bytes` is the `nodeset_max_body_bytes` the body was limited to, for the error message
+pub async fn parse_json_body<T>(max_body_bytes: usize, body: Result<Bytes, BytesRejection>) -> Result<T, Response>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    parse_json_body_with(&PAYLOAD_METRICS, max_body_bytes, body).await
+}
+
+async fn parse_json_body_with<T>(
+    metrics: &PayloadMetrics,
+    max_body_bytes: usize,
+    body: Result<Bytes, BytesRejection>,
+) -> Result<T, Response>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) => {
+            if rejection.status() != StatusCode::PAYLOAD_TOO_LARGE {
+                return Err(rejection.into_response());
+            }
+            metrics.rejected_too_large.fetch_add(1, Ordering::Relaxed);
+            log::warn!("Rejected nodeset payload larger than nodeset_max_body_bytes");
+            let response = ApiResponse::<()>::error(format!(
+                "Nodeset payload exceeds the configured limit of {} bytes (nodeset_max_body_bytes)",
+                max_body_bytes
+            ));
+            return Err((StatusCode::PAYLOAD_TOO_LARGE, Json(response)).into_response());
+        }
+    };
+
+    metrics.record(body.len() as u64);
+    log::debug!("Received nodeset payload of {} bytes", body.len());
+
+    match run_blocking(move || serde_json::from_slice::<T>(&body)).await? {
+        Ok(parsed) => Ok(parsed),
+        Err(e) => {
+            metrics.rejected_invalid.fetch_add(1, Ordering::Relaxed);
+            let response = ApiResponse::<()>::error(format!("Invalid nodeset payload: {}", e));
+            Err((StatusCode::BAD_REQUEST, Json(response)).into_response())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_snapshot() {
+        let metrics = PayloadMetrics::new();
+        assert_eq!(metrics.snapshot(100).average_bytes, 0);
+
+        metrics.record(100);
+        metrics.record(300);
+        metrics.record(200);
+
+        let snapshot = metrics.snapshot(1000);
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.total_bytes, 600);
+        assert_eq!(snapshot.average_bytes, 200);
+        assert_eq!(snapshot.max_bytes, 300);
+        assert_eq!(snapshot.last_bytes, 200);
+        assert_eq!(snapshot.max_body_bytes, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_parse_json_body() {
+        let metrics = PayloadMetrics::new();
+        let body = Bytes::from_static(br#"{"nodes": [], "edges": []}"#);
+
+        let parsed: serde_json::Value = parse_json_body_with(&metrics, 1024, Ok(body)).await.unwrap();
+        assert!(parsed["nodes"].is_array());
+        assert_eq!(metrics.snapshot(0).requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_parse_invalid_json_body() {
+        let metrics = PayloadMetrics::new();
+        let body = Bytes::from_static(b"{\"nodes\": [");
+
+        let result: Result<serde_json::Value, _> = parse_json_body_with(&metrics, 1024, Ok(body)).await;
+        assert_eq!(result.unwrap_err().status(), StatusCode::BAD_REQUEST);
+        assert_eq!(metrics.snapshot(0).rejected_invalid, 1);
+    }
+}
@@ -0,0 +1,285 @@
+//! Scheduled nodeset activation
+//!
+//! `POST /api/nodes/nodesets/:id/activate-at` queues a nodeset to become the active nodeset at
+//! a future time, e.g. switching to a vacation profile on Friday 18:00 before leaving. With
+//! `revert_after_minutes` the previously active nodeset is restored afterwards. Queued
+//! activations are stored in the database, so they survive restarts; the scheduler loop
+//! activates due ones and validates the nodeset again at that time.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use super::nodes::{activate_nodeset, validate_for_activation, ActivationError, NEW_NODESET_ID};
+use crate::{
+    app_state::AppState,
+    db,
+    types::{ApiError, ApiResponse, db_types::ScheduledActivation},
+};
+
+/// How often the scheduler checks for due activations
+const SCHEDULER_INTERVAL_SECS: u64 = 30;
+/// Longest `revert_after_minutes`, a year
+const MAX_REVERT_MINUTES: i64 = 366 * 24 * 60;
+
+/// Request for scheduling a nodeset activation
+#[derive(Deserialize, ToSchema)]
+pub struct ActivateAtRequest {
+    /// Unix timestamp at which the nodeset becomes active
+    pub activate_at: i64,
+    /// Restore the previously active nodeset this many minutes after activation (optional)
+    pub revert_after_minutes: Option<i64>,
+}
+
+/// POST /api/nodes/nodesets/:id/activate-at
+/// Queues the nodeset to become active at `activate_at`, optionally reverting after `revert_after_minutes`
+/// Returns the scheduled activation (db_types::ScheduledActivation)
+#[utoipa::path(
+    post,
+    path = "/api/nodes/nodesets/{id}/activate-at",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    request_body = ActivateAtRequest,
+    responses(
+        (status = 201, body = ApiResponse<ScheduledActivation>),
+        (status = 400, description = "Unsaved or invalid nodeset, a time in the past or `revert_after_minutes` out of range", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+pub async fn schedule_activation(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<ActivateAtRequest>,
+) -> Response {
+    let pool = &state.pool;
+
+    if id == NEW_NODESET_ID {
+        let response = ApiResponse::<()>::error("Save the profile before scheduling its activation");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+    if request.activate_at <= chrono::Utc::now().timestamp() {
+        let response = ApiResponse::<()>::error("activate_at must be in the future");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+    if request.revert_after_minutes.is_some_and(|minutes| minutes <= 0 || minutes > MAX_REVERT_MINUTES) {
+        let response = ApiResponse::<()>::error(format!(
+            "revert_after_minutes must be between 1 and {}",
+            MAX_REVERT_MINUTES
+        ));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    // Report problems now rather than when nobody is around to fix them
+    // The nodeset is validated again when it is activated
+    let validation = match pool.acquire().await {
+        Ok(mut conn) => validate_for_activation(&mut conn, &state.config(), id).await,
+        Err(e) => Err(ActivationError::Database(e)),
+    };
+    if let Err(e) = validation {
+        return e.into_response("Failed to schedule activation");
+    }
+
+    match db::scheduled_activations::insert(pool, id, request.activate_at, request.revert_after_minutes).await {
+        Ok(activation) => {
+            log::info!(
+                "Scheduled activation of nodeset {} at {} (revert after {:?} minutes)",
+                id, activation.activate_at, activation.revert_after_minutes
+            );
+            let response = ApiResponse::success(activation);
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to schedule activation: {}", e);
+            let response = ApiResponse::<()>::error("Failed to schedule activation");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// GET /api/nodes/scheduled-activations
+/// Returns the pending activations (Vec<db_types::ScheduledActivation>), soonest first
+#[utoipa::path(
+    get,
+    path = "/api/nodes/scheduled-activations",
+    tag = "nodes",
+    responses((status = 200, body = ApiResponse<Vec<ScheduledActivation>>))
+)]
+pub async fn list_scheduled_activations(State(state): State<AppState>) -> Response {
+    match db::scheduled_activations::get_pending(&state.pool).await {
+        Ok(activations) => {
+            let response = ApiResponse::success(activations);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to list scheduled activations: {}", e);
+            let response = ApiResponse::<()>::error("Failed to list scheduled activations");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// DELETE /api/nodes/scheduled-activations/:id
+/// Cancels a pending activation
+#[utoipa::path(
+    delete,
+    path = "/api/nodes/scheduled-activations/{id}",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Scheduled activation ID")),
+    responses(
+        (status = 200, description = "The cancelled activation ID", body = ApiResponse<i64>),
+        (status = 404, description = "No pending activation with this ID", body = ApiError),
+    )
+)]
+pub async fn cancel_scheduled_activation(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    match db::scheduled_activations::cancel(&state.pool, id).await {
+        Ok(true) => {
+            log::info!("Cancelled scheduled activation {}", id);
+            let response = ApiResponse::success(id);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::<()>::error("No pending scheduled activation with this id");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to cancel scheduled activation: {}", e);
+            let response = ApiResponse::<()>::error("Failed to cancel scheduled activation");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// Activate all due nodesets, oldest first, and queue their reverts
+/// Returns the number of nodesets activated
+pub async fn run_due_activations(state: &AppState, now: i64) -> Result<usize, sqlx::Error> {
+    let pool = &state.pool;
+    let mut activated = 0;
+
+    for activation in db::scheduled_activations::get_due(pool, now).await? {
+        match activate_nodeset(pool, &state.config(), activation.nodeset_id).await {
+            Ok(previous_id) => {
+                log::info!(
+                    "Activated scheduled nodeset {} (previously {})",
+                    activation.nodeset_id, previous_id
+                );
+                db::scheduled_activations::set_status(pool, activation.id, db::scheduled_activations::STATUS_DONE, None).await?;
+                activated += 1;
+
+                if let Some(minutes) = activation.revert_after_minutes {
+                    // Rows from before revert_after_minutes was bounded can still hold any value
+                    match minutes.checked_mul(60).and_then(|secs| now.checked_add(secs)) {
+                        Some(revert_at) => {
+                            db::scheduled_activations::insert(pool, previous_id, revert_at, None).await?;
+                        }
+                        None => log::warn!(
+                            "Not reverting scheduled nodeset {}, revert_after_minutes {} is out of range",
+                            activation.nodeset_id, minutes
+                        ),
+                    }
+                }
+            }
+            Err(e) => {
+                log::warn!("Scheduled activation of nodeset {} failed: {}", activation.nodeset_id, e);
+                let error = e.to_string();
+                db::scheduled_activations::set_status(pool, activation.id, db::scheduled_activations::STATUS_FAILED, Some(&error)).await?;
+            }
+        }
+    }
+
+    Ok(activated)
+}
+
+/// Activate scheduled nodesets when they are due
+pub async fn scheduled_activation_loop(state: AppState) {
+    loop {
+        match run_due_activations(&state, chrono::Utc::now().timestamp()).await {
+            // A new profile should take effect without waiting out a deep idle interval
+            Ok(activated) if activated > 0 => {
+                crate::ac_controller::idle_state::get_idle_state().wake("scheduled nodeset activation");
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to run scheduled nodeset activations: {}", e),
+        }
+
+        tokio::time::sleep(Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webserver::api::nodes::{get_active_nodeset_id, DEFAULT_NODESET_ID};
+
+    #[tokio::test]
+    async fn test_due_activation_is_activated_and_reverted() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+
+        // Activate the default nodeset for an hour, then revert to the previously active nodeset 7
+        sqlx::query("UPDATE settings SET setting_value = '7' WHERE setting_key = 'active_nodeset'")
+            .execute(pool)
+            .await
+            .unwrap();
+        db::scheduled_activations::insert(pool, DEFAULT_NODESET_ID, 1_000, Some(60)).await.unwrap();
+
+        assert_eq!(run_due_activations(&state, 999).await.unwrap(), 0);
+        assert_eq!(run_due_activations(&state, 1_000).await.unwrap(), 1);
+        assert_eq!(get_active_nodeset_id(pool).await.unwrap(), DEFAULT_NODESET_ID);
+
+        let pending = db::scheduled_activations::get_pending(pool).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].nodeset_id, 7);
+        assert_eq!(pending[0].activate_at, 1_000 + 60 * 60);
+        assert_eq!(pending[0].revert_after_minutes, None);
+    }
+
+    #[tokio::test]
+    async fn test_failed_activation_is_recorded() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+
+        let activation = db::scheduled_activations::insert(pool, 4242, 1_000, Some(60)).await.unwrap();
+        assert_eq!(run_due_activations(&state, 1_000).await.unwrap(), 0);
+
+        // Nothing is pending anymore, and no revert was queued
+        assert!(db::scheduled_activations::get_pending(pool).await.unwrap().is_empty());
+        let (status, error) = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT status, error FROM scheduled_activations WHERE id = $1",
+        )
+        .bind(activation.id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        assert_eq!(status, db::scheduled_activations::STATUS_FAILED);
+        assert_eq!(error.as_deref(), Some("Nodeset not found"));
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_revert_is_skipped() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let pool = &state.pool;
+
+        db::scheduled_activations::insert(pool, DEFAULT_NODESET_ID, 1_000, Some(i64::MAX)).await.unwrap();
+        assert_eq!(run_due_activations(&state, 1_000).await.unwrap(), 1);
+        assert!(db::scheduled_activations::get_pending(pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_revert_after_minutes_is_bounded() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let activate_at = chrono::Utc::now().timestamp() + 3600;
+
+        for minutes in [0, MAX_REVERT_MINUTES + 1, i64::MAX] {
+            let request = ActivateAtRequest { activate_at, revert_after_minutes: Some(minutes) };
+            let response = schedule_activation(State(state.clone()), Path(DEFAULT_NODESET_ID), Json(request)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "revert_after_minutes {}", minutes);
+        }
+        assert!(db::scheduled_activations::get_pending(&state.pool).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+//! Saved test cases of nodesets
+//!
+//! A test case is a named set of execution inputs with the decision the nodeset should make on
+//! them: the terminal node it reaches and, for Execute Action, the action fields that matter. Cases
+//! are saved through `POST /api/simulator/testcases` and `POST /api/nodes/nodesets/:id/run-tests`
+//! runs all cases of a nodeset, so a profile can be checked after every edit. Cases run without
+//! any live data, inputs that aren't given use their defaults.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::nodes::NodeConfiguration;
+use crate::{
+    ac_controller::cycle_snapshots,
+    app_state::AppState,
+    db::{self, nodeset_testcases::TestCaseFields},
+    nodes::{validate_nodeset_for_execution, ActionResult, ExecutionInputs, NodesetExecutor},
+    types::{ApiError, ApiResponse, db_types::NodesetTestCase},
+};
+
+/// Terminal types a test case can expect
+const TERMINAL_TYPES: [&str; 2] = ["Execute Action", "Do Nothing"];
+
+/// Action fields a test case expects, fields that are omitted aren't compared
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct ExpectedAction {
+    /// e.g. "Heat", "Cool" or "Off"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fan_speed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_powerful: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_swing: Option<bool>,
+    /// Cause reason ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause_reason: Option<String>,
+}
+
+impl ExpectedAction {
+    /// Differences between the expected fields and the action, empty if it matches
+    fn mismatches(&self, action: &ActionResult, float_tolerance: f64) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        let mut check = |field: &str, matches: bool, expected: String, actual: String| {
+            if !matches {
+                mismatches.push(format!("Expected {} {}, got {}", field, expected, actual));
+            }
+        };
+        if let Some(mode) = &self.mode {
+            check("mode", mode.eq_ignore_ascii_case(&action.mode), mode.clone(), action.mode.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            let matches = (temperature - action.temperature).abs() <= float_tolerance;
+            check("temperature", matches, temperature.to_string(), action.temperature.to_string());
+        }
+        if let Some(fan_speed) = &self.fan_speed {
+            let matches = fan_speed.eq_ignore_ascii_case(&action.fan_speed);
+            check("fan speed", matches, fan_speed.clone(), action.fan_speed.clone());
+        }
+        if let Some(is_powerful) = self.is_powerful {
+            let matches = is_powerful == action.is_powerful;
+            check("powerful", matches, is_powerful.to_string(), action.is_powerful.to_string());
+        }
+        if let Some(enable_swing) = self.enable_swing {
+            let matches = enable_swing == action.enable_swing;
+            check("swing", matches, enable_swing.to_string(), action.enable_swing.to_string());
+        }
+        if let Some(cause_reason) = &self.cause_reason {
+            let matches = cause_reason.trim() == action.cause_reason.trim();
+            check("cause reason", matches, cause_reason.clone(), action.cause_reason.clone());
+        }
+        mismatches
+    }
+}
+
+/// Request for saving a test case
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTestCaseRequest {
+    pub nodeset_id: i64,
+    pub name: String,
+    /// Execution inputs in the format of the Start node outputs, missing fields use their defaults
+    #[serde(default)]
+    pub inputs: serde_json::Value,
+    /// "Execute Action" or "Do Nothing"
+    pub expected_terminal: String,
+    /// Only with "Execute Action"
+    #[serde(default)]
+    pub expected_action: Option<ExpectedAction>,
+}
+
+/// A saved test case
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TestCase {
+    pub id: i64,
+    pub nodeset_id: i64,
+    pub name: String,
+    pub inputs: serde_json::Value,
+    pub expected_terminal: String,
+    pub expected_action: Option<ExpectedAction>,
+    pub created_at: i64,
+}
+
+impl From<NodesetTestCase> for TestCase {
+    fn from(record: NodesetTestCase) -> Self {
+        Self {
+            id: record.id,
+            nodeset_id: record.nodeset_id,
+            name: record.name,
+            inputs: serde_json::from_str(&record.inputs).unwrap_or(serde_json::Value::Null),
+            expected_terminal: record.expected_terminal,
+            expected_action: record.expected_action.and_then(|action| serde_json::from_str(&action).ok()),
+            created_at: record.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TestCasesQuery {
+    pub nodeset_id: i64,
+}
+
+/// Outcome of one test case
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TestCaseResult {
+    pub id: i64,
+    pub name: String,
+    pub passed: bool,
+    /// e.g. "Execute Action: Heat 22°C", "Do Nothing", "Error: ..."
+    pub decision: String,
+    /// Why the case failed, empty if it passed
+    pub failures: Vec<String>,
+}
+
+/// Outcome of all test cases of a nodeset
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TestRunResult {
+    pub nodeset_id: i64,
+    pub passed: usize,
+    pub failed: usize,
+    pub cases: Vec<TestCaseResult>,
+}
+
+/// Run a test case through a nodeset
+fn run_case(
+    nodes: &[serde_json::Value],
+    edges: &[serde_json::Value],
+    case: &NodesetTestCase,
+    float_tolerance: f64,
+) -> TestCaseResult {
+    let failed = |decision: String, failure: String| TestCaseResult {
+        id: case.id,
+        name: case.name.clone(),
+        passed: false,
+        decision,
+        failures: vec![failure],
+    };
+    let inputs: ExecutionInputs = match serde_json::from_str(&case.inputs) {
+        Ok(inputs) => inputs,
+        Err(e) => return failed("Error: invalid inputs".to_string(), format!("Invalid inputs: {}", e)),
+    };
+    let mut executor = match NodesetExecutor::new(nodes, edges, inputs) {
+        Ok(executor) => executor.with_float_tolerance(float_tolerance),
+        Err(e) => return failed(format!("Error: {}", e), format!("Failed to create executor: {}", e)),
+    };
+    let result = executor.execute();
+    let decision = cycle_snapshots::describe_decision(&result);
+
+    let mut failures = Vec::new();
+    if let Some(error) = &result.error {
+        failures.push(format!("Execution failed: {}", error));
+    } else if result.terminal_type.as_deref() != Some(case.expected_terminal.as_str()) {
+        failures.push(format!(
+            "Expected {}, reached {}",
+            case.expected_terminal,
+            result.terminal_type.as_deref().unwrap_or("no terminal node")
+        ));
+    } else if let (Some(expected), Some(action)) = (&case.expected_action, &result.action) {
+        match serde_json::from_str::<ExpectedAction>(expected) {
+            Ok(expected) => failures.extend(expected.mismatches(action, float_tolerance)),
+            Err(e) => failures.push(format!("Invalid expected action: {}", e)),
+        }
+    }
+
+    TestCaseResult {
+        id: case.id,
+        name: case.name.clone(),
+        passed: failures.is_empty(),
+        decision,
+        failures,
+    }
+}
+
+/// GET /api/simulator/testcases?nodeset_id=2
+/// Returns the saved test cases of a nodeset
+#[utoipa::path(
+    get,
+    path = "/api/simulator/testcases",
+    tag = "simulator",
+    params(TestCasesQuery),
+    responses((status = 200, body = ApiResponse<Vec<TestCase>>))
+)]
+pub async fn list_testcases(State(state): State<AppState>, Query(params): Query<TestCasesQuery>) -> Response {
+    match db::nodeset_testcases::get_for_nodeset(&state.pool, params.nodeset_id).await {
+        Ok(cases) => {
+            let cases: Vec<TestCase> = cases.into_iter().map(TestCase::from).collect();
+            let response = ApiResponse::success(cases);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to list test cases: {}", e);
+            let response = ApiResponse::<()>::error("Failed to list test cases");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// POST /api/simulator/testcases
+/// Saves a test case for a nodeset
+#[utoipa::path(
+    post,
+    path = "/api/simulator/testcases",
+    tag = "simulator",
+    request_body = CreateTestCaseRequest,
+    responses(
+        (status = 201, body = ApiResponse<TestCase>),
+        (status = 400, description = "Empty name, invalid inputs or an unknown terminal", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+pub async fn create_testcase(State(state): State<AppState>, Json(request): Json<CreateTestCaseRequest>) -> Response {
+    let bad_request = |message: String| {
+        let response = ApiResponse::<()>::error(message);
+        (StatusCode::BAD_REQUEST, Json(response)).into_response()
+    };
+    let name = request.name.trim();
+    if name.is_empty() {
+        return bad_request("Name cannot be empty".to_string());
+    }
+    if !TERMINAL_TYPES.contains(&request.expected_terminal.as_str()) {
+        return bad_request(format!("Expected terminal must be one of {}", TERMINAL_TYPES.join(", ")));
+    }
+    if request.expected_action.is_some() && request.expected_terminal != "Execute Action" {
+        return bad_request("An expected action requires the Execute Action terminal".to_string());
+    }
+    let inputs = match request.inputs {
+        serde_json::Value::Null => serde_json::json!({}),
+        inputs => inputs,
+    };
+    if let Err(e) = serde_json::from_value::<ExecutionInputs>(inputs.clone()) {
+        return bad_request(format!("Invalid inputs: {}", e));
+    }
+
+    let exists = sqlx::query_as::<_, (i64,)>("SELECT id FROM nodesets WHERE id = $1")
+        .bind(request.nodeset_id)
+        .fetch_optional(&state.pool)
+        .await;
+    match exists {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let response = ApiResponse::<()>::error(format!("Nodeset with id {} not found", request.nodeset_id));
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset: {}", e);
+            let response = ApiResponse::<()>::error("Failed to save test case");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    }
+
+    let inputs = inputs.to_string();
+    let expected_action = request
+        .expected_action
+        .as_ref()
+        .map(|action| serde_json::to_string(action).expect("expected action serializes"));
+    let fields = TestCaseFields {
+        nodeset_id: request.nodeset_id,
+        name,
+        inputs: &inputs,
+        expected_terminal: &request.expected_terminal,
+        expected_action: expected_action.as_deref(),
+    };
+    match db::nodeset_testcases::insert(&state.pool, &fields).await {
+        Ok(case) => {
+            log::info!("Created test case '{}' for nodeset {}", case.name, case.nodeset_id);
+            let response = ApiResponse::success(TestCase::from(case));
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to create test case: {}", e);
+            let response = ApiResponse::<()>::error("Failed to save test case");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// DELETE /api/simulator/testcases/:id
+/// Deletes a test case
+#[utoipa::path(
+    delete,
+    path = "/api/simulator/testcases/{id}",
+    tag = "simulator",
+    params(("id" = i64, Path, description = "Test case ID")),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 404, description = "Test case not found", body = ApiError),
+    )
+)]
+pub async fn delete_testcase(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    match db::nodeset_testcases::delete(&state.pool, id).await {
+        Ok(true) => {
+            let response = ApiResponse::success("Test case deleted");
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(false) => {
+            let response = ApiResponse::<()>::error("Test case not found");
+            (StatusCode::NOT_FOUND, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to delete test case: {}", e);
+            let response = ApiResponse::<()>::error("Failed to delete test case");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// POST /api/nodes/nodesets/:id/run-tests
+/// Runs all test cases of a saved nodeset and returns which passed (TestRunResult)
+#[utoipa::path(
+    post,
+    path = "/api/nodes/nodesets/{id}/run-tests",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, body = ApiResponse<TestRunResult>),
+        (status = 400, description = "The nodeset's calls can't be expanded or it is invalid", body = ApiError),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+pub async fn run_tests(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let node_json = sqlx::query_as::<_, (String,)>("SELECT node_json FROM nodesets WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await;
+    let config = match node_json {
+        Ok(Some((node_json,))) => match NodeConfiguration::from_stored_json(&node_json) {
+            Ok(config) => config,
+            Err(e) => {
+                let response = ApiResponse::<()>::error(format!("Failed to parse nodeset configuration: {}", e));
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            }
+        },
+        Ok(None) => {
+            let response = ApiResponse::<()>::error(format!("Nodeset with id {} not found", id));
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            let response = ApiResponse::<()>::error(format!("Failed to fetch nodeset: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+    let (nodes, edges) = match db::nodesets::expand_calls(&state.pool, Some(id), config.resolved_nodes(), config.edges).await {
+        Ok(expanded) => expanded,
+        Err(e) => {
+            let response = ApiResponse::<()>::error(e);
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+    let validation_errors = validate_nodeset_for_execution(&nodes, &edges);
+    if !validation_errors.is_empty() {
+        let response = ApiResponse::<()>::error(format!("Nodeset validation failed: {}", validation_errors.join("; ")));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let cases = match db::nodeset_testcases::get_for_nodeset(&state.pool, id).await {
+        Ok(cases) => cases,
+        Err(e) => {
+            let response = ApiResponse::<()>::error(format!("Failed to load the test cases: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let float_tolerance = state.config().float_tolerance;
+    let cases: Vec<TestCaseResult> = cases
+        .iter()
+        .map(|case| run_case(&nodes, &edges, case, float_tolerance))
+        .collect();
+    let passed = cases.iter().filter(|case| case.passed).count();
+    let result = TestRunResult {
+        nodeset_id: id,
+        passed,
+        failed: cases.len() - passed,
+        cases,
+    };
+
+    (StatusCode::OK, Json(ApiResponse::success(result))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers::api_request;
+
+    #[test]
+    fn test_expected_action_compares_only_given_fields() {
+        let action = ActionResult {
+            device: "Veranda".to_string(),
+            temperature: 22.0,
+            mode: "Heat".to_string(),
+            fan_speed: "Auto".to_string(),
+            is_powerful: false,
+            enable_swing: false,
+            cause_reason: "6".to_string(),
+            vertical_vane: None,
+            horizontal_vane: None,
+            annotation: None,
+        };
+        let expected = ExpectedAction {
+            mode: Some("heat".to_string()),
+            temperature: Some(22.00001),
+            ..Default::default()
+        };
+        assert!(expected.mismatches(&action, 0.0001).is_empty());
+
+        let expected = ExpectedAction {
+            is_powerful: Some(true),
+            cause_reason: Some("1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            expected.mismatches(&action, 0.0001),
+            vec!["Expected powerful true, got false", "Expected cause reason 1, got 6"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_and_run_testcases() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        let simulator = || super::super::simulator::simulator_routes(state.clone());
+        let nodes = || super::super::nodes::nodes_routes(state.clone());
+
+        // The same inputs can't both do nothing and execute an action, so exactly one case passes
+        let inputs = serde_json::json!({ "device": "Veranda", "outdoor_temperature": { "0": 8.0 } });
+        let (status, _) = api_request(simulator(), "POST", "/testcases", Some(serde_json::json!({
+            "nodeset_id": 0, "name": "Invalid", "inputs": inputs, "expected_terminal": "Do Nothing"
+        }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "temperatures are plain numbers");
+
+        let inputs = serde_json::json!({ "device": "Veranda", "outdoor_temperature": 8.0, "is_user_home": true });
+        for terminal in TERMINAL_TYPES {
+            let (status, body) = api_request(simulator(), "POST", "/testcases", Some(serde_json::json!({
+                "nodeset_id": 0, "name": terminal, "inputs": inputs, "expected_terminal": terminal
+            }))).await;
+            assert_eq!(status, StatusCode::CREATED);
+            assert_eq!(body["data"]["inputs"], inputs);
+        }
+        let (status, _) = api_request(simulator(), "POST", "/testcases", Some(serde_json::json!({
+            "nodeset_id": 0, "name": "Idle", "expected_terminal": "Do Nothing", "expected_action": { "mode": "Heat" }
+        }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, body) = api_request(simulator(), "GET", "/testcases?nodeset_id=0", None).await;
+        let cases = body["data"].as_array().unwrap();
+        assert_eq!(cases.len(), 2);
+
+        let (status, body) = api_request(nodes(), "POST", "/nodesets/0/run-tests", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["passed"], 1);
+        assert_eq!(body["data"]["failed"], 1);
+        let failed = body["data"]["cases"].as_array().unwrap().iter().find(|case| case["passed"] == false).unwrap();
+        assert!(failed["failures"][0].as_str().unwrap().starts_with("Expected "));
+
+        let id = cases[0]["id"].as_i64().unwrap();
+        let (status, _) = api_request(simulator(), "DELETE", &format!("/testcases/{}", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = api_request(nodes(), "POST", "/nodesets/999/run-tests", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,410 @@
+//! Nodeset export and import
+//!
+//! `GET /api/nodes/nodesets/:id/export` serializes a nodeset to a portable JSON document that can
+//! be kept in git or imported on another install with `POST /api/nodes/nodesets/import`.
+//! The document carries the nodeset schema version, so older exports are migrated on import
+//! and exports from a newer version are refused, and the port version of every node type it
+//! uses, so the import can point out nodes whose connections may need checking.
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State, rejection::BytesRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+use super::{
+    nodes::{
+        db_error_response, find_missing_cause_reasons, insert_nodeset, reject_unknown_cause_reasons,
+        remove_orphaned_edges, schema_error_response, update_node_definitions, NodeConfiguration, Nodeset,
+    },
+    nodeset_payload,
+};
+use crate::{
+    app_state::AppState,
+    db,
+    nodes::{self, schema::{self, SchemaError}, variables::{self, Variables}},
+    types::{ApiError, ApiResponse},
+};
+
+/// Identifies an exported nodeset document
+const EXPORT_FORMAT: &str = "power_control_center.nodeset";
+
+/// Portable nodeset document
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NodesetExport {
+    /// Always EXPORT_FORMAT
+    pub format: String,
+    /// Nodeset schema version of `nodes` and `edges`
+    pub schema_version: u32,
+    /// Version of the application that exported the nodeset
+    #[serde(default)]
+    pub app_version: String,
+    /// Unix timestamp of the export
+    #[serde(default)]
+    pub exported_at: i64,
+    pub name: String,
+    /// Port version of every node type used, by node type
+    #[serde(default)]
+    pub node_definitions: BTreeMap<String, String>,
+    pub nodes: Vec<serde_json::Value>,
+    pub edges: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub variables: Variables,
+}
+
+/// Result of an import
+#[derive(Serialize, ToSchema)]
+pub struct ImportResult {
+    pub nodeset: Nodeset,
+    /// Things to check before activating the imported nodeset
+    pub warnings: Vec<String>,
+}
+
+/// Get the node type of a stored node
+fn node_type(node: &serde_json::Value) -> Option<&str> {
+    node.get("data")
+        .and_then(|d| d.get("definition"))
+        .and_then(|def| def.get("node_type"))
+        .and_then(|nt| nt.as_str())
+}
+
+/// Port version of every current node type
+fn current_port_versions() -> HashMap<String, String> {
+    nodes::get_all_node_definitions()
+        .into_iter()
+        .map(|def| (def.node_type.clone(), def.port_version()))
+        .collect()
+}
+
+/// Build the export document of a nodeset
+fn build_export(name: String, nodes: Vec<serde_json::Value>, edges: Vec<serde_json::Value>, variables: Variables) -> NodesetExport {
+    let versions = current_port_versions();
+    let node_definitions = nodes
+        .iter()
+        .filter_map(node_type)
+        .filter_map(|nt| versions.get(nt).map(|version| (nt.to_string(), version.clone())))
+        .collect();
+
+    NodesetExport {
+        format: EXPORT_FORMAT.to_string(),
+        schema_version: schema::CURRENT_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+        name,
+        node_definitions,
+        nodes,
+        edges,
+        variables,
+    }
+}
+
+/// Check an import against the current node types
+/// Returns the node types that no longer exist, and a warning per node type whose ports changed
+fn check_node_types(export: &NodesetExport) -> (Vec<String>, Vec<String>) {
+    let versions = current_port_versions();
+
+    let mut unknown: Vec<String> = export
+        .nodes
+        .iter()
+        .filter_map(node_type)
+        .filter(|nt| !versions.contains_key(*nt))
+        .map(String::from)
+        .collect();
+    unknown.sort();
+    unknown.dedup();
+
+    let changed = export
+        .node_definitions
+        .iter()
+        .filter(|(nt, version)| versions.get(*nt).is_some_and(|current| current != *version))
+        .map(|(nt, _)| format!("Node type '{}' changed since the export, check its connections", nt))
+        .collect();
+
+    (unknown, changed)
+}
+
+/// File name for an exported nodeset, e.g. "night-mode.json"
+fn export_file_name(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "nodeset.json".to_string() } else { format!("{}.json", slug) }
+}
+
+/// GET /api/nodes/nodesets/:id/export
+/// Returns the nodeset as a downloadable export document (not wrapped in the API response envelope)
+#[utoipa::path(
+    get,
+    path = "/api/nodes/nodesets/{id}/export",
+    tag = "nodes",
+    params(("id" = i64, Path, description = "Nodeset ID")),
+    responses(
+        (status = 200, description = "Export document as an attachment", body = NodesetExport),
+        (status = 404, description = "Nodeset not found", body = ApiError),
+    )
+)]
+pub async fn export_nodeset(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let pool = &state.pool;
+
+    let (name, node_json) = match sqlx::query_as::<_, (String, String)>("SELECT name, node_json FROM nodesets WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(row)) => row,
+        Ok(None) => {
+            let response = ApiResponse::<()>::error("Nodeset not found");
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to fetch nodeset {} for export: {}", id, e);
+            return db_error_response(e, "Failed to fetch nodeset");
+        }
+    };
+
+    let config = match NodeConfiguration::from_stored_json(&node_json) {
+        Ok(config) => config,
+        Err(e) => return schema_error_response(e, "nodeset configuration"),
+    };
+    let (nodes, removed_node_ids) = update_node_definitions(pool, &state.devices(), config.nodes).await;
+    let edges = remove_orphaned_edges(config.edges, &removed_node_ids);
+
+    let file_name = export_file_name(&name);
+    let export = build_export(name, nodes, edges, config.variables);
+    match serde_json::to_string_pretty(&export) {
+        Ok(json) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/json".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)),
+            ],
+            json,
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to serialize nodeset {} for export: {}", id, e);
+            let response = ApiResponse::<()>::error("Failed to export nodeset");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+/// POST /api/nodes/nodesets/import
+/// Creates a new nodeset from an export document
+/// Returns the new nodeset and warnings about things to check before activating it
+#[utoipa::path(
+    post,
+    path = "/api/nodes/nodesets/import",
+    tag = "nodes",
+    request_body = NodesetExport,
+    responses(
+        (status = 201, body = ApiResponse<ImportResult>),
+        (status = 400, description = "Not an export document, or it has unknown node types or invalid variables", body = ApiError),
+        (status = 409, description = "Exported by a newer version", body = ApiError),
+        (status = 413, description = "Body over `nodeset_max_body_bytes`", body = ApiError),
+    )
+)]
+pub async fn import_nodeset(State(state): State<AppState>, body: Result<Bytes, BytesRejection>) -> Response {
+    let mut doc: serde_json::Value = match nodeset_payload::parse_json_body(state.config().nodeset_max_body_bytes, body).await {
+        Ok(doc) => doc,
+        Err(response) => return response,
+    };
+    let pool = &state.pool;
+
+    if doc.get("format").and_then(|f| f.as_str()) != Some(EXPORT_FORMAT) {
+        let response = ApiResponse::<()>::error(format!("Not a nodeset export, expected format '{}'", EXPORT_FORMAT));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    // Upgrade exports from older versions, refuse exports from newer versions
+    if let Err(e) = schema::migrate_nodeset(&mut doc) {
+        let status = match e {
+            SchemaError::NewerVersion { .. } => StatusCode::CONFLICT,
+            SchemaError::Invalid(_) => StatusCode::BAD_REQUEST,
+        };
+        let response = ApiResponse::<()>::error(e.to_string());
+        return (status, Json(response)).into_response();
+    }
+    let export: NodesetExport = match serde_json::from_value(doc) {
+        Ok(export) => export,
+        Err(e) => {
+            let response = ApiResponse::<()>::error(format!("Invalid nodeset export: {}", e));
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    if export.name.trim().is_empty() {
+        let response = ApiResponse::<()>::error("Nodeset name cannot be empty");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let variable_errors = variables::validate_variables(&export.variables);
+    if !variable_errors.is_empty() {
+        let response = ApiResponse::<()>::error(format!("Invalid variables: {}", variable_errors.join("; ")));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let (unknown_types, mut warnings) = check_node_types(&export);
+    if !unknown_types.is_empty() {
+        let response = ApiResponse::<()>::error(format!(
+            "Unknown node types: {}. The nodeset was exported from a different version of Power Control Center.",
+            unknown_types.join(", ")
+        ));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    // Cause reasons are per install, with strict_cause_reasons missing ones are rejected as when saving
+    if let Some(response) = reject_unknown_cause_reasons(&state, &export.nodes, &export.edges).await {
+        return response;
+    }
+    match db::cause_reasons::get_all(pool, true).await {
+        Ok(cause_reasons) => warnings.extend(find_missing_cause_reasons(
+            &export.nodes,
+            &export.edges,
+            &db::cause_reasons::known_references(&cause_reasons),
+        )),
+        Err(e) => return db_error_response(e, "Failed to load cause reasons"),
+    }
+
+    // Called profiles are referenced by id, which differs between installs
+    if nodes::calls::has_calls(&export.nodes) {
+        match db::nodesets::load_call_library(pool).await {
+            Ok(library) => {
+                if let Err(e) = nodes::calls::expand_calls(None, &export.nodes, &export.edges, &library) {
+                    warnings.push(format!("Profile calls need to be reselected: {}", e));
+                }
+            }
+            Err(e) => return db_error_response(e, "Failed to load called profiles"),
+        }
+    }
+
+    let (nodes, removed_node_ids) = update_node_definitions(pool, &state.devices(), export.nodes).await;
+    let edges = remove_orphaned_edges(export.edges, &removed_node_ids);
+    let config = NodeConfiguration::new(nodes.clone(), edges.clone()).with_variables(export.variables.clone());
+    let json_str = match serde_json::to_string(&config) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to serialize imported nodeset: {}", e);
+            let response = ApiResponse::<()>::error("Failed to serialize nodeset configuration");
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    let name = export.name.trim().to_string();
+    match insert_nodeset(pool, &state.config(), &name, &json_str).await {
+        Ok(id) => {
+            log::info!("Nodeset '{}' imported with id {} ({} warnings)", name, id, warnings.len());
+            let response = ApiResponse::success(ImportResult {
+                nodeset: Nodeset { id, name, nodes, edges, variables: export.variables },
+                warnings,
+            });
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to import nodeset: {}", e);
+            db_error_response(e, "Failed to import nodeset")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(export_file_name("Night Mode"), "night-mode.json");
+        assert_eq!(export_file_name("  Vacation (2 weeks)!"), "vacation-2-weeks.json");
+        assert_eq!(export_file_name("🌙"), "nodeset.json");
+    }
+
+    #[test]
+    fn test_check_node_types() {
+        let node = |node_type: &str| json!({ "id": format!("{}-1", node_type), "data": { "definition": { "node_type": node_type } } });
+        let mut export = build_export("Test".to_string(), vec![node("flow_start"), node("logic_and")], vec![], Variables::new());
+        assert_eq!(export.node_definitions.len(), 2);
+        assert_eq!(check_node_types(&export), (vec![], vec![]));
+
+        export.node_definitions.insert("logic_and".to_string(), "0000000000000000".to_string());
+        export.nodes.push(node("teleport"));
+        let (unknown, changed) = check_node_types(&export);
+        assert_eq!(unknown, vec!["teleport"]);
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].contains("'logic_and'"));
+    }
+
+    async fn request(state: &AppState, method: &str, uri: &str, body: Option<String>) -> (StatusCode, Option<String>, String) {
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map(axum::body::Body::from).unwrap_or_else(axum::body::Body::empty))
+            .unwrap();
+        let response = super::super::nodes::nodes_routes(state.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let disposition = response
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .map(|v| v.to_str().unwrap().to_string());
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, disposition, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_round_trip() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let (status, disposition, exported) = request(&state, "GET", "/nodesets/0/export", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(disposition.unwrap().starts_with("attachment; filename="));
+        let export: serde_json::Value = serde_json::from_str(&exported).unwrap();
+        assert_eq!(export["format"], EXPORT_FORMAT);
+        assert_eq!(export["schema_version"], schema::CURRENT_SCHEMA_VERSION);
+        assert!(export["node_definitions"]["flow_start"].is_string());
+
+        let (status, _, body) = request(&state, "POST", "/nodesets/import", Some(exported)).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+        let imported: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(imported["data"]["warnings"], json!([]));
+        assert_eq!(imported["data"]["nodeset"]["nodes"], export["nodes"]);
+        assert_eq!(imported["data"]["nodeset"]["edges"], export["edges"]);
+
+        let (status, _, _) = request(&state, "GET", "/nodesets/999/export", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_foreign_and_newer_documents() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let plain = json!({ "name": "Plain", "nodes": [], "edges": [] });
+        let (status, _, _) = request(&state, "POST", "/nodesets/import", Some(plain.to_string())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let newer = json!({
+            "format": EXPORT_FORMAT,
+            "schema_version": schema::CURRENT_SCHEMA_VERSION + 1,
+            "name": "Future",
+            "nodes": [],
+            "edges": [],
+        });
+        let (status, _, _) = request(&state, "POST", "/nodesets/import", Some(newer.to_string())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // Unversioned exports are migrated
+        let unversioned = json!({ "format": EXPORT_FORMAT, "name": "Old" });
+        let (status, _, body) = request(&state, "POST", "/nodesets/import", Some(unversioned.to_string())).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", body);
+    }
+}
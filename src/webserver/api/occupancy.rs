@@ -0,0 +1,147 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    ac_controller::{occupancy::OccupancyEvent, time_helpers},
+    app_state::AppState,
+    types::{ApiError, ApiResponse},
+};
+
+pub fn occupancy_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_occupancy))
+        .route("/door", post(door_event))
+        .with_state(state)
+}
+
+/// Occupancy of a device's room
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct RoomOccupancy {
+    pub device: String,
+    /// Probability (0-1) that the room is occupied, the evidence combined with the prior
+    pub probability: f64,
+    /// Decayed evidence (0-1) of the PIR and door sensor events
+    pub evidence: f64,
+    /// Prior (0-1) for the current local hour
+    pub prior: f64,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct DoorEventRequest {
+    device: String,
+}
+
+/// GET /api/occupancy
+/// Returns the occupancy of every device's room
+#[utoipa::path(get, path = "/api/occupancy", tag = "occupancy", responses((status = 200, body = ApiResponse<Vec<RoomOccupancy>>)))]
+async fn get_occupancy(State(state): State<AppState>) -> Response {
+    let config = state.config();
+    let now = chrono::Utc::now();
+    let hour = time_helpers::local_time_now().hour as u32;
+
+    let rooms: Vec<RoomOccupancy> = state
+        .devices()
+        .all()
+        .iter()
+        .map(|device| {
+            let device = device.as_str();
+            RoomOccupancy {
+                device: device.to_string(),
+                probability: state.occupancy.probability(device, &config.occupancy, hour, now),
+                evidence: state.occupancy.evidence(device, &config.occupancy, now),
+                prior: config.occupancy.prior(hour),
+            }
+        })
+        .collect();
+
+    let response = ApiResponse::success(rooms);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// POST /api/occupancy/door?device=Veranda
+/// Records a door sensor event (opened or closed) for the room of a device
+#[utoipa::path(
+    post,
+    path = "/api/occupancy/door",
+    tag = "occupancy",
+    params(DoorEventRequest),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 400, description = "Unknown device", body = ApiError),
+        (status = 401, description = "Missing or wrong `pir_api_key`", body = ApiError),
+    )
+)]
+async fn door_event(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<DoorEventRequest>,
+) -> Response {
+    if !super::auth::verify_api_key_header(&headers, &state.config().pir_api_key) {
+        warn!("Unauthorized door sensor event");
+        let response = ApiError::error("Unauthorized");
+        return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
+    }
+
+    if state.devices().get(&params.device).is_none() {
+        warn!("Unknown device name in door sensor event: {}", params.device);
+        let response = ApiError::error("Unknown device");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    info!("Door sensor event received for device: {}", params.device);
+    match state
+        .occupancy
+        .record_and_store(&state.pool, &params.device, OccupancyEvent::Door, &state.config().occupancy)
+        .await
+    {
+        Ok(()) => {
+            let response = ApiResponse::success("Door sensor event recorded");
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            warn!("Failed to store the occupancy of {}: {}", params.device, e);
+            let response = ApiError::error("Failed to store the door sensor event");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+
+    async fn api_request(state: &AppState, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        test_helpers::api_request(occupancy_routes(state.clone()), method, uri, None).await
+    }
+
+    #[tokio::test]
+    async fn test_door_event_raises_occupancy() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let (status, _) = api_request(&state, "POST", "/door?device=Veranda").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = api_request(&state, "POST", "/door?device=Attic").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = api_request(&state, "GET", "/").await;
+        assert_eq!(status, StatusCode::OK);
+        let rooms = body["data"].as_array().unwrap();
+        let veranda = rooms.iter().find(|r| r["device"] == "Veranda").unwrap();
+        let door_weight = state.config().occupancy.door_weight;
+        assert!((veranda["evidence"].as_f64().unwrap() - door_weight).abs() < 0.01);
+        let living_room = rooms.iter().find(|r| r["device"] == "LivingRoom").unwrap();
+        assert_eq!(living_room["evidence"], 0.0);
+    }
+}
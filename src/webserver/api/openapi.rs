@@ -0,0 +1,210 @@
+//! OpenAPI description of the API
+//!
+//! Handlers are annotated with `#[utoipa::path]` and listed in `ApiDoc`, the request and response
+//! types derive `ToSchema`. The spec is served at `/api/openapi.json` and can be browsed with
+//! Swagger UI at `/swagger-ui`. A route that isn't listed here is missing from the spec, so add
+//! new handlers to `paths` along with their route.
+
+use axum::Json;
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+};
+
+use super::{
+    ac, admin, cause_reasons, config, dashboard, debug, demand_response, devices, hooks, logs,
+    node_executions, nodes, nodeset_assignments, nodeset_calendar, nodeset_schedule, nodeset_tests,
+    nodeset_transfer, occupancy, panel, pir, presence, rpc, sensors, simulator, simulator_replay,
+    statistics, telemetry, ups, user_home, weather, ws,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Power Control Center",
+        description = "Endpoints answer with the `ApiResponse` envelope (`success`, `data` and `error`), except `/api/rpc` which answers with JSON-RPC 2.0."
+    ),
+    modifiers(&ApiKeyAuth),
+    // `data` of ApiError (ApiResponse<()>)
+    components(schemas(utoipa::TupleUnit)),
+    paths(
+        super::status_handler,
+        super::status_details_handler,
+        super::health_handler,
+        ws::ws_handler,
+        ac::get_history_page,
+        ac::get_history_count,
+        ac::reset_device_state,
+        ac::get_shadow_mode,
+        ac::set_shadow_mode,
+        ac::get_min_off_time,
+        ac::clear_min_off_time,
+        ac::replay_last_command,
+        ac::set_manual_override,
+        ac::clear_manual_override,
+        devices::list_devices,
+        devices::set_display_name,
+        devices::remove_display_name,
+        dashboard::get_dashboard_status,
+        dashboard::get_recent_commands,
+        dashboard::get_cycles,
+        dashboard::get_energy,
+        dashboard::get_history,
+        nodes::get_node_configuration,
+        nodes::list_nodesets,
+        nodes::get_nodeset,
+        nodes::get_nodeset_interface,
+        nodes::create_nodeset,
+        nodes::update_nodeset,
+        nodes::update_nodeset_variables,
+        nodes::list_nodeset_revisions,
+        nodes::restore_nodeset_revision,
+        nodes::delete_nodeset,
+        nodes::get_active_nodeset,
+        nodes::set_active_nodeset,
+        nodes::analyze_nodeset,
+        nodes::get_payload_metrics,
+        nodes::get_node_definitions,
+        nodeset_transfer::export_nodeset,
+        nodeset_transfer::import_nodeset,
+        nodeset_assignments::list_assignments,
+        nodeset_assignments::assign_nodeset,
+        nodeset_assignments::unassign_nodeset,
+        nodeset_assignments::set_interval,
+        nodeset_assignments::clear_interval,
+        nodeset_schedule::schedule_activation,
+        nodeset_schedule::list_scheduled_activations,
+        nodeset_schedule::cancel_scheduled_activation,
+        nodeset_calendar::list_schedules,
+        nodeset_calendar::create_schedule,
+        nodeset_calendar::update_schedule,
+        nodeset_calendar::delete_schedule,
+        node_executions::list_executions,
+        nodeset_tests::list_testcases,
+        nodeset_tests::create_testcase,
+        nodeset_tests::delete_testcase,
+        nodeset_tests::run_tests,
+        simulator::evaluate_workflow,
+        simulator::get_live_inputs,
+        simulator_replay::replay_nodeset,
+        cause_reasons::list_cause_reasons,
+        cause_reasons::list_all_cause_reasons,
+        cause_reasons::get_cause_reason_stats,
+        cause_reasons::get_cause_reason,
+        cause_reasons::create_cause_reason,
+        cause_reasons::update_cause_reason,
+        cause_reasons::delete_cause_reason,
+        cause_reasons::set_hidden_status,
+        cause_reasons::remap_cause_reason,
+        statistics::get_hourly,
+        statistics::get_daily,
+        weather::get_history,
+        pir::pir_detect,
+        pir::pir_sensor_detect,
+        pir::pir_alive,
+        pir::get_pir_state,
+        pir::get_pir_sensors,
+        pir::clear_pir_detection,
+        occupancy::get_occupancy,
+        occupancy::door_event,
+        sensors::get_contact_sensors,
+        sensors::contact_sensor_report,
+        presence::get_presence,
+        presence::set_presence_override,
+        presence::clear_presence_override,
+        user_home::set_user_home,
+        user_home::clear_user_home,
+        hooks::set_away_mode,
+        panel::get_panel_state,
+        panel::adjust_setpoint,
+        rpc::handle_rpc,
+        ups::ups_status,
+        demand_response::set_event,
+        demand_response::clear_event,
+        demand_response::get_status,
+        telemetry::preview,
+        logs::get_logs,
+        logs::stream_logs,
+        config::reload_config,
+        debug::get_raw_payloads_for_source,
+        admin::self_update,
+        admin::download_backup,
+        admin::restore_backup,
+    ),
+    tags(
+        (name = "status", description = "Health of the controller and the live event WebSocket"),
+        (name = "ac", description = "AC command history, overrides and device state"),
+        (name = "devices", description = "Configured AC devices and their display names"),
+        (name = "dashboard", description = "Device status and history shown on the dashboard"),
+        (name = "nodes", description = "Nodesets (profiles): editing, activation, assignments, schedules and executions"),
+        (name = "simulator", description = "Evaluating nodesets without executing actions, replays and saved test cases"),
+        (name = "cause-reasons", description = "Cause reasons set by profiles and how much they drove the ACs"),
+        (name = "statistics", description = "Hourly and daily device statistics and household energy"),
+        (name = "weather", description = "Recorded outdoor weather"),
+        (name = "pir", description = "PIR motion sensor detections"),
+        (name = "occupancy", description = "Room occupancy estimated from PIR and door sensor events"),
+        (name = "sensors", description = "Window and door contact sensors"),
+        (name = "presence", description = "Who is home, overrides and away mode"),
+        (name = "panel", description = "Wall-panel kiosk endpoints"),
+        (name = "rpc", description = "JSON-RPC 2.0 endpoint for local automations"),
+        (name = "ups", description = "UPS power source reports for grid outage detection"),
+        (name = "demand-response", description = "Demand-response events from the utility aggregator"),
+        (name = "telemetry", description = "Preview of the anonymous usage report"),
+        (name = "logs", description = "Recent log records kept in memory, require `admin_api_key`"),
+        (name = "admin", description = "Configuration reload, debugging, updates, backups and restores, require `admin_api_key`"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The `api_key` scheme of endpoints that require one of the configured API keys
+/// Keys are sent as `Authorization: Bearer <key>`, see `auth::verify_api_key_header`
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("api_key", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
+    }
+}
+
+/// GET /api/openapi.json
+/// Returns the OpenAPI spec of the API
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_schemas_exist() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = spec["components"]["schemas"].as_object().cloned().unwrap_or_default();
+        let json = spec.to_string();
+        for reference in json.split("\"$ref\":\"#/components/schemas/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(schemas.contains_key(name), "{} is referenced but not in the spec", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spec_is_served() {
+        use tower::ServiceExt;
+
+        let state = crate::app_state::AppState::for_test(crate::types::Config::default()).await;
+        let request = axum::http::Request::builder()
+            .uri("/openapi.json")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = super::super::api_routes(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let spec: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/api/nodes/nodesets/{id}"));
+        assert!(paths["/api/pir/detect"]["post"]["security"].is_array(), "API key endpoints list their scheme");
+        assert!(paths.keys().all(|path| path.starts_with("/api/") && !path.contains("/:")));
+    }
+}
@@ -0,0 +1,317 @@
+//! Wall-panel kiosk endpoints
+//!
+//! `GET /api/panel/state` returns only what a wall-mounted e-ink or ESP32 display shows, with an
+//! ETag. A panel sends its last ETag in `If-None-Match` and the request is held until the state
+//! changes or `wait` seconds pass (then `304 Not Modified`), so the panel refreshes right away
+//! without polling. `POST /api/panel/adjust` nudges a device's setpoint offset by 0.5°C.
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
+use tokio::time::Instant;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    ac_controller::{ac_executor::ac_mode_name, setpoint_offset, target_temperature},
+    app_state::AppState,
+    db,
+    device_requests,
+    live_events,
+    types::{ApiError, ApiResponse},
+};
+
+/// Setpoint change of one adjust step in °C
+const ADJUST_STEP_C: f64 = 0.5;
+/// Seconds a long-poll is held when `wait` is not given
+const DEFAULT_WAIT_SECS: u64 = 30;
+/// Longest a long-poll is held, shorter than common HTTP client timeouts
+const MAX_WAIT_SECS: u64 = 60;
+/// Sensor readings are not published as live events, so a held request re-checks this often
+const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn panel_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/state", get(get_panel_state))
+        .route("/adjust", post(adjust_setpoint))
+        .with_state(state)
+}
+
+#[derive(Serialize, Hash, ToSchema)]
+struct PanelState {
+    /// Temperatures are rounded to 0.1°C (stored as tenths) so sensor noise doesn't wake panels
+    #[serde(serialize_with = "serialize_optional_tenths")]
+    #[schema(value_type = Option<f64>)]
+    outdoor_temperature: Option<i64>,
+    devices: Vec<PanelDevice>,
+}
+
+#[derive(Serialize, Hash, ToSchema)]
+struct PanelDevice {
+    device: String,
+    display_name: String,
+    is_on: bool,
+    mode: Option<String>,
+    #[serde(serialize_with = "serialize_optional_tenths")]
+    #[schema(value_type = Option<f64>)]
+    indoor_temperature: Option<i64>,
+    #[serde(serialize_with = "serialize_optional_tenths")]
+    #[schema(value_type = Option<f64>)]
+    temperature_setpoint: Option<i64>,
+    #[serde(serialize_with = "serialize_optional_tenths")]
+    #[schema(value_type = Option<f64>)]
+    target_temperature: Option<i64>,
+    #[serde(serialize_with = "serialize_tenths")]
+    #[schema(value_type = f64)]
+    setpoint_offset: i64,
+}
+
+/// Round a temperature to tenths of a degree
+fn to_tenths(celsius: f64) -> i64 {
+    (celsius * 10.0).round() as i64
+}
+
+/// Serialize tenths of a degree as °C
+fn serialize_tenths<S: serde::Serializer>(tenths: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(*tenths as f64 / 10.0)
+}
+
+fn serialize_optional_tenths<S: serde::Serializer>(tenths: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error> {
+    tenths.map(|t| t as f64 / 10.0).serialize(serializer)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct StateQuery {
+    /// Seconds to hold the request while `If-None-Match` matches the current state
+    wait: Option<u64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Up,
+    Down,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct AdjustRequest {
+    device: String,
+    direction: Direction,
+}
+
+#[derive(Serialize, ToSchema)]
+struct AdjustResponse {
+    device: String,
+    setpoint_offset: f64,
+}
+
+/// Quoted ETag of a panel state
+fn etag_of(panel: &PanelState) -> String {
+    // Only compared within one run of the controller, so DefaultHasher is stable enough
+    let mut hasher = DefaultHasher::new();
+    panel.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Check an If-None-Match header value against an ETag, accepting lists and weak tags
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == etag || tag == "*")
+}
+
+async fn build_panel_state(state: &AppState) -> PanelState {
+    let config = state.config();
+    let outdoor_temperature = device_requests::weather::get_current_outdoor_temp_cached(config.latitude, config.longitude)
+        .await
+        .ok();
+    let display_names = db::device_aliases::get_display_names(&state.pool).await.unwrap_or_else(|e| {
+        log::warn!("Failed to get device display names: {}", e);
+        Default::default()
+    });
+
+    let mut devices = Vec::new();
+    for device in state.devices().all() {
+        let device_name = device.as_str();
+        let indoor_temperature = device_requests::ac::get_sensors_cached(device_name)
+            .await
+            .ok()
+            .map(|sensor_data| sensor_data.temperature);
+        let ac_state = state.ac_states.get_state(device_name);
+        let offset = setpoint_offset::get(&state.pool, device_name).await;
+        devices.push(PanelDevice {
+            device: device_name.to_string(),
+            display_name: super::devices::display_name(&display_names, device_name),
+            is_on: ac_state.is_on,
+            mode: ac_state.mode.and_then(ac_mode_name).map(String::from),
+            indoor_temperature: indoor_temperature.map(to_tenths),
+            temperature_setpoint: ac_state.temperature.map(|t| to_tenths(t.0)),
+            target_temperature: target_temperature::get_target_temperatures()
+                .get(device_name)
+                .map(|t| to_tenths(t.0)),
+            setpoint_offset: to_tenths(offset),
+        });
+    }
+
+    PanelState {
+        outdoor_temperature: outdoor_temperature.map(to_tenths),
+        devices,
+    }
+}
+
+/// GET /api/panel/state
+/// Returns the panel state with an ETag. With a matching `If-None-Match`, waits up to `wait` seconds
+/// (default 30, max 60) for a change and returns 304 Not Modified if nothing changed.
+#[utoipa::path(
+    get,
+    path = "/api/panel/state",
+    tag = "panel",
+    params(StateQuery, ("If-None-Match" = Option<String>, Header, description = "ETag of the state the panel shows")),
+    responses(
+        (status = 200, body = ApiResponse<PanelState>),
+        (status = 304, description = "The state didn't change within `wait` seconds"),
+    )
+)]
+async fn get_panel_state(State(state): State<AppState>, headers: HeaderMap, Query(query): Query<StateQuery>) -> Response {
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let wait = Duration::from_secs(query.wait.unwrap_or(DEFAULT_WAIT_SECS).min(MAX_WAIT_SECS));
+    let deadline = Instant::now() + wait;
+
+    // Subscribe before reading the state, so a change in between still wakes the request
+    let mut events = live_events::get_live_events().subscribe();
+    loop {
+        let panel = build_panel_state(&state).await;
+        let etag = etag_of(&panel);
+        if !if_none_match.as_deref().is_some_and(|tags| etag_matches(tags, &etag)) {
+            let headers = [(header::ETAG, etag), (header::CACHE_CONTROL, "no-cache".to_string())];
+            return (StatusCode::OK, headers, Json(ApiResponse::success(panel))).into_response();
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        }
+        // Woken by any live event or the recheck interval, the state is compared again either way
+        let _ = tokio::time::timeout(RECHECK_INTERVAL.min(deadline - now), events.recv()).await;
+    }
+}
+
+/// POST /api/panel/adjust
+/// Makes a device 0.5°C warmer (`"direction": "up"`) or cooler (`"down"`) through its setpoint offset
+#[utoipa::path(
+    post,
+    path = "/api/panel/adjust",
+    tag = "panel",
+    request_body = AdjustRequest,
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<AdjustResponse>),
+        (status = 400, description = "Unknown device", body = ApiError),
+        (status = 401, description = "Missing or wrong `rpc_api_key`", body = ApiError),
+        (status = 409, description = "Modified by a concurrent request, retry", body = ApiError),
+    )
+)]
+async fn adjust_setpoint(State(state): State<AppState>, headers: HeaderMap, Json(request): Json<AdjustRequest>) -> Response {
+    if !super::auth::verify_api_key_header(&headers, &state.config().rpc_api_key) {
+        log::warn!("Unauthorized panel adjust attempt");
+        let response = ApiError::error("Unauthorized");
+        return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
+    }
+
+    let Some(device) = state.devices().get(&request.device).cloned() else {
+        let response = ApiError::error("Unknown device");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+
+    let delta = match request.direction {
+        Direction::Up => ADJUST_STEP_C,
+        Direction::Down => -ADJUST_STEP_C,
+    };
+    match setpoint_offset::nudge(&state.pool, &request.device, delta).await {
+        Ok(offset) => {
+            setpoint_offset::apply_now(device);
+            let response = ApiResponse::success(AdjustResponse {
+                device: request.device,
+                setpoint_offset: offset,
+            });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => super::nodes::db_error_response(e, "Failed to adjust setpoint"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    fn panel(indoor: f64) -> PanelState {
+        PanelState {
+            outdoor_temperature: Some(to_tenths(8.04)),
+            devices: vec![PanelDevice {
+                device: "LivingRoom".to_string(),
+                display_name: "Living Room".to_string(),
+                is_on: true,
+                mode: Some("Heat".to_string()),
+                indoor_temperature: Some(to_tenths(indoor)),
+                temperature_setpoint: Some(to_tenths(21.0)),
+                target_temperature: Some(to_tenths(21.3)),
+                setpoint_offset: to_tenths(0.5),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_etag_ignores_sensor_noise() {
+        assert_eq!(etag_of(&panel(20.51)), etag_of(&panel(20.54)));
+        assert_ne!(etag_of(&panel(20.51)), etag_of(&panel(20.61)));
+
+        let json = serde_json::to_value(panel(20.51)).unwrap();
+        assert_eq!(json["outdoor_temperature"], 8.0);
+        assert_eq!(json["devices"][0]["indoor_temperature"], 20.5);
+        assert_eq!(json["devices"][0]["setpoint_offset"], 0.5);
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = "\"00000000000000ab\"";
+        assert!(etag_matches(etag, etag));
+        assert!(etag_matches("W/\"00000000000000ab\"", etag));
+        assert!(etag_matches("\"other\", \"00000000000000ab\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("\"other\"", etag));
+    }
+
+    #[tokio::test]
+    async fn test_adjust_rejects_unknown_device_and_missing_key() {
+        let adjust = |state: AppState| async move {
+            let request = axum::http::Request::builder()
+                .method("POST")
+                .uri("/adjust")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(r#"{"device": "Attic", "direction": "up"}"#))
+                .unwrap();
+            panel_routes(state).oneshot(request).await.unwrap().status()
+        };
+
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        assert_eq!(adjust(state).await, StatusCode::BAD_REQUEST);
+
+        let config = crate::types::Config {
+            rpc_api_key: "secret".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(adjust(AppState::for_test(config).await).await, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,37 +1,90 @@
 use axum::{
     Json, Router,
-    extract::Query,
+    extract::{Path, Query, State},
     http::{StatusCode, HeaderMap},
     response::{IntoResponse, Response},
-    routing::post,
+    routing::{delete, get, post},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use log::{info, warn};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    ac_controller::{pir_state, ac_executor, AcDevices},
+    ac_controller::{ac_executor, occupancy::OccupancyEvent, AcDevice},
+    app_state::AppState,
+    live_events::{self, LiveEvent},
     types::{ApiError, ApiResponse, CauseReason},
 };
 
-pub fn pir_routes() -> Router {
+pub fn pir_routes(state: AppState) -> Router {
     Router::new()
         .route("/detect", post(pir_detect))
         .route("/alive", post(pir_alive))
+        .route("/state", get(get_pir_state))
+        .route("/sensors", get(get_pir_sensors))
+        .route("/:device", delete(clear_pir_detection))
+        .route("/:device/detect", post(pir_sensor_detect))
+        .with_state(state)
 }
 
-#[derive(Deserialize)]
+/// PIR detection state of a device
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct PirDeviceState {
+    pub device: String,
+    /// Unix timestamp (seconds) of the last detection, None if there was none since startup
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub last_detection: Option<i64>,
+    /// Whether the detection is recent enough to keep the AC off
+    pub is_suppressed: bool,
+    /// Unix timestamp (seconds) at which the suppression ends, None if not suppressed
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub suppressed_until: Option<i64>,
+}
+
+/// PIR detection state of a named sensor
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct PirSensorState {
+    pub sensor_id: String,
+    /// Zone from `pir_sensors`, None for sensors without one (e.g. registered on their first detection)
+    pub zone: Option<String>,
+    /// Device whose AC a detection turns off
+    pub device: Option<String>,
+    /// Unix timestamp (seconds) of the last detection, None if there was none since startup
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub last_detection: Option<i64>,
+    /// Whether the last detection is within `pir_timeout_minutes`
+    pub is_recently_triggered: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct PirDetectRequest {
     device: String,
 }
 
 /// POST /api/pir/detect?device=Veranda
 /// Records a PIR detection and immediately turns off the corresponding AC device
+#[utoipa::path(
+    post,
+    path = "/api/pir/detect",
+    tag = "pir",
+    params(PirDetectRequest),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Whether the AC was turned off", body = ApiResponse<String>),
+        (status = 400, description = "Unknown device", body = ApiError),
+        (status = 401, description = "Missing or wrong `pir_api_key`", body = ApiError),
+    )
+)]
 async fn pir_detect(
+    State(state): State<AppState>,
     headers: HeaderMap,
     Query(params): Query<PirDetectRequest>,
 ) -> Response {
     // Verify API key
-    if !verify_api_key(&headers) {
+    if !verify_api_key(&state, &headers) {
         warn!("Unauthorized PIR detection attempt");
         let response = ApiError::error("Unauthorized");
         return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
@@ -39,12 +92,8 @@ async fn pir_detect(
 
     info!("PIR detection received for device: {}", params.device);
 
-    // Record the detection
-    let pir_state = pir_state::get_pir_state();
-    pir_state.record_detection(&params.device);
-
-    // Convert device name to AcDevices enum
-    let device_enum = match AcDevices::from_str(&params.device) {
+    // Look up the device in the registry
+    let device = match state.devices().get(&params.device).cloned() {
         Some(d) => d,
         None => {
             warn!("Unknown device name in PIR detection: {}", params.device);
@@ -53,24 +102,95 @@ async fn pir_detect(
         }
     };
 
+    handle_device_detection(&state, &device).await
+}
+
+/// POST /api/pir/hall-1/detect
+/// Records a detection of a named sensor, registering sensors that weren't seen before
+/// (up to `pir_state::MAX_TRACKED_SENSORS`). A sensor configured with a device also counts as a
+/// detection for that device, which turns its AC off
+#[utoipa::path(
+    post,
+    path = "/api/pir/{sensor_id}/detect",
+    tag = "pir",
+    params(("sensor_id" = String, Path)),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Whether the AC of the sensor's device was turned off", body = ApiResponse<String>),
+        (status = 400, description = "Unknown sensor and too many sensors registered", body = ApiError),
+        (status = 401, description = "Missing or wrong `pir_api_key`", body = ApiError),
+    )
+)]
+async fn pir_sensor_detect(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(sensor_id): Path<String>,
+) -> Response {
+    if !verify_api_key(&state, &headers) {
+        warn!("Unauthorized PIR detection attempt from sensor {}", sensor_id);
+        let response = ApiError::error("Unauthorized");
+        return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
+    }
+
+    let is_configured = state.config().pir_sensors.contains_key(&sensor_id);
+    match state.pir.record_sensor_detection(&sensor_id, is_configured) {
+        None => {
+            warn!("Ignoring PIR detection of unknown sensor {}, too many sensors are registered", sensor_id);
+            let response = ApiError::error("Unknown sensor, configure it in pir_sensors");
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+        Some(true) if !is_configured => {
+            info!("Registered PIR sensor {}, configure it in pir_sensors to assign a zone", sensor_id);
+        }
+        Some(_) => {}
+    }
+
+    let linked_device = state.config().pir_sensors.get(&sensor_id).and_then(|sensor| sensor.device.clone());
+    let Some(device_name) = linked_device else {
+        let response = ApiResponse::success("PIR detection recorded");
+        return (StatusCode::OK, Json(response)).into_response();
+    };
+    match state.devices().get(&device_name).cloned() {
+        Some(device) => handle_device_detection(&state, &device).await,
+        None => {
+            warn!("PIR sensor {} is configured with unknown device {}", sensor_id, device_name);
+            let response = ApiResponse::success("PIR detection recorded, its device is unknown");
+            (StatusCode::OK, Json(response)).into_response()
+        }
+    }
+}
+
+/// Record a detection for a device and turn its AC off if it is on
+async fn handle_device_detection(state: &AppState, device: &AcDevice) -> Response {
+    // Record the detection
+    state.pir.record_detection(device.as_str());
+    live_events::get_live_events().publish(LiveEvent::pir_detection(device.as_str()));
+    if let Err(e) = state
+        .occupancy
+        .record_and_store(&state.pool, device.as_str(), OccupancyEvent::Motion, &state.config().occupancy)
+        .await
+    {
+        warn!("Failed to store the occupancy of {}: {}", device.as_str(), e);
+    }
+
     // Check if device is already off - if so, no need to call executor
-    if ac_executor::is_device_off(&device_enum) {
-        info!("PIR detection for device {}, AC already off - no action needed", params.device);
+    if !state.ac_states.get_state(device.as_str()).is_on {
+        info!("PIR detection for device {}, AC already off - no action needed", device.as_str());
         let response = ApiResponse::success("PIR detection recorded, AC was already off");
         return (StatusCode::OK, Json(response)).into_response();
     }
 
     // Device is on, turn it off directly
-    match ac_executor::turn_off_device(&device_enum, CauseReason::PirDetection).await {
+    match ac_executor::turn_off_device(device, CauseReason::PirDetection).await {
         Ok(_) => {
-            info!("AC turned off for device {} due to PIR detection", params.device);
+            info!("AC turned off for device {} due to PIR detection", device.as_str());
             let response = ApiResponse::success("PIR detection recorded and AC turned off");
             (StatusCode::OK, Json(response)).into_response()
         }
         Err(e) => {
             warn!(
                 "Failed to turn off AC for device {} after PIR detection: {}",
-                params.device, e
+                device.as_str(), e
             );
             // Still record the detection even if AC control fails
             let response = ApiResponse::success("PIR detection recorded, but AC control failed");
@@ -79,7 +199,8 @@ async fn pir_detect(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct PirAliveRequest {
     #[serde(default)]
     device: String,
@@ -87,12 +208,24 @@ struct PirAliveRequest {
 
 /// POST /api/pir/alive?device=Veranda
 /// Receives a keep-alive signal from PIR devices
+#[utoipa::path(
+    post,
+    path = "/api/pir/alive",
+    tag = "pir",
+    params(PirAliveRequest),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 401, description = "Missing or wrong `pir_api_key`", body = ApiError),
+    )
+)]
 async fn pir_alive(
+    State(state): State<AppState>,
     headers: HeaderMap,
     Query(params): Query<PirAliveRequest>,
 ) -> Response {
     // Verify API key
-    if !verify_api_key(&headers) {
+    if !verify_api_key(&state, &headers) {
         warn!("Unauthorized PIR alive attempt");
         let response = ApiError::error("Unauthorized");
         return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
@@ -110,30 +243,205 @@ async fn pir_alive(
     (StatusCode::OK, Json(response)).into_response()
 }
 
+/// GET /api/pir/state
+/// Returns the last detection and active suppression of every device (Vec<PirDeviceState>)
+#[utoipa::path(get, path = "/api/pir/state", tag = "pir", responses((status = 200, body = ApiResponse<Vec<PirDeviceState>>)))]
+async fn get_pir_state(State(state): State<AppState>) -> Response {
+    let timeout_minutes = state.config().pir_timeout_minutes;
+
+    let devices: Vec<PirDeviceState> = state
+        .devices()
+        .all()
+        .iter()
+        .map(|device| {
+            let device = device.as_str();
+            let last_detection = state.pir.get_last_detection(device);
+            let is_suppressed = state.pir.has_recent_detection(device, timeout_minutes);
+            PirDeviceState {
+                device: device.to_string(),
+                last_detection: last_detection.map(|dt| dt.timestamp()),
+                is_suppressed,
+                suppressed_until: last_detection
+                    .filter(|_| is_suppressed)
+                    .map(|dt| dt.timestamp() + i64::from(timeout_minutes) * 60),
+            }
+        })
+        .collect();
+
+    let response = ApiResponse::success(devices);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// GET /api/pir/sensors
+/// Returns the detection state of the configured sensors and of sensors registered on their first detection
+#[utoipa::path(get, path = "/api/pir/sensors", tag = "pir", responses((status = 200, body = ApiResponse<Vec<PirSensorState>>)))]
+async fn get_pir_sensors(State(state): State<AppState>) -> Response {
+    let config = state.config();
+    let mut sensor_ids: Vec<String> = config.pir_sensors.keys().cloned().collect();
+    sensor_ids.extend(state.pir.get_detected_sensors());
+    sensor_ids.sort();
+    sensor_ids.dedup();
+
+    let sensors: Vec<PirSensorState> = sensor_ids
+        .into_iter()
+        .map(|sensor_id| {
+            let sensor = config.pir_sensors.get(&sensor_id);
+            let last_detection = state.pir.get_sensor_last_detection(&sensor_id);
+            PirSensorState {
+                zone: sensor.and_then(|s| s.zone.clone()),
+                device: sensor.and_then(|s| s.device.clone()),
+                last_detection: last_detection.map(|dt| dt.timestamp()),
+                is_recently_triggered: last_detection.is_some_and(|dt| {
+                    (chrono::Utc::now() - dt).num_minutes() < i64::from(config.pir_timeout_minutes)
+                }),
+                sensor_id,
+            }
+        })
+        .collect();
+
+    let response = ApiResponse::success(sensors);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// DELETE /api/pir/Veranda
+/// Clears the last detection of a device so a stuck detection no longer keeps the AC off
+#[utoipa::path(
+    delete,
+    path = "/api/pir/{device}",
+    tag = "pir",
+    params(("device" = String, Path)),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 401, description = "Missing or wrong `pir_api_key`", body = ApiError),
+        (status = 404, description = "Unknown device or no detection", body = ApiError),
+    )
+)]
+async fn clear_pir_detection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(device): Path<String>,
+) -> Response {
+    if !verify_api_key(&state, &headers) {
+        warn!("Unauthorized PIR clear attempt");
+        let response = ApiError::error("Unauthorized");
+        return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
+    }
+
+    if state.devices().get(&device).is_none() {
+        let response = ApiError::error(format!("Unknown device '{}'", device));
+        return (StatusCode::NOT_FOUND, Json(response)).into_response();
+    }
+
+    if state.pir.clear_detection(&device) {
+        let response = ApiResponse::success("PIR detection cleared");
+        (StatusCode::OK, Json(response)).into_response()
+    } else {
+        let response = ApiError::error("Device has no PIR detection");
+        (StatusCode::NOT_FOUND, Json(response)).into_response()
+    }
+}
+
 /// Verify the API key from the Authorization header
-fn verify_api_key(headers: &HeaderMap) -> bool {
-    let config = crate::config::get_config();
-    
-    // If no API key is configured, allow access (backward compatibility)
-    if config.pir_api_key.is_empty() {
-        return true;
-    }
-
-    // Check for Authorization header
-    if let Some(auth_header) = headers.get("Authorization") {
-        if let Ok(auth_str) = auth_header.to_str() {
-            // Support both "Bearer <key>" and "ApiKey <key>" formats
-            let key = if auth_str.starts_with("Bearer ") {
-                &auth_str[7..]
-            } else if auth_str.starts_with("ApiKey ") {
-                &auth_str[7..]
-            } else {
-                auth_str
-            };
-
-            return key == config.pir_api_key;
-        }
+fn verify_api_key(state: &AppState, headers: &HeaderMap) -> bool {
+    super::auth::verify_api_key_header(headers, &state.config().pir_api_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+
+    async fn api_request(state: &AppState, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        test_helpers::api_request(pir_routes(state.clone()), method, uri, None).await
     }
 
-    false
+    #[tokio::test]
+    async fn test_state_and_clear_detection() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+        state.pir.record_detection("Veranda");
+
+        let (status, body) = api_request(&state, "GET", "/state").await;
+        assert_eq!(status, StatusCode::OK);
+        let devices = body["data"].as_array().unwrap();
+        let living_room = devices.iter().find(|d| d["device"] == "LivingRoom").unwrap();
+        assert_eq!(living_room["last_detection"], serde_json::Value::Null);
+        assert_eq!(living_room["is_suppressed"], false);
+        let veranda = devices.iter().find(|d| d["device"] == "Veranda").unwrap();
+        assert_eq!(veranda["is_suppressed"], true);
+        assert_eq!(
+            veranda["suppressed_until"].as_i64().unwrap() - veranda["last_detection"].as_i64().unwrap(),
+            i64::from(state.config().pir_timeout_minutes) * 60
+        );
+
+        let (status, _) = api_request(&state, "DELETE", "/Veranda").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.pir.get_last_detection("Veranda").is_none());
+
+        let (status, _) = api_request(&state, "DELETE", "/Veranda").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = api_request(&state, "DELETE", "/Attic").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_clear_detection_requires_pir_key() {
+        let state = AppState::for_test(crate::types::Config {
+            pir_api_key: "pir".to_string(),
+            ..Default::default()
+        })
+        .await;
+        state.pir.record_detection("Veranda");
+
+        let (status, _) = api_request(&state, "DELETE", "/Veranda").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(state.pir.get_last_detection("Veranda").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sensor_detect_registers_sensor() {
+        let mut config = crate::types::Config::default();
+        config.pir_sensors.insert(
+            "veranda-door".to_string(),
+            crate::types::PirSensorConfig {
+                zone: Some("Veranda".to_string()),
+                device: None,
+            },
+        );
+        let state = AppState::for_test(config).await;
+
+        let (status, _) = api_request(&state, "POST", "/hall-1/detect").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.pir.get_sensor_last_detection("hall-1").is_some());
+        assert!(state.pir.get_last_detection("Veranda").is_none(), "no device configured for the sensor");
+
+        let (status, body) = api_request(&state, "GET", "/sensors").await;
+        assert_eq!(status, StatusCode::OK);
+        let sensors = body["data"].as_array().unwrap();
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0]["sensor_id"], "hall-1");
+        assert_eq!(sensors[0]["zone"], serde_json::Value::Null);
+        assert_eq!(sensors[0]["is_recently_triggered"], true);
+        assert_eq!(sensors[1]["sensor_id"], "veranda-door");
+        assert_eq!(sensors[1]["zone"], "Veranda");
+        assert_eq!(sensors[1]["last_detection"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_sensor_detect_records_linked_device() {
+        let mut config = crate::types::Config::default();
+        config.pir_sensors.insert(
+            "veranda-door".to_string(),
+            crate::types::PirSensorConfig {
+                zone: None,
+                device: Some("Veranda".to_string()),
+            },
+        );
+        let state = AppState::for_test(config).await;
+
+        let (status, _) = api_request(&state, "POST", "/veranda-door/detect").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.pir.get_last_detection("Veranda").is_some());
+        assert!(state.occupancy.evidence("Veranda", &state.config().occupancy, chrono::Utc::now()) > 0.99);
+    }
 }
@@ -0,0 +1,154 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    ac_controller::time_helpers,
+    app_state::AppState,
+    presence::PersonPresence,
+    types::{ApiError, ApiResponse},
+};
+
+/// Longest override with an end time (1 week)
+const MAX_OVERRIDE_MINUTES: u32 = 7 * 24 * 60;
+
+pub fn presence_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_presence))
+        .route("/:person", put(set_presence_override).delete(clear_presence_override))
+        .with_state(state)
+}
+
+#[derive(Serialize, ToSchema)]
+struct PresenceStatus {
+    /// Whether the user is considered home, as provided to nodesets
+    is_user_home: bool,
+    /// Number of people home, as provided to nodesets
+    people_home_count: i64,
+    /// Whether away mode is on, which makes everybody count as away
+    away_mode: bool,
+    /// Presence of every person reported or overridden since startup
+    people: Vec<PersonPresence>,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct PresenceOverrideRequest {
+    is_home: bool,
+    /// Minutes the override lasts, kept until cleared if not provided
+    minutes: Option<u32>,
+}
+
+/// GET /api/presence
+/// Returns who is home and what nodesets get as is_user_home and people_home_count (PresenceStatus)
+#[utoipa::path(get, path = "/api/presence", tag = "presence", responses((status = 200, body = ApiResponse<PresenceStatus>)))]
+async fn get_presence(State(state): State<AppState>) -> Response {
+    let is_user_home = time_helpers::is_user_home_and_awake_async().await;
+    let response = ApiResponse::success(PresenceStatus {
+        is_user_home,
+        people_home_count: time_helpers::people_home_count(is_user_home),
+        away_mode: state.away_mode.is_enabled(),
+        people: state.presence.people(),
+    });
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// PUT /api/presence/alex
+/// Overrides a person's presence, for `minutes` or until cleared
+#[utoipa::path(
+    put,
+    path = "/api/presence/{person}",
+    tag = "presence",
+    params(("person" = String, Path)),
+    request_body = PresenceOverrideRequest,
+    responses(
+        (status = 200, body = ApiResponse<Option<crate::presence::PersonPresence>>),
+        (status = 400, description = "Minutes out of range", body = ApiError),
+    )
+)]
+async fn set_presence_override(
+    State(state): State<AppState>,
+    Path(person): Path<String>,
+    Json(request): Json<PresenceOverrideRequest>,
+) -> Response {
+    if request.minutes.is_some_and(|minutes| minutes == 0 || minutes > MAX_OVERRIDE_MINUTES) {
+        let response = ApiError::error(format!("Minutes must be between 1 and {} (1 week)", MAX_OVERRIDE_MINUTES));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let until = request
+        .minutes
+        .map(|minutes| chrono::Utc::now() + chrono::Duration::minutes(i64::from(minutes)));
+    state.presence.set_override(&person, request.is_home, until);
+
+    let presence = state.presence.people().into_iter().find(|p| p.person == person);
+    let response = ApiResponse::success(presence);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// DELETE /api/presence/alex
+/// Removes a person's override, so the reported presence applies again
+#[utoipa::path(
+    delete,
+    path = "/api/presence/{person}",
+    tag = "presence",
+    params(("person" = String, Path)),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 404, description = "The person has no override", body = ApiError),
+    )
+)]
+async fn clear_presence_override(State(state): State<AppState>, Path(person): Path<String>) -> Response {
+    if state.presence.clear_override(&person) {
+        let response = ApiResponse::success("Presence override cleared");
+        (StatusCode::OK, Json(response)).into_response()
+    } else {
+        let response = ApiError::error("Person has no presence override");
+        (StatusCode::NOT_FOUND, Json(response)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_override_and_clear_presence() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let send = |method: &str, uri: &str, body: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = presence_routes(state.clone())
+            .oneshot(send("PUT", "/alex", r#"{"is_home": true, "minutes": 0}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = presence_routes(state.clone())
+            .oneshot(send("PUT", "/alex", r#"{"is_home": true, "minutes": 90}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.presence.people_home_count(), Some(1));
+
+        let response = presence_routes(state.clone()).oneshot(send("DELETE", "/alex", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.presence.people_home_count(), None);
+
+        let response = presence_routes(state).oneshot(send("DELETE", "/alex", "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,329 @@
+//! JSON-RPC 2.0 endpoint for local automations
+//!
+//! Scripts and microcontrollers (e.g. an ESP32 wall panel) get everything through a single
+//! `POST /api/rpc`, and can batch several calls into one request to keep overhead low.
+//! Methods:
+//! - `get_temperatures` - Outdoor temperature and the temperatures and state of every device
+//! - `nudge_setpoint` `{device, delta}` - Shift a device's setpoint offset by `delta` °C
+//! - `reset_setpoint` `{device}` - Remove a device's setpoint offset
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::{
+    ac_controller::{ac_executor::ac_mode_name, setpoint_offset, AcDevice},
+    app_state::AppState,
+    device_requests,
+    types::ApiError,
+};
+
+/// Most calls accepted in one batch
+const MAX_BATCH_SIZE: usize = 32;
+
+// Error codes defined by the JSON-RPC 2.0 specification
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+
+pub fn rpc_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", post(handle_rpc))
+        .with_state(state)
+}
+
+#[derive(Deserialize, ToSchema)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+    id: Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl RpcResponse {
+    fn result(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0", result: Some(result), error: None, id }
+    }
+
+    fn error(id: Value, error: RpcError) -> Self {
+        Self { jsonrpc: "2.0", result: None, error: Some(error), id }
+    }
+}
+
+#[derive(Deserialize)]
+struct DeviceParams {
+    device: String,
+}
+
+#[derive(Deserialize)]
+struct NudgeParams {
+    device: String,
+    /// °C to add to the current offset, negative to cool down
+    delta: f64,
+}
+
+#[derive(Serialize)]
+struct Temperatures {
+    outdoor_temperature: Option<f64>,
+    devices: Vec<DeviceTemperatures>,
+}
+
+#[derive(Serialize)]
+struct DeviceTemperatures {
+    device: String,
+    indoor_temperature: Option<f64>,
+    is_on: bool,
+    mode: Option<String>,
+    temperature_setpoint: Option<f64>,
+    setpoint_offset: f64,
+}
+
+#[derive(Serialize)]
+struct SetpointOffset {
+    device: String,
+    setpoint_offset: f64,
+}
+
+/// POST /api/rpc
+/// Handles a single JSON-RPC 2.0 call or a batch (array) of calls
+#[utoipa::path(
+    post,
+    path = "/api/rpc",
+    tag = "rpc",
+    request_body(content = RpcRequest, description = "A call, or an array of calls for a batch"),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "The response, or an array of responses for a batch", body = RpcResponse),
+        (status = 204, description = "Only notifications were sent"),
+        (status = 401, description = "Missing or wrong `rpc_api_key`", body = ApiError),
+    )
+)]
+async fn handle_rpc(State(state): State<AppState>, headers: HeaderMap, body: String) -> Response {
+    if !super::auth::verify_api_key_header(&headers, &state.config().rpc_api_key) {
+        log::warn!("Unauthorized RPC attempt");
+        let response = ApiError::error("Unauthorized");
+        return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
+    }
+
+    let request: Value = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            let response = RpcResponse::error(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()));
+            return Json(response).into_response();
+        }
+    };
+
+    match request {
+        Value::Array(calls) => {
+            if calls.is_empty() || calls.len() > MAX_BATCH_SIZE {
+                let message = format!("A batch must contain 1 to {} calls", MAX_BATCH_SIZE);
+                let response = RpcResponse::error(Value::Null, RpcError::new(INVALID_REQUEST, message));
+                return Json(response).into_response();
+            }
+            let mut responses = Vec::with_capacity(calls.len());
+            for call in calls {
+                responses.extend(handle_call(&state, call).await);
+            }
+            if responses.is_empty() {
+                StatusCode::NO_CONTENT.into_response()
+            } else {
+                Json(responses).into_response()
+            }
+        }
+        call => match handle_call(&state, call).await {
+            Some(response) => Json(response).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        },
+    }
+}
+
+/// Handle one call, None for notifications
+async fn handle_call(state: &AppState, call: Value) -> Option<RpcResponse> {
+    let request = match serde_json::from_value::<RpcRequest>(call) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        Ok(_) => {
+            return Some(RpcResponse::error(Value::Null, RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"")));
+        }
+        Err(e) => return Some(RpcResponse::error(Value::Null, RpcError::new(INVALID_REQUEST, e.to_string()))),
+    };
+
+    let result = call_method(state, &request.method, request.params).await;
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => RpcResponse::result(id, result),
+        Err(error) => RpcResponse::error(id, error),
+    })
+}
+
+async fn call_method(state: &AppState, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "get_temperatures" => to_value(get_temperatures(state).await),
+        "nudge_setpoint" => {
+            let params: NudgeParams = parse_params(params)?;
+            if !params.delta.is_finite() {
+                return Err(RpcError::new(INVALID_PARAMS, "delta must be a number"));
+            }
+            let device = find_device(state, &params.device)?;
+            let offset = setpoint_offset::nudge(&state.pool, &params.device, params.delta)
+                .await
+                .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+            setpoint_offset::apply_now(device);
+            to_value(SetpointOffset { device: params.device, setpoint_offset: offset })
+        }
+        "reset_setpoint" => {
+            let params: DeviceParams = parse_params(params)?;
+            let device = find_device(state, &params.device)?;
+            setpoint_offset::reset(&state.pool, &params.device)
+                .await
+                .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+            setpoint_offset::apply_now(device);
+            to_value(SetpointOffset { device: params.device, setpoint_offset: 0.0 })
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+    }
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))
+}
+
+fn find_device(state: &AppState, device: &str) -> Result<AcDevice, RpcError> {
+    state
+        .devices()
+        .get(device)
+        .cloned()
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown device '{}'", device)))
+}
+
+async fn get_temperatures(state: &AppState) -> Temperatures {
+    let config = state.config();
+    let outdoor_temperature =
+        match device_requests::weather::get_current_outdoor_temp_cached(config.latitude, config.longitude).await {
+            Ok(temperature) => Some(temperature),
+            Err(e) => {
+                log::warn!("Failed to get outdoor temperature: {}", e);
+                None
+            }
+        };
+
+    let mut devices = Vec::new();
+    for device in state.devices().all() {
+        let device_name = device.as_str();
+        let indoor_temperature = match device_requests::ac::get_sensors_cached(device_name).await {
+            Ok(sensor_data) => Some(sensor_data.temperature),
+            Err(e) => {
+                log::warn!("Failed to get sensor data for {}: {}", device_name, e);
+                None
+            }
+        };
+        let ac_state = state.ac_states.get_state(device_name);
+        devices.push(DeviceTemperatures {
+            device: device_name.to_string(),
+            indoor_temperature,
+            is_on: ac_state.is_on,
+            mode: ac_state.mode.and_then(ac_mode_name).map(String::from),
+            temperature_setpoint: ac_state.temperature.map(|t| t.0),
+            setpoint_offset: setpoint_offset::get(&state.pool, device_name).await,
+        });
+    }
+
+    Temperatures { outdoor_temperature, devices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_helpers;
+
+    async fn rpc(state: &AppState, body: &str) -> (StatusCode, Value) {
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap();
+        test_helpers::send_json(rpc_routes(state.clone()), request).await
+    }
+
+    #[tokio::test]
+    async fn test_rpc_errors() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let (_, body) = rpc(&state, "{not json").await;
+        assert_eq!(body["error"]["code"], PARSE_ERROR);
+
+        let (_, body) = rpc(&state, r#"{"jsonrpc":"1.0","method":"get_temperatures","id":1}"#).await;
+        assert_eq!(body["error"]["code"], INVALID_REQUEST);
+
+        let (_, body) = rpc(&state, r#"{"jsonrpc":"2.0","method":"reboot","id":1}"#).await;
+        assert_eq!(body["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(body["id"], 1);
+
+        let (_, body) = rpc(&state, r#"{"jsonrpc":"2.0","method":"reset_setpoint","params":{"device":"Attic"},"id":"a"}"#).await;
+        assert_eq!(body["error"]["code"], INVALID_PARAMS);
+        assert_eq!(body["id"], "a");
+
+        let (_, body) = rpc(&state, "[]").await;
+        assert_eq!(body["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_batch_skips_notifications() {
+        let state = AppState::for_test(crate::types::Config::default()).await;
+
+        let (status, body) = rpc(
+            &state,
+            r#"[
+                {"jsonrpc":"2.0","method":"reboot","id":1},
+                {"jsonrpc":"2.0","method":"reboot"},
+                {"jsonrpc":"2.0","method":"nudge_setpoint","params":{"device":"Attic","delta":0.5},"id":2}
+            ]"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let responses = body.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[1]["id"], 2);
+
+        // Only notifications, nothing to respond with
+        let (status, _) = rpc(&state, r#"[{"jsonrpc":"2.0","method":"reboot"}]"#).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+}
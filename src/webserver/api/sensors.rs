@@ -0,0 +1,178 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    ac_controller::occupancy::OccupancyEvent,
+    app_state::AppState,
+    types::{ApiError, ApiResponse},
+};
+
+pub fn sensors_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/contact", get(get_contact_sensors))
+        .route("/contact/:name", post(contact_sensor_report))
+        .with_state(state)
+}
+
+/// State of a window or door contact sensor
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct ContactSensorStatus {
+    pub name: String,
+    /// Device whose room the window or door is in
+    pub device: String,
+    /// Whether the sensor last reported open, false if it hasn't reported since startup
+    pub is_open: bool,
+    /// Unix timestamp (seconds) the sensor last opened or closed, None if it hasn't reported since startup
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub changed_at: Option<i64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ContactReportRequest {
+    state: String,
+}
+
+/// Parse a reported contact state, None if it isn't recognized
+fn parse_contact_state(state: &str) -> Option<bool> {
+    match state.trim().to_lowercase().as_str() {
+        "open" | "on" | "true" | "1" => Some(true),
+        "closed" | "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// GET /api/sensors/contact
+/// Returns the state of every configured contact sensor, sorted by name
+#[utoipa::path(get, path = "/api/sensors/contact", tag = "sensors", responses((status = 200, body = ApiResponse<Vec<ContactSensorStatus>>)))]
+async fn get_contact_sensors(State(state): State<AppState>) -> Response {
+    let config = state.config();
+    let mut sensors: Vec<ContactSensorStatus> = config
+        .contact_sensors
+        .iter()
+        .map(|(name, sensor)| {
+            let reading = state.contact_sensors.get(name);
+            ContactSensorStatus {
+                name: name.clone(),
+                device: sensor.device.clone(),
+                is_open: reading.is_some_and(|reading| reading.is_open),
+                changed_at: reading.map(|reading| reading.changed_at.timestamp()),
+            }
+        })
+        .collect();
+    sensors.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let response = ApiResponse::success(sensors);
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// POST /api/sensors/contact/veranda_door?state=open
+/// Records whether a window or door is open (open/closed, on/off, true/false or 1/0)
+#[utoipa::path(
+    post,
+    path = "/api/sensors/contact/{name}",
+    tag = "sensors",
+    params(("name" = String, Path, description = "Sensor name from `contact_sensors`"), ContactReportRequest),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 400, description = "Unknown sensor or state", body = ApiError),
+        (status = 401, description = "Missing or wrong `pir_api_key`", body = ApiError),
+    )
+)]
+async fn contact_sensor_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(params): Query<ContactReportRequest>,
+) -> Response {
+    let config = state.config();
+    if !super::auth::verify_api_key_header(&headers, &config.pir_api_key) {
+        warn!("Unauthorized contact sensor report");
+        let response = ApiError::error("Unauthorized");
+        return (StatusCode::UNAUTHORIZED, Json(response)).into_response();
+    }
+
+    let Some(sensor) = config.contact_sensors.get(&name) else {
+        warn!("Unknown contact sensor: {}", name);
+        let response = ApiError::error("Unknown contact sensor");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+    let Some(is_open) = parse_contact_state(&params.state) else {
+        let response = ApiError::error("State must be open or closed");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+
+    if !state.contact_sensors.report(&name, is_open, chrono::Utc::now()) {
+        let response = ApiResponse::success("Contact sensor state unchanged");
+        return (StatusCode::OK, Json(response)).into_response();
+    }
+
+    info!("Contact sensor {} reported {}", name, if is_open { "open" } else { "closed" });
+    // Somebody opened or closed the window or door
+    if let Err(e) = state
+        .occupancy
+        .record_and_store(&state.pool, &sensor.device, OccupancyEvent::Door, &config.occupancy)
+        .await
+    {
+        warn!("Failed to store the occupancy of {}: {}", sensor.device, e);
+    }
+
+    let response = ApiResponse::success("Contact sensor state recorded");
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ContactSensorConfig;
+    use super::super::test_helpers;
+
+    async fn api_request(state: &AppState, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        test_helpers::api_request(sensors_routes(state.clone()), method, uri, None).await
+    }
+
+    #[tokio::test]
+    async fn test_contact_report_records_state_and_door_event() {
+        let mut config = crate::types::Config::default();
+        config.contact_sensors.insert(
+            "veranda_door".to_string(),
+            ContactSensorConfig {
+                device: "Veranda".to_string(),
+            },
+        );
+        let state = AppState::for_test(config).await;
+
+        let (status, _) = api_request(&state, "POST", "/contact/veranda_door?state=open").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(state.contact_sensors.get("veranda_door").unwrap().is_open);
+        let door_weight = state.config().occupancy.door_weight;
+        let evidence = state.occupancy.evidence("Veranda", &state.config().occupancy, chrono::Utc::now());
+        assert!((evidence - door_weight).abs() < 0.01);
+
+        let (status, _) = api_request(&state, "POST", "/contact/veranda_door?state=ajar").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = api_request(&state, "POST", "/contact/attic_hatch?state=open").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = api_request(&state, "GET", "/contact").await;
+        assert_eq!(status, StatusCode::OK);
+        let sensors = body["data"].as_array().unwrap();
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0]["device"], "Veranda");
+        assert_eq!(sensors[0]["is_open"], true);
+
+        let (status, _) = api_request(&state, "POST", "/contact/veranda_door?state=closed").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!state.contact_sensors.get("veranda_door").unwrap().is_open);
+    }
+}
@@ -1,36 +1,47 @@
 use axum::{
     Json, Router,
+    extract::State,
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use power_control_center_core::ac_state::vane_name;
 
 use crate::{
     ac_controller::{
-        AcDevices,
-        ac_executor::{get_state_manager, AcState, AC_MODE_HEAT, AC_MODE_COOL},
+        ac_executor::{ac_mode_from_name, AcState},
+        power_budget, season, target_temperature, time_helpers,
     },
-    config,
+    app_state::AppState,
     db,
     device_requests,
-    nodes::{ExecutionInputs, NodesetExecutor, validate_nodeset_for_execution, ActiveCommandData},
-    types::ApiResponse,
+    nodes::{ExecutionInputs, NodesetExecutor, validate_nodeset_for_execution, ActiveCommandData, DataAges, DemandResponseData, EnergyPriceData, LocalTimeData, WeatherForecastData, NotificationResult, TraceStep, execution::NO_DR_EVENT_SCHEDULED, variables::{self, Variables}},
+    types::{ApiResponse, Celsius, Config, Minutes, Season, Watts},
 };
 
 use super::nodes::{validate_nodeset, NodeConfiguration, get_active_nodeset_id, DEFAULT_NODESET_ID};
 
 const KW_TO_W_MULTIPLIER: f64 = 1000.0;
 
-pub fn simulator_routes() -> Router {
+pub fn simulator_routes(state: AppState) -> Router {
     Router::new()
         .route("/evaluate", post(evaluate_workflow))
         .route("/live-inputs", get(get_live_inputs))
+        .route("/replay", post(super::simulator_replay::replay_nodeset))
+        .route("/testcases", get(super::nodeset_tests::list_testcases).post(super::nodeset_tests::create_testcase))
+        .route("/testcases/:id", delete(super::nodeset_tests::delete_testcase))
+        // Unsaved nodesets are sent along with the inputs
+        .layer(super::nodeset_payload::body_limit_layer(state.config().nodeset_max_body_bytes))
+        .with_state(state)
 }
 
 /// Input parameters for the simulator
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SimulatorInputs {
     /// Device name (e.g., "LivingRoom", "Veranda")
     pub device: String,
@@ -44,30 +55,75 @@ pub struct SimulatorInputs {
     pub outdoor_temp: Option<f64>,
     /// Average outdoor temperature in next 24 hours (optional, fetched if not provided)
     pub avg_next_24h_outdoor_temp: Option<f64>,
+    /// Lowest outdoor temperature in next 12 hours (optional, fetched if not provided)
+    pub forecast_min_next_12h_temp: Option<f64>,
+    /// Hourly forecast for the next 48 hours (optional, fetched if not provided)
+    pub weather_forecast: Option<WeatherForecastData>,
+    /// Expected PV production of the next 3 hours in Wh (optional, fetched if not provided)
+    pub expected_solar_next_3h_wh: Option<f64>,
+    /// Heating or cooling season (optional, classified from the weather history if not provided)
+    pub season: Option<Season>,
     /// Whether user is home (optional, calculated if not provided)
     pub user_is_home: Option<bool>,
+    /// Number of people home (optional, follows user_is_home if that is provided, otherwise presence detection)
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub people_home_count: Option<i64>,
     /// PIR detection status for this device (optional, defaults to false)
     pub pir_detected: Option<bool>,
     /// PIR detection minutes ago (optional, used if pir_detected is true)
     pub pir_minutes_ago: Option<u32>,
+    /// Occupancy probability (0-1) of the device's room (optional, read from the occupancy model if not provided)
+    pub occupancy_probability: Option<f64>,
+    /// Whether a window or door in the device's room is open (optional, read from the contact sensors if not provided)
+    pub is_window_open: Option<bool>,
     /// Minutes since last AC command (optional, defaults to 60)
     pub last_change_minutes: Option<i32>,
     /// Net power in watts (optional, positive = consuming, negative = exporting)
     pub net_power_watt: Option<i32>,
+    /// Whether the grid appears to be down (optional, detected if not provided)
+    pub possible_grid_outage: Option<bool>,
+    /// Whether a demand-response event is in progress (optional, uses live state if not provided)
+    pub dr_event_active: Option<bool>,
+    /// Demand-response curtailment level (optional, uses live state if not provided)
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub dr_level: Option<i64>,
+    /// Minutes until the next demand-response event, -1 if none (optional, uses live state if not provided)
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub dr_minutes_until_event: Option<i64>,
+    /// Indoor relative humidity in % (optional, unavailable if not provided)
+    pub device_humidity: Option<f64>,
+    /// Outdoor relative humidity in % (optional, fetched if not provided)
+    pub outdoor_humidity: Option<f64>,
+    /// Local time to evaluate at (optional, current local time if not provided)
+    pub local_time: Option<LocalTimeData>,
+    /// Day-ahead energy prices (optional, fetched if not provided)
+    pub energy_price: Option<EnergyPriceData>,
+    /// Home battery state of charge in % (optional, fetched if not provided)
+    pub battery_soc_percent: Option<f64>,
+    /// Home battery power in watts, positive = discharging (optional, fetched if not provided)
+    pub battery_power_watt: Option<i32>,
+    /// Minutes since each data source was read (optional, all fresh if not provided)
+    pub data_ages: Option<DataAges>,
     /// Nodeset ID to evaluate (optional, uses active nodeset if not provided)
     /// Use -1 for new unsaved nodesets
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
     pub nodeset_id: Option<i64>,
     /// Nodes configuration for unsaved/new nodesets (when nodeset_id is -1)
     pub nodes: Option<Vec<serde_json::Value>>,
     /// Edges configuration for unsaved/new nodesets (when nodeset_id is -1)
     pub edges: Option<Vec<serde_json::Value>>,
+    /// Variables for unsaved/new nodesets (when nodeset_id is -1)
+    pub variables: Option<Variables>,
     /// Active command data for simulator testing (optional)
     /// When provided, overrides the state manager's tracked state
     pub active_command: Option<SimulatorActiveCommand>,
+    /// Return the execution trace (optional, defaults to false)
+    pub trace: Option<bool>,
 }
 
 /// Active command data from the simulator input
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SimulatorActiveCommand {
     /// Whether an active command exists
     pub is_defined: bool,
@@ -75,7 +131,7 @@ pub struct SimulatorActiveCommand {
     pub is_on: bool,
     /// Target temperature in Celsius
     pub temperature: f64,
-    /// AC mode: 1 = Heat, 4 = Cool, 0 = Off
+    /// AC mode: 0 = Off, 1 = Cool, 2 = Dry, 3 = Fan, 4 = Heat
     pub mode: i32,
     /// Fan speed setting (0-5, where 0 is auto)
     pub fan_speed: i32,
@@ -83,10 +139,14 @@ pub struct SimulatorActiveCommand {
     pub swing: i32,
     /// Whether powerful mode was enabled
     pub is_powerful: bool,
+    /// Unrounded temperature the controller aims for, defaults to `temperature`
+    #[serde(default)]
+    pub target_temperature: Option<f64>,
 }
 
 /// Result of simulating a workflow
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SimulatorResult {
     /// Whether the simulation was successful
     pub success: bool,
@@ -101,10 +161,15 @@ pub struct SimulatorResult {
     /// The evaluate_every_minutes value from the Start node (no effect in simulator, just reported)
     /// This value controls how often the AC state is reevaluated in the actual controller
     pub evaluate_every_minutes: Option<i32>,
+    /// Notifications the Notify nodes on the evaluated path would send (cooldowns are not applied)
+    pub notifications: Vec<NotificationResult>,
+    /// Every node visited, output value read and branch taken in order, empty unless `trace` was requested
+    pub trace: Vec<TraceStep>,
 }
 
 /// The plan result from simulation
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SimulatorPlanResult {
     /// The request mode (Colder, Warmer, Off, NoChange)
     pub mode: String,
@@ -114,14 +179,17 @@ pub struct SimulatorPlanResult {
     pub cause_label: String,
     /// The cause reason description
     pub cause_description: String,
+    /// Annotation of the action, if the profile set one
+    pub annotation: Option<String>,
 }
 
 /// The AC state that would be set
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SimulatorAcState {
     /// Whether the AC would be on
     pub is_on: bool,
-    /// AC mode description (Heat/Cool/Off)
+    /// AC mode description (Heat/Cool/Dry/Fan)
     pub mode: Option<String>,
     /// Fan speed (0 = auto, 1-5 = manual)
     pub fan_speed: Option<i32>,
@@ -129,12 +197,17 @@ pub struct SimulatorAcState {
     pub temperature: Option<f64>,
     /// Swing setting (0 = off, 1 = on)
     pub swing: Option<i32>,
+    /// Vertical vane position name, None if not set or the device doesn't support it
+    pub vertical_vane: Option<String>,
+    /// Horizontal vane position name, None if not set or the device doesn't support it
+    pub horizontal_vane: Option<String>,
     /// Whether powerful mode would be active
     pub powerful_mode: bool,
 }
 
 /// Input values used for the simulation (including fetched defaults)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SimulatorInputsUsed {
     pub device: String,
     pub temperature: f64,
@@ -143,10 +216,32 @@ pub struct SimulatorInputsUsed {
     pub outdoor_temp: f64,
     /// Average outdoor temperature for the next 24 hours
     pub avg_next_24h_outdoor_temp: f64,
+    /// Lowest outdoor temperature for the next 12 hours
+    pub forecast_min_next_12h_temp: f64,
+    pub weather_forecast: WeatherForecastData,
+    pub expected_solar_next_3h_wh: Option<f64>,
+    pub season: Season,
     pub user_is_home: bool,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub people_home_count: i64,
     pub pir_detected: bool,
+    pub occupancy_probability: f64,
+    pub is_window_open: bool,
     pub last_change_minutes: i32,
     pub net_power_watt: i32,
+    pub possible_grid_outage: bool,
+    pub dr_event_active: bool,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub dr_level: i64,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub dr_minutes_until_event: i64,
+    pub device_humidity: Option<f64>,
+    pub outdoor_humidity: Option<f64>,
+    pub local_time: LocalTimeData,
+    pub energy_price: EnergyPriceData,
+    pub battery_soc_percent: Option<f64>,
+    pub battery_power_watt: Option<i32>,
+    pub data_ages: DataAges,
 }
 
 impl SimulatorInputsUsed {
@@ -159,16 +254,37 @@ impl SimulatorInputsUsed {
             solar_production: inputs.solar_production.unwrap_or(0),
             outdoor_temp: inputs.outdoor_temp.unwrap_or(20.0),
             avg_next_24h_outdoor_temp: inputs.avg_next_24h_outdoor_temp.unwrap_or(20.0),
+            forecast_min_next_12h_temp: inputs.forecast_min_next_12h_temp.unwrap_or(20.0),
+            weather_forecast: inputs.weather_forecast.clone().unwrap_or_default(),
+            expected_solar_next_3h_wh: inputs.expected_solar_next_3h_wh,
+            season: inputs.season.unwrap_or_default(),
             user_is_home: inputs.user_is_home.unwrap_or(false),
+            people_home_count: inputs
+                .people_home_count
+                .unwrap_or(i64::from(inputs.user_is_home.unwrap_or(false))),
             pir_detected: inputs.pir_detected.unwrap_or(false),
+            occupancy_probability: inputs.occupancy_probability.unwrap_or(0.0),
+            is_window_open: inputs.is_window_open.unwrap_or(false),
             last_change_minutes: inputs.last_change_minutes.unwrap_or(60),
             net_power_watt: inputs.net_power_watt.unwrap_or(0),
+            possible_grid_outage: inputs.possible_grid_outage.unwrap_or(false),
+            dr_event_active: inputs.dr_event_active.unwrap_or(false),
+            dr_level: inputs.dr_level.unwrap_or(0),
+            dr_minutes_until_event: inputs.dr_minutes_until_event.unwrap_or(NO_DR_EVENT_SCHEDULED),
+            device_humidity: inputs.device_humidity,
+            outdoor_humidity: inputs.outdoor_humidity,
+            local_time: inputs.local_time.clone().unwrap_or_else(time_helpers::local_time_now),
+            energy_price: inputs.energy_price.clone().unwrap_or_default(),
+            battery_soc_percent: inputs.battery_soc_percent,
+            battery_power_watt: inputs.battery_power_watt,
+            data_ages: inputs.data_ages.clone().unwrap_or_else(DataAges::fresh),
         }
     }
 }
 
 /// Live inputs from the current environment
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct LiveInputs {
     /// All configured devices
     pub devices: Vec<LiveDeviceInput>,
@@ -178,30 +294,78 @@ pub struct LiveInputs {
     pub outdoor_temp: Option<f64>,
     /// Average outdoor temperature for the next 24 hours
     pub avg_next_24h_outdoor_temp: Option<f64>,
+    /// Lowest outdoor temperature for the next 12 hours
+    pub forecast_min_next_12h_temp: Option<f64>,
+    /// Hourly forecast for the next 48 hours, empty if unavailable
+    pub weather_forecast: WeatherForecastData,
+    /// Expected PV production of the next 3 hours in Wh, None if no panels are configured
+    pub expected_solar_next_3h_wh: Option<f64>,
+    /// Current heating or cooling season
+    pub season: Season,
     /// Whether user is home
     pub user_is_home: bool,
+    /// Number of people home according to presence detection
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub people_home_count: i64,
     /// Current net power in watts (positive = consuming, negative = exporting)
     pub net_power_watt: Option<i32>,
+    /// Whether the grid appears to be down
+    pub possible_grid_outage: bool,
+    /// Whether a demand-response event is in progress
+    pub dr_event_active: bool,
+    /// Curtailment level of the current or upcoming demand-response event
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub dr_level: i64,
+    /// Minutes until the next demand-response event (-1 if none scheduled)
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub dr_minutes_until_event: i64,
+    /// Current outdoor relative humidity in %
+    pub outdoor_humidity: Option<f64>,
+    /// Current local time
+    pub local_time: LocalTimeData,
+    /// Current day-ahead energy prices
+    pub energy_price: EnergyPriceData,
+    /// Current home battery state of charge in %
+    pub battery_soc_percent: Option<f64>,
+    /// Current home battery power in watts (positive = discharging, negative = charging)
+    pub battery_power_watt: Option<i32>,
 }
 
 /// Live inputs for a specific device
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct LiveDeviceInput {
     pub name: String,
     pub temperature: Option<f64>,
+    /// Relative humidity in %, None if the controller has no humidity sensor
+    pub humidity: Option<f64>,
     pub is_auto_mode: bool,
     pub pir_recently_triggered: bool,
     pub pir_minutes_ago: Option<u32>,
+    /// Occupancy probability (0-1) of the device's room
+    pub occupancy_probability: f64,
+    /// Whether a window or door in the device's room is open
+    pub is_window_open: bool,
     pub last_change_minutes: Option<i32>,
 }
 
 /// POST /api/simulator/evaluate
 /// Evaluates the workflow with the provided inputs without executing any actions
-async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
-    let pool = db::get_pool().await;
+#[utoipa::path(
+    post,
+    path = "/api/simulator/evaluate",
+    tag = "simulator",
+    request_body = SimulatorInputs,
+    responses((status = 200, description = "Also returned when the simulation fails, see `success` of the result", body = ApiResponse<SimulatorResult>))
+)]
+async fn evaluate_workflow(
+    State(state): State<AppState>,
+    Json(inputs): Json<SimulatorInputs>,
+) -> Response {
+    let pool = &state.pool;
     
     // Validate device
-    let _device = match AcDevices::from_str(&inputs.device) {
+    let _device = match state.devices().get(&inputs.device) {
         Some(d) => d,
         None => {
             // Return a simulation result with an error - API call succeeded but simulation has invalid input
@@ -212,6 +376,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                 error: Some(format!("Unknown device: {}", inputs.device)),
                 inputs_used: SimulatorInputsUsed::from_inputs_with_defaults(&inputs),
                 evaluate_every_minutes: None,
+                notifications: vec![],
+                trace: vec![],
             };
             let response = ApiResponse::success(error_result);
             return (StatusCode::OK, Json(response)).into_response();
@@ -227,11 +393,14 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                 intensity: "Low".to_string(),
                 cause_label: "Manual Mode".to_string(),
                 cause_description: "Device is in manual mode - automatic control is disabled.".to_string(),
+                annotation: None,
             }),
             ac_state: None,
             error: None,
             inputs_used: SimulatorInputsUsed::from_inputs_with_defaults(&inputs),
             evaluate_every_minutes: None,
+            notifications: vec![],
+            trace: vec![],
         };
         let response = ApiResponse::success(result);
         return (StatusCode::OK, Json(response)).into_response();
@@ -243,23 +412,80 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         None => get_solar_production().await.unwrap_or(0),
     };
     
+    // Outdoor weather at the simulated device's coordinates
+    let weather_coordinates = state.config().get_weather_coordinates(&inputs.device);
     let outdoor_temp = match inputs.outdoor_temp {
         Some(t) => t,
-        None => get_outdoor_temp().await.unwrap_or(20.0),
+        None => get_outdoor_temp(weather_coordinates).await.unwrap_or(20.0),
     };
     
     let avg_next_24h_outdoor_temp = match inputs.avg_next_24h_outdoor_temp {
         Some(t) => t,
-        None => get_avg_next_24h_outdoor_temp().await.unwrap_or(outdoor_temp),
+        None => get_avg_next_24h_outdoor_temp(weather_coordinates).await.unwrap_or(outdoor_temp),
+    };
+    
+    let forecast_min_next_12h_temp = match inputs.forecast_min_next_12h_temp {
+        Some(t) => t,
+        None => get_min_next_12h_outdoor_temp(weather_coordinates).await.unwrap_or(outdoor_temp),
+    };
+    
+    let weather_forecast = match inputs.weather_forecast.clone() {
+        Some(forecast) => forecast,
+        None => get_weather_forecast(weather_coordinates).await,
+    };
+    
+    let expected_solar_next_3h_wh = match inputs.expected_solar_next_3h_wh {
+        Some(wh) => Some(wh),
+        None => device_requests::solar_forecast::get_expected_solar_next_3h_wh(&state.config()).await,
+    };
+    
+    let season = match inputs.season {
+        Some(season) => season,
+        None => season::current_season(&state.config(), avg_next_24h_outdoor_temp).await,
+    };
+    
+    let outdoor_humidity = match inputs.outdoor_humidity {
+        Some(h) => Some(h),
+        None => get_outdoor_humidity(weather_coordinates).await.ok(),
     };
     
     let user_is_home = match inputs.user_is_home {
         Some(is_home) => is_home,
-        None => crate::ac_controller::time_helpers::is_user_home_and_awake_async().await,
+        None => time_helpers::is_user_home_and_awake_async().await,
+    };
+    
+    let people_home_count = match (inputs.people_home_count, inputs.user_is_home) {
+        (Some(count), _) => count,
+        (None, Some(is_home)) => i64::from(is_home),
+        (None, None) => time_helpers::people_home_count(user_is_home),
+    };
+    
+    let local_time = inputs.local_time.clone().unwrap_or_else(time_helpers::local_time_now);
+    
+    let energy_price = match inputs.energy_price.clone() {
+        Some(prices) => prices,
+        None => get_energy_price(&state.config()).await,
+    };
+    
+    // Entering either battery value simulates the battery, otherwise it is read
+    let (battery_soc_percent, battery_power_watt) = match (inputs.battery_soc_percent, inputs.battery_power_watt) {
+        (None, None) => get_battery(&state.config()).await,
+        entered => entered,
     };
     
+    let data_ages = inputs.data_ages.clone().unwrap_or_else(DataAges::fresh);
+    
     let pir_detected = inputs.pir_detected.unwrap_or(false);
     let pir_minutes_ago = inputs.pir_minutes_ago.unwrap_or(0) as i64;
+    let occupancy_probability = inputs.occupancy_probability.unwrap_or_else(|| {
+        let hour = local_time.hour as u32;
+        state.occupancy.probability(&inputs.device, &state.config().occupancy, hour, chrono::Utc::now())
+    });
+    let live_open_since = state
+        .contact_sensors
+        .open_since_by_device(&state.config().contact_sensors)
+        .remove(&inputs.device);
+    let is_window_open = inputs.is_window_open.unwrap_or(live_open_since.is_some());
     let last_change_minutes = inputs.last_change_minutes.unwrap_or(60);
     
     let net_power_watt = match inputs.net_power_watt {
@@ -274,6 +500,18 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         }
     };
     
+    let possible_grid_outage = match inputs.possible_grid_outage {
+        Some(outage) => outage,
+        None => crate::ac_controller::grid_state::check_possible_grid_outage().await,
+    };
+    
+    let live_dr = crate::ac_controller::demand_response::get_demand_response_state().get_data();
+    let demand_response = DemandResponseData {
+        event_active: inputs.dr_event_active.unwrap_or(live_dr.event_active),
+        level: inputs.dr_level.unwrap_or(live_dr.level),
+        minutes_until_event: inputs.dr_minutes_until_event.unwrap_or(live_dr.minutes_until_event),
+    };
+    
     // Build inputs used struct
     let inputs_used = SimulatorInputsUsed {
         device: inputs.device.clone(),
@@ -282,14 +520,37 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         solar_production,
         outdoor_temp,
         avg_next_24h_outdoor_temp,
+        forecast_min_next_12h_temp,
+        weather_forecast: weather_forecast.clone(),
+        expected_solar_next_3h_wh,
+        season,
         user_is_home,
+        people_home_count,
         pir_detected,
+        occupancy_probability,
+        is_window_open,
         last_change_minutes,
         net_power_watt,
+        possible_grid_outage,
+        dr_event_active: demand_response.event_active,
+        dr_level: demand_response.level,
+        dr_minutes_until_event: demand_response.minutes_until_event,
+        device_humidity: inputs.device_humidity,
+        outdoor_humidity,
+        local_time: local_time.clone(),
+        energy_price: energy_price.clone(),
+        battery_soc_percent,
+        battery_power_watt,
+        data_ages: data_ages.clone(),
     };
     
-    // Get the nodeset to evaluate
-    let (nodes, edges) = match get_nodeset_to_evaluate(&inputs, pool).await {
+    // Get the nodeset to evaluate, with the profiles it calls inlined
+    let calling_nodeset_id = inputs.nodeset_id.filter(|id| *id >= 0);
+    let nodeset = match get_nodeset_to_evaluate(&inputs, pool).await {
+        Ok((n, e)) => crate::db::nodesets::expand_calls(pool, calling_nodeset_id, n, e).await,
+        Err(error_msg) => Err(error_msg),
+    };
+    let (nodes, edges) = match nodeset {
         Ok((n, e)) => (n, e),
         Err(error_msg) => {
             let error_result = SimulatorResult {
@@ -299,6 +560,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                 error: Some(error_msg),
                 inputs_used,
                 evaluate_every_minutes: None,
+                notifications: vec![],
+                trace: vec![],
             };
             let response = ApiResponse::success(error_result);
             return (StatusCode::OK, Json(response)).into_response();
@@ -319,13 +582,15 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
             error: Some(format!("Nodeset validation failed: {}", validation_errors.join("; "))),
             inputs_used,
             evaluate_every_minutes,
+            notifications: vec![],
+            trace: vec![],
         };
         let response = ApiResponse::success(error_result);
         return (StatusCode::OK, Json(response)).into_response();
     }
     
     // Also run the basic structural validation
-    let structural_validation = validate_nodeset(&nodes);
+    let structural_validation = validate_nodeset(&nodes, &edges);
     if !structural_validation.is_valid {
         let error_result = SimulatorResult {
             success: false,
@@ -334,6 +599,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
             error: Some(format!("Profile structure invalid: {}", structural_validation.errors.join("; "))),
             inputs_used,
             evaluate_every_minutes,
+            notifications: vec![],
+            trace: vec![],
         };
         let response = ApiResponse::success(error_result);
         return (StatusCode::OK, Json(response)).into_response();
@@ -341,7 +608,7 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
     
     // Build PIR state for the execution context
     let mut pir_state = HashMap::new();
-    pir_state.insert(inputs.device.clone(), (pir_detected, pir_minutes_ago));
+    pir_state.insert(inputs.device.clone(), (pir_detected, Minutes(pir_minutes_ago)));
     
     // Get active command - prefer the simulator input if provided, otherwise use state manager
     let active_command = if let Some(ref sim_active_cmd) = inputs.active_command {
@@ -350,6 +617,7 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
             is_defined: sim_active_cmd.is_defined,
             is_on: sim_active_cmd.is_on,
             temperature: sim_active_cmd.temperature,
+            target_temperature: sim_active_cmd.target_temperature.unwrap_or(sim_active_cmd.temperature),
             mode: sim_active_cmd.mode,
             fan_speed: sim_active_cmd.fan_speed,
             swing: sim_active_cmd.swing,
@@ -359,8 +627,7 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         // Fall back to the AC state manager for the tracked state
         // The state manager tracks the last known state of each device.
         // A command is considered "defined" if we have any meaningful state tracked.
-        let state_manager = get_state_manager();
-        let ac_state = state_manager.get_state(&inputs.device);
+        let ac_state = state.ac_states.get_state(&inputs.device);
         
         // Determine if an active command exists:
         // - If device is currently on, we definitely have an active command
@@ -371,7 +638,11 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         ActiveCommandData {
             is_defined,
             is_on: ac_state.is_on,
-            temperature: ac_state.temperature.unwrap_or(0.0),
+            temperature: ac_state.temperature.map_or(0.0, |t| t.0),
+            target_temperature: target_temperature::get_target_temperatures()
+                .get(&inputs.device)
+                .or(ac_state.temperature)
+                .map_or(0.0, |t| t.0),
             mode: ac_state.mode.unwrap_or(0),
             fan_speed: ac_state.fan_speed.unwrap_or(0),
             swing: ac_state.swing.unwrap_or(0),
@@ -382,21 +653,56 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
     // Build execution inputs
     let execution_inputs = ExecutionInputs {
         device: inputs.device.clone(),
-        device_sensor_temperature: inputs.temperature,
+        device_sensor_temperature: Celsius(inputs.temperature),
         is_auto_mode: inputs.is_auto_mode,
-        last_change_minutes: last_change_minutes as i64,
-        outdoor_temperature: outdoor_temp,
+        last_change_minutes: Minutes(last_change_minutes as i64),
+        outdoor_temperature: Celsius(outdoor_temp),
         is_user_home: user_is_home,
-        net_power_watt: net_power_watt as i64,
-        raw_solar_watt: solar_production as i64,
-        avg_next_24h_outdoor_temp,
+        people_home_count,
+        net_power_watt: Watts(net_power_watt as i64),
+        raw_solar_watt: Watts(solar_production as i64),
+        avg_next_24h_outdoor_temp: Celsius(avg_next_24h_outdoor_temp),
+        forecast_min_next_12h_temp: Celsius(forecast_min_next_12h_temp),
+        weather_forecast,
+        expected_solar_next_3h_wh,
+        season,
         pir_state,
+        // Named sensors and zones aren't simulated, they read as never detected
+        pir_sensors: HashMap::new(),
+        pir_zones: HashMap::new(),
+        // Only the simulated device's room is occupied
+        occupancy: HashMap::from([(inputs.device.clone(), occupancy_probability)]),
+        // Only the simulated device's room can have an open window, open since the live sensor opened
+        is_window_open,
+        open_windows: if is_window_open {
+            let open_minutes = live_open_since.map_or(0, |since| (chrono::Utc::now() - since).num_minutes().max(0));
+            HashMap::from([(inputs.device.clone(), Minutes(open_minutes))])
+        } else {
+            HashMap::new()
+        },
         active_command,
+        possible_grid_outage,
+        demand_response,
+        device_humidity: inputs.device_humidity,
+        outdoor_humidity,
+        local_time,
+        energy_price,
+        battery_soc_percent,
+        battery_power_watt: battery_power_watt.map(|w| Watts(w as i64)),
+        // The share of the power budget comes from the tracked state of the other devices
+        power_budget: power_budget::get_data(&state.config(), &state.ac_states, &inputs.device),
+        data_ages,
+        // The simulator keeps no state between runs, Hysteresis nodes start off and Cooldown nodes pass
+        hysteresis_state: HashMap::new(),
+        cooldown_state: HashMap::new(),
     };
     
     // Create and execute the nodeset
     let mut executor = match NodesetExecutor::new(&nodes, &edges, execution_inputs) {
-        Ok(e) => e,
+        Ok(e) => {
+            let e = e.with_float_tolerance(state.config().float_tolerance);
+            if inputs.trace.unwrap_or(false) { e.with_trace() } else { e }
+        }
         Err(e) => {
             let error_result = SimulatorResult {
                 success: false,
@@ -405,6 +711,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                 error: Some(format!("Failed to create executor: {}", e)),
                 inputs_used,
                 evaluate_every_minutes,
+                notifications: vec![],
+                trace: vec![],
             };
             let response = ApiResponse::success(error_result);
             return (StatusCode::OK, Json(response)).into_response();
@@ -412,6 +720,7 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
     };
     
     let execution_result = executor.execute();
+    let trace = executor.trace().to_vec();
     
     // Convert execution result to simulator result
     if let Some(error) = execution_result.error {
@@ -422,6 +731,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
             error: Some(error),
             inputs_used,
             evaluate_every_minutes,
+            notifications: vec![],
+            trace,
         };
         let response = ApiResponse::success(error_result);
         return (StatusCode::OK, Json(response)).into_response();
@@ -446,11 +757,14 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                     intensity: "Low".to_string(),
                     cause_label,
                     cause_description,
+                    annotation: None,
                 }),
                 ac_state: None,
                 error: None,
                 inputs_used,
                 evaluate_every_minutes,
+                notifications: execution_result.notifications.clone(),
+                trace,
             };
             let response = ApiResponse::success(result);
             return (StatusCode::OK, Json(response)).into_response();
@@ -458,16 +772,21 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         Some("Execute Action") => {
             if let Some(action) = execution_result.action {
                 // Convert the action to an AcState for comparison
-                let desired_state = action_to_ac_state(&action);
+                let rounding_step = state.config().temperature_rounding_step;
+                let desired_state = action_to_ac_state(&action, &state.config()).with_rounded_temperature(rounding_step);
                 
                 // Check if the active command (current state) requires a change to reach the desired state
                 // This mirrors the logic in node_executor.rs execute_action_result
                 if let Some(ref sim_active_cmd) = inputs.active_command {
                     if sim_active_cmd.is_defined {
-                        let current_state = simulator_active_command_to_ac_state(sim_active_cmd);
+                        // The simulator input has no vane positions, so they are assumed to match
+                        let current_state = simulator_active_command_to_ac_state(sim_active_cmd)
+                            .with_rounded_temperature(rounding_step)
+                            .with_vanes(desired_state.vertical_vane, desired_state.horizontal_vane);
                         
                         // If no change is required, return NoChange instead of the action
-                        if !current_state.requires_change(&desired_state) {
+                        let change_policy = state.config().get_change_policy(&inputs.device);
+                        if !current_state.requires_change(&desired_state, &change_policy) {
                             let result = SimulatorResult {
                                 success: true,
                                 plan: Some(SimulatorPlanResult {
@@ -482,11 +801,14 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                                             "Off".to_string() 
                                         }
                                     ),
+                                    annotation: action.annotation.clone(),
                                 }),
                                 ac_state: None,
                                 error: None,
                                 inputs_used,
                                 evaluate_every_minutes,
+                                notifications: execution_result.notifications.clone(),
+                                trace,
                             };
                             let response = ApiResponse::success(result);
                             return (StatusCode::OK, Json(response)).into_response();
@@ -500,10 +822,18 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                     intensity: if action.is_powerful { "High".to_string() } else { "Medium".to_string() },
                     cause_label: get_cause_reason_label(&action.cause_reason).await,
                     cause_description: format!("Action from nodeset: {} mode at {}°C", action.mode, action.temperature),
+                    annotation: action.annotation.clone(),
                 };
                 
                 // Build AC state from action
-                let ac_state = action_to_simulator_state(&action);
+                let mut ac_state = action_to_simulator_state(&action);
+                // Report the rounded temperature that would actually be sent to the AC
+                if let Some(temperature) = desired_state.temperature {
+                    ac_state.temperature = Some(temperature.0);
+                }
+                // and only the vane positions the device supports
+                ac_state.vertical_vane = desired_state.vertical_vane.and_then(vane_name).map(str::to_string);
+                ac_state.horizontal_vane = desired_state.horizontal_vane.and_then(vane_name).map(str::to_string);
                 
                 let result = SimulatorResult {
                     success: true,
@@ -512,6 +842,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
                     error: None,
                     inputs_used,
                     evaluate_every_minutes,
+                    notifications: execution_result.notifications.clone(),
+                    trace,
                 };
                 let response = ApiResponse::success(result);
                 return (StatusCode::OK, Json(response)).into_response();
@@ -528,6 +860,8 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
         error: Some("Workflow did not reach a valid terminal node".to_string()),
         inputs_used,
         evaluate_every_minutes,
+        notifications: vec![],
+        trace,
     };
     let response = ApiResponse::success(error_result);
     (StatusCode::OK, Json(response)).into_response()
@@ -535,16 +869,19 @@ async fn evaluate_workflow(Json(inputs): Json<SimulatorInputs>) -> Response {
 
 /// GET /api/simulator/live-inputs
 /// Returns live input values from the current environment
-async fn get_live_inputs() -> Response {
-    let cfg = config::get_config();
-    let pir_state = crate::ac_controller::pir_state::get_pir_state();
+#[utoipa::path(get, path = "/api/simulator/live-inputs", tag = "simulator", responses((status = 200, body = ApiResponse<LiveInputs>)))]
+async fn get_live_inputs(State(state): State<AppState>) -> Response {
+    let cfg = state.config();
+    let pir_state = &state.pir;
+    let open_windows = state.contact_sensors.open_since_by_device(&cfg.contact_sensors);
     
     // Gather device data
     let mut devices = Vec::new();
-    for device_name in cfg.ac_controller_endpoints.keys() {
-        let (temp, is_auto) = match device_requests::ac::get_sensors_cached(device_name).await {
-            Ok(sensor_data) => (Some(sensor_data.temperature), sensor_data.is_automatic_mode),
-            Err(_) => (None, false),
+    for device in state.devices().all() {
+        let device_name = device.as_str();
+        let (temp, humidity, is_auto) = match device_requests::ac::get_sensors_cached(device_name).await {
+            Ok(sensor_data) => (Some(sensor_data.temperature), sensor_data.humidity, sensor_data.is_automatic_mode),
+            Err(_) => (None, None, false),
         };
         
         let pir_recently_triggered = pir_state.has_recent_detection(device_name, cfg.pir_timeout_minutes);
@@ -555,15 +892,27 @@ async fn get_live_inputs() -> Response {
             if minutes >= 0 { minutes as u32 } else { 0 }
         });
         
+        let occupancy_probability = state.occupancy.probability(
+            device_name,
+            &cfg.occupancy,
+            time_helpers::local_time_now().hour as u32,
+            chrono::Utc::now(),
+        );
+        
+        let is_window_open = open_windows.contains_key(device_name);
+        
         // Get last change minutes from database
         let last_change_minutes = get_last_change_minutes_for_device(device_name).await;
         
         devices.push(LiveDeviceInput {
-            name: device_name.clone(),
+            name: device_name.to_string(),
             temperature: temp,
+            humidity,
             is_auto_mode: is_auto,
             pir_recently_triggered,
             pir_minutes_ago,
+            occupancy_probability,
+            is_window_open,
             last_change_minutes,
         });
     }
@@ -581,10 +930,23 @@ async fn get_live_inputs() -> Response {
         Err(_) => None,
     };
     
-    let outdoor_temp = get_outdoor_temp().await.ok();
+    let global_coordinates = (cfg.latitude, cfg.longitude);
+    let outdoor_temp = get_outdoor_temp(global_coordinates).await.ok();
     
     // Get average outdoor temperature for the next 24 hours
-    let avg_next_24h_outdoor_temp = get_avg_next_24h_outdoor_temp().await.ok();
+    let avg_next_24h_outdoor_temp = get_avg_next_24h_outdoor_temp(global_coordinates).await.ok();
+    
+    // Get the lowest outdoor temperature for the next 12 hours
+    let forecast_min_next_12h_temp = get_min_next_12h_outdoor_temp(global_coordinates).await.ok();
+    
+    let weather_forecast = get_weather_forecast(global_coordinates).await;
+    
+    let season = season::current_season(
+        &cfg,
+        avg_next_24h_outdoor_temp.or(outdoor_temp).unwrap_or(20.0),
+    ).await;
+    
+    let outdoor_humidity = get_outdoor_humidity(global_coordinates).await.ok();
     
     // Get net power from meter reading
     let net_power_watt = match device_requests::meter::get_latest_reading_cached().await {
@@ -596,15 +958,35 @@ async fn get_live_inputs() -> Response {
         Err(_) => None,
     };
     
-    let user_is_home = crate::ac_controller::time_helpers::is_user_home_and_awake_async().await;
+    let user_is_home = time_helpers::is_user_home_and_awake_async().await;
+    
+    let possible_grid_outage = crate::ac_controller::grid_state::check_possible_grid_outage().await;
+    
+    let demand_response = crate::ac_controller::demand_response::get_demand_response_state().get_data();
+    
+    let (battery_soc_percent, battery_power_watt) = get_battery(&cfg).await;
     
     let live_inputs = LiveInputs {
         devices,
         solar_production,
         outdoor_temp,
         avg_next_24h_outdoor_temp,
+        forecast_min_next_12h_temp,
+        weather_forecast,
+        expected_solar_next_3h_wh: device_requests::solar_forecast::get_expected_solar_next_3h_wh(&cfg).await,
+        season,
         user_is_home,
+        people_home_count: time_helpers::people_home_count(user_is_home),
         net_power_watt,
+        possible_grid_outage,
+        dr_event_active: demand_response.event_active,
+        dr_level: demand_response.level,
+        dr_minutes_until_event: demand_response.minutes_until_event,
+        outdoor_humidity,
+        local_time: time_helpers::local_time_now(),
+        energy_price: get_energy_price(&cfg).await,
+        battery_soc_percent,
+        battery_power_watt,
     };
     
     let response = ApiResponse::success(live_inputs);
@@ -626,21 +1008,53 @@ async fn get_solar_production() -> Result<u32, ()> {
     }
 }
 
-async fn get_outdoor_temp() -> Result<f64, ()> {
-    let cfg = config::get_config();
-    device_requests::weather::get_current_outdoor_temp_cached(cfg.latitude, cfg.longitude)
+async fn get_outdoor_temp((latitude, longitude): (f64, f64)) -> Result<f64, ()> {
+    device_requests::weather::get_current_outdoor_temp_cached(latitude, longitude)
         .await
         .map_err(|_| ())
 }
 
 /// Get average outdoor temperature for the next 24 hours
-async fn get_avg_next_24h_outdoor_temp() -> Result<f64, ()> {
-    let cfg = config::get_config();
-    device_requests::weather::get_avg_next_24h_outdoor_temp_cached(cfg.latitude, cfg.longitude)
+async fn get_avg_next_24h_outdoor_temp((latitude, longitude): (f64, f64)) -> Result<f64, ()> {
+    device_requests::weather::get_avg_next_24h_outdoor_temp_cached(latitude, longitude)
+        .await
+        .map_err(|_| ())
+}
+
+/// Get the lowest outdoor temperature for the next 12 hours
+async fn get_min_next_12h_outdoor_temp((latitude, longitude): (f64, f64)) -> Result<f64, ()> {
+    device_requests::weather::get_min_next_12h_outdoor_temp_cached(latitude, longitude)
+        .await
+        .map_err(|_| ())
+}
+
+/// Get the hourly forecast for the next 48 hours, empty if unavailable
+async fn get_weather_forecast((latitude, longitude): (f64, f64)) -> WeatherForecastData {
+    device_requests::weather::get_hourly_forecast_cached(latitude, longitude)
+        .await
+        .unwrap_or_default()
+}
+
+/// Get current outdoor relative humidity
+async fn get_outdoor_humidity((latitude, longitude): (f64, f64)) -> Result<f64, ()> {
+    device_requests::weather::get_current_outdoor_humidity_cached(latitude, longitude)
         .await
         .map_err(|_| ())
 }
 
+async fn get_energy_price(cfg: &crate::types::Config) -> EnergyPriceData {
+    device_requests::energy_price::get_energy_price_data(&cfg.energy_price_bidding_zone, chrono::Utc::now().timestamp())
+        .await
+}
+
+/// Get the home battery state of charge and power, None if no battery is configured or it can't be read
+async fn get_battery(cfg: &crate::types::Config) -> (Option<f64>, Option<i32>) {
+    match device_requests::battery::get_battery_data(&cfg.battery_api_endpoint).await {
+        Some(battery) => (Some(battery.soc_percent), Some(battery.power_watt.round() as i32)),
+        None => (None, None),
+    }
+}
+
 /// Get minutes since the last AC command for a specific device
 /// Returns i32::MAX if no actions have been recorded
 async fn get_last_change_minutes_for_device(device_name: &str) -> Option<i32> {
@@ -668,20 +1082,21 @@ async fn get_last_change_minutes_for_device(device_name: &str) -> Option<i32> {
 /// Otherwise uses the active nodeset
 async fn get_nodeset_to_evaluate(
     inputs: &SimulatorInputs,
-    pool: &sqlx::SqlitePool,
+    pool: &crate::db::DbPool,
 ) -> Result<(Vec<serde_json::Value>, Vec<serde_json::Value>), String> {
     // Check if we should use the provided nodes/edges (for new/unsaved nodesets)
     if let Some(nodeset_id) = inputs.nodeset_id {
         if nodeset_id == -1 {
             // Use nodes/edges from input (new unsaved nodeset)
-            let nodes = inputs.nodes.clone().unwrap_or_default();
+            let mut nodes = inputs.nodes.clone().unwrap_or_default();
             let edges = inputs.edges.clone().unwrap_or_default();
+            variables::resolve_variables(&mut nodes, &inputs.variables.clone().unwrap_or_default());
             return Ok((nodes, edges));
         }
         
         // Fetch specific nodeset from database
         let result = sqlx::query_as::<_, (String,)>(
-            "SELECT node_json FROM nodesets WHERE id = ?"
+            "SELECT node_json FROM nodesets WHERE id = $1"
         )
         .bind(nodeset_id)
         .fetch_optional(pool)
@@ -689,8 +1104,8 @@ async fn get_nodeset_to_evaluate(
         
         return match result {
             Ok(Some((node_json,))) => {
-                match serde_json::from_str::<NodeConfiguration>(&node_json) {
-                    Ok(config) => Ok((config.nodes, config.edges)),
+                match NodeConfiguration::from_stored_json(&node_json) {
+                    Ok(config) => Ok((config.resolved_nodes(), config.edges)),
                     Err(e) => Err(format!("Failed to parse nodeset configuration: {}", e)),
                 }
             }
@@ -706,7 +1121,7 @@ async fn get_nodeset_to_evaluate(
     };
     
     let result = sqlx::query_as::<_, (String,)>(
-        "SELECT node_json FROM nodesets WHERE id = ?"
+        "SELECT node_json FROM nodesets WHERE id = $1"
     )
     .bind(active_id)
     .fetch_optional(pool)
@@ -714,8 +1129,8 @@ async fn get_nodeset_to_evaluate(
     
     match result {
         Ok(Some((node_json,))) => {
-            match serde_json::from_str::<NodeConfiguration>(&node_json) {
-                Ok(config) => Ok((config.nodes, config.edges)),
+            match NodeConfiguration::from_stored_json(&node_json) {
+                Ok(config) => Ok((config.resolved_nodes(), config.edges)),
                 Err(e) => Err(format!("Failed to parse active nodeset configuration: {}", e)),
             }
         }
@@ -736,7 +1151,7 @@ async fn get_nodeset_to_evaluate(
 async fn get_cause_reason_label(cause_id: &str) -> String {
     // Try to parse as i32 for database lookup
     if let Ok(id) = cause_id.parse::<i32>() {
-        if let Ok(cause_reasons) = db::cause_reasons::get_all(false).await {
+        if let Ok(cause_reasons) = db::cause_reasons::get_all(db::get_pool().await, false).await {
             if let Some(cr) = cause_reasons.iter().find(|cr| cr.id == id) {
                 return cr.label.clone();
             }
@@ -748,24 +1163,13 @@ async fn get_cause_reason_label(cause_id: &str) -> String {
 }
 
 /// Convert an ActionResult to an AcState for state comparison
-fn action_to_ac_state(action: &crate::nodes::ActionResult) -> AcState {
+/// Vane positions are only kept for vanes the device supports
+pub(super) fn action_to_ac_state(action: &crate::nodes::ActionResult, config: &Config) -> AcState {
     // Convert enable_swing boolean to swing integer (0 = off, 1 = on)
     let swing = if action.enable_swing { 1 } else { 0 };
     
-    match action.mode.as_str() {
-        "Off" => AcState::new_off(),
-        "Heat" => {
-            let fan_speed = match action.fan_speed.as_str() {
-                "Auto" => 0,
-                "High" => 1,
-                "Medium" => 2,
-                "Low" => 3,
-                "Quiet" => 4,
-                _ => 0,
-            };
-            AcState::new_on(AC_MODE_HEAT, fan_speed, action.temperature, swing, action.is_powerful)
-        }
-        "Cool" => {
+    match ac_mode_from_name(&action.mode) {
+        Some(mode) => {
             let fan_speed = match action.fan_speed.as_str() {
                 "Auto" => 0,
                 "High" => 1,
@@ -774,9 +1178,15 @@ fn action_to_ac_state(action: &crate::nodes::ActionResult) -> AcState {
                 "Quiet" => 4,
                 _ => 0,
             };
-            AcState::new_on(AC_MODE_COOL, fan_speed, action.temperature, swing, action.is_powerful)
+            let (vertical_vane, horizontal_vane) = config.supported_vanes(
+                &action.device,
+                action.vertical_vane.as_deref(),
+                action.horizontal_vane.as_deref(),
+            );
+            AcState::new_on(mode, fan_speed, Celsius(action.temperature), swing, action.is_powerful)
+                .with_vanes(vertical_vane, horizontal_vane)
         }
-        _ => AcState::new_off(),
+        None => AcState::new_off(),
     }
 }
 
@@ -788,7 +1198,7 @@ fn simulator_active_command_to_ac_state(cmd: &SimulatorActiveCommand) -> AcState
         AcState::new_on(
             cmd.mode,
             cmd.fan_speed,
-            cmd.temperature,
+            Celsius(cmd.temperature),
             cmd.swing,
             cmd.is_powerful,
         )
@@ -798,8 +1208,7 @@ fn simulator_active_command_to_ac_state(cmd: &SimulatorActiveCommand) -> AcState
 /// Convert an ActionResult to a SimulatorAcState
 fn action_to_simulator_state(action: &crate::nodes::ActionResult) -> SimulatorAcState {
     let mode_str = match action.mode.as_str() {
-        "Heat" => Some("Heat".to_string()),
-        "Cool" => Some("Cool".to_string()),
+        "Heat" | "Cool" | "Dry" | "Fan" => Some(action.mode.clone()),
         "Off" => None,
         _ => Some(action.mode.clone()),
     };
@@ -825,6 +1234,8 @@ fn action_to_simulator_state(action: &crate::nodes::ActionResult) -> SimulatorAc
         fan_speed: Some(fan_speed),
         temperature: Some(action.temperature),
         swing: Some(swing),
+        vertical_vane: None,
+        horizontal_vane: None,
         powerful_mode: action.is_powerful,
     }
 }
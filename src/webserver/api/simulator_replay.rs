@@ -0,0 +1,447 @@
+//! Replays a nodeset over historical inputs
+//!
+//! Every execution records its inputs in the execution history. A replay steps through a time
+//! range at the nodeset's evaluation interval and runs the nodeset on the inputs recorded closest
+//! before each step. The active command, hysteresis and cooldown state come from the replay itself, so the
+//! nodeset sees its own earlier decisions instead of those of the profile that was running. Energy
+//! use is estimated from the device's configured draw and the recorded energy prices.
+
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    ac_controller::{ac_executor::AcState, cycle_snapshots},
+    app_state::AppState,
+    db,
+    nodes::{validate_nodeset_for_execution, ActiveCommandData, ExecutionInputs, NodesetExecutor},
+    types::{db_types::ExecutionRecord, ApiError, ApiResponse, Config, Minutes},
+};
+
+use super::nodes::NodeConfiguration;
+
+/// Longest time range a replay may cover, execution history is kept for 30 days
+const MAX_REPLAY_DAYS: i64 = 31;
+
+/// Recorded inputs older than this at a step are a gap in the history, the step is skipped
+const MAX_INPUT_AGE_SECS: i64 = 60 * 60;
+
+/// Replay request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    pub nodeset_id: i64,
+    pub device: String,
+    /// Unix timestamp the replay starts at
+    pub from: i64,
+    /// Unix timestamp the replay ends at
+    pub to: i64,
+}
+
+/// Decision of one replayed evaluation
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct ReplayStep {
+    /// Unix timestamp of the evaluation
+    pub at: i64,
+    /// e.g. "Execute Action: Heat 22°C", "Do Nothing", "Error: ..."
+    pub decision: String,
+    /// Whether the device is on after the decision
+    pub is_on: bool,
+    /// Decision of the profile that was running at the time, from the execution history
+    pub recorded_decision: Option<String>,
+}
+
+/// Result of a replay
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReplayResult {
+    pub nodeset_id: i64,
+    pub device: String,
+    pub evaluate_every_minutes: i32,
+    pub steps: Vec<ReplayStep>,
+    /// Steps without recorded inputs, e.g. while the controller was down
+    pub skipped_steps: usize,
+    /// Estimated energy used by the device in kWh
+    pub energy_kwh: f64,
+    /// Estimated cost in EUR of the energy used in hours with a known price
+    pub estimated_cost_eur: f64,
+    /// Energy used in hours without a known price, not included in the cost
+    pub unpriced_energy_kwh: f64,
+}
+
+/// POST /api/simulator/replay
+/// Runs a nodeset over the inputs recorded for a device in a time range, without executing any actions
+#[utoipa::path(
+    post,
+    path = "/api/simulator/replay",
+    tag = "simulator",
+    request_body = ReplayRequest,
+    responses(
+        (status = 200, body = ApiResponse<ReplayResult>),
+        (status = 400, description = "Invalid time range or nodeset", body = ApiError),
+        (status = 404, description = "Unknown device or nodeset", body = ApiError),
+    )
+)]
+pub async fn replay_nodeset(State(state): State<AppState>, Json(request): Json<ReplayRequest>) -> Response {
+    let Some(props) = state.config().ac_controller_endpoints.get(&request.device).cloned() else {
+        let response = ApiResponse::<()>::error(format!("Unknown device '{}'", request.device));
+        return (StatusCode::NOT_FOUND, Json(response)).into_response();
+    };
+    // Nothing is recorded after now, and the range is checked before any arithmetic on it can overflow
+    let to = request.to.min(Utc::now().timestamp());
+    let range = to.checked_sub(request.from);
+    let history_from = request.from.checked_sub(MAX_INPUT_AGE_SECS);
+    let (Some(range), Some(history_from)) = (range, history_from) else {
+        let response = ApiResponse::<()>::error("The time range is out of bounds");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+    if range <= 0 || range > MAX_REPLAY_DAYS * 24 * 60 * 60 {
+        let response = ApiResponse::<()>::error(format!(
+            "The time range must end after it starts, before now, and cover at most {} days",
+            MAX_REPLAY_DAYS
+        ));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let node_json = sqlx::query_as::<_, (String,)>("SELECT node_json FROM nodesets WHERE id = $1")
+        .bind(request.nodeset_id)
+        .fetch_optional(&state.pool)
+        .await;
+    let config = match node_json {
+        Ok(Some((node_json,))) => match NodeConfiguration::from_stored_json(&node_json) {
+            Ok(config) => config,
+            Err(e) => {
+                let response = ApiResponse::<()>::error(format!("Failed to parse nodeset configuration: {}", e));
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+            }
+        },
+        Ok(None) => {
+            let response = ApiResponse::<()>::error(format!("Nodeset with id {} not found", request.nodeset_id));
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            let response = ApiResponse::<()>::error(format!("Failed to fetch nodeset: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+    let (nodes, edges) = match db::nodesets::expand_calls(&state.pool, Some(request.nodeset_id), config.resolved_nodes(), config.edges).await {
+        Ok(expanded) => expanded,
+        Err(e) => {
+            let response = ApiResponse::<()>::error(e);
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+    let validation_errors = validate_nodeset_for_execution(&nodes, &edges);
+    if !validation_errors.is_empty() {
+        let response = ApiResponse::<()>::error(format!("Nodeset validation failed: {}", validation_errors.join("; ")));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    }
+
+    let records = match db::execution_history::get_range(
+        &state.pool,
+        &request.device,
+        history_from,
+        to,
+    )
+    .await
+    {
+        Ok(records) => records,
+        Err(e) => {
+            let response = ApiResponse::<()>::error(format!("Failed to load the execution history: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    let evaluate_every_minutes = db::nodesets::extract_evaluate_every_minutes_from_nodes(&nodes)
+        .unwrap_or(db::nodesets::DEFAULT_EVALUATE_EVERY_MINUTES);
+    let app_config = state.config();
+    let replay = Replay {
+        nodes: &nodes,
+        edges: &edges,
+        config: &app_config,
+        float_tolerance: app_config.float_tolerance,
+        power_watt: f64::from(props.power_watt),
+        powerful_power_watt: f64::from(props.powerful_power_watt),
+        evaluate_every_minutes,
+    };
+    let mut result = replay.run(&records, request.from, to);
+    result.nodeset_id = request.nodeset_id;
+    result.device = request.device;
+
+    (StatusCode::OK, Json(ApiResponse::success(result))).into_response()
+}
+
+/// A nodeset with the device parameters it is replayed with
+struct Replay<'a> {
+    nodes: &'a [serde_json::Value],
+    edges: &'a [serde_json::Value],
+    config: &'a Config,
+    float_tolerance: f64,
+    power_watt: f64,
+    powerful_power_watt: f64,
+    evaluate_every_minutes: i32,
+}
+
+impl Replay<'_> {
+    /// Step through `from..=to` evaluating the nodeset on the records, which must be sorted oldest first
+    fn run(&self, records: &[ExecutionRecord], from: i64, to: i64) -> ReplayResult {
+        let interval_secs = i64::from(self.evaluate_every_minutes) * 60;
+        let interval_hours = interval_secs as f64 / 3600.0;
+        let mut state = AcState::new_off();
+        let mut active_command = ActiveCommandData::default();
+        let mut last_change: Option<i64> = None;
+        let mut hysteresis_state = Default::default();
+        let mut cooldown_state = Default::default();
+        let mut result = ReplayResult {
+            nodeset_id: 0,
+            device: String::new(),
+            evaluate_every_minutes: self.evaluate_every_minutes,
+            steps: Vec::new(),
+            skipped_steps: 0,
+            energy_kwh: 0.0,
+            estimated_cost_eur: 0.0,
+            unpriced_energy_kwh: 0.0,
+        };
+
+        let mut next_record = 0;
+        for at in (from..=to).step_by(interval_secs as usize) {
+            // The latest record at or before this step
+            while next_record < records.len() && records[next_record].executed_at <= at {
+                next_record += 1;
+            }
+            let record = next_record
+                .checked_sub(1)
+                .map(|i| &records[i])
+                .filter(|r| at - r.executed_at <= MAX_INPUT_AGE_SECS);
+            let inputs = record.and_then(|r| {
+                let snapshot: cycle_snapshots::InputSnapshot = serde_json::from_str(&r.inputs).ok()?;
+                cycle_snapshots::inputs_from_snapshot(&snapshot).ok()
+            });
+            let Some(mut inputs) = inputs else {
+                result.skipped_steps += 1;
+                continue;
+            };
+
+            // The replayed nodeset continues from its own decisions
+            inputs.active_command = active_command.clone();
+            inputs.last_change_minutes = Minutes(last_change.map_or(i64::from(i32::MAX), |t| (at - t) / 60));
+            inputs.hysteresis_state = std::mem::take(&mut hysteresis_state);
+            inputs.cooldown_state = std::mem::take(&mut cooldown_state);
+            let price = inputs.energy_price.current_price_eur_kwh;
+
+            let (decision, new_state) = self.evaluate(inputs, at, &mut hysteresis_state, &mut cooldown_state);
+            if let Some(new_state) = new_state {
+                if !active_command.is_defined || new_state != state {
+                    last_change = Some(at);
+                }
+                active_command = active_command_for(&new_state);
+                state = new_state;
+            }
+
+            // Energy used until the next step
+            let watt = match (state.is_on, state.powerful_mode) {
+                (false, _) => 0.0,
+                (true, false) => self.power_watt,
+                (true, true) => self.powerful_power_watt,
+            };
+            let kwh = watt / 1000.0 * interval_hours;
+            result.energy_kwh += kwh;
+            match price {
+                Some(price) => result.estimated_cost_eur += kwh * price,
+                None => result.unpriced_energy_kwh += kwh,
+            }
+
+            result.steps.push(ReplayStep {
+                at,
+                decision,
+                is_on: state.is_on,
+                recorded_decision: record.map(recorded_decision),
+            });
+        }
+        result
+    }
+
+    /// Run the nodeset once at `at`, returning the decision and the state it results in (None if unchanged)
+    fn evaluate(
+        &self,
+        inputs: ExecutionInputs,
+        at: i64,
+        hysteresis_state: &mut std::collections::HashMap<String, bool>,
+        cooldown_state: &mut std::collections::HashMap<String, i64>,
+    ) -> (String, Option<AcState>) {
+        let mut executor = match NodesetExecutor::new(self.nodes, self.edges, inputs) {
+            Ok(executor) => executor.with_float_tolerance(self.float_tolerance).with_now(at),
+            Err(e) => return (format!("Error: {}", e), None),
+        };
+        let result = executor.execute();
+        *hysteresis_state = executor.hysteresis_state().clone();
+        *cooldown_state = executor.cooldown_state().clone();
+        let new_state = match (&result.error, &result.action) {
+            (None, Some(action)) => Some(super::simulator::action_to_ac_state(action, self.config)),
+            _ => None,
+        };
+        (cycle_snapshots::describe_decision(&result), new_state)
+    }
+}
+
+/// Active command after a state was sent
+fn active_command_for(state: &AcState) -> ActiveCommandData {
+    let temperature = state.temperature.map_or(0.0, |t| t.0);
+    ActiveCommandData {
+        is_defined: true,
+        is_on: state.is_on,
+        temperature,
+        target_temperature: temperature,
+        mode: state.mode.unwrap_or(0),
+        fan_speed: state.fan_speed.unwrap_or(0),
+        swing: state.swing.unwrap_or(0),
+        is_powerful: state.powerful_mode,
+    }
+}
+
+/// Decision recorded in the execution history, in the same format as replayed decisions
+fn recorded_decision(record: &ExecutionRecord) -> String {
+    if let Some(error) = &record.error {
+        return format!("Error: {}", error);
+    }
+    let action = record
+        .action
+        .as_deref()
+        .and_then(|a| serde_json::from_str::<crate::nodes::ActionResult>(a).ok());
+    match action {
+        Some(action) if action.mode == "Off" => "Execute Action: Off".to_string(),
+        Some(action) => format!("Execute Action: {} {}°C", action.mode, action.temperature),
+        None => record
+            .terminal_type
+            .clone()
+            .unwrap_or_else(|| "No terminal node reached".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Celsius;
+    use serde_json::json;
+
+    /// Start -> If (outdoor_temperature < 5) -> Heat 22°C, else Turn Off
+    fn heat_when_cold_nodeset() -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+        fn node(id: &str, node_type: &str, data: serde_json::Value) -> serde_json::Value {
+            let mut data = data;
+            data["definition"] = json!({"node_type": node_type});
+            json!({"id": id, "type": "custom", "position": {"x": 0, "y": 0}, "data": data})
+        }
+        fn edge(source: &str, source_handle: &str, target: &str, target_handle: &str) -> serde_json::Value {
+            json!({
+                "id": format!("e{}-{}-{}", source, target, target_handle),
+                "source": source,
+                "sourceHandle": source_handle,
+                "target": target,
+                "targetHandle": target_handle
+            })
+        }
+
+        let nodes = vec![
+            node("start", "flow_start", json!({})),
+            node("threshold", "primitive_float", json!({"primitiveValue": 5.0})),
+            node("cold", "logic_evaluate_number", json!({"operatorValue": "<"})),
+            node("if", "logic_if", json!({})),
+            node("temperature", "primitive_float", json!({"primitiveValue": 22.0})),
+            node("mode", "request_mode", json!({"enumValue": "Heat"})),
+            node("fan", "fan_speed", json!({"enumValue": "Auto"})),
+            node("off", "primitive_boolean", json!({"primitiveValue": false})),
+            node("cause", "cause_reason", json!({"enumValue": "1"})),
+            node("heat", "flow_execute_action", json!({})),
+            node("turn-off", "flow_turn_off", json!({})),
+        ];
+        let edges = vec![
+            edge("start", "exec_out", "if", "exec_in"),
+            edge("start", "outdoor_temperature", "cold", "input_a"),
+            edge("threshold", "value", "cold", "input_b"),
+            edge("cold", "result", "if", "condition"),
+            edge("if", "exec_true", "heat", "exec_in"),
+            edge("if", "exec_false", "turn-off", "exec_in"),
+            edge("temperature", "value", "heat", "temperature"),
+            edge("mode", "value", "heat", "mode"),
+            edge("fan", "value", "heat", "fan_speed"),
+            edge("off", "value", "heat", "is_powerful"),
+            edge("off", "value", "heat", "enable_swing"),
+            edge("cause", "value", "heat", "cause_reason"),
+            edge("cause", "value", "turn-off", "cause_reason"),
+        ];
+        (nodes, edges)
+    }
+
+    fn record(executed_at: i64, outdoor_temperature: f64, price: Option<f64>) -> ExecutionRecord {
+        let inputs = ExecutionInputs {
+            device: "LivingRoom".to_string(),
+            outdoor_temperature: Celsius(outdoor_temperature),
+            energy_price: crate::nodes::EnergyPriceData {
+                current_price_eur_kwh: price,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        ExecutionRecord {
+            id: 0,
+            device_identifier: "LivingRoom".to_string(),
+            executed_at,
+            nodeset_id: 0,
+            terminal_type: Some("Do Nothing".to_string()),
+            action: None,
+            cause_reason: None,
+            error: None,
+            inputs: serde_json::to_string(&cycle_snapshots::snapshot_inputs(&inputs)).unwrap(),
+            warnings: "[]".to_string(),
+            is_shadow: false,
+            nodeset_revision: Some(1),
+            command_discrepancies: None,
+        }
+    }
+
+    #[test]
+    fn test_replay_decisions_and_energy() {
+        let (nodes, edges) = heat_when_cold_nodeset();
+        let replay = Replay {
+            nodes: &nodes,
+            edges: &edges,
+            config: &Config::default(),
+            float_tolerance: 0.0001,
+            power_watt: 1200.0,
+            powerful_power_watt: 1500.0,
+            evaluate_every_minutes: 30,
+        };
+        // Cold for the first hour, then mild, with a gap in the history after two hours
+        let records = vec![record(0, 2.0, Some(0.25)), record(3600, 10.0, None)];
+
+        let result = replay.run(&records, 0, 3 * 3600);
+        let decisions: Vec<&str> = result.steps.iter().map(|s| s.decision.as_str()).collect();
+        assert_eq!(
+            decisions,
+            vec!["Execute Action: Heat 22°C", "Execute Action: Heat 22°C", "Execute Action: Off", "Execute Action: Off", "Execute Action: Off"]
+        );
+        assert_eq!(result.steps[1].recorded_decision.as_deref(), Some("Do Nothing"));
+        assert_eq!(result.skipped_steps, 2, "no inputs recorded within an hour");
+        assert!((result.energy_kwh - 1.2).abs() < 1e-9, "on for two half-hour steps");
+        assert!((result.estimated_cost_eur - 0.3).abs() < 1e-9);
+        assert_eq!(result.unpriced_energy_kwh, 0.0);
+
+        let result = replay.run(&records, i64::MAX - 3600, i64::MAX);
+        assert_eq!(result.skipped_steps, 3, "stops at the end of the range without overflowing");
+    }
+
+    #[tokio::test]
+    async fn test_out_of_bounds_ranges_are_rejected() {
+        let state = AppState::for_test(Config::default()).await;
+        let now = Utc::now().timestamp();
+        for (from, to) in [(-1, i64::MAX), (i64::MIN, 0), (now - 40 * 24 * 60 * 60, now), (now + 60, now + 3600)] {
+            let request = ReplayRequest { nodeset_id: 0, device: "LivingRoom".to_string(), from, to };
+            let response = replay_nodeset(State(state.clone()), Json(request)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}..{}", from, to);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    app_state::AppState,
+    db::{self, statistics::Granularity},
+    types::{ApiError, ApiResponse, db_types},
+};
+
+/// Maximum span of an hourly request (31 days)
+const MAX_HOURLY_SPAN_SECS: i64 = 31 * 24 * 60 * 60;
+
+/// Maximum span of a daily request (five years)
+const MAX_DAILY_SPAN_SECS: i64 = 5 * 366 * 24 * 60 * 60;
+
+/// Span returned when no start is given (one week)
+const DEFAULT_HOURLY_SPAN_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Span returned when no start is given (one year)
+const DEFAULT_DAILY_SPAN_SECS: i64 = 366 * 24 * 60 * 60;
+
+pub fn statistics_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/hourly", get(get_hourly))
+        .route("/daily", get(get_daily))
+        .with_state(state)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct StatisticsRequest {
+    /// Unix timestamp, defaults to one week (hourly) or one year (daily) before `end`
+    start: Option<i64>,
+    /// Unix timestamp, defaults to now
+    end: Option<i64>,
+    /// Only return statistics of this device, energy is household-wide and always returned
+    device: Option<String>,
+}
+
+/// Rolled up statistics of a range of periods, oldest first
+#[derive(Serialize, ToSchema)]
+struct StatisticsResponse {
+    devices: Vec<db_types::DeviceStats>,
+    energy: Vec<db_types::EnergyStats>,
+}
+
+/// GET /api/statistics/hourly?start=1763942400&end=1764547200&device=Veranda
+/// Returns the hourly device statistics and household energy (StatisticsResponse)
+#[utoipa::path(
+    get,
+    path = "/api/statistics/hourly",
+    tag = "statistics",
+    params(StatisticsRequest),
+    responses(
+        (status = 200, body = ApiResponse<StatisticsResponse>),
+        (status = 400, description = "Invalid range", body = ApiError),
+    )
+)]
+async fn get_hourly(State(state): State<AppState>, Query(params): Query<StatisticsRequest>) -> Response {
+    get_statistics(&state, params, Granularity::Hourly).await
+}
+
+/// GET /api/statistics/daily?start=1735689600&end=1764547200&device=Veranda
+/// Returns the daily (UTC) device statistics and household energy (StatisticsResponse)
+#[utoipa::path(
+    get,
+    path = "/api/statistics/daily",
+    tag = "statistics",
+    params(StatisticsRequest),
+    responses(
+        (status = 200, body = ApiResponse<StatisticsResponse>),
+        (status = 400, description = "Invalid range", body = ApiError),
+    )
+)]
+async fn get_daily(State(state): State<AppState>, Query(params): Query<StatisticsRequest>) -> Response {
+    get_statistics(&state, params, Granularity::Daily).await
+}
+
+/// Resolve the requested range against its defaults
+/// Returns None for a range that is reversed, longer than `max_span` or out of bounds
+fn query_range(start: Option<i64>, end: Option<i64>, now: i64, default_span: i64, max_span: i64) -> Option<(i64, i64)> {
+    let end = end.unwrap_or(now);
+    let start = match start {
+        Some(start) => start,
+        None => end.checked_sub(default_span)?,
+    };
+    let span = end.checked_sub(start)?;
+    (0..=max_span).contains(&span).then_some((start, end))
+}
+
+async fn get_statistics(state: &AppState, params: StatisticsRequest, granularity: Granularity) -> Response {
+    let (default_span, max_span, max_span_name) = match granularity {
+        Granularity::Hourly => (DEFAULT_HOURLY_SPAN_SECS, MAX_HOURLY_SPAN_SECS, "31 days"),
+        Granularity::Daily => (DEFAULT_DAILY_SPAN_SECS, MAX_DAILY_SPAN_SECS, "five years"),
+    };
+    let now = chrono::Utc::now().timestamp();
+    let Some((start, end)) = query_range(params.start, params.end, now, default_span, max_span) else {
+        let response = ApiError::error(format!(
+            "Invalid range: start must be before end and span at most {}",
+            max_span_name
+        ));
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+
+    let loaded = async {
+        let devices =
+            db::statistics::get_device_stats(&state.pool, granularity, start, end, params.device.as_deref()).await?;
+        let energy = db::statistics::get_energy_stats(&state.pool, granularity, start, end).await?;
+        Ok::<_, sqlx::Error>(StatisticsResponse { devices, energy })
+    }
+    .await;
+
+    match loaded {
+        Ok(statistics) => {
+            let response = ApiResponse::success(statistics);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(err) => {
+            log::error!("Database error in get_statistics: {}", err);
+            let response = ApiError::error("Database error has occurred");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_range() {
+        let now = 1_764_547_200;
+        let range = |start, end| query_range(start, end, now, DEFAULT_HOURLY_SPAN_SECS, MAX_HOURLY_SPAN_SECS);
+        assert_eq!(range(None, None), Some((now - DEFAULT_HOURLY_SPAN_SECS, now)));
+        assert_eq!(range(Some(now - 3600), None), Some((now - 3600, now)));
+        assert_eq!(range(Some(now), Some(now - 3600)), None);
+        assert_eq!(range(Some(now - MAX_HOURLY_SPAN_SECS - 1), None), None);
+
+        // Out of bounds values are rejected instead of overflowing
+        assert_eq!(range(None, Some(i64::MIN)), None);
+        assert_eq!(range(Some(i64::MIN), Some(i64::MAX)), None);
+        assert_eq!(range(Some(i64::MIN), None), None);
+    }
+}
@@ -0,0 +1,46 @@
+use axum::{
+    Json, Router,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    telemetry::{self, TelemetryReport},
+    types::{ApiError, ApiResponse},
+};
+
+pub fn telemetry_routes() -> Router {
+    Router::new()
+        .route("/preview", get(preview))
+}
+
+#[derive(Serialize, ToSchema)]
+struct TelemetryPreview {
+    /// Whether reports are actually being sent
+    enabled: bool,
+    /// The exact payload that would be sent
+    report: TelemetryReport,
+}
+
+/// GET /api/telemetry/preview
+/// Shows exactly what would be sent to the telemetry endpoint
+#[utoipa::path(get, path = "/api/telemetry/preview", tag = "telemetry", responses((status = 200, body = ApiResponse<TelemetryPreview>)))]
+async fn preview() -> Response {
+    match telemetry::build_report().await {
+        Ok(report) => {
+            let response = ApiResponse::success(TelemetryPreview {
+                enabled: telemetry::is_enabled(),
+                report,
+            });
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to build telemetry report: {}", e);
+            let response = ApiError::error("Failed to build telemetry report");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
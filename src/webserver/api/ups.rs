@@ -0,0 +1,89 @@
+use axum::{
+    Json, Router,
+    extract::Query,
+    http::{StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use serde::Deserialize;
+use log::warn;
+use utoipa::IntoParams;
+
+use crate::{
+    ac_controller::grid_state,
+    types::{ApiError, ApiResponse, Config},
+};
+
+pub fn ups_routes() -> Router {
+    Router::new()
+        .route("/status", post(ups_status))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct UpsStatusRequest {
+    on_battery: bool,
+}
+
+/// The 403 or 401 response unless the request may report the power source
+fn reject_request(headers: &HeaderMap, config: &Config) -> Option<Response> {
+    // Without a key anyone on the network could turn off every AC
+    if config.shed_load_on_grid_outage && config.ups_api_key.is_empty() {
+        let response = ApiError::error("shed_load_on_grid_outage requires ups_api_key to be configured");
+        return Some((StatusCode::FORBIDDEN, Json(response)).into_response());
+    }
+    if !super::auth::verify_api_key_header(headers, &config.ups_api_key) {
+        warn!("Unauthorized UPS status attempt");
+        let response = ApiError::error("Unauthorized");
+        return Some((StatusCode::UNAUTHORIZED, Json(response)).into_response());
+    }
+    None
+}
+
+/// POST /api/ups/status?on_battery=true
+/// Records the power source reported by a UPS, used for grid outage detection.
+/// Signals expire after a few minutes, so the UPS should report periodically.
+#[utoipa::path(
+    post,
+    path = "/api/ups/status",
+    tag = "ups",
+    params(UpsStatusRequest),
+    security(("api_key" = [])),
+    responses(
+        (status = 200, body = ApiResponse<String>),
+        (status = 401, description = "Missing or wrong `ups_api_key`", body = ApiError),
+        (status = 403, description = "`shed_load_on_grid_outage` is enabled without `ups_api_key`", body = ApiError),
+    )
+)]
+async fn ups_status(
+    headers: HeaderMap,
+    Query(params): Query<UpsStatusRequest>,
+) -> Response {
+    if let Some(response) = reject_request(&headers, &crate::config::get_config()) {
+        return response;
+    }
+
+    grid_state::get_grid_state().record_ups_signal(params.on_battery);
+
+    let response = ApiResponse::success("UPS status recorded");
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_shedding_requires_a_key() {
+        let headers = HeaderMap::new();
+        assert!(reject_request(&headers, &Config::default()).is_none(), "only recorded without load shedding");
+
+        let mut config = Config {
+            shed_load_on_grid_outage: true,
+            ..Default::default()
+        };
+        assert_eq!(reject_request(&headers, &config).unwrap().status(), StatusCode::FORBIDDEN);
+        config.ups_api_key = "ups".to_string();
+        assert_eq!(reject_request(&headers, &config).unwrap().status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -5,9 +5,10 @@ use axum::{
     routing::post,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    ac_controller::{self, AcDevices},
+    ac_controller::{self, devices},
     db,
     types::{ApiError, ApiResponse},
 };
@@ -18,12 +19,12 @@ pub fn user_home_routes() -> Router {
         .route("/clear", post(clear_user_home))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct SetUserHomeRequest {
     pub hours: u32,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct UserHomeResponse {
     pub success: bool,
     pub message: String,
@@ -32,9 +33,9 @@ pub struct UserHomeResponse {
 /// Trigger immediate AC evaluation for all devices after home override change
 async fn trigger_ac_evaluation(action_description: &str) {
     log::info!("Triggering immediate AC evaluation after {}", action_description);
-    for device in AcDevices::all() {
+    for device in devices::get_device_registry().all() {
         let device_name = device.as_str();
-        match ac_controller::node_executor::execute_nodeset_for_device(&device).await {
+        match ac_controller::node_executor::execute_nodeset_for_device(device).await {
             ac_controller::node_executor::NodeExecutionResult::CommandExecuted => {
                 log::info!("AC command executed for {} after {}", device_name, action_description);
             }
@@ -53,6 +54,16 @@ async fn trigger_ac_evaluation(action_description: &str) {
 
 /// POST /api/user-home/set
 /// Set user home override for the specified number of hours
+#[utoipa::path(
+    post,
+    path = "/api/user-home/set",
+    tag = "presence",
+    request_body = SetUserHomeRequest,
+    responses(
+        (status = 200, body = ApiResponse<UserHomeResponse>),
+        (status = 400, description = "Invalid number of hours", body = ApiError),
+    )
+)]
 async fn set_user_home(Json(request): Json<SetUserHomeRequest>) -> Response {
     // Validate input - reasonable range for hours
     if request.hours == 0 || request.hours > 168 {
@@ -68,7 +79,7 @@ async fn set_user_home(Json(request): Json<SetUserHomeRequest>) -> Response {
     
     // Update or insert the setting
     let result = sqlx::query(
-        "INSERT INTO settings (setting_key, setting_value) VALUES ('user_is_home_override', ?)
+        "INSERT INTO settings (setting_key, setting_value) VALUES ('user_is_home_override', $1)
          ON CONFLICT(setting_key) DO UPDATE SET setting_value = excluded.setting_value"
     )
     .bind(override_until.to_string())
@@ -100,6 +111,7 @@ async fn set_user_home(Json(request): Json<SetUserHomeRequest>) -> Response {
 
 /// POST /api/user-home/clear
 /// Clear the user home override
+#[utoipa::path(post, path = "/api/user-home/clear", tag = "presence", responses((status = 200, body = ApiResponse<UserHomeResponse>)))]
 async fn clear_user_home() -> Response {
     let pool = db::get_pool().await;
     
@@ -0,0 +1,96 @@
+use axum::{
+    Json, Router,
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    db,
+    types::{ApiError, ApiResponse, db_types},
+};
+
+/// Maximum span of a single history request (one year)
+const MAX_HISTORY_SPAN_SECS: i64 = 366 * 24 * 60 * 60;
+
+/// Span returned when no start is given (one week)
+const DEFAULT_HISTORY_SPAN_SECS: i64 = 7 * 24 * 60 * 60;
+
+pub fn weather_routes() -> Router {
+    Router::new()
+        .route("/history", get(get_history))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HistoryRequest {
+    /// Unix timestamp, defaults to one week before `end`
+    start: Option<i64>,
+    /// Unix timestamp, defaults to now
+    end: Option<i64>,
+}
+
+/// Resolve the requested range against its defaults
+/// Returns None for a range that is reversed, longer than a year or out of bounds
+fn history_range(start: Option<i64>, end: Option<i64>, now: i64) -> Option<(i64, i64)> {
+    let end = end.unwrap_or(now);
+    let start = match start {
+        Some(start) => start,
+        None => end.checked_sub(DEFAULT_HISTORY_SPAN_SECS)?,
+    };
+    let span = end.checked_sub(start)?;
+    (0..=MAX_HISTORY_SPAN_SECS).contains(&span).then_some((start, end))
+}
+
+/// GET /api/weather/history?start=1763942400&end=1764547200
+/// Returns the hourly outdoor temperature history (Vec<db_types::WeatherHour>), oldest first
+#[utoipa::path(
+    get,
+    path = "/api/weather/history",
+    tag = "weather",
+    params(HistoryRequest),
+    responses(
+        (status = 200, body = ApiResponse<Vec<db_types::WeatherHour>>),
+        (status = 400, description = "Invalid range", body = ApiError),
+    )
+)]
+async fn get_history(Query(params): Query<HistoryRequest>) -> Response {
+    let Some((start, end)) = history_range(params.start, params.end, chrono::Utc::now().timestamp()) else {
+        let response = ApiError::error("Invalid range: start must be before end and span at most one year");
+        return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+    };
+
+    match db::weather_history::get_range(start, end).await {
+        Ok(hours) => {
+            let response = ApiResponse::success(hours);
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(err) => {
+            log::error!("Database error in get_weather_history: {}", err);
+            let response = ApiError::error("Database error has occurred");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_range() {
+        let now = 1_764_547_200;
+        assert_eq!(history_range(None, None, now), Some((now - DEFAULT_HISTORY_SPAN_SECS, now)));
+        assert_eq!(history_range(Some(now - 60), Some(now), 0), Some((now - 60, now)));
+        assert_eq!(history_range(Some(now), Some(now - 60), 0), None);
+        assert_eq!(history_range(Some(now - MAX_HISTORY_SPAN_SECS - 1), None, now), None);
+
+        // Out of bounds values are rejected instead of overflowing
+        assert_eq!(history_range(None, Some(i64::MIN), now), None);
+        assert_eq!(history_range(Some(i64::MIN), Some(i64::MAX), now), None);
+        assert_eq!(history_range(Some(i64::MIN), None, now), None);
+    }
+}
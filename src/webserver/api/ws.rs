@@ -0,0 +1,48 @@
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    response::Response,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::live_events;
+
+/// GET /api/ws
+/// WebSocket that pushes every live event as a JSON text message
+/// (execution results, AC state changes, PIR detections and meter readings)
+#[utoipa::path(get, path = "/api/ws", tag = "status", responses((status = 101, description = "Switched to a WebSocket of live events")))]
+pub async fn ws_handler(ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(stream_events)
+}
+
+/// Forward live events to the socket until the client disconnects
+async fn stream_events(mut socket: WebSocket) {
+    let mut events = live_events::get_live_events().subscribe();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let json = match serde_json::to_string(&event) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            log::warn!("Failed to serialize live event: {}", e);
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    log::debug!("WebSocket client fell behind, skipped {} live events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                // Clients only listen, anything but a close is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -2,3 +2,4 @@
 pub mod router;
 pub use router::*;
 pub mod api;
+mod tls;
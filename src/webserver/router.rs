@@ -6,6 +6,7 @@ use axum::{
 };
 use log::info;
 use rust_embed::RustEmbed;
+use utoipa_swagger_ui::{Config, SwaggerUi};
 
 #[derive(RustEmbed)]
 #[folder = "frontend/dist/"]
@@ -19,15 +20,29 @@ pub async fn start_webserver() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting web server on {}", listen_addr);
 
     // Build the axum router
+    let state = crate::app_state::AppState::from_globals().await;
+
+    // Activate nodesets queued with POST /api/nodes/nodesets/:id/activate-at
+    tokio::spawn(crate::webserver::api::nodeset_schedule::scheduled_activation_loop(state.clone()));
+    // Switch nodesets by the recurring rules of /api/nodes/schedules
+    tokio::spawn(crate::webserver::api::nodeset_calendar::schedule_loop(state.clone()));
+
     let app = Router::new()
-        .nest("/api", crate::webserver::api::api_routes())
+        .nest("/api", crate::webserver::api::api_routes(state))
+        .merge(SwaggerUi::new("/swagger-ui").config(Config::from("/api/openapi.json")))
         .fallback(serve_static);
 
+    // Also serve over HTTPS when a certificate is configured
+    crate::webserver::tls::spawn_if_configured(&app, &cfg.listen_address);
+
     // Start the server
     let listener = tokio::net::TcpListener::bind(&listen_addr).await?;
     info!("Web server running on {}", listen_addr);
 
-    axum::serve(listener, app).await?;
+    // The peer address is needed for the per-IP rate limit
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+        .with_graceful_shutdown(crate::shutdown::requested())
+        .await?;
     Ok(())
 }
 
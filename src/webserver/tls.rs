@@ -0,0 +1,97 @@
+//! Optional HTTPS listener
+//!
+//! With `tls_cert_path` and `tls_key_path` set, the web server also listens on `tls_listen_port`
+//! with rustls, next to the plaintext listener. The certificate files are checked every minute and
+//! reloaded when they change, so a renewed certificate (e.g. from certbot) is picked up without a
+//! restart. A certificate that fails to load is logged and the previous one stays in use.
+
+use std::{net::SocketAddr, path::Path, time::{Duration, SystemTime}};
+
+use axum::Router;
+use axum_server::{Handle, tls_rustls::RustlsConfig};
+use log::{error, info, warn};
+
+/// How often the certificate files are checked for changes
+const CERT_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Time to let open HTTPS requests finish after shutdown was requested
+const SHUTDOWN_GRACE_SECS: u64 = 10;
+
+/// Last modification times of the certificate and key files
+fn modified_times(cert_path: &str, key_path: &str) -> Option<(SystemTime, SystemTime)> {
+    let modified = |path: &str| std::fs::metadata(Path::new(path)).and_then(|m| m.modified()).ok();
+    Some((modified(cert_path)?, modified(key_path)?))
+}
+
+/// Serve the app over HTTPS until shutdown is requested
+/// Returns an error if the listener can't be started, e.g. because the certificate can't be loaded
+pub async fn serve_tls(app: Router, listen_address: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = crate::config::get_config();
+    let addr: SocketAddr = format!("{}:{}", listen_address, cfg.tls_listen_port).parse()?;
+
+    // Every rustls config built by axum-server uses the process-wide crypto provider
+    // Installing fails harmlessly when one is already installed
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let tls_config = RustlsConfig::from_pem_file(&cfg.tls_cert_path, &cfg.tls_key_path)
+        .await
+        .map_err(|e| format!("Failed to load TLS certificate '{}' or key '{}': {}", cfg.tls_cert_path, cfg.tls_key_path, e))?;
+    tokio::spawn(reload_certificates(tls_config.clone()));
+
+    let handle = Handle::new();
+    let shutdown_handle = handle.clone();
+    tokio::spawn(async move {
+        crate::shutdown::requested().await;
+        shutdown_handle.graceful_shutdown(Some(Duration::from_secs(SHUTDOWN_GRACE_SECS)));
+    });
+
+    info!("HTTPS server running on {}", addr);
+    axum_server::bind_rustls(addr, tls_config)
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+    Ok(())
+}
+
+/// Reload the certificate whenever its files change
+/// The paths are read from the current config, so they can be changed with a config reload
+async fn reload_certificates(tls_config: RustlsConfig) {
+    let cfg = crate::config::get_config();
+    let mut last_modified = modified_times(&cfg.tls_cert_path, &cfg.tls_key_path);
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(CERT_CHECK_INTERVAL_SECS)).await;
+
+        let cfg = crate::config::get_config();
+        let modified = modified_times(&cfg.tls_cert_path, &cfg.tls_key_path);
+        if modified.is_none() || modified == last_modified {
+            continue;
+        }
+
+        match tls_config.reload_from_pem_file(&cfg.tls_cert_path, &cfg.tls_key_path).await {
+            Ok(()) => {
+                info!("Reloaded TLS certificate from '{}'", cfg.tls_cert_path);
+                last_modified = modified;
+            }
+            // Retried on the next check in case the files were only half written
+            Err(e) => warn!("Failed to reload TLS certificate '{}', keeping the current one: {}", cfg.tls_cert_path, e),
+        }
+    }
+}
+
+/// Start the HTTPS listener in the background if TLS is configured
+/// A listener that fails is logged without affecting the plaintext listener
+pub fn spawn_if_configured(app: &Router, listen_address: &str) {
+    let cfg = crate::config::get_config();
+    if !cfg.tls_enabled() {
+        return;
+    }
+
+    let app = app.clone();
+    let listen_address = listen_address.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = serve_tls(app, &listen_address).await {
+            error!("HTTPS server stopped: {}", e);
+        }
+    });
+}